@@ -0,0 +1,49 @@
+//! Configuration for event clip downloads (`src/service/clips.rs`).
+//!
+//! `GET /api/v3/events/{id}/clip.mp4` remuxes part of a recording. Each clip
+//! is cut once and cached under `cache_dir`, one directory per event, so
+//! seeking and repeat downloads read the file instead of cutting again. The
+//! cache is swept once a clip is older than `retention_days`.
+
+use std::path::PathBuf;
+
+use serde::Deserialize;
+
+#[derive(Debug, Clone, Deserialize)]
+#[serde(default)]
+pub struct ClipsConfig {
+    /// Directory finished clips are cached in.
+    pub cache_dir: PathBuf,
+    /// Longest clip that may be requested, in seconds (`end - start`, after
+    /// clamping to the recording). Longer requests are refused with 400.
+    pub max_seconds: u64,
+    /// Maximum cuts running concurrently (a tokio `Semaphore`); excess
+    /// requests queue.
+    pub max_concurrent_cuts: usize,
+    /// Days a cached clip is kept. `0` keeps them until manually pruned.
+    pub retention_days: u64,
+}
+
+impl Default for ClipsConfig {
+    fn default() -> Self {
+        Self {
+            cache_dir: PathBuf::from("/var/lib/zm-api/clips"),
+            max_seconds: 900,
+            max_concurrent_cuts: 2,
+            retention_days: 1,
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn missing_keys_take_defaults() {
+        let c: ClipsConfig = serde_json::from_str(r#"{"max_seconds": 60}"#).unwrap();
+        assert_eq!(c.max_seconds, 60);
+        assert_eq!(c.max_concurrent_cuts, 2);
+        assert_eq!(c.cache_dir, PathBuf::from("/var/lib/zm-api/clips"));
+    }
+}
@@ -0,0 +1,78 @@
+//! Configuration for background jobs (`src/service/jobs.rs`): the bulk event
+//! operations behind `POST /api/v3/events:bulk`, the per-event media jobs
+//! behind `POST /api/v3/events/{id}/jobs` and the export bundles behind
+//! `POST /api/v3/events/export`.
+//!
+//! A bulk job walks its matches in keyset batches and commits progress after
+//! each one, pausing in between so a job over tens of thousands of events
+//! cannot monopolise the database ZoneMinder itself is writing to. Media jobs
+//! are disk- and CPU-heavy instead, so at most `media_workers` run at once and
+//! the rest wait `queued`.
+
+use std::path::PathBuf;
+use std::time::Duration;
+
+use serde::Deserialize;
+
+#[derive(Debug, Deserialize, Clone)]
+pub struct JobsConfig {
+    /// Events fetched and acted on per batch (and per progress commit).
+    #[serde(default = "default_bulk_batch_size")]
+    pub bulk_batch_size: u64,
+
+    /// Pause between batches, in milliseconds. `0` runs batches back to back.
+    #[serde(default = "default_bulk_batch_pause_ms")]
+    pub bulk_batch_pause_ms: u64,
+
+    /// Media jobs (transcode, export, move, copy) allowed to run concurrently.
+    #[serde(default = "default_media_workers")]
+    pub media_workers: usize,
+
+    /// Where `export` jobs write, one `event-{id}/` directory per event, and
+    /// where export bundles too large to send at once are built.
+    #[serde(default = "default_export_dir")]
+    pub export_dir: PathBuf,
+
+    /// Export bundles of at most this many events are streamed straight back
+    /// to the caller; larger ones are built by a background job.
+    #[serde(default = "default_export_inline_max_events")]
+    pub export_inline_max_events: u64,
+}
+
+impl Default for JobsConfig {
+    fn default() -> Self {
+        Self {
+            bulk_batch_size: default_bulk_batch_size(),
+            bulk_batch_pause_ms: default_bulk_batch_pause_ms(),
+            media_workers: default_media_workers(),
+            export_dir: default_export_dir(),
+            export_inline_max_events: default_export_inline_max_events(),
+        }
+    }
+}
+
+impl JobsConfig {
+    pub fn bulk_batch_pause(&self) -> Duration {
+        Duration::from_millis(self.bulk_batch_pause_ms)
+    }
+}
+
+fn default_bulk_batch_size() -> u64 {
+    200
+}
+
+fn default_bulk_batch_pause_ms() -> u64 {
+    250
+}
+
+fn default_media_workers() -> usize {
+    2
+}
+
+fn default_export_dir() -> PathBuf {
+    PathBuf::from("/var/lib/zm-api/exports")
+}
+
+fn default_export_inline_max_events() -> u64 {
+    10
+}
@@ -78,7 +78,14 @@ pub struct AuditConfig {
     #[serde(default = "default_true")]
     pub resync_counters: bool,
 
-    /// Never delete more than this many items in one pass. A misconfigured
+    /// Walk each local storage and reconcile event directories against
+    /// `Events` rows. Directories no row accounts for are moved into
+    /// `{storage}/.quarantine/`, never removed; rows with no media, and
+    /// directories that are misplaced or carry no event id, are reported only.
+    #[serde(default = "default_true")]
+    pub reconcile_filesystem: bool,
+
+    /// Never delete (or quarantine) more than this many items in one pass. A misconfigured
     /// storage path makes every event look orphaned; this bounds the damage to
     /// something recoverable while the log makes the cause obvious.
     #[serde(default = "default_max_deletes")]
@@ -150,6 +157,7 @@ impl Default for AuditConfig {
             remove_empty_events: default_true(),
             close_unclosed_events: default_true(),
             resync_counters: default_true(),
+            reconcile_filesystem: default_true(),
             max_deletes_per_pass: default_max_deletes(),
         }
     }
@@ -7,17 +7,23 @@ use serde::Deserialize;
 use crate::util::dir::get_project_root;
 
 use self::{
-    daemon::DaemonConfig, db::DatabaseConfig, http::HttpClientConfig,
-    maintenance::MaintenanceConfig, retention::RetentionConfig, search::SearchConfig,
+    clips::ClipsConfig, daemon::DaemonConfig, db::DatabaseConfig, http::HttpClientConfig,
+    jobs::JobsConfig, maintenance::MaintenanceConfig, notifications::NotificationsConfig,
+    previews::PreviewsConfig, ptz::PtzConfig, retention::RetentionConfig, search::SearchConfig,
     secret::SecretConfig, sentry::SentryConfig, server::ServerConfig, streaming::StreamingConfig,
-    synopsis::SynopsisConfig, web::WebConfig, zmnext::ZmNextConfig,
+    synopsis::SynopsisConfig, trash::TrashConfig, web::WebConfig, zmnext::ZmNextConfig,
 };
 
+pub mod clips;
 pub mod daemon;
 pub mod db;
 pub mod env;
 pub mod http;
+pub mod jobs;
 pub mod maintenance;
+pub mod notifications;
+pub mod previews;
+pub mod ptz;
 pub mod retention;
 pub mod search;
 pub mod secret;
@@ -26,6 +32,7 @@ pub mod server;
 pub mod streaming;
 pub mod synopsis;
 pub mod tracing;
+pub mod trash;
 pub mod web;
 pub mod zmconf;
 pub mod zmnext;
@@ -49,6 +56,12 @@ pub struct AppConfig {
     /// when the `[synopsis]` block is absent.
     #[serde(default)]
     pub synopsis: SynopsisConfig,
+    /// Animated event previews and contact sheets: render cache and limits.
+    #[serde(default)]
+    pub previews: PreviewsConfig,
+    /// Event clip downloads: cut cache and the longest clip allowed.
+    #[serde(default)]
+    pub clips: ClipsConfig,
     /// Natural-language / semantic event search. Off by default.
     #[serde(default)]
     pub search: SearchConfig,
@@ -56,6 +69,17 @@ pub struct AppConfig {
     /// usage by free-space floor / age / quota.
     #[serde(default)]
     pub retention: RetentionConfig,
+    /// Purging soft-deleted events once their restore grace period has
+    /// passed. On by default.
+    #[serde(default)]
+    pub trash: TrashConfig,
+    /// Background jobs (bulk event operations): batch size and pacing.
+    #[serde(default)]
+    pub jobs: JobsConfig,
+    /// Notification rules engine: SMTP relay, MQTT broker, webhook timeout.
+    /// Off by default.
+    #[serde(default)]
+    pub notifications: NotificationsConfig,
     /// Serving the zm-web browser UI from this binary. Off by default.
     #[serde(default)]
     pub web: WebConfig,
@@ -63,6 +87,10 @@ pub struct AppConfig {
     /// zmtelemetry). Each independently switchable; all off by default.
     #[serde(default)]
     pub maintenance: MaintenanceConfig,
+    /// PTZ background work: preset tours and their schedules. Tours are on
+    /// by default but only run once one is started or scheduled.
+    #[serde(default)]
+    pub ptz: PtzConfig,
 }
 
 impl AppConfig {
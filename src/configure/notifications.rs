@@ -0,0 +1,205 @@
+//! Configuration for the notification rules engine (`src/service/notifications`).
+//!
+//! Rules themselves live in the database, per user. This block holds what is
+//! per-install: the master switch, the SMTP relay and MQTT broker the channels
+//! deliver through, and the storage-health check interval. A channel whose
+//! server is not configured records its deliveries as failed rather than
+//! silently dropping them.
+
+use std::time::Duration;
+
+use serde::Deserialize;
+
+#[derive(Debug, Deserialize, Clone)]
+pub struct NotificationsConfig {
+    /// Master switch. When false no rule is evaluated (rules can still be
+    /// created, so a UI can be set up before alerts go live).
+    #[serde(default)]
+    pub enabled: bool,
+
+    /// Signals (event open/close, scores, storage checks) queued for the
+    /// dispatcher. When full, further signals are dropped with a warning —
+    /// ingest must never block on a slow mail server.
+    #[serde(default = "default_queue_capacity")]
+    pub queue_capacity: usize,
+
+    /// How often `storage_health` rules are checked against each Storage's
+    /// free space.
+    #[serde(default = "default_storage_check_interval_seconds")]
+    pub storage_check_interval_seconds: u64,
+
+    #[serde(default)]
+    pub smtp: SmtpConfig,
+    #[serde(default)]
+    pub webhook: WebhookConfig,
+    #[serde(default)]
+    pub mqtt: MqttConfig,
+}
+
+impl Default for NotificationsConfig {
+    fn default() -> Self {
+        Self {
+            enabled: false,
+            queue_capacity: default_queue_capacity(),
+            storage_check_interval_seconds: default_storage_check_interval_seconds(),
+            smtp: SmtpConfig::default(),
+            webhook: WebhookConfig::default(),
+            mqtt: MqttConfig::default(),
+        }
+    }
+}
+
+impl NotificationsConfig {
+    pub fn storage_check_interval(&self) -> Duration {
+        Duration::from_secs(self.storage_check_interval_seconds.max(1))
+    }
+}
+
+/// How the SMTP connection is secured.
+#[derive(Debug, Deserialize, Clone, Copy, Default, PartialEq, Eq)]
+#[serde(rename_all = "lowercase")]
+pub enum SmtpSecurity {
+    /// Plain connection upgraded with `STARTTLS` (port 587).
+    #[default]
+    Starttls,
+    /// TLS from the first byte (port 465).
+    Tls,
+    /// No encryption. Only for a relay on localhost or a test stub.
+    None,
+}
+
+/// The SMTP relay the `smtp` channel sends through.
+#[derive(Debug, Deserialize, Clone)]
+pub struct SmtpConfig {
+    /// Relay host. Empty disables the channel.
+    #[serde(default)]
+    pub host: String,
+    #[serde(default = "default_smtp_port")]
+    pub port: u16,
+    #[serde(default)]
+    pub security: SmtpSecurity,
+    /// Credentials; leave both empty for an unauthenticated relay.
+    #[serde(default)]
+    pub username: String,
+    #[serde(default)]
+    pub password: String,
+    /// `From:` address, e.g. `"ZoneMinder <zm@example.com>"`.
+    #[serde(default = "default_smtp_from")]
+    pub from: String,
+    #[serde(default = "default_timeout_seconds")]
+    pub timeout_seconds: u64,
+}
+
+impl Default for SmtpConfig {
+    fn default() -> Self {
+        Self {
+            host: String::new(),
+            port: default_smtp_port(),
+            security: SmtpSecurity::default(),
+            username: String::new(),
+            password: String::new(),
+            from: default_smtp_from(),
+            timeout_seconds: default_timeout_seconds(),
+        }
+    }
+}
+
+impl SmtpConfig {
+    pub fn is_configured(&self) -> bool {
+        !self.host.trim().is_empty()
+    }
+
+    pub fn timeout(&self) -> Duration {
+        Duration::from_secs(self.timeout_seconds.max(1))
+    }
+}
+
+/// The `webhook` channel. The URL is per rule.
+#[derive(Debug, Deserialize, Clone)]
+pub struct WebhookConfig {
+    #[serde(default = "default_timeout_seconds")]
+    pub timeout_seconds: u64,
+}
+
+impl Default for WebhookConfig {
+    fn default() -> Self {
+        Self {
+            timeout_seconds: default_timeout_seconds(),
+        }
+    }
+}
+
+impl WebhookConfig {
+    pub fn timeout(&self) -> Duration {
+        Duration::from_secs(self.timeout_seconds.max(1))
+    }
+}
+
+/// The MQTT broker the `mqtt` channel publishes to. The topic is per rule.
+#[derive(Debug, Deserialize, Clone)]
+pub struct MqttConfig {
+    /// Broker host. Empty disables the channel.
+    #[serde(default)]
+    pub host: String,
+    #[serde(default = "default_mqtt_port")]
+    pub port: u16,
+    #[serde(default = "default_mqtt_client_id")]
+    pub client_id: String,
+    #[serde(default)]
+    pub username: String,
+    #[serde(default)]
+    pub password: String,
+    #[serde(default = "default_timeout_seconds")]
+    pub timeout_seconds: u64,
+}
+
+impl Default for MqttConfig {
+    fn default() -> Self {
+        Self {
+            host: String::new(),
+            port: default_mqtt_port(),
+            client_id: default_mqtt_client_id(),
+            username: String::new(),
+            password: String::new(),
+            timeout_seconds: default_timeout_seconds(),
+        }
+    }
+}
+
+impl MqttConfig {
+    pub fn is_configured(&self) -> bool {
+        !self.host.trim().is_empty()
+    }
+
+    pub fn timeout(&self) -> Duration {
+        Duration::from_secs(self.timeout_seconds.max(1))
+    }
+}
+
+fn default_queue_capacity() -> usize {
+    1024
+}
+
+fn default_storage_check_interval_seconds() -> u64 {
+    300
+}
+
+fn default_smtp_port() -> u16 {
+    587
+}
+
+fn default_smtp_from() -> String {
+    "zm-api@localhost".to_string()
+}
+
+fn default_mqtt_port() -> u16 {
+    1883
+}
+
+fn default_mqtt_client_id() -> String {
+    "zm-api".to_string()
+}
+
+fn default_timeout_seconds() -> u64 {
+    10
+}
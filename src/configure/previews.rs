@@ -0,0 +1,67 @@
+//! Configuration for animated event previews and contact sheets
+//! (`src/service/previews`).
+//!
+//! `GET /api/v3/events/{id}/preview.webp` / `.gif` loop a few seconds around
+//! the event's highest-scoring frame; `contact-sheet.jpg` tiles evenly spaced
+//! frames with their times burned in. Renders of finished events are cached
+//! under `cache_dir`, one directory per event, and swept once older than
+//! `retention_days`.
+
+use std::path::PathBuf;
+
+use serde::Deserialize;
+
+#[derive(Debug, Clone, Deserialize)]
+#[serde(default)]
+pub struct PreviewsConfig {
+    /// Directory rendered previews are cached in.
+    pub cache_dir: PathBuf,
+    /// Maximum renders running concurrently (a tokio `Semaphore`); excess
+    /// requests queue.
+    pub max_concurrent_renders: usize,
+    /// Hard wall-clock cap on a single render.
+    pub render_timeout_seconds: u64,
+    /// Days a cached preview is kept. `0` keeps them until manually pruned.
+    pub retention_days: u64,
+    /// Width of animation frames and contact-sheet tiles; smaller recordings
+    /// keep their own width.
+    pub width: u32,
+    /// Length of the animated preview.
+    pub animation_seconds: f64,
+    /// Frame rate of the animated preview.
+    pub animation_fps: u32,
+    /// Tiles in a contact sheet unless the request asks for another count.
+    pub contact_sheet_frames: u32,
+    /// Tiles per contact-sheet row unless the request asks otherwise.
+    pub contact_sheet_columns: u32,
+}
+
+impl Default for PreviewsConfig {
+    fn default() -> Self {
+        Self {
+            cache_dir: PathBuf::from("/var/lib/zm-api/previews"),
+            max_concurrent_renders: 2,
+            render_timeout_seconds: 60,
+            retention_days: 7,
+            width: 320,
+            animation_seconds: 4.0,
+            animation_fps: 5,
+            contact_sheet_frames: 12,
+            contact_sheet_columns: 4,
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn missing_keys_take_defaults() {
+        let c: PreviewsConfig = serde_json::from_str(r#"{"width": 480}"#).unwrap();
+        assert_eq!(c.width, 480);
+        assert_eq!(c.animation_fps, 5);
+        assert_eq!(c.max_concurrent_renders, 2);
+        assert_eq!(c.cache_dir, PathBuf::from("/var/lib/zm-api/previews"));
+    }
+}
@@ -0,0 +1,108 @@
+//! Configuration for PTZ arbitration and background work
+//! (`src/service/ptz_control.rs`, `src/service/ptz_tours/`).
+//!
+//! Control leases keep two users from driving one camera at once: the first
+//! to move it holds it for `lease_seconds`, renewed by every command. Once a
+//! camera's controls have been idle for the monitor's `ReturnDelay`, it goes
+//! back to its `ReturnLocation`.
+//!
+//! Tours walk a camera through its presets on their own, either on demand
+//! (`POST /api/v3/ptz/monitors/{id}/tours/{tour_id}/start`) or inside a weekly
+//! schedule. Whenever a user drives the camera the tour steps aside, and picks
+//! up again once the controls have been idle for `resume_after_seconds`.
+
+use std::time::Duration;
+
+use serde::Deserialize;
+
+#[derive(Debug, Default, Deserialize, Clone)]
+pub struct PtzConfig {
+    #[serde(default)]
+    pub control: PtzControlConfig,
+    #[serde(default)]
+    pub tours: PtzToursConfig,
+}
+
+#[derive(Debug, Deserialize, Clone)]
+pub struct PtzControlConfig {
+    /// How long a user keeps a camera after their last command. Other users
+    /// get 409 meanwhile, unless they have `System: Edit` and the holder does
+    /// not. 0 turns arbitration off.
+    #[serde(default = "default_lease_seconds")]
+    pub lease_seconds: u64,
+
+    /// Send idle cameras back to their monitor's `ReturnLocation` after its
+    /// `ReturnDelay`.
+    #[serde(default = "default_enabled")]
+    pub auto_return: bool,
+}
+
+impl Default for PtzControlConfig {
+    fn default() -> Self {
+        Self {
+            lease_seconds: default_lease_seconds(),
+            auto_return: default_enabled(),
+        }
+    }
+}
+
+impl PtzControlConfig {
+    /// The lease duration; `None` when arbitration is off.
+    pub fn lease(&self) -> Option<Duration> {
+        (self.lease_seconds > 0).then(|| Duration::from_secs(self.lease_seconds))
+    }
+}
+
+#[derive(Debug, Deserialize, Clone)]
+pub struct PtzToursConfig {
+    /// Master switch. When false the tour runner never spawns and starting a
+    /// tour is refused; stored tours are kept.
+    #[serde(default = "default_enabled")]
+    pub enabled: bool,
+
+    /// How long the controls must sit idle after a user command before a
+    /// paused tour carries on.
+    #[serde(default = "default_resume_after_seconds")]
+    pub resume_after_seconds: u64,
+
+    /// How often tour schedules are checked against the clock. Edits through
+    /// the API are picked up at once regardless.
+    #[serde(default = "default_schedule_check_seconds")]
+    pub schedule_check_seconds: u64,
+}
+
+impl Default for PtzToursConfig {
+    fn default() -> Self {
+        Self {
+            enabled: default_enabled(),
+            resume_after_seconds: default_resume_after_seconds(),
+            schedule_check_seconds: default_schedule_check_seconds(),
+        }
+    }
+}
+
+impl PtzToursConfig {
+    pub fn resume_after(&self) -> Duration {
+        Duration::from_secs(self.resume_after_seconds)
+    }
+
+    pub fn schedule_check_interval(&self) -> Duration {
+        Duration::from_secs(self.schedule_check_seconds.max(1))
+    }
+}
+
+fn default_enabled() -> bool {
+    true
+}
+
+fn default_lease_seconds() -> u64 {
+    30
+}
+
+fn default_resume_after_seconds() -> u64 {
+    30
+}
+
+fn default_schedule_check_seconds() -> u64 {
+    30
+}
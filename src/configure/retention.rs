@@ -4,6 +4,8 @@
 //! disk can't silently fill the way it did before this existed. Three
 //! independent limits; an event is eligible for deletion if **any** is
 //! exceeded, deleting oldest-first until all are satisfied. Off by default.
+//! These are the defaults; per-storage and per-monitor rows override the age,
+//! quota and archived protection (`service::retention::policy`).
 
 use std::time::Duration;
 
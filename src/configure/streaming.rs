@@ -11,6 +11,8 @@ pub struct StreamingConfig {
     pub webrtc: WebRtcConfig,
     pub hls: HlsConfig,
     pub rtsp_proxy: RtspProxyConfig,
+    pub mjpeg: MjpegConfig,
+    pub talkback: TalkbackConfig,
 }
 
 impl Default for StreamingConfig {
@@ -24,6 +26,8 @@ impl Default for StreamingConfig {
             webrtc: WebRtcConfig::default(),
             hls: HlsConfig::default(),
             rtsp_proxy: RtspProxyConfig::default(),
+            mjpeg: MjpegConfig::default(),
+            talkback: TalkbackConfig::default(),
         }
     }
 }
@@ -186,6 +190,11 @@ pub struct HlsConfig {
     /// blocking LL-HLS player could be reaped between requests. The 90s default
     /// leaves a wide margin.
     pub idle_timeout_seconds: u64,
+    /// Publish a monitor's `SecondPath` (when it is an RTSP URL) as a second,
+    /// lower-bandwidth rendition in the master playlist. zm-api pulls it from
+    /// the camera itself, so it costs one extra camera connection per watched
+    /// monitor.
+    pub substream_enabled: bool,
     pub storage: HlsStorageConfig,
 }
 
@@ -198,6 +207,7 @@ impl Default for HlsConfig {
             ll_hls_enabled: false,
             partial_segment_ms: 200,
             idle_timeout_seconds: 90,
+            substream_enabled: true,
             storage: HlsStorageConfig::default(),
         }
     }
@@ -219,15 +229,34 @@ impl Default for HlsStorageConfig {
     }
 }
 
+/// The RTSP re-streaming server (`rtsp://host:port/monitor/{id}`), fed from
+/// the same per-monitor source as WebRTC and HLS. See `crate::streaming::rtsp`.
 #[derive(Debug, Clone, Deserialize)]
 #[serde(default)]
 pub struct RtspProxyConfig {
     pub enabled: bool,
     pub port: u16,
+    /// Inclusive range the server's UDP RTP/RTCP port pairs are drawn from.
     pub rtp_port_range_start: u16,
     pub rtp_port_range_end: u16,
+    /// Concurrent client connections; further connections are refused.
     pub max_sessions: u32,
     pub transport: String, // "udp" | "tcp" | "auto"
+    /// Digest realm. Cached digests are tied to it, so changing it makes every
+    /// user sign in to the API once more before RTSP accepts them.
+    pub realm: String,
+}
+
+impl RtspProxyConfig {
+    /// Whether SETUP may negotiate RTP over UDP.
+    pub fn allows_udp(&self) -> bool {
+        !self.transport.eq_ignore_ascii_case("tcp")
+    }
+
+    /// Whether SETUP may negotiate RTP interleaved on the RTSP connection.
+    pub fn allows_tcp(&self) -> bool {
+        !self.transport.eq_ignore_ascii_case("udp")
+    }
 }
 
 impl Default for RtspProxyConfig {
@@ -239,6 +268,62 @@ impl Default for RtspProxyConfig {
             rtp_port_range_end: 30000,
             max_sessions: 100,
             transport: "auto".to_string(),
+            realm: "zm-api".to_string(),
+        }
+    }
+}
+
+/// MJPEG live streams (`/api/v3/live/{id}/mjpeg`) for clients that only
+/// understand `multipart/x-mixed-replace`. See `crate::streaming::mjpeg`.
+#[derive(Debug, Clone, Deserialize)]
+#[serde(default)]
+pub struct MjpegConfig {
+    pub enabled: bool,
+    /// Frame rate when the request gives no `fps`.
+    pub default_fps: u32,
+    /// Requests for more are served at this rate. Every running rate is a
+    /// decoder of its own, so keep it low.
+    pub max_fps: u32,
+}
+
+impl Default for MjpegConfig {
+    fn default() -> Self {
+        Self {
+            enabled: true,
+            default_fps: 5,
+            max_fps: 15,
+        }
+    }
+}
+
+/// Two-way audio: a viewer's microphone, received on the WebRTC session and
+/// played out of the camera's speaker. See `crate::streaming::talkback`.
+#[derive(Debug, Clone, Deserialize)]
+#[serde(default)]
+pub struct TalkbackConfig {
+    pub enabled: bool,
+    /// `onvif` — an ONVIF backchannel RTSP session to the camera;
+    /// `socket` — `Talkback` messages up the monitor's stream socket (needs a
+    /// worker that consumes them); `auto` — ONVIF when the camera offers a
+    /// backchannel, else the socket.
+    pub transport: String, // "auto" | "onvif" | "socket"
+    /// Codec sent up the stream socket when the monitor's own audio codec
+    /// does not say what the camera speaks: `pcmu`, `pcma` or `aac`.
+    pub socket_codec: String,
+    /// Sample rate of AAC sent up the stream socket.
+    pub aac_sample_rate: u32,
+    /// How long the camera may take to set up a backchannel.
+    pub connect_timeout_seconds: u64,
+}
+
+impl Default for TalkbackConfig {
+    fn default() -> Self {
+        Self {
+            enabled: true,
+            transport: "auto".to_string(),
+            socket_codec: "pcmu".to_string(),
+            aac_sample_rate: 16000,
+            connect_timeout_seconds: 5,
         }
     }
 }
@@ -303,5 +388,21 @@ mod tests {
         assert_eq!(config.port, 8554);
         assert_eq!(config.rtp_port_range_start, 20000);
         assert_eq!(config.rtp_port_range_end, 30000);
+        assert_eq!(config.realm, "zm-api");
+        assert!(config.allows_udp() && config.allows_tcp());
+    }
+
+    #[test]
+    fn test_rtsp_proxy_transport_restricts_setup() {
+        let tcp = RtspProxyConfig {
+            transport: "tcp".to_string(),
+            ..Default::default()
+        };
+        assert!(tcp.allows_tcp() && !tcp.allows_udp());
+        let udp = RtspProxyConfig {
+            transport: "UDP".to_string(),
+            ..Default::default()
+        };
+        assert!(udp.allows_udp() && !udp.allows_tcp());
     }
 }
@@ -0,0 +1,72 @@
+//! Configuration for the event trash reaper (`src/service/trash.rs`).
+//!
+//! `DELETE /api/v3/events/{id}` tombstones an event and moves its media into
+//! the storage's `.trash/` directory. The reaper purges each one — DB rows and
+//! media — once it has sat there for the grace period, during which `POST
+//! /api/v3/events/{id}:restore` can still undo the delete. On by default:
+//! with it off, deleted events hold their disk space forever.
+
+use std::time::Duration;
+
+use serde::Deserialize;
+
+#[derive(Debug, Deserialize, Clone)]
+pub struct TrashConfig {
+    /// Master switch. When false the reaper never spawns and the trash is
+    /// never emptied.
+    #[serde(default = "default_enabled")]
+    pub enabled: bool,
+
+    /// How often the reaper looks for expired tombstones.
+    #[serde(default = "default_interval_seconds")]
+    pub interval_seconds: u64,
+
+    /// How long a deleted event stays restorable before it is purged.
+    #[serde(default = "default_grace_hours")]
+    pub grace_hours: u64,
+
+    /// Event directories removed at once, so a large purge cannot starve
+    /// recording IO on the same disk.
+    #[serde(default = "default_max_concurrent")]
+    pub max_concurrent: usize,
+}
+
+impl Default for TrashConfig {
+    fn default() -> Self {
+        Self {
+            enabled: default_enabled(),
+            interval_seconds: default_interval_seconds(),
+            grace_hours: default_grace_hours(),
+            max_concurrent: default_max_concurrent(),
+        }
+    }
+}
+
+impl TrashConfig {
+    pub fn interval(&self) -> Duration {
+        Duration::from_secs(self.interval_seconds.max(1))
+    }
+
+    pub fn grace(&self) -> chrono::Duration {
+        i64::try_from(self.grace_hours)
+            .ok()
+            .and_then(chrono::Duration::try_hours)
+            .unwrap_or(chrono::Duration::MAX)
+    }
+}
+
+fn default_enabled() -> bool {
+    true
+}
+
+fn default_interval_seconds() -> u64 {
+    600
+}
+
+fn default_grace_hours() -> u64 {
+    72
+}
+
+fn default_max_concurrent() -> usize {
+    4
+}
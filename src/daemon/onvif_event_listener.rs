@@ -38,6 +38,7 @@ use crate::onvif::error::OnvifError;
 use crate::onvif::events::{EventsClient, NotificationMessage, PullPointSubscription};
 use crate::repo::events as events_repo;
 use crate::server::state::AppState;
+use crate::service::event_feed::{EventNotification, EventNotificationSource};
 
 /// XML duration string requesting the device keep the subscription alive this
 /// long absent a `Renew`. Kept short so a dead listener's subscription expires
@@ -327,6 +328,14 @@ impl MonitorEventListener {
         };
 
         let saved = events_repo::create(&self.state, active).await?;
+        self.state.event_feed.publish(EventNotification::event_open(
+            EventNotificationSource::Onvif,
+            self.monitor_id,
+            saved.id,
+            Some(self.alarm_cause.clone()),
+            start,
+        ));
+        self.state.notifier.event_start(self.monitor_id, saved.id);
         Ok(saved.id)
     }
 
@@ -347,6 +356,15 @@ impl MonitorEventListener {
         active.end_date_time = Set(Some(end));
         active.length = Set(rust_decimal::Decimal::from_f64_retain(length).unwrap_or_default());
         events_repo::update(&self.state, active).await?;
+        self.state
+            .event_feed
+            .publish(EventNotification::event_close(
+                EventNotificationSource::Onvif,
+                self.monitor_id,
+                event_id,
+                end,
+            ));
+        self.state.notifier.event_end(self.monitor_id, event_id);
         Ok(())
     }
 
@@ -0,0 +1,35 @@
+//! Request DTO for `POST /api/v3/events:bulk`.
+
+use serde::{Deserialize, Serialize};
+use utoipa::ToSchema;
+
+use crate::dto::request::filter_ast::FilterQuery;
+
+/// What a bulk job does to each matching event.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize, ToSchema)]
+#[serde(rename_all = "snake_case")]
+pub enum BulkEventAction {
+    Archive,
+    Unarchive,
+    /// Soft-delete the events, as `DELETE /events/{id}` does: hidden at once,
+    /// media moved to the trash and purged after the grace period. Archived
+    /// and still-recording events are skipped.
+    Delete,
+}
+
+/// Run an action over every event matching a filter, as a background job.
+///
+/// Exactly one of `saved_search_id` and `filter` selects the events.
+#[derive(Debug, Clone, Serialize, Deserialize, ToSchema)]
+pub struct BulkEventsRequest {
+    /// A saved search (`/api/v3/saved-searches`) the caller can read.
+    #[serde(default)]
+    pub saved_search_id: Option<u64>,
+    /// An inline filter AST, as accepted by `POST /api/v3/filters/preview`.
+    #[serde(default, alias = "ast")]
+    pub filter: Option<FilterQuery>,
+    pub action: BulkEventAction,
+    /// Count and sample what would change without changing anything.
+    #[serde(default)]
+    pub dry_run: bool,
+}
@@ -0,0 +1,45 @@
+//! Request DTO for `POST /api/v3/events/export`.
+
+use serde::{Deserialize, Serialize};
+use utoipa::ToSchema;
+
+use crate::dto::request::filter_ast::FilterQuery;
+
+/// Archive format of an export bundle.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Serialize, Deserialize, ToSchema)]
+#[serde(rename_all = "snake_case")]
+pub enum ExportFormat {
+    #[default]
+    Zip,
+    Tar,
+}
+
+impl ExportFormat {
+    pub fn extension(self) -> &'static str {
+        match self {
+            ExportFormat::Zip => "zip",
+            ExportFormat::Tar => "tar",
+        }
+    }
+
+    pub fn content_type(self) -> &'static str {
+        match self {
+            ExportFormat::Zip => "application/zip",
+            ExportFormat::Tar => "application/x-tar",
+        }
+    }
+}
+
+/// Bundle events' media and metadata into one archive.
+///
+/// Exactly one of `event_ids` and `filter` selects the events.
+#[derive(Debug, Clone, Serialize, Deserialize, ToSchema)]
+pub struct EventExportRequest {
+    #[serde(default)]
+    pub event_ids: Option<Vec<u64>>,
+    /// An inline filter AST, as accepted by `POST /api/v3/filters/preview`.
+    #[serde(default, alias = "ast")]
+    pub filter: Option<FilterQuery>,
+    #[serde(default)]
+    pub format: ExportFormat,
+}
@@ -0,0 +1,51 @@
+//! Request DTO for `POST /api/v3/events/{id}/jobs`.
+
+use garde::Validate;
+use serde::{Deserialize, Serialize};
+use utoipa::ToSchema;
+
+use crate::entity::sea_orm_active_enums::JobKind;
+
+/// What a media job does to its event.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize, ToSchema)]
+#[serde(rename_all = "snake_case")]
+pub enum EventJobKind {
+    /// Re-encode the event's recording (or, for a JPEG-only event, its
+    /// captured frames) to an H.264 mp4 and make it the default video.
+    Transcode,
+    /// Copy the event's media to `[jobs].export_dir/event-{id}/`.
+    Export,
+    /// Relocate the event's media to `target_storage_id` and repoint
+    /// `Events.StorageId` at it.
+    Move,
+    /// Copy the event's media to `target_storage_id` and record it as
+    /// `Events.SecondaryStorageId`.
+    Copy,
+}
+
+impl From<EventJobKind> for JobKind {
+    fn from(kind: EventJobKind) -> Self {
+        match kind {
+            EventJobKind::Transcode => JobKind::EventTranscode,
+            EventJobKind::Export => JobKind::EventExport,
+            EventJobKind::Move => JobKind::EventMove,
+            EventJobKind::Copy => JobKind::EventCopy,
+        }
+    }
+}
+
+/// Queue a media job for one event.
+#[derive(Debug, Clone, Serialize, Deserialize, ToSchema, Validate)]
+pub struct EventJobRequest {
+    #[garde(skip)]
+    pub kind: EventJobKind,
+    /// Destination `Storage` row; required for `move` and `copy`.
+    #[schema(example = 2)]
+    #[garde(skip)]
+    pub target_storage_id: Option<u16>,
+    /// `transcode` only: scale down to at most this width, keeping the aspect
+    /// ratio. Omit to keep the source size.
+    #[schema(example = 1280)]
+    #[garde(inner(range(min = 16, max = 7680)))]
+    pub max_width: Option<u32>,
+}
@@ -0,0 +1,20 @@
+//! Request DTOs for the cross-monitor event firehose.
+
+use serde::Deserialize;
+use utoipa::ToSchema;
+
+/// Query parameters for `GET /api/v3/events/stream` and its WebSocket twin.
+#[derive(Debug, Default, Deserialize, ToSchema)]
+pub struct EventStreamQuery {
+    /// Comma-separated monitor ids to narrow the stream to (still subject to
+    /// the caller's ACL). Absent means every permitted monitor.
+    #[schema(example = "1,4")]
+    pub monitors: Option<String>,
+
+    /// Comma-separated notification kinds to receive (`event_open`,
+    /// `event_close`, `alarm_score`, `capture_fault`, `capture_restored`,
+    /// `ptz_control`).
+    /// Absent means all kinds.
+    #[schema(example = "event_open,capture_fault")]
+    pub kinds: Option<String>,
+}
@@ -1,4 +1,5 @@
 pub mod ai;
+pub mod bulk_events;
 pub mod config;
 pub mod control_presets;
 pub mod controls;
@@ -7,6 +8,9 @@ pub mod devices;
 #[cfg(feature = "onvif-discovery")]
 pub mod discovery;
 pub mod event_data;
+pub mod event_export;
+pub mod event_jobs;
+pub mod event_stream;
 pub mod events;
 pub mod events_tags;
 pub mod filter_ast;
@@ -23,11 +27,14 @@ pub mod monitor_presets;
 pub mod monitor_status;
 pub mod monitors_permissions;
 pub mod montage_layouts;
+pub mod notification_rules;
 pub mod object_types;
 pub mod ptz;
 pub mod reports;
+pub mod retention;
 #[allow(clippy::module_inception)]
 mod request;
+pub mod saved_searches;
 pub mod search;
 pub mod server_stats;
 pub mod servers;
@@ -39,6 +46,7 @@ pub mod stats;
 pub mod storage;
 mod streaming;
 pub mod tags;
+pub mod timeline;
 pub mod triggers_x10;
 pub mod user_preferences;
 pub mod users;
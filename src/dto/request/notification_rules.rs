@@ -0,0 +1,179 @@
+//! Request DTOs for notification rules (`/api/v3/notification-rules`).
+
+use garde::Validate;
+use serde::{Deserialize, Deserializer, Serialize};
+use utoipa::ToSchema;
+
+use crate::dto::request::filter_ast::FilterQuery;
+use crate::entity::sea_orm_active_enums::{NotificationChannel, NotificationTrigger};
+
+/// Longest dedupe window a rule may ask for (one week).
+pub const MAX_DEDUPE_WINDOW_SECONDS: u32 = 7 * 24 * 3600;
+
+/// Shortest webhook signing key accepted from a client.
+pub const MIN_SECRET_LEN: usize = 16;
+
+/// Message template. `{{placeholders}}` are filled from the notification:
+/// `rule`, `trigger`, `monitor_id`, `event_id`, `cause`, `score`,
+/// `storage_id`, `free_pct` and `time`. Used as the email subject and body;
+/// webhook and MQTT payloads carry the rendered `body` as `message`.
+#[derive(Debug, Clone, Default, PartialEq, Eq, Serialize, Deserialize, ToSchema, Validate)]
+pub struct NotificationTemplate {
+    #[schema(example = "{{cause}} on monitor {{monitor_id}}")]
+    #[garde(inner(length(max = 255)))]
+    pub subject: Option<String>,
+    #[garde(inner(length(max = 4096)))]
+    pub body: Option<String>,
+}
+
+/// Create a notification rule owned by the caller.
+#[derive(Clone, Serialize, Deserialize, ToSchema, Validate)]
+pub struct CreateNotificationRuleRequest {
+    #[schema(example = "Person on the driveway")]
+    #[garde(length(min = 1, max = 64))]
+    pub name: String,
+    #[serde(default = "default_enabled")]
+    #[garde(skip)]
+    pub enabled: bool,
+    #[garde(skip)]
+    pub trigger: NotificationTrigger,
+    /// Filter AST the event must match (same shape as
+    /// `POST /api/v3/filters/preview`). Omit to match every event on monitors
+    /// the owner can see. Not used by `storage_health`.
+    #[garde(skip)]
+    pub predicate: Option<FilterQuery>,
+    /// Minimum frame score for `score_threshold` (required there); free-space
+    /// floor in percent for `storage_health` (default 10).
+    #[garde(skip)]
+    pub threshold: Option<u32>,
+    #[garde(skip)]
+    pub channel: NotificationChannel,
+    /// Email address (`smtp`), `http(s)` URL (`webhook`) or topic (`mqtt`).
+    #[schema(example = "alerts@example.com")]
+    #[garde(length(min = 1, max = 512))]
+    pub target: String,
+    /// HMAC-SHA256 signing key for a `webhook` rule. Omit to have one
+    /// generated; it is returned once, in the create response.
+    #[garde(inner(length(min = MIN_SECRET_LEN, max = 128)))]
+    pub secret: Option<String>,
+    #[garde(dive)]
+    pub template: Option<NotificationTemplate>,
+    /// Suppress repeat notifications for the same monitor (or storage) for
+    /// this many seconds. `0` notifies every time.
+    #[serde(default)]
+    #[garde(range(max = MAX_DEDUPE_WINDOW_SECONDS))]
+    pub dedupe_window_seconds: u32,
+}
+
+// Manual Debug: the webhook secret must never reach logs.
+impl std::fmt::Debug for CreateNotificationRuleRequest {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        f.debug_struct("CreateNotificationRuleRequest")
+            .field("name", &self.name)
+            .field("enabled", &self.enabled)
+            .field("trigger", &self.trigger)
+            .field("predicate", &self.predicate)
+            .field("threshold", &self.threshold)
+            .field("channel", &self.channel)
+            .field("target", &self.target)
+            .field("secret", &self.secret.as_ref().map(|_| "[REDACTED]"))
+            .field("template", &self.template)
+            .field("dedupe_window_seconds", &self.dedupe_window_seconds)
+            .finish()
+    }
+}
+
+/// Change any subset of a rule. Omitted fields are kept; for `predicate`,
+/// `threshold` and `template`, an explicit `null` clears the value.
+#[derive(Default, Clone, Serialize, Deserialize, ToSchema, Validate)]
+pub struct UpdateNotificationRuleRequest {
+    #[garde(inner(length(min = 1, max = 64)))]
+    pub name: Option<String>,
+    #[garde(skip)]
+    pub enabled: Option<bool>,
+    #[garde(skip)]
+    pub trigger: Option<NotificationTrigger>,
+    #[serde(default, deserialize_with = "explicit_null")]
+    #[garde(skip)]
+    pub predicate: Option<Option<FilterQuery>>,
+    #[serde(default, deserialize_with = "explicit_null")]
+    #[garde(skip)]
+    pub threshold: Option<Option<u32>>,
+    #[garde(skip)]
+    pub channel: Option<NotificationChannel>,
+    #[garde(inner(length(min = 1, max = 512)))]
+    pub target: Option<String>,
+    /// Replace a `webhook` rule's signing key.
+    #[garde(inner(length(min = MIN_SECRET_LEN, max = 128)))]
+    pub secret: Option<String>,
+    /// Generate a new signing key for a `webhook` rule; it is returned once,
+    /// in the update response.
+    #[serde(default)]
+    #[garde(skip)]
+    pub rotate_secret: bool,
+    #[serde(default, deserialize_with = "explicit_null")]
+    #[garde(skip)]
+    pub template: Option<Option<NotificationTemplate>>,
+    #[garde(inner(range(max = MAX_DEDUPE_WINDOW_SECONDS)))]
+    pub dedupe_window_seconds: Option<u32>,
+}
+
+// Manual Debug: the webhook secret must never reach logs.
+impl std::fmt::Debug for UpdateNotificationRuleRequest {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        f.debug_struct("UpdateNotificationRuleRequest")
+            .field("name", &self.name)
+            .field("enabled", &self.enabled)
+            .field("trigger", &self.trigger)
+            .field("predicate", &self.predicate)
+            .field("threshold", &self.threshold)
+            .field("channel", &self.channel)
+            .field("target", &self.target)
+            .field("secret", &self.secret.as_ref().map(|_| "[REDACTED]"))
+            .field("rotate_secret", &self.rotate_secret)
+            .field("template", &self.template)
+            .field("dedupe_window_seconds", &self.dedupe_window_seconds)
+            .finish()
+    }
+}
+
+fn default_enabled() -> bool {
+    true
+}
+
+/// Deserialize a present field — including an explicit `null` — as `Some`, so
+/// that with `#[serde(default)]` an omitted field (`None`) can be told apart
+/// from one being cleared (`Some(None)`).
+pub(crate) fn explicit_null<'de, D, T>(deserializer: D) -> Result<Option<Option<T>>, D::Error>
+where
+    D: Deserializer<'de>,
+    T: Deserialize<'de>,
+{
+    Option::<T>::deserialize(deserializer).map(Some)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn update_tells_omitted_from_null() {
+        let req: UpdateNotificationRuleRequest =
+            serde_json::from_str(r#"{"threshold": null}"#).unwrap();
+        assert_eq!(req.threshold, Some(None), "null clears");
+        assert!(req.predicate.is_none(), "omitted keeps");
+
+        let req: UpdateNotificationRuleRequest =
+            serde_json::from_str(r#"{"threshold": 80}"#).unwrap();
+        assert_eq!(req.threshold, Some(Some(80)));
+    }
+
+    #[test]
+    fn debug_redacts_the_secret() {
+        let req: UpdateNotificationRuleRequest =
+            serde_json::from_str(r#"{"secret": "hunter2hunter2hunter2"}"#).unwrap();
+        let out = format!("{req:?}");
+        assert!(!out.contains("hunter2"), "{out}");
+        assert!(out.contains("[REDACTED]"));
+    }
+}
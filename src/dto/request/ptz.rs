@@ -4,6 +4,8 @@ use garde::Validate;
 use serde::{Deserialize, Serialize};
 use utoipa::ToSchema;
 
+use crate::dto::request::notification_rules::explicit_null;
+use crate::ptz::aim::{FrameArea, FramePoint};
 use crate::ptz::traits::{FocusParams, MoveParams, ZoomParams};
 
 /// Request for continuous movement
@@ -138,6 +140,58 @@ pub struct PtzRelativeRequest {
     pub zoom_delta: Option<f64>,
 }
 
+/// Request to center the camera on a point of its picture, as clicked on the
+/// live view
+#[derive(Debug, Clone, Deserialize, Serialize, ToSchema, Validate)]
+pub struct PtzPointRequest {
+    /// Horizontal position, 0.0 at the left edge to 1.0 at the right
+    #[garde(range(min = 0.0, max = 1.0))]
+    pub x: f64,
+
+    /// Vertical position, 0.0 at the top edge to 1.0 at the bottom
+    #[garde(range(min = 0.0, max = 1.0))]
+    pub y: f64,
+}
+
+impl From<PtzPointRequest> for FramePoint {
+    fn from(req: PtzPointRequest) -> Self {
+        Self { x: req.x, y: req.y }
+    }
+}
+
+/// Request to center the camera on an area of its picture and zoom in until
+/// it fills the frame, as dragged out on the live view
+#[derive(Debug, Clone, Deserialize, Serialize, ToSchema, Validate)]
+pub struct PtzAreaRequest {
+    /// Left edge, 0.0 to 1.0 of the frame width
+    #[garde(range(min = 0.0, max = 1.0))]
+    pub x: f64,
+
+    /// Top edge, 0.0 to 1.0 of the frame height
+    #[garde(range(min = 0.0, max = 1.0))]
+    pub y: f64,
+
+    /// Width as a fraction of the frame width
+    #[garde(range(min = 0.01, max = 1.0))]
+    pub width: f64,
+
+    /// Height as a fraction of the frame height
+    #[garde(range(min = 0.01, max = 1.0))]
+    pub height: f64,
+}
+
+impl From<PtzAreaRequest> for FrameArea {
+    /// An area running off the frame is cut at its edge.
+    fn from(req: PtzAreaRequest) -> Self {
+        Self {
+            x: req.x,
+            y: req.y,
+            width: req.width.min(1.0 - req.x),
+            height: req.height.min(1.0 - req.y),
+        }
+    }
+}
+
 /// Generic PTZ command request
 #[derive(Debug, Clone, Deserialize, Serialize, ToSchema, Validate)]
 pub struct PtzGenericCommandRequest {
@@ -151,6 +205,115 @@ pub struct PtzGenericCommandRequest {
     pub params: Option<serde_json::Value>,
 }
 
+/// Longest a tour may hold at one preset (one hour).
+pub const MAX_TOUR_DWELL_SECONDS: u32 = 3600;
+
+/// Most presets one tour may visit.
+pub const MAX_TOUR_STEPS: usize = 64;
+
+/// Most weekly windows one tour schedule may have.
+pub const MAX_TOUR_WINDOWS: usize = 32;
+
+/// One stop on a tour
+#[derive(Debug, Clone, PartialEq, Eq, Deserialize, Serialize, ToSchema, Validate)]
+pub struct PtzTourStep {
+    /// Preset to go to
+    #[garde(range(min = 1))]
+    pub preset_id: u32,
+
+    /// Seconds to hold there before moving on
+    #[garde(range(min = 1, max = MAX_TOUR_DWELL_SECONDS))]
+    pub dwell_seconds: u32,
+
+    /// Travel speed (0-100 percent), for protocols that can set one (Axis
+    /// VAPIX, Sony VISCA); others use the camera's own preset speed
+    #[garde(range(max = 100))]
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub speed: Option<u8>,
+}
+
+/// Day of the week in a tour schedule
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Deserialize, Serialize, ToSchema)]
+#[serde(rename_all = "lowercase")]
+pub enum PtzTourDay {
+    Mon,
+    Tue,
+    Wed,
+    Thu,
+    Fri,
+    Sat,
+    Sun,
+}
+
+/// A weekly window a tour runs in by itself, in server local time. An `end`
+/// before `start` runs past midnight into the next day; `end` equal to
+/// `start` runs all day.
+#[derive(Debug, Clone, PartialEq, Eq, Deserialize, Serialize, ToSchema, Validate)]
+pub struct PtzTourWindow {
+    /// Days the window opens on
+    #[garde(length(min = 1, max = 7))]
+    pub days: Vec<PtzTourDay>,
+
+    /// Opening time, `HH:MM`
+    #[schema(example = "08:00")]
+    #[garde(pattern(r"^([01][0-9]|2[0-3]):[0-5][0-9]$"))]
+    pub start: String,
+
+    /// Closing time, `HH:MM`
+    #[schema(example = "18:00")]
+    #[garde(pattern(r"^([01][0-9]|2[0-3]):[0-5][0-9]$"))]
+    pub end: String,
+}
+
+/// Request to create a tour on a monitor
+#[derive(Debug, Clone, Deserialize, Serialize, ToSchema, Validate)]
+pub struct CreatePtzTourRequest {
+    /// Tour name, unique per monitor
+    #[schema(example = "Perimeter")]
+    #[garde(length(min = 1, max = 64))]
+    pub name: String,
+
+    /// Disabled tours can neither be started nor run on their schedule
+    #[garde(skip)]
+    #[serde(default = "default_enabled")]
+    pub enabled: bool,
+
+    /// Presets to visit, in order; the tour loops back to the first
+    #[garde(length(min = 1, max = MAX_TOUR_STEPS), dive)]
+    pub steps: Vec<PtzTourStep>,
+
+    /// Weekly windows the tour runs in by itself. Omit to run it only when
+    /// started.
+    #[garde(dive)]
+    #[serde(default)]
+    pub schedule: Option<Vec<PtzTourWindow>>,
+}
+
+/// Request to change any subset of a tour. `schedule: null` removes the
+/// schedule.
+#[derive(Debug, Clone, Default, Deserialize, Serialize, ToSchema, Validate)]
+pub struct UpdatePtzTourRequest {
+    #[garde(inner(length(min = 1, max = 64)))]
+    #[serde(default)]
+    pub name: Option<String>,
+
+    #[garde(skip)]
+    #[serde(default)]
+    pub enabled: Option<bool>,
+
+    #[garde(inner(length(min = 1, max = MAX_TOUR_STEPS)), dive)]
+    #[serde(default)]
+    pub steps: Option<Vec<PtzTourStep>>,
+
+    #[garde(dive)]
+    #[serde(default, deserialize_with = "explicit_null")]
+    pub schedule: Option<Option<Vec<PtzTourWindow>>>,
+}
+
+fn default_enabled() -> bool {
+    true
+}
+
 #[cfg(test)]
 mod tests {
     use super::*;
@@ -220,4 +383,94 @@ mod tests {
         assert!(preset(Some("Cam-01_west")).validate().is_ok());
         assert!(preset(Some("a-")).validate().is_ok());
     }
+
+    fn tour(steps: Vec<PtzTourStep>, schedule: Option<Vec<PtzTourWindow>>) -> CreatePtzTourRequest {
+        CreatePtzTourRequest {
+            name: "Perimeter".into(),
+            enabled: true,
+            steps,
+            schedule,
+        }
+    }
+
+    fn step(preset_id: u32, dwell_seconds: u32) -> PtzTourStep {
+        PtzTourStep {
+            preset_id,
+            dwell_seconds,
+            speed: None,
+        }
+    }
+
+    fn window(start: &str, end: &str) -> PtzTourWindow {
+        PtzTourWindow {
+            days: vec![PtzTourDay::Mon],
+            start: start.into(),
+            end: end.into(),
+        }
+    }
+
+    #[test]
+    fn tour_needs_steps_with_a_preset_and_a_dwell() {
+        assert!(tour(vec![step(1, 10), step(2, 5)], None).validate().is_ok());
+        assert!(tour(vec![], None).validate().is_err());
+        assert!(tour(vec![step(0, 10)], None).validate().is_err());
+        assert!(tour(vec![step(1, 0)], None).validate().is_err());
+        assert!(tour(vec![step(1, MAX_TOUR_DWELL_SECONDS + 1)], None)
+            .validate()
+            .is_err());
+    }
+
+    #[test]
+    fn tour_windows_take_hours_and_minutes() {
+        let ok = tour(vec![step(1, 10)], Some(vec![window("22:00", "06:30")]));
+        assert!(ok.validate().is_ok());
+        for (start, end) in [("8:00", "18:00"), ("24:00", "01:00"), ("08:00", "18:60")] {
+            let bad = tour(vec![step(1, 10)], Some(vec![window(start, end)]));
+            assert!(bad.validate().is_err(), "{start}-{end}");
+        }
+        let mut no_days = window("08:00", "18:00");
+        no_days.days.clear();
+        assert!(tour(vec![step(1, 10)], Some(vec![no_days]))
+            .validate()
+            .is_err());
+    }
+
+    #[test]
+    fn tour_update_checks_only_what_it_changes() {
+        let omitted: UpdatePtzTourRequest = serde_json::from_str(r#"{"enabled": false}"#).unwrap();
+        assert!(omitted.schedule.is_none());
+        let cleared: UpdatePtzTourRequest = serde_json::from_str(r#"{"schedule": null}"#).unwrap();
+        assert_eq!(cleared.schedule, Some(None));
+
+        let emptied = UpdatePtzTourRequest {
+            steps: Some(vec![]),
+            ..Default::default()
+        };
+        assert!(emptied.validate().is_err());
+    }
+
+    #[test]
+    fn aim_requests_stay_inside_the_frame() {
+        let point = PtzPointRequest { x: 0.5, y: 1.2 };
+        assert!(point.validate().is_err());
+
+        let area = PtzAreaRequest {
+            x: 0.8,
+            y: 0.1,
+            width: 0.4,
+            height: 0.2,
+        };
+        assert!(area.validate().is_ok());
+        let area = FrameArea::from(area);
+        assert!((area.width - 0.2).abs() < 1e-9);
+        assert!((area.height - 0.2).abs() < 1e-9);
+
+        let sliver = PtzAreaRequest {
+            x: 0.1,
+            y: 0.1,
+            width: 0.0,
+            height: 0.2,
+        };
+        assert!(sliver.validate().is_err());
+    }
 }
@@ -0,0 +1,51 @@
+//! Request DTOs for retention overrides (`/api/v3/storage/{id}/retention`,
+//! `/api/v3/monitors/{id}/retention`).
+//!
+//! Every field is optional: an omitted (or `null`) field inherits — from the
+//! global `[retention]` section for a storage, from the event's storage for a
+//! monitor. `0` for a limit switches that limit off at this level. A `PUT`
+//! replaces the whole override.
+
+use garde::Validate;
+use serde::{Deserialize, Serialize};
+use utoipa::ToSchema;
+
+/// Longest age limit accepted (one hundred years).
+pub const MAX_AGE_DAYS: u32 = 36_500;
+
+/// Retention overrides for one storage.
+#[derive(Debug, Clone, Default, Serialize, Deserialize, ToSchema, Validate)]
+pub struct StorageRetentionRequest {
+    /// Delete (or tier) events older than this many days; `0` disables.
+    #[schema(example = 30)]
+    #[garde(inner(range(max = MAX_AGE_DAYS)))]
+    pub max_age_days: Option<u32>,
+    /// Cap on the bytes this storage may hold (sum of `Events.DiskSpace`);
+    /// `0` disables.
+    #[garde(skip)]
+    pub max_storage_bytes: Option<u64>,
+    /// Whether archived events are exempt. Defaults to `true`.
+    #[garde(skip)]
+    pub protect_archived: Option<bool>,
+    /// Move expiring events to this storage instead of deleting them. It must
+    /// exist and must not lead back to this storage.
+    #[garde(skip)]
+    pub tier_storage_id: Option<u16>,
+}
+
+/// Retention overrides for one monitor's events, on whichever storage they
+/// are.
+#[derive(Debug, Clone, Default, Serialize, Deserialize, ToSchema, Validate)]
+pub struct MonitorRetentionRequest {
+    /// Expire this monitor's events older than this many days; `0` disables.
+    #[schema(example = 7)]
+    #[garde(inner(range(max = MAX_AGE_DAYS)))]
+    pub max_age_days: Option<u32>,
+    /// Cap on the bytes this monitor's events may hold on each storage. Not
+    /// inherited: omitted or `0` leaves only the storage-wide quota.
+    #[garde(skip)]
+    pub max_storage_bytes: Option<u64>,
+    /// Whether this monitor's archived events are exempt.
+    #[garde(skip)]
+    pub protect_archived: Option<bool>,
+}
@@ -0,0 +1,27 @@
+//! Request DTOs for saved event searches (`/api/v3/saved-searches`).
+
+use garde::Validate;
+use serde::{Deserialize, Serialize};
+use utoipa::ToSchema;
+
+use crate::dto::request::filter_ast::FilterQuery;
+
+/// Save a named event search. The filter is the same structured AST accepted by
+/// `POST /api/v3/filters/preview`; it is validated and compiled before saving.
+#[derive(Debug, Clone, Serialize, Deserialize, ToSchema, Validate)]
+pub struct CreateSavedSearchRequest {
+    #[schema(example = "Driveway people at night")]
+    #[garde(length(min = 1, max = 64))]
+    pub name: String,
+    #[garde(skip)]
+    pub filter: FilterQuery,
+}
+
+/// Rename a saved search and/or replace its filter. Omitted fields are kept.
+#[derive(Debug, Default, Clone, Serialize, Deserialize, ToSchema, Validate)]
+pub struct UpdateSavedSearchRequest {
+    #[garde(length(min = 1, max = 64))]
+    pub name: Option<String>,
+    #[garde(skip)]
+    pub filter: Option<FilterQuery>,
+}
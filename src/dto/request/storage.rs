@@ -11,3 +11,23 @@ pub struct CreateStorageRequest {
     pub server_id: Option<u32>,
     pub url: Option<String>,
 }
+
+/// Body of `POST /api/v3/storage/{id}:reap`. Omit it for a dry run.
+#[derive(Debug, Clone, Serialize, Deserialize, ToSchema)]
+pub struct ReapStorageRequest {
+    /// Report what would be deleted or moved without touching anything.
+    #[serde(default = "default_dry_run")]
+    pub dry_run: bool,
+}
+
+impl Default for ReapStorageRequest {
+    fn default() -> Self {
+        Self {
+            dry_run: default_dry_run(),
+        }
+    }
+}
+
+fn default_dry_run() -> bool {
+    true
+}
@@ -0,0 +1,17 @@
+//! Query parameters for `GET /api/v3/monitors/timeline`.
+
+use serde::Deserialize;
+
+use crate::dto::wrappers::DateTimeWrapper;
+
+#[derive(Debug, Deserialize)]
+pub struct TimelineQuery {
+    /// Range start (RFC 3339).
+    pub from: DateTimeWrapper,
+    /// Range end (RFC 3339), exclusive.
+    pub to: DateTimeWrapper,
+    /// Bucket width: `hour`, `day` or `week`, a number with a unit (`90s`,
+    /// `15m`, `6h`, `2d`), or plain seconds. Defaults to `hour`.
+    #[serde(default)]
+    pub bucket: Option<String>,
+}
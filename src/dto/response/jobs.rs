@@ -0,0 +1,65 @@
+//! Response DTO for background jobs (`GET /api/v3/jobs/{id}`).
+
+use serde::{Deserialize, Serialize};
+use serde_json::Value;
+use utoipa::ToSchema;
+
+use crate::entity::jobs;
+use crate::entity::sea_orm_active_enums::{JobKind, JobStatus};
+
+/// A background job's parameters, progress and outcome.
+#[derive(Debug, Clone, Serialize, Deserialize, ToSchema)]
+pub struct JobResponse {
+    pub id: u64,
+    pub kind: JobKind,
+    pub status: JobStatus,
+    pub user_id: Option<u32>,
+    /// Kind-specific parameters, as captured at submission.
+    #[schema(value_type = Object)]
+    pub params: Value,
+    /// Last processed key; progress resumes after it.
+    pub cursor: Option<u64>,
+    /// Items the job expects to visit, when known up front.
+    pub total: Option<u64>,
+    pub processed: u64,
+    /// Items changed — or, for a dry run, that would be.
+    pub affected: u64,
+    pub failed: u64,
+    /// Kind-specific result (for a bulk dry run, a sample of matching ids).
+    #[serde(skip_serializing_if = "Option::is_none")]
+    #[schema(value_type = Option<Object>)]
+    pub result: Option<Value>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub error: Option<String>,
+    /// RFC 3339 timestamps.
+    pub created_at: String,
+    pub started_at: Option<String>,
+    pub finished_at: Option<String>,
+    pub updated_at: String,
+}
+
+impl From<jobs::Model> for JobResponse {
+    fn from(m: jobs::Model) -> Self {
+        Self {
+            id: m.id,
+            kind: m.kind,
+            status: m.status,
+            user_id: m.user_id,
+            params: serde_json::from_str(&m.params_json).unwrap_or(Value::Null),
+            cursor: m.cursor,
+            total: m.total,
+            processed: m.processed,
+            affected: m.affected,
+            failed: m.failed,
+            result: m
+                .result_json
+                .as_deref()
+                .and_then(|r| serde_json::from_str(r).ok()),
+            error: m.error,
+            created_at: m.created_at.and_utc().to_rfc3339(),
+            started_at: m.started_at.map(|t| t.and_utc().to_rfc3339()),
+            finished_at: m.finished_at.map(|t| t.and_utc().to_rfc3339()),
+            updated_at: m.updated_at.and_utc().to_rfc3339(),
+        }
+    }
+}
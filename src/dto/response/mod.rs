@@ -14,6 +14,7 @@ pub mod frames;
 pub mod groups;
 pub mod groups_monitors;
 pub mod groups_permissions;
+pub mod jobs;
 pub mod logs;
 pub mod manufacturers;
 pub mod models;
@@ -24,11 +25,14 @@ pub mod monitor_status;
 pub mod monitors;
 pub mod monitors_permissions;
 pub mod montage_layouts;
+pub mod notification_rules;
 pub mod object_types;
 pub mod ptz;
 pub mod reports;
+pub mod retention;
 #[allow(clippy::module_inception)]
 mod response;
+pub mod saved_searches;
 pub mod search;
 mod server;
 pub mod server_stats;
@@ -41,6 +45,7 @@ pub mod stats;
 pub mod storage;
 mod streaming;
 pub mod tags;
+pub mod timeline;
 pub mod triggers_x10;
 pub mod user_preferences;
 pub mod users;
@@ -63,6 +68,7 @@ pub use frames::*;
 pub use groups::*;
 pub use groups_monitors::*;
 pub use groups_permissions::*;
+pub use jobs::*;
 pub use logs::*;
 pub use manufacturers::*;
 pub use models::*;
@@ -73,9 +79,12 @@ pub use monitor_status::*;
 pub use monitors::*;
 pub use monitors_permissions::*;
 pub use montage_layouts::*;
+pub use notification_rules::*;
 pub use object_types::*;
 pub use reports::*;
+pub use retention::*;
 pub use response::*;
+pub use saved_searches::*;
 pub use server::*;
 pub use server_stats::*;
 pub use servers::*;
@@ -87,6 +96,7 @@ pub use stats::*;
 pub use storage::*;
 pub use streaming::*;
 pub use tags::*;
+pub use timeline::*;
 pub use triggers_x10::*;
 pub use user_preferences::*;
 pub use users::*;
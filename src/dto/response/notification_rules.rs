@@ -0,0 +1,170 @@
+//! Response DTOs for notification rules (`/api/v3/notification-rules`) and
+//! their delivery history.
+
+use serde::{Deserialize, Serialize};
+use serde_json::Value;
+use utoipa::ToSchema;
+
+use crate::dto::PaginatedResponse;
+use crate::entity::sea_orm_active_enums::{
+    DeliveryStatus, NotificationChannel, NotificationTrigger,
+};
+use crate::entity::{notification_deliveries, notification_rules};
+
+/// A notification rule.
+#[derive(Debug, Clone, Serialize, Deserialize, ToSchema)]
+pub struct NotificationRuleResponse {
+    pub id: u64,
+    pub user_id: u32,
+    pub name: String,
+    pub enabled: bool,
+    pub trigger: NotificationTrigger,
+    /// The stored filter AST (`FilterQuery`), if any.
+    #[schema(value_type = Option<Object>)]
+    pub predicate: Option<Value>,
+    pub threshold: Option<u32>,
+    pub channel: NotificationChannel,
+    pub target: String,
+    /// Whether webhook calls are signed (always, for `webhook` rules).
+    pub has_secret: bool,
+    /// The signing key — present only in the response that generated it
+    /// (create without `secret`, or update with `rotate_secret`).
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub secret: Option<String>,
+    /// The stored `NotificationTemplate`, if any.
+    #[schema(value_type = Option<Object>)]
+    pub template: Option<Value>,
+    pub dedupe_window_seconds: u32,
+    /// RFC 3339 timestamps.
+    pub created_at: String,
+    pub updated_at: String,
+}
+
+impl From<notification_rules::Model> for NotificationRuleResponse {
+    fn from(m: notification_rules::Model) -> Self {
+        // Both documents are validated on write; an unparseable one can only
+        // come from a hand-edited row and is shown as absent.
+        let parse = |s: Option<String>| s.and_then(|s| serde_json::from_str(&s).ok());
+        Self {
+            id: m.id,
+            user_id: m.user_id,
+            name: m.name,
+            enabled: m.enabled,
+            trigger: m.trigger,
+            predicate: parse(m.predicate_json),
+            threshold: m.threshold,
+            channel: m.channel,
+            target: m.target,
+            has_secret: m.secret.is_some(),
+            secret: None,
+            template: parse(m.template_json),
+            dedupe_window_seconds: m.dedupe_window_seconds,
+            created_at: m.created_at.and_utc().to_rfc3339(),
+            updated_at: m.updated_at.and_utc().to_rfc3339(),
+        }
+    }
+}
+
+/// Paginated response for notification rules
+#[derive(Debug, Clone, Deserialize, Serialize, ToSchema)]
+pub struct PaginatedNotificationRulesResponse {
+    pub items: Vec<NotificationRuleResponse>,
+    pub total: u64,
+    pub per_page: u64,
+    pub current_page: u64,
+    pub last_page: u64,
+}
+
+impl From<PaginatedResponse<NotificationRuleResponse>> for PaginatedNotificationRulesResponse {
+    fn from(r: PaginatedResponse<NotificationRuleResponse>) -> Self {
+        Self {
+            items: r.items,
+            total: r.total,
+            per_page: r.per_page,
+            current_page: r.current_page,
+            last_page: r.last_page,
+        }
+    }
+}
+
+/// One call made for a delivery (webhooks retry; other channels call once).
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize, ToSchema)]
+pub struct DeliveryAttempt {
+    /// RFC 3339 timestamp of the call.
+    pub at: String,
+    /// HTTP status answered, if any.
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub status: Option<u16>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub error: Option<String>,
+    pub duration_ms: u64,
+}
+
+/// One delivery of a rule, with every call it took.
+#[derive(Debug, Clone, Serialize, Deserialize, ToSchema)]
+pub struct NotificationDeliveryResponse {
+    pub id: u64,
+    pub rule_id: u64,
+    pub trigger: NotificationTrigger,
+    pub channel: NotificationChannel,
+    pub event_id: Option<u64>,
+    pub monitor_id: Option<u32>,
+    pub storage_id: Option<u16>,
+    pub status: DeliveryStatus,
+    pub attempts: u32,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub error: Option<String>,
+    /// HTTP status of the last webhook call.
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub response_status: Option<u16>,
+    pub attempt_log: Vec<DeliveryAttempt>,
+    /// RFC 3339 timestamp.
+    pub created_at: String,
+}
+
+impl From<notification_deliveries::Model> for NotificationDeliveryResponse {
+    fn from(m: notification_deliveries::Model) -> Self {
+        Self {
+            id: m.id,
+            rule_id: m.rule_id,
+            trigger: m.trigger,
+            channel: m.channel,
+            event_id: m.event_id,
+            monitor_id: m.monitor_id,
+            storage_id: m.storage_id,
+            status: m.status,
+            attempts: m.attempts,
+            error: m.error,
+            response_status: m.response_status,
+            attempt_log: m
+                .attempt_log_json
+                .and_then(|j| serde_json::from_str(&j).ok())
+                .unwrap_or_default(),
+            created_at: m.created_at.and_utc().to_rfc3339(),
+        }
+    }
+}
+
+/// Paginated response for a rule's deliveries
+#[derive(Debug, Clone, Deserialize, Serialize, ToSchema)]
+pub struct PaginatedNotificationDeliveriesResponse {
+    pub items: Vec<NotificationDeliveryResponse>,
+    pub total: u64,
+    pub per_page: u64,
+    pub current_page: u64,
+    pub last_page: u64,
+}
+
+impl From<PaginatedResponse<NotificationDeliveryResponse>>
+    for PaginatedNotificationDeliveriesResponse
+{
+    fn from(r: PaginatedResponse<NotificationDeliveryResponse>) -> Self {
+        Self {
+            items: r.items,
+            total: r.total,
+            per_page: r.per_page,
+            current_page: r.current_page,
+            last_page: r.last_page,
+        }
+    }
+}
@@ -3,7 +3,10 @@
 use serde::{Deserialize, Serialize};
 use utoipa::ToSchema;
 
+use crate::dto::request::ptz::{PtzTourStep, PtzTourWindow};
+use crate::entity::ptz_tours;
 use crate::ptz::capabilities::PtzCapabilities;
+use crate::ptz::lease::ControlLease;
 use crate::ptz::registry::ProtocolInfo;
 
 /// Response for PTZ command execution
@@ -53,6 +56,14 @@ pub struct PtzStatusResponse {
     /// Current position (if available)
     #[serde(skip_serializing_if = "Option::is_none")]
     pub position: Option<PtzPositionResponse>,
+
+    /// The tour driving the camera (if one is running)
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub tour: Option<PtzTourStatusResponse>,
+
+    /// The user holding the camera (if anyone does)
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub control: Option<PtzControlLeaseResponse>,
 }
 
 /// Current PTZ position
@@ -127,3 +138,93 @@ pub struct PtzCapabilitiesResponse {
     #[serde(flatten)]
     pub capabilities: PtzCapabilities,
 }
+
+/// A preset tour
+#[derive(Debug, Clone, Serialize, Deserialize, ToSchema)]
+pub struct PtzTourResponse {
+    pub id: u64,
+    pub monitor_id: u32,
+    pub name: String,
+    pub enabled: bool,
+    pub steps: Vec<PtzTourStep>,
+    /// Weekly windows the tour runs in by itself; absent when it only runs
+    /// on demand
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub schedule: Option<Vec<PtzTourWindow>>,
+    /// RFC 3339 timestamps
+    pub created_at: String,
+    pub updated_at: String,
+}
+
+impl From<ptz_tours::Model> for PtzTourResponse {
+    fn from(m: ptz_tours::Model) -> Self {
+        // Both documents are validated on write; an unparseable one can only
+        // come from a hand-edited row and is shown as empty.
+        Self {
+            id: m.id,
+            monitor_id: m.monitor_id,
+            name: m.name,
+            enabled: m.enabled,
+            steps: serde_json::from_str(&m.steps_json).unwrap_or_default(),
+            schedule: m.schedule_json.and_then(|s| serde_json::from_str(&s).ok()),
+            created_at: m.created_at.and_utc().to_rfc3339(),
+            updated_at: m.updated_at.and_utc().to_rfc3339(),
+        }
+    }
+}
+
+/// What started a running tour
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize, ToSchema)]
+#[serde(rename_all = "lowercase")]
+pub enum PtzTourTrigger {
+    /// `POST .../tours/{tour_id}/start`
+    Manual,
+    /// One of the tour's weekly windows opened
+    Schedule,
+}
+
+/// Progress of the tour driving a camera
+#[derive(Debug, Clone, Serialize, Deserialize, ToSchema)]
+pub struct PtzTourStatusResponse {
+    pub tour_id: u64,
+    pub name: String,
+    pub started_by: PtzTourTrigger,
+    /// Index of the current step (0-based)
+    pub step: usize,
+    pub step_count: usize,
+    /// Preset of the current step
+    pub preset_id: u32,
+    /// Whether the tour is waiting for a user to let go of the controls
+    pub paused: bool,
+    /// Seconds until the tour moves on; absent while paused or before the
+    /// current preset has been sent
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub next_step_in_seconds: Option<u64>,
+}
+
+/// The user holding a camera's controls
+#[derive(Debug, Clone, Serialize, Deserialize, ToSchema)]
+pub struct PtzControlLeaseResponse {
+    pub user_id: u32,
+    pub username: String,
+    /// 1 for users with `System: Edit`, who can take control from others; 0
+    /// for everyone else
+    pub priority: u8,
+    /// Seconds since the holder took control
+    pub held_for_seconds: u64,
+    /// Seconds until the lease lapses, unless a command renews it
+    pub expires_in_seconds: u64,
+}
+
+impl From<&ControlLease> for PtzControlLeaseResponse {
+    fn from(lease: &ControlLease) -> Self {
+        let now = std::time::Instant::now();
+        Self {
+            user_id: lease.holder.user_id,
+            username: lease.holder.username.clone(),
+            priority: lease.holder.priority,
+            held_for_seconds: now.saturating_duration_since(lease.acquired_at).as_secs(),
+            expires_in_seconds: lease.remaining(now).as_secs(),
+        }
+    }
+}
@@ -0,0 +1,60 @@
+//! Response DTOs for the retention override endpoints.
+
+use serde::{Deserialize, Serialize};
+use utoipa::ToSchema;
+
+use crate::entity::{monitor_retention, storage_retention};
+
+/// A storage's stored retention overrides. `null` fields inherit from the
+/// global `[retention]` section.
+#[derive(Debug, Clone, Serialize, Deserialize, ToSchema)]
+pub struct StorageRetentionResponse {
+    pub storage_id: u16,
+    pub max_age_days: Option<u32>,
+    pub max_storage_bytes: Option<u64>,
+    pub protect_archived: Option<bool>,
+    pub tier_storage_id: Option<u16>,
+    /// RFC 3339 timestamps.
+    pub created_at: String,
+    pub updated_at: String,
+}
+
+impl From<storage_retention::Model> for StorageRetentionResponse {
+    fn from(m: storage_retention::Model) -> Self {
+        Self {
+            storage_id: m.storage_id,
+            max_age_days: m.max_age_days,
+            max_storage_bytes: m.max_storage_bytes,
+            protect_archived: m.protect_archived,
+            tier_storage_id: m.tier_storage_id,
+            created_at: m.created_at.and_utc().to_rfc3339(),
+            updated_at: m.updated_at.and_utc().to_rfc3339(),
+        }
+    }
+}
+
+/// A monitor's stored retention overrides. `null` fields inherit from the
+/// storage each event is on.
+#[derive(Debug, Clone, Serialize, Deserialize, ToSchema)]
+pub struct MonitorRetentionResponse {
+    pub monitor_id: u32,
+    pub max_age_days: Option<u32>,
+    pub max_storage_bytes: Option<u64>,
+    pub protect_archived: Option<bool>,
+    /// RFC 3339 timestamps.
+    pub created_at: String,
+    pub updated_at: String,
+}
+
+impl From<monitor_retention::Model> for MonitorRetentionResponse {
+    fn from(m: monitor_retention::Model) -> Self {
+        Self {
+            monitor_id: m.monitor_id,
+            max_age_days: m.max_age_days,
+            max_storage_bytes: m.max_storage_bytes,
+            protect_archived: m.protect_archived,
+            created_at: m.created_at.and_utc().to_rfc3339(),
+            updated_at: m.updated_at.and_utc().to_rfc3339(),
+        }
+    }
+}
@@ -0,0 +1,60 @@
+//! Response DTOs for saved event searches (`/api/v3/saved-searches`).
+
+use serde::{Deserialize, Serialize};
+use serde_json::Value;
+use utoipa::ToSchema;
+
+use crate::dto::PaginatedResponse;
+use crate::entity::saved_searches;
+
+/// A saved event search.
+#[derive(Debug, Clone, Serialize, Deserialize, ToSchema)]
+pub struct SavedSearchResponse {
+    pub id: u64,
+    pub user_id: u32,
+    pub name: String,
+    /// The stored filter AST (`FilterQuery`).
+    #[schema(value_type = Object)]
+    pub filter: Value,
+    /// RFC 3339 timestamps.
+    pub created_at: String,
+    pub updated_at: String,
+}
+
+impl From<saved_searches::Model> for SavedSearchResponse {
+    fn from(m: saved_searches::Model) -> Self {
+        // The AST is validated on write, so it parses; fall back to Null only
+        // defensively (e.g. a hand-edited DB row).
+        let filter = serde_json::from_str(&m.ast_json).unwrap_or(Value::Null);
+        Self {
+            id: m.id,
+            user_id: m.user_id,
+            name: m.name,
+            filter,
+            created_at: m.created_at.and_utc().to_rfc3339(),
+            updated_at: m.updated_at.and_utc().to_rfc3339(),
+        }
+    }
+}
+
+/// Paginated response for saved searches
+#[derive(Debug, Clone, Deserialize, Serialize, ToSchema)]
+pub struct PaginatedSavedSearchesResponse {
+    pub items: Vec<SavedSearchResponse>,
+    pub total: u64,
+    pub per_page: u64,
+    pub current_page: u64,
+    pub last_page: u64,
+}
+
+impl From<PaginatedResponse<SavedSearchResponse>> for PaginatedSavedSearchesResponse {
+    fn from(r: PaginatedResponse<SavedSearchResponse>) -> Self {
+        Self {
+            items: r.items,
+            total: r.total,
+            per_page: r.per_page,
+            current_page: r.current_page,
+            last_page: r.last_page,
+        }
+    }
+}
@@ -1,3 +1,4 @@
+use crate::dto::wrappers::DateTimeWrapper;
 use crate::dto::PaginatedResponse;
 use serde::{Deserialize, Serialize};
 use utoipa::ToSchema;
@@ -58,3 +59,88 @@ impl From<PaginatedResponse<StorageResponse>> for PaginatedStorageResponse {
         }
     }
 }
+
+/// Usage and limits of one storage, for `GET /api/v3/storage/{id}/health`.
+#[derive(Debug, Clone, Serialize, Deserialize, ToSchema)]
+pub struct StorageHealthResponse {
+    pub storage_id: u16,
+    /// Sum of `Events.DiskSpace` on this storage, archived events included.
+    pub used_bytes: u64,
+    pub archived_bytes: u64,
+    pub events: u64,
+    /// Size of the filesystem holding the storage; `null` when it can't be
+    /// read (a remote storage, or the path is missing).
+    pub capacity_bytes: Option<u64>,
+    pub free_bytes: Option<u64>,
+    pub free_pct: Option<f64>,
+    /// The retention free-space floor, in percent.
+    pub min_free_pct: f64,
+    /// The effective byte quota for this storage; `null` when there is none.
+    pub quota_bytes: Option<u64>,
+    pub oldest_event_ts: Option<DateTimeWrapper>,
+    /// Below the free-space floor or over the quota.
+    pub full: bool,
+    /// Per-monitor breakdown, by monitor id.
+    pub monitors: Vec<StorageMonitorUsage>,
+}
+
+/// One monitor's share of a storage.
+#[derive(Debug, Clone, Serialize, Deserialize, ToSchema)]
+pub struct StorageMonitorUsage {
+    pub monitor_id: u32,
+    pub events: u64,
+    pub bytes: u64,
+    pub archived_bytes: u64,
+    pub oldest_event_ts: Option<DateTimeWrapper>,
+}
+
+/// What `POST /api/v3/storage/{id}:reap` deleted or moved — or, for a dry
+/// run, would have.
+#[derive(Debug, Clone, Serialize, Deserialize, ToSchema)]
+pub struct StorageReapResponse {
+    pub storage_id: u16,
+    pub dry_run: bool,
+    pub deleted: usize,
+    pub moved: usize,
+    /// Bytes freed on this storage.
+    pub reclaimed_bytes: u64,
+    pub events: Vec<ReapedEventResponse>,
+}
+
+/// One event in a reap report.
+#[derive(Debug, Clone, Serialize, Deserialize, ToSchema)]
+pub struct ReapedEventResponse {
+    pub event_id: u64,
+    pub monitor_id: u32,
+    pub bytes: u64,
+    pub start_date_time: Option<DateTimeWrapper>,
+    /// `delete` or `move`.
+    #[schema(example = "delete")]
+    pub action: String,
+    /// The tier storage a `move` went to.
+    pub moved_to: Option<u16>,
+    /// The limits the event breached: `min_free_pct`, `max_storage_bytes`,
+    /// `monitor_max_storage_bytes`, `max_age_days`.
+    pub reasons: Vec<String>,
+}
+
+impl From<crate::service::retention::ReapedEvent> for ReapedEventResponse {
+    fn from(e: crate::service::retention::ReapedEvent) -> Self {
+        Self {
+            event_id: e.event_id,
+            monitor_id: e.monitor_id,
+            bytes: e.bytes,
+            start_date_time: e
+                .start
+                .map(|ndt| DateTimeWrapper(crate::util::naive_local_to_utc(ndt))),
+            action: if e.moved_to.is_some() {
+                "move"
+            } else {
+                "delete"
+            }
+            .to_string(),
+            moved_to: e.moved_to,
+            reasons: e.reasons.into_iter().map(String::from).collect(),
+        }
+    }
+}
@@ -0,0 +1,50 @@
+//! Response DTOs for `GET /api/v3/monitors/timeline`.
+
+use serde::{Deserialize, Serialize};
+use utoipa::ToSchema;
+
+use crate::dto::wrappers::DateTimeWrapper;
+
+/// Event density per monitor over a time range, in fixed-width buckets.
+#[derive(Debug, Clone, Serialize, Deserialize, ToSchema)]
+pub struct TimelineResponse {
+    pub from: DateTimeWrapper,
+    pub to: DateTimeWrapper,
+    pub bucket_seconds: u64,
+    /// Number of buckets from `from` to `to`; the last may be short.
+    pub bucket_count: u64,
+    /// Where the events were read from: the `Events_Hour`, `Events_Day`,
+    /// `Events_Week` or `Events_Month` rollup when the range lies inside it,
+    /// otherwise `Events`.
+    #[schema(example = "Events_Day")]
+    pub source: String,
+    /// Every monitor the caller may view, in id order.
+    pub monitors: Vec<MonitorTimeline>,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize, ToSchema)]
+pub struct MonitorTimeline {
+    pub monitor_id: u32,
+    /// Buckets with at least one event, in order. Missing buckets are empty.
+    pub buckets: Vec<TimelineBucket>,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize, ToSchema)]
+pub struct TimelineBucket {
+    /// Position from `from`, counting from 0.
+    pub index: u64,
+    pub start: DateTimeWrapper,
+    /// Events overlapping the bucket. An event that spans buckets counts in
+    /// each of them.
+    pub event_count: u32,
+    /// Highest `MaxScore` among those events.
+    pub max_score: u16,
+    /// Seconds of alarm in the bucket, estimated from each event's share of
+    /// alarm frames and spread evenly over its length.
+    pub alarm_seconds: f64,
+    /// Seconds of the bucket covered by recordings.
+    pub recorded_seconds: f64,
+    /// The overlapping events, oldest first; at most 100, so compare with
+    /// `event_count`.
+    pub event_ids: Vec<u64>,
+}
@@ -81,6 +81,11 @@ pub struct Model {
         nullable
     )]
     pub longitude: Option<Decimal>,
+    /// Soft-delete tombstone (zm-api-owned column, see the
+    /// `m20261018_000007_add_event_deleted_at` migration). `None` for a live
+    /// event.
+    #[sea_orm(column_name = "deleted_at")]
+    pub deleted_at: Option<DateTime>,
 }
 
 #[derive(Copy, Clone, Debug, EnumIter, DeriveRelation)]
@@ -0,0 +1,49 @@
+//! zm-api-owned `jobs` table — durable state for background jobs.
+//!
+//! Unlike the rest of `src/entity/`, this is **not** generated from ZoneMinder's
+//! schema: it is a zm-api-owned table created by the migration in
+//! `src/migration/`. One row per job records its parameters, a resume cursor,
+//! progress counters and outcome; clients poll it via `GET /api/v3/jobs/{id}`.
+//! Columns are snake_case (our own naming).
+
+use super::sea_orm_active_enums::{JobKind, JobStatus};
+use sea_orm::entity::prelude::*;
+
+#[derive(Clone, Debug, PartialEq, Eq, DeriveEntityModel)]
+#[sea_orm(table_name = "jobs")]
+pub struct Model {
+    #[sea_orm(primary_key)]
+    pub id: u64,
+    pub kind: JobKind,
+    pub status: JobStatus,
+    /// Submitting user; logical FK to `Users.Id`. `None` for system jobs.
+    pub user_id: Option<u32>,
+    /// The event a media job (transcode/export/move/copy) acts on; logical FK
+    /// to `Events.Id`. `None` for jobs over many events.
+    pub event_id: Option<u64>,
+    /// Kind-specific parameters, captured at submission.
+    #[sea_orm(column_type = "Text")]
+    pub params_json: String,
+    /// Last processed key. A resumed job continues strictly after it.
+    pub cursor: Option<u64>,
+    /// Items the job expects to visit, when known up front.
+    pub total: Option<u64>,
+    pub processed: u64,
+    /// Items the job changed (or, for a dry run, would change).
+    pub affected: u64,
+    pub failed: u64,
+    /// Kind-specific result document (e.g. a dry run's sample of matches).
+    #[sea_orm(column_type = "Text", nullable)]
+    pub result_json: Option<String>,
+    #[sea_orm(column_type = "Text", nullable)]
+    pub error: Option<String>,
+    pub created_at: DateTime,
+    pub started_at: Option<DateTime>,
+    pub finished_at: Option<DateTime>,
+    pub updated_at: DateTime,
+}
+
+#[derive(Copy, Clone, Debug, EnumIter, DeriveRelation)]
+pub enum Relation {}
+
+impl ActiveModelBehavior for ActiveModel {}
@@ -30,18 +30,25 @@ pub mod frames;
 pub mod groups;
 pub mod groups_monitors;
 pub mod groups_permissions;
+pub mod jobs;
 pub mod logs;
 pub mod manufacturers;
 pub mod models;
 pub mod monitor_pipeline;
 pub mod monitor_presets;
+pub mod monitor_retention;
 pub mod monitor_status;
 pub mod monitors;
 pub mod monitors_permissions;
 pub mod montage_layouts;
+pub mod notification_deliveries;
+pub mod notification_rules;
 pub mod object_types;
 pub mod prelude;
+pub mod ptz_tours;
 pub mod reports;
+pub mod rtsp_credentials;
+pub mod saved_searches;
 pub mod sea_orm_active_enums;
 pub mod server_stats;
 pub mod servers;
@@ -51,6 +58,7 @@ pub mod snapshots_events;
 pub mod states;
 pub mod stats;
 pub mod storage;
+pub mod storage_retention;
 pub mod tags;
 pub mod triggers_x10;
 pub mod user_preferences;
@@ -0,0 +1,49 @@
+//! zm-api-owned `monitor_retention` table — per-monitor retention overrides.
+//!
+//! Unlike the rest of `src/entity/`, this is **not** generated from ZoneMinder's
+//! schema: it is a zm-api-owned table created by the migration in
+//! `src/migration/`. One row per monitor that overrides its storage's
+//! retention; a NULL column inherits from the storage the event is on. Columns
+//! are snake_case (our own naming).
+
+use sea_orm::entity::prelude::*;
+
+#[derive(Clone, Debug, PartialEq, Eq, DeriveEntityModel)]
+#[sea_orm(table_name = "monitor_retention")]
+pub struct Model {
+    /// Logical FK to `Monitors.Id`; one policy per monitor.
+    #[sea_orm(primary_key, auto_increment = false)]
+    pub monitor_id: u32,
+    /// This monitor's events older than this expire; `0` disables the age
+    /// limit for it.
+    pub max_age_days: Option<u32>,
+    /// Cap on the bytes this monitor's events may hold on each storage. Not
+    /// inherited — NULL or `0` leaves only the storage-wide quota.
+    pub max_storage_bytes: Option<u64>,
+    /// Whether this monitor's archived events are exempt.
+    pub protect_archived: Option<bool>,
+    pub created_at: DateTime,
+    pub updated_at: DateTime,
+}
+
+/// `monitor_id` is a *logical* FK to `Monitors.Id`. No hard DB constraint is
+/// created — zm-api does not own ZoneMinder's `Monitors` table.
+#[derive(Copy, Clone, Debug, EnumIter, DeriveRelation)]
+pub enum Relation {
+    #[sea_orm(
+        belongs_to = "super::monitors::Entity",
+        from = "Column::MonitorId",
+        to = "super::monitors::Column::Id",
+        on_update = "NoAction",
+        on_delete = "NoAction"
+    )]
+    Monitors,
+}
+
+impl Related<super::monitors::Entity> for Entity {
+    fn to() -> RelationDef {
+        Relation::Monitors.def()
+    }
+}
+
+impl ActiveModelBehavior for ActiveModel {}
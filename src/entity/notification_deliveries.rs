@@ -0,0 +1,45 @@
+//! zm-api-owned `notification_deliveries` table — one row per delivery of a
+//! notification rule, with every call it took.
+//!
+//! Unlike the rest of `src/entity/`, this is **not** generated from ZoneMinder's
+//! schema: it is a zm-api-owned table created by the migration in
+//! `src/migration/`. It replaces the per-event `Events.Emailed`/`Messaged`
+//! flags with per-rule, per-channel history. Columns are snake_case (our own
+//! naming).
+
+use super::sea_orm_active_enums::{DeliveryStatus, NotificationChannel, NotificationTrigger};
+use sea_orm::entity::prelude::*;
+
+#[derive(Clone, Debug, PartialEq, Eq, DeriveEntityModel)]
+#[sea_orm(table_name = "notification_deliveries")]
+pub struct Model {
+    #[sea_orm(primary_key)]
+    pub id: u64,
+    /// Logical FK to `notification_rules.id`.
+    pub rule_id: u64,
+    pub trigger: NotificationTrigger,
+    pub channel: NotificationChannel,
+    /// The event notified about; `None` for `storage_health`.
+    pub event_id: Option<u64>,
+    pub monitor_id: Option<u32>,
+    pub storage_id: Option<u16>,
+    /// Dedupe group, e.g. `monitor:3` or `storage:1`.
+    pub dedupe_key: String,
+    pub status: DeliveryStatus,
+    pub attempts: u32,
+    /// Last error, when `status` is `failed`.
+    #[sea_orm(column_type = "Text", nullable)]
+    pub error: Option<String>,
+    /// HTTP status of the last webhook call.
+    pub response_status: Option<u16>,
+    /// Every call made (`DeliveryAttempt` JSON array) — the webhook audit
+    /// trail.
+    #[sea_orm(column_type = "Text", nullable)]
+    pub attempt_log_json: Option<String>,
+    pub created_at: DateTime,
+}
+
+#[derive(Copy, Clone, Debug, EnumIter, DeriveRelation)]
+pub enum Relation {}
+
+impl ActiveModelBehavior for ActiveModel {}
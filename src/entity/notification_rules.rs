@@ -0,0 +1,64 @@
+//! zm-api-owned `notification_rules` table — per-user notification rules.
+//!
+//! Unlike the rest of `src/entity/`, this is **not** generated from ZoneMinder's
+//! schema: it is a zm-api-owned table created by the migration in
+//! `src/migration/`. Each row says when to notify (trigger, optional event
+//! predicate, threshold), how (channel + target) and how often (dedupe window).
+//! Columns are snake_case (our own naming).
+
+use super::sea_orm_active_enums::{NotificationChannel, NotificationTrigger};
+use sea_orm::entity::prelude::*;
+
+#[derive(Clone, Debug, PartialEq, Eq, DeriveEntityModel)]
+#[sea_orm(table_name = "notification_rules")]
+pub struct Model {
+    #[sea_orm(primary_key)]
+    pub id: u64,
+    /// Owner; logical FK to `Users.Id`. The rule only ever sees what this user
+    /// may see.
+    pub user_id: u32,
+    pub name: String,
+    pub enabled: bool,
+    pub trigger: NotificationTrigger,
+    /// Filter AST (`FilterQuery`) the event must match; `None` matches all.
+    #[sea_orm(column_type = "Text", nullable)]
+    pub predicate_json: Option<String>,
+    /// Minimum score for `score_threshold`; free-space floor (percent) for
+    /// `storage_health`. Unused by the other triggers.
+    pub threshold: Option<u32>,
+    pub channel: NotificationChannel,
+    /// Email address, webhook URL or MQTT topic, per channel.
+    pub target: String,
+    /// HMAC-SHA256 key webhook calls are signed with. Never returned by the
+    /// API after it is first issued.
+    pub secret: Option<String>,
+    /// Optional `{"subject": .., "body": ..}` message template.
+    #[sea_orm(column_type = "Text", nullable)]
+    pub template_json: Option<String>,
+    /// Suppress repeats for the same monitor (or storage) for this long.
+    pub dedupe_window_seconds: u32,
+    pub created_at: DateTime,
+    pub updated_at: DateTime,
+}
+
+/// `user_id` is a *logical* FK to `Users.Id`. No hard DB constraint is created —
+/// zm-api does not own ZoneMinder's `Users` table.
+#[derive(Copy, Clone, Debug, EnumIter, DeriveRelation)]
+pub enum Relation {
+    #[sea_orm(
+        belongs_to = "super::users::Entity",
+        from = "Column::UserId",
+        to = "super::users::Column::Id",
+        on_update = "NoAction",
+        on_delete = "NoAction"
+    )]
+    Users,
+}
+
+impl Related<super::users::Entity> for Entity {
+    fn to() -> RelationDef {
+        Relation::Users.def()
+    }
+}
+
+impl ActiveModelBehavior for ActiveModel {}
@@ -19,17 +19,24 @@ pub use super::frames::Entity as Frames;
 pub use super::groups::Entity as Groups;
 pub use super::groups_monitors::Entity as GroupsMonitors;
 pub use super::groups_permissions::Entity as GroupsPermissions;
+pub use super::jobs::Entity as Jobs;
 pub use super::logs::Entity as Logs;
 pub use super::manufacturers::Entity as Manufacturers;
 pub use super::models::Entity as Models;
 pub use super::monitor_pipeline::Entity as MonitorPipeline;
 pub use super::monitor_presets::Entity as MonitorPresets;
+pub use super::monitor_retention::Entity as MonitorRetention;
 pub use super::monitor_status::Entity as MonitorStatus;
 pub use super::monitors::Entity as Monitors;
 pub use super::monitors_permissions::Entity as MonitorsPermissions;
 pub use super::montage_layouts::Entity as MontageLayouts;
+pub use super::notification_deliveries::Entity as NotificationDeliveries;
+pub use super::notification_rules::Entity as NotificationRules;
 pub use super::object_types::Entity as ObjectTypes;
+pub use super::ptz_tours::Entity as PtzTours;
 pub use super::reports::Entity as Reports;
+pub use super::rtsp_credentials::Entity as RtspCredentials;
+pub use super::saved_searches::Entity as SavedSearches;
 pub use super::server_stats::Entity as ServerStats;
 pub use super::servers::Entity as Servers;
 pub use super::sessions::Entity as Sessions;
@@ -38,6 +45,7 @@ pub use super::snapshots_events::Entity as SnapshotsEvents;
 pub use super::states::Entity as States;
 pub use super::stats::Entity as Stats;
 pub use super::storage::Entity as Storage;
+pub use super::storage_retention::Entity as StorageRetention;
 pub use super::tags::Entity as Tags;
 pub use super::triggers_x10::Entity as TriggersX10;
 pub use super::user_preferences::Entity as UserPreferences;
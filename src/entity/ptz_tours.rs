@@ -0,0 +1,53 @@
+//! zm-api-owned `ptz_tours` table — preset tours per PTZ monitor.
+//!
+//! Unlike the rest of `src/entity/`, this is **not** generated from ZoneMinder's
+//! schema: it is a zm-api-owned table created by the migration in
+//! `src/migration/`. Each row is an ordered list of presets with dwell times,
+//! optionally bound to weekly windows it runs in by itself. Columns are
+//! snake_case (our own naming).
+
+use sea_orm::entity::prelude::*;
+
+#[derive(Clone, Debug, PartialEq, Eq, DeriveEntityModel)]
+#[sea_orm(table_name = "ptz_tours")]
+pub struct Model {
+    #[sea_orm(primary_key)]
+    pub id: u64,
+    /// Logical FK to `Monitors.Id`.
+    pub monitor_id: u32,
+    /// Unique per monitor.
+    pub name: String,
+    /// Disabled tours keep their definition but can neither be started nor
+    /// run on their schedule.
+    pub enabled: bool,
+    /// `[PtzTourStep]`, in the order they are visited.
+    #[sea_orm(column_type = "Text")]
+    pub steps_json: String,
+    /// `[PtzTourWindow]` in server local time; `None` runs only on demand.
+    #[sea_orm(column_type = "Text", nullable)]
+    pub schedule_json: Option<String>,
+    pub created_at: DateTime,
+    pub updated_at: DateTime,
+}
+
+/// `monitor_id` is a *logical* FK to `Monitors.Id`. No hard DB constraint is
+/// created — zm-api does not own ZoneMinder's `Monitors` table.
+#[derive(Copy, Clone, Debug, EnumIter, DeriveRelation)]
+pub enum Relation {
+    #[sea_orm(
+        belongs_to = "super::monitors::Entity",
+        from = "Column::MonitorId",
+        to = "super::monitors::Column::Id",
+        on_update = "NoAction",
+        on_delete = "NoAction"
+    )]
+    Monitors,
+}
+
+impl Related<super::monitors::Entity> for Entity {
+    fn to() -> RelationDef {
+        Relation::Monitors.def()
+    }
+}
+
+impl ActiveModelBehavior for ActiveModel {}
@@ -0,0 +1,50 @@
+//! zm-api-owned `rtsp_credentials` table — cached Digest HA1 per user.
+//!
+//! Unlike the rest of `src/entity/`, this is **not** generated from ZoneMinder's
+//! schema: it is a zm-api-owned table created by the migration in
+//! `src/migration/`. One row per user whose password zm-api has seen since the
+//! RTSP server was enabled; the RTSP server verifies Digest responses against
+//! it, as long as the user's `Users.Password` is unchanged. Columns are
+//! snake_case (our own naming).
+
+use sea_orm::entity::prelude::*;
+
+#[derive(Clone, Debug, PartialEq, Eq, DeriveEntityModel)]
+#[sea_orm(table_name = "rtsp_credentials")]
+pub struct Model {
+    /// Logical FK to `Users.Id`; one digest per user.
+    #[sea_orm(primary_key, auto_increment = false)]
+    pub user_id: u32,
+    /// The realm `ha1` was computed for. A row for another realm is stale.
+    pub realm: String,
+    /// `MD5(username:realm:password)` as lower-case hex. Password-equivalent
+    /// for RTSP, so it is never returned by the API.
+    pub ha1: String,
+    /// SHA-256 of the `Users.Password` hash `ha1` was captured against. A
+    /// digest whose fingerprint no longer matches is stale; `None` (captured
+    /// before fingerprints were kept) always is.
+    pub password_fingerprint: Option<String>,
+    pub updated_at: DateTime,
+}
+
+/// `user_id` is a *logical* FK to `Users.Id`. No hard DB constraint is
+/// created — zm-api does not own ZoneMinder's `Users` table.
+#[derive(Copy, Clone, Debug, EnumIter, DeriveRelation)]
+pub enum Relation {
+    #[sea_orm(
+        belongs_to = "super::users::Entity",
+        from = "Column::UserId",
+        to = "super::users::Column::Id",
+        on_update = "NoAction",
+        on_delete = "NoAction"
+    )]
+    Users,
+}
+
+impl Related<super::users::Entity> for Entity {
+    fn to() -> RelationDef {
+        Relation::Users.def()
+    }
+}
+
+impl ActiveModelBehavior for ActiveModel {}
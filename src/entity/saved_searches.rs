@@ -0,0 +1,48 @@
+//! zm-api-owned `saved_searches` table — named, reusable event searches.
+//!
+//! Unlike the rest of `src/entity/`, this is **not** generated from ZoneMinder's
+//! schema: it is a zm-api-owned table created by the migration in
+//! `src/migration/`. One row holds a user's named filter AST (the typed
+//! `FilterQuery` tree) as JSON. It is the search half of a ZoneMinder `Filters`
+//! row with none of the auto-action or scheduling columns — acting on the
+//! matches is an explicit bulk job. Columns are snake_case (our own naming).
+
+use sea_orm::entity::prelude::*;
+
+#[derive(Clone, Debug, PartialEq, Eq, DeriveEntityModel)]
+#[sea_orm(table_name = "saved_searches")]
+pub struct Model {
+    #[sea_orm(primary_key)]
+    pub id: u64,
+    /// Owner; logical FK to `Users.Id`.
+    pub user_id: u32,
+    pub name: String,
+    /// The `FilterQuery` AST as JSON. Validated and compiled on write, so a
+    /// stored search always compiles to a query.
+    #[sea_orm(column_type = "Text")]
+    pub ast_json: String,
+    pub created_at: DateTime,
+    pub updated_at: DateTime,
+}
+
+/// `user_id` is a *logical* FK to `Users.Id`. No hard DB constraint is created —
+/// zm-api does not own ZoneMinder's `Users` table.
+#[derive(Copy, Clone, Debug, EnumIter, DeriveRelation)]
+pub enum Relation {
+    #[sea_orm(
+        belongs_to = "super::users::Entity",
+        from = "Column::UserId",
+        to = "super::users::Column::Id",
+        on_update = "NoAction",
+        on_delete = "NoAction"
+    )]
+    Users,
+}
+
+impl Related<super::users::Entity> for Entity {
+    fn to() -> RelationDef {
+        Relation::Users.def()
+    }
+}
+
+impl ActiveModelBehavior for ActiveModel {}
@@ -640,6 +640,177 @@ pub enum SynopsisStatus {
     Failed,
 }
 
+/// What a row of the zm-api-owned `jobs` table does (not part of ZoneMinder's
+/// schema). Selects how `params_json` is read and which runner resumes it.
+#[derive(
+    Debug,
+    Clone,
+    Copy,
+    PartialEq,
+    Eq,
+    EnumIter,
+    DeriveActiveEnum,
+    serde::Serialize,
+    serde::Deserialize,
+    ToSchema,
+)]
+#[sea_orm(rs_type = "String", db_type = "String(StringLen::N(32))")]
+#[serde(rename_all = "snake_case")]
+pub enum JobKind {
+    /// Archive / unarchive / delete every event matching a filter AST.
+    #[sea_orm(string_value = "bulk_events")]
+    #[serde(rename = "bulk_events", alias = "BulkEvents")]
+    BulkEvents,
+    /// Re-encode one event's recording to H.264 mp4.
+    #[sea_orm(string_value = "event_transcode")]
+    #[serde(rename = "event_transcode", alias = "EventTranscode")]
+    EventTranscode,
+    /// Copy one event's media out to the export directory.
+    #[sea_orm(string_value = "event_export")]
+    #[serde(rename = "event_export", alias = "EventExport")]
+    EventExport,
+    /// Relocate one event's media to another `Storage` row.
+    #[sea_orm(string_value = "event_move")]
+    #[serde(rename = "event_move", alias = "EventMove")]
+    EventMove,
+    /// Copy one event's media to another `Storage` row as its secondary copy.
+    #[sea_orm(string_value = "event_copy")]
+    #[serde(rename = "event_copy", alias = "EventCopy")]
+    EventCopy,
+    /// Bundle many events' media and metadata into one zip or tar archive.
+    #[sea_orm(string_value = "events_export")]
+    #[serde(rename = "events_export", alias = "EventsExport")]
+    EventsExport,
+}
+
+/// Lifecycle of a row in the zm-api-owned `jobs` table. Stored as a short
+/// portable string column, like [`SynopsisStatus`].
+#[derive(
+    Debug,
+    Clone,
+    Copy,
+    PartialEq,
+    Eq,
+    EnumIter,
+    DeriveActiveEnum,
+    serde::Serialize,
+    serde::Deserialize,
+    ToSchema,
+)]
+#[sea_orm(rs_type = "String", db_type = "String(StringLen::N(16))")]
+#[serde(rename_all = "lowercase")]
+pub enum JobStatus {
+    /// Accepted; no batch has run yet.
+    #[sea_orm(string_value = "queued")]
+    #[serde(rename = "queued", alias = "Queued")]
+    Queued,
+    /// Working through batches. Also the state a job is left in by a restart,
+    /// from which it is resumed at its cursor.
+    #[sea_orm(string_value = "running")]
+    #[serde(rename = "running", alias = "Running")]
+    Running,
+    /// Visited every match. Per-item failures are counted, not fatal.
+    #[sea_orm(string_value = "succeeded")]
+    #[serde(rename = "succeeded", alias = "Succeeded")]
+    Succeeded,
+    /// Stopped early; `error` says why.
+    #[sea_orm(string_value = "failed")]
+    #[serde(rename = "failed", alias = "Failed")]
+    Failed,
+}
+
+/// When a notification rule fires. Stored in the zm-api-owned
+/// `notification_rules` / `notification_deliveries` tables.
+#[derive(
+    Debug,
+    Clone,
+    Copy,
+    PartialEq,
+    Eq,
+    Hash,
+    EnumIter,
+    DeriveActiveEnum,
+    serde::Serialize,
+    serde::Deserialize,
+    ToSchema,
+)]
+#[sea_orm(rs_type = "String", db_type = "String(StringLen::N(32))")]
+#[serde(rename_all = "snake_case")]
+pub enum NotificationTrigger {
+    /// An `Events` row was opened.
+    #[sea_orm(string_value = "event_start")]
+    #[serde(rename = "event_start", alias = "EventStart")]
+    EventStart,
+    /// An event was closed with an end time.
+    #[sea_orm(string_value = "event_end")]
+    #[serde(rename = "event_end", alias = "EventEnd")]
+    EventEnd,
+    /// A frame of an open event scored at least the rule's threshold. Fires
+    /// at most once per event.
+    #[sea_orm(string_value = "score_threshold")]
+    #[serde(rename = "score_threshold", alias = "ScoreThreshold")]
+    ScoreThreshold,
+    /// A Storage's free space fell below the rule's threshold percent.
+    #[sea_orm(string_value = "storage_health")]
+    #[serde(rename = "storage_health", alias = "StorageHealth")]
+    StorageHealth,
+}
+
+/// How a notification rule delivers.
+#[derive(
+    Debug,
+    Clone,
+    Copy,
+    PartialEq,
+    Eq,
+    EnumIter,
+    DeriveActiveEnum,
+    serde::Serialize,
+    serde::Deserialize,
+    ToSchema,
+)]
+#[sea_orm(rs_type = "String", db_type = "String(StringLen::N(16))")]
+#[serde(rename_all = "lowercase")]
+pub enum NotificationChannel {
+    /// Email through the configured `[notifications.smtp]` relay.
+    #[sea_orm(string_value = "smtp")]
+    #[serde(rename = "smtp", alias = "Smtp")]
+    Smtp,
+    /// JSON `POST` to the rule's URL.
+    #[sea_orm(string_value = "webhook")]
+    #[serde(rename = "webhook", alias = "Webhook")]
+    Webhook,
+    /// JSON publish to the rule's topic on the `[notifications.mqtt]` broker.
+    #[sea_orm(string_value = "mqtt")]
+    #[serde(rename = "mqtt", alias = "Mqtt")]
+    Mqtt,
+}
+
+/// Outcome of one row in `notification_deliveries`.
+#[derive(
+    Debug,
+    Clone,
+    Copy,
+    PartialEq,
+    Eq,
+    EnumIter,
+    DeriveActiveEnum,
+    serde::Serialize,
+    serde::Deserialize,
+    ToSchema,
+)]
+#[sea_orm(rs_type = "String", db_type = "String(StringLen::N(16))")]
+#[serde(rename_all = "lowercase")]
+pub enum DeliveryStatus {
+    #[sea_orm(string_value = "sent")]
+    #[serde(rename = "sent", alias = "Sent")]
+    Sent,
+    /// Not delivered; `error` says why.
+    #[sea_orm(string_value = "failed")]
+    #[serde(rename = "failed", alias = "Failed")]
+    Failed,
+}
+
 #[derive(
     Debug,
     Clone,
@@ -744,6 +915,11 @@ mod tests {
             OutputContainer,
             StorageType,
             SynopsisStatus,
+            JobKind,
+            JobStatus,
+            NotificationTrigger,
+            NotificationChannel,
+            DeliveryStatus,
             Framework,
         );
     }
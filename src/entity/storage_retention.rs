@@ -0,0 +1,50 @@
+//! zm-api-owned `storage_retention` table — per-storage retention overrides.
+//!
+//! Unlike the rest of `src/entity/`, this is **not** generated from ZoneMinder's
+//! schema: it is a zm-api-owned table created by the migration in
+//! `src/migration/`. One row per storage that overrides any of the global
+//! `[retention]` knobs; a NULL column inherits the global value. Columns are
+//! snake_case (our own naming).
+
+use sea_orm::entity::prelude::*;
+
+#[derive(Clone, Debug, PartialEq, Eq, DeriveEntityModel)]
+#[sea_orm(table_name = "storage_retention")]
+pub struct Model {
+    /// Logical FK to `Storage.Id`; one policy per storage.
+    #[sea_orm(primary_key, auto_increment = false)]
+    pub storage_id: u16,
+    /// Events older than this expire; `0` disables the age limit here.
+    pub max_age_days: Option<u32>,
+    /// Cap on the bytes this storage may hold; `0` disables the quota here.
+    pub max_storage_bytes: Option<u64>,
+    /// Whether archived events are exempt. Inherits `true`.
+    pub protect_archived: Option<bool>,
+    /// Expiring events are moved to this storage instead of deleted; logical
+    /// FK to `Storage.Id`.
+    pub tier_storage_id: Option<u16>,
+    pub created_at: DateTime,
+    pub updated_at: DateTime,
+}
+
+/// `storage_id` is a *logical* FK to `Storage.Id`. No hard DB constraint is
+/// created — zm-api does not own ZoneMinder's `Storage` table.
+#[derive(Copy, Clone, Debug, EnumIter, DeriveRelation)]
+pub enum Relation {
+    #[sea_orm(
+        belongs_to = "super::storage::Entity",
+        from = "Column::StorageId",
+        to = "super::storage::Column::Id",
+        on_update = "NoAction",
+        on_delete = "NoAction"
+    )]
+    Storage,
+}
+
+impl Related<super::storage::Entity> for Entity {
+    fn to() -> RelationDef {
+        Relation::Storage.def()
+    }
+}
+
+impl ActiveModelBehavior for ActiveModel {}
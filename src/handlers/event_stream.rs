@@ -0,0 +1,291 @@
+//! Cross-monitor event firehose: `GET /api/v3/events/stream` (SSE) and
+//! `GET /api/v3/events/stream/ws` (WebSocket).
+//!
+//! Both relay the [`EventFeed`](crate::service::event_feed::EventFeed) —
+//! event open/close, alarm scores, capture faults and PTZ control hand-offs
+//! from every monitor — filtered to the monitors the caller may view. The
+//! caller's `MonitorScope` is resolved once at connect, so a permission change
+//! applies on reconnect.
+
+use std::collections::HashSet;
+use std::convert::Infallible;
+use std::time::Duration;
+
+use axum::{
+    extract::{
+        ws::{Message, WebSocket},
+        Query, State, WebSocketUpgrade,
+    },
+    response::{
+        sse::{Event, KeepAlive, Sse},
+        Response,
+    },
+};
+use futures_util::{SinkExt, Stream, StreamExt};
+use serde::Serialize;
+use tokio::sync::broadcast;
+use tracing::{debug, warn};
+
+use crate::dto::request::event_stream::EventStreamQuery;
+use crate::error::{AppError, AppResponseError, AppResult};
+use crate::server::state::AppState;
+use crate::service::event_feed::{EventNotification, EventNotificationKind};
+use crate::service::monitor_acl::MonitorScope;
+use crate::util::authz::Level;
+
+/// SSE keep-alive comment interval, matching the per-monitor events stream.
+const EVENT_STREAM_KEEPALIVE: Duration = Duration::from_secs(15);
+
+/// Which notifications one subscriber receives: the caller's ACL, narrowed by
+/// the optional `monitors` / `kinds` query filters.
+#[derive(Debug)]
+struct StreamFilter {
+    scope: MonitorScope,
+    monitors: Option<HashSet<u32>>,
+    kinds: Option<HashSet<EventNotificationKind>>,
+}
+
+impl StreamFilter {
+    fn new(scope: MonitorScope, query: &EventStreamQuery) -> AppResult<Self> {
+        let monitors = query
+            .monitors
+            .as_deref()
+            .map(|raw| {
+                split_list(raw)
+                    .map(|part| {
+                        part.parse::<u32>().map_err(|_| {
+                            AppError::BadRequestError(format!("invalid monitor id `{part}`"))
+                        })
+                    })
+                    .collect::<AppResult<HashSet<u32>>>()
+            })
+            .transpose()?;
+        let kinds = query
+            .kinds
+            .as_deref()
+            .map(|raw| {
+                split_list(raw)
+                    .map(|part| {
+                        EventNotificationKind::parse(part).ok_or_else(|| {
+                            AppError::BadRequestError(format!("unknown event kind `{part}`"))
+                        })
+                    })
+                    .collect::<AppResult<HashSet<EventNotificationKind>>>()
+            })
+            .transpose()?;
+        Ok(Self {
+            scope,
+            monitors,
+            kinds,
+        })
+    }
+
+    fn admits(&self, n: &EventNotification) -> bool {
+        self.scope.allows(n.monitor_id, Level::View)
+            && self
+                .monitors
+                .as_ref()
+                .is_none_or(|m| m.contains(&n.monitor_id))
+            && self.kinds.as_ref().is_none_or(|k| k.contains(&n.kind))
+    }
+}
+
+fn split_list(raw: &str) -> impl Iterator<Item = &str> {
+    raw.split(',').map(str::trim).filter(|s| !s.is_empty())
+}
+
+/// Sent when a subscriber fell behind the feed and notifications were dropped.
+/// Sequence gaps cannot signal loss on a filtered stream, so it is explicit.
+#[derive(Debug, Serialize)]
+struct LaggedNotice {
+    kind: &'static str,
+    missed: u64,
+}
+
+impl LaggedNotice {
+    fn new(missed: u64) -> Self {
+        Self {
+            kind: "lagged",
+            missed,
+        }
+    }
+}
+
+/// Stream event activity across all permitted monitors as Server-Sent Events
+///
+/// Each frame is an `EventNotification`; `event:` is its kind and `id:` its
+/// feed sequence. A subscriber that falls behind receives `event: lagged` with
+/// the number of notifications it missed. Nothing is replayed on reconnect.
+///
+/// Accepts the JWT via `?token=` because `EventSource` cannot set headers.
+#[utoipa::path(
+    get,
+    path = "/api/v3/events/stream",
+    operation_id = "streamEvents",
+    tag = "Events",
+    params(
+        ("monitors" = Option<String>, Query, description = "Comma-separated monitor ids (subject to ACL)", example = "1,4"),
+        ("kinds" = Option<String>, Query, description = "Comma-separated notification kinds", example = "event_open,capture_fault"),
+        ("token" = Option<String>, Query, description = "JWT, for EventSource clients")
+    ),
+    responses(
+        (status = 200, description = "`text/event-stream` of `EventNotification` JSON frames; \
+            `event:` is the kind, `id:` the feed sequence",
+            content_type = "text/event-stream", body = EventNotification),
+        (status = 400, description = "Bad request", body = AppResponseError),
+        (status = 401, description = "Unauthorized", body = AppResponseError)
+    ),
+    security(("jwt" = []))
+)]
+pub async fn stream_events(
+    State(state): State<AppState>,
+    scope: MonitorScope,
+    Query(query): Query<EventStreamQuery>,
+) -> AppResult<Sse<impl Stream<Item = Result<Event, Infallible>>>> {
+    let filter = StreamFilter::new(scope, &query)?;
+    let rx = state.event_feed.subscribe();
+
+    let stream = futures_util::stream::unfold((rx, filter), |(mut rx, filter)| async move {
+        loop {
+            match rx.recv().await {
+                Ok(n) if filter.admits(&n) => {
+                    let frame = Event::default()
+                        .event(n.kind.as_str())
+                        .id(n.seq.to_string())
+                        .data(serde_json::to_string(&n).unwrap_or_default());
+                    return Some((Ok(frame), (rx, filter)));
+                }
+                Ok(_) => continue,
+                Err(broadcast::error::RecvError::Lagged(missed)) => {
+                    warn!("Event stream subscriber lagged, {missed} notifications dropped");
+                    let frame = Event::default().event("lagged").data(
+                        serde_json::to_string(&LaggedNotice::new(missed)).unwrap_or_default(),
+                    );
+                    return Some((Ok(frame), (rx, filter)));
+                }
+                Err(broadcast::error::RecvError::Closed) => return None,
+            }
+        }
+    });
+
+    Ok(Sse::new(stream).keep_alive(KeepAlive::new().interval(EVENT_STREAM_KEEPALIVE)))
+}
+
+/// Stream event activity across all permitted monitors over a WebSocket
+///
+/// The same notifications as `GET /api/v3/events/stream`, one JSON text frame
+/// each, plus `{"kind":"lagged","missed":N}` when the client falls behind.
+/// Client messages are ignored apart from close.
+#[utoipa::path(
+    get,
+    path = "/api/v3/events/stream/ws",
+    operation_id = "streamEventsWebSocket",
+    tag = "Events",
+    params(
+        ("monitors" = Option<String>, Query, description = "Comma-separated monitor ids (subject to ACL)", example = "1,4"),
+        ("kinds" = Option<String>, Query, description = "Comma-separated notification kinds", example = "event_open,capture_fault"),
+        ("token" = Option<String>, Query, description = "JWT, for browser WebSocket clients")
+    ),
+    responses(
+        (status = 101, description = "WebSocket upgraded. Frames are `EventNotification` JSON (text).",
+            body = EventNotification),
+        (status = 400, description = "Bad request", body = AppResponseError),
+        (status = 401, description = "Unauthorized", body = AppResponseError)
+    ),
+    security(("jwt" = []))
+)]
+pub async fn stream_events_websocket(
+    State(state): State<AppState>,
+    scope: MonitorScope,
+    Query(query): Query<EventStreamQuery>,
+    ws: WebSocketUpgrade,
+) -> AppResult<Response> {
+    let filter = StreamFilter::new(scope, &query)?;
+    let rx = state.event_feed.subscribe();
+    Ok(ws.on_upgrade(move |socket| relay_events_websocket(socket, rx, filter)))
+}
+
+async fn relay_events_websocket(
+    socket: WebSocket,
+    mut rx: broadcast::Receiver<EventNotification>,
+    filter: StreamFilter,
+) {
+    let (mut ws_sender, mut ws_receiver) = socket.split();
+    loop {
+        let json = tokio::select! {
+            incoming = ws_receiver.next() => match incoming {
+                Some(Ok(Message::Close(_))) | Some(Err(_)) | None => break,
+                Some(Ok(_)) => continue,
+            },
+            received = rx.recv() => match received {
+                Ok(n) if filter.admits(&n) => serde_json::to_string(&n),
+                Ok(_) => continue,
+                Err(broadcast::error::RecvError::Lagged(missed)) => {
+                    warn!("Event stream WebSocket lagged, {missed} notifications dropped");
+                    serde_json::to_string(&LaggedNotice::new(missed))
+                }
+                Err(broadcast::error::RecvError::Closed) => break,
+            },
+        };
+        let Ok(json) = json else { continue };
+        if ws_sender.send(Message::Text(json.into())).await.is_err() {
+            break;
+        }
+    }
+    debug!("Event stream WebSocket closed");
+}
+
+#[cfg(test)]
+mod tests {
+    use std::collections::HashMap;
+
+    use super::*;
+    use crate::service::event_feed::EventNotificationSource;
+
+    fn open(monitor_id: u32) -> EventNotification {
+        EventNotification::event_open(
+            EventNotificationSource::Zmnext,
+            monitor_id,
+            1,
+            None,
+            chrono::Utc::now().naive_utc(),
+        )
+    }
+
+    #[test]
+    fn filter_applies_scope_then_query() {
+        let scope = MonitorScope::Restricted(HashMap::from([(1, Level::View), (2, Level::None)]));
+        let filter = StreamFilter::new(scope, &EventStreamQuery::default()).unwrap();
+        assert!(filter.admits(&open(1)));
+        assert!(!filter.admits(&open(2)), "Level::None is not View");
+        assert!(!filter.admits(&open(3)), "unlisted monitor");
+
+        // Asking for a monitor outside the scope does not widen it.
+        let query = EventStreamQuery {
+            monitors: Some("1, 3".into()),
+            kinds: Some("event_close".into()),
+        };
+        let scope = MonitorScope::Restricted(HashMap::from([(1, Level::View)]));
+        let filter = StreamFilter::new(scope, &query).unwrap();
+        assert!(!filter.admits(&open(1)), "kind filtered out");
+        let mut close = open(1);
+        close.kind = EventNotificationKind::EventClose;
+        assert!(filter.admits(&close));
+        close.monitor_id = 3;
+        assert!(!filter.admits(&close));
+    }
+
+    #[test]
+    fn filter_rejects_malformed_query() {
+        for (monitors, kinds) in [(Some("1,x"), None), (None, Some("event_open,bogus"))] {
+            let query = EventStreamQuery {
+                monitors: monitors.map(Into::into),
+                kinds: kinds.map(Into::into),
+            };
+            assert!(matches!(
+                StreamFilter::new(MonitorScope::All, &query),
+                Err(AppError::BadRequestError(_))
+            ));
+        }
+    }
+}
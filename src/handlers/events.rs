@@ -1,6 +1,8 @@
 use axum::{
+    body::Body,
     extract::{Path, Query, State},
-    http::StatusCode,
+    http::{header, StatusCode},
+    response::{IntoResponse, Response},
     Json,
 };
 // use chrono::Utc; // not needed in this module
@@ -8,17 +10,22 @@ use garde::Validate;
 use tracing::{info, instrument};
 
 use crate::{
+    dto::response::jobs::JobResponse,
     dto::{
+        request::bulk_events::BulkEventsRequest,
+        request::event_export::EventExportRequest,
+        request::event_jobs::EventJobRequest,
         request::events::{EventCreateRequest, EventQueryParams, EventUpdateRequest},
         response::events::{
             EventCountsByMonitorResponse, EventCountsResponse, EventResponse,
             PaginatedEventsResponse,
         },
     },
-    error::{AppError, AppResponseError, AppResult},
+    error::{AppError, AppResponseError, AppResult, Resource, ResourceType},
     server::state::AppState,
     service,
     service::monitor_acl::MonitorScope,
+    util::claim::UserClaims,
 };
 
 /// Get a paginated list of events
@@ -165,6 +172,11 @@ pub async fn update_event(
 }
 
 /// Delete an event
+///
+/// The event is hidden at once and its media moved to the storage's trash;
+/// both are purged after `[trash].grace_hours`, until when
+/// `POST /api/v3/events/{id}:restore` undoes the delete. Deleting an event
+/// that is already in the trash succeeds.
 #[utoipa::path(
     delete,
     path = "/api/v3/events/{id}",
@@ -176,6 +188,8 @@ pub async fn update_event(
     responses(
         (status = 204, description = "Event deleted"),
         (status = 401, description = "Unauthorized", body = AppResponseError),
+        (status = 404, description = "Event not found", body = AppResponseError),
+        (status = 409, description = "Event has a media job in progress", body = AppResponseError),
         (status = 500, description = "Internal server error", body = AppResponseError)
     ),
     security(
@@ -193,6 +207,222 @@ pub async fn delete_event(
     Ok(StatusCode::NO_CONTENT)
 }
 
+/// Restore a deleted event
+///
+/// Moves the event's media back out of the trash and makes it visible again.
+/// Only possible within `[trash].grace_hours` of the delete. Requires write
+/// access to the event's monitor.
+#[utoipa::path(
+    post,
+    path = "/api/v3/events/{id}:restore",
+    operation_id = "restoreEvent",
+    tag = "Events",
+    params(
+        ("id" = u32, Path, description = "Event ID")
+    ),
+    responses(
+        (status = 200, description = "Event restored", body = EventResponse),
+        (status = 401, description = "Unauthorized", body = AppResponseError),
+        (status = 404, description = "No deleted event with that id", body = AppResponseError),
+        (status = 409, description = "Grace period has passed", body = AppResponseError),
+        (status = 500, description = "Internal server error", body = AppResponseError)
+    ),
+    security(
+        ("jwt" = [])
+    )
+)]
+#[instrument(skip(state, scope))]
+pub async fn restore_event(
+    State(state): State<AppState>,
+    Path(target): Path<String>,
+    scope: MonitorScope,
+) -> AppResult<Json<EventResponse>> {
+    // The router matches the whole `{id}:restore` segment; split the custom
+    // method off here.
+    let id = target
+        .strip_suffix(":restore")
+        .and_then(|id| id.parse::<u32>().ok())
+        .ok_or_else(|| {
+            AppError::NotFoundError(Resource {
+                details: vec![("path".to_string(), target.clone())],
+                resource_type: ResourceType::Message,
+            })
+        })?;
+    let event = service::events::restore(&state, id, &scope).await?;
+    Ok(Json(event))
+}
+
+/// Archive, unarchive or delete every event matching a filter
+///
+/// Events are selected by a saved search or an inline filter AST (exactly one)
+/// and limited to monitors the caller may edit. The work runs as a background
+/// job, walked in batches; poll `GET /api/v3/jobs/{id}` for progress. With
+/// `dry_run` nothing changes and the job reports what would, with a sample of
+/// matching ids. Delete skips archived and still-recording events.
+#[utoipa::path(
+    post,
+    path = "/api/v3/events:bulk",
+    operation_id = "bulkEvents",
+    tag = "Events",
+    request_body = BulkEventsRequest,
+    responses(
+        (status = 202, description = "Job queued", body = JobResponse),
+        (status = 400, description = "Bad request", body = AppResponseError),
+        (status = 401, description = "Unauthorized", body = AppResponseError),
+        (status = 404, description = "Saved search not found", body = AppResponseError),
+        (status = 500, description = "Internal server error", body = AppResponseError)
+    ),
+    security(
+        ("jwt" = [])
+    )
+)]
+#[instrument(skip(state, claims, scope, req))]
+pub async fn bulk_events(
+    State(state): State<AppState>,
+    claims: UserClaims,
+    scope: MonitorScope,
+    Json(req): Json<BulkEventsRequest>,
+) -> AppResult<(StatusCode, Json<JobResponse>)> {
+    let job = service::bulk_events::submit(&state, req, &claims, &scope).await?;
+    Ok((StatusCode::ACCEPTED, Json(job)))
+}
+
+/// Bundle events into a zip or tar for hand-over
+///
+/// Events are selected by id or by an inline filter AST (exactly one) and
+/// limited to monitors the caller may view; every listed id must be visible.
+/// Each event contributes its recording, alarm frame JPEGs, `Frames`, `Stats`
+/// and tags as JSON and its synopsis mp4 when one is ready, and the archive
+/// ends with a `manifest.json` giving each file's SHA-256. Up to
+/// `[jobs].export_inline_max_events` events are streamed back directly;
+/// larger selections are queued as a job whose bundle can be downloaded from
+/// `GET /api/v3/events/export/{job_id}` while it is being built.
+#[utoipa::path(
+    post,
+    path = "/api/v3/events/export",
+    operation_id = "exportEvents",
+    tag = "Events",
+    request_body = EventExportRequest,
+    responses(
+        (status = 200, description = "The bundle, streamed as it is built", content_type = "application/zip"),
+        (status = 202, description = "Job queued", body = JobResponse),
+        (status = 400, description = "Bad request", body = AppResponseError),
+        (status = 401, description = "Unauthorized", body = AppResponseError),
+        (status = 404, description = "Event not found", body = AppResponseError),
+        (status = 500, description = "Internal server error", body = AppResponseError)
+    ),
+    security(
+        ("jwt" = [])
+    )
+)]
+#[instrument(skip(state, claims, scope, req))]
+pub async fn export_events(
+    State(state): State<AppState>,
+    claims: UserClaims,
+    scope: MonitorScope,
+    Json(req): Json<EventExportRequest>,
+) -> AppResult<Response> {
+    match service::event_export::submit(&state, req, &claims, &scope).await? {
+        service::event_export::Export::Inline(bundle) => Ok(bundle_response(bundle)),
+        service::event_export::Export::Queued(job) => {
+            Ok((StatusCode::ACCEPTED, Json(job)).into_response())
+        }
+    }
+}
+
+/// Download the bundle built by an export job
+///
+/// Available to the job's submitter and to anyone with System View. While
+/// the job runs the bundle is sent as it is written, without a
+/// `Content-Length`; the download ends in an error rather than early if the
+/// job fails.
+#[utoipa::path(
+    get,
+    path = "/api/v3/events/export/{job_id}",
+    operation_id = "downloadEventExport",
+    tag = "Events",
+    params(("job_id" = u64, Path, description = "Export job ID")),
+    responses(
+        (status = 200, description = "The bundle", content_type = "application/zip"),
+        (status = 401, description = "Unauthorized", body = AppResponseError),
+        (status = 404, description = "Export job not found", body = AppResponseError),
+        (status = 409, description = "The export job failed", body = AppResponseError),
+        (status = 500, description = "Internal server error", body = AppResponseError)
+    ),
+    security(
+        ("jwt" = [])
+    )
+)]
+#[instrument(skip(state, claims))]
+pub async fn download_event_export(
+    State(state): State<AppState>,
+    Path(job_id): Path<u64>,
+    claims: UserClaims,
+) -> AppResult<Response> {
+    let bundle = service::event_export::download(&state, job_id, &claims).await?;
+    Ok(bundle_response(bundle))
+}
+
+fn bundle_response(bundle: service::event_export::BundleStream) -> Response {
+    let mut response = Response::builder()
+        .status(StatusCode::OK)
+        .header(header::CONTENT_TYPE, bundle.content_type)
+        .header(
+            header::CONTENT_DISPOSITION,
+            format!("attachment; filename=\"{}\"", bundle.filename),
+        )
+        .header(header::CACHE_CONTROL, "no-store");
+    if let Some(length) = bundle.content_length {
+        response = response.header(header::CONTENT_LENGTH, length.to_string());
+    }
+    response
+        .body(Body::from_stream(bundle.body))
+        .expect("export response")
+}
+
+/// Queue a media job for one event
+///
+/// `transcode` re-encodes the event to H.264 mp4 and makes it the default
+/// video; `export` copies its media to the configured export directory;
+/// `move` relocates its media to `target_storage_id` and repoints
+/// `StorageId`; `copy` copies it there and records `SecondaryStorageId`.
+/// Requires write access to the event's monitor. Jobs run on a bounded worker
+/// pool; poll `GET /api/v3/jobs/{id}` for progress. An event has at most one
+/// media job queued or running.
+#[utoipa::path(
+    post,
+    path = "/api/v3/events/{id}/jobs",
+    operation_id = "createEventJob",
+    tag = "Events",
+    params(
+        ("id" = u64, Path, description = "Event ID")
+    ),
+    request_body = EventJobRequest,
+    responses(
+        (status = 202, description = "Job queued", body = JobResponse),
+        (status = 400, description = "Bad request", body = AppResponseError),
+        (status = 401, description = "Unauthorized", body = AppResponseError),
+        (status = 404, description = "Event not found", body = AppResponseError),
+        (status = 409, description = "Event still recording or already has a media job", body = AppResponseError),
+        (status = 500, description = "Internal server error", body = AppResponseError)
+    ),
+    security(
+        ("jwt" = [])
+    )
+)]
+#[instrument(skip(state, claims, scope, req))]
+pub async fn create_event_job(
+    State(state): State<AppState>,
+    Path(id): Path<u64>,
+    claims: UserClaims,
+    scope: MonitorScope,
+    Json(req): Json<EventJobRequest>,
+) -> AppResult<(StatusCode, Json<JobResponse>)> {
+    req.validate().map_err(AppError::InvalidInputError)?;
+    let job = service::media_jobs::submit(&state, id, req, &claims, &scope).await?;
+    Ok((StatusCode::ACCEPTED, Json(job)))
+}
+
 /// Get event counts grouped by hour
 #[utoipa::path(
     get,
@@ -5,11 +5,13 @@
 
 use axum::{
     body::Body,
-    extract::{Path, State},
+    extract::{Path, Query, State},
     http::{header, HeaderMap, StatusCode},
     response::Response,
     Json,
 };
+use chrono::{DateTime, Utc};
+use futures::stream::{self, StreamExt};
 use serde::{Deserialize, Serialize};
 use std::path::{Path as StdPath, PathBuf};
 use tokio::fs::File;
@@ -23,6 +25,7 @@ use crate::repo;
 use crate::server::state::AppState;
 use crate::service::event_storage::{build_event_directory_path, resolve_event_storage_path};
 use crate::service::monitor_acl::MonitorScope;
+use crate::streaming::clip::{ClipFormat, ClipRange};
 use crate::util::authz::Level;
 
 // ============================================================================
@@ -42,6 +45,42 @@ pub struct EventSegmentPath {
     pub seq: usize,
 }
 
+/// Path parameters for a monitor's time-range playback.
+#[derive(Debug, Deserialize)]
+pub struct MonitorPlaybackPath {
+    pub monitor_id: u32,
+}
+
+/// Query parameters for a monitor's time-range playback.
+#[derive(Debug, Deserialize)]
+pub struct PlaybackRangeQuery {
+    /// Range start (RFC 3339).
+    pub from: DateTime<Utc>,
+    /// Range end (RFC 3339), exclusive.
+    pub to: DateTime<Utc>,
+}
+
+/// Query parameters for an event clip.
+#[derive(Debug, Deserialize)]
+pub struct ClipQuery {
+    /// Clip start, in seconds from the start of the event.
+    pub start: f64,
+    /// Clip end, in seconds from the start of the event.
+    pub end: f64,
+    #[serde(default)]
+    pub format: ClipFormat,
+    /// Serve as an attachment (`Content-Disposition: attachment`) rather than
+    /// inline.
+    #[serde(default)]
+    pub download: bool,
+}
+
+/// Most events one time-range playlist may stitch together.
+const MAX_RANGE_EVENTS: usize = 500;
+
+/// Events indexed at once while building a time-range playlist.
+const RANGE_INDEXING_CONCURRENCY: usize = 4;
+
 /// Response for event video metadata
 #[derive(Debug, Serialize, utoipa::ToSchema)]
 pub struct EventVideoInfo {
@@ -125,7 +164,7 @@ fn select_video_filename(event_id: u64, default_video: &str) -> String {
 /// - **Deep**: `{storage_path}/{MonitorId}/{YY/MM/DD/HH/MM/SS}/{video_file}`
 /// - **Medium**: `{storage_path}/{MonitorId}/{YYYY-MM-DD}/{EventId}/{video_file}`
 /// - **Shallow**: `{storage_path}/{MonitorId}/{EventId}/{video_file}`
-async fn get_event_video_path(
+pub(crate) async fn get_event_video_path(
     state: &AppState,
     event_id: u64,
     scope: &MonitorScope,
@@ -242,12 +281,12 @@ pub(crate) fn not_found_event(event_id: u64) -> AppError {
 /// close, so a NULL end means the event is in progress — there is no finalized
 /// `{id}-video.*.mp4` yet, only a growing `incomplete.*.mp4` plus ZoneMinder's
 /// live `index.m3u8`.
-fn event_is_in_progress(event: &EventModel) -> bool {
+pub(crate) fn event_is_in_progress(event: &EventModel) -> bool {
     event.end_date_time.is_none()
 }
 
 /// The on-disk directory for an event, resolving storage + scheme.
-async fn event_directory(
+pub(crate) async fn event_directory(
     state: &AppState,
     event: &EventModel,
     event_id: u64,
@@ -277,7 +316,7 @@ async fn find_incomplete_media(dir: &StdPath) -> Option<PathBuf> {
 
 /// Resolve the media file to byte-serve for an event: the growing
 /// `incomplete.*.mp4` while recording, else the finalized recorded video.
-async fn get_event_media_path(
+pub(crate) async fn get_event_media_path(
     state: &AppState,
     event_id: u64,
     scope: &MonitorScope,
@@ -443,6 +482,123 @@ pub async fn get_event_playlist(
         .unwrap())
 }
 
+/// Get an HLS-VOD playlist over a time range of a monitor's recordings
+///
+/// Stitches every closed event of the monitor that overlaps `[from, to)` into
+/// one VOD playlist, oldest first, with `#EXT-X-DISCONTINUITY` between events
+/// and `#EXT-X-PROGRAM-DATE-TIME` at the start of each, so a player can scrub
+/// the range as one timeline. Segments outside the range are left out, and
+/// events still recording or without a packageable video are skipped. Segment
+/// URIs point at each event's `stream/` routes. A range with more than 500
+/// events is refused; ask for a shorter one.
+#[utoipa::path(
+    get,
+    path = "/api/v3/monitors/{monitor_id}/playback.m3u8",
+    operation_id = "getMonitorPlaybackPlaylist",
+    tag = "Event Playback",
+    params(
+        ("monitor_id" = u32, Path, description = "Monitor ID"),
+        ("from" = String, Query, description = "Range start (RFC 3339)", example = "2026-10-18T08:00:00Z"),
+        ("to" = String, Query, description = "Range end (RFC 3339), exclusive", example = "2026-10-18T09:00:00Z")
+    ),
+    responses(
+        (status = 200, description = "HLS-VOD playlist", content_type = "application/vnd.apple.mpegurl"),
+        (status = 400, description = "Invalid range, or too many events in it", body = AppResponseError),
+        (status = 404, description = "Monitor not found, or no recordings in range", body = AppResponseError),
+        (status = 500, description = "Internal server error", body = AppResponseError)
+    ),
+    security(
+        ("jwt" = [])
+    )
+)]
+pub async fn get_monitor_playback_playlist(
+    State(state): State<AppState>,
+    Path(path): Path<MonitorPlaybackPath>,
+    Query(query): Query<PlaybackRangeQuery>,
+    scope: MonitorScope,
+) -> Result<Response, AppError> {
+    use crate::streaming::hls::vod::{build_range_playlist, get_or_index, RangeEntry};
+    use crate::util::{naive_local_to_utc, utc_to_naive_local};
+
+    let monitor_id = path.monitor_id;
+    // Hidden monitors look the same as missing ones.
+    if !scope.allows(monitor_id, Level::View) {
+        return Err(AppError::NotFoundError(Resource {
+            resource_type: ResourceType::Monitor,
+            details: vec![("monitor_id".to_string(), monitor_id.to_string())],
+        }));
+    }
+    if query.to <= query.from {
+        return Err(AppError::BadRequestError(
+            "`to` must be after `from`".to_string(),
+        ));
+    }
+
+    let events = repo::events::find_closed_in_range(
+        state.db(),
+        monitor_id,
+        utc_to_naive_local(query.from),
+        utc_to_naive_local(query.to),
+        MAX_RANGE_EVENTS as u64 + 1,
+    )
+    .await?;
+    if events.len() > MAX_RANGE_EVENTS {
+        return Err(AppError::BadRequestError(format!(
+            "more than {MAX_RANGE_EVENTS} events in range; ask for a shorter one"
+        )));
+    }
+    debug!(
+        "Building playback playlist for monitor {monitor_id} over {} events",
+        events.len()
+    );
+
+    let (state, scope) = (&state, &scope);
+    let entries: Vec<RangeEntry> = stream::iter(events)
+        .map(|event| async move {
+            let start = naive_local_to_utc(event.start_date_time?);
+            // Only the segment durations are needed here; each segment is
+            // packaged when the player fetches it from the event's routes.
+            let durations = match get_event_video_path(state, event.id, scope).await {
+                Ok(path) => get_or_index(event.id, path)
+                    .await
+                    .map_err(AppError::InternalServerError),
+                Err(e) => Err(e),
+            };
+            match durations {
+                Ok(durations) => Some(RangeEntry {
+                    event_id: event.id,
+                    start,
+                    durations,
+                }),
+                Err(e) => {
+                    // JPEG-only and damaged recordings leave a gap.
+                    debug!("Leaving event {} out of playback: {e}", event.id);
+                    None
+                }
+            }
+        })
+        .buffered(RANGE_INDEXING_CONCURRENCY)
+        .filter_map(futures::future::ready)
+        .collect()
+        .await;
+
+    let playlist = build_range_playlist(&entries, query.from, query.to).ok_or_else(|| {
+        AppError::NotFoundError(Resource {
+            resource_type: ResourceType::Event,
+            details: vec![
+                ("monitor_id".to_string(), monitor_id.to_string()),
+                ("reason".to_string(), "no recordings in range".to_string()),
+            ],
+        })
+    })?;
+    Ok(Response::builder()
+        .status(StatusCode::OK)
+        .header(header::CONTENT_TYPE, "application/vnd.apple.mpegurl")
+        .header(header::CACHE_CONTROL, "no-cache")
+        .body(Body::from(playlist))
+        .unwrap())
+}
+
 /// Probe + package (cached) the HLS-VOD assets for an event.
 async fn event_vod_assets(
     state: &AppState,
@@ -555,25 +711,123 @@ pub async fn get_event_video(
     serve_file_with_range(&video_path, &headers).await
 }
 
+/// Download part of an event's recording
+///
+/// Remuxes `[start, end)` (seconds from the start of the event) out of the
+/// recording without transcoding. The clip opens on the last keyframe at or
+/// before `start`, so it may begin up to one keyframe interval early; the
+/// `X-ZM-Clip-Lead-In` header gives that extra time in seconds. Timestamps are
+/// rewritten to start at zero, and an AAC audio track is kept in step. A clip
+/// is cut once and cached, so Range requests and repeat downloads are served
+/// from the file. Clips longer than `[clips].max_seconds` are refused.
+#[utoipa::path(
+    get,
+    path = "/api/v3/events/{id}/clip.mp4",
+    operation_id = "getEventClip",
+    tag = "Event Playback",
+    params(
+        ("id" = u64, Path, description = "Event ID"),
+        ("start" = f64, Query, description = "Clip start, seconds from the start of the event", example = 120.0),
+        ("end" = f64, Query, description = "Clip end, seconds from the start of the event; clamped to the recording", example = 140.0),
+        ("format" = Option<String>, Query, description = "Container: `mp4` (fragmented, default) or `mkv`"),
+        ("download" = Option<bool>, Query, description = "Send `Content-Disposition: attachment` instead of `inline`")
+    ),
+    responses(
+        (status = 200, description = "Clip (full)", content_type = "video/mp4"),
+        (status = 206, description = "Partial content", content_type = "video/mp4"),
+        (status = 400, description = "Invalid range, clip too long, bad format or Range header", body = AppResponseError),
+        (status = 404, description = "Event or video not found", body = AppResponseError),
+        (status = 500, description = "Internal server error", body = AppResponseError)
+    ),
+    security(("jwt" = []))
+)]
+pub async fn get_event_clip(
+    State(state): State<AppState>,
+    Path(path): Path<EventPlaybackPath>,
+    Query(query): Query<ClipQuery>,
+    scope: MonitorScope,
+    headers: HeaderMap,
+) -> Result<Response, AppError> {
+    let video_path = get_event_video_path(&state, path.id, &scope).await?;
+    let info = crate::streaming::probe::probe_event_media(path.id, video_path.clone())
+        .await
+        .map_err(AppError::InternalServerError)?;
+    let max_seconds = state.clip_service.config().max_seconds;
+    let range = ClipRange::new(query.start, query.end, info.duration_seconds, max_seconds)
+        .map_err(AppError::BadRequestError)?;
+    debug!(
+        "Clip {:?}..{:?} of event {} as {:?}",
+        range.start, range.end, path.id, query.format
+    );
+    let cut = state
+        .clip_service
+        .get(path.id, video_path, info, range, query.format)
+        .await
+        .map_err(|e| AppError::InternalServerError(e.to_string()))?;
+
+    let mut response =
+        serve_open_file_with_range(cut.file, cut.size, query.format.content_type(), &headers)
+            .await?;
+    let disposition = format!(
+        "{}; filename=\"{}\"",
+        if query.download {
+            "attachment"
+        } else {
+            "inline"
+        },
+        clip_filename(path.id, range, query.format)
+    );
+    let headers = response.headers_mut();
+    headers.insert(
+        header::CONTENT_DISPOSITION,
+        disposition.parse().expect("ASCII filename"),
+    );
+    headers.insert(
+        "x-zm-clip-lead-in",
+        format!("{:.3}", cut.lead_in.as_secs_f64())
+            .parse()
+            .expect("number"),
+    );
+    Ok(response)
+}
+
+/// `event-{id}-{start}-{end}.{ext}`, offsets in whole milliseconds.
+fn clip_filename(event_id: u64, range: ClipRange, format: ClipFormat) -> String {
+    format!(
+        "event-{event_id}-{}-{}.{}",
+        range.start.as_millis(),
+        range.end.as_millis(),
+        format.extension()
+    )
+}
+
 /// Serve a file as `video/mp4` with HTTP Range support — 200 for a full request,
 /// 206 for a byte range. Streams from disk so multi-GB recordings (and the
 /// growing in-progress file) don't buffer into memory.
 async fn serve_file_with_range(path: &StdPath, headers: &HeaderMap) -> Result<Response, AppError> {
-    let metadata = tokio::fs::metadata(path).await.map_err(|e| {
+    let file = File::open(path)
+        .await
+        .map_err(|e| AppError::InternalServerError(format!("Failed to open video: {}", e)))?;
+    let metadata = file.metadata().await.map_err(|e| {
         AppError::InternalServerError(format!("Failed to read video metadata: {}", e))
     })?;
-    let file_size = metadata.len();
+    serve_open_file_with_range(file, metadata.len(), "video/mp4", headers).await
+}
 
+/// Serve an already open `file` of `file_size` bytes as `content_type`, with
+/// the Range handling of [`serve_file_with_range`].
+async fn serve_open_file_with_range(
+    mut file: File,
+    file_size: u64,
+    content_type: &str,
+    headers: &HeaderMap,
+) -> Result<Response, AppError> {
     let range_header = headers.get(header::RANGE).and_then(|v| v.to_str().ok());
 
     if let Some(range_str) = range_header {
         if let Some((start, end)) = parse_range_header(Some(range_str), file_size) {
             let length = end - start + 1;
 
-            let mut file = File::open(path).await.map_err(|e| {
-                AppError::InternalServerError(format!("Failed to open video: {}", e))
-            })?;
-
             use tokio::io::AsyncSeekExt;
             file.seek(std::io::SeekFrom::Start(start))
                 .await
@@ -585,7 +839,7 @@ async fn serve_file_with_range(path: &StdPath, headers: &HeaderMap) -> Result<Re
 
             return Ok(Response::builder()
                 .status(StatusCode::PARTIAL_CONTENT)
-                .header(header::CONTENT_TYPE, "video/mp4")
+                .header(header::CONTENT_TYPE, content_type)
                 .header(header::ACCEPT_RANGES, "bytes")
                 .header(
                     header::CONTENT_RANGE,
@@ -603,14 +857,11 @@ async fn serve_file_with_range(path: &StdPath, headers: &HeaderMap) -> Result<Re
 
     // No range request - stream the full file from disk rather than reading it
     // entirely into memory (events can be multiple GB).
-    let file = File::open(path)
-        .await
-        .map_err(|e| AppError::InternalServerError(format!("Failed to open video: {}", e)))?;
     let stream = ReaderStream::new(file);
 
     Ok(Response::builder()
         .status(StatusCode::OK)
-        .header(header::CONTENT_TYPE, "video/mp4")
+        .header(header::CONTENT_TYPE, content_type)
         .header(header::ACCEPT_RANGES, "bytes")
         .header(header::CONTENT_LENGTH, file_size.to_string())
         .body(Body::from_stream(stream))
@@ -1134,4 +1385,17 @@ mod tests {
         assert!(out.contains("\nmedia.mp4\n"));
         assert!(!out.contains("index.php"));
     }
+
+    // ------------------------------------------------------------------
+    // clip_filename
+    // ------------------------------------------------------------------
+
+    #[test]
+    fn clip_filename_names_event_range_and_container() {
+        let range = ClipRange::new(12.5, 32.0, 600.0, 0).unwrap();
+        assert_eq!(
+            clip_filename(4812, range, ClipFormat::Mkv),
+            "event-4812-12500-32000.mkv"
+        );
+    }
 }
@@ -0,0 +1,29 @@
+use crate::dto::response::jobs::JobResponse;
+use crate::error::AppResult;
+use crate::server::state::AppState;
+use crate::util::claim::UserClaims;
+use axum::{
+    extract::{Path, State},
+    Json,
+};
+
+/// Get a background job's status and progress.
+///
+/// - Visible to the submitting user, or to anyone with System View.
+/// - Requires a valid JWT.
+#[utoipa::path(
+    get,
+    path = "/api/v3/jobs/{id}",
+    params(("id" = u64, Path, description = "Job ID")),
+    responses((status = 200, description = "Job status", body = JobResponse)),
+    tag = "Jobs",
+    security(("jwt" = []))
+)]
+pub async fn get_job(
+    Path(id): Path<u64>,
+    State(state): State<AppState>,
+    claims: UserClaims,
+) -> AppResult<Json<JobResponse>> {
+    let job = crate::service::jobs::get_by_id(&state, id, &claims).await?;
+    Ok(Json(job))
+}
@@ -9,25 +9,34 @@ use axum::{
         ws::{Message, WebSocket},
         Path, Query, State, WebSocketUpgrade,
     },
-    http::{header, StatusCode},
-    response::Response,
+    http::{header, HeaderMap, StatusCode, Uri},
+    response::{
+        sse::{Event, KeepAlive, Sse},
+        Response,
+    },
     Json,
 };
-use futures_util::{SinkExt, StreamExt};
+use futures_util::{SinkExt, Stream, StreamExt};
 use serde::{Deserialize, Serialize};
+use std::convert::Infallible;
 use std::sync::Arc;
 use std::time::{Duration, Instant};
 use tokio::sync::broadcast;
 use tracing::{debug, error, info, warn};
 
 use crate::error::{AppError, AppResponseError, AppResult};
+use crate::repo;
 use crate::server::state::AppState;
-use crate::streaming::live::audio::AacToOpusTranscoder;
+use crate::streaming::hls::{Rendition, StreamKey};
 use crate::streaming::live::webrtc::{
-    AccessUnitAssembler, AssembledAccessUnit, AudioTrackKind, WebRtcLiveConfig, WebRtcLiveManager,
+    inject_startup_keyframe, select_audio_track, startup_codec, write_audio_packet,
+    AccessUnitAssembler, WebRtcLiveConfig, WebRtcLiveError, WebRtcLiveManager,
 };
+use crate::streaming::live::whep::{WhepError, WhepSessions};
 use crate::streaming::live::{CoordinatorError, LiveStreamConfig};
-use crate::streaming::source::{AudioCodec, CachedKeyframe, MonitorSource, VideoCodec};
+use crate::streaming::source::{MonitorEvent, RouterError, SubstreamSource};
+use crate::streaming::talkback::{self, TalkbackPlan};
+use crate::util::authz::{self, Feature, Level};
 
 // ============================================================================
 // DTOs
@@ -63,6 +72,9 @@ pub struct StartLiveResponse {
     pub monitor_id: u32,
     pub status: String,
     pub hls_playlist: Option<String>,
+    /// Adaptive-bitrate entry point: the main stream plus the substream, once
+    /// the camera delivers it
+    pub hls_master_playlist: Option<String>,
     pub webrtc_signaling: Option<String>,
 }
 
@@ -109,6 +121,56 @@ pub struct LiveProtocolStatus {
     pub webrtc: bool,
 }
 
+/// One monitor EVENT as delivered on `GET /monitors/{id}/events` — the SSE
+/// `data:` payload. The frame's `event:` field repeats `code`, and its `id:`
+/// is the producer's per-monitor event sequence.
+#[derive(Debug, Serialize, utoipa::ToSchema)]
+pub struct MonitorEventFrame {
+    pub monitor_id: u32,
+    /// Event name (`snapshot`, `connection_failed`, `state_changed`, ...).
+    /// Codes this build does not know are rendered as hex, e.g. `0x09ff`.
+    pub code: String,
+    pub message: Option<String>,
+    /// Media stream generation in effect when the event was emitted.
+    pub generation: u32,
+    /// Producer wall clock (RFC 3339), when the event carried one.
+    pub wall_clock: Option<String>,
+    pub state_id: Option<u32>,
+    pub prev_state_id: Option<u32>,
+    pub state_name: Option<String>,
+    /// Active fault code on a `snapshot` (0 = healthy).
+    pub health_code: Option<u16>,
+    /// errno / ffmpeg error code accompanying a fault.
+    pub detail: Option<u32>,
+    /// zm-next structured analysis detail (detections, descriptions, ...).
+    #[schema(value_type = Option<Object>)]
+    pub json_detail: Option<serde_json::Value>,
+}
+
+impl MonitorEventFrame {
+    fn from_event(monitor_id: u32, ev: &MonitorEvent) -> Self {
+        Self {
+            monitor_id,
+            code: ev.name().into_owned(),
+            message: ev.message.clone(),
+            generation: ev.generation,
+            wall_clock: ev
+                .wall_clock_us
+                .and_then(|us| chrono::DateTime::from_timestamp_micros(us as i64))
+                .map(|t| t.to_rfc3339_opts(chrono::SecondsFormat::Millis, true)),
+            state_id: ev.state_id,
+            prev_state_id: ev.prev_state_id,
+            state_name: ev.state_name.clone(),
+            health_code: ev.health_code,
+            detail: ev.detail,
+            json_detail: ev
+                .json_detail
+                .as_deref()
+                .and_then(|json| serde_json::from_str(json).ok()),
+        }
+    }
+}
+
 /// Query parameters for HLS media playlist (LL-HLS support)
 #[derive(Debug, Deserialize)]
 pub struct LlHlsQuery {
@@ -158,9 +220,21 @@ pub async fn start_live_stream(
         AppError::ServiceUnavailableError("Live streaming not configured".to_string())
     })?;
 
+    // The substream comes from the monitor's SecondPath; a monitor without
+    // one (or with a non-RTSP one) publishes the main rendition only.
+    let substream = if request.enable_hls && state.config.streaming.hls.substream_enabled {
+        repo::monitors::find_by_id(state.db(), monitor_id)
+            .await?
+            .as_ref()
+            .and_then(SubstreamSource::from_monitor)
+    } else {
+        None
+    };
+
     let config = LiveStreamConfig {
         enable_hls: request.enable_hls,
         enable_webrtc: request.enable_webrtc,
+        substream,
     };
 
     coordinator
@@ -196,6 +270,11 @@ pub async fn start_live_stream(
         } else {
             None
         },
+        hls_master_playlist: if config.enable_hls {
+            Some(format!("{}/hls/master.m3u8", base_url))
+        } else {
+            None
+        },
         webrtc_signaling: if config.enable_webrtc {
             Some(format!("{}/webrtc/ws", base_url))
         } else {
@@ -402,7 +481,16 @@ pub async fn get_live_media_playlist(
     Path(monitor_id): Path<u32>,
     Query(ll_hls): Query<LlHlsQuery>,
 ) -> Result<Response, AppError> {
-    debug!("Serving live media playlist for monitor {}", monitor_id);
+    serve_media_playlist(&state, StreamKey::from(monitor_id), ll_hls).await
+}
+
+async fn serve_media_playlist(
+    state: &AppState,
+    stream: StreamKey,
+    ll_hls: LlHlsQuery,
+) -> Result<Response, AppError> {
+    let monitor_id = stream.monitor_id;
+    debug!("Serving live media playlist for monitor {}", stream);
 
     let hls_manager = state.hls_session_manager.as_ref().ok_or_else(|| {
         AppError::ServiceUnavailableError("HLS streaming not configured".to_string())
@@ -412,15 +500,15 @@ pub async fn get_live_media_playlist(
     if let Some(msn) = ll_hls.msn {
         let timeout = Duration::from_secs(5);
         if hls_manager
-            .wait_for_segment(monitor_id, msn, timeout)
+            .wait_for_segment(stream, msn, timeout)
             .await
             .is_err()
         {
-            debug!("LL-HLS wait timeout for monitor {} msn {}", monitor_id, msn);
+            debug!("LL-HLS wait timeout for monitor {} msn {}", stream, msn);
         }
     }
 
-    let playlist = hls_manager.get_playlist(monitor_id).await.map_err(|_| {
+    let playlist = hls_manager.get_playlist(stream).await.map_err(|_| {
         AppError::NotFoundError(crate::error::Resource {
             resource_type: crate::error::ResourceType::Monitor,
             details: vec![("monitor_id".to_string(), monitor_id.to_string())],
@@ -454,24 +542,26 @@ pub async fn get_live_init_segment(
     State(state): State<AppState>,
     Path(monitor_id): Path<u32>,
 ) -> Result<Response, AppError> {
-    debug!("Serving live init segment for monitor {}", monitor_id);
+    serve_init_segment(&state, StreamKey::from(monitor_id)).await
+}
+
+async fn serve_init_segment(state: &AppState, stream: StreamKey) -> Result<Response, AppError> {
+    let monitor_id = stream.monitor_id;
+    debug!("Serving live init segment for monitor {}", stream);
 
     let hls_manager = state.hls_session_manager.as_ref().ok_or_else(|| {
         AppError::ServiceUnavailableError("HLS streaming not configured".to_string())
     })?;
 
-    let data = hls_manager
-        .get_init_segment(monitor_id)
-        .await
-        .map_err(|_| {
-            AppError::NotFoundError(crate::error::Resource {
-                resource_type: crate::error::ResourceType::Monitor,
-                details: vec![
-                    ("monitor_id".to_string(), monitor_id.to_string()),
-                    ("segment".to_string(), "init.mp4".to_string()),
-                ],
-            })
-        })?;
+    let data = hls_manager.get_init_segment(stream).await.map_err(|_| {
+        AppError::NotFoundError(crate::error::Resource {
+            resource_type: crate::error::ResourceType::Monitor,
+            details: vec![
+                ("monitor_id".to_string(), monitor_id.to_string()),
+                ("segment".to_string(), "init.mp4".to_string()),
+            ],
+        })
+    })?;
 
     Ok(Response::builder()
         .status(StatusCode::OK)
@@ -508,28 +598,32 @@ pub async fn get_live_segment(
     State(state): State<AppState>,
     Path(path): Path<HlsSegmentPath>,
 ) -> Result<Response, AppError> {
-    debug!(
-        "Serving live segment {} for monitor {}",
-        path.segment, path.monitor_id
-    );
+    serve_segment(&state, StreamKey::from(path.monitor_id), &path.segment).await
+}
 
-    let sequence = parse_segment_sequence(&path.segment).ok_or_else(|| {
-        AppError::BadRequestError(format!("Invalid segment name: {}", path.segment))
-    })?;
+async fn serve_segment(
+    state: &AppState,
+    stream: StreamKey,
+    segment: &str,
+) -> Result<Response, AppError> {
+    debug!("Serving live segment {} for monitor {}", segment, stream);
+
+    let sequence = parse_segment_sequence(segment)
+        .ok_or_else(|| AppError::BadRequestError(format!("Invalid segment name: {}", segment)))?;
 
     let hls_manager = state.hls_session_manager.as_ref().ok_or_else(|| {
         AppError::ServiceUnavailableError("HLS streaming not configured".to_string())
     })?;
 
     let data = hls_manager
-        .get_segment(path.monitor_id, sequence)
+        .get_segment(stream, sequence)
         .await
         .map_err(|_| {
             AppError::NotFoundError(crate::error::Resource {
                 resource_type: crate::error::ResourceType::Monitor,
                 details: vec![
-                    ("monitor_id".to_string(), path.monitor_id.to_string()),
-                    ("segment".to_string(), path.segment.clone()),
+                    ("monitor_id".to_string(), stream.monitor_id.to_string()),
+                    ("segment".to_string(), segment.to_string()),
                 ],
             })
         })?;
@@ -542,6 +636,109 @@ pub async fn get_live_segment(
         .unwrap())
 }
 
+/// Path parameters for an HLS rendition's playlist or init segment
+#[derive(Debug, Deserialize)]
+pub struct HlsRenditionPath {
+    pub monitor_id: u32,
+    pub rendition: String,
+}
+
+/// Path parameters for an HLS rendition's segment
+#[derive(Debug, Deserialize)]
+pub struct HlsRenditionSegmentPath {
+    pub monitor_id: u32,
+    pub rendition: String,
+    pub segment: String,
+}
+
+/// Resolve a rendition directory from the URL; unknown ones are 404.
+fn rendition_stream(monitor_id: u32, rendition: &str) -> AppResult<StreamKey> {
+    let rendition = Rendition::from_dir(rendition).ok_or_else(|| {
+        AppError::NotFoundError(crate::error::Resource {
+            resource_type: crate::error::ResourceType::Monitor,
+            details: vec![
+                ("monitor_id".to_string(), monitor_id.to_string()),
+                ("rendition".to_string(), rendition.to_string()),
+            ],
+        })
+    })?;
+    Ok(StreamKey::new(monitor_id, rendition))
+}
+
+/// Get an HLS rendition's media playlist
+#[utoipa::path(
+    get,
+    path = "/api/v3/live/{monitor_id}/hls/{rendition}/live.m3u8",
+    operation_id = "getLiveRenditionPlaylist",
+    tag = "Live Streaming",
+    params(
+        ("monitor_id" = u32, Path, description = "Monitor/Camera ID"),
+        ("rendition" = String, Path, description = "Rendition listed in the master playlist (e.g., sub)")
+    ),
+    responses(
+        (status = 200, description = "Media playlist", content_type = "application/vnd.apple.mpegurl"),
+        (status = 404, description = "Session or rendition not found", body = AppResponseError)
+    ),
+    security(("jwt" = []))
+)]
+pub async fn get_live_rendition_playlist(
+    State(state): State<AppState>,
+    Path(path): Path<HlsRenditionPath>,
+    Query(ll_hls): Query<LlHlsQuery>,
+) -> Result<Response, AppError> {
+    let stream = rendition_stream(path.monitor_id, &path.rendition)?;
+    serve_media_playlist(&state, stream, ll_hls).await
+}
+
+/// Get an HLS rendition's init segment
+#[utoipa::path(
+    get,
+    path = "/api/v3/live/{monitor_id}/hls/{rendition}/init.mp4",
+    operation_id = "getLiveRenditionInitSegment",
+    tag = "Live Streaming",
+    params(
+        ("monitor_id" = u32, Path, description = "Monitor/Camera ID"),
+        ("rendition" = String, Path, description = "Rendition listed in the master playlist (e.g., sub)")
+    ),
+    responses(
+        (status = 200, description = "Init segment", content_type = "video/mp4"),
+        (status = 404, description = "Init segment not ready", body = AppResponseError)
+    ),
+    security(("jwt" = []))
+)]
+pub async fn get_live_rendition_init_segment(
+    State(state): State<AppState>,
+    Path(path): Path<HlsRenditionPath>,
+) -> Result<Response, AppError> {
+    let stream = rendition_stream(path.monitor_id, &path.rendition)?;
+    serve_init_segment(&state, stream).await
+}
+
+/// Get an HLS rendition's media segment
+#[utoipa::path(
+    get,
+    path = "/api/v3/live/{monitor_id}/hls/{rendition}/{segment}",
+    operation_id = "getLiveRenditionSegment",
+    tag = "Live Streaming",
+    params(
+        ("monitor_id" = u32, Path, description = "Monitor/Camera ID"),
+        ("rendition" = String, Path, description = "Rendition listed in the master playlist (e.g., sub)"),
+        ("segment" = String, Path, description = "Segment filename (e.g., segment_00001.m4s)")
+    ),
+    responses(
+        (status = 200, description = "Media segment", content_type = "video/iso.segment"),
+        (status = 404, description = "Segment not found", body = AppResponseError)
+    ),
+    security(("jwt" = []))
+)]
+pub async fn get_live_rendition_segment(
+    State(state): State<AppState>,
+    Path(path): Path<HlsRenditionSegmentPath>,
+) -> Result<Response, AppError> {
+    let stream = rendition_stream(path.monitor_id, &path.rendition)?;
+    serve_segment(&state, stream, &path.segment).await
+}
+
 /// Parse sequence number from segment filename
 fn parse_segment_sequence(filename: &str) -> Option<u64> {
     let name = filename.strip_suffix(".m4s")?;
@@ -594,6 +791,9 @@ pub enum WebRtcSignalingMessage {
         /// Whether the session carries an audio track (G.711 pass-through
         /// or AAC transcoded to Opus) alongside the video track
         has_audio: bool,
+        /// Whether the session accepts the viewer's microphone for talkback
+        #[serde(default)]
+        has_talkback: bool,
     },
     /// Error message
     Error {
@@ -604,6 +804,15 @@ pub enum WebRtcSignalingMessage {
     Pong,
 }
 
+/// Query parameters for the WebRTC signaling WebSocket
+#[derive(Debug, Default, Deserialize)]
+pub struct WebRtcWsQuery {
+    /// Offer a receive-only audio m-line for the viewer's microphone, played
+    /// out of the camera. Needs `Control: Edit` and edit access to the monitor.
+    #[serde(default)]
+    pub talkback: bool,
+}
+
 /// WebSocket handler for WebRTC signaling.
 ///
 /// Upgrades to a WebSocket and exchanges WebRTC signaling over JSON **text
@@ -630,18 +839,28 @@ pub enum WebRtcSignalingMessage {
 /// 5. **Errors** arrive as `{"type":"error","message":"..."}`.
 /// 6. **Keepalive**: either side may send `{"type":"ping"}`; the peer replies
 ///    `{"type":"pong"}`.
+///
+/// ## Talkback
+///
+/// With `?talkback=true` the offer carries an extra `recvonly` Opus audio
+/// m-line. The client attaches its microphone track to that transceiver
+/// before answering; `ready` then reports `"has_talkback":true`. The audio is
+/// transcoded and played through the camera's ONVIF backchannel or the
+/// monitor's stream socket (see `streaming.talkback`).
 #[utoipa::path(
     get,
     path = "/api/v3/live/{monitor_id}/webrtc/ws",
     operation_id = "webrtcSignalingStream",
     tag = "Live Streaming",
     params(
-        ("monitor_id" = u32, Path, description = "Monitor/Camera ID")
+        ("monitor_id" = u32, Path, description = "Monitor/Camera ID"),
+        ("talkback" = Option<bool>, Query, description = "Accept the viewer's microphone for two-way audio")
     ),
     responses(
         (status = 101, description = "WebSocket upgraded. Frames are JSON `WebRtcSignalingMessage` \
             objects (text). Server sends `offer`/`ready`/`icecandidate`/`error`/`pong`; client \
             sends `answer`/`icecandidate`/`ping`.", body = WebRtcSignalingMessage),
+        (status = 403, description = "Talkback requested without control access", body = AppResponseError),
         (status = 404, description = "Monitor not found", body = AppResponseError),
         (status = 503, description = "Service unavailable", body = AppResponseError)
     )
@@ -649,6 +868,9 @@ pub enum WebRtcSignalingMessage {
 pub async fn webrtc_websocket_handler(
     State(state): State<AppState>,
     Path(monitor_id): Path<u32>,
+    Query(query): Query<WebRtcWsQuery>,
+    headers: HeaderMap,
+    uri: Uri,
     ws: WebSocketUpgrade,
 ) -> Result<Response, AppError> {
     info!(
@@ -675,6 +897,12 @@ pub async fn webrtc_websocket_handler(
         }));
     }
 
+    let talkback = if query.talkback {
+        Some(talkback_plan(&state, monitor_id, &headers, &uri, source_router).await?)
+    } else {
+        None
+    };
+
     let router = Arc::clone(source_router);
 
     // Honor the operator's configured STUN/TURN servers instead of the
@@ -682,48 +910,55 @@ pub async fn webrtc_websocket_handler(
     let webrtc_config = WebRtcLiveConfig::from_webrtc_config(&state.config.streaming.webrtc);
 
     Ok(ws.on_upgrade(move |socket| {
-        handle_webrtc_websocket(router, monitor_id, webrtc_config, socket)
+        handle_webrtc_websocket(router, monitor_id, webrtc_config, talkback, socket)
     }))
 }
 
-/// Inject a keyframe at DTLS-connected time so the viewer sees a picture
-/// immediately instead of waiting a full GOP for the next natural IDR.
-///
-/// Crucially this re-reads the source's keyframe cache **live**. On a cold
-/// start the cache is empty when signaling begins, but the socket reader almost
-/// always populates it during the seconds of ICE/DTLS negotiation. The old code
-/// injected the snapshot captured *before* signaling (`pre_signaling`), which on
-/// a cold start was `None` — throwing away the freshly-cached keyframe and
-/// forcing a GOP-length stall (1–8s depending on the camera). We prefer the live
-/// read, fall back to the pre-signaling snapshot, then to nothing (the normal
-/// access-unit path then waits for the next keyframe). See REVIEW_FIXES_PLAN §2.1.
-async fn inject_startup_keyframe(
-    webrtc_manager: &WebRtcLiveManager,
-    session_id: &str,
-    source: &MonitorSource,
-    pre_signaling: &Option<CachedKeyframe>,
-    au_assembler: &mut AccessUnitAssembler,
-) -> bool {
-    let Some(ck) = source.cached_keyframe().or_else(|| pre_signaling.clone()) else {
-        return false;
-    };
-    let au = AssembledAccessUnit {
-        data: ck.keyframe_au.clone(),
-        timestamp_us: ck.timestamp_us,
-        is_keyframe: true,
-    };
-    if let Err(e) = webrtc_manager.write_access_unit(session_id, &au).await {
-        warn!("Failed to inject startup keyframe: {}", e);
-        return false;
+/// Authorize talkback and work out where the viewer's audio goes. The
+/// signaling route only demands `Stream: View`; speaking through a camera is
+/// control, so it needs `Control: Edit` and edit access to the monitor.
+async fn talkback_plan(
+    state: &AppState,
+    monitor_id: u32,
+    headers: &HeaderMap,
+    uri: &Uri,
+    source_router: &Arc<crate::streaming::source::SourceRouter>,
+) -> AppResult<TalkbackPlan> {
+    let config = &state.config.streaming.talkback;
+    if !config.enabled {
+        return Err(AppError::ServiceUnavailableError(
+            "Talkback is disabled".to_string(),
+        ));
     }
-    au_assembler.clear_needs_keyframe();
-    true
+    let claims = authz::require(state, headers, uri, Feature::Control, Level::Edit)?;
+    let scope = crate::service::monitor_acl::resolve(state.db(), claims.uid).await?;
+    if !scope.allows(monitor_id, Level::Edit) {
+        return Err(AppError::PermissionDeniedError(format!(
+            "Edit access to monitor {monitor_id} required for talkback"
+        )));
+    }
+
+    let monitor = repo::monitors::find_by_id(state.db(), monitor_id)
+        .await?
+        .ok_or_else(|| {
+            AppError::NotFoundError(crate::error::Resource {
+                resource_type: crate::error::ResourceType::Monitor,
+                details: vec![("monitor_id".to_string(), monitor_id.to_string())],
+            })
+        })?;
+    let source = source_router
+        .get_source(monitor_id)
+        .await
+        .map_err(|e| AppError::ServiceUnavailableError(e.to_string()))?;
+    TalkbackPlan::resolve(config, &monitor, source)
+        .map_err(|e| AppError::BadRequestError(format!("Talkback unavailable: {e}")))
 }
 
 async fn handle_webrtc_websocket(
     source_router: Arc<crate::streaming::source::SourceRouter>,
     monitor_id: u32,
     webrtc_config: WebRtcLiveConfig,
+    talkback: Option<TalkbackPlan>,
     socket: WebSocket,
 ) {
     info!(
@@ -755,74 +990,13 @@ async fn handle_webrtc_websocket(
     };
     let get_source_ms = get_source_at.elapsed().as_millis() as u64;
 
-    // Try the keyframe cache first (warm path: reader already active).
-    // If populated, we skip the cold-path codec-detection grace period entirely.
-    let cached: Option<CachedKeyframe> = source.cached_keyframe();
-
-    let (codec, profile_level_id) = if let Some(ref ck) = cached {
-        info!(
-            "Using cached keyframe for monitor {} (profile-level-id={})",
-            monitor_id, ck.profile_level_id
-        );
-        (ck.codec, Some(ck.profile_level_id.clone()))
-    } else {
-        // Cold path: no cached keyframe yet (first viewer since the reader
-        // started). Determine the codec from the source — the socket HELLO sets
-        // it as soon as it parses a packet; give it a brief grace period, then
-        // default to H.264.
-        //
-        // We deliberately do NOT wait for an SPS NAL here. The SDP offer
-        // advertises fixed, browser-universal H.264 profiles regardless of the
-        // camera's real profile-level-id (see `h264_offer_profile_level_ids`),
-        // so the detected value only ever reached a debug log — the previous
-        // up-to-5s SPS scan added that much startup latency for nothing.
-        // See REVIEW_FIXES_PLAN §2.3.
-        debug!(
-            "No keyframe cache for monitor {}, detecting codec from source",
-            monitor_id
-        );
-
-        tokio::time::sleep(Duration::from_millis(100)).await;
-        let codec = match source.codec().await {
-            VideoCodec::Unknown => VideoCodec::H264,
-            known => known,
-        };
-
-        (codec, None)
-    };
+    // Codec from the keyframe cache when the reader is warm, else from the
+    // socket HELLO after a brief grace period.
+    let (codec, cached) = startup_codec(&source).await;
+    let profile_level_id = cached.as_ref().map(|ck| ck.profile_level_id.clone());
 
-    // Decide the audio track for the offer. G.711 passes through unchanged
-    // (a mandatory WebRTC codec); AAC is transcoded to Opus — browsers do
-    // not implement AAC over RTP. The transcoder is created up front so a
-    // missing libopus downgrades the offer to video-only instead of
-    // advertising an audio m-line that could never carry media.
-    let mut audio_transcoder: Option<AacToOpusTranscoder> = None;
-    let audio_kind = match source.audio_codec() {
-        Some(AudioCodec::G711Ulaw) => Some(AudioTrackKind::Pcmu),
-        Some(AudioCodec::G711Alaw) => Some(AudioTrackKind::Pcma),
-        Some(AudioCodec::Aac) => match AacToOpusTranscoder::new() {
-            Ok(t) => {
-                audio_transcoder = Some(t);
-                Some(AudioTrackKind::Opus)
-            }
-            Err(e) => {
-                warn!(
-                    "Monitor {} has AAC audio but transcoding is unavailable ({}); \
-                     offering video-only",
-                    monitor_id, e
-                );
-                None
-            }
-        },
-        Some(other) => {
-            debug!(
-                "Monitor {} audio codec {:?} not supported over WebRTC; video-only",
-                monitor_id, other
-            );
-            None
-        }
-        None => None,
-    };
+    // G.711 passes through; AAC is transcoded to Opus when libopus is there.
+    let (audio_kind, mut audio_transcoder) = select_audio_track(monitor_id, &source);
 
     // Create WebRTC session. With trickle ICE the offer is returned immediately
     // (no ICE-gather wait); candidates stream out on `candidate_rx` and the
@@ -833,8 +1007,15 @@ async fn handle_webrtc_websocket(
         mut pc_state_rx,
         mut ice_connected_rx,
         mut candidate_rx,
+        talkback_rx,
     } = match webrtc_manager
-        .create_session(monitor_id, codec, profile_level_id.as_deref(), audio_kind)
+        .create_session(
+            monitor_id,
+            codec,
+            profile_level_id.as_deref(),
+            audio_kind,
+            talkback.is_some(),
+        )
         .await
     {
         Ok(result) => result,
@@ -852,6 +1033,16 @@ async fn handle_webrtc_websocket(
         session_id, monitor_id
     );
 
+    // The talkback task ends when the peer connection drops its microphone
+    // sender, i.e. with the session.
+    let has_talkback = match (talkback, talkback_rx) {
+        (Some(plan), Some(mic_rx)) => {
+            tokio::spawn(talkback::run(plan, mic_rx));
+            true
+        }
+        _ => false,
+    };
+
     let (mut ws_sender, mut ws_receiver) = socket.split();
 
     // Send offer to client
@@ -964,6 +1155,7 @@ async fn handle_webrtc_websocket(
                                                         session_id: session_id.clone(),
                                                         monitor_id,
                                                         has_audio: audio_kind.is_some(),
+                                                        has_talkback,
                                                     };
                                                     if let Ok(json) = serde_json::to_string(&ready_msg) {
                                                         let _ = ws_sender.send(Message::Text(json.into())).await;
@@ -1039,6 +1231,7 @@ async fn handle_webrtc_websocket(
                                     session_id: session_id.clone(),
                                     monitor_id,
                                     has_audio: audio_kind.is_some(),
+                                    has_talkback,
                                 };
                                 if let Ok(json) = serde_json::to_string(&ready_msg) {
                                     let _ = ws_sender.send(Message::Text(json.into())).await;
@@ -1168,49 +1361,10 @@ async fn handle_webrtc_websocket(
                 }
             } => {
                 match result {
-                    Ok(packet) if streaming_started => match packet.codec {
-                        AudioCodec::G711Alaw | AudioCodec::G711Ulaw => {
-                            // G.711: 8 kHz, one byte per sample — pass through.
-                            let duration = Duration::from_micros(
-                                packet.data.len() as u64 * 1_000_000 / 8_000,
-                            );
-                            if let Err(e) = webrtc_manager
-                                .write_audio_sample(&session_id, &packet.data, duration)
-                                .await
-                            {
-                                debug!("Failed to write audio sample: {}", e);
-                            }
-                        }
-                        AudioCodec::Aac => {
-                            // ADTS AAC → Opus. Sub-millisecond per frame, so
-                            // inline in the async loop is fine.
-                            if let Some(transcoder) = audio_transcoder.as_mut() {
-                                match transcoder.transcode(&packet.data) {
-                                    Ok(frames) => {
-                                        for frame in frames {
-                                            if let Err(e) = webrtc_manager
-                                                .write_audio_sample(
-                                                    &session_id,
-                                                    &frame.data,
-                                                    frame.duration,
-                                                )
-                                                .await
-                                            {
-                                                debug!("Failed to write Opus frame: {}", e);
-                                            }
-                                        }
-                                    }
-                                    Err(e) => {
-                                        debug!(
-                                            "AAC→Opus transcode error for session {}: {}",
-                                            session_id, e
-                                        );
-                                    }
-                                }
-                            }
-                        }
-                        _ => {}
-                    },
+                    Ok(packet) if streaming_started => {
+                        write_audio_packet(&webrtc_manager, &session_id, &packet, &mut audio_transcoder)
+                            .await;
+                    }
                     Ok(_) => {} // pre-DTLS audio: drained and dropped
                     Err(broadcast::error::RecvError::Lagged(n)) => {
                         debug!("WebRTC session {} audio lagged {} packets", session_id, n);
@@ -1262,6 +1416,191 @@ async fn handle_webrtc_websocket(
     );
 }
 
+// ============================================================================
+// WHEP Endpoints (WebRTC-HTTP Egress Protocol)
+// ============================================================================
+
+/// Media type of WHEP offers and answers.
+const SDP_CONTENT_TYPE: &str = "application/sdp";
+/// Media type of WHEP trickle-ICE PATCH bodies (RFC 8840).
+const SDPFRAG_CONTENT_TYPE: &str = "application/trickle-ice-sdpfrag";
+
+fn whep_sessions(state: &AppState) -> AppResult<&Arc<WhepSessions>> {
+    state.whep_sessions.as_ref().ok_or_else(|| {
+        AppError::ServiceUnavailableError("WebRTC live streaming not configured".to_string())
+    })
+}
+
+fn require_content_type(headers: &HeaderMap, expected: &str) -> AppResult<()> {
+    let content_type = headers
+        .get(header::CONTENT_TYPE)
+        .and_then(|v| v.to_str().ok())
+        .and_then(|v| v.split(';').next())
+        .map(str::trim)
+        .unwrap_or_default();
+    if content_type.eq_ignore_ascii_case(expected) {
+        Ok(())
+    } else {
+        Err(AppError::BadRequestError(format!(
+            "Content-Type must be {expected}"
+        )))
+    }
+}
+
+fn whep_error(monitor_id: u32, err: WhepError) -> AppError {
+    match err {
+        WhepError::Source(RouterError::SocketNotFound(_) | RouterError::SourceNotAvailable(_)) => {
+            AppError::NotFoundError(crate::error::Resource {
+                resource_type: crate::error::ResourceType::Monitor,
+                details: vec![
+                    ("monitor_id".to_string(), monitor_id.to_string()),
+                    (
+                        "reason".to_string(),
+                        "stream socket not available".to_string(),
+                    ),
+                ],
+            })
+        }
+        WhepError::SessionNotFound(id)
+        | WhepError::WebRtc(WebRtcLiveError::SessionNotFound(id)) => {
+            AppError::NotFoundError(crate::error::Resource {
+                resource_type: crate::error::ResourceType::Session,
+                details: vec![("session_id".to_string(), id)],
+            })
+        }
+        WhepError::WebRtc(WebRtcLiveError::SessionLimit(_)) | WhepError::Source(_) => {
+            AppError::ServiceUnavailableError(err.to_string())
+        }
+        WhepError::WebRtc(
+            WebRtcLiveError::InvalidSdp(_) | WebRtcLiveError::UnsupportedCodec(_),
+        )
+        | WhepError::IceRestart => AppError::BadRequestError(err.to_string()),
+        WhepError::WebRtc(e) => AppError::InternalServerError(e.to_string()),
+    }
+}
+
+/// Start WHEP playback of a monitor
+///
+/// WHEP (RFC 9725) lets standard players — OBS, GStreamer `whepsrc`, browser
+/// WHEP libraries — play a monitor over WebRTC without the custom WebSocket
+/// signaling. POST the player's SDP offer (`Content-Type: application/sdp`);
+/// the response is the SDP answer, with the session's resource URL in
+/// `Location`. The answer already carries the server's ICE candidates; the
+/// player may trickle its own by PATCHing the resource, and ends the session
+/// with DELETE. Video is H.264/H.265 as the camera sends it; audio is G.711
+/// or AAC transcoded to Opus, as on the WebSocket path.
+#[utoipa::path(
+    post,
+    path = "/api/v3/live/{monitor_id}/whep",
+    operation_id = "whepOffer",
+    tag = "Live Streaming",
+    params(
+        ("monitor_id" = u32, Path, description = "Monitor/Camera ID")
+    ),
+    request_body(content = String, content_type = "application/sdp", description = "SDP offer"),
+    responses(
+        (status = 201, description = "SDP answer; the session resource is in `Location`",
+            body = String, content_type = "application/sdp"),
+        (status = 400, description = "Not an SDP offer, or no codec in common", body = AppResponseError),
+        (status = 404, description = "Monitor not found", body = AppResponseError),
+        (status = 503, description = "Service unavailable or session limit reached", body = AppResponseError)
+    ),
+    security(
+        ("jwt" = [])
+    )
+)]
+pub async fn whep_offer(
+    State(state): State<AppState>,
+    Path(monitor_id): Path<u32>,
+    headers: HeaderMap,
+    offer: String,
+) -> AppResult<Response> {
+    require_content_type(&headers, SDP_CONTENT_TYPE)?;
+    let answer = whep_sessions(&state)?
+        .create(monitor_id, offer)
+        .await
+        .map_err(|e| whep_error(monitor_id, e))?;
+
+    Ok(Response::builder()
+        .status(StatusCode::CREATED)
+        .header(header::CONTENT_TYPE, SDP_CONTENT_TYPE)
+        .header(
+            header::LOCATION,
+            format!("/api/v3/live/{monitor_id}/whep/{}", answer.session_id),
+        )
+        .body(Body::from(answer.sdp))
+        .unwrap())
+}
+
+/// Trickle ICE candidates to a WHEP session
+///
+/// The body is an `application/trickle-ice-sdpfrag` (RFC 8840) carrying the
+/// player's `a=candidate` lines. ICE restarts are not supported — a fragment
+/// with a new `a=ice-ufrag` is rejected; start a new session instead.
+#[utoipa::path(
+    patch,
+    path = "/api/v3/live/{monitor_id}/whep/{session_id}",
+    operation_id = "whepTrickleIce",
+    tag = "Live Streaming",
+    params(
+        ("monitor_id" = u32, Path, description = "Monitor/Camera ID"),
+        ("session_id" = String, Path, description = "WHEP session ID, from the offer's `Location`")
+    ),
+    request_body(content = String, content_type = "application/trickle-ice-sdpfrag",
+        description = "Trickled ICE candidates"),
+    responses(
+        (status = 204, description = "Candidates added"),
+        (status = 400, description = "Bad fragment or ICE restart", body = AppResponseError),
+        (status = 404, description = "Session not found", body = AppResponseError),
+        (status = 503, description = "Service unavailable", body = AppResponseError)
+    ),
+    security(
+        ("jwt" = [])
+    )
+)]
+pub async fn whep_trickle_ice(
+    State(state): State<AppState>,
+    Path((monitor_id, session_id)): Path<(u32, String)>,
+    headers: HeaderMap,
+    fragment: String,
+) -> AppResult<StatusCode> {
+    require_content_type(&headers, SDPFRAG_CONTENT_TYPE)?;
+    whep_sessions(&state)?
+        .add_candidates(monitor_id, &session_id, &fragment)
+        .await
+        .map_err(|e| whep_error(monitor_id, e))?;
+    Ok(StatusCode::NO_CONTENT)
+}
+
+/// End a WHEP session
+#[utoipa::path(
+    delete,
+    path = "/api/v3/live/{monitor_id}/whep/{session_id}",
+    operation_id = "whepDelete",
+    tag = "Live Streaming",
+    params(
+        ("monitor_id" = u32, Path, description = "Monitor/Camera ID"),
+        ("session_id" = String, Path, description = "WHEP session ID, from the offer's `Location`")
+    ),
+    responses(
+        (status = 200, description = "Session ended"),
+        (status = 404, description = "Session not found", body = AppResponseError),
+        (status = 503, description = "Service unavailable", body = AppResponseError)
+    ),
+    security(
+        ("jwt" = [])
+    )
+)]
+pub async fn whep_delete(
+    State(state): State<AppState>,
+    Path((monitor_id, session_id)): Path<(u32, String)>,
+) -> AppResult<StatusCode> {
+    whep_sessions(&state)?
+        .remove(monitor_id, &session_id)
+        .map_err(|e| whep_error(monitor_id, e))?;
+    Ok(StatusCode::OK)
+}
+
 // ============================================================================
 // Monitor Snapshot
 // ============================================================================
@@ -1322,6 +1661,215 @@ pub async fn get_monitor_snapshot(
         .unwrap())
 }
 
+// ============================================================================
+// MJPEG
+// ============================================================================
+
+/// Query parameters for an MJPEG stream
+#[derive(Debug, Deserialize)]
+pub struct MjpegQuery {
+    /// Frames per second (default and ceiling from `[streaming.mjpeg]`)
+    pub fps: Option<u32>,
+    /// Output size as a percentage of the camera's, 1–100
+    pub scale: Option<u32>,
+}
+
+/// Stream a live monitor as MJPEG (`multipart/x-mixed-replace`)
+///
+/// For clients that predate HLS and WebRTC, as ZoneMinder's `nph-zms` served.
+/// Viewers asking for the same monitor, rate and scale share one decoder.
+/// Accepts the JWT via `?token=` because `<img>` cannot set headers.
+#[utoipa::path(
+    get,
+    path = "/api/v3/live/{monitor_id}/mjpeg",
+    operation_id = "getLiveMjpeg",
+    tag = "Live Streaming",
+    params(
+        ("monitor_id" = u32, Path, description = "Monitor/Camera ID"),
+        ("fps" = Option<u32>, Query, description = "Frames per second"),
+        ("scale" = Option<u32>, Query, description = "Output size in percent (1-100)")
+    ),
+    responses(
+        (status = 200, description = "MJPEG stream", content_type = "multipart/x-mixed-replace"),
+        (status = 404, description = "Monitor not found", body = AppResponseError),
+        (status = 503, description = "Service unavailable", body = AppResponseError)
+    ),
+    security(("jwt" = []))
+)]
+pub async fn get_live_mjpeg(
+    State(state): State<AppState>,
+    Path(monitor_id): Path<u32>,
+    Query(query): Query<MjpegQuery>,
+) -> AppResult<Response> {
+    use crate::streaming::mjpeg::{self, MjpegKey};
+    use crate::streaming::snapshot::SnapshotError;
+
+    let service = state.mjpeg_service.as_ref().ok_or_else(|| {
+        AppError::ServiceUnavailableError("MJPEG streaming not configured".to_string())
+    })?;
+
+    let config = &state.config.streaming.mjpeg;
+    let key = MjpegKey {
+        monitor_id,
+        fps: query
+            .fps
+            .unwrap_or(config.default_fps)
+            .clamp(1, config.max_fps.max(1)),
+        scale: query.scale.unwrap_or(100).clamp(1, 100),
+    };
+
+    let frames = service.subscribe(key).await.map_err(|e| match e {
+        SnapshotError::SourceNotAvailable(id) => AppError::NotFoundError(crate::error::Resource {
+            resource_type: crate::error::ResourceType::Monitor,
+            details: vec![
+                ("monitor_id".to_string(), id.to_string()),
+                ("reason".to_string(), e.to_string()),
+            ],
+        }),
+        _ => AppError::InternalServerError(e.to_string()),
+    })?;
+
+    let stream = futures_util::stream::unfold(frames, |mut frames| async move {
+        loop {
+            frames.changed().await.ok()?;
+            let jpeg = frames.borrow_and_update().clone();
+            if let Some(jpeg) = jpeg {
+                return Some((Ok::<_, Infallible>(mjpeg::multipart_part(&jpeg)), frames));
+            }
+        }
+    });
+
+    Ok(Response::builder()
+        .status(StatusCode::OK)
+        .header(
+            header::CONTENT_TYPE,
+            format!("multipart/x-mixed-replace; boundary={}", mjpeg::BOUNDARY),
+        )
+        .header(header::CACHE_CONTROL, "no-cache, no-store")
+        .body(Body::from_stream(stream))
+        .unwrap())
+}
+
+// ============================================================================
+// Monitor Events (SSE)
+// ============================================================================
+
+/// SSE keep-alive comment interval. Liveness for the browser and any proxy in
+/// between; independent of zmc's own 5s STATS cadence on the socket.
+const MONITOR_EVENTS_KEEPALIVE: Duration = Duration::from_secs(15);
+
+/// Stream a monitor's lifecycle events as Server-Sent Events
+///
+/// The first frame is the monitor's current status (`event: snapshot`), so a
+/// client is in sync without waiting for the next transition. After that each
+/// health fault/recovery, state change and analysis event is relayed as it is
+/// decoded off the stream socket. If the client falls behind, a fresh
+/// `snapshot` re-syncs it; gaps in `id` show what was dropped. `Last-Event-ID`
+/// is accepted but not replayed — the snapshot re-establishes current state.
+///
+/// Accepts the JWT via `?token=` because `EventSource` cannot set headers.
+#[utoipa::path(
+    get,
+    path = "/api/v3/monitors/{monitor_id}/events",
+    operation_id = "streamMonitorEvents",
+    tag = "Live Streaming",
+    params(
+        ("monitor_id" = u32, Path, description = "Monitor/Camera ID"),
+        ("token" = Option<String>, Query, description = "JWT, for EventSource clients")
+    ),
+    responses(
+        (status = 200, description = "`text/event-stream` of `MonitorEventFrame` \
+            JSON frames; `event:` is the event name, `id:` the event sequence",
+            content_type = "text/event-stream", body = MonitorEventFrame),
+        (status = 404, description = "Monitor not found", body = AppResponseError),
+        (status = 503, description = "Service unavailable", body = AppResponseError)
+    ),
+    security(("jwt" = []))
+)]
+pub async fn stream_monitor_events(
+    State(state): State<AppState>,
+    Path(monitor_id): Path<u32>,
+    headers: HeaderMap,
+) -> AppResult<Sse<impl Stream<Item = Result<Event, Infallible>>>> {
+    let source_router = state.source_router.as_ref().ok_or_else(|| {
+        AppError::ServiceUnavailableError("Live streaming not configured".to_string())
+    })?;
+
+    // `get_source` starts (or keeps) the reader connected; holding the source
+    // for the connection's lifetime, with a live event receiver, keeps the
+    // idle reaper from disconnecting it while nobody watches video.
+    let source = source_router.get_source(monitor_id).await.map_err(|e| {
+        AppError::NotFoundError(crate::error::Resource {
+            resource_type: crate::error::ResourceType::Monitor,
+            details: vec![
+                ("monitor_id".to_string(), monitor_id.to_string()),
+                ("reason".to_string(), e.to_string()),
+            ],
+        })
+    })?;
+
+    if let Some(last_id) = headers
+        .get("last-event-id")
+        .and_then(|value| value.to_str().ok())
+    {
+        debug!(
+            "Monitor {} events: client resumed after id {} (no replay; snapshot re-syncs)",
+            monitor_id, last_id
+        );
+    }
+
+    // Subscribe before reading the status so no transition falls between the
+    // two; at worst the client sees one twice.
+    let events = source.subscribe_events();
+    let initial = source.current_status();
+
+    let stream = futures_util::stream::unfold(
+        (source, events, initial),
+        move |(source, mut events, pending)| async move {
+            if let Some(snapshot) = pending {
+                let frame = monitor_event_sse(monitor_id, &snapshot, false);
+                return Some((Ok(frame), (source, events, None)));
+            }
+            loop {
+                match events.recv().await {
+                    Ok(ev) => {
+                        let frame = monitor_event_sse(monitor_id, &ev, true);
+                        return Some((Ok(frame), (source, events, None)));
+                    }
+                    Err(broadcast::error::RecvError::Lagged(skipped)) => {
+                        warn!(
+                            "Monitor {} events: subscriber lagged by {} events, re-syncing",
+                            monitor_id, skipped
+                        );
+                        if let Some(snapshot) = source.current_status() {
+                            let frame = monitor_event_sse(monitor_id, &snapshot, false);
+                            return Some((Ok(frame), (source, events, None)));
+                        }
+                    }
+                    Err(broadcast::error::RecvError::Closed) => return None,
+                }
+            }
+        },
+    );
+
+    Ok(Sse::new(stream).keep_alive(KeepAlive::new().interval(MONITOR_EVENTS_KEEPALIVE)))
+}
+
+/// Render one EVENT as an SSE frame. Live events carry their sequence as the
+/// `id:`; replayed status snapshots do not, since their sequence was already
+/// delivered (or deliberately skipped).
+fn monitor_event_sse(monitor_id: u32, ev: &MonitorEvent, with_id: bool) -> Event {
+    let frame = MonitorEventFrame::from_event(monitor_id, ev);
+    let event = Event::default()
+        .event(frame.code.as_str())
+        .data(serde_json::to_string(&frame).unwrap_or_default());
+    if with_id {
+        event.id(ev.sequence.to_string())
+    } else {
+        event
+    }
+}
+
 // ============================================================================
 // Source Statistics
 // ============================================================================
@@ -1358,6 +1906,41 @@ pub async fn get_live_sources(State(state): State<AppState>) -> AppResult<Json<s
 mod tests {
     use super::*;
 
+    #[test]
+    fn monitor_event_frame_maps_names_and_wall_clock() {
+        use crate::streaming::source::protocol::EVENT_CONNECTION_FAILED;
+
+        let ev = MonitorEvent {
+            code: EVENT_CONNECTION_FAILED,
+            wall_clock_us: Some(1_781_428_303_501_000),
+            message: Some("rtsp timeout".to_string()),
+            json_detail: Some(r#"{"retry_in_s":5}"#.to_string()),
+            sequence: 42,
+            generation: 4,
+            ..MonitorEvent::default()
+        };
+        let frame = MonitorEventFrame::from_event(3, &ev);
+        assert_eq!(frame.monitor_id, 3);
+        assert_eq!(frame.code, "connection_failed");
+        assert_eq!(frame.generation, 4);
+        assert_eq!(
+            frame.wall_clock.as_deref(),
+            Some("2026-06-14T09:11:43.501Z")
+        );
+        assert_eq!(frame.json_detail.unwrap()["retry_in_s"], 5);
+
+        // Malformed JSON detail is dropped rather than failing the frame.
+        let ev = MonitorEvent {
+            code: 0x09FF,
+            json_detail: Some("{not json".to_string()),
+            ..MonitorEvent::default()
+        };
+        let frame = MonitorEventFrame::from_event(3, &ev);
+        assert_eq!(frame.code, "0x09ff");
+        assert!(frame.json_detail.is_none());
+        assert!(frame.wall_clock.is_none());
+    }
+
     #[test]
     fn test_parse_segment_sequence() {
         assert_eq!(parse_segment_sequence("segment_00001.m4s"), Some(1));
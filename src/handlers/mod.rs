@@ -14,6 +14,7 @@ pub mod frames;
 pub mod groups;
 pub mod groups_monitors;
 pub mod groups_permissions;
+pub mod jobs;
 pub mod logs;
 pub mod manufacturers;
 pub mod models;
@@ -21,10 +22,14 @@ pub mod monitor_presets;
 pub mod monitor_status;
 pub mod monitors_permissions;
 pub mod montage_layouts;
+pub mod notification_rules;
 pub mod object_types;
 pub mod openapi;
+pub mod previews;
 pub mod ptz;
 pub mod reports;
+pub mod retention;
+pub mod saved_searches;
 pub mod server_stats;
 pub mod servers;
 pub mod sessions;
@@ -34,6 +39,7 @@ pub mod states;
 pub mod stats;
 pub mod storage;
 pub mod tags;
+pub mod timeline;
 pub mod triggers_x10;
 pub mod user_preferences;
 pub mod users;
@@ -41,6 +47,7 @@ pub mod zone_presets;
 pub mod zones;
 
 pub mod auth;
+pub mod event_stream;
 pub mod events;
 pub mod events_playback;
 pub mod live;
@@ -0,0 +1,157 @@
+use crate::dto::request::notification_rules::{
+    CreateNotificationRuleRequest, UpdateNotificationRuleRequest,
+};
+use crate::dto::response::notification_rules::{
+    NotificationRuleResponse, PaginatedNotificationDeliveriesResponse,
+    PaginatedNotificationRulesResponse,
+};
+use crate::dto::PaginationParams;
+use crate::error::{AppError, AppResult};
+use crate::server::state::AppState;
+use crate::util::claim::UserClaims;
+use axum::{
+    extract::{Path, Query, State},
+    Json,
+};
+use garde::Validate;
+
+/// List the caller's notification rules (all users' with System View).
+///
+/// - Requires a valid JWT.
+#[utoipa::path(
+    get,
+    path = "/api/v3/notification-rules",
+    params(
+        ("page" = Option<u64>, Query, description = "Page number (1-indexed)", example = 1),
+        ("page_size" = Option<u64>, Query, description = "Items per page (max 1000)", example = 25)
+    ),
+    responses((status = 200, description = "Paginated list of notification rules", body = PaginatedNotificationRulesResponse)),
+    tag = "Notifications",
+    security(("jwt" = []))
+)]
+pub async fn list_notification_rules(
+    State(state): State<AppState>,
+    claims: UserClaims,
+    Query(params): Query<PaginationParams>,
+) -> AppResult<Json<PaginatedNotificationRulesResponse>> {
+    let result =
+        crate::service::notification_rules::list_paginated(&state, &params, &claims).await?;
+    Ok(Json(PaginatedNotificationRulesResponse::from(result)))
+}
+
+/// Get a single notification rule by id.
+///
+/// - Requires a valid JWT.
+#[utoipa::path(
+    get,
+    path = "/api/v3/notification-rules/{id}",
+    params(("id" = u64, Path, description = "Notification rule ID")),
+    responses((status = 200, description = "Notification rule details", body = NotificationRuleResponse)),
+    tag = "Notifications",
+    security(("jwt" = []))
+)]
+pub async fn get_notification_rule(
+    Path(id): Path<u64>,
+    State(state): State<AppState>,
+    claims: UserClaims,
+) -> AppResult<Json<NotificationRuleResponse>> {
+    let item = crate::service::notification_rules::get_by_id(&state, id, &claims).await?;
+    Ok(Json(item))
+}
+
+/// Create a notification rule owned by the caller.
+///
+/// - The target must suit the channel, `score_threshold` needs a threshold,
+///   and a predicate must compile; anything else is a 400.
+/// - `storage_health` rules need System View.
+/// - Requires a valid JWT.
+#[utoipa::path(
+    post,
+    path = "/api/v3/notification-rules",
+    request_body = CreateNotificationRuleRequest,
+    responses((status = 201, description = "Created notification rule", body = NotificationRuleResponse)),
+    tag = "Notifications",
+    security(("jwt" = []))
+)]
+pub async fn create_notification_rule(
+    State(state): State<AppState>,
+    claims: UserClaims,
+    Json(req): Json<CreateNotificationRuleRequest>,
+) -> AppResult<(axum::http::StatusCode, Json<NotificationRuleResponse>)> {
+    req.validate().map_err(AppError::InvalidInputError)?;
+    let item = crate::service::notification_rules::create(&state, req, &claims).await?;
+    Ok((axum::http::StatusCode::CREATED, Json(item)))
+}
+
+/// Update a notification rule.
+///
+/// - Only fields present in the body are changed; `null` clears `predicate`,
+///   `threshold` or `template`.
+/// - Requires a valid JWT.
+#[utoipa::path(
+    put,
+    path = "/api/v3/notification-rules/{id}",
+    params(("id" = u64, Path, description = "Notification rule ID")),
+    request_body = UpdateNotificationRuleRequest,
+    responses((status = 200, description = "Updated notification rule", body = NotificationRuleResponse)),
+    tag = "Notifications",
+    security(("jwt" = []))
+)]
+pub async fn update_notification_rule(
+    Path(id): Path<u64>,
+    State(state): State<AppState>,
+    claims: UserClaims,
+    Json(req): Json<UpdateNotificationRuleRequest>,
+) -> AppResult<Json<NotificationRuleResponse>> {
+    req.validate().map_err(AppError::InvalidInputError)?;
+    let item = crate::service::notification_rules::update(&state, id, req, &claims).await?;
+    Ok(Json(item))
+}
+
+/// Delete a notification rule by id.
+///
+/// - Responds 204 on success, 404 if not found.
+/// - Requires a valid JWT.
+#[utoipa::path(
+    delete,
+    path = "/api/v3/notification-rules/{id}",
+    params(("id" = u64, Path, description = "Notification rule ID")),
+    responses((status = 204, description = "Deleted notification rule")),
+    tag = "Notifications",
+    security(("jwt" = []))
+)]
+pub async fn delete_notification_rule(
+    Path(id): Path<u64>,
+    State(state): State<AppState>,
+    claims: UserClaims,
+) -> AppResult<axum::http::StatusCode> {
+    crate::service::notification_rules::delete(&state, id, &claims).await?;
+    Ok(axum::http::StatusCode::NO_CONTENT)
+}
+
+/// List the deliveries a notification rule has made, newest first.
+///
+/// - Failed deliveries carry the channel's error.
+/// - Requires a valid JWT.
+#[utoipa::path(
+    get,
+    path = "/api/v3/notification-rules/{id}/deliveries",
+    params(
+        ("id" = u64, Path, description = "Notification rule ID"),
+        ("page" = Option<u64>, Query, description = "Page number (1-indexed)", example = 1),
+        ("page_size" = Option<u64>, Query, description = "Items per page (max 1000)", example = 25)
+    ),
+    responses((status = 200, description = "Paginated delivery history", body = PaginatedNotificationDeliveriesResponse)),
+    tag = "Notifications",
+    security(("jwt" = []))
+)]
+pub async fn list_notification_deliveries(
+    Path(id): Path<u64>,
+    State(state): State<AppState>,
+    claims: UserClaims,
+    Query(params): Query<PaginationParams>,
+) -> AppResult<Json<PaginatedNotificationDeliveriesResponse>> {
+    let result =
+        crate::service::notification_rules::list_deliveries(&state, id, &params, &claims).await?;
+    Ok(Json(PaginatedNotificationDeliveriesResponse::from(result)))
+}
@@ -99,24 +99,41 @@ use crate::util::claim::UserClaims;
         crate::handlers::events::get_event_counts_by_monitor,
         crate::handlers::events::create_event,
         crate::handlers::events::delete_event,
+        crate::handlers::events::restore_event,
+        crate::handlers::events::bulk_events,
+        crate::handlers::events::export_events,
+        crate::handlers::events::download_event_export,
+        crate::handlers::events::create_event_job,
         crate::handlers::events::get_event,
         crate::handlers::events::list_events,
         crate::handlers::events::update_event,
 
         // event playback
         crate::handlers::events_playback::get_event_playlist,
+        crate::handlers::events_playback::get_monitor_playback_playlist,
         crate::handlers::events_playback::get_event_video,
+        crate::handlers::events_playback::get_event_clip,
         crate::handlers::events_playback::get_event_stream_video,
         crate::handlers::events_playback::get_event_stream_media,
         crate::handlers::events_playback::get_event_thumbnail,
         crate::handlers::events_playback::get_event_info,
         crate::handlers::events_playback::get_event_init,
         crate::handlers::events_playback::get_event_segment,
+        crate::handlers::previews::get_event_preview_webp,
+        crate::handlers::previews::get_event_preview_gif,
+        crate::handlers::previews::get_event_contact_sheet,
+
+        // event firehose
+        crate::handlers::event_stream::stream_events,
+        crate::handlers::event_stream::stream_events_websocket,
 
         // event summaries
         crate::handlers::event_summaries::list_event_summaries,
         crate::handlers::event_summaries::get_event_summary,
 
+        // monitor timeline
+        crate::handlers::timeline::get_monitor_timeline,
+
         // natural-language / semantic search
         crate::handlers::search::search_events,
         crate::handlers::search::similar_events,
@@ -135,6 +152,24 @@ use crate::util::claim::UserClaims;
         crate::handlers::filters::update_filter,
         crate::handlers::filters::preview_filter,
 
+        // saved searches
+        crate::handlers::saved_searches::create_saved_search,
+        crate::handlers::saved_searches::delete_saved_search,
+        crate::handlers::saved_searches::get_saved_search,
+        crate::handlers::saved_searches::list_saved_searches,
+        crate::handlers::saved_searches::update_saved_search,
+
+        // jobs
+        crate::handlers::jobs::get_job,
+
+        // notification rules
+        crate::handlers::notification_rules::create_notification_rule,
+        crate::handlers::notification_rules::delete_notification_rule,
+        crate::handlers::notification_rules::get_notification_rule,
+        crate::handlers::notification_rules::list_notification_rules,
+        crate::handlers::notification_rules::update_notification_rule,
+        crate::handlers::notification_rules::list_notification_deliveries,
+
         // frames
         crate::handlers::frames::create_frame,
         crate::handlers::frames::delete_frame,
@@ -171,9 +206,17 @@ use crate::util::claim::UserClaims;
         crate::handlers::live::get_live_media_playlist,
         crate::handlers::live::get_live_init_segment,
         crate::handlers::live::get_live_segment,
+        crate::handlers::live::get_live_rendition_playlist,
+        crate::handlers::live::get_live_rendition_init_segment,
+        crate::handlers::live::get_live_rendition_segment,
         crate::handlers::live::get_live_sources,
         crate::handlers::live::get_monitor_snapshot,
+        crate::handlers::live::get_live_mjpeg,
+        crate::handlers::live::stream_monitor_events,
         crate::handlers::live::webrtc_websocket_handler,
+        crate::handlers::live::whep_offer,
+        crate::handlers::live::whep_trickle_ice,
+        crate::handlers::live::whep_delete,
 
         // logs
         crate::handlers::logs::get_log,
@@ -207,6 +250,9 @@ use crate::util::claim::UserClaims;
         crate::handlers::monitor_pipeline::delete_monitor_pipeline,
         crate::handlers::monitor_pipeline::enable_monitor_zmnext,
         crate::handlers::monitor_pipeline::disable_monitor_zmnext,
+        crate::handlers::retention::get_monitor_retention,
+        crate::handlers::retention::put_monitor_retention,
+        crate::handlers::retention::delete_monitor_retention,
 
         // monitor presets
         crate::handlers::monitor_presets::create_monitor_preset,
@@ -275,6 +321,17 @@ use crate::util::claim::UserClaims;
         crate::handlers::ptz::goto_home,
         crate::handlers::ptz::move_absolute,
         crate::handlers::ptz::move_relative,
+        crate::handlers::ptz::point,
+        crate::handlers::ptz::area,
+        crate::handlers::ptz::list_tours,
+        crate::handlers::ptz::create_tour,
+        crate::handlers::ptz::get_tour,
+        crate::handlers::ptz::update_tour,
+        crate::handlers::ptz::delete_tour,
+        crate::handlers::ptz::start_tour,
+        crate::handlers::ptz::stop_tour,
+        crate::handlers::ptz::acquire_control,
+        crate::handlers::ptz::release_control,
 
         // reports
         crate::handlers::reports::create_report,
@@ -342,6 +399,11 @@ use crate::util::claim::UserClaims;
         crate::handlers::storage::get_storage,
         crate::handlers::storage::list_storage,
         crate::handlers::storage::update_storage,
+        crate::handlers::storage::get_storage_health,
+        crate::handlers::storage::reap_storage,
+        crate::handlers::retention::get_storage_retention,
+        crate::handlers::retention::put_storage_retention,
+        crate::handlers::retention::delete_storage_retention,
 
         // tags
         crate::handlers::tags::create_tag,
@@ -461,6 +523,11 @@ use crate::util::claim::UserClaims;
             // event summaries
             crate::dto::response::event_summaries::EventSummaryResponse,
 
+            // monitor timeline
+            crate::dto::response::timeline::TimelineResponse,
+            crate::dto::response::timeline::MonitorTimeline,
+            crate::dto::response::timeline::TimelineBucket,
+
             // events tags
             crate::dto::request::events_tags::CreateEventTagRequest,
             crate::dto::response::events_tags::EventTagResponse,
@@ -479,6 +546,34 @@ use crate::util::claim::UserClaims;
             crate::dto::request::filter_ast::FilterSort,
             crate::dto::response::filters::FilterResponse,
 
+            // saved searches and bulk event jobs
+            crate::dto::request::saved_searches::CreateSavedSearchRequest,
+            crate::dto::request::saved_searches::UpdateSavedSearchRequest,
+            crate::dto::response::saved_searches::SavedSearchResponse,
+            crate::dto::response::saved_searches::PaginatedSavedSearchesResponse,
+            crate::dto::request::bulk_events::BulkEventsRequest,
+            crate::dto::request::bulk_events::BulkEventAction,
+            crate::dto::request::event_export::EventExportRequest,
+            crate::dto::request::event_export::ExportFormat,
+            crate::dto::request::event_jobs::EventJobRequest,
+            crate::dto::request::event_jobs::EventJobKind,
+            crate::dto::response::jobs::JobResponse,
+            crate::entity::sea_orm_active_enums::JobKind,
+            crate::entity::sea_orm_active_enums::JobStatus,
+
+            // notification rules
+            crate::dto::request::notification_rules::CreateNotificationRuleRequest,
+            crate::dto::request::notification_rules::UpdateNotificationRuleRequest,
+            crate::dto::request::notification_rules::NotificationTemplate,
+            crate::dto::response::notification_rules::NotificationRuleResponse,
+            crate::dto::response::notification_rules::PaginatedNotificationRulesResponse,
+            crate::dto::response::notification_rules::NotificationDeliveryResponse,
+            crate::dto::response::notification_rules::DeliveryAttempt,
+            crate::dto::response::notification_rules::PaginatedNotificationDeliveriesResponse,
+            crate::entity::sea_orm_active_enums::NotificationTrigger,
+            crate::entity::sea_orm_active_enums::NotificationChannel,
+            crate::entity::sea_orm_active_enums::DeliveryStatus,
+
             // frames
             crate::dto::request::frames::CreateFrameRequest,
             crate::dto::request::frames::UpdateFrameRequest,
@@ -503,9 +598,15 @@ use crate::util::claim::UserClaims;
             crate::handlers::live::StartLiveResponse,
             crate::handlers::live::LiveStatsResponse,
             crate::handlers::live::LiveProtocolStatus,
+            crate::handlers::live::MonitorEventFrame,
             // WebRTC signaling WebSocket message envelope (see webrtc_websocket_handler)
             crate::handlers::live::WebRtcSignalingMessage,
 
+            // event firehose
+            crate::service::event_feed::EventNotification,
+            crate::service::event_feed::EventNotificationKind,
+            crate::service::event_feed::EventNotificationSource,
+
             // logs
             crate::dto::request::logs::LogQueryParams,
             crate::dto::response::logs::LogResponse,
@@ -560,11 +661,22 @@ use crate::util::claim::UserClaims;
             crate::dto::request::ptz::PtzPresetRequest,
             crate::dto::request::ptz::PtzAbsoluteRequest,
             crate::dto::request::ptz::PtzRelativeRequest,
+            crate::dto::request::ptz::PtzPointRequest,
+            crate::dto::request::ptz::PtzAreaRequest,
             crate::dto::response::ptz::PtzCommandResponse,
             crate::dto::response::ptz::PtzStatusResponse,
             crate::dto::response::ptz::PtzCapabilitiesResponse,
             crate::dto::response::ptz::PtzProtocolListResponse,
             crate::dto::response::ptz::PtzProtocolInfo,
+            crate::dto::request::ptz::PtzTourStep,
+            crate::dto::request::ptz::PtzTourDay,
+            crate::dto::request::ptz::PtzTourWindow,
+            crate::dto::request::ptz::CreatePtzTourRequest,
+            crate::dto::request::ptz::UpdatePtzTourRequest,
+            crate::dto::response::ptz::PtzTourResponse,
+            crate::dto::response::ptz::PtzTourTrigger,
+            crate::dto::response::ptz::PtzTourStatusResponse,
+            crate::dto::response::ptz::PtzControlLeaseResponse,
             crate::ptz::capabilities::PtzCapabilities,
             crate::ptz::capabilities::PowerCapabilities,
             crate::ptz::capabilities::PanTiltCapabilities,
@@ -638,6 +750,15 @@ use crate::util::claim::UserClaims;
             crate::dto::response::storage::PaginatedStorageResponse,
             crate::dto::response::storage::StorageResponse,
             crate::handlers::storage::UpdateStorageRequest,
+            crate::dto::request::storage::ReapStorageRequest,
+            crate::dto::response::storage::StorageHealthResponse,
+            crate::dto::response::storage::StorageMonitorUsage,
+            crate::dto::response::storage::StorageReapResponse,
+            crate::dto::response::storage::ReapedEventResponse,
+            crate::dto::request::retention::StorageRetentionRequest,
+            crate::dto::request::retention::MonitorRetentionRequest,
+            crate::dto::response::retention::StorageRetentionResponse,
+            crate::dto::response::retention::MonitorRetentionResponse,
 
             // streaming
             crate::dto::response::StreamEndpoints,
@@ -695,6 +816,7 @@ use crate::util::claim::UserClaims;
         (name = "Groups", description = "Group management endpoints"),
         (name = "Groups Monitors", description = "Group-monitor associations"),
         (name = "Groups Permissions", description = "Group permission management"),
+        (name = "Jobs", description = "Background job status"),
         (name = "Live Streaming", description = "Live video streaming (HLS, WebRTC)"),
         (name = "Logs", description = "Log endpoints"),
         (name = "Manufacturers", description = "Camera manufacturers"),
@@ -704,9 +826,11 @@ use crate::util::claim::UserClaims;
         (name = "Monitors", description = "Monitor management endpoints"),
         (name = "Monitors Permissions", description = "Monitor permission management"),
         (name = "Montage Layouts", description = "UI montage layouts"),
+        (name = "Notifications", description = "Notification rules and their delivery history"),
         (name = "Object Types", description = "Object detection type definitions"),
         (name = "PTZ", description = "Pan-Tilt-Zoom camera control"),
         (name = "Reports", description = "Report definitions and templates"),
+        (name = "Saved Searches", description = "Saved event filter searches"),
         (name = "Search", description = "Natural-language / semantic event search"),
         (name = "Server", description = "Server information endpoints"),
         (name = "Server Stats", description = "Server performance statistics"),
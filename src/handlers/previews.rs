@@ -0,0 +1,226 @@
+//! Animated-preview and contact-sheet HTTP handlers.
+//!
+//! Same row-level ACL as the rest of event playback: an event outside the
+//! caller's monitors answers the same `NotFound` as a missing one. Only
+//! finished recordings are previewed — a growing file has no index to seek in,
+//! and its render would be stale as soon as it was cached.
+
+use axum::{
+    body::Body,
+    extract::{Path, Query, State},
+    http::{header, StatusCode},
+    response::Response,
+};
+use rust_decimal::prelude::ToPrimitive;
+use serde::Deserialize;
+use tracing::{debug, warn};
+
+use crate::error::{AppError, AppResult, Resource, ResourceType};
+use crate::handlers::events_playback::{
+    event_is_in_progress, get_event_entity, get_event_video_path, monitor_orientation,
+    EventPlaybackPath,
+};
+use crate::repo;
+use crate::server::state::AppState;
+use crate::service::monitor_acl::MonitorScope;
+use crate::service::previews::{PreviewError, PreviewKind, PreviewSource};
+
+/// Most tiles one contact sheet may hold.
+const MAX_SHEET_FRAMES: u32 = 60;
+/// Most tiles per contact-sheet row.
+const MAX_SHEET_COLUMNS: u32 = 12;
+
+/// Query parameters for a contact sheet.
+#[derive(Debug, Deserialize)]
+pub struct ContactSheetQuery {
+    /// Number of tiles (1–60). Defaults to `contact_sheet_frames`.
+    pub frames: Option<u32>,
+    /// Tiles per row (1–12). Defaults to `contact_sheet_columns`.
+    pub columns: Option<u32>,
+}
+
+/// Map a [`PreviewError`] onto an HTTP error.
+fn map_err(event_id: u64, err: PreviewError) -> AppError {
+    match err {
+        PreviewError::EncoderUnavailable(m) => AppError::ServiceUnavailableError(m),
+        PreviewError::NoFrames => AppError::NotFoundError(Resource {
+            resource_type: ResourceType::Event,
+            details: vec![
+                ("event_id".to_string(), event_id.to_string()),
+                ("reason".to_string(), "No decodable frames".to_string()),
+            ],
+        }),
+        PreviewError::Io(e) => AppError::IoError(e),
+        e => AppError::InternalServerError(format!("preview render failed: {e}")),
+    }
+}
+
+/// Gather what the renderer needs to know about an event's recording.
+async fn preview_source(
+    state: &AppState,
+    event_id: u64,
+    scope: &MonitorScope,
+) -> AppResult<PreviewSource> {
+    let event = get_event_entity(state, event_id, scope).await?;
+    if event_is_in_progress(&event) {
+        return Err(AppError::NotFoundError(Resource {
+            resource_type: ResourceType::Event,
+            details: vec![
+                ("event_id".to_string(), event_id.to_string()),
+                ("reason".to_string(), "Event is still recording".to_string()),
+            ],
+        }));
+    }
+    let video = get_event_video_path(state, event_id, scope).await?;
+    let info = crate::streaming::probe::probe_event_media(event_id, video.clone())
+        .await
+        .map_err(AppError::InternalServerError)?;
+
+    // Without a max-score frame the animation centres on the recording.
+    let focus = match event.max_score_frame_id {
+        Some(frame_id) => {
+            match repo::frames::find_by_event_frame(state.db(), event_id, frame_id).await {
+                Ok(frame) => frame.and_then(|f| f.delta.to_f64()),
+                Err(e) => {
+                    warn!("could not read max-score frame of event {event_id}: {e}");
+                    None
+                }
+            }
+        }
+        None => None,
+    };
+
+    Ok(PreviewSource {
+        event_id,
+        video,
+        info,
+        focus,
+        started: event.start_date_time,
+        orientation: monitor_orientation(state, event.monitor_id).await,
+    })
+}
+
+async fn serve_preview(
+    state: &AppState,
+    event_id: u64,
+    scope: &MonitorScope,
+    kind: PreviewKind,
+) -> Result<Response, AppError> {
+    let source = preview_source(state, event_id, scope).await?;
+    debug!("Serving {kind:?} preview for event {event_id}");
+    let bytes = state
+        .preview_service
+        .get(source, kind)
+        .await
+        .map_err(|e| map_err(event_id, e))?;
+
+    Ok(Response::builder()
+        .status(StatusCode::OK)
+        .header(header::CONTENT_TYPE, kind.content_type())
+        .header(header::CACHE_CONTROL, "private, max-age=86400")
+        .header(header::CONTENT_LENGTH, bytes.len().to_string())
+        .body(Body::from(bytes))
+        .unwrap())
+}
+
+/// Get an animated WebP preview of an event
+///
+/// A few seconds of the recording around its highest-scoring frame, looping,
+/// scaled to `[previews].width` and rotated to the monitor's orientation.
+/// Rendered on first request and cached.
+#[utoipa::path(
+    get,
+    path = "/api/v3/events/{id}/preview.webp",
+    operation_id = "getEventPreviewWebp",
+    tag = "Event Playback",
+    params(("id" = u64, Path, description = "Event ID")),
+    responses(
+        (status = 200, description = "Looping animation", content_type = "image/webp"),
+        (status = 404, description = "Event or video not found, or still recording", body = crate::error::AppResponseError),
+        (status = 503, description = "ffmpeg was built without libwebp", body = crate::error::AppResponseError),
+        (status = 500, description = "Internal server error", body = crate::error::AppResponseError)
+    ),
+    security(("jwt" = []))
+)]
+pub async fn get_event_preview_webp(
+    State(state): State<AppState>,
+    Path(path): Path<EventPlaybackPath>,
+    scope: MonitorScope,
+) -> Result<Response, AppError> {
+    serve_preview(&state, path.id, &scope, PreviewKind::Webp).await
+}
+
+/// Get an animated GIF preview of an event
+///
+/// The same animation as `preview.webp`, for clients that cannot show WebP.
+/// Larger and lower in colour depth.
+#[utoipa::path(
+    get,
+    path = "/api/v3/events/{id}/preview.gif",
+    operation_id = "getEventPreviewGif",
+    tag = "Event Playback",
+    params(("id" = u64, Path, description = "Event ID")),
+    responses(
+        (status = 200, description = "Looping animation", content_type = "image/gif"),
+        (status = 404, description = "Event or video not found, or still recording", body = crate::error::AppResponseError),
+        (status = 500, description = "Internal server error", body = crate::error::AppResponseError)
+    ),
+    security(("jwt" = []))
+)]
+pub async fn get_event_preview_gif(
+    State(state): State<AppState>,
+    Path(path): Path<EventPlaybackPath>,
+    scope: MonitorScope,
+) -> Result<Response, AppError> {
+    serve_preview(&state, path.id, &scope, PreviewKind::Gif).await
+}
+
+/// Get a contact sheet of an event
+///
+/// A JPEG grid of evenly spaced frames, each labelled with its wall-clock
+/// time. Rendered on first request and cached per tile count and layout.
+#[utoipa::path(
+    get,
+    path = "/api/v3/events/{id}/contact-sheet.jpg",
+    operation_id = "getEventContactSheet",
+    tag = "Event Playback",
+    params(
+        ("id" = u64, Path, description = "Event ID"),
+        ("frames" = Option<u32>, Query, description = "Number of tiles, 1–60", example = 12),
+        ("columns" = Option<u32>, Query, description = "Tiles per row, 1–12", example = 4)
+    ),
+    responses(
+        (status = 200, description = "Contact sheet", content_type = "image/jpeg"),
+        (status = 400, description = "frames or columns out of range", body = crate::error::AppResponseError),
+        (status = 404, description = "Event or video not found, or still recording", body = crate::error::AppResponseError),
+        (status = 500, description = "Internal server error", body = crate::error::AppResponseError)
+    ),
+    security(("jwt" = []))
+)]
+pub async fn get_event_contact_sheet(
+    State(state): State<AppState>,
+    Path(path): Path<EventPlaybackPath>,
+    Query(query): Query<ContactSheetQuery>,
+    scope: MonitorScope,
+) -> Result<Response, AppError> {
+    let config = state.preview_service.config();
+    let frames = query.frames.unwrap_or(config.contact_sheet_frames);
+    let columns = query.columns.unwrap_or(config.contact_sheet_columns);
+    if !(1..=MAX_SHEET_FRAMES).contains(&frames) {
+        return Err(AppError::BadRequestError(format!(
+            "frames must be between 1 and {MAX_SHEET_FRAMES}"
+        )));
+    }
+    if !(1..=MAX_SHEET_COLUMNS).contains(&columns) {
+        return Err(AppError::BadRequestError(format!(
+            "columns must be between 1 and {MAX_SHEET_COLUMNS}"
+        )));
+    }
+    serve_preview(
+        &state,
+        path.id,
+        &scope,
+        PreviewKind::ContactSheet { frames, columns },
+    )
+    .await
+}
@@ -8,15 +8,17 @@ use garde::Validate;
 use tracing::instrument;
 
 use crate::dto::request::ptz::{
-    PtzAbsoluteRequest, PtzFocusRequest, PtzMoveRequest, PtzPresetRequest, PtzRelativeRequest,
-    PtzZoomRequest,
+    CreatePtzTourRequest, PtzAbsoluteRequest, PtzAreaRequest, PtzFocusRequest, PtzMoveRequest,
+    PtzPointRequest, PtzPresetRequest, PtzRelativeRequest, PtzZoomRequest, UpdatePtzTourRequest,
 };
 use crate::dto::response::ptz::{
-    PtzCapabilitiesResponse, PtzCommandResponse, PtzProtocolListResponse, PtzStatusResponse,
+    PtzCapabilitiesResponse, PtzCommandResponse, PtzControlLeaseResponse, PtzProtocolListResponse,
+    PtzStatusResponse, PtzTourResponse, PtzTourStatusResponse,
 };
 use crate::error::{AppError, AppResponseError, AppResult};
 use crate::server::state::AppState;
 use crate::service;
+use crate::util::claim::UserClaims;
 
 /// Get PTZ status for a monitor
 #[utoipa::path(
@@ -101,20 +103,23 @@ pub async fn list_protocols(
     responses(
         (status = 200, description = "Command executed", body = PtzCommandResponse),
         (status = 400, description = "Bad request", body = AppResponseError),
+        (status = 409, description = "Another user holds the camera", body = AppResponseError),
         (status = 404, description = "Monitor not found", body = AppResponseError),
         (status = 500, description = "Internal server error", body = AppResponseError)
     ),
     security(("jwt" = []))
 )]
-#[instrument(skip(state))]
+#[instrument(skip(state, claims))]
 pub async fn move_up(
     Path(id): Path<u32>,
     State(state): State<AppState>,
+    claims: UserClaims,
     Json(request): Json<PtzMoveRequest>,
 ) -> AppResult<Json<PtzCommandResponse>> {
     request.validate().map_err(AppError::InvalidInputError)?;
     let ptz_manager = state.ptz_manager();
-    let result = service::ptz::move_direction(&state, ptz_manager, id, "up", request).await?;
+    let result =
+        service::ptz::move_direction(&state, ptz_manager, &claims, id, "up", request).await?;
     Ok(Json(result))
 }
 
@@ -129,20 +134,23 @@ pub async fn move_up(
     responses(
         (status = 200, description = "Command executed", body = PtzCommandResponse),
         (status = 400, description = "Bad request", body = AppResponseError),
+        (status = 409, description = "Another user holds the camera", body = AppResponseError),
         (status = 404, description = "Monitor not found", body = AppResponseError),
         (status = 500, description = "Internal server error", body = AppResponseError)
     ),
     security(("jwt" = []))
 )]
-#[instrument(skip(state))]
+#[instrument(skip(state, claims))]
 pub async fn move_down(
     Path(id): Path<u32>,
     State(state): State<AppState>,
+    claims: UserClaims,
     Json(request): Json<PtzMoveRequest>,
 ) -> AppResult<Json<PtzCommandResponse>> {
     request.validate().map_err(AppError::InvalidInputError)?;
     let ptz_manager = state.ptz_manager();
-    let result = service::ptz::move_direction(&state, ptz_manager, id, "down", request).await?;
+    let result =
+        service::ptz::move_direction(&state, ptz_manager, &claims, id, "down", request).await?;
     Ok(Json(result))
 }
 
@@ -157,20 +165,23 @@ pub async fn move_down(
     responses(
         (status = 200, description = "Command executed", body = PtzCommandResponse),
         (status = 400, description = "Bad request", body = AppResponseError),
+        (status = 409, description = "Another user holds the camera", body = AppResponseError),
         (status = 404, description = "Monitor not found", body = AppResponseError),
         (status = 500, description = "Internal server error", body = AppResponseError)
     ),
     security(("jwt" = []))
 )]
-#[instrument(skip(state))]
+#[instrument(skip(state, claims))]
 pub async fn move_left(
     Path(id): Path<u32>,
     State(state): State<AppState>,
+    claims: UserClaims,
     Json(request): Json<PtzMoveRequest>,
 ) -> AppResult<Json<PtzCommandResponse>> {
     request.validate().map_err(AppError::InvalidInputError)?;
     let ptz_manager = state.ptz_manager();
-    let result = service::ptz::move_direction(&state, ptz_manager, id, "left", request).await?;
+    let result =
+        service::ptz::move_direction(&state, ptz_manager, &claims, id, "left", request).await?;
     Ok(Json(result))
 }
 
@@ -185,20 +196,23 @@ pub async fn move_left(
     responses(
         (status = 200, description = "Command executed", body = PtzCommandResponse),
         (status = 400, description = "Bad request", body = AppResponseError),
+        (status = 409, description = "Another user holds the camera", body = AppResponseError),
         (status = 404, description = "Monitor not found", body = AppResponseError),
         (status = 500, description = "Internal server error", body = AppResponseError)
     ),
     security(("jwt" = []))
 )]
-#[instrument(skip(state))]
+#[instrument(skip(state, claims))]
 pub async fn move_right(
     Path(id): Path<u32>,
     State(state): State<AppState>,
+    claims: UserClaims,
     Json(request): Json<PtzMoveRequest>,
 ) -> AppResult<Json<PtzCommandResponse>> {
     request.validate().map_err(AppError::InvalidInputError)?;
     let ptz_manager = state.ptz_manager();
-    let result = service::ptz::move_direction(&state, ptz_manager, id, "right", request).await?;
+    let result =
+        service::ptz::move_direction(&state, ptz_manager, &claims, id, "right", request).await?;
     Ok(Json(result))
 }
 
@@ -213,20 +227,23 @@ pub async fn move_right(
     responses(
         (status = 200, description = "Command executed", body = PtzCommandResponse),
         (status = 400, description = "Bad request", body = AppResponseError),
+        (status = 409, description = "Another user holds the camera", body = AppResponseError),
         (status = 404, description = "Monitor not found", body = AppResponseError),
         (status = 500, description = "Internal server error", body = AppResponseError)
     ),
     security(("jwt" = []))
 )]
-#[instrument(skip(state))]
+#[instrument(skip(state, claims))]
 pub async fn move_up_left(
     Path(id): Path<u32>,
     State(state): State<AppState>,
+    claims: UserClaims,
     Json(request): Json<PtzMoveRequest>,
 ) -> AppResult<Json<PtzCommandResponse>> {
     request.validate().map_err(AppError::InvalidInputError)?;
     let ptz_manager = state.ptz_manager();
-    let result = service::ptz::move_direction(&state, ptz_manager, id, "up-left", request).await?;
+    let result =
+        service::ptz::move_direction(&state, ptz_manager, &claims, id, "up-left", request).await?;
     Ok(Json(result))
 }
 
@@ -241,20 +258,23 @@ pub async fn move_up_left(
     responses(
         (status = 200, description = "Command executed", body = PtzCommandResponse),
         (status = 400, description = "Bad request", body = AppResponseError),
+        (status = 409, description = "Another user holds the camera", body = AppResponseError),
         (status = 404, description = "Monitor not found", body = AppResponseError),
         (status = 500, description = "Internal server error", body = AppResponseError)
     ),
     security(("jwt" = []))
 )]
-#[instrument(skip(state))]
+#[instrument(skip(state, claims))]
 pub async fn move_up_right(
     Path(id): Path<u32>,
     State(state): State<AppState>,
+    claims: UserClaims,
     Json(request): Json<PtzMoveRequest>,
 ) -> AppResult<Json<PtzCommandResponse>> {
     request.validate().map_err(AppError::InvalidInputError)?;
     let ptz_manager = state.ptz_manager();
-    let result = service::ptz::move_direction(&state, ptz_manager, id, "up-right", request).await?;
+    let result =
+        service::ptz::move_direction(&state, ptz_manager, &claims, id, "up-right", request).await?;
     Ok(Json(result))
 }
 
@@ -269,21 +289,24 @@ pub async fn move_up_right(
     responses(
         (status = 200, description = "Command executed", body = PtzCommandResponse),
         (status = 400, description = "Bad request", body = AppResponseError),
+        (status = 409, description = "Another user holds the camera", body = AppResponseError),
         (status = 404, description = "Monitor not found", body = AppResponseError),
         (status = 500, description = "Internal server error", body = AppResponseError)
     ),
     security(("jwt" = []))
 )]
-#[instrument(skip(state))]
+#[instrument(skip(state, claims))]
 pub async fn move_down_left(
     Path(id): Path<u32>,
     State(state): State<AppState>,
+    claims: UserClaims,
     Json(request): Json<PtzMoveRequest>,
 ) -> AppResult<Json<PtzCommandResponse>> {
     request.validate().map_err(AppError::InvalidInputError)?;
     let ptz_manager = state.ptz_manager();
     let result =
-        service::ptz::move_direction(&state, ptz_manager, id, "down-left", request).await?;
+        service::ptz::move_direction(&state, ptz_manager, &claims, id, "down-left", request)
+            .await?;
     Ok(Json(result))
 }
 
@@ -298,21 +321,24 @@ pub async fn move_down_left(
     responses(
         (status = 200, description = "Command executed", body = PtzCommandResponse),
         (status = 400, description = "Bad request", body = AppResponseError),
+        (status = 409, description = "Another user holds the camera", body = AppResponseError),
         (status = 404, description = "Monitor not found", body = AppResponseError),
         (status = 500, description = "Internal server error", body = AppResponseError)
     ),
     security(("jwt" = []))
 )]
-#[instrument(skip(state))]
+#[instrument(skip(state, claims))]
 pub async fn move_down_right(
     Path(id): Path<u32>,
     State(state): State<AppState>,
+    claims: UserClaims,
     Json(request): Json<PtzMoveRequest>,
 ) -> AppResult<Json<PtzCommandResponse>> {
     request.validate().map_err(AppError::InvalidInputError)?;
     let ptz_manager = state.ptz_manager();
     let result =
-        service::ptz::move_direction(&state, ptz_manager, id, "down-right", request).await?;
+        service::ptz::move_direction(&state, ptz_manager, &claims, id, "down-right", request)
+            .await?;
     Ok(Json(result))
 }
 
@@ -325,18 +351,20 @@ pub async fn move_down_right(
     params(("id" = u32, Path, description = "Monitor ID")),
     responses(
         (status = 200, description = "Command executed", body = PtzCommandResponse),
+        (status = 409, description = "Another user holds the camera", body = AppResponseError),
         (status = 404, description = "Monitor not found", body = AppResponseError),
         (status = 500, description = "Internal server error", body = AppResponseError)
     ),
     security(("jwt" = []))
 )]
-#[instrument(skip(state))]
+#[instrument(skip(state, claims))]
 pub async fn move_stop(
     Path(id): Path<u32>,
     State(state): State<AppState>,
+    claims: UserClaims,
 ) -> AppResult<Json<PtzCommandResponse>> {
     let ptz_manager = state.ptz_manager();
-    let result = service::ptz::move_stop(&state, ptz_manager, id).await?;
+    let result = service::ptz::move_stop(&state, ptz_manager, &claims, id).await?;
     Ok(Json(result))
 }
 
@@ -351,20 +379,22 @@ pub async fn move_stop(
     responses(
         (status = 200, description = "Command executed", body = PtzCommandResponse),
         (status = 400, description = "Bad request", body = AppResponseError),
+        (status = 409, description = "Another user holds the camera", body = AppResponseError),
         (status = 404, description = "Monitor not found", body = AppResponseError),
         (status = 500, description = "Internal server error", body = AppResponseError)
     ),
     security(("jwt" = []))
 )]
-#[instrument(skip(state))]
+#[instrument(skip(state, claims))]
 pub async fn zoom_in(
     Path(id): Path<u32>,
     State(state): State<AppState>,
+    claims: UserClaims,
     Json(request): Json<PtzZoomRequest>,
 ) -> AppResult<Json<PtzCommandResponse>> {
     request.validate().map_err(AppError::InvalidInputError)?;
     let ptz_manager = state.ptz_manager();
-    let result = service::ptz::zoom(&state, ptz_manager, id, "in", request).await?;
+    let result = service::ptz::zoom(&state, ptz_manager, &claims, id, "in", request).await?;
     Ok(Json(result))
 }
 
@@ -379,20 +409,22 @@ pub async fn zoom_in(
     responses(
         (status = 200, description = "Command executed", body = PtzCommandResponse),
         (status = 400, description = "Bad request", body = AppResponseError),
+        (status = 409, description = "Another user holds the camera", body = AppResponseError),
         (status = 404, description = "Monitor not found", body = AppResponseError),
         (status = 500, description = "Internal server error", body = AppResponseError)
     ),
     security(("jwt" = []))
 )]
-#[instrument(skip(state))]
+#[instrument(skip(state, claims))]
 pub async fn zoom_out(
     Path(id): Path<u32>,
     State(state): State<AppState>,
+    claims: UserClaims,
     Json(request): Json<PtzZoomRequest>,
 ) -> AppResult<Json<PtzCommandResponse>> {
     request.validate().map_err(AppError::InvalidInputError)?;
     let ptz_manager = state.ptz_manager();
-    let result = service::ptz::zoom(&state, ptz_manager, id, "out", request).await?;
+    let result = service::ptz::zoom(&state, ptz_manager, &claims, id, "out", request).await?;
     Ok(Json(result))
 }
 
@@ -405,18 +437,20 @@ pub async fn zoom_out(
     params(("id" = u32, Path, description = "Monitor ID")),
     responses(
         (status = 200, description = "Command executed", body = PtzCommandResponse),
+        (status = 409, description = "Another user holds the camera", body = AppResponseError),
         (status = 404, description = "Monitor not found", body = AppResponseError),
         (status = 500, description = "Internal server error", body = AppResponseError)
     ),
     security(("jwt" = []))
 )]
-#[instrument(skip(state))]
+#[instrument(skip(state, claims))]
 pub async fn zoom_stop(
     Path(id): Path<u32>,
     State(state): State<AppState>,
+    claims: UserClaims,
 ) -> AppResult<Json<PtzCommandResponse>> {
     let ptz_manager = state.ptz_manager();
-    let result = service::ptz::zoom_stop(&state, ptz_manager, id).await?;
+    let result = service::ptz::zoom_stop(&state, ptz_manager, &claims, id).await?;
     Ok(Json(result))
 }
 
@@ -431,20 +465,22 @@ pub async fn zoom_stop(
     responses(
         (status = 200, description = "Command executed", body = PtzCommandResponse),
         (status = 400, description = "Bad request", body = AppResponseError),
+        (status = 409, description = "Another user holds the camera", body = AppResponseError),
         (status = 404, description = "Monitor not found", body = AppResponseError),
         (status = 500, description = "Internal server error", body = AppResponseError)
     ),
     security(("jwt" = []))
 )]
-#[instrument(skip(state))]
+#[instrument(skip(state, claims))]
 pub async fn focus_near(
     Path(id): Path<u32>,
     State(state): State<AppState>,
+    claims: UserClaims,
     Json(request): Json<PtzFocusRequest>,
 ) -> AppResult<Json<PtzCommandResponse>> {
     request.validate().map_err(AppError::InvalidInputError)?;
     let ptz_manager = state.ptz_manager();
-    let result = service::ptz::focus(&state, ptz_manager, id, "near", request).await?;
+    let result = service::ptz::focus(&state, ptz_manager, &claims, id, "near", request).await?;
     Ok(Json(result))
 }
 
@@ -459,20 +495,22 @@ pub async fn focus_near(
     responses(
         (status = 200, description = "Command executed", body = PtzCommandResponse),
         (status = 400, description = "Bad request", body = AppResponseError),
+        (status = 409, description = "Another user holds the camera", body = AppResponseError),
         (status = 404, description = "Monitor not found", body = AppResponseError),
         (status = 500, description = "Internal server error", body = AppResponseError)
     ),
     security(("jwt" = []))
 )]
-#[instrument(skip(state))]
+#[instrument(skip(state, claims))]
 pub async fn focus_far(
     Path(id): Path<u32>,
     State(state): State<AppState>,
+    claims: UserClaims,
     Json(request): Json<PtzFocusRequest>,
 ) -> AppResult<Json<PtzCommandResponse>> {
     request.validate().map_err(AppError::InvalidInputError)?;
     let ptz_manager = state.ptz_manager();
-    let result = service::ptz::focus(&state, ptz_manager, id, "far", request).await?;
+    let result = service::ptz::focus(&state, ptz_manager, &claims, id, "far", request).await?;
     Ok(Json(result))
 }
 
@@ -485,19 +523,28 @@ pub async fn focus_far(
     params(("id" = u32, Path, description = "Monitor ID")),
     responses(
         (status = 200, description = "Command executed", body = PtzCommandResponse),
+        (status = 409, description = "Another user holds the camera", body = AppResponseError),
         (status = 404, description = "Monitor not found", body = AppResponseError),
         (status = 500, description = "Internal server error", body = AppResponseError)
     ),
     security(("jwt" = []))
 )]
-#[instrument(skip(state))]
+#[instrument(skip(state, claims))]
 pub async fn focus_auto(
     Path(id): Path<u32>,
     State(state): State<AppState>,
+    claims: UserClaims,
 ) -> AppResult<Json<PtzCommandResponse>> {
     let ptz_manager = state.ptz_manager();
-    let result =
-        service::ptz::focus(&state, ptz_manager, id, "auto", PtzFocusRequest::default()).await?;
+    let result = service::ptz::focus(
+        &state,
+        ptz_manager,
+        &claims,
+        id,
+        "auto",
+        PtzFocusRequest::default(),
+    )
+    .await?;
     Ok(Json(result))
 }
 
@@ -510,18 +557,20 @@ pub async fn focus_auto(
     params(("id" = u32, Path, description = "Monitor ID")),
     responses(
         (status = 200, description = "Command executed", body = PtzCommandResponse),
+        (status = 409, description = "Another user holds the camera", body = AppResponseError),
         (status = 404, description = "Monitor not found", body = AppResponseError),
         (status = 500, description = "Internal server error", body = AppResponseError)
     ),
     security(("jwt" = []))
 )]
-#[instrument(skip(state))]
+#[instrument(skip(state, claims))]
 pub async fn focus_stop(
     Path(id): Path<u32>,
     State(state): State<AppState>,
+    claims: UserClaims,
 ) -> AppResult<Json<PtzCommandResponse>> {
     let ptz_manager = state.ptz_manager();
-    let result = service::ptz::focus_stop(&state, ptz_manager, id).await?;
+    let result = service::ptz::focus_stop(&state, ptz_manager, &claims, id).await?;
     Ok(Json(result))
 }
 
@@ -535,18 +584,20 @@ pub async fn focus_stop(
     responses(
         (status = 200, description = "Command executed", body = PtzCommandResponse),
         (status = 400, description = "Bad request", body = AppResponseError),
+        (status = 409, description = "Another user holds the camera", body = AppResponseError),
         (status = 404, description = "Monitor not found", body = AppResponseError),
         (status = 500, description = "Internal server error", body = AppResponseError)
     ),
     security(("jwt" = []))
 )]
-#[instrument(skip(state))]
+#[instrument(skip(state, claims))]
 pub async fn iris_open(
     Path(id): Path<u32>,
     State(state): State<AppState>,
+    claims: UserClaims,
 ) -> AppResult<Json<PtzCommandResponse>> {
     let ptz_manager = state.ptz_manager();
-    let result = service::ptz::iris(&state, ptz_manager, id, "open").await?;
+    let result = service::ptz::iris(&state, ptz_manager, &claims, id, "open").await?;
     Ok(Json(result))
 }
 
@@ -560,18 +611,20 @@ pub async fn iris_open(
     responses(
         (status = 200, description = "Command executed", body = PtzCommandResponse),
         (status = 400, description = "Bad request", body = AppResponseError),
+        (status = 409, description = "Another user holds the camera", body = AppResponseError),
         (status = 404, description = "Monitor not found", body = AppResponseError),
         (status = 500, description = "Internal server error", body = AppResponseError)
     ),
     security(("jwt" = []))
 )]
-#[instrument(skip(state))]
+#[instrument(skip(state, claims))]
 pub async fn iris_close(
     Path(id): Path<u32>,
     State(state): State<AppState>,
+    claims: UserClaims,
 ) -> AppResult<Json<PtzCommandResponse>> {
     let ptz_manager = state.ptz_manager();
-    let result = service::ptz::iris(&state, ptz_manager, id, "close").await?;
+    let result = service::ptz::iris(&state, ptz_manager, &claims, id, "close").await?;
     Ok(Json(result))
 }
 
@@ -585,18 +638,20 @@ pub async fn iris_close(
     responses(
         (status = 200, description = "Command executed", body = PtzCommandResponse),
         (status = 400, description = "Bad request", body = AppResponseError),
+        (status = 409, description = "Another user holds the camera", body = AppResponseError),
         (status = 404, description = "Monitor not found", body = AppResponseError),
         (status = 500, description = "Internal server error", body = AppResponseError)
     ),
     security(("jwt" = []))
 )]
-#[instrument(skip(state))]
+#[instrument(skip(state, claims))]
 pub async fn iris_auto(
     Path(id): Path<u32>,
     State(state): State<AppState>,
+    claims: UserClaims,
 ) -> AppResult<Json<PtzCommandResponse>> {
     let ptz_manager = state.ptz_manager();
-    let result = service::ptz::iris(&state, ptz_manager, id, "auto").await?;
+    let result = service::ptz::iris(&state, ptz_manager, &claims, id, "auto").await?;
     Ok(Json(result))
 }
 
@@ -610,18 +665,20 @@ pub async fn iris_auto(
     responses(
         (status = 200, description = "Command executed", body = PtzCommandResponse),
         (status = 400, description = "Bad request", body = AppResponseError),
+        (status = 409, description = "Another user holds the camera", body = AppResponseError),
         (status = 404, description = "Monitor not found", body = AppResponseError),
         (status = 500, description = "Internal server error", body = AppResponseError)
     ),
     security(("jwt" = []))
 )]
-#[instrument(skip(state))]
+#[instrument(skip(state, claims))]
 pub async fn iris_stop(
     Path(id): Path<u32>,
     State(state): State<AppState>,
+    claims: UserClaims,
 ) -> AppResult<Json<PtzCommandResponse>> {
     let ptz_manager = state.ptz_manager();
-    let result = service::ptz::iris(&state, ptz_manager, id, "stop").await?;
+    let result = service::ptz::iris(&state, ptz_manager, &claims, id, "stop").await?;
     Ok(Json(result))
 }
 
@@ -635,18 +692,20 @@ pub async fn iris_stop(
     responses(
         (status = 200, description = "Command executed", body = PtzCommandResponse),
         (status = 400, description = "Bad request", body = AppResponseError),
+        (status = 409, description = "Another user holds the camera", body = AppResponseError),
         (status = 404, description = "Monitor not found", body = AppResponseError),
         (status = 500, description = "Internal server error", body = AppResponseError)
     ),
     security(("jwt" = []))
 )]
-#[instrument(skip(state))]
+#[instrument(skip(state, claims))]
 pub async fn power_wake(
     Path(id): Path<u32>,
     State(state): State<AppState>,
+    claims: UserClaims,
 ) -> AppResult<Json<PtzCommandResponse>> {
     let ptz_manager = state.ptz_manager();
-    let result = service::ptz::power(&state, ptz_manager, id, "wake").await?;
+    let result = service::ptz::power(&state, ptz_manager, &claims, id, "wake").await?;
     Ok(Json(result))
 }
 
@@ -660,18 +719,20 @@ pub async fn power_wake(
     responses(
         (status = 200, description = "Command executed", body = PtzCommandResponse),
         (status = 400, description = "Bad request", body = AppResponseError),
+        (status = 409, description = "Another user holds the camera", body = AppResponseError),
         (status = 404, description = "Monitor not found", body = AppResponseError),
         (status = 500, description = "Internal server error", body = AppResponseError)
     ),
     security(("jwt" = []))
 )]
-#[instrument(skip(state))]
+#[instrument(skip(state, claims))]
 pub async fn power_sleep(
     Path(id): Path<u32>,
     State(state): State<AppState>,
+    claims: UserClaims,
 ) -> AppResult<Json<PtzCommandResponse>> {
     let ptz_manager = state.ptz_manager();
-    let result = service::ptz::power(&state, ptz_manager, id, "sleep").await?;
+    let result = service::ptz::power(&state, ptz_manager, &claims, id, "sleep").await?;
     Ok(Json(result))
 }
 
@@ -685,18 +746,20 @@ pub async fn power_sleep(
     responses(
         (status = 200, description = "Command executed", body = PtzCommandResponse),
         (status = 400, description = "Bad request", body = AppResponseError),
+        (status = 409, description = "Another user holds the camera", body = AppResponseError),
         (status = 404, description = "Monitor not found", body = AppResponseError),
         (status = 500, description = "Internal server error", body = AppResponseError)
     ),
     security(("jwt" = []))
 )]
-#[instrument(skip(state))]
+#[instrument(skip(state, claims))]
 pub async fn power_reset(
     Path(id): Path<u32>,
     State(state): State<AppState>,
+    claims: UserClaims,
 ) -> AppResult<Json<PtzCommandResponse>> {
     let ptz_manager = state.ptz_manager();
-    let result = service::ptz::power(&state, ptz_manager, id, "reset").await?;
+    let result = service::ptz::power(&state, ptz_manager, &claims, id, "reset").await?;
     Ok(Json(result))
 }
 
@@ -710,18 +773,20 @@ pub async fn power_reset(
     responses(
         (status = 200, description = "Command executed", body = PtzCommandResponse),
         (status = 400, description = "Bad request", body = AppResponseError),
+        (status = 409, description = "Another user holds the camera", body = AppResponseError),
         (status = 404, description = "Monitor not found", body = AppResponseError),
         (status = 500, description = "Internal server error", body = AppResponseError)
     ),
     security(("jwt" = []))
 )]
-#[instrument(skip(state))]
+#[instrument(skip(state, claims))]
 pub async fn power_reboot(
     Path(id): Path<u32>,
     State(state): State<AppState>,
+    claims: UserClaims,
 ) -> AppResult<Json<PtzCommandResponse>> {
     let ptz_manager = state.ptz_manager();
-    let result = service::ptz::power(&state, ptz_manager, id, "reboot").await?;
+    let result = service::ptz::power(&state, ptz_manager, &claims, id, "reboot").await?;
     Ok(Json(result))
 }
 
@@ -737,18 +802,20 @@ pub async fn power_reboot(
     ),
     responses(
         (status = 200, description = "Command executed", body = PtzCommandResponse),
+        (status = 409, description = "Another user holds the camera", body = AppResponseError),
         (status = 404, description = "Monitor not found", body = AppResponseError),
         (status = 500, description = "Internal server error", body = AppResponseError)
     ),
     security(("jwt" = []))
 )]
-#[instrument(skip(state))]
+#[instrument(skip(state, claims))]
 pub async fn goto_preset(
     Path((id, preset_id)): Path<(u32, u32)>,
     State(state): State<AppState>,
+    claims: UserClaims,
 ) -> AppResult<Json<PtzCommandResponse>> {
     let ptz_manager = state.ptz_manager();
-    let result = service::ptz::goto_preset(&state, ptz_manager, id, preset_id).await?;
+    let result = service::ptz::goto_preset(&state, ptz_manager, &claims, id, preset_id).await?;
     Ok(Json(result))
 }
 
@@ -766,21 +833,24 @@ pub async fn goto_preset(
     responses(
         (status = 200, description = "Command executed", body = PtzCommandResponse),
         (status = 400, description = "Bad request", body = AppResponseError),
+        (status = 409, description = "Another user holds the camera", body = AppResponseError),
         (status = 404, description = "Monitor not found", body = AppResponseError),
         (status = 500, description = "Internal server error", body = AppResponseError)
     ),
     security(("jwt" = []))
 )]
-#[instrument(skip(state))]
+#[instrument(skip(state, claims))]
 pub async fn set_preset(
     Path((id, preset_id)): Path<(u32, u32)>,
     State(state): State<AppState>,
+    claims: UserClaims,
     Json(request): Json<PtzPresetRequest>,
 ) -> AppResult<Json<PtzCommandResponse>> {
     request.validate().map_err(AppError::InvalidInputError)?;
 
     let ptz_manager = state.ptz_manager();
-    let result = service::ptz::set_preset(&state, ptz_manager, id, preset_id, request).await?;
+    let result =
+        service::ptz::set_preset(&state, ptz_manager, &claims, id, preset_id, request).await?;
     Ok(Json(result))
 }
 
@@ -796,18 +866,20 @@ pub async fn set_preset(
     ),
     responses(
         (status = 200, description = "Command executed", body = PtzCommandResponse),
+        (status = 409, description = "Another user holds the camera", body = AppResponseError),
         (status = 404, description = "Monitor not found", body = AppResponseError),
         (status = 500, description = "Internal server error", body = AppResponseError)
     ),
     security(("jwt" = []))
 )]
-#[instrument(skip(state))]
+#[instrument(skip(state, claims))]
 pub async fn clear_preset(
     Path((id, preset_id)): Path<(u32, u32)>,
     State(state): State<AppState>,
+    claims: UserClaims,
 ) -> AppResult<Json<PtzCommandResponse>> {
     let ptz_manager = state.ptz_manager();
-    let result = service::ptz::clear_preset(&state, ptz_manager, id, preset_id).await?;
+    let result = service::ptz::clear_preset(&state, ptz_manager, &claims, id, preset_id).await?;
     Ok(Json(result))
 }
 
@@ -820,18 +892,20 @@ pub async fn clear_preset(
     params(("id" = u32, Path, description = "Monitor ID")),
     responses(
         (status = 200, description = "Command executed", body = PtzCommandResponse),
+        (status = 409, description = "Another user holds the camera", body = AppResponseError),
         (status = 404, description = "Monitor not found", body = AppResponseError),
         (status = 500, description = "Internal server error", body = AppResponseError)
     ),
     security(("jwt" = []))
 )]
-#[instrument(skip(state))]
+#[instrument(skip(state, claims))]
 pub async fn goto_home(
     Path(id): Path<u32>,
     State(state): State<AppState>,
+    claims: UserClaims,
 ) -> AppResult<Json<PtzCommandResponse>> {
     let ptz_manager = state.ptz_manager();
-    let result = service::ptz::goto_home(&state, ptz_manager, id).await?;
+    let result = service::ptz::goto_home(&state, ptz_manager, &claims, id).await?;
     Ok(Json(result))
 }
 
@@ -846,20 +920,22 @@ pub async fn goto_home(
     responses(
         (status = 200, description = "Command executed", body = PtzCommandResponse),
         (status = 400, description = "Bad request", body = AppResponseError),
+        (status = 409, description = "Another user holds the camera", body = AppResponseError),
         (status = 404, description = "Monitor not found", body = AppResponseError),
         (status = 500, description = "Internal server error", body = AppResponseError)
     ),
     security(("jwt" = []))
 )]
-#[instrument(skip(state))]
+#[instrument(skip(state, claims))]
 pub async fn move_absolute(
     Path(id): Path<u32>,
     State(state): State<AppState>,
+    claims: UserClaims,
     Json(request): Json<PtzAbsoluteRequest>,
 ) -> AppResult<Json<PtzCommandResponse>> {
     request.validate().map_err(AppError::InvalidInputError)?;
     let ptz_manager = state.ptz_manager();
-    let result = service::ptz::move_absolute(&state, ptz_manager, id, request).await?;
+    let result = service::ptz::move_absolute(&state, ptz_manager, &claims, id, request).await?;
     Ok(Json(result))
 }
 
@@ -874,19 +950,343 @@ pub async fn move_absolute(
     responses(
         (status = 200, description = "Command executed", body = PtzCommandResponse),
         (status = 400, description = "Bad request", body = AppResponseError),
+        (status = 409, description = "Another user holds the camera", body = AppResponseError),
         (status = 404, description = "Monitor not found", body = AppResponseError),
         (status = 500, description = "Internal server error", body = AppResponseError)
     ),
     security(("jwt" = []))
 )]
-#[instrument(skip(state))]
+#[instrument(skip(state, claims))]
 pub async fn move_relative(
     Path(id): Path<u32>,
     State(state): State<AppState>,
+    claims: UserClaims,
     Json(request): Json<PtzRelativeRequest>,
 ) -> AppResult<Json<PtzCommandResponse>> {
     request.validate().map_err(AppError::InvalidInputError)?;
     let ptz_manager = state.ptz_manager();
-    let result = service::ptz::move_relative(&state, ptz_manager, id, request).await?;
+    let result = service::ptz::move_relative(&state, ptz_manager, &claims, id, request).await?;
+    Ok(Json(result))
+}
+
+/// Center on a point of the picture
+///
+/// `x` and `y` run from 0.0 to 1.0 across the video frame, origin top-left,
+/// so a click on the live view can be sent as is.
+#[utoipa::path(
+    post,
+    path = "/api/v3/ptz/monitors/{id}/point",
+    operation_id = "ptzPoint",
+    tag = "PTZ",
+    params(("id" = u32, Path, description = "Monitor ID")),
+    request_body = PtzPointRequest,
+    responses(
+        (status = 200, description = "Command executed", body = PtzCommandResponse),
+        (status = 400, description = "Bad request", body = AppResponseError),
+        (status = 409, description = "Another user holds the camera", body = AppResponseError),
+        (status = 404, description = "Monitor not found", body = AppResponseError),
+        (status = 500, description = "Internal server error", body = AppResponseError)
+    ),
+    security(("jwt" = []))
+)]
+#[instrument(skip(state, claims))]
+pub async fn point(
+    Path(id): Path<u32>,
+    State(state): State<AppState>,
+    claims: UserClaims,
+    Json(request): Json<PtzPointRequest>,
+) -> AppResult<Json<PtzCommandResponse>> {
+    request.validate().map_err(AppError::InvalidInputError)?;
+    let ptz_manager = state.ptz_manager();
+    let result = service::ptz::point(&state, ptz_manager, &claims, id, request).await?;
+    Ok(Json(result))
+}
+
+/// Zoom into an area of the picture
+///
+/// Centers on the rectangle and zooms in until it fills the frame, as far as
+/// the lens allows. Coordinates are as for `point`.
+#[utoipa::path(
+    post,
+    path = "/api/v3/ptz/monitors/{id}/area",
+    operation_id = "ptzArea",
+    tag = "PTZ",
+    params(("id" = u32, Path, description = "Monitor ID")),
+    request_body = PtzAreaRequest,
+    responses(
+        (status = 200, description = "Command executed", body = PtzCommandResponse),
+        (status = 400, description = "Bad request", body = AppResponseError),
+        (status = 409, description = "Another user holds the camera", body = AppResponseError),
+        (status = 404, description = "Monitor not found", body = AppResponseError),
+        (status = 500, description = "Internal server error", body = AppResponseError)
+    ),
+    security(("jwt" = []))
+)]
+#[instrument(skip(state, claims))]
+pub async fn area(
+    Path(id): Path<u32>,
+    State(state): State<AppState>,
+    claims: UserClaims,
+    Json(request): Json<PtzAreaRequest>,
+) -> AppResult<Json<PtzCommandResponse>> {
+    request.validate().map_err(AppError::InvalidInputError)?;
+    let ptz_manager = state.ptz_manager();
+    let result = service::ptz::area(&state, ptz_manager, &claims, id, request).await?;
+    Ok(Json(result))
+}
+
+/// List a monitor's preset tours
+#[utoipa::path(
+    get,
+    path = "/api/v3/ptz/monitors/{id}/tours",
+    operation_id = "listPtzTours",
+    tag = "PTZ",
+    params(("id" = u32, Path, description = "Monitor ID")),
+    responses(
+        (status = 200, description = "Tours, by name", body = Vec<PtzTourResponse>),
+        (status = 404, description = "Monitor not found", body = AppResponseError),
+        (status = 500, description = "Internal server error", body = AppResponseError)
+    ),
+    security(("jwt" = []))
+)]
+#[instrument(skip(state))]
+pub async fn list_tours(
+    Path(id): Path<u32>,
+    State(state): State<AppState>,
+) -> AppResult<Json<Vec<PtzTourResponse>>> {
+    let result = service::ptz_tours::list(&state, id).await?;
+    Ok(Json(result))
+}
+
+/// Create a preset tour
+#[utoipa::path(
+    post,
+    path = "/api/v3/ptz/monitors/{id}/tours",
+    operation_id = "createPtzTour",
+    tag = "PTZ",
+    params(("id" = u32, Path, description = "Monitor ID")),
+    request_body = CreatePtzTourRequest,
+    responses(
+        (status = 201, description = "Tour created", body = PtzTourResponse),
+        (status = 400, description = "Bad request", body = AppResponseError),
+        (status = 404, description = "Monitor not found", body = AppResponseError),
+        (status = 409, description = "Name already used on this monitor", body = AppResponseError),
+        (status = 500, description = "Internal server error", body = AppResponseError)
+    ),
+    security(("jwt" = []))
+)]
+#[instrument(skip(state, request))]
+pub async fn create_tour(
+    Path(id): Path<u32>,
+    State(state): State<AppState>,
+    Json(request): Json<CreatePtzTourRequest>,
+) -> AppResult<(axum::http::StatusCode, Json<PtzTourResponse>)> {
+    request.validate().map_err(AppError::InvalidInputError)?;
+    let result = service::ptz_tours::create(&state, id, request).await?;
+    Ok((axum::http::StatusCode::CREATED, Json(result)))
+}
+
+/// Get a preset tour
+#[utoipa::path(
+    get,
+    path = "/api/v3/ptz/monitors/{id}/tours/{tour_id}",
+    operation_id = "getPtzTour",
+    tag = "PTZ",
+    params(
+        ("id" = u32, Path, description = "Monitor ID"),
+        ("tour_id" = u64, Path, description = "Tour ID")
+    ),
+    responses(
+        (status = 200, description = "Tour", body = PtzTourResponse),
+        (status = 404, description = "Tour not found", body = AppResponseError),
+        (status = 500, description = "Internal server error", body = AppResponseError)
+    ),
+    security(("jwt" = []))
+)]
+#[instrument(skip(state))]
+pub async fn get_tour(
+    Path((id, tour_id)): Path<(u32, u64)>,
+    State(state): State<AppState>,
+) -> AppResult<Json<PtzTourResponse>> {
+    let result = service::ptz_tours::get(&state, id, tour_id).await?;
+    Ok(Json(result))
+}
+
+/// Update a preset tour
+///
+/// A running tour picks up new steps at once, starting over from the first;
+/// disabling it stops it.
+#[utoipa::path(
+    patch,
+    path = "/api/v3/ptz/monitors/{id}/tours/{tour_id}",
+    operation_id = "updatePtzTour",
+    tag = "PTZ",
+    params(
+        ("id" = u32, Path, description = "Monitor ID"),
+        ("tour_id" = u64, Path, description = "Tour ID")
+    ),
+    request_body = UpdatePtzTourRequest,
+    responses(
+        (status = 200, description = "Tour updated", body = PtzTourResponse),
+        (status = 400, description = "Bad request", body = AppResponseError),
+        (status = 404, description = "Tour not found", body = AppResponseError),
+        (status = 409, description = "Name already used on this monitor", body = AppResponseError),
+        (status = 500, description = "Internal server error", body = AppResponseError)
+    ),
+    security(("jwt" = []))
+)]
+#[instrument(skip(state, request))]
+pub async fn update_tour(
+    Path((id, tour_id)): Path<(u32, u64)>,
+    State(state): State<AppState>,
+    Json(request): Json<UpdatePtzTourRequest>,
+) -> AppResult<Json<PtzTourResponse>> {
+    request.validate().map_err(AppError::InvalidInputError)?;
+    let result = service::ptz_tours::update(&state, id, tour_id, request).await?;
+    Ok(Json(result))
+}
+
+/// Delete a preset tour, stopping it if it is running
+#[utoipa::path(
+    delete,
+    path = "/api/v3/ptz/monitors/{id}/tours/{tour_id}",
+    operation_id = "deletePtzTour",
+    tag = "PTZ",
+    params(
+        ("id" = u32, Path, description = "Monitor ID"),
+        ("tour_id" = u64, Path, description = "Tour ID")
+    ),
+    responses(
+        (status = 204, description = "Tour deleted"),
+        (status = 404, description = "Tour not found", body = AppResponseError),
+        (status = 500, description = "Internal server error", body = AppResponseError)
+    ),
+    security(("jwt" = []))
+)]
+#[instrument(skip(state))]
+pub async fn delete_tour(
+    Path((id, tour_id)): Path<(u32, u64)>,
+    State(state): State<AppState>,
+) -> AppResult<axum::http::StatusCode> {
+    service::ptz_tours::delete(&state, id, tour_id).await?;
+    Ok(axum::http::StatusCode::NO_CONTENT)
+}
+
+/// Start a preset tour
+///
+/// Replaces any tour running on the monitor and runs until stopped. The tour
+/// pauses while users drive the camera and resumes once they let go.
+#[utoipa::path(
+    post,
+    path = "/api/v3/ptz/monitors/{id}/tours/{tour_id}/start",
+    operation_id = "startPtzTour",
+    tag = "PTZ",
+    params(
+        ("id" = u32, Path, description = "Monitor ID"),
+        ("tour_id" = u64, Path, description = "Tour ID")
+    ),
+    responses(
+        (status = 200, description = "Tour started", body = PtzTourStatusResponse),
+        (status = 400, description = "Tour disabled or monitor not controllable", body = AppResponseError),
+        (status = 404, description = "Tour not found", body = AppResponseError),
+        (status = 503, description = "Tours disabled in configuration", body = AppResponseError)
+    ),
+    security(("jwt" = []))
+)]
+#[instrument(skip(state))]
+pub async fn start_tour(
+    Path((id, tour_id)): Path<(u32, u64)>,
+    State(state): State<AppState>,
+) -> AppResult<Json<PtzTourStatusResponse>> {
+    let result = service::ptz_tours::start(&state, id, tour_id).await?;
+    Ok(Json(result))
+}
+
+/// Stop a running preset tour
+///
+/// The camera stays where it is. A scheduled tour stopped inside its window
+/// stays stopped until the window closes.
+#[utoipa::path(
+    post,
+    path = "/api/v3/ptz/monitors/{id}/tours/{tour_id}/stop",
+    operation_id = "stopPtzTour",
+    tag = "PTZ",
+    params(
+        ("id" = u32, Path, description = "Monitor ID"),
+        ("tour_id" = u64, Path, description = "Tour ID")
+    ),
+    responses(
+        (status = 200, description = "Tour stopped", body = PtzCommandResponse),
+        (status = 404, description = "Tour not found", body = AppResponseError),
+        (status = 409, description = "Tour not running", body = AppResponseError)
+    ),
+    security(("jwt" = []))
+)]
+#[instrument(skip(state))]
+pub async fn stop_tour(
+    Path((id, tour_id)): Path<(u32, u64)>,
+    State(state): State<AppState>,
+) -> AppResult<Json<PtzCommandResponse>> {
+    let result = service::ptz_tours::stop(&state, id, tour_id).await?;
+    Ok(Json(result))
+}
+
+/// Take control of a PTZ camera
+///
+/// Holds the camera for `[ptz.control].lease_seconds` without moving it, or
+/// renews the caller's lease. Every movement command does the same. While
+/// the lease is live other users get 409, unless they have `System: Edit`
+/// and the holder does not.
+#[utoipa::path(
+    post,
+    path = "/api/v3/ptz/monitors/{id}/control",
+    operation_id = "acquirePtzControl",
+    tag = "PTZ",
+    params(("id" = u32, Path, description = "Monitor ID")),
+    responses(
+        (status = 200, description = "Control held", body = PtzControlLeaseResponse),
+        (status = 400, description = "Monitor not controllable", body = AppResponseError),
+        (status = 404, description = "Monitor not found", body = AppResponseError),
+        (status = 409, description = "Another user holds the camera", body = AppResponseError),
+        (status = 503, description = "Control leases disabled in configuration", body = AppResponseError)
+    ),
+    security(("jwt" = []))
+)]
+#[instrument(skip(state, claims))]
+pub async fn acquire_control(
+    Path(id): Path<u32>,
+    State(state): State<AppState>,
+    claims: UserClaims,
+) -> AppResult<Json<PtzControlLeaseResponse>> {
+    let ptz_manager = state.ptz_manager();
+    let result = service::ptz::acquire_control(&state, ptz_manager, &claims, id).await?;
+    Ok(Json(result))
+}
+
+/// Release control of a PTZ camera
+///
+/// Lets go of the caller's lease. A user with `System: Edit` may also clear
+/// the lease of one without.
+#[utoipa::path(
+    delete,
+    path = "/api/v3/ptz/monitors/{id}/control",
+    operation_id = "releasePtzControl",
+    tag = "PTZ",
+    params(("id" = u32, Path, description = "Monitor ID")),
+    responses(
+        (status = 200, description = "Control released", body = PtzCommandResponse),
+        (status = 400, description = "Monitor not controllable", body = AppResponseError),
+        (status = 404, description = "Monitor not found", body = AppResponseError),
+        (status = 409, description = "Another user holds the camera", body = AppResponseError)
+    ),
+    security(("jwt" = []))
+)]
+#[instrument(skip(state, claims))]
+pub async fn release_control(
+    Path(id): Path<u32>,
+    State(state): State<AppState>,
+    claims: UserClaims,
+) -> AppResult<Json<PtzCommandResponse>> {
+    let result = service::ptz::release_control(&state, &claims, id).await?;
     Ok(Json(result))
 }
@@ -0,0 +1,171 @@
+//! HTTP handlers for retention overrides.
+//!
+//! Storage overrides live under `/storage/{id}/retention` and share the storage
+//! routes' `System` permission. Monitor overrides live under
+//! `/monitors/{id}/retention`: `GET` needs view access to the monitor, and
+//! `PUT`/`DELETE` need **unrestricted** monitor access, since loosening a
+//! monitor's retention deletes its events (mirroring the pipeline endpoints).
+
+use axum::extract::{Path, State};
+use axum::Json;
+use garde::Validate;
+use tracing::info;
+
+use crate::dto::request::retention::{MonitorRetentionRequest, StorageRetentionRequest};
+use crate::dto::response::retention::{MonitorRetentionResponse, StorageRetentionResponse};
+use crate::error::{AppError, AppResponseError, AppResult};
+use crate::server::state::AppState;
+use crate::service::monitor_acl::MonitorScope;
+use crate::service::retention::policy;
+
+#[utoipa::path(
+    get,
+    path = "/api/v3/storage/{id}/retention",
+    params(("id" = u16, Path, description = "Storage ID")),
+    responses(
+        (status = 200, description = "The storage's retention overrides", body = StorageRetentionResponse),
+        (status = 401, description = "Unauthorized", body = AppResponseError),
+        (status = 404, description = "Storage not found or has no overrides", body = AppResponseError),
+        (status = 500, description = "Internal server error", body = AppResponseError)
+    ),
+    security(("jwt" = [])),
+    tag = "Storage"
+)]
+pub async fn get_storage_retention(
+    State(state): State<AppState>,
+    Path(id): Path<u16>,
+) -> AppResult<Json<StorageRetentionResponse>> {
+    Ok(Json(policy::get_storage(&state, id).await?))
+}
+
+#[utoipa::path(
+    put,
+    path = "/api/v3/storage/{id}/retention",
+    params(("id" = u16, Path, description = "Storage ID")),
+    request_body = StorageRetentionRequest,
+    responses(
+        (status = 200, description = "Overrides replaced", body = StorageRetentionResponse),
+        (status = 400, description = "Invalid tier storage", body = AppResponseError),
+        (status = 401, description = "Unauthorized", body = AppResponseError),
+        (status = 404, description = "Storage not found", body = AppResponseError),
+        (status = 422, description = "Invalid input", body = AppResponseError),
+        (status = 500, description = "Internal server error", body = AppResponseError)
+    ),
+    security(("jwt" = [])),
+    tag = "Storage"
+)]
+pub async fn put_storage_retention(
+    State(state): State<AppState>,
+    Path(id): Path<u16>,
+    Json(req): Json<StorageRetentionRequest>,
+) -> AppResult<Json<StorageRetentionResponse>> {
+    req.validate().map_err(AppError::InvalidInputError)?;
+    info!("Replacing retention overrides for storage {id}.");
+    Ok(Json(policy::replace_storage(&state, id, req).await?))
+}
+
+#[utoipa::path(
+    delete,
+    path = "/api/v3/storage/{id}/retention",
+    params(("id" = u16, Path, description = "Storage ID")),
+    responses(
+        (status = 200, description = "Overrides removed (the storage follows [retention] again)"),
+        (status = 401, description = "Unauthorized", body = AppResponseError),
+        (status = 404, description = "Storage not found or has no overrides", body = AppResponseError),
+        (status = 500, description = "Internal server error", body = AppResponseError)
+    ),
+    security(("jwt" = [])),
+    tag = "Storage"
+)]
+pub async fn delete_storage_retention(
+    State(state): State<AppState>,
+    Path(id): Path<u16>,
+) -> AppResult<Json<()>> {
+    info!("Removing retention overrides for storage {id}.");
+    policy::delete_storage(&state, id).await?;
+    Ok(Json(()))
+}
+
+#[utoipa::path(
+    get,
+    path = "/api/v3/monitors/{id}/retention",
+    params(("id" = u32, Path, description = "Monitor identifier")),
+    responses(
+        (status = 200, description = "The monitor's retention overrides", body = MonitorRetentionResponse),
+        (status = 401, description = "Unauthorized", body = AppResponseError),
+        (status = 404, description = "Monitor not found or has no overrides", body = AppResponseError),
+        (status = 500, description = "Internal server error", body = AppResponseError)
+    ),
+    security(("jwt" = [])),
+    tag = "Monitors"
+)]
+pub async fn get_monitor_retention(
+    State(state): State<AppState>,
+    Path(id): Path<u32>,
+    scope: MonitorScope,
+) -> AppResult<Json<MonitorRetentionResponse>> {
+    Ok(Json(policy::get_monitor(&state, id, &scope).await?))
+}
+
+#[utoipa::path(
+    put,
+    path = "/api/v3/monitors/{id}/retention",
+    params(("id" = u32, Path, description = "Monitor identifier")),
+    request_body = MonitorRetentionRequest,
+    responses(
+        (status = 200, description = "Overrides replaced", body = MonitorRetentionResponse),
+        (status = 401, description = "Unauthorized", body = AppResponseError),
+        (status = 403, description = "Caller's monitor access is restricted", body = AppResponseError),
+        (status = 404, description = "Monitor not found", body = AppResponseError),
+        (status = 422, description = "Invalid input", body = AppResponseError),
+        (status = 500, description = "Internal server error", body = AppResponseError)
+    ),
+    security(("jwt" = [])),
+    tag = "Monitors"
+)]
+pub async fn put_monitor_retention(
+    State(state): State<AppState>,
+    Path(id): Path<u32>,
+    scope: MonitorScope,
+    Json(req): Json<MonitorRetentionRequest>,
+) -> AppResult<Json<MonitorRetentionResponse>> {
+    if scope.is_restricted() {
+        return Err(AppError::PermissionDeniedError(
+            "editing a monitor's retention requires unrestricted monitor access".to_string(),
+        ));
+    }
+    req.validate().map_err(AppError::InvalidInputError)?;
+    info!("Replacing retention overrides for monitor {id}.");
+    Ok(Json(
+        policy::replace_monitor(&state, id, req, &scope).await?,
+    ))
+}
+
+#[utoipa::path(
+    delete,
+    path = "/api/v3/monitors/{id}/retention",
+    params(("id" = u32, Path, description = "Monitor identifier")),
+    responses(
+        (status = 200, description = "Overrides removed (the monitor follows its storage again)"),
+        (status = 401, description = "Unauthorized", body = AppResponseError),
+        (status = 403, description = "Caller's monitor access is restricted", body = AppResponseError),
+        (status = 404, description = "Monitor not found or has no overrides", body = AppResponseError),
+        (status = 500, description = "Internal server error", body = AppResponseError)
+    ),
+    security(("jwt" = [])),
+    tag = "Monitors"
+)]
+pub async fn delete_monitor_retention(
+    State(state): State<AppState>,
+    Path(id): Path<u32>,
+    scope: MonitorScope,
+) -> AppResult<Json<()>> {
+    if scope.is_restricted() {
+        return Err(AppError::PermissionDeniedError(
+            "deleting a monitor's retention requires unrestricted monitor access".to_string(),
+        ));
+    }
+    info!("Removing retention overrides for monitor {id}.");
+    policy::delete_monitor(&state, id, &scope).await?;
+    Ok(Json(()))
+}
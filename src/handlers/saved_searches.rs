@@ -0,0 +1,120 @@
+use crate::dto::request::saved_searches::{CreateSavedSearchRequest, UpdateSavedSearchRequest};
+use crate::dto::response::saved_searches::{PaginatedSavedSearchesResponse, SavedSearchResponse};
+use crate::dto::PaginationParams;
+use crate::error::{AppError, AppResult};
+use crate::server::state::AppState;
+use crate::util::claim::UserClaims;
+use axum::{
+    extract::{Path, Query, State},
+    Json,
+};
+use garde::Validate;
+
+/// List the caller's saved event searches (all users' with System View).
+///
+/// - Requires a valid JWT.
+#[utoipa::path(
+    get,
+    path = "/api/v3/saved-searches",
+    params(
+        ("page" = Option<u64>, Query, description = "Page number (1-indexed)", example = 1),
+        ("page_size" = Option<u64>, Query, description = "Items per page (max 1000)", example = 25)
+    ),
+    responses((status = 200, description = "Paginated list of saved searches", body = PaginatedSavedSearchesResponse)),
+    tag = "Saved Searches",
+    security(("jwt" = []))
+)]
+pub async fn list_saved_searches(
+    State(state): State<AppState>,
+    claims: UserClaims,
+    Query(params): Query<PaginationParams>,
+) -> AppResult<Json<PaginatedSavedSearchesResponse>> {
+    let result = crate::service::saved_searches::list_paginated(&state, &params, &claims).await?;
+    Ok(Json(PaginatedSavedSearchesResponse::from(result)))
+}
+
+/// Get a single saved search by id.
+///
+/// - Requires a valid JWT.
+#[utoipa::path(
+    get,
+    path = "/api/v3/saved-searches/{id}",
+    params(("id" = u64, Path, description = "Saved search ID")),
+    responses((status = 200, description = "Saved search details", body = SavedSearchResponse)),
+    tag = "Saved Searches",
+    security(("jwt" = []))
+)]
+pub async fn get_saved_search(
+    Path(id): Path<u64>,
+    State(state): State<AppState>,
+    claims: UserClaims,
+) -> AppResult<Json<SavedSearchResponse>> {
+    let item = crate::service::saved_searches::get_by_id(&state, id, &claims).await?;
+    Ok(Json(item))
+}
+
+/// Save a named filter AST.
+///
+/// - The filter is compiled before saving; one that could not run is a 400.
+/// - Requires a valid JWT.
+#[utoipa::path(
+    post,
+    path = "/api/v3/saved-searches",
+    request_body = CreateSavedSearchRequest,
+    responses((status = 201, description = "Created saved search", body = SavedSearchResponse)),
+    tag = "Saved Searches",
+    security(("jwt" = []))
+)]
+pub async fn create_saved_search(
+    State(state): State<AppState>,
+    claims: UserClaims,
+    Json(req): Json<CreateSavedSearchRequest>,
+) -> AppResult<(axum::http::StatusCode, Json<SavedSearchResponse>)> {
+    req.validate().map_err(AppError::InvalidInputError)?;
+    let item = crate::service::saved_searches::create(&state, req, &claims).await?;
+    Ok((axum::http::StatusCode::CREATED, Json(item)))
+}
+
+/// Rename a saved search and/or replace its filter.
+///
+/// - Requires a valid JWT; only fields present in the body are changed.
+#[utoipa::path(
+    put,
+    path = "/api/v3/saved-searches/{id}",
+    params(("id" = u64, Path, description = "Saved search ID")),
+    request_body = UpdateSavedSearchRequest,
+    responses((status = 200, description = "Updated saved search", body = SavedSearchResponse)),
+    tag = "Saved Searches",
+    security(("jwt" = []))
+)]
+pub async fn update_saved_search(
+    Path(id): Path<u64>,
+    State(state): State<AppState>,
+    claims: UserClaims,
+    Json(req): Json<UpdateSavedSearchRequest>,
+) -> AppResult<Json<SavedSearchResponse>> {
+    req.validate().map_err(AppError::InvalidInputError)?;
+    let item = crate::service::saved_searches::update(&state, id, req, &claims).await?;
+    Ok(Json(item))
+}
+
+/// Delete a saved search by id.
+///
+/// - Responds 204 on success, 404 if not found.
+/// - Requires a valid JWT.
+#[utoipa::path(
+    delete,
+    path = "/api/v3/saved-searches/{id}",
+    params(("id" = u64, Path, description = "Saved search ID")),
+    responses((status = 204, description = "Deleted saved search")),
+    tag = "Saved Searches",
+    security(("jwt" = []))
+)]
+pub async fn delete_saved_search(
+    Path(id): Path<u64>,
+    State(state): State<AppState>,
+    claims: UserClaims,
+) -> AppResult<axum::http::StatusCode> {
+    crate::service::saved_searches::delete(&state, id, &claims).await?;
+    Ok(axum::http::StatusCode::NO_CONTENT)
+}
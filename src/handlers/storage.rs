@@ -1,8 +1,10 @@
-use crate::dto::request::CreateStorageRequest;
-use crate::dto::response::storage::PaginatedStorageResponse;
+use crate::dto::request::{CreateStorageRequest, ReapStorageRequest};
+use crate::dto::response::storage::{
+    PaginatedStorageResponse, StorageHealthResponse, StorageReapResponse,
+};
 use crate::dto::response::StorageResponse;
 use crate::dto::PaginationParams;
-use crate::error::AppResult;
+use crate::error::{AppError, AppResponseError, AppResult, Resource, ResourceType};
 use crate::server::state::AppState;
 use axum::{
     extract::{Path, Query, State},
@@ -134,3 +136,66 @@ pub async fn delete_storage(
     crate::service::storage::delete(&state, id).await?;
     Ok(axum::http::StatusCode::NO_CONTENT)
 }
+
+/// Usage and health of a storage.
+///
+/// - Bytes used (archived included), filesystem capacity and free space, the
+///   oldest event, and a per-monitor breakdown.
+/// - `full` is set below the retention free-space floor or over the quota.
+/// - Requires a valid JWT; responds 404 if not found.
+#[utoipa::path(
+    get,
+    path = "/api/v3/storage/{id}/health",
+    params(("id" = u16, Path, description = "Storage ID")),
+    responses(
+        (status = 200, description = "Storage usage and health", body = StorageHealthResponse),
+        (status = 404, description = "Storage not found", body = AppResponseError)
+    ),
+    tag = "Storage",
+    security(("jwt" = []))
+)]
+pub async fn get_storage_health(
+    Path(id): Path<u16>,
+    State(state): State<AppState>,
+) -> AppResult<Json<StorageHealthResponse>> {
+    Ok(Json(crate::service::storage::health(&state, id).await?))
+}
+
+/// Run the retention reaper on one storage now.
+///
+/// - Uses the `[retention]` limits and the storage's overrides, even when the
+///   periodic reaper is disabled.
+/// - A dry run (the default, and what an empty body gives) only reports.
+/// - Requires a valid JWT; responds 404 if not found.
+#[utoipa::path(
+    post,
+    path = "/api/v3/storage/{id}:reap",
+    params(("id" = u16, Path, description = "Storage ID")),
+    request_body(content = Option<ReapStorageRequest>, description = "Omit for a dry run"),
+    responses(
+        (status = 200, description = "What was (or would be) deleted or moved", body = StorageReapResponse),
+        (status = 404, description = "Storage not found", body = AppResponseError)
+    ),
+    tag = "Storage",
+    security(("jwt" = []))
+)]
+pub async fn reap_storage(
+    Path(target): Path<String>,
+    State(state): State<AppState>,
+    body: Option<Json<ReapStorageRequest>>,
+) -> AppResult<Json<StorageReapResponse>> {
+    // The router matches the whole `{id}:reap` segment; split the custom
+    // method off here.
+    let id = target
+        .strip_suffix(":reap")
+        .and_then(|id| id.parse::<u16>().ok())
+        .ok_or_else(|| {
+            AppError::NotFoundError(Resource {
+                details: vec![("path".to_string(), target.clone())],
+                resource_type: ResourceType::Message,
+            })
+        })?;
+    let req = body.map(|Json(req)| req).unwrap_or_default();
+    let report = crate::service::storage::reap(&state, id, req.dry_run).await?;
+    Ok(Json(report))
+}
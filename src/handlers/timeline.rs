@@ -0,0 +1,52 @@
+use axum::{
+    extract::{Query, State},
+    Json,
+};
+use tracing::instrument;
+
+use crate::{
+    dto::request::timeline::TimelineQuery,
+    dto::response::timeline::TimelineResponse,
+    error::{AppResponseError, AppResult},
+    server::state::AppState,
+    service,
+    service::monitor_acl::MonitorScope,
+};
+
+/// Get the event timeline of every monitor
+///
+/// Splits `[from, to)` into `bucket`-wide buckets and, for each monitor the
+/// caller may view, reports per bucket the events overlapping it, their
+/// highest score and the seconds of alarm and recording within it. Only
+/// buckets with events are listed. Ranges inside the last hour, day, week or
+/// month are served from ZoneMinder's rolling event tables; `source` names the
+/// table used.
+#[utoipa::path(
+    get,
+    path = "/api/v3/monitors/timeline",
+    operation_id = "getMonitorTimeline",
+    tag = "Events",
+    params(
+        ("from" = String, Query, description = "Range start (RFC 3339)", example = "2025-04-24T00:00:00Z"),
+        ("to" = String, Query, description = "Range end (RFC 3339), exclusive", example = "2025-04-25T00:00:00Z"),
+        ("bucket" = Option<String>, Query, description = "Bucket width: hour, day, week, a number with s/m/h/d/w, or seconds (default hour; at most 2000 buckets)", example = "15m")
+    ),
+    responses(
+        (status = 200, description = "Per-monitor event buckets", body = TimelineResponse),
+        (status = 400, description = "Invalid range or bucket, or too many buckets or events", body = AppResponseError),
+        (status = 401, description = "Unauthorized", body = AppResponseError),
+        (status = 500, description = "Internal server error", body = AppResponseError)
+    ),
+    security(
+        ("jwt" = [])
+    )
+)]
+#[instrument(skip(state, scope))]
+pub async fn get_monitor_timeline(
+    State(state): State<AppState>,
+    Query(query): Query<TimelineQuery>,
+    scope: MonitorScope,
+) -> AppResult<Json<TimelineResponse>> {
+    let timeline = service::timeline::get(&state, query, &scope).await?;
+    Ok(Json(timeline))
+}
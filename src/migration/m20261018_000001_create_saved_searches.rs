@@ -0,0 +1,134 @@
+//! Create the zm-api-owned `saved_searches` table.
+//!
+//! One row per named event search: the typed filter AST
+//! (`dto::request::filter_ast::FilterQuery`) serialized as JSON. This is the
+//! replacement for the search half of ZoneMinder's `Filters` row — there are
+//! deliberately no `Auto*`, execution, `Background`/`Concurrent`/`LockRows`
+//! columns. Running a search over its matches is an explicit, one-off
+//! `POST /api/v3/events:bulk` job, never a scheduled side effect.
+//!
+//! `user_id` is a *logical* FK to `Users.Id`; no hard cross-table constraint is
+//! created because zm-api does not own ZoneMinder's `Users` table. Columns are
+//! snake_case to match the hand-written entity in `src/entity/saved_searches.rs`.
+
+use sea_orm_migration::prelude::*;
+
+#[derive(DeriveMigrationName)]
+pub struct Migration;
+
+/// The `saved_searches` table create statement. Extracted so the DDL can be
+/// rendered and asserted offline (the migration itself needs a live DB).
+fn saved_searches_table() -> TableCreateStatement {
+    Table::create()
+        .table(SavedSearches::Table)
+        .if_not_exists()
+        .col(
+            ColumnDef::new(SavedSearches::Id)
+                .big_unsigned()
+                .not_null()
+                .auto_increment()
+                .primary_key(),
+        )
+        .col(ColumnDef::new(SavedSearches::UserId).unsigned().not_null())
+        .col(
+            ColumnDef::new(SavedSearches::Name)
+                .string_len(64)
+                .not_null(),
+        )
+        // The filter AST as a JSON document (validated and compiled by zm-api
+        // before write, so a stored search always compiles).
+        .col(ColumnDef::new(SavedSearches::AstJson).text().not_null())
+        .col(
+            ColumnDef::new(SavedSearches::CreatedAt)
+                .date_time()
+                .not_null(),
+        )
+        .col(
+            ColumnDef::new(SavedSearches::UpdatedAt)
+                .date_time()
+                .not_null(),
+        )
+        .to_owned()
+}
+
+#[async_trait::async_trait]
+impl MigrationTrait for Migration {
+    async fn up(&self, manager: &SchemaManager) -> Result<(), DbErr> {
+        manager.create_table(saved_searches_table()).await?;
+
+        // Every list is scoped to the caller unless they hold System.
+        manager
+            .create_index(
+                Index::create()
+                    .if_not_exists()
+                    .name("idx_saved_searches_user")
+                    .table(SavedSearches::Table)
+                    .col(SavedSearches::UserId)
+                    .to_owned(),
+            )
+            .await
+    }
+
+    async fn down(&self, manager: &SchemaManager) -> Result<(), DbErr> {
+        manager
+            .drop_table(Table::drop().table(SavedSearches::Table).to_owned())
+            .await
+    }
+}
+
+/// Idens spell the table/column names exactly as the entity expects them.
+#[derive(DeriveIden)]
+enum SavedSearches {
+    #[sea_orm(iden = "saved_searches")]
+    Table,
+    #[sea_orm(iden = "id")]
+    Id,
+    #[sea_orm(iden = "user_id")]
+    UserId,
+    #[sea_orm(iden = "name")]
+    Name,
+    #[sea_orm(iden = "ast_json")]
+    AstJson,
+    #[sea_orm(iden = "created_at")]
+    CreatedAt,
+    #[sea_orm(iden = "updated_at")]
+    UpdatedAt,
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use sea_orm::sea_query::MysqlQueryBuilder;
+
+    #[test]
+    fn table_ddl_has_expected_columns() {
+        let sql = saved_searches_table()
+            .to_string(MysqlQueryBuilder)
+            .to_lowercase();
+
+        assert!(sql.contains("`saved_searches`"), "table name: {sql}");
+        assert!(
+            sql.contains("`id`") && sql.contains("auto_increment") && sql.contains("primary key"),
+            "id pk: {sql}"
+        );
+        assert!(
+            sql.contains("`user_id`") && sql.contains("int unsigned not null"),
+            "user_id: {sql}"
+        );
+        assert!(
+            sql.contains("`name`") && sql.contains("varchar(64)"),
+            "name: {sql}"
+        );
+        assert!(
+            sql.contains("`ast_json`") && sql.contains("text"),
+            "ast_json text: {sql}"
+        );
+        // No legacy auto-action / execution columns.
+        assert!(
+            !sql.contains("auto_delete") && !sql.contains("auto_execute"),
+            "no auto-action columns: {sql}"
+        );
+        assert!(sql.contains("`created_at`") && sql.contains("datetime"));
+        assert!(sql.contains("`updated_at`"), "updated_at: {sql}");
+    }
+}
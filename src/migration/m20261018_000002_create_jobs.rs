@@ -0,0 +1,171 @@
+//! Create the zm-api-owned `jobs` table.
+//!
+//! One row per background job (today: bulk event operations). The row is the
+//! job's durable state: what to do (`kind` + `params_json`), how far it got
+//! (`cursor` + counters) and how it ended (`status`, `result_json`, `error`).
+//! Progress is committed after every batch, so a job interrupted by a restart
+//! resumes from its cursor instead of starting over.
+//!
+//! `status`/`kind` are short portable strings rather than native `ENUM`s, so
+//! the same migration runs on MySQL and Postgres. Columns are snake_case to
+//! match the hand-written entity in `src/entity/jobs.rs`.
+
+use sea_orm_migration::prelude::*;
+
+#[derive(DeriveMigrationName)]
+pub struct Migration;
+
+/// The `jobs` table create statement. Extracted so the DDL can be rendered and
+/// asserted offline (the migration itself needs a live DB).
+fn jobs_table() -> TableCreateStatement {
+    Table::create()
+        .table(Jobs::Table)
+        .if_not_exists()
+        .col(
+            ColumnDef::new(Jobs::Id)
+                .big_unsigned()
+                .not_null()
+                .auto_increment()
+                .primary_key(),
+        )
+        .col(ColumnDef::new(Jobs::Kind).string_len(32).not_null())
+        .col(
+            ColumnDef::new(Jobs::Status)
+                .string_len(16)
+                .not_null()
+                .default("queued"),
+        )
+        // Submitting user; logical FK to `Users.Id`. NULL for system jobs.
+        .col(ColumnDef::new(Jobs::UserId).unsigned().null())
+        .col(ColumnDef::new(Jobs::ParamsJson).text().not_null())
+        // Last processed key (for bulk event jobs, `Events.Id`).
+        .col(ColumnDef::new(Jobs::Cursor).big_unsigned().null())
+        .col(ColumnDef::new(Jobs::Total).big_unsigned().null())
+        .col(
+            ColumnDef::new(Jobs::Processed)
+                .big_unsigned()
+                .not_null()
+                .default(0),
+        )
+        .col(
+            ColumnDef::new(Jobs::Affected)
+                .big_unsigned()
+                .not_null()
+                .default(0),
+        )
+        .col(
+            ColumnDef::new(Jobs::Failed)
+                .big_unsigned()
+                .not_null()
+                .default(0),
+        )
+        .col(ColumnDef::new(Jobs::ResultJson).text().null())
+        .col(ColumnDef::new(Jobs::Error).text().null())
+        .col(ColumnDef::new(Jobs::CreatedAt).date_time().not_null())
+        .col(ColumnDef::new(Jobs::StartedAt).date_time().null())
+        .col(ColumnDef::new(Jobs::FinishedAt).date_time().null())
+        .col(ColumnDef::new(Jobs::UpdatedAt).date_time().not_null())
+        .to_owned()
+}
+
+#[async_trait::async_trait]
+impl MigrationTrait for Migration {
+    async fn up(&self, manager: &SchemaManager) -> Result<(), DbErr> {
+        manager.create_table(jobs_table()).await?;
+
+        // Startup resumes unfinished jobs by status.
+        manager
+            .create_index(
+                Index::create()
+                    .if_not_exists()
+                    .name("idx_jobs_status")
+                    .table(Jobs::Table)
+                    .col(Jobs::Status)
+                    .to_owned(),
+            )
+            .await
+    }
+
+    async fn down(&self, manager: &SchemaManager) -> Result<(), DbErr> {
+        manager
+            .drop_table(Table::drop().table(Jobs::Table).to_owned())
+            .await
+    }
+}
+
+/// Idens spell the table/column names exactly as the entity expects them.
+#[derive(DeriveIden)]
+enum Jobs {
+    #[sea_orm(iden = "jobs")]
+    Table,
+    #[sea_orm(iden = "id")]
+    Id,
+    #[sea_orm(iden = "kind")]
+    Kind,
+    #[sea_orm(iden = "status")]
+    Status,
+    #[sea_orm(iden = "user_id")]
+    UserId,
+    #[sea_orm(iden = "params_json")]
+    ParamsJson,
+    #[sea_orm(iden = "cursor")]
+    Cursor,
+    #[sea_orm(iden = "total")]
+    Total,
+    #[sea_orm(iden = "processed")]
+    Processed,
+    #[sea_orm(iden = "affected")]
+    Affected,
+    #[sea_orm(iden = "failed")]
+    Failed,
+    #[sea_orm(iden = "result_json")]
+    ResultJson,
+    #[sea_orm(iden = "error")]
+    Error,
+    #[sea_orm(iden = "created_at")]
+    CreatedAt,
+    #[sea_orm(iden = "started_at")]
+    StartedAt,
+    #[sea_orm(iden = "finished_at")]
+    FinishedAt,
+    #[sea_orm(iden = "updated_at")]
+    UpdatedAt,
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use sea_orm::sea_query::MysqlQueryBuilder;
+
+    #[test]
+    fn table_ddl_has_expected_columns() {
+        let sql = jobs_table().to_string(MysqlQueryBuilder).to_lowercase();
+
+        assert!(sql.contains("`jobs`"), "table name: {sql}");
+        assert!(
+            sql.contains("`id`") && sql.contains("auto_increment") && sql.contains("primary key"),
+            "id pk: {sql}"
+        );
+        assert!(
+            sql.contains("`status`") && sql.contains("default 'queued'"),
+            "status default: {sql}"
+        );
+        assert!(sql.contains("`kind` varchar(32)"), "kind: {sql}");
+        // The resume cursor is nullable: a fresh job has processed nothing.
+        assert!(
+            sql.contains("`cursor` bigint unsigned null"),
+            "cursor: {sql}"
+        );
+        for counter in ["processed", "affected", "failed"] {
+            assert!(
+                sql.contains(&format!("`{counter}` bigint unsigned not null default 0")),
+                "{counter} counter: {sql}"
+            );
+        }
+        assert!(sql.contains("`params_json` text not null"), "params: {sql}");
+        assert!(
+            sql.contains("`finished_at` datetime null"),
+            "finished_at: {sql}"
+        );
+    }
+}
@@ -0,0 +1,186 @@
+//! Create the zm-api-owned `notification_rules` table.
+//!
+//! One row per user notification rule: *when* (`trigger`, plus an optional
+//! filter-AST `predicate_json` over the event and a `threshold`), *how*
+//! (`channel` + `target`, with an optional subject/body `template_json`) and
+//! *how often* (`dedupe_window_seconds`). This is the notification half of a
+//! ZoneMinder `Filters` row (`AutoEmail`/`AutoMessage`), evaluated in-process
+//! as events open and close instead of by `zmfilter.pl` polling.
+//!
+//! `trigger`/`channel` are short portable strings rather than native `ENUM`s,
+//! so the same migration runs on MySQL and Postgres. `user_id` is a logical FK
+//! to `Users.Id` (zm-api does not own that table). Columns are snake_case to
+//! match the hand-written entity in `src/entity/notification_rules.rs`.
+
+use sea_orm_migration::prelude::*;
+
+#[derive(DeriveMigrationName)]
+pub struct Migration;
+
+/// The `notification_rules` table create statement. Extracted so the DDL can
+/// be rendered and asserted offline (the migration itself needs a live DB).
+fn notification_rules_table() -> TableCreateStatement {
+    Table::create()
+        .table(NotificationRules::Table)
+        .if_not_exists()
+        .col(
+            ColumnDef::new(NotificationRules::Id)
+                .big_unsigned()
+                .not_null()
+                .auto_increment()
+                .primary_key(),
+        )
+        .col(
+            ColumnDef::new(NotificationRules::UserId)
+                .unsigned()
+                .not_null(),
+        )
+        .col(
+            ColumnDef::new(NotificationRules::Name)
+                .string_len(64)
+                .not_null(),
+        )
+        .col(
+            ColumnDef::new(NotificationRules::Enabled)
+                .boolean()
+                .not_null()
+                .default(true),
+        )
+        .col(
+            ColumnDef::new(NotificationRules::Trigger)
+                .string_len(32)
+                .not_null(),
+        )
+        // Filter AST over the event; NULL matches every event the owner may see.
+        .col(
+            ColumnDef::new(NotificationRules::PredicateJson)
+                .text()
+                .null(),
+        )
+        // Minimum score (score_threshold) or free-space percent (storage_health).
+        .col(
+            ColumnDef::new(NotificationRules::Threshold)
+                .unsigned()
+                .null(),
+        )
+        .col(
+            ColumnDef::new(NotificationRules::Channel)
+                .string_len(16)
+                .not_null(),
+        )
+        // Email address, webhook URL or MQTT topic, per channel.
+        .col(
+            ColumnDef::new(NotificationRules::Target)
+                .string_len(512)
+                .not_null(),
+        )
+        .col(
+            ColumnDef::new(NotificationRules::TemplateJson)
+                .text()
+                .null(),
+        )
+        .col(
+            ColumnDef::new(NotificationRules::DedupeWindowSeconds)
+                .unsigned()
+                .not_null()
+                .default(0),
+        )
+        .col(
+            ColumnDef::new(NotificationRules::CreatedAt)
+                .date_time()
+                .not_null(),
+        )
+        .col(
+            ColumnDef::new(NotificationRules::UpdatedAt)
+                .date_time()
+                .not_null(),
+        )
+        .to_owned()
+}
+
+#[async_trait::async_trait]
+impl MigrationTrait for Migration {
+    async fn up(&self, manager: &SchemaManager) -> Result<(), DbErr> {
+        manager.create_table(notification_rules_table()).await?;
+
+        // Every list is scoped to the caller unless they hold System.
+        manager
+            .create_index(
+                Index::create()
+                    .if_not_exists()
+                    .name("idx_notification_rules_user")
+                    .table(NotificationRules::Table)
+                    .col(NotificationRules::UserId)
+                    .to_owned(),
+            )
+            .await
+    }
+
+    async fn down(&self, manager: &SchemaManager) -> Result<(), DbErr> {
+        manager
+            .drop_table(Table::drop().table(NotificationRules::Table).to_owned())
+            .await
+    }
+}
+
+/// Idens spell the table/column names exactly as the entity expects them.
+#[derive(DeriveIden)]
+enum NotificationRules {
+    #[sea_orm(iden = "notification_rules")]
+    Table,
+    #[sea_orm(iden = "id")]
+    Id,
+    #[sea_orm(iden = "user_id")]
+    UserId,
+    #[sea_orm(iden = "name")]
+    Name,
+    #[sea_orm(iden = "enabled")]
+    Enabled,
+    #[sea_orm(iden = "trigger")]
+    Trigger,
+    #[sea_orm(iden = "predicate_json")]
+    PredicateJson,
+    #[sea_orm(iden = "threshold")]
+    Threshold,
+    #[sea_orm(iden = "channel")]
+    Channel,
+    #[sea_orm(iden = "target")]
+    Target,
+    #[sea_orm(iden = "template_json")]
+    TemplateJson,
+    #[sea_orm(iden = "dedupe_window_seconds")]
+    DedupeWindowSeconds,
+    #[sea_orm(iden = "created_at")]
+    CreatedAt,
+    #[sea_orm(iden = "updated_at")]
+    UpdatedAt,
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use sea_orm::sea_query::MysqlQueryBuilder;
+
+    #[test]
+    fn table_ddl_has_expected_columns() {
+        let sql = notification_rules_table()
+            .to_string(MysqlQueryBuilder)
+            .to_lowercase();
+
+        assert!(sql.contains("`notification_rules`"), "table name: {sql}");
+        assert!(
+            sql.contains("`user_id` int unsigned not null"),
+            "owner: {sql}"
+        );
+        assert!(sql.contains("`trigger` varchar(32)"), "trigger: {sql}");
+        assert!(sql.contains("`channel` varchar(16)"), "channel: {sql}");
+        assert!(
+            sql.contains("`predicate_json` text null"),
+            "predicate is optional: {sql}"
+        );
+        assert!(
+            sql.contains("`dedupe_window_seconds` int unsigned not null default 0"),
+            "dedupe window: {sql}"
+        );
+    }
+}
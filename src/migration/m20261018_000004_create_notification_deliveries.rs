@@ -0,0 +1,190 @@
+//! Create the zm-api-owned `notification_deliveries` table.
+//!
+//! One row per attempt to deliver a notification: which rule fired, for which
+//! event (or storage), over which channel, and whether it got through. This
+//! replaces ZoneMinder's per-event `Events.Emailed`/`Messaged` flags, which can
+//! only say "something was sent once" — a rule may email but fail to post, and
+//! two rules may notify about the same event. It is also the dedupe window's
+//! memory: a rule is suppressed while a recent `sent` row shares its key.
+//!
+//! `rule_id`, `event_id` and `storage_id` are logical FKs; events are deleted by
+//! ZoneMinder and the reaper, and the delivery history should outlive them.
+
+use sea_orm_migration::prelude::*;
+
+#[derive(DeriveMigrationName)]
+pub struct Migration;
+
+/// The `notification_deliveries` table create statement. Extracted so the DDL
+/// can be rendered and asserted offline (the migration itself needs a live DB).
+fn notification_deliveries_table() -> TableCreateStatement {
+    Table::create()
+        .table(NotificationDeliveries::Table)
+        .if_not_exists()
+        .col(
+            ColumnDef::new(NotificationDeliveries::Id)
+                .big_unsigned()
+                .not_null()
+                .auto_increment()
+                .primary_key(),
+        )
+        .col(
+            ColumnDef::new(NotificationDeliveries::RuleId)
+                .big_unsigned()
+                .not_null(),
+        )
+        .col(
+            ColumnDef::new(NotificationDeliveries::Trigger)
+                .string_len(32)
+                .not_null(),
+        )
+        .col(
+            ColumnDef::new(NotificationDeliveries::Channel)
+                .string_len(16)
+                .not_null(),
+        )
+        .col(
+            ColumnDef::new(NotificationDeliveries::EventId)
+                .big_unsigned()
+                .null(),
+        )
+        .col(
+            ColumnDef::new(NotificationDeliveries::MonitorId)
+                .unsigned()
+                .null(),
+        )
+        .col(
+            ColumnDef::new(NotificationDeliveries::StorageId)
+                .small_unsigned()
+                .null(),
+        )
+        // What the dedupe window groups by, e.g. `monitor:3` or `storage:1`.
+        .col(
+            ColumnDef::new(NotificationDeliveries::DedupeKey)
+                .string_len(64)
+                .not_null(),
+        )
+        .col(
+            ColumnDef::new(NotificationDeliveries::Status)
+                .string_len(16)
+                .not_null(),
+        )
+        .col(
+            ColumnDef::new(NotificationDeliveries::Attempts)
+                .unsigned()
+                .not_null()
+                .default(0),
+        )
+        .col(ColumnDef::new(NotificationDeliveries::Error).text().null())
+        .col(
+            ColumnDef::new(NotificationDeliveries::CreatedAt)
+                .date_time()
+                .not_null(),
+        )
+        .to_owned()
+}
+
+#[async_trait::async_trait]
+impl MigrationTrait for Migration {
+    async fn up(&self, manager: &SchemaManager) -> Result<(), DbErr> {
+        manager
+            .create_table(notification_deliveries_table())
+            .await?;
+
+        // Dedupe lookups: the latest delivery of a rule for one key.
+        manager
+            .create_index(
+                Index::create()
+                    .if_not_exists()
+                    .name("idx_notification_deliveries_rule_key")
+                    .table(NotificationDeliveries::Table)
+                    .col(NotificationDeliveries::RuleId)
+                    .col(NotificationDeliveries::DedupeKey)
+                    .col(NotificationDeliveries::CreatedAt)
+                    .to_owned(),
+            )
+            .await?;
+
+        // "What was sent about this event?"
+        manager
+            .create_index(
+                Index::create()
+                    .if_not_exists()
+                    .name("idx_notification_deliveries_event")
+                    .table(NotificationDeliveries::Table)
+                    .col(NotificationDeliveries::EventId)
+                    .to_owned(),
+            )
+            .await
+    }
+
+    async fn down(&self, manager: &SchemaManager) -> Result<(), DbErr> {
+        manager
+            .drop_table(
+                Table::drop()
+                    .table(NotificationDeliveries::Table)
+                    .to_owned(),
+            )
+            .await
+    }
+}
+
+/// Idens spell the table/column names exactly as the entity expects them.
+#[derive(DeriveIden)]
+enum NotificationDeliveries {
+    #[sea_orm(iden = "notification_deliveries")]
+    Table,
+    #[sea_orm(iden = "id")]
+    Id,
+    #[sea_orm(iden = "rule_id")]
+    RuleId,
+    #[sea_orm(iden = "trigger")]
+    Trigger,
+    #[sea_orm(iden = "channel")]
+    Channel,
+    #[sea_orm(iden = "event_id")]
+    EventId,
+    #[sea_orm(iden = "monitor_id")]
+    MonitorId,
+    #[sea_orm(iden = "storage_id")]
+    StorageId,
+    #[sea_orm(iden = "dedupe_key")]
+    DedupeKey,
+    #[sea_orm(iden = "status")]
+    Status,
+    #[sea_orm(iden = "attempts")]
+    Attempts,
+    #[sea_orm(iden = "error")]
+    Error,
+    #[sea_orm(iden = "created_at")]
+    CreatedAt,
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use sea_orm::sea_query::MysqlQueryBuilder;
+
+    #[test]
+    fn table_ddl_has_expected_columns() {
+        let sql = notification_deliveries_table()
+            .to_string(MysqlQueryBuilder)
+            .to_lowercase();
+
+        assert!(sql.contains("`notification_deliveries`"), "table: {sql}");
+        assert!(
+            sql.contains("`rule_id` bigint unsigned not null"),
+            "rule: {sql}"
+        );
+        // A storage_health delivery has no event.
+        assert!(
+            sql.contains("`event_id` bigint unsigned null"),
+            "event: {sql}"
+        );
+        assert!(
+            sql.contains("`dedupe_key` varchar(64)"),
+            "dedupe key: {sql}"
+        );
+        assert!(sql.contains("`status` varchar(16)"), "status: {sql}");
+    }
+}
@@ -0,0 +1,139 @@
+//! Add webhook signing and the per-call audit trail to the notification tables.
+//!
+//! - `notification_rules.secret`: the per-hook HMAC-SHA256 key. Webhook rules
+//!   always carry one; other channels leave it NULL.
+//! - `notification_deliveries.response_status`: the HTTP status of the last
+//!   webhook call (NULL for other channels, or when no response arrived).
+//! - `notification_deliveries.attempt_log_json`: every call made for the
+//!   delivery — time, status, error, duration — as a JSON array.
+//!
+//! MySQL has no `ADD COLUMN IF NOT EXISTS`, so each column is added only when
+//! `has_column` says it is missing; re-running the migration is a no-op.
+
+use sea_orm_migration::prelude::*;
+
+#[derive(DeriveMigrationName)]
+pub struct Migration;
+
+fn add_secret() -> TableAlterStatement {
+    Table::alter()
+        .table(NotificationRules::Table)
+        .add_column(
+            ColumnDef::new(NotificationRules::Secret)
+                .string_len(128)
+                .null(),
+        )
+        .to_owned()
+}
+
+fn add_response_status() -> TableAlterStatement {
+    Table::alter()
+        .table(NotificationDeliveries::Table)
+        .add_column(
+            ColumnDef::new(NotificationDeliveries::ResponseStatus)
+                .small_unsigned()
+                .null(),
+        )
+        .to_owned()
+}
+
+fn add_attempt_log() -> TableAlterStatement {
+    Table::alter()
+        .table(NotificationDeliveries::Table)
+        .add_column(
+            ColumnDef::new(NotificationDeliveries::AttemptLogJson)
+                .text()
+                .null(),
+        )
+        .to_owned()
+}
+
+#[async_trait::async_trait]
+impl MigrationTrait for Migration {
+    async fn up(&self, manager: &SchemaManager) -> Result<(), DbErr> {
+        if !manager.has_column("notification_rules", "secret").await? {
+            manager.alter_table(add_secret()).await?;
+        }
+        if !manager
+            .has_column("notification_deliveries", "response_status")
+            .await?
+        {
+            manager.alter_table(add_response_status()).await?;
+        }
+        if !manager
+            .has_column("notification_deliveries", "attempt_log_json")
+            .await?
+        {
+            manager.alter_table(add_attempt_log()).await?;
+        }
+        Ok(())
+    }
+
+    async fn down(&self, manager: &SchemaManager) -> Result<(), DbErr> {
+        manager
+            .alter_table(
+                Table::alter()
+                    .table(NotificationDeliveries::Table)
+                    .drop_column(NotificationDeliveries::AttemptLogJson)
+                    .drop_column(NotificationDeliveries::ResponseStatus)
+                    .to_owned(),
+            )
+            .await?;
+        manager
+            .alter_table(
+                Table::alter()
+                    .table(NotificationRules::Table)
+                    .drop_column(NotificationRules::Secret)
+                    .to_owned(),
+            )
+            .await
+    }
+}
+
+/// Idens spell the table/column names exactly as the entities expect them.
+#[derive(DeriveIden)]
+enum NotificationRules {
+    #[sea_orm(iden = "notification_rules")]
+    Table,
+    #[sea_orm(iden = "secret")]
+    Secret,
+}
+
+#[derive(DeriveIden)]
+enum NotificationDeliveries {
+    #[sea_orm(iden = "notification_deliveries")]
+    Table,
+    #[sea_orm(iden = "response_status")]
+    ResponseStatus,
+    #[sea_orm(iden = "attempt_log_json")]
+    AttemptLogJson,
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use sea_orm::sea_query::MysqlQueryBuilder;
+
+    #[test]
+    fn alter_ddl_adds_nullable_columns() {
+        let sql = add_secret().to_string(MysqlQueryBuilder).to_lowercase();
+        assert!(
+            sql.contains("alter table `notification_rules` add column `secret` varchar(128) null"),
+            "secret: {sql}"
+        );
+        let sql = add_response_status()
+            .to_string(MysqlQueryBuilder)
+            .to_lowercase();
+        assert!(
+            sql.contains("`response_status` smallint unsigned null"),
+            "response status: {sql}"
+        );
+        let sql = add_attempt_log()
+            .to_string(MysqlQueryBuilder)
+            .to_lowercase();
+        assert!(
+            sql.contains("`attempt_log_json` text null"),
+            "attempt log: {sql}"
+        );
+    }
+}
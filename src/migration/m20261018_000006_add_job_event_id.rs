@@ -0,0 +1,77 @@
+//! Add `jobs.event_id` for per-event media jobs.
+//!
+//! Transcode, export, move and copy jobs (`POST /api/v3/events/{id}/jobs`) act
+//! on a single event. Recording it in its own indexed column — rather than only
+//! inside `params_json` — lets submission look up the event's queued or running
+//! job and refuse a second one. The check alone is not atomic; the unique key
+//! added by `m20261018_000011_unique_active_event_job` enforces it. Bulk jobs
+//! leave it NULL.
+//!
+//! MySQL has no `ADD COLUMN IF NOT EXISTS`, so the column is added only when
+//! `has_column` says it is missing; re-running the migration is a no-op.
+
+use sea_orm_migration::prelude::*;
+
+#[derive(DeriveMigrationName)]
+pub struct Migration;
+
+fn add_event_id() -> TableAlterStatement {
+    Table::alter()
+        .table(Jobs::Table)
+        .add_column(ColumnDef::new(Jobs::EventId).big_unsigned().null())
+        .to_owned()
+}
+
+#[async_trait::async_trait]
+impl MigrationTrait for Migration {
+    async fn up(&self, manager: &SchemaManager) -> Result<(), DbErr> {
+        if !manager.has_column("jobs", "event_id").await? {
+            manager.alter_table(add_event_id()).await?;
+        }
+        manager
+            .create_index(
+                Index::create()
+                    .if_not_exists()
+                    .name("idx_jobs_event_id")
+                    .table(Jobs::Table)
+                    .col(Jobs::EventId)
+                    .to_owned(),
+            )
+            .await
+    }
+
+    async fn down(&self, manager: &SchemaManager) -> Result<(), DbErr> {
+        manager
+            .alter_table(
+                Table::alter()
+                    .table(Jobs::Table)
+                    .drop_column(Jobs::EventId)
+                    .to_owned(),
+            )
+            .await
+    }
+}
+
+/// Idens spell the table/column names exactly as the entity expects them.
+#[derive(DeriveIden)]
+enum Jobs {
+    #[sea_orm(iden = "jobs")]
+    Table,
+    #[sea_orm(iden = "event_id")]
+    EventId,
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use sea_orm::sea_query::MysqlQueryBuilder;
+
+    #[test]
+    fn alter_ddl_adds_nullable_event_id() {
+        let sql = add_event_id().to_string(MysqlQueryBuilder).to_lowercase();
+        assert!(
+            sql.contains("alter table `jobs` add column `event_id` bigint unsigned null"),
+            "event_id: {sql}"
+        );
+    }
+}
@@ -0,0 +1,79 @@
+//! Add `Events.deleted_at`, the soft-delete tombstone.
+//!
+//! `DELETE /api/v3/events/{id}` no longer drops the row: it stamps
+//! `deleted_at` and moves the media into its storage's `.trash/` directory.
+//! Every list/count/search query skips tombstoned rows, `POST
+//! /api/v3/events/{id}:restore` clears the stamp within the grace period, and
+//! the trash reaper hard-deletes the row and its media once that has passed.
+//! The index serves the reaper's "tombstoned before the cutoff" scan.
+//!
+//! The column is snake_case, unlike ZoneMinder's own `Events` columns, to mark
+//! it as zm-api's; ZoneMinder ignores it. MySQL has no `ADD COLUMN IF NOT
+//! EXISTS`, so the column is added only when `has_column` says it is missing;
+//! re-running the migration is a no-op.
+
+use sea_orm_migration::prelude::*;
+
+#[derive(DeriveMigrationName)]
+pub struct Migration;
+
+fn add_deleted_at() -> TableAlterStatement {
+    Table::alter()
+        .table(Events::Table)
+        .add_column(ColumnDef::new(Events::DeletedAt).date_time().null())
+        .to_owned()
+}
+
+#[async_trait::async_trait]
+impl MigrationTrait for Migration {
+    async fn up(&self, manager: &SchemaManager) -> Result<(), DbErr> {
+        if !manager.has_column("Events", "deleted_at").await? {
+            manager.alter_table(add_deleted_at()).await?;
+        }
+        manager
+            .create_index(
+                Index::create()
+                    .if_not_exists()
+                    .name("idx_events_deleted_at")
+                    .table(Events::Table)
+                    .col(Events::DeletedAt)
+                    .to_owned(),
+            )
+            .await
+    }
+
+    async fn down(&self, manager: &SchemaManager) -> Result<(), DbErr> {
+        manager
+            .alter_table(
+                Table::alter()
+                    .table(Events::Table)
+                    .drop_column(Events::DeletedAt)
+                    .to_owned(),
+            )
+            .await
+    }
+}
+
+/// Idens spell the table/column names exactly as the entity expects them.
+#[derive(DeriveIden)]
+enum Events {
+    #[sea_orm(iden = "Events")]
+    Table,
+    #[sea_orm(iden = "deleted_at")]
+    DeletedAt,
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use sea_orm::sea_query::MysqlQueryBuilder;
+
+    #[test]
+    fn alter_ddl_adds_nullable_deleted_at() {
+        let sql = add_deleted_at().to_string(MysqlQueryBuilder).to_lowercase();
+        assert!(
+            sql.contains("alter table `events` add column `deleted_at` datetime null"),
+            "deleted_at: {sql}"
+        );
+    }
+}
@@ -0,0 +1,204 @@
+//! Create the zm-api-owned `storage_retention` and `monitor_retention` tables.
+//!
+//! Per-storage and per-monitor overrides of the global `[retention]` knobs,
+//! editable over the API. One row per storage / monitor that has any; every
+//! value column is nullable, and NULL means "inherit" — from the storage for a
+//! monitor, from `[retention]` for a storage. `storage_retention` also names
+//! the colder storage expiring events are moved to instead of deleted.
+//!
+//! `storage_id` / `monitor_id` are the primary keys and *logical* FKs to
+//! `Storage.Id` / `Monitors.Id`; no hard constraint is created because zm-api
+//! does not own ZoneMinder's tables. Columns are snake_case to match the
+//! hand-written entities in `src/entity/`.
+
+use sea_orm_migration::prelude::*;
+
+#[derive(DeriveMigrationName)]
+pub struct Migration;
+
+/// The `storage_retention` create statement. Extracted so the DDL can be
+/// rendered and asserted offline (the migration itself needs a live DB).
+fn storage_retention_table() -> TableCreateStatement {
+    Table::create()
+        .table(StorageRetention::Table)
+        .if_not_exists()
+        .col(
+            ColumnDef::new(StorageRetention::StorageId)
+                .small_unsigned()
+                .not_null()
+                .primary_key(),
+        )
+        .col(
+            ColumnDef::new(StorageRetention::MaxAgeDays)
+                .unsigned()
+                .null(),
+        )
+        .col(
+            ColumnDef::new(StorageRetention::MaxStorageBytes)
+                .big_unsigned()
+                .null(),
+        )
+        .col(
+            ColumnDef::new(StorageRetention::ProtectArchived)
+                .boolean()
+                .null(),
+        )
+        // Where expiring events go instead of being deleted; logical FK to
+        // `Storage.Id`. NULL deletes them.
+        .col(
+            ColumnDef::new(StorageRetention::TierStorageId)
+                .small_unsigned()
+                .null(),
+        )
+        .col(
+            ColumnDef::new(StorageRetention::CreatedAt)
+                .date_time()
+                .not_null(),
+        )
+        .col(
+            ColumnDef::new(StorageRetention::UpdatedAt)
+                .date_time()
+                .not_null(),
+        )
+        .to_owned()
+}
+
+/// The `monitor_retention` create statement.
+fn monitor_retention_table() -> TableCreateStatement {
+    Table::create()
+        .table(MonitorRetention::Table)
+        .if_not_exists()
+        .col(
+            ColumnDef::new(MonitorRetention::MonitorId)
+                .unsigned()
+                .not_null()
+                .primary_key(),
+        )
+        .col(
+            ColumnDef::new(MonitorRetention::MaxAgeDays)
+                .unsigned()
+                .null(),
+        )
+        .col(
+            ColumnDef::new(MonitorRetention::MaxStorageBytes)
+                .big_unsigned()
+                .null(),
+        )
+        .col(
+            ColumnDef::new(MonitorRetention::ProtectArchived)
+                .boolean()
+                .null(),
+        )
+        .col(
+            ColumnDef::new(MonitorRetention::CreatedAt)
+                .date_time()
+                .not_null(),
+        )
+        .col(
+            ColumnDef::new(MonitorRetention::UpdatedAt)
+                .date_time()
+                .not_null(),
+        )
+        .to_owned()
+}
+
+#[async_trait::async_trait]
+impl MigrationTrait for Migration {
+    async fn up(&self, manager: &SchemaManager) -> Result<(), DbErr> {
+        manager.create_table(storage_retention_table()).await?;
+        manager.create_table(monitor_retention_table()).await
+    }
+
+    async fn down(&self, manager: &SchemaManager) -> Result<(), DbErr> {
+        manager
+            .drop_table(Table::drop().table(MonitorRetention::Table).to_owned())
+            .await?;
+        manager
+            .drop_table(Table::drop().table(StorageRetention::Table).to_owned())
+            .await
+    }
+}
+
+/// Idens spell the table/column names exactly as the entities expect them.
+#[derive(DeriveIden)]
+enum StorageRetention {
+    #[sea_orm(iden = "storage_retention")]
+    Table,
+    #[sea_orm(iden = "storage_id")]
+    StorageId,
+    #[sea_orm(iden = "max_age_days")]
+    MaxAgeDays,
+    #[sea_orm(iden = "max_storage_bytes")]
+    MaxStorageBytes,
+    #[sea_orm(iden = "protect_archived")]
+    ProtectArchived,
+    #[sea_orm(iden = "tier_storage_id")]
+    TierStorageId,
+    #[sea_orm(iden = "created_at")]
+    CreatedAt,
+    #[sea_orm(iden = "updated_at")]
+    UpdatedAt,
+}
+
+#[derive(DeriveIden)]
+enum MonitorRetention {
+    #[sea_orm(iden = "monitor_retention")]
+    Table,
+    #[sea_orm(iden = "monitor_id")]
+    MonitorId,
+    #[sea_orm(iden = "max_age_days")]
+    MaxAgeDays,
+    #[sea_orm(iden = "max_storage_bytes")]
+    MaxStorageBytes,
+    #[sea_orm(iden = "protect_archived")]
+    ProtectArchived,
+    #[sea_orm(iden = "created_at")]
+    CreatedAt,
+    #[sea_orm(iden = "updated_at")]
+    UpdatedAt,
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use sea_orm::sea_query::MysqlQueryBuilder;
+
+    /// Render the DDL offline and assert its shape: keyed by the owning row,
+    /// no auto-increment, and every override nullable so NULL can mean
+    /// "inherit".
+    #[test]
+    fn table_ddl_has_nullable_overrides() {
+        let storage = storage_retention_table()
+            .to_string(MysqlQueryBuilder)
+            .to_lowercase();
+        assert!(
+            storage.contains("`storage_retention`") && storage.contains("primary key"),
+            "storage pk: {storage}"
+        );
+        assert!(!storage.contains("auto_increment"), "{storage}");
+        for col in [
+            "`max_age_days` int unsigned null",
+            "`max_storage_bytes` bigint unsigned null",
+            "`protect_archived` bool null",
+            "`tier_storage_id` smallint unsigned null",
+        ] {
+            assert!(storage.contains(col), "{col}: {storage}");
+        }
+
+        let monitor = monitor_retention_table()
+            .to_string(MysqlQueryBuilder)
+            .to_lowercase();
+        assert!(
+            monitor.contains("`monitor_retention`") && monitor.contains("primary key"),
+            "monitor pk: {monitor}"
+        );
+        assert!(
+            !monitor.contains("tier_storage_id"),
+            "tiering is per storage: {monitor}"
+        );
+        assert!(
+            monitor.contains("`max_storage_bytes` bigint unsigned null"),
+            "{monitor}"
+        );
+    }
+}
@@ -0,0 +1,96 @@
+//! Create the zm-api-owned `rtsp_credentials` table.
+//!
+//! RTSP clients authenticate with HTTP Digest, which needs
+//! `HA1 = MD5(username:realm:password)` on the server side. ZoneMinder only
+//! keeps a bcrypt hash in `Users.Password`, so the RTSP server cannot derive
+//! HA1 from it; instead HA1 is captured whenever zm-api sees the plaintext
+//! password (login, password change, admin password reset) and stored here.
+//!
+//! `user_id` is the primary key and a *logical* FK to `Users.Id`; no hard
+//! constraint is created because zm-api does not own ZoneMinder's tables. The
+//! realm is stored alongside so changing `[streaming.rtsp_proxy].realm`
+//! invalidates every cached digest instead of silently mismatching.
+//! `m20261018_000012_add_rtsp_password_fingerprint` later ties each digest to
+//! the `Users.Password` it was captured against.
+
+use sea_orm_migration::prelude::*;
+
+#[derive(DeriveMigrationName)]
+pub struct Migration;
+
+/// The create statement. Extracted so the DDL can be rendered and asserted
+/// offline (the migration itself needs a live DB).
+fn rtsp_credentials_table() -> TableCreateStatement {
+    Table::create()
+        .table(RtspCredentials::Table)
+        .if_not_exists()
+        .col(
+            ColumnDef::new(RtspCredentials::UserId)
+                .unsigned()
+                .not_null()
+                .primary_key(),
+        )
+        .col(
+            ColumnDef::new(RtspCredentials::Realm)
+                .string_len(128)
+                .not_null(),
+        )
+        // Lower-case hex MD5: always 32 characters.
+        .col(ColumnDef::new(RtspCredentials::Ha1).char_len(32).not_null())
+        .col(
+            ColumnDef::new(RtspCredentials::UpdatedAt)
+                .date_time()
+                .not_null(),
+        )
+        .to_owned()
+}
+
+#[async_trait::async_trait]
+impl MigrationTrait for Migration {
+    async fn up(&self, manager: &SchemaManager) -> Result<(), DbErr> {
+        manager.create_table(rtsp_credentials_table()).await
+    }
+
+    async fn down(&self, manager: &SchemaManager) -> Result<(), DbErr> {
+        manager
+            .drop_table(Table::drop().table(RtspCredentials::Table).to_owned())
+            .await
+    }
+}
+
+/// Idens spell the table/column names exactly as the entity expects them.
+#[derive(DeriveIden)]
+enum RtspCredentials {
+    #[sea_orm(iden = "rtsp_credentials")]
+    Table,
+    #[sea_orm(iden = "user_id")]
+    UserId,
+    #[sea_orm(iden = "realm")]
+    Realm,
+    #[sea_orm(iden = "ha1")]
+    Ha1,
+    #[sea_orm(iden = "updated_at")]
+    UpdatedAt,
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use sea_orm::sea_query::MysqlQueryBuilder;
+
+    /// Render the DDL offline: keyed by the user, no auto-increment, and the
+    /// digest sized for hex MD5.
+    #[test]
+    fn table_ddl_is_keyed_by_user() {
+        let sql = rtsp_credentials_table()
+            .to_string(MysqlQueryBuilder)
+            .to_lowercase();
+        assert!(
+            sql.contains("`rtsp_credentials`") && sql.contains("primary key"),
+            "{sql}"
+        );
+        assert!(!sql.contains("auto_increment"), "{sql}");
+        assert!(sql.contains("`ha1` char(32) not null"), "{sql}");
+        assert!(sql.contains("`realm` varchar(128) not null"), "{sql}");
+    }
+}
@@ -0,0 +1,113 @@
+//! Create the zm-api-owned `ptz_tours` table.
+//!
+//! A tour is a named, ordered list of presets a PTZ camera cycles through,
+//! each held for its dwell time. `steps_json` holds the steps and
+//! `schedule_json` the optional weekly windows the tour runs in on its own;
+//! both are validated on write, so the columns stay plain `TEXT`.
+//!
+//! `monitor_id` is a *logical* FK to `Monitors.Id`; no hard constraint is
+//! created because zm-api does not own ZoneMinder's tables. Names are unique
+//! per monitor.
+
+use sea_orm_migration::prelude::*;
+
+#[derive(DeriveMigrationName)]
+pub struct Migration;
+
+/// The create statement. Extracted so the DDL can be rendered and asserted
+/// offline (the migration itself needs a live DB).
+fn ptz_tours_table() -> TableCreateStatement {
+    Table::create()
+        .table(PtzTours::Table)
+        .if_not_exists()
+        .col(
+            ColumnDef::new(PtzTours::Id)
+                .big_unsigned()
+                .not_null()
+                .auto_increment()
+                .primary_key(),
+        )
+        .col(ColumnDef::new(PtzTours::MonitorId).unsigned().not_null())
+        .col(ColumnDef::new(PtzTours::Name).string_len(64).not_null())
+        .col(
+            ColumnDef::new(PtzTours::Enabled)
+                .boolean()
+                .not_null()
+                .default(true),
+        )
+        .col(ColumnDef::new(PtzTours::StepsJson).text().not_null())
+        .col(ColumnDef::new(PtzTours::ScheduleJson).text().null())
+        .col(ColumnDef::new(PtzTours::CreatedAt).date_time().not_null())
+        .col(ColumnDef::new(PtzTours::UpdatedAt).date_time().not_null())
+        .to_owned()
+}
+
+#[async_trait::async_trait]
+impl MigrationTrait for Migration {
+    async fn up(&self, manager: &SchemaManager) -> Result<(), DbErr> {
+        manager.create_table(ptz_tours_table()).await?;
+
+        manager
+            .create_index(
+                Index::create()
+                    .if_not_exists()
+                    .name("uniq_ptz_tours_monitor_name")
+                    .table(PtzTours::Table)
+                    .col(PtzTours::MonitorId)
+                    .col(PtzTours::Name)
+                    .unique()
+                    .to_owned(),
+            )
+            .await
+    }
+
+    async fn down(&self, manager: &SchemaManager) -> Result<(), DbErr> {
+        manager
+            .drop_table(Table::drop().table(PtzTours::Table).to_owned())
+            .await
+    }
+}
+
+/// Idens spell the table/column names exactly as the entity expects them.
+#[derive(DeriveIden)]
+enum PtzTours {
+    #[sea_orm(iden = "ptz_tours")]
+    Table,
+    #[sea_orm(iden = "id")]
+    Id,
+    #[sea_orm(iden = "monitor_id")]
+    MonitorId,
+    #[sea_orm(iden = "name")]
+    Name,
+    #[sea_orm(iden = "enabled")]
+    Enabled,
+    #[sea_orm(iden = "steps_json")]
+    StepsJson,
+    #[sea_orm(iden = "schedule_json")]
+    ScheduleJson,
+    #[sea_orm(iden = "created_at")]
+    CreatedAt,
+    #[sea_orm(iden = "updated_at")]
+    UpdatedAt,
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use sea_orm::sea_query::MysqlQueryBuilder;
+
+    /// Render the DDL offline: auto-increment key, steps required, schedule
+    /// optional.
+    #[test]
+    fn table_ddl_has_expected_columns() {
+        let sql = ptz_tours_table()
+            .to_string(MysqlQueryBuilder)
+            .to_lowercase();
+        assert!(sql.contains("`ptz_tours`"), "{sql}");
+        assert!(sql.contains("auto_increment"), "{sql}");
+        assert!(sql.contains("`steps_json` text not null"), "{sql}");
+        assert!(sql.contains("`schedule_json` text null"), "{sql}");
+        assert!(sql.contains("`name` varchar(64) not null"), "{sql}");
+        assert!(sql.contains("`monitor_id` int unsigned not null"), "{sql}");
+    }
+}
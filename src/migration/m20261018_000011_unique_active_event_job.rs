@@ -0,0 +1,109 @@
+//! Allow at most one queued or running media job per event, in the database.
+//!
+//! Submission checks for an active job before inserting, but two concurrent
+//! requests can both pass the check. A unique key over the event of every
+//! *active* job closes that gap: the second insert fails and is answered 409.
+//!
+//! - MySQL/MariaDB: a virtual generated column `active_event_id`, equal to
+//!   `event_id` while the job is `queued`/`running` and NULL otherwise, with
+//!   a UNIQUE index (NULLs never collide).
+//! - PostgreSQL: a partial UNIQUE index on `event_id` over the same statuses.
+//!
+//! Duplicates left by the old check-then-insert are failed first, keeping the
+//! oldest active job per event, so the index can be built.
+
+use sea_orm::{ConnectionTrait, DatabaseBackend};
+use sea_orm_migration::prelude::*;
+
+#[derive(DeriveMigrationName)]
+pub struct Migration;
+
+const INDEX: &str = "idx_jobs_active_event";
+
+const MYSQL_FAIL_DUPLICATES: &str = "UPDATE `jobs` j \
+     JOIN (SELECT `event_id`, MIN(`id`) AS `keep` FROM `jobs` \
+           WHERE `event_id` IS NOT NULL AND `status` IN ('queued', 'running') \
+           GROUP BY `event_id`) k ON j.`event_id` = k.`event_id` \
+     SET j.`status` = 'failed', j.`error` = 'superseded by another job for the event' \
+     WHERE j.`status` IN ('queued', 'running') AND j.`id` <> k.`keep`";
+
+const MYSQL_ADD_COLUMN: &str = "ALTER TABLE `jobs` ADD COLUMN `active_event_id` BIGINT UNSIGNED \
+     AS (CASE WHEN `status` IN ('queued', 'running') THEN `event_id` END) VIRTUAL";
+
+const MYSQL_ADD_INDEX: &str =
+    "CREATE UNIQUE INDEX `idx_jobs_active_event` ON `jobs` (`active_event_id`)";
+
+const POSTGRES_FAIL_DUPLICATES: &str = "UPDATE jobs j \
+     SET status = 'failed', error = 'superseded by another job for the event' \
+     FROM (SELECT event_id, MIN(id) AS keep FROM jobs \
+           WHERE event_id IS NOT NULL AND status IN ('queued', 'running') \
+           GROUP BY event_id) k \
+     WHERE j.event_id = k.event_id AND j.status IN ('queued', 'running') AND j.id <> k.keep";
+
+const POSTGRES_ADD_INDEX: &str = "CREATE UNIQUE INDEX IF NOT EXISTS idx_jobs_active_event \
+     ON jobs (event_id) WHERE status IN ('queued', 'running')";
+
+#[async_trait::async_trait]
+impl MigrationTrait for Migration {
+    async fn up(&self, manager: &SchemaManager) -> Result<(), DbErr> {
+        let db = manager.get_connection();
+        match db.get_database_backend() {
+            DatabaseBackend::MySql => {
+                if manager.has_index("jobs", INDEX).await? {
+                    return Ok(());
+                }
+                db.execute_unprepared(MYSQL_FAIL_DUPLICATES).await?;
+                if !manager.has_column("jobs", "active_event_id").await? {
+                    db.execute_unprepared(MYSQL_ADD_COLUMN).await?;
+                }
+                db.execute_unprepared(MYSQL_ADD_INDEX).await?;
+            }
+            DatabaseBackend::Postgres => {
+                db.execute_unprepared(POSTGRES_FAIL_DUPLICATES).await?;
+                db.execute_unprepared(POSTGRES_ADD_INDEX).await?;
+            }
+            _ => {}
+        }
+        Ok(())
+    }
+
+    async fn down(&self, manager: &SchemaManager) -> Result<(), DbErr> {
+        let db = manager.get_connection();
+        match db.get_database_backend() {
+            DatabaseBackend::MySql => {
+                if manager.has_index("jobs", INDEX).await? {
+                    db.execute_unprepared("DROP INDEX `idx_jobs_active_event` ON `jobs`")
+                        .await?;
+                }
+                if manager.has_column("jobs", "active_event_id").await? {
+                    db.execute_unprepared("ALTER TABLE `jobs` DROP COLUMN `active_event_id`")
+                        .await?;
+                }
+            }
+            DatabaseBackend::Postgres => {
+                db.execute_unprepared("DROP INDEX IF EXISTS idx_jobs_active_event")
+                    .await?;
+            }
+            _ => {}
+        }
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn active_key_covers_only_queued_and_running_jobs() {
+        for sql in [MYSQL_ADD_COLUMN, POSTGRES_ADD_INDEX] {
+            assert!(sql.contains("IN ('queued', 'running')"), "{sql}");
+        }
+        assert!(MYSQL_ADD_COLUMN.contains("THEN `event_id` END"));
+        assert!(MYSQL_ADD_INDEX.starts_with("CREATE UNIQUE INDEX"));
+        // Duplicates keep the oldest job and only ever touch active ones.
+        for sql in [MYSQL_FAIL_DUPLICATES, POSTGRES_FAIL_DUPLICATES] {
+            assert!(sql.contains("MIN(") && sql.contains("<> k."), "{sql}");
+        }
+    }
+}
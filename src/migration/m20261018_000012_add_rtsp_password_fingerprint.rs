@@ -0,0 +1,80 @@
+//! Add `rtsp_credentials.password_fingerprint`.
+//!
+//! A cached HA1 outlives the password it was computed from whenever that
+//! password changes where zm-api does not see it (ZoneMinder's own UI, or
+//! `Users.Password` edited directly). The fingerprint — SHA-256 of the
+//! `Users.Password` hash at capture time — lets the RTSP server notice and
+//! drop such a digest.
+//!
+//! Rows captured before this migration have no fingerprint and are refused
+//! like any other stale digest, so each RTSP user signs in to zm-api once more.
+//!
+//! MySQL has no `ADD COLUMN IF NOT EXISTS`, so the column is added only when
+//! `has_column` says it is missing; re-running the migration is a no-op.
+
+use sea_orm_migration::prelude::*;
+
+#[derive(DeriveMigrationName)]
+pub struct Migration;
+
+fn add_password_fingerprint() -> TableAlterStatement {
+    Table::alter()
+        .table(RtspCredentials::Table)
+        // Lower-case hex SHA-256: always 64 characters.
+        .add_column(
+            ColumnDef::new(RtspCredentials::PasswordFingerprint)
+                .char_len(64)
+                .null(),
+        )
+        .to_owned()
+}
+
+#[async_trait::async_trait]
+impl MigrationTrait for Migration {
+    async fn up(&self, manager: &SchemaManager) -> Result<(), DbErr> {
+        if !manager
+            .has_column("rtsp_credentials", "password_fingerprint")
+            .await?
+        {
+            manager.alter_table(add_password_fingerprint()).await?;
+        }
+        Ok(())
+    }
+
+    async fn down(&self, manager: &SchemaManager) -> Result<(), DbErr> {
+        manager
+            .alter_table(
+                Table::alter()
+                    .table(RtspCredentials::Table)
+                    .drop_column(RtspCredentials::PasswordFingerprint)
+                    .to_owned(),
+            )
+            .await
+    }
+}
+
+/// Idens spell the table/column names exactly as the entity expects them.
+#[derive(DeriveIden)]
+enum RtspCredentials {
+    #[sea_orm(iden = "rtsp_credentials")]
+    Table,
+    #[sea_orm(iden = "password_fingerprint")]
+    PasswordFingerprint,
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use sea_orm::sea_query::MysqlQueryBuilder;
+
+    #[test]
+    fn alter_ddl_adds_nullable_fingerprint() {
+        let sql = add_password_fingerprint()
+            .to_string(MysqlQueryBuilder)
+            .to_lowercase();
+        assert!(
+            sql.contains("add column `password_fingerprint` char(64) null"),
+            "password_fingerprint: {sql}"
+        );
+    }
+}
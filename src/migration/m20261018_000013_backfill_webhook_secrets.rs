@@ -0,0 +1,123 @@
+//! Issue a signing secret to every webhook rule that has none.
+//!
+//! `notification_rules.secret` arrived after the rules themselves, so webhook
+//! rules created before `m20261018_000005_add_webhook_signing` kept a NULL
+//! secret and their calls went out unsigned. Each such rule gets its own
+//! random `whsec_` key, the form the API issues. Its owner has never seen it:
+//! a receiver that wants to verify signatures needs the rule rotated
+//! (`rotate_secret`), which shows the new key once.
+//!
+//! Only NULL or empty secrets are written, so re-running the migration is a
+//! no-op.
+
+use sea_orm_migration::prelude::*;
+use tracing::warn;
+
+#[derive(DeriveMigrationName)]
+pub struct Migration;
+
+/// Ids of the webhook rules without a secret.
+fn unsigned_webhooks() -> SelectStatement {
+    Query::select()
+        .column(NotificationRules::Id)
+        .from(NotificationRules::Table)
+        .and_where(Expr::col(NotificationRules::Channel).eq("webhook"))
+        .cond_where(
+            Cond::any()
+                .add(Expr::col(NotificationRules::Secret).is_null())
+                .add(Expr::col(NotificationRules::Secret).eq("")),
+        )
+        .to_owned()
+}
+
+/// Give rule `id` the secret `secret`, unless it gained one meanwhile.
+fn set_secret(id: u64, secret: &str) -> UpdateStatement {
+    Query::update()
+        .table(NotificationRules::Table)
+        .value(NotificationRules::Secret, secret)
+        .and_where(Expr::col(NotificationRules::Id).eq(id))
+        .cond_where(
+            Cond::any()
+                .add(Expr::col(NotificationRules::Secret).is_null())
+                .add(Expr::col(NotificationRules::Secret).eq("")),
+        )
+        .to_owned()
+}
+
+/// A signing key as `service::notification_rules` issues it: `whsec_` and 32
+/// random alphanumerics.
+fn generate_secret() -> String {
+    format!("whsec_{}", crate::util::random::generate_random_string(32))
+}
+
+#[async_trait::async_trait]
+impl MigrationTrait for Migration {
+    async fn up(&self, manager: &SchemaManager) -> Result<(), DbErr> {
+        let db = manager.get_connection();
+        let backend = db.get_database_backend();
+        let rows = db.query_all(backend.build(&unsigned_webhooks())).await?;
+        let mut issued = 0u64;
+        for row in rows {
+            let id: u64 = row.try_get_by_index(0)?;
+            issued += db
+                .execute(backend.build(&set_secret(id, &generate_secret())))
+                .await?
+                .rows_affected();
+        }
+        if issued > 0 {
+            warn!(
+                rules = issued,
+                "issued signing secrets to webhook rules that had none; rotate them to see the new keys"
+            );
+        }
+        Ok(())
+    }
+
+    async fn down(&self, _manager: &SchemaManager) -> Result<(), DbErr> {
+        // The issued keys are indistinguishable from ones issued by the API;
+        // leaving them in place keeps the calls signed.
+        Ok(())
+    }
+}
+
+/// Idens spell the table/column names exactly as the entity expects them.
+#[derive(DeriveIden)]
+enum NotificationRules {
+    #[sea_orm(iden = "notification_rules")]
+    Table,
+    #[sea_orm(iden = "id")]
+    Id,
+    #[sea_orm(iden = "channel")]
+    Channel,
+    #[sea_orm(iden = "secret")]
+    Secret,
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use sea_orm::sea_query::MysqlQueryBuilder;
+
+    #[test]
+    fn only_webhooks_without_a_secret_are_selected() {
+        let sql = unsigned_webhooks().to_string(MysqlQueryBuilder);
+        assert!(sql.contains("`channel` = 'webhook'"), "{sql}");
+        assert!(sql.contains("(`secret` IS NULL OR `secret` = '')"), "{sql}");
+    }
+
+    #[test]
+    fn a_secret_is_only_written_where_none_is_set() {
+        let sql = set_secret(3, "whsec_abc").to_string(MysqlQueryBuilder);
+        assert!(sql.contains("SET `secret` = 'whsec_abc'"), "{sql}");
+        assert!(sql.contains("`id` = 3"), "{sql}");
+        assert!(sql.contains("`secret` IS NULL OR `secret` = ''"), "{sql}");
+    }
+
+    #[test]
+    fn issued_secrets_look_like_the_apis() {
+        let secret = generate_secret();
+        assert!(secret.starts_with("whsec_"));
+        assert_eq!(secret.len(), 6 + 32);
+        assert_ne!(secret, generate_secret());
+    }
+}
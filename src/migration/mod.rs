@@ -15,6 +15,19 @@ pub mod legacy_bridge;
 mod m00000000_000001_zm_baseline;
 mod m20260625_000001_create_event_synopsis;
 mod m20260627_000001_create_monitor_pipeline;
+mod m20261018_000001_create_saved_searches;
+mod m20261018_000002_create_jobs;
+mod m20261018_000003_create_notification_rules;
+mod m20261018_000004_create_notification_deliveries;
+mod m20261018_000005_add_webhook_signing;
+mod m20261018_000006_add_job_event_id;
+mod m20261018_000007_add_event_deleted_at;
+mod m20261018_000008_create_retention_policies;
+mod m20261018_000009_create_rtsp_credentials;
+mod m20261018_000010_create_ptz_tours;
+mod m20261018_000011_unique_active_event_job;
+mod m20261018_000012_add_rtsp_password_fingerprint;
+mod m20261018_000013_backfill_webhook_secrets;
 pub mod stamp;
 
 pub struct Migrator;
@@ -26,6 +39,19 @@ impl MigratorTrait for Migrator {
             Box::new(m00000000_000001_zm_baseline::Migration),
             Box::new(m20260625_000001_create_event_synopsis::Migration),
             Box::new(m20260627_000001_create_monitor_pipeline::Migration),
+            Box::new(m20261018_000001_create_saved_searches::Migration),
+            Box::new(m20261018_000002_create_jobs::Migration),
+            Box::new(m20261018_000003_create_notification_rules::Migration),
+            Box::new(m20261018_000004_create_notification_deliveries::Migration),
+            Box::new(m20261018_000005_add_webhook_signing::Migration),
+            Box::new(m20261018_000006_add_job_event_id::Migration),
+            Box::new(m20261018_000007_add_event_deleted_at::Migration),
+            Box::new(m20261018_000008_create_retention_policies::Migration),
+            Box::new(m20261018_000009_create_rtsp_credentials::Migration),
+            Box::new(m20261018_000010_create_ptz_tours::Migration),
+            Box::new(m20261018_000011_unique_active_event_job::Migration),
+            Box::new(m20261018_000012_add_rtsp_password_fingerprint::Migration),
+            Box::new(m20261018_000013_backfill_webhook_secrets::Migration),
         ]
     }
 }
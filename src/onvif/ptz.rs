@@ -8,6 +8,7 @@
 //! - [`PtzClient::stop`] — `Stop` (halt pan/tilt and/or zoom).
 //! - [`PtzClient::get_status`] — `GetStatus` (current position + move state).
 //! - [`PtzClient::get_configurations`] — `GetConfigurations` (PTZ config list).
+//! - [`PtzClient::get_nodes`] — `GetNodes` (PTZ nodes and their spaces).
 //!
 //! Each method builds the SOAP body, dispatches it through
 //! [`OnvifTransport::call`] against the PTZ service XAddr, and parses the
@@ -43,6 +44,12 @@ const PTZ_WSDL_NS: &str = "http://www.onvif.org/ver20/ptz/wsdl";
 /// ONVIF common schema namespace (the `tt:` types: PTZVector, PanTilt, Zoom…).
 const ONVIF_SCHEMA_NS: &str = "http://www.onvif.org/ver10/schema";
 
+/// Pan/tilt translation space relative to the field of view, where `±1`
+/// reaches the edges of the picture. Optional; listed in a node's supported
+/// spaces when the camera has it.
+pub const TRANSLATION_SPACE_FOV: &str =
+    "http://www.onvif.org/ver10/tptz/PanTiltSpaces/TranslationSpaceFov";
+
 /// Default per-request timeout when the caller does not specify one.
 const DEFAULT_TIMEOUT: Duration = Duration::from_secs(10);
 
@@ -154,11 +161,32 @@ pub struct PtzStatus {
     pub utc_time: Option<String>,
 }
 
+/// A `<Min>`/`<Max>` pair from a limits range.
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct FloatRange {
+    pub min: f32,
+    pub max: f32,
+}
+
+/// The `<Range>` of a `PanTiltLimits` or `ZoomLimits` element: the space the
+/// bounds are given in and the bounds themselves. `y` is always `None` for
+/// zoom.
+#[derive(Debug, Clone, Default, PartialEq)]
+pub struct SpaceLimits {
+    /// The space URI (`<URI>`), e.g. the generic position space.
+    pub space: Option<String>,
+    /// Bounds of the `x` axis (`<XRange>`).
+    pub x: Option<FloatRange>,
+    /// Bounds of the `y` axis (`<YRange>`).
+    pub y: Option<FloatRange>,
+}
+
 /// A single PTZ configuration entry from `GetConfigurations`.
 ///
-/// We surface the configuration token (needed to drive moves) and its
-/// human-readable name; the full speed/limit tree is intentionally not modeled.
-#[derive(Debug, Clone, Default, PartialEq, Eq)]
+/// We surface the configuration token (needed to drive moves), its
+/// human-readable name and its position limits; the speed tree is
+/// intentionally not modeled.
+#[derive(Debug, Clone, Default, PartialEq)]
 pub struct PtzConfiguration {
     /// The configuration token (the `token` attribute of `PTZConfiguration`).
     pub token: Option<String>,
@@ -166,6 +194,27 @@ pub struct PtzConfiguration {
     pub name: Option<String>,
     /// The associated node token (`<NodeToken>`), if present.
     pub node_token: Option<String>,
+    /// `<PanTiltLimits>`, if the configuration restricts pan/tilt.
+    pub pan_tilt_limits: Option<SpaceLimits>,
+    /// `<ZoomLimits>`, if the configuration restricts zoom.
+    pub zoom_limits: Option<SpaceLimits>,
+}
+
+/// A PTZ node from `GetNodes`, reduced to the relative pan/tilt spaces it
+/// supports.
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct PtzNode {
+    /// The node token (the `token` attribute of `PTZNode`).
+    pub token: Option<String>,
+    /// URIs of every `<RelativePanTiltTranslationSpace>` the node lists.
+    pub relative_pan_tilt_spaces: Vec<String>,
+}
+
+impl PtzNode {
+    /// Whether the node can take a pan/tilt translation in `space`.
+    pub fn supports_relative_pan_tilt(&self, space: &str) -> bool {
+        self.relative_pan_tilt_spaces.iter().any(|s| s == space)
+    }
 }
 
 /// PTZ service client bound to a single device's PTZ-service XAddr.
@@ -266,6 +315,34 @@ impl PtzClient {
         self.call_void(&body, "RelativeMove").await
     }
 
+    /// `RelativeMove` of the pan/tilt axes by `translation` in `space`,
+    /// for the optional spaces (such as [`TRANSLATION_SPACE_FOV`]) a node
+    /// lists beside the generic one.
+    pub async fn relative_move_in_space(
+        &self,
+        profile_token: &str,
+        translation: PanTilt,
+        space: &str,
+    ) -> OnvifResult<()> {
+        let body = format!(
+            concat!(
+                "<tptz:RelativeMove xmlns:tptz=\"{ns}\">",
+                "<tptz:ProfileToken>{profile}</tptz:ProfileToken>",
+                "<tptz:Translation>",
+                "<tt:PanTilt x=\"{x}\" y=\"{y}\" space=\"{space}\" xmlns:tt=\"{tt}\"/>",
+                "</tptz:Translation>",
+                "</tptz:RelativeMove>",
+            ),
+            ns = PTZ_WSDL_NS,
+            profile = xml_escape(profile_token),
+            x = fmt_f32(translation.x),
+            y = fmt_f32(translation.y),
+            space = xml_escape(space),
+            tt = ONVIF_SCHEMA_NS,
+        );
+        self.call_void(&body, "RelativeMove").await
+    }
+
     /// `Stop` — halt motion on `profile_token`. `pan_tilt` and `zoom` select
     /// which axes to stop (ONVIF lets you stop them independently).
     pub async fn stop(&self, profile_token: &str, pan_tilt: bool, zoom: bool) -> OnvifResult<()> {
@@ -330,6 +407,23 @@ impl PtzClient {
         parse_configurations(&xml)
     }
 
+    /// `GetNodes` — the PTZ nodes on the device and the spaces they support.
+    pub async fn get_nodes(&self) -> OnvifResult<Vec<PtzNode>> {
+        let body = format!("<tptz:GetNodes xmlns:tptz=\"{ns}\"/>", ns = PTZ_WSDL_NS);
+        let action = format!("{PTZ_WSDL_NS}/GetNodes");
+        let xml = self
+            .transport
+            .call(
+                &self.xaddr,
+                &action,
+                &body,
+                self.creds.as_ref(),
+                self.timeout,
+            )
+            .await?;
+        parse_nodes(&xml)
+    }
+
     /// Dispatch a body whose response carries no payload we need (the move/stop
     /// operations). The operation `name` is used to build the SOAP action.
     async fn call_void(&self, body: &str, name: &str) -> OnvifResult<()> {
@@ -530,17 +624,46 @@ fn parent_is(stack: &[String], local: &str) -> bool {
     stack.last().map(|s| s.as_str()) == Some(local)
 }
 
+/// The `PanTiltLimits`/`ZoomLimits` block [`parse_configurations`] is inside.
+#[derive(Default)]
+struct LimitsScan {
+    limits: SpaceLimits,
+    /// `Some(true)` inside `XRange`, `Some(false)` inside `YRange`.
+    in_x: Option<bool>,
+    min: Option<f32>,
+    max: Option<f32>,
+}
+
+impl LimitsScan {
+    /// Close the current `XRange`/`YRange`, keeping it when both bounds parsed.
+    fn close_range(&mut self) {
+        if let (Some(in_x), Some(min), Some(max)) = (self.in_x.take(), self.min, self.max) {
+            let range = Some(FloatRange { min, max });
+            if in_x {
+                self.limits.x = range;
+            } else {
+                self.limits.y = range;
+            }
+        }
+        self.min = None;
+        self.max = None;
+    }
+}
+
 /// Parse a `GetConfigurationsResponse` into a list of [`PtzConfiguration`].
 ///
 /// The response carries repeated `<PTZConfiguration token="...">` elements,
-/// each with a `<Name>` and a `<NodeToken>` child. We collect one entry per
-/// `PTZConfiguration`; missing children stay `None`.
+/// each with a `<Name>`, a `<NodeToken>` and optionally `<PanTiltLimits>` /
+/// `<ZoomLimits>`, whose `<Range>` holds a `<URI>` and `<XRange>` (and for
+/// pan/tilt `<YRange>`) bounds. We collect one entry per `PTZConfiguration`;
+/// missing children stay `None`, as does a range lacking either bound.
 fn parse_configurations(xml: &str) -> OnvifResult<Vec<PtzConfiguration>> {
     let mut reader = Reader::from_str(xml);
     reader.config_mut().trim_text(true);
 
     let mut configs = Vec::new();
     let mut current: Option<PtzConfiguration> = None;
+    let mut limits: Option<LimitsScan> = None;
 
     loop {
         match reader.read_event() {
@@ -553,6 +676,30 @@ fn parse_configurations(xml: &str) -> OnvifResult<Vec<PtzConfiguration>> {
                             ..Default::default()
                         });
                     }
+                    "PanTiltLimits" | "ZoomLimits" if current.is_some() => {
+                        limits = Some(LimitsScan::default());
+                    }
+                    "XRange" | "YRange" => {
+                        if let Some(l) = limits.as_mut() {
+                            l.in_x = Some(local == "XRange");
+                        }
+                    }
+                    "URI" => {
+                        let v = non_empty(read_text(&mut reader));
+                        if let Some(l) = limits.as_mut() {
+                            l.limits.space = v;
+                        }
+                    }
+                    "Min" | "Max" => {
+                        let v = read_text(&mut reader).trim().parse::<f32>().ok();
+                        if let Some(l) = limits.as_mut().filter(|l| l.in_x.is_some()) {
+                            if local == "Min" {
+                                l.min = v;
+                            } else {
+                                l.max = v;
+                            }
+                        }
+                    }
                     "Name" => {
                         let v = non_empty(read_text(&mut reader));
                         if let Some(c) = current.as_mut() {
@@ -576,11 +723,28 @@ fn parse_configurations(xml: &str) -> OnvifResult<Vec<PtzConfiguration>> {
                     ..Default::default()
                 });
             }
-            Ok(Event::End(e)) if local_name(e.name().as_ref()) == "PTZConfiguration" => {
-                if let Some(c) = current.take() {
-                    configs.push(c);
+            Ok(Event::End(e)) => match local_name(e.name().as_ref()).as_str() {
+                "PTZConfiguration" => {
+                    if let Some(c) = current.take() {
+                        configs.push(c);
+                    }
                 }
-            }
+                "XRange" | "YRange" => {
+                    if let Some(l) = limits.as_mut() {
+                        l.close_range();
+                    }
+                }
+                local @ ("PanTiltLimits" | "ZoomLimits") => {
+                    if let (Some(c), Some(l)) = (current.as_mut(), limits.take()) {
+                        if local == "PanTiltLimits" {
+                            c.pan_tilt_limits = Some(l.limits);
+                        } else {
+                            c.zoom_limits = Some(l.limits);
+                        }
+                    }
+                }
+                _ => {}
+            },
             Ok(Event::Eof) => break,
             Err(e) => return Err(OnvifError::Parse(format!("ptz configurations xml: {e}"))),
             _ => {}
@@ -589,6 +753,53 @@ fn parse_configurations(xml: &str) -> OnvifResult<Vec<PtzConfiguration>> {
     Ok(configs)
 }
 
+/// Parse a `GetNodesResponse` into a list of [`PtzNode`].
+///
+/// Each `<PTZNode token="...">` lists its spaces under `<SupportedPTZSpaces>`;
+/// we keep the `<URI>` of every `<RelativePanTiltTranslationSpace>`.
+fn parse_nodes(xml: &str) -> OnvifResult<Vec<PtzNode>> {
+    let mut reader = Reader::from_str(xml);
+    reader.config_mut().trim_text(true);
+
+    let mut nodes = Vec::new();
+    let mut current: Option<PtzNode> = None;
+    let mut in_relative_space = false;
+
+    loop {
+        match reader.read_event() {
+            Ok(Event::Start(e)) => match local_name(e.name().as_ref()).as_str() {
+                "PTZNode" => {
+                    current = Some(PtzNode {
+                        token: attr_str(&e, "token"),
+                        ..Default::default()
+                    });
+                }
+                "RelativePanTiltTranslationSpace" => in_relative_space = true,
+                "URI" if in_relative_space => {
+                    let v = non_empty(read_text(&mut reader));
+                    if let (Some(n), Some(uri)) = (current.as_mut(), v) {
+                        n.relative_pan_tilt_spaces.push(uri);
+                    }
+                }
+                _ => {}
+            },
+            Ok(Event::End(e)) => match local_name(e.name().as_ref()).as_str() {
+                "PTZNode" => {
+                    if let Some(n) = current.take() {
+                        nodes.push(n);
+                    }
+                }
+                "RelativePanTiltTranslationSpace" => in_relative_space = false,
+                _ => {}
+            },
+            Ok(Event::Eof) => break,
+            Err(e) => return Err(OnvifError::Parse(format!("ptz nodes xml: {e}"))),
+            _ => {}
+        }
+    }
+    Ok(nodes)
+}
+
 /// Minimal XML text escaping for element content (profile tokens, etc.).
 fn xml_escape(s: &str) -> String {
     let mut out = String::with_capacity(s.len());
@@ -833,10 +1044,109 @@ mod tests {
         assert_eq!(cfgs[0].node_token, None);
     }
 
+    /// A configuration with limits in the generic space, as most cameras
+    /// report them, plus a zoom range starting above zero.
+    const CONFIGS_LIMITS: &str = r#"<s:Envelope xmlns:s="http://www.w3.org/2003/05/soap-envelope">
+  <s:Body>
+    <tptz:GetConfigurationsResponse xmlns:tptz="http://www.onvif.org/ver20/ptz/wsdl"
+                                    xmlns:tt="http://www.onvif.org/ver10/schema">
+      <tptz:PTZConfiguration token="PTZCfg_1">
+        <tt:Name>PtzConfig1</tt:Name>
+        <tt:NodeToken>PtzNode_1</tt:NodeToken>
+        <tt:PanTiltLimits>
+          <tt:Range>
+            <tt:URI>http://www.onvif.org/ver10/tptz/PanTiltSpaces/PositionGenericSpace</tt:URI>
+            <tt:XRange><tt:Min>-1</tt:Min><tt:Max>1</tt:Max></tt:XRange>
+            <tt:YRange><tt:Min>-0.5</tt:Min><tt:Max>1</tt:Max></tt:YRange>
+          </tt:Range>
+        </tt:PanTiltLimits>
+        <tt:ZoomLimits>
+          <tt:Range>
+            <tt:URI>http://www.onvif.org/ver10/tptz/ZoomSpaces/PositionGenericSpace</tt:URI>
+            <tt:XRange><tt:Min>0.1</tt:Min><tt:Max>1</tt:Max></tt:XRange>
+          </tt:Range>
+        </tt:ZoomLimits>
+      </tptz:PTZConfiguration>
+    </tptz:GetConfigurationsResponse>
+  </s:Body>
+</s:Envelope>"#;
+
+    #[test]
+    fn configurations_with_limits() {
+        let cfgs = parse_configurations(CONFIGS_LIMITS).expect("parse");
+        assert_eq!(cfgs.len(), 1);
+        assert_eq!(cfgs[0].name.as_deref(), Some("PtzConfig1"));
+        let pt = cfgs[0].pan_tilt_limits.as_ref().expect("pan/tilt limits");
+        assert!(pt
+            .space
+            .as_deref()
+            .unwrap()
+            .ends_with("PositionGenericSpace"));
+        assert_eq!(
+            pt.x,
+            Some(FloatRange {
+                min: -1.0,
+                max: 1.0
+            })
+        );
+        assert_eq!(
+            pt.y,
+            Some(FloatRange {
+                min: -0.5,
+                max: 1.0
+            })
+        );
+        let zoom = cfgs[0].zoom_limits.as_ref().expect("zoom limits");
+        assert_eq!(zoom.x, Some(FloatRange { min: 0.1, max: 1.0 }));
+        assert_eq!(zoom.y, None);
+        // The configurations without limits leave them unset.
+        let cfgs = parse_configurations(CONFIGS_NORMAL).expect("parse");
+        assert_eq!(cfgs[0].pan_tilt_limits, None);
+        assert_eq!(cfgs[0].zoom_limits, None);
+    }
+
+    /// A node listing the FOV translation space beside the generic one.
+    const NODES_FOV: &str = r#"<s:Envelope xmlns:s="http://www.w3.org/2003/05/soap-envelope">
+  <s:Body>
+    <tptz:GetNodesResponse xmlns:tptz="http://www.onvif.org/ver20/ptz/wsdl"
+                           xmlns:tt="http://www.onvif.org/ver10/schema">
+      <tptz:PTZNode token="PtzNode_1">
+        <tt:Name>PTZ</tt:Name>
+        <tt:SupportedPTZSpaces>
+          <tt:AbsolutePanTiltPositionSpace>
+            <tt:URI>http://www.onvif.org/ver10/tptz/PanTiltSpaces/PositionGenericSpace</tt:URI>
+          </tt:AbsolutePanTiltPositionSpace>
+          <tt:RelativePanTiltTranslationSpace>
+            <tt:URI>http://www.onvif.org/ver10/tptz/PanTiltSpaces/TranslationGenericSpace</tt:URI>
+            <tt:XRange><tt:Min>-1</tt:Min><tt:Max>1</tt:Max></tt:XRange>
+          </tt:RelativePanTiltTranslationSpace>
+          <tt:RelativePanTiltTranslationSpace>
+            <tt:URI>http://www.onvif.org/ver10/tptz/PanTiltSpaces/TranslationSpaceFov</tt:URI>
+          </tt:RelativePanTiltTranslationSpace>
+        </tt:SupportedPTZSpaces>
+      </tptz:PTZNode>
+    </tptz:GetNodesResponse>
+  </s:Body>
+</s:Envelope>"#;
+
+    #[test]
+    fn nodes_list_their_relative_spaces() {
+        let nodes = parse_nodes(NODES_FOV).expect("parse");
+        assert_eq!(nodes.len(), 1);
+        assert_eq!(nodes[0].token.as_deref(), Some("PtzNode_1"));
+        assert_eq!(nodes[0].relative_pan_tilt_spaces.len(), 2);
+        assert!(nodes[0].supports_relative_pan_tilt(TRANSLATION_SPACE_FOV));
+        // The absolute space is not a relative one.
+        assert!(!nodes[0].supports_relative_pan_tilt(
+            "http://www.onvif.org/ver10/tptz/PanTiltSpaces/PositionGenericSpace"
+        ));
+    }
+
     #[test]
     fn garbage_input_does_not_panic() {
         let _ = parse_status("<not><closed>");
         let _ = parse_configurations("not xml at all");
+        let _ = parse_nodes("<PTZNode><URI>");
         let _ = parse_vector("<<<>>>");
     }
 }
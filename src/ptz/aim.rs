@@ -0,0 +1,319 @@
+//! Aiming a camera at a spot in its own picture
+//!
+//! A click on the live view is a [`FramePoint`] and a dragged box a
+//! [`FrameArea`], both normalized to the frame with the origin top-left.
+//! Cameras that can center on a pixel themselves (Axis `center`/`areazoom`)
+//! are handed the target as is. For the rest, the point's offset from the
+//! middle of the frame becomes an angle through the lens's field of view at
+//! the current zoom, and the angle a `MoveRelative` in the driver's
+//! [`PositionUnits`].
+//!
+//! Fields of view come from a small table of common camera models, matched
+//! against the monitor's model name; anything unknown gets a typical 4 mm
+//! lens with a 4× zoom, which at worst lands the target off-center rather
+//! than off-screen.
+
+use super::traits::RelativePosition;
+
+/// A point in the video frame: `0,0` is the top-left corner and `1,1` the
+/// bottom-right
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct FramePoint {
+    pub x: f64,
+    pub y: f64,
+}
+
+/// A rectangle in the video frame, its top-left corner and size in the same
+/// normalized coordinates as [`FramePoint`]
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct FrameArea {
+    pub x: f64,
+    pub y: f64,
+    pub width: f64,
+    pub height: f64,
+}
+
+impl FrameArea {
+    pub fn center(&self) -> FramePoint {
+        FramePoint {
+            x: self.x + self.width / 2.0,
+            y: self.y + self.height / 2.0,
+        }
+    }
+
+    /// How much closer the area must be brought to fill the frame
+    pub fn zoom_factor(&self) -> f64 {
+        1.0 / self.width.max(self.height).max(f64::EPSILON)
+    }
+}
+
+/// Where in the picture to aim
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum AimTarget {
+    /// Center the camera on a point, keeping the zoom
+    Point(FramePoint),
+    /// Center on an area and zoom in until it fills the frame
+    Area(FrameArea),
+}
+
+impl AimTarget {
+    /// The point that should end up in the middle of the frame
+    pub fn center(&self) -> FramePoint {
+        match self {
+            Self::Point(point) => *point,
+            Self::Area(area) => area.center(),
+        }
+    }
+}
+
+/// What a zoom position means in terms of magnification
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum ZoomUnits {
+    /// The position is the magnification itself (`1.0` = 1×)
+    Magnification,
+    /// The position runs linearly from `wide` (1×) to `tele` (the lens's full
+    /// optical zoom)
+    Linear { wide: f64, tele: f64 },
+}
+
+/// How a driver's `MoveRelative`/`get_position` units relate to angles
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct PositionUnits {
+    /// Pan units per degree to the right
+    pub pan_per_degree: f64,
+    /// Tilt units per degree up; negative when the camera counts downwards
+    pub tilt_per_degree: f64,
+    pub zoom: ZoomUnits,
+}
+
+impl ZoomUnits {
+    /// The magnification at a zoom position
+    pub fn magnification(&self, position: f64, lens: &Lens) -> f64 {
+        let m = match *self {
+            Self::Magnification => position,
+            Self::Linear { wide, tele } if tele != wide => {
+                1.0 + (position - wide) / (tele - wide) * (lens.optical_zoom - 1.0)
+            }
+            Self::Linear { .. } => 1.0,
+        };
+        m.clamp(1.0, lens.optical_zoom)
+    }
+
+    /// The zoom position for a magnification
+    pub fn position(&self, magnification: f64, lens: &Lens) -> f64 {
+        let m = magnification.clamp(1.0, lens.optical_zoom);
+        match *self {
+            Self::Magnification => m,
+            Self::Linear { wide, tele } if lens.optical_zoom > 1.0 => {
+                wide + (m - 1.0) / (lens.optical_zoom - 1.0) * (tele - wide)
+            }
+            Self::Linear { wide, .. } => wide,
+        }
+    }
+}
+
+/// A camera's optics
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct Lens {
+    /// Horizontal field of view fully zoomed out, in degrees
+    pub wide_hfov: f64,
+    /// Optical zoom range, `1.0` for a fixed lens
+    pub optical_zoom: f64,
+}
+
+/// Used for models the table does not know.
+pub const DEFAULT_LENS: Lens = Lens {
+    wide_hfov: 60.0,
+    optical_zoom: 4.0,
+};
+
+/// Wide-end field of view and optical zoom of common PTZ models, matched
+/// case-insensitively as a substring of the model name. More specific
+/// names come first.
+const LENSES: &[(&str, Lens)] = &[
+    // Hikvision
+    ("DS-2DE4A425", lens(56.8, 25.0)),
+    ("DS-2DE4A225", lens(57.6, 25.0)),
+    ("DS-2DE2A404", lens(83.0, 4.0)),
+    ("DS-2DE3A404", lens(83.0, 4.0)),
+    ("DS-2DE5425", lens(57.6, 25.0)),
+    ("DS-2DE5432", lens(58.0, 32.0)),
+    ("DS-2DE7A232", lens(58.5, 32.0)),
+    ("DS-2DF8", lens(58.3, 36.0)),
+    // Dahua / Amcrest
+    ("SD49225", lens(59.2, 25.0)),
+    ("SD59225", lens(59.2, 25.0)),
+    ("SD49425", lens(56.4, 25.0)),
+    ("SD6AL", lens(58.4, 45.0)),
+    ("SD1A404", lens(86.0, 4.0)),
+    ("SD22404", lens(80.0, 4.0)),
+    ("IP2M-841", lens(90.0, 1.0)),
+    ("IP4M-1051", lens(88.0, 4.0)),
+    ("IP8M-2496", lens(85.0, 1.0)),
+    // Axis
+    ("Q6075", lens(61.8, 20.0)),
+    ("Q6135", lens(63.7, 32.0)),
+    ("Q6155", lens(65.0, 30.0)),
+    ("M5525", lens(64.8, 10.0)),
+    ("M5075", lens(63.7, 5.0)),
+    ("P5655", lens(63.7, 32.0)),
+    ("V5925", lens(64.2, 30.0)),
+    // Sony
+    ("SRG-300", lens(71.0, 30.0)),
+    ("EVI-D70", lens(48.0, 18.0)),
+    ("EVI-H100", lens(70.0, 20.0)),
+];
+
+const fn lens(wide_hfov: f64, optical_zoom: f64) -> Lens {
+    Lens {
+        wide_hfov,
+        optical_zoom,
+    }
+}
+
+/// The lens of the first known model whose name occurs in `model`
+pub fn lens_for_model(model: &str) -> Option<Lens> {
+    let model = model.to_ascii_uppercase();
+    LENSES
+        .iter()
+        .find(|(name, _)| model.contains(&name.to_ascii_uppercase()))
+        .map(|&(_, lens)| lens)
+}
+
+impl Lens {
+    /// Horizontal field of view at a magnification, in degrees
+    pub fn hfov(&self, magnification: f64) -> f64 {
+        let half = (self.wide_hfov / 2.0).to_radians().tan() / magnification.max(1.0);
+        2.0 * half.atan().to_degrees()
+    }
+}
+
+/// Degrees to pan right and tilt up to bring `point` to the middle of a frame
+/// `hfov` degrees wide with the given width/height `aspect`
+pub fn offset_degrees(point: FramePoint, hfov: f64, aspect: f64) -> (f64, f64) {
+    let half_width = (hfov / 2.0).to_radians().tan();
+    let half_height = half_width / aspect.max(f64::EPSILON);
+    let pan = ((point.x - 0.5) * 2.0 * half_width).atan().to_degrees();
+    let tilt = ((0.5 - point.y) * 2.0 * half_height).atan().to_degrees();
+    (pan, tilt)
+}
+
+/// The move, in a driver's units, that aims at `target` from a camera whose
+/// zoom is at `zoom` (1× when unknown)
+pub fn relative_move(
+    target: &AimTarget,
+    zoom: Option<f64>,
+    units: &PositionUnits,
+    lens: &Lens,
+    aspect: f64,
+) -> RelativePosition {
+    let now = zoom.map_or(1.0, |z| units.zoom.magnification(z, lens));
+    let (pan, tilt) = offset_degrees(target.center(), lens.hfov(now), aspect);
+    let zoom_delta = match target {
+        AimTarget::Point(_) => None,
+        AimTarget::Area(area) => {
+            let to = now * area.zoom_factor();
+            Some(units.zoom.position(to, lens) - units.zoom.position(now, lens))
+        }
+    };
+    RelativePosition {
+        pan_delta: Some(pan * units.pan_per_degree),
+        tilt_delta: Some(tilt * units.tilt_per_degree),
+        zoom_delta,
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const DEGREES: PositionUnits = PositionUnits {
+        pan_per_degree: 1.0,
+        tilt_per_degree: -1.0,
+        zoom: ZoomUnits::Magnification,
+    };
+
+    fn close(a: f64, b: f64) -> bool {
+        (a - b).abs() < 0.01
+    }
+
+    #[test]
+    fn field_of_view_narrows_with_zoom() {
+        let lens = lens(90.0, 10.0);
+        assert!(close(lens.hfov(1.0), 90.0));
+        // tan(45°) / 2 = 0.5, and 2·atan(0.5) ≈ 53.13°.
+        assert!(close(lens.hfov(2.0), 53.13));
+        assert!(lens.hfov(10.0) < lens.hfov(2.0));
+    }
+
+    #[test]
+    fn the_frame_edge_is_half_the_field_of_view_away() {
+        let (pan, tilt) = offset_degrees(FramePoint { x: 1.0, y: 0.5 }, 90.0, 16.0 / 9.0);
+        assert!(close(pan, 45.0) && close(tilt, 0.0));
+
+        let (pan, tilt) = offset_degrees(FramePoint { x: 0.5, y: 0.0 }, 90.0, 1.0);
+        assert!(close(pan, 0.0) && close(tilt, 45.0));
+
+        let (pan, tilt) = offset_degrees(FramePoint { x: 0.5, y: 0.5 }, 60.0, 1.5);
+        assert!(close(pan, 0.0) && close(tilt, 0.0));
+    }
+
+    #[test]
+    fn a_click_pans_and_tilts_in_driver_units() {
+        let lens = lens(90.0, 10.0);
+        let point = AimTarget::Point(FramePoint { x: 0.0, y: 0.0 });
+        let delta = relative_move(&point, Some(1.0), &DEGREES, &lens, 1.0);
+        assert!(close(delta.pan_delta.unwrap(), -45.0));
+        // Up is negative for a camera that counts its tilt downwards.
+        assert!(close(delta.tilt_delta.unwrap(), -45.0));
+        assert_eq!(delta.zoom_delta, None);
+
+        // Zoomed in, the same click turns the camera less.
+        let zoomed = relative_move(&point, Some(4.0), &DEGREES, &lens, 1.0);
+        assert!(zoomed.pan_delta.unwrap().abs() < 45.0);
+    }
+
+    #[test]
+    fn an_area_zooms_until_it_fills_the_frame() {
+        let lens = lens(60.0, 20.0);
+        let area = AimTarget::Area(FrameArea {
+            x: 0.25,
+            y: 0.25,
+            width: 0.5,
+            height: 0.25,
+        });
+        let delta = relative_move(&area, Some(2.0), &DEGREES, &lens, 16.0 / 9.0);
+        assert!(close(delta.pan_delta.unwrap(), 0.0));
+        assert!(close(delta.zoom_delta.unwrap(), 2.0));
+
+        // The zoom stops at the lens's limit.
+        let delta = relative_move(&area, Some(10.0), &DEGREES, &lens, 16.0 / 9.0);
+        assert!(close(delta.zoom_delta.unwrap(), 10.0));
+    }
+
+    #[test]
+    fn linear_zoom_maps_onto_the_optical_range() {
+        let lens = lens(60.0, 21.0);
+        let units = ZoomUnits::Linear {
+            wide: 1.0,
+            tele: 9999.0,
+        };
+        assert!(close(units.magnification(1.0, &lens), 1.0));
+        assert!(close(units.magnification(5000.0, &lens), 11.0));
+        assert!(close(units.position(21.0, &lens), 9999.0));
+        assert!(close(
+            units.position(units.magnification(2500.0, &lens), &lens),
+            2500.0
+        ));
+    }
+
+    #[test]
+    fn known_models_are_matched_by_substring() {
+        assert_eq!(
+            lens_for_model("Hikvision DS-2DE4A425IW-DE"),
+            Some(lens(56.8, 25.0))
+        );
+        assert_eq!(lens_for_model("axis q6135-le"), Some(lens(63.7, 32.0)));
+        assert_eq!(lens_for_model("Generic PTZ"), None);
+    }
+}
@@ -102,7 +102,7 @@ impl PerlControlProxy {
                     options.insert("speed".to_string(), json!(self.scale_zoom_speed(speed)));
                 }
             }
-            PtzCommand::GotoPreset { preset_id } => {
+            PtzCommand::GotoPreset { preset_id, .. } => {
                 options.insert("preset".to_string(), json!(preset_id));
             }
             PtzCommand::SetPreset { preset_id, name } => {
@@ -249,7 +249,7 @@ impl PerlControlProxy {
             PtzCommand::ZoomIn(params) | PtzCommand::ZoomOut(params) => {
                 self.add_zoom_params(&mut args, params);
             }
-            PtzCommand::GotoPreset { preset_id } => {
+            PtzCommand::GotoPreset { preset_id, .. } => {
                 args.push("--preset".to_string());
                 args.push(preset_id.to_string());
             }
@@ -517,6 +517,7 @@ mod tests {
         PtzConnectionConfig {
             monitor_id: 1,
             address: "192.168.1.100".to_string(),
+            device: None,
             username: Some("admin".to_string()),
             password: Some("password".to_string()),
             protocol: "onvif".to_string(),
@@ -556,7 +557,10 @@ mod tests {
     fn test_build_args_goto_preset() {
         let proxy = PerlControlProxy::new(test_config(), test_capabilities(), None, None);
 
-        let args = proxy.build_args(&PtzCommand::GotoPreset { preset_id: 5 });
+        let args = proxy.build_args(&PtzCommand::GotoPreset {
+            preset_id: 5,
+            speed: None,
+        });
 
         assert!(args.contains(&"--command".to_string()));
         assert!(args.contains(&"presetGoto".to_string()));
@@ -593,7 +597,10 @@ mod tests {
     fn test_build_json_options_goto_preset() {
         let proxy = PerlControlProxy::new(test_config(), test_capabilities(), None, None);
 
-        let json = proxy.build_json_options(&PtzCommand::GotoPreset { preset_id: 5 });
+        let json = proxy.build_json_options(&PtzCommand::GotoPreset {
+            preset_id: 5,
+            speed: None,
+        });
 
         assert_eq!(json["command"], "presetGoto");
         assert_eq!(json["preset"], 5);
@@ -0,0 +1,221 @@
+//! Per-monitor PTZ control leases
+//!
+//! The first user to drive a camera holds it for the lease duration, and
+//! every command they send renews the lease. While it is live, other users'
+//! commands are refused unless they outrank the holder, in which case they
+//! take the camera over. An expired lease is up for grabs.
+
+use std::collections::HashMap;
+use std::time::{Duration, Instant};
+
+/// Who holds, or wants, a camera
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct ControlHolder {
+    pub user_id: u32,
+    pub username: String,
+    /// Higher takes control from lower; equals wait their turn
+    pub priority: u8,
+}
+
+/// A live claim on one monitor's camera
+#[derive(Debug, Clone)]
+pub struct ControlLease {
+    pub holder: ControlHolder,
+    pub acquired_at: Instant,
+    pub expires_at: Instant,
+}
+
+impl ControlLease {
+    /// Time left before the lease lapses
+    pub fn remaining(&self, now: Instant) -> Duration {
+        self.expires_at.saturating_duration_since(now)
+    }
+
+    fn is_live(&self, now: Instant) -> bool {
+        now < self.expires_at
+    }
+}
+
+/// How a lease request was granted
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum LeaseGrant {
+    /// Nobody held the camera; the requester does now
+    Acquired,
+    /// The requester already held it; the lease was extended
+    Renewed,
+    /// The requester outranked the holder, returned here, and took over
+    TakenOver(ControlHolder),
+}
+
+/// A request refused because someone else holds the camera
+#[derive(Debug, Clone)]
+pub struct LeaseConflict {
+    pub holder: ControlHolder,
+    pub remaining: Duration,
+}
+
+/// Grant `holder` the lease on `monitor_id` until `now + duration`, unless a
+/// live lease belongs to another user of equal or higher priority
+pub(super) fn acquire(
+    leases: &mut HashMap<u32, ControlLease>,
+    monitor_id: u32,
+    holder: ControlHolder,
+    duration: Duration,
+    now: Instant,
+) -> Result<LeaseGrant, LeaseConflict> {
+    let grant = match leases.get(&monitor_id).filter(|l| l.is_live(now)) {
+        None => LeaseGrant::Acquired,
+        Some(current) if current.holder.user_id == holder.user_id => LeaseGrant::Renewed,
+        Some(current) if holder.priority > current.holder.priority => {
+            LeaseGrant::TakenOver(current.holder.clone())
+        }
+        Some(current) => {
+            return Err(LeaseConflict {
+                holder: current.holder.clone(),
+                remaining: current.remaining(now),
+            })
+        }
+    };
+    let acquired_at = match (&grant, leases.get(&monitor_id)) {
+        (LeaseGrant::Renewed, Some(current)) => current.acquired_at,
+        _ => now,
+    };
+    leases.insert(
+        monitor_id,
+        ControlLease {
+            holder,
+            acquired_at,
+            expires_at: now + duration,
+        },
+    );
+    Ok(grant)
+}
+
+/// Give up the lease on `monitor_id` on behalf of `requester`: the holder
+/// may always let go, and a higher-priority user may clear someone else's.
+/// Returns the holder whose lease was dropped, `None` if nothing was held.
+pub(super) fn release(
+    leases: &mut HashMap<u32, ControlLease>,
+    monitor_id: u32,
+    requester: &ControlHolder,
+    now: Instant,
+) -> Result<Option<ControlHolder>, LeaseConflict> {
+    let Some(current) = leases.get(&monitor_id).filter(|l| l.is_live(now)) else {
+        leases.remove(&monitor_id);
+        return Ok(None);
+    };
+    if current.holder.user_id != requester.user_id && requester.priority <= current.holder.priority
+    {
+        return Err(LeaseConflict {
+            holder: current.holder.clone(),
+            remaining: current.remaining(now),
+        });
+    }
+    Ok(leases.remove(&monitor_id).map(|l| l.holder))
+}
+
+/// Drop every lease that has lapsed, returning who held them
+pub(super) fn expire(
+    leases: &mut HashMap<u32, ControlLease>,
+    now: Instant,
+) -> Vec<(u32, ControlHolder)> {
+    let lapsed: Vec<u32> = leases
+        .iter()
+        .filter(|(_, l)| !l.is_live(now))
+        .map(|(&monitor_id, _)| monitor_id)
+        .collect();
+    lapsed
+        .into_iter()
+        .filter_map(|monitor_id| leases.remove(&monitor_id).map(|l| (monitor_id, l.holder)))
+        .collect()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const LEASE: Duration = Duration::from_secs(30);
+
+    fn user(user_id: u32, priority: u8) -> ControlHolder {
+        ControlHolder {
+            user_id,
+            username: format!("user{user_id}"),
+            priority,
+        }
+    }
+
+    #[test]
+    fn first_mover_holds_until_the_lease_lapses() {
+        let mut leases = HashMap::new();
+        let t0 = Instant::now();
+
+        assert_eq!(
+            acquire(&mut leases, 1, user(1, 0), LEASE, t0).unwrap(),
+            LeaseGrant::Acquired
+        );
+        let conflict = acquire(&mut leases, 1, user(2, 0), LEASE, t0 + LEASE / 2).unwrap_err();
+        assert_eq!(conflict.holder.user_id, 1);
+        assert_eq!(conflict.remaining, LEASE / 2);
+
+        // Another monitor is free.
+        assert!(acquire(&mut leases, 2, user(2, 0), LEASE, t0).is_ok());
+
+        assert_eq!(
+            acquire(&mut leases, 1, user(2, 0), LEASE, t0 + LEASE).unwrap(),
+            LeaseGrant::Acquired
+        );
+    }
+
+    #[test]
+    fn commands_renew_and_higher_priority_takes_over() {
+        let mut leases = HashMap::new();
+        let t0 = Instant::now();
+        acquire(&mut leases, 1, user(1, 0), LEASE, t0).unwrap();
+
+        let t = t0 + Duration::from_secs(20);
+        assert_eq!(
+            acquire(&mut leases, 1, user(1, 0), LEASE, t).unwrap(),
+            LeaseGrant::Renewed
+        );
+        assert_eq!(leases[&1].acquired_at, t0);
+        assert_eq!(leases[&1].expires_at, t + LEASE);
+
+        assert_eq!(
+            acquire(&mut leases, 1, user(9, 1), LEASE, t).unwrap(),
+            LeaseGrant::TakenOver(user(1, 0))
+        );
+        assert!(acquire(&mut leases, 1, user(1, 0), LEASE, t).is_err());
+    }
+
+    #[test]
+    fn release_is_for_the_holder_or_a_higher_priority() {
+        let mut leases = HashMap::new();
+        let t0 = Instant::now();
+        acquire(&mut leases, 1, user(1, 0), LEASE, t0).unwrap();
+
+        assert!(release(&mut leases, 1, &user(2, 0), t0).is_err());
+        assert_eq!(
+            release(&mut leases, 1, &user(1, 0), t0).unwrap(),
+            Some(user(1, 0))
+        );
+        assert_eq!(release(&mut leases, 1, &user(1, 0), t0).unwrap(), None);
+
+        acquire(&mut leases, 1, user(1, 0), LEASE, t0).unwrap();
+        assert_eq!(
+            release(&mut leases, 1, &user(9, 1), t0).unwrap(),
+            Some(user(1, 0))
+        );
+    }
+
+    #[test]
+    fn expire_drops_only_lapsed_leases() {
+        let mut leases = HashMap::new();
+        let t0 = Instant::now();
+        acquire(&mut leases, 1, user(1, 0), LEASE, t0).unwrap();
+        acquire(&mut leases, 2, user(2, 0), LEASE, t0 + LEASE / 2).unwrap();
+
+        assert!(expire(&mut leases, t0 + LEASE / 2).is_empty());
+        assert_eq!(expire(&mut leases, t0 + LEASE), [(1, user(1, 0))]);
+        assert!(leases.contains_key(&2));
+    }
+}
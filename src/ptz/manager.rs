@@ -2,13 +2,18 @@
 
 use std::collections::HashMap;
 use std::sync::Arc;
+use std::time::Instant;
 use tokio::sync::RwLock;
 use tracing::{debug, info, instrument};
 
+use super::aim::{self, AimTarget, Lens};
 use super::capabilities::PtzCapabilities;
 use super::error::{PtzError, PtzResult};
+use super::lease::{self, ControlHolder, ControlLease, LeaseConflict, LeaseGrant};
 use super::registry::{ProtocolInfo, PtzRegistry};
-use super::traits::{PtzCommand, PtzCommandResult, PtzConnectionConfig, PtzControl};
+use super::traits::{
+    AbsolutePosition, PtzCommand, PtzCommandResult, PtzConnectionConfig, PtzControl,
+};
 
 use crate::entity::controls::Model as ControlModel;
 use crate::entity::monitors::Model as MonitorModel;
@@ -28,6 +33,11 @@ struct CachedControl {
 pub struct PtzManager {
     registry: Arc<PtzRegistry>,
     cache: RwLock<HashMap<u32, CachedControl>>,
+    /// When each monitor last took a command from a user, so background
+    /// drivers (tours) can yield to them
+    manual: std::sync::Mutex<HashMap<u32, Instant>>,
+    /// Which user holds each monitor's camera
+    leases: std::sync::Mutex<HashMap<u32, ControlLease>>,
 }
 
 impl PtzManager {
@@ -36,16 +46,31 @@ impl PtzManager {
         Self {
             registry: Arc::new(registry),
             cache: RwLock::new(HashMap::new()),
+            manual: std::sync::Mutex::new(HashMap::new()),
+            leases: std::sync::Mutex::new(HashMap::new()),
         }
     }
 
-    /// Create a manager with the default registry: the native ONVIF factory
+    /// Create a manager with the default registry: the native ONVIF,
+    /// Hikvision, Dahua/Amcrest, Axis, Pelco-D, Pelco-P and VISCA factories
     /// registered, with the Perl bridge as fallback for all other protocols.
     pub fn with_defaults() -> Self {
-        #[allow(unused_mut)]
+        use super::protocols::dahua::{self, DahuaControlFactory};
+        use super::protocols::isapi::IsapiControlFactory;
+        use super::protocols::pelco::{PelcoControlFactory, PelcoVariant};
+        use super::protocols::vapix::VapixControlFactory;
+        use super::protocols::visca::ViscaControlFactory;
+
         let mut registry = PtzRegistry::default();
         #[cfg(feature = "onvif-ptz")]
         registry.register_native(Arc::new(super::protocols::onvif::OnvifControlFactory::new()));
+        registry.register_native(Arc::new(IsapiControlFactory::new()));
+        registry.register_native(Arc::new(DahuaControlFactory::new(dahua::PROTOCOL_DAHUA)));
+        registry.register_native(Arc::new(DahuaControlFactory::new(dahua::PROTOCOL_AMCREST)));
+        registry.register_native(Arc::new(VapixControlFactory::new()));
+        registry.register_native(Arc::new(PelcoControlFactory::new(PelcoVariant::D)));
+        registry.register_native(Arc::new(PelcoControlFactory::new(PelcoVariant::P)));
+        registry.register_native(Arc::new(ViscaControlFactory::new()));
         Self::new(registry)
     }
 
@@ -222,6 +247,10 @@ impl PtzManager {
         Ok(PtzConnectionConfig {
             monitor_id: monitor.id,
             address: parsed_address,
+            device: monitor
+                .control_device
+                .clone()
+                .filter(|device| !device.trim().is_empty()),
             username,
             password,
             protocol: control
@@ -325,6 +354,133 @@ impl PtzManager {
         Ok(cached.control.capabilities().clone())
     }
 
+    /// Read back the current position of a cached monitor's camera, `None`
+    /// when its driver cannot report one
+    pub async fn get_position(&self, monitor_id: u32) -> PtzResult<Option<AbsolutePosition>> {
+        let cache = self.cache.read().await;
+        let cached = cache
+            .get(&monitor_id)
+            .ok_or(PtzError::MonitorNotFound(monitor_id))?;
+        cached.control.get_position().await
+    }
+
+    /// Aim a monitor's camera at a spot in its picture using the monitor and
+    /// control models directly
+    #[instrument(skip(self, monitor, control, lens))]
+    pub async fn aim_with_models(
+        &self,
+        monitor: &MonitorModel,
+        control: &ControlModel,
+        target: AimTarget,
+        lens: &Lens,
+    ) -> PtzResult<PtzCommandResult> {
+        {
+            let cache = self.cache.read().await;
+            if !cache.contains_key(&monitor.id) {
+                drop(cache);
+                self.create_and_cache(monitor, control).await?;
+            }
+        }
+        let aspect = f64::from(monitor.width) / f64::from(monitor.height.max(1));
+        self.aim(monitor.id, target, lens, aspect).await
+    }
+
+    /// Aim a cached monitor's camera at a spot in its picture: natively when
+    /// the driver can, otherwise by a `MoveRelative` worked out from the
+    /// lens's field of view at the current zoom. `aspect` is the picture's
+    /// width over its height.
+    pub async fn aim(
+        &self,
+        monitor_id: u32,
+        target: AimTarget,
+        lens: &Lens,
+        aspect: f64,
+    ) -> PtzResult<PtzCommandResult> {
+        let cache = self.cache.read().await;
+        let cached = cache
+            .get(&monitor_id)
+            .ok_or(PtzError::MonitorNotFound(monitor_id))?;
+        let control = &cached.control;
+        if let Some(result) = control.aim(&target).await? {
+            return Ok(result);
+        }
+        let units = control.position_units().ok_or_else(|| {
+            PtzError::CommandNotSupported(format!(
+                "{} cannot aim at a point in the picture",
+                control.protocol_name()
+            ))
+        })?;
+        let zoom = control.get_position().await?.and_then(|p| p.zoom);
+        let delta = aim::relative_move(&target, zoom, &units, lens, aspect);
+        debug!(monitor_id, ?target, ?delta, "aiming by relative move");
+        control.execute(PtzCommand::MoveRelative(delta)).await
+    }
+
+    /// Record that a user just drove a monitor's camera
+    pub fn note_manual_control(&self, monitor_id: u32) {
+        self.manual
+            .lock()
+            .unwrap_or_else(|e| e.into_inner())
+            .insert(monitor_id, Instant::now());
+    }
+
+    /// When a user last drove a monitor's camera, if ever
+    pub fn last_manual_control(&self, monitor_id: u32) -> Option<Instant> {
+        self.manual
+            .lock()
+            .unwrap_or_else(|e| e.into_inner())
+            .get(&monitor_id)
+            .copied()
+    }
+
+    /// Every monitor a user has driven, with when they last did
+    pub fn manual_controls(&self) -> Vec<(u32, Instant)> {
+        self.manual
+            .lock()
+            .unwrap_or_else(|e| e.into_inner())
+            .iter()
+            .map(|(&monitor_id, &at)| (monitor_id, at))
+            .collect()
+    }
+
+    /// Take or renew the control lease on a monitor's camera for `duration`
+    pub fn acquire_control(
+        &self,
+        monitor_id: u32,
+        holder: ControlHolder,
+        duration: std::time::Duration,
+    ) -> Result<LeaseGrant, LeaseConflict> {
+        let mut leases = self.leases.lock().unwrap_or_else(|e| e.into_inner());
+        lease::acquire(&mut leases, monitor_id, holder, duration, Instant::now())
+    }
+
+    /// Give up a monitor's control lease, returning who held it
+    pub fn release_control(
+        &self,
+        monitor_id: u32,
+        requester: &ControlHolder,
+    ) -> Result<Option<ControlHolder>, LeaseConflict> {
+        let mut leases = self.leases.lock().unwrap_or_else(|e| e.into_inner());
+        lease::release(&mut leases, monitor_id, requester, Instant::now())
+    }
+
+    /// The live control lease on a monitor's camera, if any
+    pub fn control_lease(&self, monitor_id: u32) -> Option<ControlLease> {
+        let now = Instant::now();
+        self.leases
+            .lock()
+            .unwrap_or_else(|e| e.into_inner())
+            .get(&monitor_id)
+            .filter(|l| l.expires_at > now)
+            .cloned()
+    }
+
+    /// Drop lapsed control leases, returning the monitors and who held them
+    pub fn expire_control_leases(&self) -> Vec<(u32, ControlHolder)> {
+        let mut leases = self.leases.lock().unwrap_or_else(|e| e.into_inner());
+        lease::expire(&mut leases, Instant::now())
+    }
+
     /// List available protocols
     pub fn list_protocols(&self) -> Vec<ProtocolInfo> {
         self.registry.list_protocols()
@@ -700,6 +856,27 @@ mod tests {
         }
     }
 
+    #[test]
+    fn test_http_and_serial_protocols_are_native() {
+        let manager = PtzManager::with_defaults();
+
+        for protocol in [
+            "HikVision",
+            "Dahua",
+            "Amcrest_HTTP",
+            "AxisV2",
+            "PelcoD",
+            "PelcoP",
+            "Visca",
+        ] {
+            assert!(
+                manager.is_native_protocol(protocol),
+                "{protocol} should be native"
+            );
+        }
+        assert!(!manager.is_native_protocol("Ncs370"));
+    }
+
     #[test]
     fn test_parse_control_address_simple() {
         let manager = PtzManager::with_defaults();
@@ -738,4 +915,53 @@ mod tests {
         assert_eq!(user, Some("admin".to_string()));
         assert_eq!(pass, Some("secret".to_string()));
     }
+
+    #[test]
+    fn test_manual_control_is_tracked_per_monitor() {
+        let manager = PtzManager::with_defaults();
+        assert!(manager.last_manual_control(1).is_none());
+
+        let before = Instant::now();
+        manager.note_manual_control(1);
+
+        assert!(manager.last_manual_control(1).unwrap() >= before);
+        assert!(manager.last_manual_control(2).is_none());
+        assert_eq!(manager.manual_controls().len(), 1);
+    }
+
+    #[test]
+    fn test_control_lease_blocks_other_users() {
+        let manager = PtzManager::with_defaults();
+        let holder = |user_id| ControlHolder {
+            user_id,
+            username: format!("user{user_id}"),
+            priority: 0,
+        };
+        let lease = std::time::Duration::from_secs(30);
+
+        assert_eq!(
+            manager.acquire_control(1, holder(1), lease).unwrap(),
+            LeaseGrant::Acquired
+        );
+        assert!(manager.acquire_control(1, holder(2), lease).is_err());
+        assert_eq!(manager.control_lease(1).unwrap().holder.user_id, 1);
+        assert!(manager.control_lease(2).is_none());
+
+        assert_eq!(
+            manager.release_control(1, &holder(1)).unwrap(),
+            Some(holder(1))
+        );
+        assert!(manager.control_lease(1).is_none());
+        assert!(manager.expire_control_leases().is_empty());
+    }
+
+    #[tokio::test]
+    async fn test_aim_needs_a_cached_control() {
+        let manager = PtzManager::with_defaults();
+        let target = AimTarget::Point(aim::FramePoint { x: 0.5, y: 0.5 });
+        assert!(matches!(
+            manager.aim(1, target, &aim::DEFAULT_LENS, 16.0 / 9.0).await,
+            Err(PtzError::MonitorNotFound(1))
+        ));
+    }
 }
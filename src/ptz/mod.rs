@@ -4,20 +4,28 @@
 //! various protocols. It supports both native Rust implementations and a Perl
 //! bridge for legacy protocol support.
 
+pub mod aim;
 pub mod bridge;
 pub mod capabilities;
 pub mod error;
+pub mod lease;
 pub mod manager;
-#[cfg(feature = "onvif-ptz")]
 pub mod protocols;
 pub mod registry;
 pub mod traits;
 
 // Re-export commonly used types
+pub use aim::{AimTarget, FrameArea, FramePoint, Lens};
 pub use capabilities::PtzCapabilities;
 pub use error::PtzError;
+pub use lease::{ControlHolder, ControlLease, LeaseConflict, LeaseGrant};
 pub use manager::PtzManager;
+pub use protocols::dahua::DahuaControlFactory;
+pub use protocols::isapi::IsapiControlFactory;
 #[cfg(feature = "onvif-ptz")]
 pub use protocols::onvif::{OnvifControl, OnvifControlFactory};
+pub use protocols::pelco::{PelcoControlFactory, PelcoVariant};
+pub use protocols::vapix::VapixControlFactory;
+pub use protocols::visca::ViscaControlFactory;
 pub use registry::PtzRegistry;
 pub use traits::{PtzCommand, PtzControl};
@@ -0,0 +1,448 @@
+//! Native Dahua / Amcrest HTTP API PTZ driver (`Controls.Protocol` `Dahua`
+//! or `Amcrest_HTTP`; Amcrest cameras run Dahua firmware).
+//!
+//! Every command is a `GET` of `/cgi-bin/ptz.cgi?action=start|stop&code=…`
+//! with up to three integer arguments, answered with `OK`:
+//!
+//! | `PtzCommand` | `code` (arguments) |
+//! |---|---|
+//! | `MoveUp` … `MoveRight` | `Up` … `Right` (speed `1..=8` in `arg2`) |
+//! | diagonals | `LeftUp` … `RightDown` (vertical, horizontal speed) |
+//! | `Zoom*`, `Focus*`, `Iris*` | `ZoomTele`/`ZoomWide`, `FocusNear`/`FocusFar`, `IrisLarge`/`IrisSmall` |
+//! | `*Stop` | `action=stop` with the axis's code |
+//! | presets | `GotoPreset` / `SetPreset` / `ClearPreset` (preset in `arg2`) |
+//! | `MoveAbsolute` | `PositionABS` (pan and tilt in degrees, zoom multiple) |
+//! | `MoveRelative` | `getStatus`, then `PositionABS` to the offset position |
+//! | `Reboot` | `/cgi-bin/magicBox.cgi?action=reboot` |
+//!
+//! Dahua has no preset names: a `SetPreset` name is not stored on the camera.
+
+use async_trait::async_trait;
+use tracing::{debug, instrument};
+
+use super::http::{self, axis_range, speed_range, CameraHttp};
+use crate::ptz::aim::{PositionUnits, ZoomUnits};
+use crate::ptz::capabilities::PtzCapabilities;
+use crate::ptz::error::{PtzError, PtzResult};
+use crate::ptz::traits::{
+    AbsolutePosition, PtzCommand, PtzCommandResult, PtzConnectionConfig, PtzControl,
+    PtzControlFactory, RelativePosition,
+};
+
+pub const PROTOCOL_DAHUA: &str = "Dahua";
+pub const PROTOCOL_AMCREST: &str = "Amcrest_HTTP";
+/// `ptz.cgi` speeds are `1..=8`.
+const MAX_SPEED: u32 = 8;
+const MAX_PRESETS: u8 = 255;
+
+/// Native Dahua/Amcrest controller for one channel.
+pub struct DahuaControl {
+    protocol: &'static str,
+    capabilities: PtzCapabilities,
+    /// The camera, or why it could not be resolved from the monitor's settings.
+    http: Result<CameraHttp, String>,
+    channel: u32,
+    monitor_id: u32,
+}
+
+impl DahuaControl {
+    pub fn new(
+        protocol: &'static str,
+        config: PtzConnectionConfig,
+        capabilities: PtzCapabilities,
+    ) -> Self {
+        Self {
+            protocol,
+            capabilities: dahua_capabilities(capabilities),
+            http: CameraHttp::new(&config).map_err(|e| e.to_string()),
+            channel: http::channel(&config),
+            monitor_id: config.monitor_id,
+        }
+    }
+
+    fn http(&self) -> PtzResult<&CameraHttp> {
+        self.http
+            .as_ref()
+            .map_err(|e| PtzError::InvalidParameter(e.clone()))
+    }
+
+    /// `ptz.cgi?action={action}&code={code}&arg1=…&arg2=…&arg3=…`; a body
+    /// other than `OK` is the camera refusing the command.
+    async fn ptz(&self, action: &str, code: &str, args: [i64; 3]) -> PtzResult<()> {
+        let path = format!(
+            "cgi-bin/ptz.cgi?action={action}&channel={}&code={code}&arg1={}&arg2={}&arg3={}",
+            self.channel, args[0], args[1], args[2]
+        );
+        let body = self.http()?.get(&path).await?;
+        if body.trim().eq_ignore_ascii_case("ok") || body.trim().is_empty() {
+            Ok(())
+        } else {
+            Err(PtzError::ProtocolError(format!(
+                "{code}: {}",
+                body.lines().next().unwrap_or_default().trim()
+            )))
+        }
+    }
+
+    async fn absolute(&self, target: &AbsolutePosition) -> PtzResult<()> {
+        if target.pan.is_none() && target.tilt.is_none() && target.zoom.is_none() {
+            return Err(PtzError::InvalidParameter(
+                "MoveAbsolute requires at least one of pan/tilt/zoom".to_string(),
+            ));
+        }
+        // PositionABS takes all three axes; keep the current value of any the
+        // caller left out.
+        let current = if target.pan.is_none() || target.tilt.is_none() || target.zoom.is_none() {
+            self.status().await?
+        } else {
+            AbsolutePosition::default()
+        };
+        let pan = target.pan.or(current.pan).unwrap_or(0.0).rem_euclid(360.0);
+        let tilt = target.tilt.or(current.tilt).unwrap_or(0.0);
+        let zoom = target.zoom.or(current.zoom).unwrap_or(1.0).max(1.0);
+        self.ptz(
+            "start",
+            "PositionABS",
+            [pan.round() as i64, tilt.round() as i64, zoom.round() as i64],
+        )
+        .await
+    }
+
+    async fn relative(&self, delta: &RelativePosition) -> PtzResult<()> {
+        if delta.pan_delta.is_none() && delta.tilt_delta.is_none() && delta.zoom_delta.is_none() {
+            return Err(PtzError::InvalidParameter(
+                "MoveRelative requires at least one of pan/tilt/zoom delta".to_string(),
+            ));
+        }
+        let current = self.status().await?;
+        let offset = |now: Option<f64>, by: Option<f64>| now.map(|now| now + by.unwrap_or(0.0));
+        self.absolute(&AbsolutePosition {
+            pan: offset(current.pan, delta.pan_delta),
+            tilt: offset(current.tilt, delta.tilt_delta),
+            zoom: offset(current.zoom, delta.zoom_delta),
+        })
+        .await
+    }
+
+    async fn status(&self) -> PtzResult<AbsolutePosition> {
+        let path = format!("cgi-bin/ptz.cgi?action=getStatus&channel={}", self.channel);
+        let body = self.http()?.get(&path).await?;
+        parse_status(&body)
+            .ok_or_else(|| PtzError::ProtocolError("Dahua PTZ status has no position".to_string()))
+    }
+}
+
+/// The driver's view of a Dahua PTZ: everything `ptz.cgi` offers, with the
+/// `Controls` row's ranges where it sets them.
+fn dahua_capabilities(mut caps: PtzCapabilities) -> PtzCapabilities {
+    let pt = &mut caps.pan_tilt;
+    pt.can_pan = true;
+    pt.can_tilt = true;
+    pt.can_move = true;
+    pt.can_move_diag = true;
+    pt.can_move_con = true;
+    pt.can_move_abs = true;
+    pt.can_move_rel = true;
+    pt.pan_speed = speed_range(&pt.pan_speed, 1, MAX_SPEED as i32);
+    pt.tilt_speed = speed_range(&pt.tilt_speed, 1, MAX_SPEED as i32);
+    pt.pan_range = axis_range(&pt.pan_range, 0, 360);
+    pt.tilt_range = axis_range(&pt.tilt_range, -15, 90);
+
+    caps.zoom.can = true;
+    caps.zoom.can_con = true;
+    caps.zoom.can_abs = true;
+    caps.zoom.can_rel = true;
+    caps.zoom.speed = speed_range(&caps.zoom.speed, 1, MAX_SPEED as i32);
+    caps.focus.can = true;
+    caps.focus.can_con = true;
+    caps.focus.speed = speed_range(&caps.focus.speed, 1, MAX_SPEED as i32);
+    caps.iris.can = true;
+    caps.iris.can_con = true;
+
+    caps.presets.has_presets = true;
+    caps.presets.can_set_presets = true;
+    if caps.presets.num_presets == 0 {
+        caps.presets.num_presets = MAX_PRESETS;
+    }
+    caps.power.can_reboot = true;
+    caps
+}
+
+fn preset(preset_id: u32) -> PtzResult<i64> {
+    if (1..=u32::from(MAX_PRESETS)).contains(&preset_id) {
+        Ok(i64::from(preset_id))
+    } else {
+        Err(PtzError::InvalidParameter(format!(
+            "preset {preset_id} is not in 1..={MAX_PRESETS}"
+        )))
+    }
+}
+
+fn speed(percent: Option<u8>) -> i64 {
+    i64::from(http::scale_speed(percent, MAX_SPEED))
+}
+
+/// The position of a `getStatus` reply: `status.Postion[0..2]` (sic — the
+/// firmware's spelling; `Position` is accepted too) as pan and tilt degrees
+/// and zoom multiple.
+fn parse_status(body: &str) -> Option<AbsolutePosition> {
+    let mut position = AbsolutePosition::default();
+    for line in body.lines() {
+        let Some((key, value)) = line.split_once('=') else {
+            continue;
+        };
+        let key = key.trim();
+        let Some(index) = key
+            .strip_prefix("status.Postion[")
+            .or_else(|| key.strip_prefix("status.Position["))
+            .and_then(|rest| rest.strip_suffix(']'))
+        else {
+            continue;
+        };
+        let value = value.trim().parse::<f64>().ok();
+        match index {
+            "0" => position.pan = value,
+            "1" => position.tilt = value,
+            "2" => position.zoom = value,
+            _ => {}
+        }
+    }
+    (position.pan.is_some() || position.tilt.is_some() || position.zoom.is_some())
+        .then_some(position)
+}
+
+#[async_trait]
+impl PtzControl for DahuaControl {
+    fn capabilities(&self) -> &PtzCapabilities {
+        &self.capabilities
+    }
+
+    fn protocol_name(&self) -> &str {
+        self.protocol
+    }
+
+    fn is_native(&self) -> bool {
+        true
+    }
+
+    #[instrument(skip(self), fields(monitor_id = self.monitor_id, protocol = self.protocol, command = ?command))]
+    async fn execute(&self, command: PtzCommand) -> PtzResult<PtzCommandResult> {
+        debug!("dispatching Dahua PTZ command");
+        match &command {
+            PtzCommand::MoveUp(p) => self.ptz("start", "Up", [0, speed(p.tilt_speed), 0]).await?,
+            PtzCommand::MoveDown(p) => {
+                self.ptz("start", "Down", [0, speed(p.tilt_speed), 0])
+                    .await?
+            }
+            PtzCommand::MoveLeft(p) => {
+                self.ptz("start", "Left", [0, speed(p.pan_speed), 0])
+                    .await?
+            }
+            PtzCommand::MoveRight(p) => {
+                self.ptz("start", "Right", [0, speed(p.pan_speed), 0])
+                    .await?
+            }
+            PtzCommand::MoveUpLeft(p) => {
+                self.ptz(
+                    "start",
+                    "LeftUp",
+                    [speed(p.tilt_speed), speed(p.pan_speed), 0],
+                )
+                .await?
+            }
+            PtzCommand::MoveUpRight(p) => {
+                self.ptz(
+                    "start",
+                    "RightUp",
+                    [speed(p.tilt_speed), speed(p.pan_speed), 0],
+                )
+                .await?
+            }
+            PtzCommand::MoveDownLeft(p) => {
+                self.ptz(
+                    "start",
+                    "LeftDown",
+                    [speed(p.tilt_speed), speed(p.pan_speed), 0],
+                )
+                .await?
+            }
+            PtzCommand::MoveDownRight(p) => {
+                self.ptz(
+                    "start",
+                    "RightDown",
+                    [speed(p.tilt_speed), speed(p.pan_speed), 0],
+                )
+                .await?
+            }
+            // Stopping any direction stops pan and tilt.
+            PtzCommand::MoveStop => self.ptz("stop", "Up", [0, 0, 0]).await?,
+
+            PtzCommand::ZoomIn(p) => {
+                self.ptz("start", "ZoomTele", [0, speed(p.speed), 0])
+                    .await?
+            }
+            PtzCommand::ZoomOut(p) => {
+                self.ptz("start", "ZoomWide", [0, speed(p.speed), 0])
+                    .await?
+            }
+            PtzCommand::ZoomStop => self.ptz("stop", "ZoomTele", [0, 0, 0]).await?,
+            PtzCommand::FocusNear(p) => {
+                self.ptz("start", "FocusNear", [0, speed(p.speed), 0])
+                    .await?
+            }
+            PtzCommand::FocusFar(p) => {
+                self.ptz("start", "FocusFar", [0, speed(p.speed), 0])
+                    .await?
+            }
+            PtzCommand::FocusStop => self.ptz("stop", "FocusNear", [0, 0, 0]).await?,
+            PtzCommand::IrisOpen => self.ptz("start", "IrisLarge", [0, speed(None), 0]).await?,
+            PtzCommand::IrisClose => self.ptz("start", "IrisSmall", [0, speed(None), 0]).await?,
+            PtzCommand::IrisStop => self.ptz("stop", "IrisLarge", [0, 0, 0]).await?,
+
+            PtzCommand::GotoPreset { preset_id, .. } => {
+                self.ptz("start", "GotoPreset", [0, preset(*preset_id)?, 0])
+                    .await?
+            }
+            PtzCommand::SetPreset { preset_id, .. } => {
+                self.ptz("start", "SetPreset", [0, preset(*preset_id)?, 0])
+                    .await?
+            }
+            PtzCommand::ClearPreset { preset_id } => {
+                self.ptz("start", "ClearPreset", [0, preset(*preset_id)?, 0])
+                    .await?
+            }
+
+            PtzCommand::MoveAbsolute(position) => self.absolute(position).await?,
+            PtzCommand::MoveRelative(delta) => self.relative(delta).await?,
+
+            PtzCommand::Reboot => {
+                self.http()?
+                    .get("cgi-bin/magicBox.cgi?action=reboot")
+                    .await?;
+            }
+            other => {
+                return Err(PtzError::CommandNotSupported(format!(
+                    "Dahua driver does not support {}",
+                    other.zmcontrol_command()
+                )))
+            }
+        }
+        Ok(PtzCommandResult::success(format!(
+            "Dahua {} sent",
+            command.zmcontrol_command()
+        )))
+    }
+
+    async fn get_position(&self) -> PtzResult<Option<AbsolutePosition>> {
+        self.status().await.map(Some)
+    }
+
+    fn position_units(&self) -> Option<PositionUnits> {
+        Some(PositionUnits {
+            pan_per_degree: 1.0,
+            // Tilt counts downwards from the horizon.
+            tilt_per_degree: -1.0,
+            zoom: ZoomUnits::Magnification,
+        })
+    }
+}
+
+/// Factory for [`DahuaControl`], registered once per protocol name.
+#[derive(Debug)]
+pub struct DahuaControlFactory {
+    protocol: &'static str,
+}
+
+impl DahuaControlFactory {
+    /// A factory for `protocol`, [`PROTOCOL_DAHUA`] or [`PROTOCOL_AMCREST`].
+    pub fn new(protocol: &'static str) -> Self {
+        Self { protocol }
+    }
+}
+
+impl PtzControlFactory for DahuaControlFactory {
+    fn protocol_name(&self) -> &str {
+        self.protocol
+    }
+
+    fn is_native(&self) -> bool {
+        true
+    }
+
+    fn create(
+        &self,
+        config: PtzConnectionConfig,
+        capabilities: PtzCapabilities,
+    ) -> Box<dyn PtzControl> {
+        Box::new(DahuaControl::new(self.protocol, config, capabilities))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::ptz::protocols::http::tests::{config, next_request, stub_camera};
+    use crate::ptz::traits::MoveParams;
+
+    const STATUS: &str = "status.Action=Idle\r\nstatus.Postion[0]=350.5\r\nstatus.Postion[1]=12.0\r\nstatus.Postion[2]=4\r\n";
+
+    #[test]
+    fn parses_the_status_position() {
+        let position = parse_status(STATUS).unwrap();
+        assert_eq!(position.pan, Some(350.5));
+        assert_eq!(position.tilt, Some(12.0));
+        assert_eq!(position.zoom, Some(4.0));
+        assert!(parse_status("status.Action=Idle").is_none());
+    }
+
+    #[tokio::test]
+    async fn diagonal_moves_carry_both_speeds() {
+        let (addr, mut rx) = stub_camera(vec!["OK\r\n".into()]).await;
+        let ctrl = DahuaControlFactory::new(PROTOCOL_AMCREST)
+            .create(config(&addr, None), Default::default());
+        assert_eq!(ctrl.protocol_name(), "Amcrest_HTTP");
+        ctrl.execute(PtzCommand::MoveDownRight(MoveParams {
+            pan_speed: Some(100),
+            tilt_speed: Some(0),
+            ..MoveParams::default()
+        }))
+        .await
+        .unwrap();
+        assert!(next_request(&mut rx).await.starts_with(
+            "GET /cgi-bin/ptz.cgi?action=start&channel=1&code=RightDown&arg1=1&arg2=8&arg3=0 "
+        ));
+    }
+
+    #[tokio::test]
+    async fn relative_moves_offset_the_current_position() {
+        let (addr, mut rx) = stub_camera(vec![STATUS.into(), "OK".into()]).await;
+        let ctrl = DahuaControl::new(PROTOCOL_DAHUA, config(&addr, None), Default::default());
+        ctrl.execute(PtzCommand::MoveRelative(RelativePosition {
+            pan_delta: Some(20.0),
+            tilt_delta: Some(-2.0),
+            zoom_delta: None,
+        }))
+        .await
+        .unwrap();
+        assert!(next_request(&mut rx)
+            .await
+            .starts_with("GET /cgi-bin/ptz.cgi?action=getStatus&channel=1 "));
+        assert!(next_request(&mut rx).await.starts_with(
+            "GET /cgi-bin/ptz.cgi?action=start&channel=1&code=PositionABS&arg1=11&arg2=10&arg3=4 "
+        ));
+    }
+
+    #[tokio::test]
+    async fn a_refused_command_is_an_error() {
+        let (addr, _rx) = stub_camera(vec!["Error\r\nBad Request!\r\n".into()]).await;
+        let ctrl = DahuaControl::new(PROTOCOL_DAHUA, config(&addr, None), Default::default());
+        assert!(matches!(
+            ctrl.execute(PtzCommand::GotoPreset {
+                preset_id: 2,
+                speed: None,
+            })
+            .await,
+            Err(PtzError::ProtocolError(_))
+        ));
+    }
+}
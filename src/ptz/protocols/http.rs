@@ -0,0 +1,399 @@
+//! HTTP transport shared by the CGI-style PTZ protocols (Hikvision ISAPI,
+//! Dahua/Amcrest, Axis VAPIX).
+//!
+//! The monitor's `ControlAddress` is the camera's web interface, `host[:port]`
+//! or a full `http(s)://` URL (only its origin is used). Credentials come from
+//! the address or the monitor's `User`/`Pass`, as for the other drivers. A
+//! numeric `ControlDevice` selects the video channel on multi-channel devices
+//! (NVRs, encoders); it defaults to channel 1.
+//!
+//! Cameras authenticate with Digest, a few older ones with Basic. The first
+//! request goes out unauthenticated; the challenge it draws is remembered and
+//! answered up front afterwards, re-challenging only when the nonce goes stale.
+
+use std::time::Duration;
+
+use base64::Engine as _;
+use rand::Rng as _;
+use reqwest::header::{AUTHORIZATION, CONTENT_TYPE, WWW_AUTHENTICATE};
+use reqwest::{Method, StatusCode};
+use tokio::sync::Mutex;
+use tracing::debug;
+use url::Url;
+
+use crate::ptz::capabilities::{AxisRange, AxisSpeed};
+use crate::ptz::error::{PtzError, PtzResult};
+use crate::ptz::traits::PtzConnectionConfig;
+use crate::util::digest::DigestChallenge;
+
+/// Per-request timeout; PTZ CGIs answer at once, the move runs afterwards.
+const REQUEST_TIMEOUT: Duration = Duration::from_secs(5);
+/// How much of an error body to quote back.
+const ERROR_BODY_LIMIT: usize = 200;
+
+enum Auth {
+    Digest { challenge: DigestChallenge, nc: u32 },
+    Basic,
+}
+
+/// A camera's HTTP API, with its authentication state.
+pub struct CameraHttp {
+    client: reqwest::Client,
+    base: Url,
+    credentials: Option<(String, String)>,
+    auth: Mutex<Option<Auth>>,
+    cnonce: String,
+}
+
+impl CameraHttp {
+    /// Resolve the camera's base URL from a connection config.
+    pub fn new(config: &PtzConnectionConfig) -> PtzResult<Self> {
+        let address = config.address.trim();
+        let url = if address.starts_with("http://") || address.starts_with("https://") {
+            Url::parse(address)
+        } else {
+            Url::parse(&format!("http://{}", address.trim_end_matches('/')))
+        };
+        let mut base = url
+            .ok()
+            .filter(|u| u.host_str().is_some_and(|h| !h.is_empty()))
+            .ok_or_else(|| {
+                PtzError::InvalidParameter(format!(
+                    "ControlAddress {address:?} is not a camera host or URL"
+                ))
+            })?;
+        base.set_path("/");
+        base.set_query(None);
+        base.set_fragment(None);
+
+        let credentials = match (&config.username, &config.password) {
+            (Some(user), pass) if !user.is_empty() => {
+                Some((user.clone(), pass.clone().unwrap_or_default()))
+            }
+            _ => None,
+        };
+        let client = reqwest::Client::builder()
+            .timeout(REQUEST_TIMEOUT)
+            .build()
+            .map_err(|e| PtzError::InternalError(format!("HTTP client: {e}")))?;
+        Ok(Self {
+            client,
+            base,
+            credentials,
+            auth: Mutex::new(None),
+            cnonce: format!("{:016x}", rand::rng().random::<u64>()),
+        })
+    }
+
+    pub fn base(&self) -> &Url {
+        &self.base
+    }
+
+    pub async fn get(&self, path: &str) -> PtzResult<String> {
+        self.request(Method::GET, path, None).await
+    }
+
+    /// `PUT` an XML document, the ISAPI way of issuing commands.
+    pub async fn put_xml(&self, path: &str, xml: String) -> PtzResult<String> {
+        self.request(Method::PUT, path, Some(("application/xml", xml)))
+            .await
+    }
+
+    pub async fn delete(&self, path: &str) -> PtzResult<String> {
+        self.request(Method::DELETE, path, None).await
+    }
+
+    /// Send a request to `path` (relative to the camera root, query included)
+    /// and return the body of a 2xx reply.
+    pub async fn request(
+        &self,
+        method: Method,
+        path: &str,
+        body: Option<(&'static str, String)>,
+    ) -> PtzResult<String> {
+        let url = self
+            .base
+            .join(path.trim_start_matches('/'))
+            .map_err(|e| PtzError::InvalidParameter(format!("{path}: {e}")))?;
+        let uri = match url.query() {
+            Some(query) => format!("{}?{query}", url.path()),
+            None => url.path().to_string(),
+        };
+
+        // One retry: the first 401 carries the challenge (or a fresh nonce).
+        let mut challenged = false;
+        loop {
+            let mut request = self.client.request(method.clone(), url.clone());
+            if let Some(value) = self.authorization(method.as_str(), &uri).await {
+                request = request.header(AUTHORIZATION, value);
+            }
+            if let Some((content_type, body)) = &body {
+                request = request
+                    .header(CONTENT_TYPE, *content_type)
+                    .body(body.clone());
+            }
+            let response = request.send().await.map_err(|e| self.transport_error(e))?;
+            let status = response.status();
+
+            if status == StatusCode::UNAUTHORIZED && !challenged {
+                let headers: Vec<String> = response
+                    .headers()
+                    .get_all(WWW_AUTHENTICATE)
+                    .iter()
+                    .filter_map(|v| v.to_str().ok().map(str::to_string))
+                    .collect();
+                if self.challenged(&headers).await {
+                    challenged = true;
+                    continue;
+                }
+            }
+
+            let text = response.text().await.map_err(|e| self.transport_error(e))?;
+            return if status.is_success() {
+                Ok(text)
+            } else if status == StatusCode::UNAUTHORIZED || status == StatusCode::FORBIDDEN {
+                Err(PtzError::AuthenticationFailed(format!(
+                    "{} {uri}: {status}",
+                    self.base.host_str().unwrap_or_default()
+                )))
+            } else {
+                Err(PtzError::ProtocolError(format!(
+                    "{method} {uri}: {status}: {}",
+                    truncate(text.trim(), ERROR_BODY_LIMIT)
+                )))
+            };
+        }
+    }
+
+    async fn authorization(&self, method: &str, uri: &str) -> Option<String> {
+        let (user, pass) = self.credentials.as_ref()?;
+        let mut auth = self.auth.lock().await;
+        match auth.as_mut()? {
+            Auth::Digest { challenge, nc } => {
+                *nc += 1;
+                Some(challenge.authorization(user, pass, method, uri, *nc, &self.cnonce))
+            }
+            Auth::Basic => Some(format!(
+                "Basic {}",
+                base64::engine::general_purpose::STANDARD.encode(format!("{user}:{pass}"))
+            )),
+        }
+    }
+
+    /// Adopt a 401's challenge. `false` when there is nothing new to try.
+    async fn challenged(&self, headers: &[String]) -> bool {
+        if self.credentials.is_none() {
+            return false;
+        }
+        let next = match headers.iter().find_map(|h| DigestChallenge::parse(h)) {
+            Some(challenge) => Auth::Digest { challenge, nc: 0 },
+            None if headers
+                .iter()
+                .any(|h| h.trim().to_ascii_lowercase().starts_with("basic")) =>
+            {
+                Auth::Basic
+            }
+            None => return false,
+        };
+        let mut auth = self.auth.lock().await;
+        // Basic that already failed will fail again.
+        if matches!((&*auth, &next), (Some(Auth::Basic), Auth::Basic)) {
+            return false;
+        }
+        debug!("{}: adopting HTTP authentication challenge", self.base);
+        *auth = Some(next);
+        true
+    }
+
+    fn transport_error(&self, e: reqwest::Error) -> PtzError {
+        let host = self.base.host_str().unwrap_or_default();
+        if e.is_timeout() {
+            PtzError::CommandTimeout(format!("{host}: {e}"))
+        } else {
+            PtzError::CameraOffline(format!("{host}: {e}"))
+        }
+    }
+}
+
+/// The video channel a numeric `ControlDevice` selects, else 1.
+pub fn channel(config: &PtzConnectionConfig) -> u32 {
+    config
+        .device
+        .as_deref()
+        .and_then(|d| d.trim().parse().ok())
+        .filter(|c| *c >= 1)
+        .unwrap_or(1)
+}
+
+/// Map a `0..=100` percentage onto `1..=max`, full speed when unspecified.
+pub fn scale_speed(percent: Option<u8>, max: u32) -> u32 {
+    let percent = u32::from(percent.unwrap_or(100).min(100));
+    (1 + percent * (max - 1) / 100).max(1)
+}
+
+/// A speed range the camera takes, keeping one the `Controls` row sets.
+pub fn speed_range(configured: &AxisSpeed, min: i32, max: i32) -> AxisSpeed {
+    AxisSpeed {
+        has_speed: true,
+        min: configured.min.or(Some(min)),
+        max: configured.max.or(Some(max)),
+    }
+}
+
+/// A position range the camera takes, keeping one the `Controls` row sets.
+pub fn axis_range(configured: &AxisRange, min: i32, max: i32) -> AxisRange {
+    AxisRange {
+        min: configured.min.or(Some(min)),
+        max: configured.max.or(Some(max)),
+    }
+}
+
+fn truncate(s: &str, max: usize) -> &str {
+    match s.char_indices().nth(max) {
+        Some((i, _)) => &s[..i],
+        None => s,
+    }
+}
+
+#[cfg(test)]
+pub(crate) mod tests {
+    use super::*;
+    use tokio::io::{AsyncReadExt, AsyncWriteExt};
+    use tokio::net::TcpListener;
+
+    pub(crate) fn config(address: &str, user: Option<&str>) -> PtzConnectionConfig {
+        PtzConnectionConfig {
+            monitor_id: 5,
+            address: address.to_string(),
+            device: None,
+            username: user.map(str::to_string),
+            password: user.map(|_| "secret".to_string()),
+            protocol: "HikVision".to_string(),
+            auto_stop_timeout: None,
+        }
+    }
+
+    /// A camera that answers each request with the next of `replies` (a
+    /// status line plus body, or a full response when it starts with
+    /// `HTTP/`), forwarding the raw requests it receives.
+    pub(crate) async fn stub_camera(
+        replies: Vec<String>,
+    ) -> (String, tokio::sync::mpsc::UnboundedReceiver<String>) {
+        let listener = TcpListener::bind("127.0.0.1:0").await.unwrap();
+        let addr = listener.local_addr().unwrap().to_string();
+        let (tx, rx) = tokio::sync::mpsc::unbounded_channel();
+        tokio::spawn(async move {
+            for reply in replies {
+                let Ok((mut socket, _)) = listener.accept().await else {
+                    return;
+                };
+                let request = read_request(&mut socket).await;
+                let _ = tx.send(request);
+                let response = if reply.starts_with("HTTP/") {
+                    reply
+                } else {
+                    format!(
+                        "HTTP/1.1 200 OK\r\nContent-Length: {}\r\nConnection: close\r\n\r\n{reply}",
+                        reply.len()
+                    )
+                };
+                let _ = socket.write_all(response.as_bytes()).await;
+                let _ = socket.shutdown().await;
+            }
+        });
+        (addr, rx)
+    }
+
+    async fn read_request(socket: &mut tokio::net::TcpStream) -> String {
+        let mut buf = Vec::new();
+        let mut chunk = [0u8; 4096];
+        loop {
+            let n = socket.read(&mut chunk).await.unwrap_or(0);
+            if n == 0 {
+                break;
+            }
+            buf.extend_from_slice(&chunk[..n]);
+            let text = String::from_utf8_lossy(&buf);
+            if let Some(end) = text.find("\r\n\r\n") {
+                let length = text[..end]
+                    .lines()
+                    .find_map(|l| {
+                        let (name, value) = l.split_once(':')?;
+                        name.eq_ignore_ascii_case("content-length")
+                            .then(|| value.trim().parse::<usize>().ok())?
+                    })
+                    .unwrap_or(0);
+                if buf.len() >= end + 4 + length {
+                    break;
+                }
+            }
+        }
+        String::from_utf8_lossy(&buf).into_owned()
+    }
+
+    /// The next request a stub camera received.
+    pub(crate) async fn next_request(
+        rx: &mut tokio::sync::mpsc::UnboundedReceiver<String>,
+    ) -> String {
+        tokio::time::timeout(Duration::from_secs(2), rx.recv())
+            .await
+            .expect("a request from the driver")
+            .expect("camera open")
+    }
+
+    #[test]
+    fn base_url_keeps_only_the_origin() {
+        let http = CameraHttp::new(&config("10.0.0.5:8080", None)).unwrap();
+        assert_eq!(http.base().as_str(), "http://10.0.0.5:8080/");
+
+        let http = CameraHttp::new(&config("https://cam.local/onvif/device", None)).unwrap();
+        assert_eq!(http.base().as_str(), "https://cam.local/");
+
+        assert!(CameraHttp::new(&config("", None)).is_err());
+    }
+
+    #[test]
+    fn channel_comes_from_a_numeric_device() {
+        let mut cfg = config("cam", None);
+        assert_eq!(channel(&cfg), 1);
+        cfg.device = Some("3".to_string());
+        assert_eq!(channel(&cfg), 3);
+        cfg.device = Some("/dev/ttyS0".to_string());
+        assert_eq!(channel(&cfg), 1);
+    }
+
+    #[tokio::test]
+    async fn answers_a_digest_challenge_and_reuses_it() {
+        let challenge = "HTTP/1.1 401 Unauthorized\r\nWWW-Authenticate: Digest realm=\"cam\", nonce=\"n1\", qop=\"auth\"\r\nContent-Length: 0\r\nConnection: close\r\n\r\n";
+        let (addr, mut rx) =
+            stub_camera(vec![challenge.to_string(), "one".into(), "two".into()]).await;
+        let http = CameraHttp::new(&config(&addr, Some("admin"))).unwrap();
+
+        assert_eq!(http.get("/status?x=1").await.unwrap(), "one");
+        assert!(!next_request(&mut rx).await.contains("Authorization"));
+        let retried = next_request(&mut rx).await;
+        assert!(retried.contains("Authorization: Digest username=\"admin\""));
+        assert!(retried.contains("uri=\"/status?x=1\""));
+        assert!(retried.contains("nc=00000001"));
+
+        assert_eq!(http.get("status").await.unwrap(), "two");
+        assert!(next_request(&mut rx).await.contains("nc=00000002"));
+    }
+
+    #[tokio::test]
+    async fn maps_failures_onto_ptz_errors() {
+        let denied = "HTTP/1.1 401 Unauthorized\r\nContent-Length: 0\r\nConnection: close\r\n\r\n";
+        let broken = "HTTP/1.1 500 Internal Server Error\r\nContent-Length: 4\r\nConnection: close\r\n\r\noops";
+        let (addr, _rx) = stub_camera(vec![denied.into(), broken.into()]).await;
+        let http = CameraHttp::new(&config(&addr, None)).unwrap();
+
+        assert!(matches!(
+            http.get("a").await,
+            Err(PtzError::AuthenticationFailed(_))
+        ));
+        assert!(matches!(
+            http.get("b").await,
+            Err(PtzError::ProtocolError(msg)) if msg.contains("oops")
+        ));
+    }
+}
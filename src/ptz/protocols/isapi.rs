@@ -0,0 +1,483 @@
+//! Native Hikvision ISAPI PTZ driver (`Controls.Protocol` `HikVision`).
+//!
+//! Commands are XML documents `PUT` to `/ISAPI/PTZCtrl/channels/{ch}/…`;
+//! focus and iris live under `/ISAPI/System/Video/inputs/channels/{ch}/`.
+//!
+//! | `PtzCommand` | ISAPI |
+//! |---|---|
+//! | `Move*`, `Zoom*`, `MoveStop` | `continuous` with pan/tilt/zoom speeds `-100..=100` |
+//! | `Focus*`, `Iris*` | `focus` / `iris` with a signed speed, `0` to stop |
+//! | `MoveAbsolute` | `absolute` (`AbsoluteHigh`) |
+//! | `MoveRelative` | `status`, then `absolute` to the offset position |
+//! | presets | `presets/{id}` (`PUT` to save, `DELETE` to clear, `…/goto`) |
+//! | `GotoHome` | `homeposition/goto` |
+//! | `Reboot` | `/ISAPI/System/reboot` |
+//!
+//! Positions are degrees of azimuth (`0..360`, pan) and elevation (tilt) and
+//! zoom magnification (`1.0` = 1×); ISAPI itself counts tenths of each.
+
+use async_trait::async_trait;
+use quick_xml::events::Event;
+use quick_xml::reader::Reader;
+use tracing::{debug, instrument};
+
+use super::http::{self, axis_range, speed_range, CameraHttp};
+use crate::ptz::aim::{PositionUnits, ZoomUnits};
+use crate::ptz::capabilities::PtzCapabilities;
+use crate::ptz::error::{PtzError, PtzResult};
+use crate::ptz::traits::{
+    AbsolutePosition, PtzCommand, PtzCommandResult, PtzConnectionConfig, PtzControl,
+    PtzControlFactory, RelativePosition,
+};
+
+pub const PROTOCOL: &str = "HikVision";
+/// ISAPI continuous-move speeds are `-100..=100`.
+const MAX_SPEED: u32 = 100;
+const MAX_PRESETS: u8 = 255;
+
+/// Native Hikvision controller for one channel.
+pub struct IsapiControl {
+    capabilities: PtzCapabilities,
+    /// The camera, or why it could not be resolved from the monitor's settings.
+    http: Result<CameraHttp, String>,
+    channel: u32,
+    monitor_id: u32,
+}
+
+impl IsapiControl {
+    pub fn new(config: PtzConnectionConfig, capabilities: PtzCapabilities) -> Self {
+        Self {
+            capabilities: isapi_capabilities(capabilities),
+            http: CameraHttp::new(&config).map_err(|e| e.to_string()),
+            channel: http::channel(&config),
+            monitor_id: config.monitor_id,
+        }
+    }
+
+    fn http(&self) -> PtzResult<&CameraHttp> {
+        self.http
+            .as_ref()
+            .map_err(|e| PtzError::InvalidParameter(e.clone()))
+    }
+
+    fn ptz_path(&self, rest: &str) -> String {
+        format!("ISAPI/PTZCtrl/channels/{}/{rest}", self.channel)
+    }
+
+    async fn continuous(&self, pan: i32, tilt: i32, zoom: i32) -> PtzResult<()> {
+        let xml =
+            format!("<PTZData><pan>{pan}</pan><tilt>{tilt}</tilt><zoom>{zoom}</zoom></PTZData>");
+        self.http()?
+            .put_xml(&self.ptz_path("continuous"), xml)
+            .await
+            .map(drop)
+    }
+
+    async fn lens(&self, axis: &str, speed: i32) -> PtzResult<()> {
+        let tag = match axis {
+            "focus" => "FocusData",
+            _ => "IrisData",
+        };
+        let path = format!("ISAPI/System/Video/inputs/channels/{}/{axis}", self.channel);
+        let xml = format!("<{tag}><{axis}>{speed}</{axis}></{tag}>");
+        self.http()?.put_xml(&path, xml).await.map(drop)
+    }
+
+    async fn absolute(&self, target: &AbsolutePosition) -> PtzResult<()> {
+        if target.pan.is_none() && target.tilt.is_none() && target.zoom.is_none() {
+            return Err(PtzError::InvalidParameter(
+                "MoveAbsolute requires at least one of pan/tilt/zoom".to_string(),
+            ));
+        }
+        // AbsoluteHigh takes all three axes; keep the current value of any
+        // the caller left out.
+        let current = if target.pan.is_none() || target.tilt.is_none() || target.zoom.is_none() {
+            self.status().await?
+        } else {
+            AbsolutePosition::default()
+        };
+        let pan = target.pan.or(current.pan).unwrap_or(0.0).rem_euclid(360.0);
+        let tilt = target.tilt.or(current.tilt).unwrap_or(0.0);
+        let zoom = target.zoom.or(current.zoom).unwrap_or(1.0).max(1.0);
+        let xml = format!(
+            "<PTZData><AbsoluteHigh><elevation>{}</elevation><azimuth>{}</azimuth><absoluteZoom>{}</absoluteZoom></AbsoluteHigh></PTZData>",
+            tenths(tilt),
+            tenths(pan),
+            tenths(zoom)
+        );
+        self.http()?
+            .put_xml(&self.ptz_path("absolute"), xml)
+            .await
+            .map(drop)
+    }
+
+    async fn relative(&self, delta: &RelativePosition) -> PtzResult<()> {
+        if delta.pan_delta.is_none() && delta.tilt_delta.is_none() && delta.zoom_delta.is_none() {
+            return Err(PtzError::InvalidParameter(
+                "MoveRelative requires at least one of pan/tilt/zoom delta".to_string(),
+            ));
+        }
+        let current = self.status().await?;
+        let offset = |now: Option<f64>, by: Option<f64>| now.map(|now| now + by.unwrap_or(0.0));
+        self.absolute(&AbsolutePosition {
+            pan: offset(current.pan, delta.pan_delta),
+            tilt: offset(current.tilt, delta.tilt_delta),
+            zoom: offset(current.zoom, delta.zoom_delta),
+        })
+        .await
+    }
+
+    async fn status(&self) -> PtzResult<AbsolutePosition> {
+        let xml = self.http()?.get(&self.ptz_path("status")).await?;
+        parse_status(&xml).ok_or_else(|| {
+            PtzError::ProtocolError("ISAPI PTZ status has no AbsoluteHigh position".to_string())
+        })
+    }
+
+    async fn preset(&self, command: &PtzCommand) -> PtzResult<()> {
+        let http = self.http()?;
+        match command {
+            PtzCommand::GotoPreset { preset_id, .. } => {
+                let path = self.ptz_path(&format!("presets/{}/goto", preset(*preset_id)?));
+                http.put_xml(&path, String::new()).await?;
+            }
+            PtzCommand::SetPreset { preset_id, name } => {
+                let id = preset(*preset_id)?;
+                let name = name.clone().unwrap_or_else(|| format!("Preset {id}"));
+                let xml = format!(
+                    "<PTZPreset><id>{id}</id><presetName>{}</presetName><enabled>true</enabled></PTZPreset>",
+                    xml_escape(&name)
+                );
+                http.put_xml(&self.ptz_path(&format!("presets/{id}")), xml)
+                    .await?;
+            }
+            PtzCommand::ClearPreset { preset_id } => {
+                let path = self.ptz_path(&format!("presets/{}", preset(*preset_id)?));
+                http.delete(&path).await?;
+            }
+            _ => unreachable!("not a preset command"),
+        }
+        Ok(())
+    }
+}
+
+/// The driver's view of a Hikvision PTZ: everything ISAPI offers, with the
+/// `Controls` row's ranges where it sets them.
+fn isapi_capabilities(mut caps: PtzCapabilities) -> PtzCapabilities {
+    let pt = &mut caps.pan_tilt;
+    pt.can_pan = true;
+    pt.can_tilt = true;
+    pt.can_move = true;
+    pt.can_move_diag = true;
+    pt.can_move_con = true;
+    pt.can_move_abs = true;
+    pt.can_move_rel = true;
+    pt.pan_speed = speed_range(&pt.pan_speed, 1, MAX_SPEED as i32);
+    pt.tilt_speed = speed_range(&pt.tilt_speed, 1, MAX_SPEED as i32);
+    pt.pan_range = axis_range(&pt.pan_range, 0, 360);
+    pt.tilt_range = axis_range(&pt.tilt_range, -90, 90);
+
+    caps.zoom.can = true;
+    caps.zoom.can_con = true;
+    caps.zoom.can_abs = true;
+    caps.zoom.can_rel = true;
+    caps.zoom.speed = speed_range(&caps.zoom.speed, 1, MAX_SPEED as i32);
+    caps.focus.can = true;
+    caps.focus.can_con = true;
+    caps.focus.speed = speed_range(&caps.focus.speed, 1, MAX_SPEED as i32);
+    caps.iris.can = true;
+    caps.iris.can_con = true;
+
+    caps.presets.has_presets = true;
+    caps.presets.can_set_presets = true;
+    caps.presets.has_home_preset = true;
+    if caps.presets.num_presets == 0 {
+        caps.presets.num_presets = MAX_PRESETS;
+    }
+    caps.power.can_reboot = true;
+    caps
+}
+
+fn preset(preset_id: u32) -> PtzResult<u32> {
+    if (1..=u32::from(MAX_PRESETS)).contains(&preset_id) {
+        Ok(preset_id)
+    } else {
+        Err(PtzError::InvalidParameter(format!(
+            "preset {preset_id} is not in 1..={MAX_PRESETS}"
+        )))
+    }
+}
+
+fn tenths(value: f64) -> i64 {
+    (value * 10.0).round() as i64
+}
+
+/// A signed ISAPI speed for a `0..=100` percentage and a direction.
+fn speed(percent: Option<u8>, direction: i32) -> i32 {
+    direction * http::scale_speed(percent, MAX_SPEED) as i32
+}
+
+/// The `AbsoluteHigh` position of a `PTZStatus` document, in degrees and ×.
+fn parse_status(xml: &str) -> Option<AbsolutePosition> {
+    let mut reader = Reader::from_str(xml);
+    let mut current = String::new();
+    let mut position = AbsolutePosition::default();
+    loop {
+        match reader.read_event() {
+            Ok(Event::Start(e)) => {
+                let name = String::from_utf8_lossy(e.local_name().as_ref()).into_owned();
+                current = name;
+            }
+            Ok(Event::Text(t)) => {
+                let value = t
+                    .xml10_content()
+                    .ok()
+                    .and_then(|v| v.trim().parse::<f64>().ok())
+                    .map(|v| v / 10.0);
+                match current.as_str() {
+                    "azimuth" => position.pan = value,
+                    "elevation" => position.tilt = value,
+                    "absoluteZoom" => position.zoom = value,
+                    _ => {}
+                }
+            }
+            Ok(Event::End(_)) => current.clear(),
+            Ok(Event::Eof) | Err(_) => break,
+            _ => {}
+        }
+    }
+    (position.pan.is_some() || position.tilt.is_some() || position.zoom.is_some())
+        .then_some(position)
+}
+
+fn xml_escape(s: &str) -> String {
+    s.replace('&', "&amp;")
+        .replace('<', "&lt;")
+        .replace('>', "&gt;")
+        .replace('"', "&quot;")
+        .replace('\'', "&apos;")
+}
+
+#[async_trait]
+impl PtzControl for IsapiControl {
+    fn capabilities(&self) -> &PtzCapabilities {
+        &self.capabilities
+    }
+
+    fn protocol_name(&self) -> &str {
+        PROTOCOL
+    }
+
+    fn is_native(&self) -> bool {
+        true
+    }
+
+    #[instrument(skip(self), fields(monitor_id = self.monitor_id, protocol = "isapi", command = ?command))]
+    async fn execute(&self, command: PtzCommand) -> PtzResult<PtzCommandResult> {
+        debug!("dispatching ISAPI PTZ command");
+        match &command {
+            PtzCommand::MoveUp(p) => self.continuous(0, speed(p.tilt_speed, 1), 0).await?,
+            PtzCommand::MoveDown(p) => self.continuous(0, speed(p.tilt_speed, -1), 0).await?,
+            PtzCommand::MoveLeft(p) => self.continuous(speed(p.pan_speed, -1), 0, 0).await?,
+            PtzCommand::MoveRight(p) => self.continuous(speed(p.pan_speed, 1), 0, 0).await?,
+            PtzCommand::MoveUpLeft(p) => {
+                self.continuous(speed(p.pan_speed, -1), speed(p.tilt_speed, 1), 0)
+                    .await?
+            }
+            PtzCommand::MoveUpRight(p) => {
+                self.continuous(speed(p.pan_speed, 1), speed(p.tilt_speed, 1), 0)
+                    .await?
+            }
+            PtzCommand::MoveDownLeft(p) => {
+                self.continuous(speed(p.pan_speed, -1), speed(p.tilt_speed, -1), 0)
+                    .await?
+            }
+            PtzCommand::MoveDownRight(p) => {
+                self.continuous(speed(p.pan_speed, 1), speed(p.tilt_speed, -1), 0)
+                    .await?
+            }
+            PtzCommand::MoveStop | PtzCommand::ZoomStop => self.continuous(0, 0, 0).await?,
+            PtzCommand::ZoomIn(p) => self.continuous(0, 0, speed(p.speed, 1)).await?,
+            PtzCommand::ZoomOut(p) => self.continuous(0, 0, speed(p.speed, -1)).await?,
+
+            PtzCommand::FocusFar(p) => self.lens("focus", speed(p.speed, 1)).await?,
+            PtzCommand::FocusNear(p) => self.lens("focus", speed(p.speed, -1)).await?,
+            PtzCommand::FocusStop => self.lens("focus", 0).await?,
+            PtzCommand::IrisOpen => self.lens("iris", speed(None, 1)).await?,
+            PtzCommand::IrisClose => self.lens("iris", speed(None, -1)).await?,
+            PtzCommand::IrisStop => self.lens("iris", 0).await?,
+
+            PtzCommand::GotoPreset { .. }
+            | PtzCommand::SetPreset { .. }
+            | PtzCommand::ClearPreset { .. } => self.preset(&command).await?,
+            PtzCommand::GotoHome => {
+                self.http()?
+                    .put_xml(&self.ptz_path("homeposition/goto"), String::new())
+                    .await?;
+            }
+
+            PtzCommand::MoveAbsolute(position) => self.absolute(position).await?,
+            PtzCommand::MoveRelative(delta) => self.relative(delta).await?,
+
+            PtzCommand::Reboot => {
+                self.http()?
+                    .put_xml("ISAPI/System/reboot", String::new())
+                    .await?;
+            }
+            other => {
+                return Err(PtzError::CommandNotSupported(format!(
+                    "ISAPI driver does not support {}",
+                    other.zmcontrol_command()
+                )))
+            }
+        }
+        Ok(PtzCommandResult::success(format!(
+            "ISAPI {} sent",
+            command.zmcontrol_command()
+        )))
+    }
+
+    async fn get_position(&self) -> PtzResult<Option<AbsolutePosition>> {
+        self.status().await.map(Some)
+    }
+
+    fn position_units(&self) -> Option<PositionUnits> {
+        Some(PositionUnits {
+            pan_per_degree: 1.0,
+            // Tilt counts downwards from the horizon.
+            tilt_per_degree: -1.0,
+            zoom: ZoomUnits::Magnification,
+        })
+    }
+}
+
+/// Factory for [`IsapiControl`].
+#[derive(Debug, Default)]
+pub struct IsapiControlFactory;
+
+impl IsapiControlFactory {
+    pub fn new() -> Self {
+        Self
+    }
+}
+
+impl PtzControlFactory for IsapiControlFactory {
+    fn protocol_name(&self) -> &str {
+        PROTOCOL
+    }
+
+    fn is_native(&self) -> bool {
+        true
+    }
+
+    fn create(
+        &self,
+        config: PtzConnectionConfig,
+        capabilities: PtzCapabilities,
+    ) -> Box<dyn PtzControl> {
+        Box::new(IsapiControl::new(config, capabilities))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::ptz::protocols::http::tests::{config, next_request, stub_camera};
+    use crate::ptz::traits::MoveParams;
+
+    const STATUS: &str = r#"<?xml version="1.0" encoding="UTF-8"?>
+<PTZStatus version="2.0" xmlns="http://www.hikvision.com/ver20/XMLSchema">
+<AbsoluteHigh><elevation>-45</elevation><azimuth>3550</azimuth><absoluteZoom>20</absoluteZoom></AbsoluteHigh>
+</PTZStatus>"#;
+
+    #[test]
+    fn parses_the_status_position() {
+        let position = parse_status(STATUS).unwrap();
+        assert_eq!(position.pan, Some(355.0));
+        assert_eq!(position.tilt, Some(-4.5));
+        assert_eq!(position.zoom, Some(2.0));
+        assert!(parse_status("<PTZStatus/>").is_none());
+    }
+
+    #[test]
+    fn maps_capabilities_over_the_controls_row() {
+        let mut row = PtzCapabilities::default();
+        row.pan_tilt.pan_speed.max = Some(60);
+        let caps = isapi_capabilities(row);
+        assert!(caps.pan_tilt.can_move_abs && caps.pan_tilt.can_move_rel);
+        assert_eq!(caps.pan_tilt.pan_speed.max, Some(60));
+        assert_eq!(caps.pan_tilt.tilt_speed.max, Some(100));
+        assert_eq!(caps.presets.num_presets, MAX_PRESETS);
+        assert!(caps.power.can_reboot && !caps.power.can_wake);
+    }
+
+    #[tokio::test]
+    async fn continuous_moves_put_ptz_data() {
+        let (addr, mut rx) = stub_camera(vec!["<ResponseStatus/>".into()]).await;
+        let ctrl = IsapiControlFactory::new().create(config(&addr, None), Default::default());
+        ctrl.execute(PtzCommand::MoveUpLeft(MoveParams {
+            pan_speed: Some(40),
+            tilt_speed: Some(100),
+            ..MoveParams::default()
+        }))
+        .await
+        .unwrap();
+        let request = next_request(&mut rx).await;
+        assert!(request.starts_with("PUT /ISAPI/PTZCtrl/channels/1/continuous HTTP/1.1"));
+        assert!(
+            request.ends_with("<PTZData><pan>-40</pan><tilt>100</tilt><zoom>0</zoom></PTZData>")
+        );
+    }
+
+    #[tokio::test]
+    async fn relative_moves_offset_the_current_position() {
+        let (addr, mut rx) = stub_camera(vec![STATUS.into(), "<ResponseStatus/>".into()]).await;
+        let ctrl = IsapiControl::new(config(&addr, None), Default::default());
+        ctrl.execute(PtzCommand::MoveRelative(RelativePosition {
+            pan_delta: Some(10.0),
+            ..Default::default()
+        }))
+        .await
+        .unwrap();
+        assert!(next_request(&mut rx)
+            .await
+            .starts_with("GET /ISAPI/PTZCtrl/channels/1/status"));
+        let request = next_request(&mut rx).await;
+        assert!(request.starts_with("PUT /ISAPI/PTZCtrl/channels/1/absolute"));
+        assert!(request.contains(
+            "<elevation>-45</elevation><azimuth>50</azimuth><absoluteZoom>20</absoluteZoom>"
+        ));
+    }
+
+    #[tokio::test]
+    async fn presets_are_saved_named_and_cleared() {
+        let ok = "<ResponseStatus/>".to_string();
+        let (addr, mut rx) = stub_camera(vec![ok.clone(), ok]).await;
+        let ctrl = IsapiControl::new(config(&addr, None), Default::default());
+        ctrl.execute(PtzCommand::SetPreset {
+            preset_id: 4,
+            name: Some("Gate & yard".to_string()),
+        })
+        .await
+        .unwrap();
+        let request = next_request(&mut rx).await;
+        assert!(request.starts_with("PUT /ISAPI/PTZCtrl/channels/1/presets/4 "));
+        assert!(request.contains("<presetName>Gate &amp; yard</presetName>"));
+
+        ctrl.execute(PtzCommand::ClearPreset { preset_id: 4 })
+            .await
+            .unwrap();
+        assert!(next_request(&mut rx)
+            .await
+            .starts_with("DELETE /ISAPI/PTZCtrl/channels/1/presets/4 "));
+
+        assert!(matches!(
+            ctrl.execute(PtzCommand::GotoPreset {
+                preset_id: 0,
+                speed: None,
+            })
+            .await,
+            Err(PtzError::InvalidParameter(_))
+        ));
+    }
+}
@@ -1,8 +1,19 @@
 //! Native PTZ protocol implementations.
 //!
 //! Each submodule implements [`crate::ptz::traits::PtzControl`] for a specific
-//! camera control protocol. Currently: ONVIF. These are registered as native
-//! factories in the [`crate::ptz::registry::PtzRegistry`] at server startup;
-//! protocols without a native implementation fall back to the Perl bridge.
+//! camera control protocol: ONVIF (behind the `onvif-ptz` feature), the
+//! vendor HTTP APIs of Hikvision (ISAPI), Dahua/Amcrest and Axis (VAPIX), and
+//! the serial protocols Pelco-D, Pelco-P and VISCA over a serial-to-IP bridge
+//! or a local port. These are registered as native factories in the
+//! [`crate::ptz::registry::PtzRegistry`] at server startup; protocols without a
+//! native implementation fall back to the Perl bridge.
 
+pub mod dahua;
+pub mod http;
+pub mod isapi;
+#[cfg(feature = "onvif-ptz")]
 pub mod onvif;
+pub mod pelco;
+pub mod serial;
+pub mod vapix;
+pub mod visca;
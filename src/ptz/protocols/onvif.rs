@@ -30,15 +30,32 @@
 //! command (e.g. `MoveLeft` → negative pan). [`AbsolutePosition`] /
 //! [`RelativePosition`] values are assumed to already be in normalized ONVIF
 //! space and are clamped to `[-1.0, 1.0]`.
+//!
+//! Position readback is `GetStatus`. The first aim at a spot in the picture
+//! probes the camera with `GetConfigurations` and `GetNodes`. A node that
+//! lists the optional FOV translation space centers on a clicked point by
+//! itself, with a `RelativeMove` in that space; ONVIF has no area zoom, so a
+//! dragged box, and a click on any other camera, becomes a generic-space
+//! `RelativeMove` worked out from the field of view. For that the
+//! configuration's `PanTiltLimits` are taken as the camera's full travel,
+//! a generic range spanning 360° of pan and 180° of tilt and a degrees range
+//! as itself, and its `ZoomLimits` as running from wide to tele. Without
+//! limits, or when the probe fails, the generic space is assumed to span the
+//! usual `[-1.0, 1.0]` for pan and tilt and `0.0..=1.0` for zoom.
 
 use async_trait::async_trait;
-use tracing::{debug, info, instrument};
+use tokio::sync::OnceCell;
+use tracing::{debug, info, instrument, warn};
 
 use crate::onvif::error::OnvifError;
-use crate::onvif::ptz::{PanTilt, PtzClient, PtzVector, Zoom};
+use crate::onvif::ptz::{
+    FloatRange, PanTilt, PtzClient, PtzConfiguration, PtzNode, PtzVector, SpaceLimits, Zoom,
+    TRANSLATION_SPACE_FOV,
+};
 use crate::onvif::transport::OnvifTransport;
 use crate::onvif::types::Credentials;
 
+use crate::ptz::aim::{AimTarget, PositionUnits, ZoomUnits};
 use crate::ptz::capabilities::PtzCapabilities;
 use crate::ptz::error::{PtzError, PtzResult};
 use crate::ptz::traits::{
@@ -51,6 +68,29 @@ use crate::ptz::traits::{
 /// conventional first-profile token emitted by the vast majority of cameras.
 const DEFAULT_PROFILE_TOKEN: &str = "Profile_1";
 
+/// Space URI of limits given in degrees rather than generic units.
+const SPHERICAL_SPACE_DEGREES: &str =
+    "http://www.onvif.org/ver10/tptz/PanTiltSpaces/SphericalPositionSpaceDegrees";
+
+/// Units assumed when the camera reports no limits: generic `[-1.0, 1.0]`
+/// across 360° of pan and 180° of tilt, zoom `0.0..=1.0`.
+const GENERIC_UNITS: PositionUnits = PositionUnits {
+    pan_per_degree: 2.0 / 360.0,
+    tilt_per_degree: 2.0 / 180.0,
+    zoom: ZoomUnits::Linear {
+        wide: 0.0,
+        tele: 1.0,
+    },
+};
+
+/// What the first aim learned about the camera's PTZ configuration and node.
+#[derive(Debug, Clone, Copy, PartialEq)]
+struct AimProbe {
+    units: PositionUnits,
+    /// The node takes pan/tilt translations relative to the field of view.
+    fov_translation: bool,
+}
+
 /// Native ONVIF PTZ controller for a single monitor.
 ///
 /// Holds an [`onvif::ptz::PtzClient`] bound to the camera's PTZ-service XAddr
@@ -65,6 +105,8 @@ pub struct OnvifControl {
     profile_token: String,
     /// Owning monitor id (for tracing context).
     monitor_id: u32,
+    /// Limits and spaces, read on the first aim.
+    probe: OnceCell<AimProbe>,
 }
 
 impl OnvifControl {
@@ -107,6 +149,7 @@ impl OnvifControl {
             client,
             profile_token: DEFAULT_PROFILE_TOKEN.to_string(),
             monitor_id: config.monitor_id,
+            probe: OnceCell::new(),
         }
     }
 
@@ -182,6 +225,38 @@ impl OnvifControl {
         Ok(PtzCommandResult::success("RelativeMove sent"))
     }
 
+    /// Read the configuration's limits and the node's spaces once. A failed
+    /// probe is not cached: the aim goes ahead on [`GENERIC_UNITS`] and the
+    /// next one asks again.
+    async fn aim_probe(&self) -> AimProbe {
+        let probed = self
+            .probe
+            .get_or_try_init(|| async {
+                let configs = self.client.get_configurations().await?;
+                let config = configs
+                    .iter()
+                    .find(|c| c.pan_tilt_limits.is_some() || c.zoom_limits.is_some())
+                    .or(configs.first());
+                let nodes = self.client.get_nodes().await?;
+                Ok::<_, OnvifError>(AimProbe {
+                    units: units_from_config(config),
+                    fov_translation: node_for(&nodes, config)
+                        .is_some_and(|n| n.supports_relative_pan_tilt(TRANSLATION_SPACE_FOV)),
+                })
+            })
+            .await;
+        match probed {
+            Ok(probe) => *probe,
+            Err(e) => {
+                warn!(monitor_id = self.monitor_id, error = %e, "ONVIF PTZ probe failed, assuming generic limits");
+                AimProbe {
+                    units: GENERIC_UNITS,
+                    fov_translation: false,
+                }
+            }
+        }
+    }
+
     /// Issue a `Stop`, selecting which axes to halt.
     async fn stop(&self, pan_tilt: bool, zoom: bool) -> PtzResult<PtzCommandResult> {
         self.client
@@ -246,6 +321,37 @@ impl PtzControl for OnvifControl {
         }
         result
     }
+
+    async fn get_position(&self) -> PtzResult<Option<AbsolutePosition>> {
+        let status = self
+            .client
+            .get_status(&self.profile_token)
+            .await
+            .map_err(map_onvif_error)?;
+        Ok(vector_to_position(&status.position))
+    }
+
+    fn position_units(&self) -> Option<PositionUnits> {
+        Some(self.probe.get().map_or(GENERIC_UNITS, |p| p.units))
+    }
+
+    async fn aim(&self, target: &AimTarget) -> PtzResult<Option<PtzCommandResult>> {
+        if !self.aim_probe().await.fov_translation {
+            return Ok(None);
+        }
+        let AimTarget::Point(point) = target else {
+            return Ok(None);
+        };
+        self.client
+            .relative_move_in_space(
+                &self.profile_token,
+                fov_translation(point.x, point.y),
+                TRANSLATION_SPACE_FOV,
+            )
+            .await
+            .map_err(map_onvif_error)?;
+        Ok(Some(PtzCommandResult::success("RelativeMove (FOV) sent")))
+    }
 }
 
 /// Map a generic `0..=100` percentage speed to a normalized ONVIF velocity
@@ -294,6 +400,73 @@ fn translation_to_vector(pos: &RelativePosition) -> PtzVector {
     }
 }
 
+/// The FOV-space translation that brings a point of the frame (`0,0`
+/// top-left) to its middle: `±1` reaches the edges, with up positive.
+fn fov_translation(x: f64, y: f64) -> PanTilt {
+    PanTilt::new(clamp_norm((x - 0.5) * 2.0), clamp_norm((0.5 - y) * 2.0))
+}
+
+/// The node a configuration drives, or the first node when it names none.
+fn node_for<'a>(nodes: &'a [PtzNode], config: Option<&PtzConfiguration>) -> Option<&'a PtzNode> {
+    match config.and_then(|c| c.node_token.as_deref()) {
+        Some(token) => nodes.iter().find(|n| n.token.as_deref() == Some(token)),
+        None => nodes.first(),
+    }
+}
+
+/// Units per degree of an axis whose limits span `range`, taken as its full
+/// travel of `travel` degrees; `None` for an empty or missing range.
+fn per_degree(limits: &SpaceLimits, range: Option<FloatRange>, travel: f64) -> Option<f64> {
+    let range = range?;
+    let span = f64::from(range.max) - f64::from(range.min);
+    if span.is_nan() || span <= 0.0 {
+        return None;
+    }
+    if limits.space.as_deref() == Some(SPHERICAL_SPACE_DEGREES) {
+        // The generic translation space spans `[-1.0, 1.0]` across the range.
+        Some(2.0 / span)
+    } else {
+        Some(span / travel)
+    }
+}
+
+/// The position units a configuration's limits describe, falling back to
+/// [`GENERIC_UNITS`] axis by axis.
+fn units_from_config(config: Option<&PtzConfiguration>) -> PositionUnits {
+    let mut units = GENERIC_UNITS;
+    if let Some(limits) = config.and_then(|c| c.pan_tilt_limits.as_ref()) {
+        if let Some(pan) = per_degree(limits, limits.x, 360.0) {
+            units.pan_per_degree = pan;
+        }
+        if let Some(tilt) = per_degree(limits, limits.y, 180.0) {
+            units.tilt_per_degree = tilt;
+        }
+    }
+    if let Some(range) = config
+        .and_then(|c| c.zoom_limits.as_ref())
+        .and_then(|z| z.x)
+        .filter(|r| r.max > r.min)
+    {
+        units.zoom = ZoomUnits::Linear {
+            wide: f64::from(range.min),
+            tele: f64::from(range.max),
+        };
+    }
+    units
+}
+
+/// The axes a `GetStatus` position reports, `None` when it reports none.
+fn vector_to_position(vector: &PtzVector) -> Option<AbsolutePosition> {
+    if vector.pan_tilt.is_none() && vector.zoom.is_none() {
+        return None;
+    }
+    Some(AbsolutePosition {
+        pan: vector.pan_tilt.map(|pt| f64::from(pt.x)),
+        tilt: vector.pan_tilt.map(|pt| f64::from(pt.y)),
+        zoom: vector.zoom.map(|z| f64::from(z.x)),
+    })
+}
+
 /// Translate an [`OnvifError`] into the generic [`PtzError`] surface so the PTZ
 /// manager/handlers can map it to the appropriate HTTP status.
 fn map_onvif_error(err: OnvifError) -> PtzError {
@@ -362,6 +535,7 @@ mod tests {
         PtzConnectionConfig {
             monitor_id: 7,
             address: "http://192.168.1.10/onvif/ptz_service".to_string(),
+            device: None,
             username: Some("admin".to_string()),
             password: Some("secret".to_string()),
             protocol: "onvif".to_string(),
@@ -469,6 +643,127 @@ mod tests {
         assert_eq!(v.zoom, Some(Zoom::new(0.2)));
     }
 
+    #[test]
+    fn status_position_reports_only_present_axes() {
+        assert!(vector_to_position(&PtzVector::default()).is_none());
+        let pos = vector_to_position(&PtzVector::zoom(0.5)).unwrap();
+        assert_eq!((pos.pan, pos.tilt, pos.zoom), (None, None, Some(0.5)));
+        let pos = vector_to_position(&PtzVector::pan_tilt(-0.25, 0.75)).unwrap();
+        assert_eq!(
+            (pos.pan, pos.tilt, pos.zoom),
+            (Some(-0.25), Some(0.75), None)
+        );
+    }
+
+    fn limits(space: &str, x: (f32, f32), y: Option<(f32, f32)>) -> SpaceLimits {
+        let range = |(min, max)| FloatRange { min, max };
+        SpaceLimits {
+            space: Some(format!("http://www.onvif.org/ver10/tptz/{space}")),
+            x: Some(range(x)),
+            y: y.map(range),
+        }
+    }
+
+    #[test]
+    fn units_come_from_the_configured_limits() {
+        // Nothing to go on: the generic assumption.
+        assert_eq!(units_from_config(None), GENERIC_UNITS);
+        assert_eq!(
+            units_from_config(Some(&PtzConfiguration::default())),
+            GENERIC_UNITS
+        );
+
+        // A generic range that spans 0..1 covers the full turn in one unit.
+        let config = PtzConfiguration {
+            pan_tilt_limits: Some(limits(
+                "PanTiltSpaces/PositionGenericSpace",
+                (0.0, 1.0),
+                Some((-1.0, 1.0)),
+            )),
+            zoom_limits: Some(limits("ZoomSpaces/PositionGenericSpace", (0.0, 0.5), None)),
+            ..Default::default()
+        };
+        let units = units_from_config(Some(&config));
+        assert!((units.pan_per_degree - 1.0 / 360.0).abs() < 1e-9);
+        assert!((units.tilt_per_degree - 1.0 / 90.0).abs() < 1e-9);
+        assert_eq!(
+            units.zoom,
+            ZoomUnits::Linear {
+                wide: 0.0,
+                tele: 0.5
+            }
+        );
+
+        // Degrees limits are the travel the generic [-1, 1] spans.
+        let config = PtzConfiguration {
+            pan_tilt_limits: Some(limits(
+                "PanTiltSpaces/SphericalPositionSpaceDegrees",
+                (-170.0, 170.0),
+                Some((-5.0, 85.0)),
+            )),
+            ..Default::default()
+        };
+        let units = units_from_config(Some(&config));
+        assert!((units.pan_per_degree - 2.0 / 340.0).abs() < 1e-9);
+        assert!((units.tilt_per_degree - 2.0 / 90.0).abs() < 1e-9);
+        assert_eq!(units.zoom, GENERIC_UNITS.zoom);
+    }
+
+    #[test]
+    fn empty_limits_fall_back_axis_by_axis() {
+        let config = PtzConfiguration {
+            pan_tilt_limits: Some(limits(
+                "PanTiltSpaces/PositionGenericSpace",
+                (1.0, 1.0),
+                Some((-0.5, 0.5)),
+            )),
+            zoom_limits: Some(limits("ZoomSpaces/PositionGenericSpace", (1.0, 0.0), None)),
+            ..Default::default()
+        };
+        let units = units_from_config(Some(&config));
+        assert_eq!(units.pan_per_degree, GENERIC_UNITS.pan_per_degree);
+        assert!((units.tilt_per_degree - 1.0 / 180.0).abs() < 1e-9);
+        assert_eq!(units.zoom, GENERIC_UNITS.zoom);
+    }
+
+    #[test]
+    fn fov_translation_centers_the_point() {
+        assert_eq!(fov_translation(0.5, 0.5), PanTilt::new(0.0, 0.0));
+        assert_eq!(fov_translation(1.0, 0.0), PanTilt::new(1.0, 1.0));
+        assert_eq!(fov_translation(0.25, 0.75), PanTilt::new(-0.5, -0.5));
+        assert_eq!(fov_translation(-1.0, 2.0), PanTilt::new(-1.0, -1.0));
+    }
+
+    #[test]
+    fn the_configured_node_is_probed() {
+        let node = |token: &str, fov: bool| PtzNode {
+            token: Some(token.to_string()),
+            relative_pan_tilt_spaces: if fov {
+                vec![TRANSLATION_SPACE_FOV.to_string()]
+            } else {
+                Vec::new()
+            },
+        };
+        let nodes = [node("A", false), node("B", true)];
+        let config = PtzConfiguration {
+            node_token: Some("B".to_string()),
+            ..Default::default()
+        };
+        assert_eq!(node_for(&nodes, Some(&config)), Some(&nodes[1]));
+        assert_eq!(node_for(&nodes, None), Some(&nodes[0]));
+        let config = PtzConfiguration {
+            node_token: Some("C".to_string()),
+            ..Default::default()
+        };
+        assert_eq!(node_for(&nodes, Some(&config)), None);
+    }
+
+    #[test]
+    fn position_units_default_to_generic_before_the_probe() {
+        let ctrl = OnvifControl::new(test_config(), PtzCapabilities::default());
+        assert_eq!(ctrl.position_units(), Some(GENERIC_UNITS));
+    }
+
     #[test]
     fn onvif_error_maps_to_ptz_error() {
         assert!(matches!(
@@ -509,7 +804,12 @@ mod tests {
     #[tokio::test]
     async fn unsupported_command_reports_not_supported() {
         let ctrl = OnvifControl::new(test_config(), PtzCapabilities::default());
-        let res = ctrl.execute(PtzCommand::GotoPreset { preset_id: 3 }).await;
+        let res = ctrl
+            .execute(PtzCommand::GotoPreset {
+                preset_id: 3,
+                speed: None,
+            })
+            .await;
         assert!(matches!(res, Err(PtzError::CommandNotSupported(_))));
     }
 
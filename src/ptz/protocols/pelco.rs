@@ -0,0 +1,588 @@
+//! Native Pelco-D and Pelco-P PTZ drivers.
+//!
+//! Both are fixed-length binary frames on an RS-485 bus, reached through
+//! [`super::serial`]:
+//!
+//! | | Frame | Checksum |
+//! |---|---|---|
+//! | Pelco-D | `FF addr cmd1 cmd2 data1 data2 sum` | `addr..=data2` summed mod 256 |
+//! | Pelco-P | `A0 addr data1 data2 data3 data4 AF xor` | XOR of the seven bytes before it |
+//!
+//! Pelco-D addresses cameras from 1, Pelco-P from 0; both take the
+//! 1-based address ZoneMinder stores. Pan and tilt speeds are `0x00..=0x3F`.
+//! Presets, home and the Pelco-D absolute-position commands use the
+//! "extended" command form (`cmd1 = 0`, an odd `cmd2`). Neither has a preset
+//! travel speed; domes recall presets at their own.
+//!
+//! Neither protocol acknowledges commands; a frame written is a frame sent.
+
+use async_trait::async_trait;
+use tracing::{debug, instrument};
+
+use super::serial::{self, scale_speed, SerialLink};
+use crate::ptz::capabilities::PtzCapabilities;
+use crate::ptz::error::{PtzError, PtzResult};
+use crate::ptz::traits::{
+    AbsolutePosition, MoveParams, PtzCommand, PtzCommandResult, PtzConnectionConfig, PtzControl,
+    PtzControlFactory,
+};
+
+/// Fastest pan/tilt speed below turbo.
+const MAX_SPEED: u8 = 0x3F;
+/// Pelco's "go to zero pan" preset, which domes treat as home.
+const HOME_PRESET: u8 = 0x22;
+
+/// Pelco-D `cmd1` bits.
+mod d1 {
+    pub const FOCUS_NEAR: u8 = 0x01;
+    pub const IRIS_OPEN: u8 = 0x02;
+    pub const IRIS_CLOSE: u8 = 0x04;
+    pub const CAMERA_ON_OFF: u8 = 0x08;
+    pub const SENSE: u8 = 0x80;
+}
+
+/// Pelco-D `cmd2` bits, and Pelco-P `data2` — the two share a layout.
+mod motion {
+    pub const RIGHT: u8 = 0x02;
+    pub const LEFT: u8 = 0x04;
+    pub const UP: u8 = 0x08;
+    pub const DOWN: u8 = 0x10;
+    pub const ZOOM_TELE: u8 = 0x20;
+    pub const ZOOM_WIDE: u8 = 0x40;
+    /// Pelco-D only; Pelco-P carries focus far in `data1`.
+    pub const FOCUS_FAR: u8 = 0x80;
+}
+
+/// Pelco-P `data1` bits.
+mod p1 {
+    pub const FOCUS_FAR: u8 = 0x01;
+    pub const FOCUS_NEAR: u8 = 0x02;
+    pub const IRIS_OPEN: u8 = 0x04;
+    pub const IRIS_CLOSE: u8 = 0x08;
+    pub const CAMERA_ON_OFF: u8 = 0x10;
+    pub const CAMERA_ON: u8 = 0x40;
+}
+
+/// Extended commands, shared by both protocols.
+mod ext {
+    pub const SET_PRESET: u8 = 0x03;
+    pub const CLEAR_PRESET: u8 = 0x05;
+    pub const GOTO_PRESET: u8 = 0x07;
+    pub const RESET: u8 = 0x29;
+    pub const AUTO_FOCUS: u8 = 0x2B;
+    pub const AUTO_IRIS: u8 = 0x2D;
+    pub const REMOTE_RESET: u8 = 0x0F;
+    /// Pelco-D only: absolute pan / tilt / zoom.
+    pub const PAN_POSITION: u8 = 0x4B;
+    pub const TILT_POSITION: u8 = 0x4D;
+    pub const ZOOM_POSITION: u8 = 0x4F;
+}
+
+/// The two Pelco dialects.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum PelcoVariant {
+    D,
+    P,
+}
+
+impl PelcoVariant {
+    /// ZoneMinder's `Controls.Protocol` name.
+    pub fn protocol_name(&self) -> &'static str {
+        match self {
+            Self::D => "PelcoD",
+            Self::P => "PelcoP",
+        }
+    }
+
+    /// Highest camera address the dialect can express (1-based).
+    fn max_address(&self) -> u8 {
+        match self {
+            Self::D => 255,
+            Self::P => 32,
+        }
+    }
+}
+
+/// One Pelco command before framing: the four payload bytes both dialects
+/// carry, in Pelco-D terms.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+struct Payload {
+    cmd1: u8,
+    cmd2: u8,
+    data1: u8,
+    data2: u8,
+}
+
+impl Payload {
+    fn extended(command: u8, data1: u8, data2: u8) -> Self {
+        Self {
+            cmd1: 0,
+            cmd2: command,
+            data1,
+            data2,
+        }
+    }
+}
+
+/// Frame a Pelco-D command for camera `address` (1-based).
+pub fn pelco_d_frame(address: u8, cmd1: u8, cmd2: u8, data1: u8, data2: u8) -> [u8; 7] {
+    let sum = [address, cmd1, cmd2, data1, data2]
+        .iter()
+        .fold(0u8, |acc, b| acc.wrapping_add(*b));
+    [0xFF, address, cmd1, cmd2, data1, data2, sum]
+}
+
+/// Frame a Pelco-P command for camera `address` (1-based; sent 0-based).
+pub fn pelco_p_frame(address: u8, data1: u8, data2: u8, data3: u8, data4: u8) -> [u8; 8] {
+    let mut frame = [
+        0xA0,
+        address.saturating_sub(1),
+        data1,
+        data2,
+        data3,
+        data4,
+        0xAF,
+        0,
+    ];
+    frame[7] = frame[..7].iter().fold(0u8, |acc, b| acc ^ b);
+    frame
+}
+
+/// Native Pelco-D / Pelco-P controller for one camera on a bus.
+pub struct PelcoControl {
+    variant: PelcoVariant,
+    capabilities: PtzCapabilities,
+    /// The bus, or why it could not be resolved from the monitor's settings —
+    /// reported on the first command rather than at construction.
+    link: Result<(SerialLink, u8), String>,
+    monitor_id: u32,
+}
+
+impl PelcoControl {
+    pub fn new(
+        variant: PelcoVariant,
+        config: PtzConnectionConfig,
+        capabilities: PtzCapabilities,
+    ) -> Self {
+        let link = serial::resolve(&config, variant.max_address())
+            .map(|(endpoint, address)| (SerialLink::new(endpoint), address))
+            .map_err(|e| e.to_string());
+        Self {
+            variant,
+            capabilities,
+            link,
+            monitor_id: config.monitor_id,
+        }
+    }
+
+    /// Frame a payload in this controller's dialect.
+    fn frame(&self, address: u8, payload: Payload) -> Vec<u8> {
+        match self.variant {
+            PelcoVariant::D => pelco_d_frame(
+                address,
+                payload.cmd1,
+                payload.cmd2,
+                payload.data1,
+                payload.data2,
+            )
+            .to_vec(),
+            PelcoVariant::P => {
+                // Pelco-P keeps focus far with the lens bits and has no sense
+                // bit: camera on/off is its own pair of flags.
+                let mut data1 = 0;
+                let mut data2 = payload.cmd2;
+                if payload.cmd1 != 0 || payload.cmd2 & 0x01 == 0 {
+                    if payload.cmd1 & d1::FOCUS_NEAR != 0 {
+                        data1 |= p1::FOCUS_NEAR;
+                    }
+                    if payload.cmd1 & d1::IRIS_OPEN != 0 {
+                        data1 |= p1::IRIS_OPEN;
+                    }
+                    if payload.cmd1 & d1::IRIS_CLOSE != 0 {
+                        data1 |= p1::IRIS_CLOSE;
+                    }
+                    if payload.cmd1 & d1::CAMERA_ON_OFF != 0 {
+                        data1 |= p1::CAMERA_ON_OFF;
+                        if payload.cmd1 & d1::SENSE != 0 {
+                            data1 |= p1::CAMERA_ON;
+                        }
+                    }
+                    if payload.cmd2 & motion::FOCUS_FAR != 0 {
+                        data1 |= p1::FOCUS_FAR;
+                        data2 &= !motion::FOCUS_FAR;
+                    }
+                }
+                pelco_p_frame(address, data1, data2, payload.data1, payload.data2).to_vec()
+            }
+        }
+    }
+
+    /// The payloads a command becomes.
+    fn payloads(&self, command: &PtzCommand) -> PtzResult<Vec<Payload>> {
+        let payload = match command {
+            PtzCommand::MoveUp(p) => movement(p, motion::UP),
+            PtzCommand::MoveDown(p) => movement(p, motion::DOWN),
+            PtzCommand::MoveLeft(p) => movement(p, motion::LEFT),
+            PtzCommand::MoveRight(p) => movement(p, motion::RIGHT),
+            PtzCommand::MoveUpLeft(p) => movement(p, motion::UP | motion::LEFT),
+            PtzCommand::MoveUpRight(p) => movement(p, motion::UP | motion::RIGHT),
+            PtzCommand::MoveDownLeft(p) => movement(p, motion::DOWN | motion::LEFT),
+            PtzCommand::MoveDownRight(p) => movement(p, motion::DOWN | motion::RIGHT),
+            // A frame with no motion bits stops every axis.
+            PtzCommand::MoveStop
+            | PtzCommand::ZoomStop
+            | PtzCommand::FocusStop
+            | PtzCommand::IrisStop => Payload::default(),
+
+            PtzCommand::ZoomIn(_) => Payload {
+                cmd2: motion::ZOOM_TELE,
+                ..Payload::default()
+            },
+            PtzCommand::ZoomOut(_) => Payload {
+                cmd2: motion::ZOOM_WIDE,
+                ..Payload::default()
+            },
+            PtzCommand::FocusNear(_) => Payload {
+                cmd1: d1::FOCUS_NEAR,
+                ..Payload::default()
+            },
+            PtzCommand::FocusFar(_) => Payload {
+                cmd2: motion::FOCUS_FAR,
+                ..Payload::default()
+            },
+            PtzCommand::FocusAuto => Payload::extended(ext::AUTO_FOCUS, 0, 0),
+            PtzCommand::IrisOpen => Payload {
+                cmd1: d1::IRIS_OPEN,
+                ..Payload::default()
+            },
+            PtzCommand::IrisClose => Payload {
+                cmd1: d1::IRIS_CLOSE,
+                ..Payload::default()
+            },
+            PtzCommand::IrisAuto => Payload::extended(ext::AUTO_IRIS, 0, 0),
+
+            // No speed field in the frame; the dome's own preset speed applies.
+            PtzCommand::GotoPreset {
+                preset_id,
+                speed: _,
+            } => Payload::extended(ext::GOTO_PRESET, 0, preset(*preset_id)?),
+            PtzCommand::SetPreset { preset_id, .. } => {
+                Payload::extended(ext::SET_PRESET, 0, preset(*preset_id)?)
+            }
+            PtzCommand::ClearPreset { preset_id } => {
+                Payload::extended(ext::CLEAR_PRESET, 0, preset(*preset_id)?)
+            }
+            PtzCommand::GotoHome => Payload::extended(ext::GOTO_PRESET, 0, HOME_PRESET),
+
+            PtzCommand::Wake => Payload {
+                cmd1: d1::SENSE | d1::CAMERA_ON_OFF,
+                ..Payload::default()
+            },
+            PtzCommand::Sleep => Payload {
+                cmd1: d1::CAMERA_ON_OFF,
+                ..Payload::default()
+            },
+            PtzCommand::Reset => Payload::extended(ext::RESET, 0, 0),
+            PtzCommand::Reboot => Payload::extended(ext::REMOTE_RESET, 0, 0),
+
+            PtzCommand::MoveAbsolute(position) if self.variant == PelcoVariant::D => {
+                return absolute(position);
+            }
+            other => {
+                return Err(PtzError::CommandNotSupported(format!(
+                    "{} does not support {}",
+                    self.variant.protocol_name(),
+                    other.zmcontrol_command()
+                )))
+            }
+        };
+        Ok(vec![payload])
+    }
+}
+
+/// A pan/tilt movement frame with its speeds.
+fn movement(params: &MoveParams, bits: u8) -> Payload {
+    Payload {
+        cmd1: 0,
+        cmd2: bits,
+        data1: if bits & (motion::LEFT | motion::RIGHT) != 0 {
+            scale_speed(params.pan_speed, MAX_SPEED)
+        } else {
+            0
+        },
+        data2: if bits & (motion::UP | motion::DOWN) != 0 {
+            scale_speed(params.tilt_speed.or(params.pan_speed), MAX_SPEED)
+        } else {
+            0
+        },
+    }
+}
+
+fn preset(preset_id: u32) -> PtzResult<u8> {
+    u8::try_from(preset_id)
+        .ok()
+        .filter(|id| *id >= 1)
+        .ok_or_else(|| PtzError::InvalidParameter(format!("preset {preset_id} is not in 1..=255")))
+}
+
+/// Pelco-D absolute positioning, in the dome's units: pan and tilt in
+/// hundredths of a degree, zoom as the lens's own position value.
+fn absolute(position: &AbsolutePosition) -> PtzResult<Vec<Payload>> {
+    let axes = [
+        (position.pan, ext::PAN_POSITION, 35_999.0),
+        (position.tilt, ext::TILT_POSITION, 35_999.0),
+        (position.zoom, ext::ZOOM_POSITION, f64::from(u16::MAX)),
+    ];
+    let mut payloads = Vec::new();
+    for (value, command, max) in axes {
+        let Some(value) = value else { continue };
+        if !(0.0..=max).contains(&value) {
+            return Err(PtzError::InvalidParameter(format!(
+                "position {value} is not in 0..={max}"
+            )));
+        }
+        let [high, low] = (value.round() as u16).to_be_bytes();
+        payloads.push(Payload::extended(command, high, low));
+    }
+    if payloads.is_empty() {
+        return Err(PtzError::InvalidParameter(
+            "MoveAbsolute requires at least one of pan/tilt/zoom".to_string(),
+        ));
+    }
+    Ok(payloads)
+}
+
+#[async_trait]
+impl PtzControl for PelcoControl {
+    fn capabilities(&self) -> &PtzCapabilities {
+        &self.capabilities
+    }
+
+    fn protocol_name(&self) -> &str {
+        self.variant.protocol_name()
+    }
+
+    fn is_native(&self) -> bool {
+        true
+    }
+
+    #[instrument(skip(self), fields(monitor_id = self.monitor_id, protocol = self.variant.protocol_name(), command = ?command))]
+    async fn execute(&self, command: PtzCommand) -> PtzResult<PtzCommandResult> {
+        let (link, address) = self
+            .link
+            .as_ref()
+            .map_err(|e| PtzError::InvalidParameter(e.clone()))?;
+        let frames: Vec<Vec<u8>> = self
+            .payloads(&command)?
+            .into_iter()
+            .map(|payload| self.frame(*address, payload))
+            .collect();
+        debug!("sending {} frame(s) to {:?}", frames.len(), link.endpoint());
+        link.send(&frames).await?;
+        Ok(PtzCommandResult::success(format!(
+            "{} {} sent",
+            self.variant.protocol_name(),
+            command.zmcontrol_command()
+        )))
+    }
+}
+
+/// Factory for [`PelcoControl`] in one dialect.
+pub struct PelcoControlFactory {
+    variant: PelcoVariant,
+}
+
+impl PelcoControlFactory {
+    pub fn new(variant: PelcoVariant) -> Self {
+        Self { variant }
+    }
+}
+
+impl PtzControlFactory for PelcoControlFactory {
+    fn protocol_name(&self) -> &str {
+        self.variant.protocol_name()
+    }
+
+    fn is_native(&self) -> bool {
+        true
+    }
+
+    fn create(
+        &self,
+        config: PtzConnectionConfig,
+        capabilities: PtzCapabilities,
+    ) -> Box<dyn PtzControl> {
+        Box::new(PelcoControl::new(self.variant, config, capabilities))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::ptz::protocols::serial::tests::{config, received, stub_bridge};
+
+    fn full_speed() -> MoveParams {
+        MoveParams {
+            pan_speed: Some(100),
+            tilt_speed: Some(100),
+            ..MoveParams::default()
+        }
+    }
+
+    #[test]
+    fn pelco_d_checksum_is_the_byte_sum() {
+        // Camera 1, pan right at speed 0x20: the datasheet example.
+        assert_eq!(
+            pelco_d_frame(1, 0x00, 0x02, 0x20, 0x00),
+            [0xFF, 0x01, 0x00, 0x02, 0x20, 0x00, 0x23]
+        );
+        // The sum wraps.
+        assert_eq!(pelco_d_frame(0xFF, 0, 0x07, 0, 0x22)[6], 0x28);
+    }
+
+    #[test]
+    fn pelco_p_is_zero_based_with_an_xor_checksum() {
+        assert_eq!(
+            pelco_p_frame(1, 0x00, 0x02, 0x20, 0x00),
+            [0xA0, 0x00, 0x00, 0x02, 0x20, 0x00, 0xAF, 0x2D]
+        );
+    }
+
+    #[test]
+    fn pelco_p_moves_focus_far_and_power_into_data1() {
+        let ctrl = PelcoControl::new(
+            PelcoVariant::P,
+            config("2", Some("/dev/ttyS0")),
+            PtzCapabilities::default(),
+        );
+        let far = ctrl
+            .payloads(&PtzCommand::FocusFar(Default::default()))
+            .unwrap()[0];
+        assert_eq!(ctrl.frame(2, far)[1..6], [0x01, p1::FOCUS_FAR, 0, 0, 0]);
+        let wake = ctrl.payloads(&PtzCommand::Wake).unwrap()[0];
+        assert_eq!(ctrl.frame(2, wake)[2], p1::CAMERA_ON_OFF | p1::CAMERA_ON);
+        // Extended commands pass through untouched.
+        let home = ctrl.payloads(&PtzCommand::GotoHome).unwrap()[0];
+        assert_eq!(
+            ctrl.frame(2, home)[2..6],
+            [0, ext::GOTO_PRESET, 0, HOME_PRESET]
+        );
+    }
+
+    #[test]
+    fn absolute_position_is_one_frame_per_axis() {
+        let payloads = absolute(&AbsolutePosition {
+            pan: Some(9000.0),
+            tilt: None,
+            zoom: Some(1.0),
+        })
+        .unwrap();
+        assert_eq!(
+            payloads,
+            vec![
+                Payload::extended(ext::PAN_POSITION, 0x23, 0x28),
+                Payload::extended(ext::ZOOM_POSITION, 0x00, 0x01),
+            ]
+        );
+        assert!(absolute(&AbsolutePosition::default()).is_err());
+        assert!(absolute(&AbsolutePosition {
+            pan: Some(-1.0),
+            ..Default::default()
+        })
+        .is_err());
+    }
+
+    #[test]
+    fn preset_recall_has_no_speed() {
+        let ctrl = PelcoControl::new(
+            PelcoVariant::D,
+            config("1", Some("/dev/ttyS0")),
+            PtzCapabilities::default(),
+        );
+        let goto = |speed| {
+            ctrl.payloads(&PtzCommand::GotoPreset {
+                preset_id: 4,
+                speed,
+            })
+            .unwrap()
+        };
+        assert_eq!(goto(Some(10)), goto(None));
+        assert_eq!(goto(None), vec![Payload::extended(ext::GOTO_PRESET, 0, 4)]);
+    }
+
+    #[test]
+    fn presets_must_fit_a_byte() {
+        assert!(preset(0).is_err());
+        assert!(preset(256).is_err());
+        assert_eq!(preset(12).unwrap(), 12);
+    }
+
+    #[tokio::test]
+    async fn pelco_d_sends_exact_frames_over_tcp() {
+        let (addr, mut rx) = stub_bridge(None).await;
+        let ctrl = PelcoControlFactory::new(PelcoVariant::D)
+            .create(config("3", Some(&addr)), PtzCapabilities::default());
+        assert_eq!(ctrl.protocol_name(), "PelcoD");
+        assert!(ctrl.is_native());
+
+        ctrl.execute(PtzCommand::MoveUpLeft(full_speed()))
+            .await
+            .unwrap();
+        assert_eq!(
+            received(&mut rx, 7).await,
+            pelco_d_frame(3, 0, motion::UP | motion::LEFT, 0x3F, 0x3F)
+        );
+        ctrl.execute(PtzCommand::MoveStop).await.unwrap();
+        assert_eq!(received(&mut rx, 7).await, pelco_d_frame(3, 0, 0, 0, 0));
+        ctrl.execute(PtzCommand::SetPreset {
+            preset_id: 5,
+            name: None,
+        })
+        .await
+        .unwrap();
+        assert_eq!(
+            received(&mut rx, 7).await,
+            [0xFF, 0x03, 0x00, 0x03, 0x00, 0x05, 0x0B]
+        );
+    }
+
+    #[tokio::test]
+    async fn pelco_p_sends_exact_frames_over_tcp() {
+        let (addr, mut rx) = stub_bridge(None).await;
+        let ctrl = PelcoControl::new(
+            PelcoVariant::P,
+            config("1", Some(&addr)),
+            PtzCapabilities::default(),
+        );
+        ctrl.execute(PtzCommand::ZoomIn(Default::default()))
+            .await
+            .unwrap();
+        assert_eq!(
+            received(&mut rx, 8).await,
+            [0xA0, 0x00, 0x00, 0x20, 0x00, 0x00, 0xAF, 0x2F]
+        );
+    }
+
+    #[tokio::test]
+    async fn misconfigured_bus_is_reported_per_command() {
+        let ctrl = PelcoControl::new(
+            PelcoVariant::D,
+            config("1", None),
+            PtzCapabilities::default(),
+        );
+        assert!(matches!(
+            ctrl.execute(PtzCommand::MoveStop).await,
+            Err(PtzError::InvalidParameter(_))
+        ));
+        let ctrl = PelcoControl::new(
+            PelcoVariant::P,
+            config("1", Some("/dev/ttyS0")),
+            PtzCapabilities::default(),
+        );
+        assert!(matches!(
+            ctrl.execute(PtzCommand::MoveRelative(Default::default()))
+                .await,
+            Err(PtzError::CommandNotSupported(_))
+        ));
+    }
+}
@@ -0,0 +1,329 @@
+//! Byte transport shared by the serial PTZ protocols (Pelco-D, Pelco-P, VISCA).
+//!
+//! Analogue domes sit on an RS-485/RS-232 bus, reached either through a
+//! serial-to-IP converter (`host:port`, a raw TCP socket) or a local serial
+//! port (`/dev/ttyUSB0`). Following ZoneMinder's convention the monitor's
+//! `ControlDevice` names that endpoint and `ControlAddress` the camera's
+//! address on the bus; a `host:port` in `ControlAddress` with no device is
+//! accepted too, with the camera at address 1.
+//!
+//! Line settings (baud rate, parity) of a local port are not touched — set
+//! them once with `stty` or a udev rule, as for `zmcontrol.pl`.
+
+use std::time::Duration;
+
+use tokio::io::{AsyncReadExt, AsyncWriteExt};
+use tokio::net::TcpStream;
+use tokio::sync::Mutex;
+use tracing::debug;
+
+use crate::ptz::error::{PtzError, PtzResult};
+use crate::ptz::traits::PtzConnectionConfig;
+
+/// How long to wait for a serial bridge to accept the TCP connection.
+const CONNECT_TIMEOUT: Duration = Duration::from_secs(3);
+/// How long a single frame write may take.
+const WRITE_TIMEOUT: Duration = Duration::from_secs(2);
+
+/// Where a serial bus is reached.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum Endpoint {
+    /// A serial-to-IP bridge, `host:port`.
+    Tcp(String),
+    /// A local serial port.
+    Device(String),
+}
+
+impl Endpoint {
+    fn parse(value: &str) -> Option<Self> {
+        let value = value.trim();
+        let value = value.strip_prefix("tcp://").unwrap_or(value);
+        if value.starts_with('/') {
+            Some(Self::Device(value.to_string()))
+        } else if value
+            .rsplit_once(':')
+            .is_some_and(|(host, port)| !host.is_empty() && port.parse::<u16>().is_ok())
+        {
+            Some(Self::Tcp(value.trim_end_matches('/').to_string()))
+        } else {
+            None
+        }
+    }
+}
+
+/// The bus endpoint and the camera's address on it, from a connection config.
+///
+/// Fails when neither `ControlDevice` nor `ControlAddress` names an endpoint,
+/// or the address is not a number in `1..=max_address`.
+pub fn resolve(config: &PtzConnectionConfig, max_address: u8) -> PtzResult<(Endpoint, u8)> {
+    let device = config.device.as_deref().and_then(Endpoint::parse);
+    let (endpoint, address) = match device {
+        // With no `ControlAddress` the manager passes the device through as
+        // the address; that is camera 1 on the bus.
+        Some(endpoint) if Some(config.address.trim()) == config.device.as_deref().map(str::trim) => {
+            (endpoint, "")
+        }
+        Some(endpoint) => (endpoint, config.address.trim()),
+        None => match Endpoint::parse(&config.address) {
+            Some(endpoint) => (endpoint, ""),
+            None => {
+                return Err(PtzError::InvalidParameter(format!(
+                    "no serial port or host:port bridge configured (ControlDevice {:?}, ControlAddress {:?})",
+                    config.device.as_deref().unwrap_or_default(),
+                    config.address
+                )))
+            }
+        },
+    };
+    let address = match address {
+        "" => 1,
+        value => value
+            .parse::<u8>()
+            .ok()
+            .filter(|a| (1..=max_address).contains(a))
+            .ok_or_else(|| {
+                PtzError::InvalidParameter(format!(
+                    "camera address {value:?} is not in 1..={max_address}"
+                ))
+            })?,
+    };
+    Ok((endpoint, address))
+}
+
+enum Stream {
+    Tcp(TcpStream),
+    Device(tokio::fs::File),
+}
+
+/// A lazily opened connection to a serial bus. Frames are written whole
+/// under a lock, so concurrent commands never interleave on the wire; a failed
+/// write drops the connection and the next command reopens it.
+pub struct SerialLink {
+    endpoint: Endpoint,
+    stream: Mutex<Option<Stream>>,
+}
+
+impl SerialLink {
+    pub fn new(endpoint: Endpoint) -> Self {
+        Self {
+            endpoint,
+            stream: Mutex::new(None),
+        }
+    }
+
+    pub fn endpoint(&self) -> &Endpoint {
+        &self.endpoint
+    }
+
+    async fn open(&self) -> PtzResult<Stream> {
+        match &self.endpoint {
+            Endpoint::Tcp(addr) => {
+                let stream = tokio::time::timeout(CONNECT_TIMEOUT, TcpStream::connect(addr))
+                    .await
+                    .map_err(|_| PtzError::CameraOffline(format!("{addr}: connect timed out")))?
+                    .map_err(|e| PtzError::CameraOffline(format!("{addr}: {e}")))?;
+                let _ = stream.set_nodelay(true);
+                Ok(Stream::Tcp(stream))
+            }
+            Endpoint::Device(path) => tokio::fs::OpenOptions::new()
+                .read(true)
+                .write(true)
+                .open(path)
+                .await
+                .map(Stream::Device)
+                .map_err(|e| PtzError::CameraOffline(format!("{path}: {e}"))),
+        }
+    }
+
+    /// Write `frames` back to back, (re)opening the connection if needed.
+    pub async fn send(&self, frames: &[Vec<u8>]) -> PtzResult<()> {
+        self.exchange(frames, None).await.map(|_| ())
+    }
+
+    /// Write `frames`, then collect the reply until `complete` accepts it or
+    /// `wait` passes. Replies are only read from TCP bridges: a blocking read
+    /// on a local port would hold the port past the timeout, so there the
+    /// reply is always empty.
+    pub async fn exchange(
+        &self,
+        frames: &[Vec<u8>],
+        reply: Option<(Duration, fn(&[u8]) -> bool)>,
+    ) -> PtzResult<Vec<u8>> {
+        let mut guard = self.stream.lock().await;
+        if guard.is_none() {
+            *guard = Some(self.open().await?);
+            debug!("opened serial PTZ link {:?}", self.endpoint);
+        }
+        let stream = guard.as_mut().expect("stream opened");
+
+        let written = tokio::time::timeout(WRITE_TIMEOUT, async {
+            for frame in frames {
+                match stream {
+                    Stream::Tcp(s) => s.write_all(frame).await?,
+                    Stream::Device(f) => f.write_all(frame).await?,
+                }
+            }
+            match stream {
+                Stream::Tcp(s) => s.flush().await,
+                Stream::Device(f) => f.flush().await,
+            }
+        })
+        .await;
+        match written {
+            Ok(Ok(())) => {}
+            Ok(Err(e)) => {
+                *guard = None;
+                return Err(PtzError::CameraOffline(format!("{:?}: {e}", self.endpoint)));
+            }
+            Err(_) => {
+                *guard = None;
+                return Err(PtzError::CommandTimeout(format!(
+                    "{:?}: write timed out",
+                    self.endpoint
+                )));
+            }
+        }
+
+        let (Some((wait, complete)), Stream::Tcp(socket)) = (reply, stream) else {
+            return Ok(Vec::new());
+        };
+        let mut buf = Vec::new();
+        let mut closed = false;
+        let deadline = tokio::time::Instant::now() + wait;
+        while !complete(&buf) {
+            match tokio::time::timeout_at(deadline, socket.read_buf(&mut buf)).await {
+                Ok(Ok(0)) => {
+                    closed = true;
+                    break;
+                }
+                Ok(Ok(_)) => {}
+                Ok(Err(e)) => {
+                    *guard = None;
+                    return Err(PtzError::CameraOffline(format!("{:?}: {e}", self.endpoint)));
+                }
+                Err(_) => break,
+            }
+        }
+        if closed {
+            *guard = None;
+        }
+        Ok(buf)
+    }
+}
+
+/// Map a `0..=100` percentage speed onto a protocol's `1..=max` range. A
+/// missing speed is half speed: a full-speed default whips an analogue dome
+/// past its target.
+pub fn scale_speed(percent: Option<u8>, max: u8) -> u8 {
+    let percent = u32::from(percent.unwrap_or(50).min(100));
+    (1 + percent * (u32::from(max) - 1) / 100) as u8
+}
+
+#[cfg(test)]
+pub(crate) mod tests {
+    use super::*;
+    use tokio::net::TcpListener;
+
+    pub(crate) fn config(address: &str, device: Option<&str>) -> PtzConnectionConfig {
+        PtzConnectionConfig {
+            monitor_id: 3,
+            address: address.to_string(),
+            device: device.map(str::to_string),
+            username: None,
+            password: None,
+            protocol: "pelcod".to_string(),
+            auto_stop_timeout: None,
+        }
+    }
+
+    /// A serial bridge that records every byte it receives, answering each
+    /// read with `reply` when given.
+    pub(crate) async fn stub_bridge(
+        reply: Option<Vec<u8>>,
+    ) -> (String, tokio::sync::mpsc::UnboundedReceiver<Vec<u8>>) {
+        let listener = TcpListener::bind("127.0.0.1:0").await.unwrap();
+        let addr = listener.local_addr().unwrap().to_string();
+        let (tx, rx) = tokio::sync::mpsc::unbounded_channel();
+        tokio::spawn(async move {
+            let (mut socket, _) = listener.accept().await.unwrap();
+            let mut buf = [0u8; 256];
+            loop {
+                match socket.read(&mut buf).await {
+                    Ok(0) | Err(_) => break,
+                    Ok(n) => {
+                        let _ = tx.send(buf[..n].to_vec());
+                        if let Some(reply) = &reply {
+                            let _ = socket.write_all(reply).await;
+                        }
+                    }
+                }
+            }
+        });
+        (addr, rx)
+    }
+
+    /// Collect `len` bytes from a stub bridge.
+    pub(crate) async fn received(
+        rx: &mut tokio::sync::mpsc::UnboundedReceiver<Vec<u8>>,
+        len: usize,
+    ) -> Vec<u8> {
+        let mut out = Vec::new();
+        while out.len() < len {
+            let chunk = tokio::time::timeout(Duration::from_secs(2), rx.recv())
+                .await
+                .expect("bytes from the driver")
+                .expect("bridge open");
+            out.extend_from_slice(&chunk);
+        }
+        out
+    }
+
+    #[test]
+    fn device_names_the_bus_and_address_the_camera() {
+        let (endpoint, address) = resolve(&config("5", Some("10.0.0.9:4001")), 255).unwrap();
+        assert_eq!(endpoint, Endpoint::Tcp("10.0.0.9:4001".to_string()));
+        assert_eq!(address, 5);
+
+        let (endpoint, _) = resolve(&config("2", Some("/dev/ttyUSB0")), 255).unwrap();
+        assert_eq!(endpoint, Endpoint::Device("/dev/ttyUSB0".to_string()));
+
+        let (_, address) = resolve(&config("/dev/ttyUSB0", Some("/dev/ttyUSB0")), 255).unwrap();
+        assert_eq!(address, 1);
+    }
+
+    #[test]
+    fn address_alone_may_name_a_bridge() {
+        let (endpoint, address) = resolve(&config("tcp://bridge:4001", None), 255).unwrap();
+        assert_eq!(endpoint, Endpoint::Tcp("bridge:4001".to_string()));
+        assert_eq!(address, 1);
+    }
+
+    #[test]
+    fn rejects_missing_endpoint_and_bad_address() {
+        assert!(resolve(&config("1", None), 255).is_err());
+        assert!(resolve(&config("0", Some("/dev/ttyS0")), 255).is_err());
+        assert!(resolve(&config("8", Some("/dev/ttyS0")), 7).is_err());
+    }
+
+    #[test]
+    fn speed_scales_into_the_protocol_range() {
+        assert_eq!(scale_speed(Some(0), 0x3F), 1);
+        assert_eq!(scale_speed(Some(100), 0x3F), 0x3F);
+        assert_eq!(scale_speed(Some(250), 0x18), 0x18);
+        assert_eq!(scale_speed(None, 0x3F), 32);
+    }
+
+    #[tokio::test]
+    async fn writes_frames_and_reports_an_unreachable_bridge() {
+        let (addr, mut rx) = stub_bridge(None).await;
+        let link = SerialLink::new(Endpoint::Tcp(addr));
+        link.send(&[vec![1, 2, 3]]).await.unwrap();
+        assert_eq!(received(&mut rx, 3).await, vec![1, 2, 3]);
+
+        let unreachable = SerialLink::new(Endpoint::Tcp("127.0.0.1:1".to_string()));
+        assert!(matches!(
+            unreachable.send(&[vec![0]]).await,
+            Err(PtzError::CameraOffline(_))
+        ));
+    }
+}
@@ -0,0 +1,466 @@
+//! Native Axis VAPIX PTZ driver (`Controls.Protocol` `AxisV2`).
+//!
+//! Every command is a `GET` of `/axis-cgi/com/ptz.cgi?camera={ch}&…`:
+//!
+//! | `PtzCommand` | VAPIX parameter |
+//! |---|---|
+//! | `Move*` | `continuouspantiltmove=pan,tilt` (speeds `-100..=100`) |
+//! | `Zoom*`, `Focus*`, `Iris*` | `continuouszoommove`, `continuousfocusmove`, `continuousirismove` |
+//! | `MoveStop` | `move=stop` |
+//! | `FocusAuto`, `IrisAuto` | `autofocus=on`, `autoiris=on` |
+//! | `MoveAbsolute` | `pan`, `tilt` (degrees), `zoom` (`1..=9999`) |
+//! | `MoveRelative` | `rpan`, `rtilt`, `rzoom` |
+//! | presets | `gotoserverpresetno` (with `speed` when given), `setserverpresetno`, `removeserverpresetno` |
+//! | `GotoHome` | `move=home` |
+//! | `Reboot` | `/axis-cgi/restart.cgi` |
+//!
+//! Aiming at a spot in the picture uses the camera's own `center` and
+//! `areazoom`, against a virtual image [`AIM_GRID`] pixels square.
+//!
+//! Position readback is `query=position`. Axis answers success with an empty
+//! body (`204`) and failures with an `Error:` line, often still as `200`.
+
+use async_trait::async_trait;
+use tracing::{debug, instrument};
+
+use super::http::{self, axis_range, speed_range, CameraHttp};
+use crate::ptz::aim::{AimTarget, PositionUnits, ZoomUnits};
+use crate::ptz::capabilities::PtzCapabilities;
+use crate::ptz::error::{PtzError, PtzResult};
+use crate::ptz::traits::{
+    AbsolutePosition, PtzCommand, PtzCommandResult, PtzConnectionConfig, PtzControl,
+    PtzControlFactory, RelativePosition,
+};
+
+pub const PROTOCOL: &str = "AxisV2";
+/// VAPIX continuous speeds are `-100..=100`.
+const MAX_SPEED: u32 = 100;
+const MAX_ZOOM: f64 = 9999.0;
+/// Server-side presets the driver addresses by number.
+const MAX_PRESETS: u8 = 100;
+/// Width and height of the image `center`/`areazoom` coordinates refer to;
+/// any size works as long as it matches `imagewidth`/`imageheight`.
+const AIM_GRID: f64 = 10000.0;
+
+/// Native Axis controller for one video channel.
+pub struct VapixControl {
+    capabilities: PtzCapabilities,
+    /// The camera, or why it could not be resolved from the monitor's settings.
+    http: Result<CameraHttp, String>,
+    channel: u32,
+    monitor_id: u32,
+}
+
+impl VapixControl {
+    pub fn new(config: PtzConnectionConfig, capabilities: PtzCapabilities) -> Self {
+        Self {
+            capabilities: vapix_capabilities(capabilities),
+            http: CameraHttp::new(&config).map_err(|e| e.to_string()),
+            channel: http::channel(&config),
+            monitor_id: config.monitor_id,
+        }
+    }
+
+    fn http(&self) -> PtzResult<&CameraHttp> {
+        self.http
+            .as_ref()
+            .map_err(|e| PtzError::InvalidParameter(e.clone()))
+    }
+
+    /// `ptz.cgi?camera={ch}&{query}`, returning the reply body.
+    async fn ptz(&self, query: &str) -> PtzResult<String> {
+        let path = format!("axis-cgi/com/ptz.cgi?camera={}&{query}", self.channel);
+        let body = self.http()?.get(&path).await?;
+        match body
+            .lines()
+            .map(str::trim)
+            .find(|l| l.to_ascii_lowercase().starts_with("error"))
+        {
+            Some(error) => Err(PtzError::ProtocolError(error.to_string())),
+            None => Ok(body),
+        }
+    }
+
+    async fn absolute(&self, target: &AbsolutePosition) -> PtzResult<()> {
+        let mut params = Vec::new();
+        if let Some(pan) = target.pan {
+            // VAPIX pans `-180..=180`.
+            params.push(format!("pan={}", (pan + 180.0).rem_euclid(360.0) - 180.0));
+        }
+        if let Some(tilt) = target.tilt {
+            params.push(format!("tilt={tilt}"));
+        }
+        if let Some(zoom) = target.zoom {
+            params.push(format!("zoom={}", zoom.clamp(1.0, MAX_ZOOM).round()));
+        }
+        if params.is_empty() {
+            return Err(PtzError::InvalidParameter(
+                "MoveAbsolute requires at least one of pan/tilt/zoom".to_string(),
+            ));
+        }
+        self.ptz(&params.join("&")).await.map(drop)
+    }
+
+    async fn relative(&self, delta: &RelativePosition) -> PtzResult<()> {
+        let params: Vec<String> = [
+            ("rpan", delta.pan_delta),
+            ("rtilt", delta.tilt_delta),
+            ("rzoom", delta.zoom_delta.map(f64::round)),
+        ]
+        .into_iter()
+        .filter_map(|(name, value)| Some(format!("{name}={}", value?)))
+        .collect();
+        if params.is_empty() {
+            return Err(PtzError::InvalidParameter(
+                "MoveRelative requires at least one of pan/tilt/zoom delta".to_string(),
+            ));
+        }
+        self.ptz(&params.join("&")).await.map(drop)
+    }
+}
+
+/// The driver's view of an Axis PTZ: everything VAPIX offers, with the
+/// `Controls` row's ranges where it sets them.
+fn vapix_capabilities(mut caps: PtzCapabilities) -> PtzCapabilities {
+    let pt = &mut caps.pan_tilt;
+    pt.can_pan = true;
+    pt.can_tilt = true;
+    pt.can_move = true;
+    pt.can_move_diag = true;
+    pt.can_move_con = true;
+    pt.can_move_abs = true;
+    pt.can_move_rel = true;
+    pt.pan_speed = speed_range(&pt.pan_speed, 1, MAX_SPEED as i32);
+    pt.tilt_speed = speed_range(&pt.tilt_speed, 1, MAX_SPEED as i32);
+    pt.pan_range = axis_range(&pt.pan_range, -180, 180);
+    pt.tilt_range = axis_range(&pt.tilt_range, -90, 90);
+
+    caps.zoom.can = true;
+    caps.zoom.can_con = true;
+    caps.zoom.can_abs = true;
+    caps.zoom.can_rel = true;
+    caps.zoom.range = axis_range(&caps.zoom.range, 1, MAX_ZOOM as i32);
+    caps.zoom.speed = speed_range(&caps.zoom.speed, 1, MAX_SPEED as i32);
+    caps.focus.can = true;
+    caps.focus.can_con = true;
+    caps.focus.can_auto = true;
+    caps.focus.speed = speed_range(&caps.focus.speed, 1, MAX_SPEED as i32);
+    caps.iris.can = true;
+    caps.iris.can_con = true;
+    caps.iris.can_auto = true;
+
+    caps.presets.has_presets = true;
+    caps.presets.can_set_presets = true;
+    caps.presets.has_home_preset = true;
+    if caps.presets.num_presets == 0 {
+        caps.presets.num_presets = MAX_PRESETS;
+    }
+    caps.power.can_reboot = true;
+    caps
+}
+
+fn preset(preset_id: u32) -> PtzResult<u32> {
+    if (1..=u32::from(MAX_PRESETS)).contains(&preset_id) {
+        Ok(preset_id)
+    } else {
+        Err(PtzError::InvalidParameter(format!(
+            "preset {preset_id} is not in 1..={MAX_PRESETS}"
+        )))
+    }
+}
+
+/// A signed VAPIX speed for a `0..=100` percentage and a direction.
+fn speed(percent: Option<u8>, direction: i32) -> i32 {
+    direction * http::scale_speed(percent, MAX_SPEED) as i32
+}
+
+/// The `center=` or `areazoom=` query that aims at `target`.
+fn aim_query(target: &AimTarget) -> String {
+    let grid = |v: f64| (v.clamp(0.0, 1.0) * AIM_GRID).round();
+    let point = target.center();
+    let (x, y) = (grid(point.x), grid(point.y));
+    let aim = match target {
+        AimTarget::Point(_) => format!("center={x},{y}"),
+        // `areazoom` zooms by z/100: 200 brings the area twice as close.
+        AimTarget::Area(area) => format!(
+            "areazoom={x},{y},{}",
+            (area.zoom_factor() * 100.0).round().min(MAX_ZOOM)
+        ),
+    };
+    format!("{aim}&imagewidth={AIM_GRID}&imageheight={AIM_GRID}")
+}
+
+/// The `pan=`/`tilt=`/`zoom=` lines of a `query=position` reply.
+fn parse_position(body: &str) -> Option<AbsolutePosition> {
+    let mut position = AbsolutePosition::default();
+    for line in body.lines() {
+        let Some((key, value)) = line.split_once('=') else {
+            continue;
+        };
+        let value = value.trim().parse::<f64>().ok();
+        match key.trim() {
+            "pan" => position.pan = value,
+            "tilt" => position.tilt = value,
+            "zoom" => position.zoom = value,
+            _ => {}
+        }
+    }
+    (position.pan.is_some() || position.tilt.is_some() || position.zoom.is_some())
+        .then_some(position)
+}
+
+#[async_trait]
+impl PtzControl for VapixControl {
+    fn capabilities(&self) -> &PtzCapabilities {
+        &self.capabilities
+    }
+
+    fn protocol_name(&self) -> &str {
+        PROTOCOL
+    }
+
+    fn is_native(&self) -> bool {
+        true
+    }
+
+    #[instrument(skip(self), fields(monitor_id = self.monitor_id, protocol = "vapix", command = ?command))]
+    async fn execute(&self, command: PtzCommand) -> PtzResult<PtzCommandResult> {
+        debug!("dispatching VAPIX PTZ command");
+        let query = match &command {
+            PtzCommand::MoveUp(p) => format!("continuouspantiltmove=0,{}", speed(p.tilt_speed, 1)),
+            PtzCommand::MoveDown(p) => {
+                format!("continuouspantiltmove=0,{}", speed(p.tilt_speed, -1))
+            }
+            PtzCommand::MoveLeft(p) => {
+                format!("continuouspantiltmove={},0", speed(p.pan_speed, -1))
+            }
+            PtzCommand::MoveRight(p) => {
+                format!("continuouspantiltmove={},0", speed(p.pan_speed, 1))
+            }
+            PtzCommand::MoveUpLeft(p) => format!(
+                "continuouspantiltmove={},{}",
+                speed(p.pan_speed, -1),
+                speed(p.tilt_speed, 1)
+            ),
+            PtzCommand::MoveUpRight(p) => format!(
+                "continuouspantiltmove={},{}",
+                speed(p.pan_speed, 1),
+                speed(p.tilt_speed, 1)
+            ),
+            PtzCommand::MoveDownLeft(p) => format!(
+                "continuouspantiltmove={},{}",
+                speed(p.pan_speed, -1),
+                speed(p.tilt_speed, -1)
+            ),
+            PtzCommand::MoveDownRight(p) => format!(
+                "continuouspantiltmove={},{}",
+                speed(p.pan_speed, 1),
+                speed(p.tilt_speed, -1)
+            ),
+            PtzCommand::MoveStop => "move=stop".to_string(),
+
+            PtzCommand::ZoomIn(p) => format!("continuouszoommove={}", speed(p.speed, 1)),
+            PtzCommand::ZoomOut(p) => format!("continuouszoommove={}", speed(p.speed, -1)),
+            PtzCommand::ZoomStop => "continuouszoommove=0".to_string(),
+            PtzCommand::FocusFar(p) => format!("continuousfocusmove={}", speed(p.speed, 1)),
+            PtzCommand::FocusNear(p) => format!("continuousfocusmove={}", speed(p.speed, -1)),
+            PtzCommand::FocusStop => "continuousfocusmove=0".to_string(),
+            PtzCommand::FocusAuto => "autofocus=on".to_string(),
+            PtzCommand::IrisOpen => format!("continuousirismove={}", speed(None, 1)),
+            PtzCommand::IrisClose => format!("continuousirismove={}", speed(None, -1)),
+            PtzCommand::IrisStop => "continuousirismove=0".to_string(),
+            PtzCommand::IrisAuto => "autoiris=on".to_string(),
+
+            PtzCommand::GotoPreset { preset_id, speed } => {
+                let preset = preset(*preset_id)?;
+                match speed {
+                    Some(percent) => format!(
+                        "gotoserverpresetno={preset}&speed={}",
+                        http::scale_speed(Some(*percent), MAX_SPEED)
+                    ),
+                    None => format!("gotoserverpresetno={preset}"),
+                }
+            }
+            PtzCommand::SetPreset { preset_id, .. } => {
+                format!("setserverpresetno={}", preset(*preset_id)?)
+            }
+            PtzCommand::ClearPreset { preset_id } => {
+                format!("removeserverpresetno={}", preset(*preset_id)?)
+            }
+            PtzCommand::GotoHome => "move=home".to_string(),
+
+            PtzCommand::MoveAbsolute(position) => {
+                self.absolute(position).await?;
+                String::new()
+            }
+            PtzCommand::MoveRelative(delta) => {
+                self.relative(delta).await?;
+                String::new()
+            }
+            PtzCommand::Reboot => {
+                self.http()?.get("axis-cgi/restart.cgi").await?;
+                String::new()
+            }
+            other => {
+                return Err(PtzError::CommandNotSupported(format!(
+                    "VAPIX driver does not support {}",
+                    other.zmcontrol_command()
+                )))
+            }
+        };
+        if !query.is_empty() {
+            self.ptz(&query).await?;
+        }
+        Ok(PtzCommandResult::success(format!(
+            "VAPIX {} sent",
+            command.zmcontrol_command()
+        )))
+    }
+
+    async fn get_position(&self) -> PtzResult<Option<AbsolutePosition>> {
+        let body = self.ptz("query=position").await?;
+        Ok(parse_position(&body))
+    }
+
+    fn position_units(&self) -> Option<PositionUnits> {
+        Some(PositionUnits {
+            pan_per_degree: 1.0,
+            tilt_per_degree: 1.0,
+            zoom: ZoomUnits::Linear {
+                wide: 1.0,
+                tele: MAX_ZOOM,
+            },
+        })
+    }
+
+    async fn aim(&self, target: &AimTarget) -> PtzResult<Option<PtzCommandResult>> {
+        self.ptz(&aim_query(target)).await?;
+        Ok(Some(PtzCommandResult::success("VAPIX aim sent")))
+    }
+}
+
+/// Factory for [`VapixControl`].
+#[derive(Debug, Default)]
+pub struct VapixControlFactory;
+
+impl VapixControlFactory {
+    pub fn new() -> Self {
+        Self
+    }
+}
+
+impl PtzControlFactory for VapixControlFactory {
+    fn protocol_name(&self) -> &str {
+        PROTOCOL
+    }
+
+    fn is_native(&self) -> bool {
+        true
+    }
+
+    fn create(
+        &self,
+        config: PtzConnectionConfig,
+        capabilities: PtzCapabilities,
+    ) -> Box<dyn PtzControl> {
+        Box::new(VapixControl::new(config, capabilities))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::ptz::aim::{FrameArea, FramePoint};
+    use crate::ptz::protocols::http::tests::{config, next_request, stub_camera};
+    use crate::ptz::traits::ZoomParams;
+
+    #[test]
+    fn parses_a_position_query() {
+        let position = parse_position("pan=-12.5\r\ntilt=3\r\nzoom=1200\r\niris=500\r\n").unwrap();
+        assert_eq!(position.pan, Some(-12.5));
+        assert_eq!(position.tilt, Some(3.0));
+        assert_eq!(position.zoom, Some(1200.0));
+        assert!(parse_position("").is_none());
+    }
+
+    #[tokio::test]
+    async fn zoom_and_absolute_moves_build_queries() {
+        let (addr, mut rx) = stub_camera(vec![String::new(); 3]).await;
+        let ctrl = VapixControlFactory::new().create(config(&addr, None), Default::default());
+        ctrl.execute(PtzCommand::ZoomOut(ZoomParams {
+            speed: Some(50),
+            duration_ms: None,
+        }))
+        .await
+        .unwrap();
+        assert!(next_request(&mut rx)
+            .await
+            .starts_with("GET /axis-cgi/com/ptz.cgi?camera=1&continuouszoommove=-50 "));
+
+        ctrl.execute(PtzCommand::MoveAbsolute(AbsolutePosition {
+            pan: Some(270.0),
+            tilt: None,
+            zoom: Some(20000.0),
+        }))
+        .await
+        .unwrap();
+        assert!(next_request(&mut rx)
+            .await
+            .starts_with("GET /axis-cgi/com/ptz.cgi?camera=1&pan=-90&zoom=9999 "));
+        ctrl.execute(PtzCommand::GotoPreset {
+            preset_id: 4,
+            speed: Some(30),
+        })
+        .await
+        .unwrap();
+        assert!(next_request(&mut rx)
+            .await
+            .starts_with("GET /axis-cgi/com/ptz.cgi?camera=1&gotoserverpresetno=4&speed=30 "));
+    }
+
+    #[tokio::test]
+    async fn reads_back_the_position_and_reports_errors() {
+        let (addr, _rx) = stub_camera(vec![
+            "pan=10\ntilt=-5\nzoom=1\n".into(),
+            "Error: preset not found\n".into(),
+        ])
+        .await;
+        let ctrl = VapixControl::new(config(&addr, None), Default::default());
+        let position = ctrl.get_position().await.unwrap().unwrap();
+        assert_eq!(position.pan, Some(10.0));
+
+        assert!(matches!(
+            ctrl.execute(PtzCommand::GotoPreset {
+            preset_id: 9,
+            speed: None,
+        }).await,
+            Err(PtzError::ProtocolError(msg)) if msg.contains("preset not found")
+        ));
+    }
+
+    #[tokio::test]
+    async fn aims_with_center_and_areazoom() {
+        let (addr, mut rx) = stub_camera(vec![String::new(); 2]).await;
+        let ctrl = VapixControl::new(config(&addr, None), Default::default());
+        ctrl.aim(&AimTarget::Point(FramePoint { x: 0.25, y: 0.5 }))
+            .await
+            .unwrap()
+            .unwrap();
+        assert!(next_request(&mut rx).await.starts_with(
+            "GET /axis-cgi/com/ptz.cgi?camera=1&center=2500,5000&imagewidth=10000&imageheight=10000 "
+        ));
+
+        ctrl.aim(&AimTarget::Area(FrameArea {
+            x: 0.5,
+            y: 0.0,
+            width: 0.5,
+            height: 0.25,
+        }))
+        .await
+        .unwrap()
+        .unwrap();
+        assert!(next_request(&mut rx).await.starts_with(
+            "GET /axis-cgi/com/ptz.cgi?camera=1&areazoom=7500,1250,200&imagewidth=10000&imageheight=10000 "
+        ));
+    }
+}
@@ -0,0 +1,481 @@
+//! Native Sony VISCA PTZ driver.
+//!
+//! VISCA packets are `8x … FF`: a header byte carrying the camera address
+//! (`0x80 | 1..=7`), the command, and an `FF` terminator. Cameras answer each
+//! command with an ACK (`y0 4z FF`) and a completion (`y0 5z FF`), or an error
+//! (`y0 6z ee FF`). Over a TCP serial bridge the driver waits briefly for the
+//! ACK so a rejected command surfaces as an error; bridges and ports that
+//! echo nothing back are fine, the command is then assumed accepted.
+//!
+//! Positions are in the camera's own units: pan and tilt as signed 16-bit
+//! values, zoom `0x0000..=0x4000` (optical range). Presets map ZoneMinder's
+//! 1-based ids onto VISCA memories `0..=127`. A preset recall with a speed
+//! first sets that memory's travel speed (`01 7E 01 0B pp ss`, Sony's
+//! preset-speed command), so cameras without it refuse only such recalls.
+
+use std::time::Duration;
+
+use async_trait::async_trait;
+use tracing::{debug, instrument};
+
+use super::serial::{self, scale_speed, SerialLink};
+use crate::ptz::capabilities::PtzCapabilities;
+use crate::ptz::error::{PtzError, PtzResult};
+use crate::ptz::traits::{
+    AbsolutePosition, MoveParams, PtzCommand, PtzCommandResult, PtzConnectionConfig, PtzControl,
+    PtzControlFactory, RelativePosition,
+};
+
+/// Highest VISCA camera address on one daisy chain.
+const MAX_ADDRESS: u8 = 7;
+const MAX_PAN_SPEED: u8 = 0x18;
+const MAX_TILT_SPEED: u8 = 0x14;
+/// Variable zoom / focus speed range `0..=7`.
+const MAX_LENS_SPEED: u8 = 7;
+/// How long to wait for the camera's ACK before assuming it was accepted.
+const ACK_WAIT: Duration = Duration::from_millis(300);
+
+/// Pan/tilt drive directions (`VV WW XX YY`: XX pan, YY tilt).
+const PAN_LEFT: u8 = 0x01;
+const PAN_RIGHT: u8 = 0x02;
+const TILT_UP: u8 = 0x01;
+const TILT_DOWN: u8 = 0x02;
+const AXIS_STOP: u8 = 0x03;
+
+/// Frame a VISCA command for camera `address` (1..=7).
+pub fn visca_packet(address: u8, body: &[u8]) -> Vec<u8> {
+    let mut packet = Vec::with_capacity(body.len() + 2);
+    packet.push(0x80 | (address & 0x07));
+    packet.extend_from_slice(body);
+    packet.push(0xFF);
+    packet
+}
+
+/// A 16-bit value as the four low nibbles VISCA positions use.
+fn nibbles(value: u16) -> [u8; 4] {
+    [
+        ((value >> 12) & 0x0F) as u8,
+        ((value >> 8) & 0x0F) as u8,
+        ((value >> 4) & 0x0F) as u8,
+        (value & 0x0F) as u8,
+    ]
+}
+
+/// Whether a reply holds a complete ACK, completion or error message.
+fn reply_complete(buf: &[u8]) -> bool {
+    buf.split(|b| *b == 0xFF)
+        .filter(|m| m.len() >= 2)
+        .any(|m| matches!(m[1] & 0xF0, 0x40 | 0x50 | 0x60))
+}
+
+/// The error in a reply, if the camera refused the command.
+fn reply_error(buf: &[u8]) -> Option<PtzError> {
+    let message = buf
+        .split(|b| *b == 0xFF)
+        .find(|m| m.len() >= 3 && m[1] & 0xF0 == 0x60)?;
+    let reason = match message[2] {
+        0x01 => "message length error",
+        0x02 => "syntax error",
+        0x03 => "command buffer full",
+        0x04 => "command canceled",
+        0x05 => "no socket",
+        0x41 => "command not executable",
+        _ => "unknown error",
+    };
+    Some(PtzError::ProtocolError(format!(
+        "VISCA {reason} (0x{:02X})",
+        message[2]
+    )))
+}
+
+/// Native VISCA controller for one camera on a chain.
+pub struct ViscaControl {
+    capabilities: PtzCapabilities,
+    /// The chain, or why it could not be resolved from the monitor's settings.
+    link: Result<(SerialLink, u8), String>,
+    monitor_id: u32,
+}
+
+impl ViscaControl {
+    pub fn new(config: PtzConnectionConfig, capabilities: PtzCapabilities) -> Self {
+        let link = serial::resolve(&config, MAX_ADDRESS)
+            .map(|(endpoint, address)| (SerialLink::new(endpoint), address))
+            .map_err(|e| e.to_string());
+        Self {
+            capabilities,
+            link,
+            monitor_id: config.monitor_id,
+        }
+    }
+
+    /// The command bodies (without header and terminator) a command becomes.
+    fn bodies(command: &PtzCommand) -> PtzResult<Vec<Vec<u8>>> {
+        let body = match command {
+            PtzCommand::MoveUp(p) => drive(p, AXIS_STOP, TILT_UP),
+            PtzCommand::MoveDown(p) => drive(p, AXIS_STOP, TILT_DOWN),
+            PtzCommand::MoveLeft(p) => drive(p, PAN_LEFT, AXIS_STOP),
+            PtzCommand::MoveRight(p) => drive(p, PAN_RIGHT, AXIS_STOP),
+            PtzCommand::MoveUpLeft(p) => drive(p, PAN_LEFT, TILT_UP),
+            PtzCommand::MoveUpRight(p) => drive(p, PAN_RIGHT, TILT_UP),
+            PtzCommand::MoveDownLeft(p) => drive(p, PAN_LEFT, TILT_DOWN),
+            PtzCommand::MoveDownRight(p) => drive(p, PAN_RIGHT, TILT_DOWN),
+            PtzCommand::MoveStop => {
+                return Ok(vec![
+                    vec![0x01, 0x06, 0x01, 0x01, 0x01, AXIS_STOP, AXIS_STOP],
+                    vec![0x01, 0x04, 0x07, 0x00],
+                ])
+            }
+
+            PtzCommand::ZoomIn(p) => vec![0x01, 0x04, 0x07, 0x20 | lens_speed(p.speed)],
+            PtzCommand::ZoomOut(p) => vec![0x01, 0x04, 0x07, 0x30 | lens_speed(p.speed)],
+            PtzCommand::ZoomStop => vec![0x01, 0x04, 0x07, 0x00],
+            PtzCommand::FocusFar(p) => vec![0x01, 0x04, 0x08, 0x20 | lens_speed(p.speed)],
+            PtzCommand::FocusNear(p) => vec![0x01, 0x04, 0x08, 0x30 | lens_speed(p.speed)],
+            PtzCommand::FocusStop => vec![0x01, 0x04, 0x08, 0x00],
+            PtzCommand::FocusAuto => vec![0x01, 0x04, 0x38, 0x02],
+            // Iris steps one notch per command; there is nothing to stop.
+            PtzCommand::IrisOpen => vec![0x01, 0x04, 0x0B, 0x02],
+            PtzCommand::IrisClose => vec![0x01, 0x04, 0x0B, 0x03],
+            PtzCommand::IrisStop => return Ok(Vec::new()),
+            // Full-auto exposure drives the iris.
+            PtzCommand::IrisAuto => vec![0x01, 0x04, 0x39, 0x00],
+
+            PtzCommand::GotoPreset { preset_id, speed } => {
+                let memory = memory(*preset_id)?;
+                let recall = vec![0x01, 0x04, 0x3F, 0x02, memory];
+                return Ok(match speed {
+                    Some(percent) => vec![
+                        vec![
+                            0x01,
+                            0x7E,
+                            0x01,
+                            0x0B,
+                            memory,
+                            scale_speed(Some(*percent), MAX_PAN_SPEED),
+                        ],
+                        recall,
+                    ],
+                    None => vec![recall],
+                });
+            }
+            PtzCommand::SetPreset { preset_id, .. } => {
+                vec![0x01, 0x04, 0x3F, 0x01, memory(*preset_id)?]
+            }
+            PtzCommand::ClearPreset { preset_id } => {
+                vec![0x01, 0x04, 0x3F, 0x00, memory(*preset_id)?]
+            }
+            PtzCommand::GotoHome => vec![0x01, 0x06, 0x04],
+
+            PtzCommand::MoveAbsolute(position) => return absolute(position),
+            PtzCommand::MoveRelative(delta) => return relative(delta),
+
+            PtzCommand::Wake => vec![0x01, 0x04, 0x00, 0x02],
+            PtzCommand::Sleep => vec![0x01, 0x04, 0x00, 0x03],
+            // Pan/tilt reset: recalibrates the head and returns it home.
+            PtzCommand::Reset => vec![0x01, 0x06, 0x05],
+            PtzCommand::Reboot => {
+                return Err(PtzError::CommandNotSupported(
+                    "VISCA has no reboot command".to_string(),
+                ))
+            }
+        };
+        Ok(vec![body])
+    }
+}
+
+/// `Pan-tiltDrive`: `01 06 01 VV WW XX YY`.
+fn drive(params: &MoveParams, pan: u8, tilt: u8) -> Vec<u8> {
+    vec![
+        0x01,
+        0x06,
+        0x01,
+        scale_speed(params.pan_speed, MAX_PAN_SPEED),
+        scale_speed(params.tilt_speed.or(params.pan_speed), MAX_TILT_SPEED),
+        pan,
+        tilt,
+    ]
+}
+
+/// Variable zoom/focus speed `0..=7`; half speed when unspecified.
+fn lens_speed(percent: Option<u8>) -> u8 {
+    scale_speed(percent, MAX_LENS_SPEED + 1) - 1
+}
+
+fn memory(preset_id: u32) -> PtzResult<u8> {
+    preset_id
+        .checked_sub(1)
+        .and_then(|m| u8::try_from(m).ok())
+        .filter(|m| *m <= 0x7F)
+        .ok_or_else(|| PtzError::InvalidParameter(format!("preset {preset_id} is not in 1..=128")))
+}
+
+/// A signed camera-unit pan or tilt value.
+fn axis(value: f64, name: &str) -> PtzResult<u16> {
+    if !(f64::from(i16::MIN)..=f64::from(i16::MAX)).contains(&value) {
+        return Err(PtzError::InvalidParameter(format!(
+            "{name} {value} is outside the camera's 16-bit range"
+        )));
+    }
+    Ok(value.round() as i16 as u16)
+}
+
+/// `AbsolutePosition` (`01 06 02 VV WW 0Y0Y0Y0Y 0Z0Z0Z0Z`) and/or
+/// `ZoomDirect` (`01 04 47 0p0q0r0s`). VISCA moves pan and tilt together, so
+/// both are needed for a pan/tilt move.
+fn absolute(position: &AbsolutePosition) -> PtzResult<Vec<Vec<u8>>> {
+    let mut bodies = Vec::new();
+    match (position.pan, position.tilt) {
+        (Some(pan), Some(tilt)) => {
+            let mut body = vec![0x01, 0x06, 0x02, MAX_PAN_SPEED, MAX_TILT_SPEED];
+            body.extend_from_slice(&nibbles(axis(pan, "pan")?));
+            body.extend_from_slice(&nibbles(axis(tilt, "tilt")?));
+            bodies.push(body);
+        }
+        (None, None) => {}
+        _ => {
+            return Err(PtzError::InvalidParameter(
+                "VISCA absolute moves need both pan and tilt".to_string(),
+            ))
+        }
+    }
+    if let Some(zoom) = position.zoom {
+        if !(0.0..=f64::from(u16::MAX)).contains(&zoom) {
+            return Err(PtzError::InvalidParameter(format!(
+                "zoom {zoom} is not a VISCA zoom position"
+            )));
+        }
+        let mut body = vec![0x01, 0x04, 0x47];
+        body.extend_from_slice(&nibbles(zoom.round() as u16));
+        bodies.push(body);
+    }
+    if bodies.is_empty() {
+        return Err(PtzError::InvalidParameter(
+            "MoveAbsolute requires at least one of pan/tilt/zoom".to_string(),
+        ));
+    }
+    Ok(bodies)
+}
+
+/// `RelativePosition` (`01 06 03 …`); a missing axis moves by zero. Zoom has
+/// no relative form.
+fn relative(delta: &RelativePosition) -> PtzResult<Vec<Vec<u8>>> {
+    if delta.zoom_delta.is_some() {
+        return Err(PtzError::CommandNotSupported(
+            "VISCA has no relative zoom".to_string(),
+        ));
+    }
+    if delta.pan_delta.is_none() && delta.tilt_delta.is_none() {
+        return Err(PtzError::InvalidParameter(
+            "MoveRelative requires a pan or tilt delta".to_string(),
+        ));
+    }
+    let mut body = vec![0x01, 0x06, 0x03, MAX_PAN_SPEED, MAX_TILT_SPEED];
+    body.extend_from_slice(&nibbles(axis(delta.pan_delta.unwrap_or(0.0), "pan")?));
+    body.extend_from_slice(&nibbles(axis(delta.tilt_delta.unwrap_or(0.0), "tilt")?));
+    Ok(vec![body])
+}
+
+#[async_trait]
+impl PtzControl for ViscaControl {
+    fn capabilities(&self) -> &PtzCapabilities {
+        &self.capabilities
+    }
+
+    fn protocol_name(&self) -> &str {
+        "Visca"
+    }
+
+    fn is_native(&self) -> bool {
+        true
+    }
+
+    #[instrument(skip(self), fields(monitor_id = self.monitor_id, protocol = "visca", command = ?command))]
+    async fn execute(&self, command: PtzCommand) -> PtzResult<PtzCommandResult> {
+        let (link, address) = self
+            .link
+            .as_ref()
+            .map_err(|e| PtzError::InvalidParameter(e.clone()))?;
+        // One packet at a time: the camera's two command sockets fill if a
+        // second arrives before the first is acknowledged.
+        for body in Self::bodies(&command)? {
+            let packet = visca_packet(*address, &body);
+            debug!("sending VISCA {:02X?} to {:?}", packet, link.endpoint());
+            let reply = link
+                .exchange(&[packet], Some((ACK_WAIT, reply_complete)))
+                .await?;
+            if let Some(error) = reply_error(&reply) {
+                return Err(error);
+            }
+        }
+        Ok(PtzCommandResult::success(format!(
+            "VISCA {} sent",
+            command.zmcontrol_command()
+        )))
+    }
+}
+
+/// Factory for [`ViscaControl`].
+#[derive(Debug, Default)]
+pub struct ViscaControlFactory;
+
+impl ViscaControlFactory {
+    pub fn new() -> Self {
+        Self
+    }
+}
+
+impl PtzControlFactory for ViscaControlFactory {
+    fn protocol_name(&self) -> &str {
+        "Visca"
+    }
+
+    fn is_native(&self) -> bool {
+        true
+    }
+
+    fn create(
+        &self,
+        config: PtzConnectionConfig,
+        capabilities: PtzCapabilities,
+    ) -> Box<dyn PtzControl> {
+        Box::new(ViscaControl::new(config, capabilities))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::ptz::protocols::serial::tests::{config, received, stub_bridge};
+
+    #[test]
+    fn packets_carry_the_address_and_terminator() {
+        assert_eq!(
+            visca_packet(1, &[0x01, 0x06, 0x04]),
+            vec![0x81, 0x01, 0x06, 0x04, 0xFF]
+        );
+        assert_eq!(visca_packet(7, &[])[0], 0x87);
+    }
+
+    #[test]
+    fn drive_and_lens_speeds_scale_into_range() {
+        let up_left = ViscaControl::bodies(&PtzCommand::MoveUpLeft(MoveParams {
+            pan_speed: Some(100),
+            tilt_speed: Some(0),
+            ..MoveParams::default()
+        }))
+        .unwrap();
+        assert_eq!(
+            up_left,
+            vec![vec![0x01, 0x06, 0x01, 0x18, 0x01, PAN_LEFT, TILT_UP]]
+        );
+        assert_eq!(lens_speed(Some(0)), 0);
+        assert_eq!(lens_speed(Some(100)), 7);
+    }
+
+    #[test]
+    fn absolute_positions_are_nibble_encoded() {
+        let bodies = absolute(&AbsolutePosition {
+            pan: Some(-1.0),
+            tilt: Some(0x0123 as f64),
+            zoom: Some(0x4000 as f64),
+        })
+        .unwrap();
+        assert_eq!(
+            bodies,
+            vec![
+                vec![0x01, 0x06, 0x02, 0x18, 0x14, 0x0F, 0x0F, 0x0F, 0x0F, 0x00, 0x01, 0x02, 0x03],
+                vec![0x01, 0x04, 0x47, 0x04, 0x00, 0x00, 0x00],
+            ]
+        );
+        assert!(absolute(&AbsolutePosition {
+            pan: Some(1.0),
+            ..Default::default()
+        })
+        .is_err());
+    }
+
+    #[test]
+    fn presets_are_zero_based_memories() {
+        assert_eq!(memory(1).unwrap(), 0);
+        assert_eq!(memory(128).unwrap(), 0x7F);
+        assert!(memory(0).is_err());
+        assert!(memory(129).is_err());
+    }
+
+    #[test]
+    fn a_preset_speed_is_set_before_the_recall() {
+        let bodies = ViscaControl::bodies(&PtzCommand::GotoPreset {
+            preset_id: 3,
+            speed: Some(100),
+        })
+        .unwrap();
+        assert_eq!(
+            bodies,
+            vec![
+                vec![0x01, 0x7E, 0x01, 0x0B, 0x02, MAX_PAN_SPEED],
+                vec![0x01, 0x04, 0x3F, 0x02, 0x02],
+            ]
+        );
+        // Without one the camera's own preset speed stands.
+        let bodies = ViscaControl::bodies(&PtzCommand::GotoPreset {
+            preset_id: 3,
+            speed: None,
+        })
+        .unwrap();
+        assert_eq!(bodies, vec![vec![0x01, 0x04, 0x3F, 0x02, 0x02]]);
+    }
+
+    #[test]
+    fn replies_are_recognised() {
+        assert!(!reply_complete(&[]));
+        assert!(reply_complete(&[0x90, 0x41, 0xFF]));
+        assert!(reply_error(&[0x90, 0x41, 0xFF, 0x90, 0x51, 0xFF]).is_none());
+        assert!(matches!(
+            reply_error(&[0x90, 0x60, 0x02, 0xFF]),
+            Some(PtzError::ProtocolError(msg)) if msg.contains("syntax error")
+        ));
+    }
+
+    #[tokio::test]
+    async fn sends_exact_packets_and_waits_for_the_ack() {
+        let (addr, mut rx) = stub_bridge(Some(vec![0x90, 0x41, 0xFF])).await;
+        let ctrl = ViscaControlFactory::new().create(config("2", Some(&addr)), Default::default());
+        assert_eq!(ctrl.protocol_name(), "Visca");
+
+        ctrl.execute(PtzCommand::GotoPreset {
+            preset_id: 3,
+            speed: None,
+        })
+        .await
+        .unwrap();
+        assert_eq!(
+            received(&mut rx, 7).await,
+            vec![0x82, 0x01, 0x04, 0x3F, 0x02, 0x02, 0xFF]
+        );
+
+        ctrl.execute(PtzCommand::MoveStop).await.unwrap();
+        assert_eq!(
+            received(&mut rx, 15).await,
+            vec![
+                0x82, 0x01, 0x06, 0x01, 0x01, 0x01, 0x03, 0x03, 0xFF, 0x82, 0x01, 0x04, 0x07, 0x00,
+                0xFF
+            ]
+        );
+    }
+
+    #[tokio::test]
+    async fn a_refused_command_is_an_error() {
+        let (addr, _rx) = stub_bridge(Some(vec![0x90, 0x60, 0x41, 0xFF])).await;
+        let ctrl = ViscaControl::new(config("1", Some(&addr)), Default::default());
+        assert!(matches!(
+            ctrl.execute(PtzCommand::Wake).await,
+            Err(PtzError::ProtocolError(_))
+        ));
+    }
+
+    #[test]
+    fn address_is_limited_to_the_chain() {
+        let ctrl = ViscaControl::new(config("8", Some("/dev/ttyS0")), Default::default());
+        assert!(ctrl.link.is_err());
+    }
+}
@@ -4,6 +4,7 @@ use async_trait::async_trait;
 use serde::{Deserialize, Serialize};
 use utoipa::ToSchema;
 
+use super::aim::{AimTarget, PositionUnits};
 use super::capabilities::PtzCapabilities;
 use super::error::PtzResult;
 
@@ -182,6 +183,10 @@ pub enum PtzCommand {
     // Preset commands
     GotoPreset {
         preset_id: u32,
+        /// Travel speed (0-100 percent) for drivers that take one; the
+        /// camera's own preset speed otherwise
+        #[serde(default)]
+        speed: Option<u8>,
     },
     SetPreset {
         preset_id: u32,
@@ -250,6 +255,11 @@ pub struct PtzConnectionConfig {
     /// Control address (host:port, URL, or device path)
     pub address: String,
 
+    /// Control device (`ControlDevice`): the serial port or serial-to-IP
+    /// bridge that serial protocols talk through, whose `address` is then the
+    /// camera's bus address
+    pub device: Option<String>,
+
     /// Username for authentication
     pub username: Option<String>,
 
@@ -319,6 +329,20 @@ pub trait PtzControl: Send + Sync {
     async fn stop_all(&self) -> PtzResult<PtzCommandResult> {
         self.execute(PtzCommand::MoveStop).await
     }
+
+    /// How this driver's positions relate to angles and magnification, so
+    /// a spot in the picture can be turned into a `MoveRelative`; `None`
+    /// when it cannot say
+    fn position_units(&self) -> Option<PositionUnits> {
+        None
+    }
+
+    /// Aim at a spot in the picture with the camera's own click-to-center or
+    /// area zoom. `Ok(None)` when it has none, and the caller works the move
+    /// out from the field of view instead.
+    async fn aim(&self, _target: &AimTarget) -> PtzResult<Option<PtzCommandResult>> {
+        Ok(None)
+    }
 }
 
 /// Factory trait for creating PTZ control instances
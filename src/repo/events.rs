@@ -1,6 +1,6 @@
 use std::collections::HashMap;
 
-use sea_orm::sea_query::{Alias, MysqlQueryBuilder, Order};
+use sea_orm::sea_query::{Alias, Expr, MysqlQueryBuilder, Order};
 use sea_orm::*;
 use tracing::instrument;
 
@@ -62,6 +62,81 @@ fn apply_sorting<E: EntityTrait>(
     }
 }
 
+/// Tombstoned (soft-deleted) events are hidden from every read in this module
+/// except the `*_trashed_*` helpers, which exist for the restore path and the
+/// trash reaper.
+fn live() -> sea_orm::sea_query::SimpleExpr {
+    events::Column::DeletedAt.is_null()
+}
+
+/// Events stored on `storage_id`. The default (lowest-id) storage also owns
+/// the events whose `StorageId` is the `0` / NULL "default storage" sentinel.
+pub fn on_storage(storage_id: u16, is_default: bool) -> Condition {
+    let mut cond = Condition::any().add(events::Column::StorageId.eq(storage_id));
+    if is_default {
+        cond = cond
+            .add(events::Column::StorageId.is_null())
+            .add(events::Column::StorageId.eq(0));
+    }
+    cond
+}
+
+/// One monitor's share of a storage, from [`usage_by_monitor`].
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct MonitorUsage {
+    pub monitor_id: u32,
+    pub events: u64,
+    /// Sum of `DiskSpace`, archived events included.
+    pub bytes: u64,
+    pub archived_bytes: u64,
+    pub oldest_start: Option<sea_orm::prelude::DateTime>,
+}
+
+/// Per-monitor event count, bytes and oldest start on one storage, over live
+/// events, ordered by monitor id.
+pub async fn usage_by_monitor(
+    db: &DatabaseConnection,
+    storage_id: u16,
+    is_default: bool,
+) -> Result<Vec<MonitorUsage>, DbErr> {
+    use sea_orm::sea_query::Query;
+
+    // SUM over an integer column is DECIMAL in MySQL; cast so it decodes as
+    // u64.
+    let mut select = Query::select();
+    select
+        .expr(Expr::col(events::Column::MonitorId))
+        .expr(Expr::cust("COUNT(*)"))
+        .expr(Expr::cust("CAST(COALESCE(SUM(DiskSpace), 0) AS UNSIGNED)"))
+        .expr(Expr::cust(
+            "CAST(COALESCE(SUM(CASE WHEN Archived = 1 THEN DiskSpace ELSE 0 END), 0) AS UNSIGNED)",
+        ))
+        .expr(Expr::cust("MIN(StartDateTime)"))
+        .from(events::Entity)
+        .cond_where(on_storage(storage_id, is_default))
+        .and_where(live())
+        .group_by_col(events::Column::MonitorId)
+        .order_by(events::Column::MonitorId, Order::Asc);
+    let sql = select.to_string(MysqlQueryBuilder);
+    let stmt = Statement::from_sql_and_values(db.get_database_backend(), sql, vec![]);
+
+    // Decode by index, as `get_counts_by_monitor` does: MariaDB does not
+    // report SELECT aliases reliably.
+    let rows = db.query_all(stmt).await?;
+    let mut usage = Vec::with_capacity(rows.len());
+    for row in rows {
+        let events: i64 = row.try_get_by_index(1)?;
+        usage.push(MonitorUsage {
+            monitor_id: row.try_get_by_index(0)?,
+            events: events as u64,
+            bytes: row.try_get_by_index(2)?,
+            archived_bytes: row.try_get_by_index(3)?,
+            oldest_start: row.try_get_by_index(4)?,
+        });
+    }
+    Ok(usage)
+}
+
 /// Find all events with pagination and filtering/sorting options
 #[instrument(skip(state))]
 pub async fn find_all(
@@ -80,7 +155,7 @@ pub async fn find_with_options(
     page: u64,
     page_size: u64,
 ) -> Result<(Vec<events::Model>, u64), DbErr> {
-    let mut query = Events::find();
+    let mut query = Events::find().filter(live());
 
     // Apply filters
     if let Some(monitor_id) = options.monitor_id {
@@ -158,7 +233,7 @@ pub async fn find_with_condition(
     page: u64,
     page_size: u64,
 ) -> Result<(Vec<events::Model>, u64), DbErr> {
-    let mut query = Events::find().filter(condition);
+    let mut query = Events::find().filter(condition).filter(live());
 
     // Row-level ACL: restrict to the caller's permitted monitors.
     if let Some(monitor_ids) = &monitor_filter {
@@ -173,6 +248,293 @@ pub async fn find_with_condition(
     Ok((items, total))
 }
 
+/// Count the events matching `condition`, restricted to `monitor_filter` when
+/// set.
+pub async fn count_with_condition(
+    db: &DatabaseConnection,
+    condition: Condition,
+    monitor_filter: Option<&[u32]>,
+) -> Result<u64, DbErr> {
+    let mut query = Events::find().filter(condition).filter(live());
+    if let Some(monitor_ids) = monitor_filter {
+        query = query.filter(events::Column::MonitorId.is_in(monitor_ids.iter().copied()));
+    }
+    query.count(db).await
+}
+
+/// Keyset page of the events matching `condition`: the next `limit` rows with
+/// `Id > after`, ascending by `Id`. Unlike offset paging this stays correct
+/// while the caller archives or deletes the rows it has already visited.
+pub async fn find_batch_after(
+    db: &DatabaseConnection,
+    condition: Condition,
+    monitor_filter: Option<&[u32]>,
+    after: Option<u64>,
+    limit: u64,
+) -> Result<Vec<events::Model>, DbErr> {
+    let mut query = Events::find().filter(condition).filter(live());
+    if let Some(monitor_ids) = monitor_filter {
+        query = query.filter(events::Column::MonitorId.is_in(monitor_ids.iter().copied()));
+    }
+    if let Some(after) = after {
+        query = query.filter(events::Column::Id.gt(after));
+    }
+    query
+        .order_by_asc(events::Column::Id)
+        .limit(limit)
+        .all(db)
+        .await
+}
+
+/// Set `Archived` on the listed events, skipping any already in that state.
+/// Returns how many rows changed.
+pub async fn set_archived(
+    db: &DatabaseConnection,
+    ids: &[u64],
+    archived: bool,
+) -> Result<u64, DbErr> {
+    if ids.is_empty() {
+        return Ok(0);
+    }
+    let value: u8 = if archived { 1 } else { 0 };
+    let res = Events::update_many()
+        .col_expr(events::Column::Archived, Expr::value(value))
+        .filter(events::Column::Id.is_in(ids.iter().copied()))
+        .filter(events::Column::Archived.ne(value))
+        .exec(db)
+        .await?;
+    Ok(res.rows_affected)
+}
+
+/// Repoint an event at another storage — `StorageId = to` — but only while it
+/// still reads `from`. The compare-and-set makes the switch atomic with respect
+/// to anything else relocating the same event: `false` means the row changed
+/// (or vanished) underneath and nothing was written.
+pub async fn set_storage(
+    db: &DatabaseConnection,
+    id: u64,
+    from: Option<u16>,
+    to: u16,
+) -> Result<bool, DbErr> {
+    let current = match from {
+        Some(sid) => events::Column::StorageId.eq(sid),
+        None => events::Column::StorageId.is_null(),
+    };
+    let res = Events::update_many()
+        .col_expr(events::Column::StorageId, Expr::value(to))
+        .filter(events::Column::Id.eq(id))
+        .filter(current)
+        .exec(db)
+        .await?;
+    Ok(res.rows_affected > 0)
+}
+
+/// Record `storage_id` as the storage holding an event's secondary copy.
+pub async fn set_secondary_storage(
+    db: &DatabaseConnection,
+    id: u64,
+    storage_id: u16,
+) -> Result<bool, DbErr> {
+    let res = Events::update_many()
+        .col_expr(events::Column::SecondaryStorageId, Expr::value(storage_id))
+        .filter(events::Column::Id.eq(id))
+        .exec(db)
+        .await?;
+    Ok(res.rows_affected > 0)
+}
+
+/// Make `video` (a file name inside the event directory) the event's default
+/// video and mark the event as videoed. `disk_space` is the directory's size
+/// with the new file in it, so `DiskSpace` (and, through ZoneMinder's
+/// triggers, `Event_Summaries`) counts it.
+pub async fn set_default_video(
+    db: &DatabaseConnection,
+    id: u64,
+    video: &str,
+    disk_space: u64,
+) -> Result<bool, DbErr> {
+    let res = Events::update_many()
+        .col_expr(events::Column::DefaultVideo, Expr::value(video))
+        .col_expr(events::Column::Videoed, Expr::value(1u8))
+        .col_expr(events::Column::DiskSpace, Expr::value(disk_space))
+        .filter(events::Column::Id.eq(id))
+        .exec(db)
+        .await?;
+    Ok(res.rows_affected > 0)
+}
+
+/// Tombstone an event: stamp `deleted_at` unless it already carries one.
+/// Returns whether this call stamped it, so a repeated delete is a no-op.
+pub async fn soft_delete(
+    db: &DatabaseConnection,
+    id: u64,
+    now: sea_orm::prelude::DateTime,
+) -> Result<bool, DbErr> {
+    let res = Events::update_many()
+        .col_expr(events::Column::DeletedAt, Expr::value(now))
+        .filter(events::Column::Id.eq(id))
+        .filter(events::Column::DeletedAt.is_null())
+        .exec(db)
+        .await?;
+    Ok(res.rows_affected > 0)
+}
+
+/// Clear an event's tombstone, provided it was stamped no earlier than
+/// `not_before` (the start of the restore grace period). Returns whether the
+/// event came back; `false` once the grace period has passed or the reaper has
+/// already purged it.
+pub async fn restore(
+    db: &DatabaseConnection,
+    id: u64,
+    not_before: sea_orm::prelude::DateTime,
+) -> Result<bool, DbErr> {
+    let res = Events::update_many()
+        .col_expr(
+            events::Column::DeletedAt,
+            Expr::value(Option::<sea_orm::prelude::DateTime>::None),
+        )
+        .filter(events::Column::Id.eq(id))
+        .filter(events::Column::DeletedAt.gte(not_before))
+        .exec(db)
+        .await?;
+    Ok(res.rows_affected > 0)
+}
+
+/// A tombstoned event by id.
+pub async fn find_trashed_by_id(
+    db: &DatabaseConnection,
+    id: u64,
+) -> Result<Option<events::Model>, DbErr> {
+    Events::find_by_id(id)
+        .filter(events::Column::DeletedAt.is_not_null())
+        .one(db)
+        .await
+}
+
+/// Keyset page of the events tombstoned before `cutoff`: the next `limit` rows
+/// with `Id > after`, ascending by `Id`, so a row the reaper fails to purge is
+/// stepped over rather than fetched again.
+pub async fn find_trashed_before(
+    db: &DatabaseConnection,
+    cutoff: sea_orm::prelude::DateTime,
+    after: Option<u64>,
+    limit: u64,
+) -> Result<Vec<events::Model>, DbErr> {
+    let mut query = Events::find().filter(events::Column::DeletedAt.lt(cutoff));
+    if let Some(after) = after {
+        query = query.filter(events::Column::Id.gt(after));
+    }
+    query
+        .order_by_asc(events::Column::Id)
+        .limit(limit)
+        .all(db)
+        .await
+}
+
+/// A count of events and their summed `DiskSpace`.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub struct EventTally {
+    pub events: u64,
+    pub bytes: u64,
+}
+
+/// One monitor's tombstoned events, as `Event_Summaries` counts them: in
+/// total, in each rolling window table still holding them, and archived.
+/// The ZoneMinder triggers only drop an event from the summary when its row
+/// is deleted, so until the reaper purges it a trashed event is still in
+/// there.
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct TrashedTally {
+    pub monitor_id: u32,
+    pub total: EventTally,
+    /// Indexed like [`EventWindow::ALL`].
+    pub windows: [EventTally; 4],
+    pub archived: EventTally,
+}
+
+/// The grouped query behind [`trashed_tally_by_monitor`].
+fn trashed_tally_select(monitor_filter: Option<&[u32]>) -> sea_orm::sea_query::SelectStatement {
+    use sea_orm::sea_query::{JoinType, Query};
+
+    // SUM over an integer column is DECIMAL in MySQL; cast so it decodes as
+    // u64.
+    let mut select = Query::select();
+    select
+        .expr(Expr::col((events::Entity, events::Column::MonitorId)))
+        .expr(Expr::cust("COUNT(*)"))
+        .expr(Expr::cust(
+            "CAST(COALESCE(SUM(`Events`.`DiskSpace`), 0) AS UNSIGNED)",
+        ));
+    for window in EventWindow::ALL {
+        let table = window.table();
+        select
+            .expr(Expr::cust(format!("COUNT(`{table}`.`EventId`)")))
+            .expr(Expr::cust(format!(
+                "CAST(COALESCE(SUM(`{table}`.`DiskSpace`), 0) AS UNSIGNED)"
+            )))
+            .join(
+                JoinType::LeftJoin,
+                Alias::new(table),
+                Expr::col((Alias::new(table), Alias::new("EventId")))
+                    .equals((events::Entity, events::Column::Id)),
+            );
+    }
+    select
+        .expr(Expr::cust(
+            "CAST(COALESCE(SUM(CASE WHEN `Events`.`Archived` = 1 THEN 1 ELSE 0 END), 0) AS UNSIGNED)",
+        ))
+        .expr(Expr::cust(
+            "CAST(COALESCE(SUM(CASE WHEN `Events`.`Archived` = 1 THEN `Events`.`DiskSpace` ELSE 0 END), 0) AS UNSIGNED)",
+        ))
+        .from(events::Entity)
+        .and_where(Expr::col((events::Entity, events::Column::DeletedAt)).is_not_null())
+        .group_by_col((events::Entity, events::Column::MonitorId));
+    if let Some(ids) = monitor_filter {
+        select.and_where(
+            Expr::col((events::Entity, events::Column::MonitorId)).is_in(ids.iter().copied()),
+        );
+    }
+    select
+}
+
+/// Per-monitor tally of the tombstoned events, for taking them back out of
+/// `Event_Summaries`. Monitors without trashed events are absent.
+pub async fn trashed_tally_by_monitor(
+    db: &DatabaseConnection,
+    monitor_filter: Option<&[u32]>,
+) -> Result<Vec<TrashedTally>, DbErr> {
+    let sql = trashed_tally_select(monitor_filter).to_string(MysqlQueryBuilder);
+    let stmt = Statement::from_sql_and_values(db.get_database_backend(), sql, vec![]);
+
+    // Decode by index, as `usage_by_monitor` does.
+    let tally = |row: &QueryResult, at: usize| -> Result<EventTally, DbErr> {
+        let events: i64 = row.try_get_by_index(at)?;
+        Ok(EventTally {
+            events: events as u64,
+            bytes: row.try_get_by_index(at + 1)?,
+        })
+    };
+    let rows = db.query_all(stmt).await?;
+    let mut out = Vec::with_capacity(rows.len());
+    for row in rows {
+        let mut windows = [EventTally::default(); 4];
+        for (i, window) in windows.iter_mut().enumerate() {
+            *window = tally(&row, 3 + 2 * i)?;
+        }
+        let archived = EventTally {
+            events: row.try_get_by_index(11)?,
+            bytes: row.try_get_by_index(12)?,
+        };
+        out.push(TrashedTally {
+            monitor_id: row.try_get_by_index(0)?,
+            total: tally(&row, 1)?,
+            windows,
+            archived,
+        });
+    }
+    Ok(out)
+}
+
 /// Find events by monitor ID with optional date range filter (legacy helper)
 #[instrument(skip(state))]
 pub async fn find_by_monitor_id(
@@ -197,10 +559,194 @@ pub async fn find_by_monitor_id(
     .await
 }
 
+/// ZoneMinder's rolling event windows. Its `Events` insert trigger adds every
+/// new event to all four tables, and the stats maintenance ages rows out once
+/// they are older than the window, so each holds at least the events that
+/// started within its span of now.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum EventWindow {
+    Hour,
+    Day,
+    Week,
+    Month,
+}
+
+impl EventWindow {
+    /// Smallest first.
+    pub const ALL: [EventWindow; 4] = [
+        EventWindow::Hour,
+        EventWindow::Day,
+        EventWindow::Week,
+        EventWindow::Month,
+    ];
+
+    pub fn table(self) -> &'static str {
+        match self {
+            EventWindow::Hour => "Events_Hour",
+            EventWindow::Day => "Events_Day",
+            EventWindow::Week => "Events_Week",
+            EventWindow::Month => "Events_Month",
+        }
+    }
+
+    /// How far back the window reaches. Maintenance keeps a calendar month;
+    /// this takes the shortest one.
+    pub fn span(self) -> chrono::Duration {
+        match self {
+            EventWindow::Hour => chrono::Duration::hours(1),
+            EventWindow::Day => chrono::Duration::days(1),
+            EventWindow::Week => chrono::Duration::weeks(1),
+            EventWindow::Month => chrono::Duration::days(28),
+        }
+    }
+}
+
+/// The columns of an event a timeline needs.
+#[derive(Debug, Clone, FromQueryResult)]
+pub struct EventSpan {
+    pub id: u64,
+    pub monitor_id: u32,
+    pub start_date_time: Option<sea_orm::prelude::DateTime>,
+    pub end_date_time: Option<sea_orm::prelude::DateTime>,
+    pub length: sea_orm::prelude::Decimal,
+    pub frames: Option<u32>,
+    pub alarm_frames: Option<u32>,
+    pub max_score: Option<u16>,
+}
+
+fn span_query() -> Select<Events> {
+    Events::find()
+        .select_only()
+        .column_as(events::Column::Id, "id")
+        .column_as(events::Column::MonitorId, "monitor_id")
+        .column_as(events::Column::StartDateTime, "start_date_time")
+        .column_as(events::Column::EndDateTime, "end_date_time")
+        .column_as(events::Column::Length, "length")
+        .column_as(events::Column::Frames, "frames")
+        .column_as(events::Column::AlarmFrames, "alarm_frames")
+        .column_as(events::Column::MaxScore, "max_score")
+        .filter(live())
+}
+
+/// Events of `monitor_ids` that started in `[from, to)`, oldest first, at
+/// most `limit`.
+///
+/// With a `window`, the query is driven from that rollup table: its
+/// `MonitorId`/`StartDateTime` pick the rows and `Events` is only joined on
+/// `EventId` for the columns, so the large table is never range-scanned. The
+/// caller must make sure the window reaches back to `from`.
+pub async fn find_spans_starting_in(
+    db: &DatabaseConnection,
+    monitor_ids: &[u32],
+    from: sea_orm::prelude::DateTime,
+    to: sea_orm::prelude::DateTime,
+    window: Option<EventWindow>,
+    limit: u64,
+) -> Result<Vec<EventSpan>, DbErr> {
+    spans_starting_in(monitor_ids, from, to, window)
+        .limit(limit)
+        .into_model::<EventSpan>()
+        .all(db)
+        .await
+}
+
+fn spans_starting_in(
+    monitor_ids: &[u32],
+    from: sea_orm::prelude::DateTime,
+    to: sea_orm::prelude::DateTime,
+    window: Option<EventWindow>,
+) -> Select<Events> {
+    let Some(window) = window else {
+        return span_query()
+            .filter(events::Column::MonitorId.is_in(monitor_ids.iter().copied()))
+            .filter(events::Column::StartDateTime.gte(from))
+            .filter(events::Column::StartDateTime.lt(to))
+            .order_by_asc(events::Column::StartDateTime);
+    };
+    let rollup = Alias::new(window.table());
+    let col = |name: &str| Expr::col((rollup.clone(), Alias::new(name)));
+    let mut query = span_query();
+    QueryTrait::query(&mut query).join(
+        JoinType::InnerJoin,
+        rollup.clone(),
+        col("EventId").equals((Events, events::Column::Id)),
+    );
+    query
+        .filter(col("MonitorId").is_in(monitor_ids.iter().copied()))
+        .filter(col("StartDateTime").gte(from))
+        .filter(col("StartDateTime").lt(to))
+        .order_by_asc(col("StartDateTime"))
+}
+
+/// For each of `monitor_ids`, the last event to start before `before`: the
+/// only one that can still be running into a range starting there. One
+/// grouped query — the latest start per monitor, joined back to `Events`.
+pub async fn find_last_spans_before(
+    db: &DatabaseConnection,
+    monitor_ids: &[u32],
+    before: sea_orm::prelude::DateTime,
+) -> Result<Vec<EventSpan>, DbErr> {
+    last_spans_before(monitor_ids, before)
+        .into_model::<EventSpan>()
+        .all(db)
+        .await
+}
+
+fn last_spans_before(monitor_ids: &[u32], before: sea_orm::prelude::DateTime) -> Select<Events> {
+    let last = Alias::new("last");
+    let last_start = Alias::new("LastStart");
+    let latest = sea_orm::sea_query::Query::select()
+        .column(events::Column::MonitorId)
+        .expr_as(
+            Expr::col(events::Column::StartDateTime).max(),
+            last_start.clone(),
+        )
+        .from(Events)
+        .and_where(events::Column::MonitorId.is_in(monitor_ids.iter().copied()))
+        .and_where(events::Column::StartDateTime.lt(before))
+        .and_where(live())
+        .group_by_col(events::Column::MonitorId)
+        .to_owned();
+    let mut query = span_query();
+    QueryTrait::query(&mut query).join_subquery(
+        JoinType::InnerJoin,
+        latest,
+        last.clone(),
+        Condition::all()
+            .add(
+                Expr::col((last.clone(), events::Column::MonitorId))
+                    .equals((Events, events::Column::MonitorId)),
+            )
+            .add(Expr::col((last, last_start)).equals((Events, events::Column::StartDateTime))),
+    );
+    query
+}
+
+/// Closed events of `monitor_id` overlapping `[from, to)`, oldest first, at
+/// most `limit`. Events still recording (no `EndDateTime`) are left out.
+pub async fn find_closed_in_range(
+    db: &DatabaseConnection,
+    monitor_id: u32,
+    from: sea_orm::prelude::DateTime,
+    to: sea_orm::prelude::DateTime,
+    limit: u64,
+) -> Result<Vec<events::Model>, DbErr> {
+    Events::find()
+        .filter(live())
+        .filter(events::Column::MonitorId.eq(monitor_id))
+        .filter(events::Column::StartDateTime.lt(to))
+        .filter(events::Column::EndDateTime.gt(from))
+        .order_by_asc(events::Column::StartDateTime)
+        .order_by_asc(events::Column::Id)
+        .limit(limit)
+        .all(db)
+        .await
+}
+
 /// Find event by ID
 #[instrument(skip(state))]
 pub async fn find_by_id(state: &AppState, id: u64) -> Result<Option<events::Model>, DbErr> {
-    Events::find_by_id(id).one(state.db()).await
+    Events::find_by_id(id).filter(live()).one(state.db()).await
 }
 
 /// Create new event
@@ -227,9 +773,9 @@ pub async fn delete(state: &AppState, id: u64) -> Result<DeleteResult, DbErr> {
 /// (`Events_Hour/Day/Week/Month`) plus `Events_Archived` have **no** FK cascade
 /// to `Events`, so deleting only the event row orphans them. `Snapshots_Events`
 /// and `Events_Tags` *do* cascade via their FKs and are removed by the event
-/// delete itself. This is the single DB-side delete both the interactive
-/// `DELETE /events/{id}` path and the retention reaper use, so they can never
-/// leave different debris behind.
+/// delete itself. This is the single DB-side delete both the trash reaper (which
+/// purges what `DELETE /events/{id}` tombstoned) and the retention reaper use, so
+/// they can never leave different debris behind.
 pub async fn delete_with_children<C>(conn: &C, id: u64) -> Result<(), DbErr>
 where
     C: ConnectionTrait + TransactionTrait,
@@ -290,6 +836,7 @@ pub async fn get_counts_by_monitor(
         .expr(Expr::cust("COUNT(*)"))
         .from(events::Entity)
         .and_where(Expr::col(events::Column::StartDateTime).gte(time_boundary_str))
+        .and_where(Expr::col(events::Column::DeletedAt).is_null())
         .group_by_col(events::Column::MonitorId);
     // Row-level ACL: restrict the aggregation to permitted monitors.
     if let Some(ids) = monitor_filter {
@@ -345,6 +892,7 @@ pub async fn get_counts_by_hour(
         .expr_as(Expr::cust("COUNT(*)"), count_alias.clone())
         .from(events::Entity)
         .and_where(Expr::col(events::Column::StartDateTime).gte(time_boundary_str))
+        .and_where(Expr::col(events::Column::DeletedAt).is_null())
         .group_by_col(hour_alias.clone())
         .order_by(hour_alias.clone(), Order::Asc);
     // Row-level ACL: restrict the aggregation to permitted monitors.
@@ -422,7 +970,10 @@ pub async fn find_by_id_with_tags(
     state: &AppState,
     id: u64,
 ) -> Result<Option<(events::Model, Vec<tags::Model>)>, DbErr> {
-    let event = Events::find_by_id(id).one(state.db()).await?;
+    let event = Events::find_by_id(id)
+        .filter(live())
+        .one(state.db())
+        .await?;
 
     match event {
         Some(e) => {
@@ -459,4 +1010,93 @@ mod tests {
             .await
             .expect("transactional delete should succeed");
     }
+
+    /// A second delete of an already-tombstoned event matches no row and
+    /// reports that it did nothing, which is what makes `DELETE` idempotent.
+    #[tokio::test]
+    async fn soft_delete_reports_whether_it_stamped_the_row() {
+        let now = chrono::Utc::now().naive_utc();
+        let db = MockDatabase::new(DatabaseBackend::MySql)
+            .append_exec_results(vec![
+                MockExecResult {
+                    last_insert_id: 0,
+                    rows_affected: 1,
+                },
+                MockExecResult {
+                    last_insert_id: 0,
+                    rows_affected: 0,
+                },
+            ])
+            .into_connection();
+
+        assert!(soft_delete(&db, 42, now).await.unwrap());
+        assert!(!soft_delete(&db, 42, now).await.unwrap());
+    }
+
+    fn timeline_sql(select: Select<Events>) -> String {
+        select.build(DatabaseBackend::MySql).to_string()
+    }
+
+    #[test]
+    fn rollup_window_drives_the_span_query() {
+        let at = chrono::NaiveDate::from_ymd_opt(2026, 10, 18)
+            .unwrap()
+            .and_hms_opt(12, 0, 0)
+            .unwrap();
+        let sql = timeline_sql(spans_starting_in(
+            &[1, 2],
+            at,
+            at + chrono::Duration::hours(1),
+            Some(EventWindow::Day),
+        ));
+        assert!(
+            sql.contains("INNER JOIN `Events_Day` ON `Events_Day`.`EventId` = `Events`.`Id`"),
+            "{sql}"
+        );
+        assert!(sql.contains("`Events_Day`.`MonitorId` IN (1, 2)"), "{sql}");
+        assert!(
+            sql.contains("ORDER BY `Events_Day`.`StartDateTime` ASC"),
+            "{sql}"
+        );
+        assert!(!sql.contains("IN (SELECT"), "{sql}");
+
+        let sql = timeline_sql(spans_starting_in(&[1], at, at, None));
+        assert!(!sql.contains("JOIN"), "{sql}");
+    }
+
+    #[test]
+    fn trashed_tally_joins_every_window() {
+        let sql = trashed_tally_select(Some(&[3, 4])).to_string(MysqlQueryBuilder);
+        for window in EventWindow::ALL {
+            let table = window.table();
+            assert!(
+                sql.contains(&format!(
+                    "LEFT JOIN `{table}` ON `{table}`.`EventId` = `Events`.`Id`"
+                )),
+                "{sql}"
+            );
+        }
+        assert!(sql.contains("`Events`.`deleted_at` IS NOT NULL"), "{sql}");
+        assert!(sql.contains("`Events`.`MonitorId` IN (3, 4)"), "{sql}");
+        assert!(sql.ends_with("GROUP BY `Events`.`MonitorId`"), "{sql}");
+
+        let sql = trashed_tally_select(None).to_string(MysqlQueryBuilder);
+        assert!(!sql.contains(" IN ("), "{sql}");
+    }
+
+    #[test]
+    fn running_events_come_from_one_grouped_query() {
+        let at = chrono::NaiveDate::from_ymd_opt(2026, 10, 18)
+            .unwrap()
+            .and_hms_opt(12, 0, 0)
+            .unwrap();
+        let sql = timeline_sql(last_spans_before(&[1, 2, 3], at));
+        assert!(sql.contains("MAX(`StartDateTime`) AS `LastStart`"), "{sql}");
+        assert!(sql.contains("GROUP BY `MonitorId`"), "{sql}");
+        assert!(
+            sql.contains("`last`.`LastStart` = `Events`.`StartDateTime`"),
+            "{sql}"
+        );
+        assert_eq!(sql.matches("SELECT").count(), 2, "{sql}");
+    }
 }
@@ -181,6 +181,16 @@ pub async fn create(
     Ok(am.insert(db).await?)
 }
 
+/// Filters with `AutoExecute` set: stored shell commands that zmfilter.pl
+/// would still run.
+#[tracing::instrument(skip_all)]
+pub async fn find_auto_execute(db: &DatabaseConnection) -> AppResult<Vec<FilterModel>> {
+    Ok(Filters::find()
+        .filter(FilterColumn::AutoExecute.ne(0))
+        .all(db)
+        .await?)
+}
+
 #[tracing::instrument(skip_all)]
 pub async fn delete_by_id(db: &DatabaseConnection, id: u32) -> AppResult<bool> {
     use sea_orm::EntityTrait;
@@ -74,6 +74,21 @@ pub async fn find_by_id(db: &DatabaseConnection, id: u64) -> AppResult<Option<Fr
     Ok(frame)
 }
 
+/// Find one frame of an event by its `FrameId` (its number within the event,
+/// as `Events.MaxScoreFrameId` records it).
+pub async fn find_by_event_frame(
+    db: &DatabaseConnection,
+    event_id: u64,
+    frame_id: u32,
+) -> AppResult<Option<FrameModel>> {
+    let frame = FrameEntity::find()
+        .filter(Column::EventId.eq(event_id))
+        .filter(Column::FrameId.eq(frame_id))
+        .one(db)
+        .await?;
+    Ok(frame)
+}
+
 /// Create a new frame
 pub async fn create(db: &DatabaseConnection, req: &CreateFrameRequest) -> AppResult<FrameModel> {
     // Parse timestamp
@@ -0,0 +1,94 @@
+//! DB query layer for the zm-api-owned `jobs` table.
+
+use sea_orm::*;
+
+use crate::entity::jobs;
+use crate::entity::prelude::Jobs;
+use crate::entity::sea_orm_active_enums::{JobKind, JobStatus};
+
+/// Find a job by id.
+pub async fn find_by_id(db: &DatabaseConnection, id: u64) -> Result<Option<jobs::Model>, DbErr> {
+    Jobs::find_by_id(id).one(db).await
+}
+
+/// Insert a `queued` job, returning the persisted row. `event_id` is set for
+/// jobs that act on a single event.
+pub async fn insert(
+    db: &DatabaseConnection,
+    kind: JobKind,
+    user_id: Option<u32>,
+    event_id: Option<u64>,
+    params_json: String,
+    now: chrono::NaiveDateTime,
+) -> Result<jobs::Model, DbErr> {
+    jobs::ActiveModel {
+        kind: Set(kind),
+        status: Set(JobStatus::Queued),
+        user_id: Set(user_id),
+        event_id: Set(event_id),
+        params_json: Set(params_json),
+        cursor: Set(None),
+        total: Set(None),
+        processed: Set(0),
+        affected: Set(0),
+        failed: Set(0),
+        result_json: Set(None),
+        error: Set(None),
+        created_at: Set(now),
+        started_at: Set(None),
+        finished_at: Set(None),
+        updated_at: Set(now),
+        ..Default::default()
+    }
+    .insert(db)
+    .await
+}
+
+/// Jobs of `kind` that have not finished (`queued` or `running`), oldest first —
+/// the set a restart must resume.
+pub async fn find_unfinished(
+    db: &DatabaseConnection,
+    kind: JobKind,
+) -> Result<Vec<jobs::Model>, DbErr> {
+    Jobs::find()
+        .filter(jobs::Column::Kind.eq(kind))
+        .filter(jobs::Column::Status.is_in([JobStatus::Queued, JobStatus::Running]))
+        .order_by_asc(jobs::Column::Id)
+        .all(db)
+        .await
+}
+
+/// The unfinished job acting on `event_id`, if any.
+pub async fn find_active_for_event(
+    db: &DatabaseConnection,
+    event_id: u64,
+) -> Result<Option<jobs::Model>, DbErr> {
+    Jobs::find()
+        .filter(jobs::Column::EventId.eq(event_id))
+        .filter(jobs::Column::Status.is_in([JobStatus::Queued, JobStatus::Running]))
+        .one(db)
+        .await
+}
+
+/// Persist a job's mutable state (status, cursor, counters, result, times).
+/// `kind`, `user_id`, `event_id`, `params_json` and `created_at` are fixed at
+/// insert.
+pub async fn save(db: &DatabaseConnection, job: &jobs::Model) -> Result<jobs::Model, DbErr> {
+    jobs::ActiveModel {
+        id: Unchanged(job.id),
+        status: Set(job.status),
+        cursor: Set(job.cursor),
+        total: Set(job.total),
+        processed: Set(job.processed),
+        affected: Set(job.affected),
+        failed: Set(job.failed),
+        result_json: Set(job.result_json.clone()),
+        error: Set(job.error.clone()),
+        started_at: Set(job.started_at),
+        finished_at: Set(job.finished_at),
+        updated_at: Set(job.updated_at),
+        ..Default::default()
+    }
+    .update(db)
+    .await
+}
@@ -18,6 +18,7 @@ pub mod frames;
 pub mod groups;
 pub mod groups_monitors;
 pub mod groups_permissions;
+pub mod jobs;
 pub mod logs;
 pub mod manufacturers;
 pub mod models;
@@ -27,9 +28,15 @@ pub mod monitor_status;
 pub mod monitors;
 pub mod monitors_permissions;
 pub mod montage_layouts;
+pub mod notification_deliveries;
+pub mod notification_rules;
 pub mod object_types;
 pub mod ptz;
+pub mod ptz_tours;
 pub mod reports;
+pub mod retention;
+pub mod rtsp_credentials;
+pub mod saved_searches;
 pub mod server_stats;
 pub mod servers;
 pub mod sessions;
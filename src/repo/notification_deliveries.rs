@@ -0,0 +1,64 @@
+//! DB query layer for the zm-api-owned `notification_deliveries` table.
+
+use sea_orm::*;
+
+use crate::dto::PaginationParams;
+use crate::entity::notification_deliveries;
+use crate::entity::prelude::NotificationDeliveries;
+use crate::entity::sea_orm_active_enums::DeliveryStatus;
+
+/// Record one delivery attempt.
+pub async fn insert(
+    db: &DatabaseConnection,
+    active: notification_deliveries::ActiveModel,
+) -> Result<notification_deliveries::Model, DbErr> {
+    active.insert(db).await
+}
+
+/// One page of a rule's deliveries, newest first.
+pub async fn find_by_rule_paginated(
+    db: &DatabaseConnection,
+    rule_id: u64,
+    params: &PaginationParams,
+) -> Result<(Vec<notification_deliveries::Model>, u64), DbErr> {
+    let paginator = NotificationDeliveries::find()
+        .filter(notification_deliveries::Column::RuleId.eq(rule_id))
+        .order_by_desc(notification_deliveries::Column::Id)
+        .paginate(db, params.page_size());
+    let total = paginator.num_items().await?;
+    let items = paginator.fetch_page(params.page() - 1).await?;
+    Ok((items, total))
+}
+
+/// Whether the rule delivered successfully for `dedupe_key` at or after
+/// `since` — i.e. whether a new notification falls inside the dedupe window.
+pub async fn sent_since(
+    db: &DatabaseConnection,
+    rule_id: u64,
+    dedupe_key: &str,
+    since: chrono::NaiveDateTime,
+) -> Result<bool, DbErr> {
+    let count = NotificationDeliveries::find()
+        .filter(notification_deliveries::Column::RuleId.eq(rule_id))
+        .filter(notification_deliveries::Column::DedupeKey.eq(dedupe_key))
+        .filter(notification_deliveries::Column::Status.eq(DeliveryStatus::Sent))
+        .filter(notification_deliveries::Column::CreatedAt.gte(since))
+        .count(db)
+        .await?;
+    Ok(count > 0)
+}
+
+/// Whether the rule has already recorded a delivery (of any outcome) for this
+/// event and trigger. Keeps a trigger to once per event across restarts.
+pub async fn exists_for_event(
+    db: &DatabaseConnection,
+    rule_id: u64,
+    event_id: u64,
+) -> Result<bool, DbErr> {
+    let count = NotificationDeliveries::find()
+        .filter(notification_deliveries::Column::RuleId.eq(rule_id))
+        .filter(notification_deliveries::Column::EventId.eq(event_id))
+        .count(db)
+        .await?;
+    Ok(count > 0)
+}
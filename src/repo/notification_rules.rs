@@ -0,0 +1,66 @@
+//! DB query layer for the zm-api-owned `notification_rules` table.
+
+use sea_orm::*;
+
+use crate::dto::PaginationParams;
+use crate::entity::notification_rules;
+use crate::entity::prelude::NotificationRules;
+
+/// Find a rule by id.
+pub async fn find_by_id(
+    db: &DatabaseConnection,
+    id: u64,
+) -> Result<Option<notification_rules::Model>, DbErr> {
+    NotificationRules::find_by_id(id).one(db).await
+}
+
+/// One page of rules, by name. `owner` restricts the list to one user's rules;
+/// `None` lists everyone's (System viewers).
+pub async fn find_paginated(
+    db: &DatabaseConnection,
+    params: &PaginationParams,
+    owner: Option<u32>,
+) -> Result<(Vec<notification_rules::Model>, u64), DbErr> {
+    let mut query = NotificationRules::find().order_by_asc(notification_rules::Column::Name);
+    if let Some(uid) = owner {
+        query = query.filter(notification_rules::Column::UserId.eq(uid));
+    }
+    let paginator = query.paginate(db, params.page_size());
+    let total = paginator.num_items().await?;
+    let items = paginator.fetch_page(params.page() - 1).await?;
+    Ok((items, total))
+}
+
+/// Every enabled rule, for the dispatcher's cache.
+pub async fn find_enabled(
+    db: &DatabaseConnection,
+) -> Result<Vec<notification_rules::Model>, DbErr> {
+    NotificationRules::find()
+        .filter(notification_rules::Column::Enabled.eq(true))
+        .order_by_asc(notification_rules::Column::Id)
+        .all(db)
+        .await
+}
+
+/// Insert a rule, returning the persisted row.
+pub async fn insert(
+    db: &DatabaseConnection,
+    active: notification_rules::ActiveModel,
+) -> Result<notification_rules::Model, DbErr> {
+    active.insert(db).await
+}
+
+/// Persist a partially-updated rule.
+pub async fn update(
+    db: &DatabaseConnection,
+    active: notification_rules::ActiveModel,
+) -> Result<notification_rules::Model, DbErr> {
+    active.update(db).await
+}
+
+/// Delete a rule by id. Returns whether a row was removed. Its delivery
+/// history is kept.
+pub async fn delete_by_id(db: &DatabaseConnection, id: u64) -> Result<bool, DbErr> {
+    let res = NotificationRules::delete_by_id(id).exec(db).await?;
+    Ok(res.rows_affected > 0)
+}
@@ -0,0 +1,72 @@
+//! DB query layer for the zm-api-owned `ptz_tours` table.
+
+use sea_orm::*;
+
+use crate::entity::prelude::PtzTours;
+use crate::entity::ptz_tours;
+
+/// Find a tour by id.
+pub async fn find_by_id(
+    db: &DatabaseConnection,
+    id: u64,
+) -> Result<Option<ptz_tours::Model>, DbErr> {
+    PtzTours::find_by_id(id).one(db).await
+}
+
+/// A monitor's tours, by name.
+pub async fn find_by_monitor(
+    db: &DatabaseConnection,
+    monitor_id: u32,
+) -> Result<Vec<ptz_tours::Model>, DbErr> {
+    PtzTours::find()
+        .filter(ptz_tours::Column::MonitorId.eq(monitor_id))
+        .order_by_asc(ptz_tours::Column::Name)
+        .all(db)
+        .await
+}
+
+/// A monitor's tour by name, for the uniqueness check.
+pub async fn find_by_monitor_and_name(
+    db: &DatabaseConnection,
+    monitor_id: u32,
+    name: &str,
+) -> Result<Option<ptz_tours::Model>, DbErr> {
+    PtzTours::find()
+        .filter(ptz_tours::Column::MonitorId.eq(monitor_id))
+        .filter(ptz_tours::Column::Name.eq(name))
+        .one(db)
+        .await
+}
+
+/// Every enabled tour with a schedule, oldest first — the order the runner
+/// gives way in when two windows on one monitor overlap.
+pub async fn find_scheduled(db: &DatabaseConnection) -> Result<Vec<ptz_tours::Model>, DbErr> {
+    PtzTours::find()
+        .filter(ptz_tours::Column::Enabled.eq(true))
+        .filter(ptz_tours::Column::ScheduleJson.is_not_null())
+        .order_by_asc(ptz_tours::Column::Id)
+        .all(db)
+        .await
+}
+
+/// Insert a tour, returning the persisted row.
+pub async fn insert(
+    db: &DatabaseConnection,
+    active: ptz_tours::ActiveModel,
+) -> Result<ptz_tours::Model, DbErr> {
+    active.insert(db).await
+}
+
+/// Persist a partially-updated tour.
+pub async fn update(
+    db: &DatabaseConnection,
+    active: ptz_tours::ActiveModel,
+) -> Result<ptz_tours::Model, DbErr> {
+    active.update(db).await
+}
+
+/// Delete a tour by id. Returns whether a row was removed.
+pub async fn delete_by_id(db: &DatabaseConnection, id: u64) -> Result<bool, DbErr> {
+    let res = PtzTours::delete_by_id(id).exec(db).await?;
+    Ok(res.rows_affected > 0)
+}
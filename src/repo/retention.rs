@@ -0,0 +1,126 @@
+//! DB query layer for the zm-api-owned `storage_retention` and
+//! `monitor_retention` tables.
+//!
+//! One row per storage / monitor that overrides the global `[retention]`
+//! knobs (see [`crate::entity::storage_retention`] and
+//! [`crate::entity::monitor_retention`]). Each is keyed by the row it belongs
+//! to, so a write is an upsert.
+
+use sea_orm::*;
+
+use crate::entity::prelude::{MonitorRetention, StorageRetention};
+use crate::entity::{monitor_retention, storage_retention};
+
+/// The override values shared by both tables. `None` inherits.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub struct Overrides {
+    pub max_age_days: Option<u32>,
+    pub max_storage_bytes: Option<u64>,
+    pub protect_archived: Option<bool>,
+}
+
+/// Every storage override, for the reaper's per-pass snapshot.
+pub async fn find_all_storage(
+    db: &DatabaseConnection,
+) -> Result<Vec<storage_retention::Model>, DbErr> {
+    StorageRetention::find().all(db).await
+}
+
+/// Every monitor override, for the reaper's per-pass snapshot.
+pub async fn find_all_monitor(
+    db: &DatabaseConnection,
+) -> Result<Vec<monitor_retention::Model>, DbErr> {
+    MonitorRetention::find().all(db).await
+}
+
+pub async fn find_by_storage(
+    db: &DatabaseConnection,
+    storage_id: u16,
+) -> Result<Option<storage_retention::Model>, DbErr> {
+    StorageRetention::find_by_id(storage_id).one(db).await
+}
+
+pub async fn find_by_monitor(
+    db: &DatabaseConnection,
+    monitor_id: u32,
+) -> Result<Option<monitor_retention::Model>, DbErr> {
+    MonitorRetention::find_by_id(monitor_id).one(db).await
+}
+
+/// Insert or replace a storage's overrides. `created_at` is set on first write
+/// and preserved on update; `updated_at` always advances.
+pub async fn upsert_storage(
+    db: &DatabaseConnection,
+    storage_id: u16,
+    values: Overrides,
+    tier_storage_id: Option<u16>,
+    now: chrono::NaiveDateTime,
+) -> Result<storage_retention::Model, DbErr> {
+    match StorageRetention::find_by_id(storage_id).one(db).await? {
+        Some(existing) => {
+            let mut active: storage_retention::ActiveModel = existing.into();
+            active.max_age_days = Set(values.max_age_days);
+            active.max_storage_bytes = Set(values.max_storage_bytes);
+            active.protect_archived = Set(values.protect_archived);
+            active.tier_storage_id = Set(tier_storage_id);
+            active.updated_at = Set(now);
+            active.update(db).await
+        }
+        None => {
+            storage_retention::ActiveModel {
+                storage_id: Set(storage_id),
+                max_age_days: Set(values.max_age_days),
+                max_storage_bytes: Set(values.max_storage_bytes),
+                protect_archived: Set(values.protect_archived),
+                tier_storage_id: Set(tier_storage_id),
+                created_at: Set(now),
+                updated_at: Set(now),
+            }
+            .insert(db)
+            .await
+        }
+    }
+}
+
+/// Insert or replace a monitor's overrides, as [`upsert_storage`].
+pub async fn upsert_monitor(
+    db: &DatabaseConnection,
+    monitor_id: u32,
+    values: Overrides,
+    now: chrono::NaiveDateTime,
+) -> Result<monitor_retention::Model, DbErr> {
+    match MonitorRetention::find_by_id(monitor_id).one(db).await? {
+        Some(existing) => {
+            let mut active: monitor_retention::ActiveModel = existing.into();
+            active.max_age_days = Set(values.max_age_days);
+            active.max_storage_bytes = Set(values.max_storage_bytes);
+            active.protect_archived = Set(values.protect_archived);
+            active.updated_at = Set(now);
+            active.update(db).await
+        }
+        None => {
+            monitor_retention::ActiveModel {
+                monitor_id: Set(monitor_id),
+                max_age_days: Set(values.max_age_days),
+                max_storage_bytes: Set(values.max_storage_bytes),
+                protect_archived: Set(values.protect_archived),
+                created_at: Set(now),
+                updated_at: Set(now),
+            }
+            .insert(db)
+            .await
+        }
+    }
+}
+
+/// Remove a storage's overrides; `false` if it had none.
+pub async fn delete_by_storage(db: &DatabaseConnection, storage_id: u16) -> Result<bool, DbErr> {
+    let res = StorageRetention::delete_by_id(storage_id).exec(db).await?;
+    Ok(res.rows_affected > 0)
+}
+
+/// Remove a monitor's overrides; `false` if it had none.
+pub async fn delete_by_monitor(db: &DatabaseConnection, monitor_id: u32) -> Result<bool, DbErr> {
+    let res = MonitorRetention::delete_by_id(monitor_id).exec(db).await?;
+    Ok(res.rows_affected > 0)
+}
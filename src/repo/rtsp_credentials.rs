@@ -0,0 +1,70 @@
+//! DB query layer for the zm-api-owned `rtsp_credentials` table (see
+//! [`crate::entity::rtsp_credentials`]). Keyed by user, so a write is an
+//! upsert.
+
+use sea_orm::*;
+
+use crate::entity::prelude::RtspCredentials;
+use crate::entity::rtsp_credentials;
+
+pub async fn find_by_user(
+    db: &DatabaseConnection,
+    user_id: u32,
+) -> Result<Option<rtsp_credentials::Model>, DbErr> {
+    RtspCredentials::find_by_id(user_id).one(db).await
+}
+
+/// Insert or replace a user's digest and the fingerprint of the password hash
+/// it belongs to.
+pub async fn upsert(
+    db: &DatabaseConnection,
+    user_id: u32,
+    realm: &str,
+    ha1: &str,
+    password_fingerprint: &str,
+    now: chrono::NaiveDateTime,
+) -> Result<rtsp_credentials::Model, DbErr> {
+    match RtspCredentials::find_by_id(user_id).one(db).await? {
+        Some(existing) => {
+            let mut active: rtsp_credentials::ActiveModel = existing.into();
+            active.realm = Set(realm.to_string());
+            active.ha1 = Set(ha1.to_string());
+            active.password_fingerprint = Set(Some(password_fingerprint.to_string()));
+            active.updated_at = Set(now);
+            active.update(db).await
+        }
+        None => {
+            rtsp_credentials::ActiveModel {
+                user_id: Set(user_id),
+                realm: Set(realm.to_string()),
+                ha1: Set(ha1.to_string()),
+                password_fingerprint: Set(Some(password_fingerprint.to_string())),
+                updated_at: Set(now),
+            }
+            .insert(db)
+            .await
+        }
+    }
+}
+
+/// Forget a user's digest; `false` if there was none.
+pub async fn delete_by_user(db: &DatabaseConnection, user_id: u32) -> Result<bool, DbErr> {
+    let res = RtspCredentials::delete_by_id(user_id).exec(db).await?;
+    Ok(res.rows_affected > 0)
+}
+
+/// Forget the digests of users that no longer exist (deleted outside zm-api).
+pub async fn delete_orphans(db: &DatabaseConnection) -> Result<u64, DbErr> {
+    let res = RtspCredentials::delete_many()
+        .filter(
+            rtsp_credentials::Column::UserId.not_in_subquery(
+                sea_query::Query::select()
+                    .column(crate::entity::users::Column::Id)
+                    .from(crate::entity::users::Entity)
+                    .to_owned(),
+            ),
+        )
+        .exec(db)
+        .await?;
+    Ok(res.rows_affected)
+}
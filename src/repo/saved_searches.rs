@@ -0,0 +1,77 @@
+//! DB query layer for the zm-api-owned `saved_searches` table.
+
+use sea_orm::*;
+
+use crate::dto::PaginationParams;
+use crate::entity::prelude::SavedSearches;
+use crate::entity::saved_searches;
+
+/// Find a saved search by id.
+pub async fn find_by_id(
+    db: &DatabaseConnection,
+    id: u64,
+) -> Result<Option<saved_searches::Model>, DbErr> {
+    SavedSearches::find_by_id(id).one(db).await
+}
+
+/// One page of saved searches, by name. `owner` restricts the list to one
+/// user's searches; `None` lists everyone's (System viewers).
+pub async fn find_paginated(
+    db: &DatabaseConnection,
+    params: &PaginationParams,
+    owner: Option<u32>,
+) -> Result<(Vec<saved_searches::Model>, u64), DbErr> {
+    let mut query = SavedSearches::find().order_by_asc(saved_searches::Column::Name);
+    if let Some(uid) = owner {
+        query = query.filter(saved_searches::Column::UserId.eq(uid));
+    }
+    let paginator = query.paginate(db, params.page_size());
+    let total = paginator.num_items().await?;
+    let items = paginator.fetch_page(params.page() - 1).await?;
+    Ok((items, total))
+}
+
+/// Insert a saved search, returning the persisted row.
+pub async fn insert(
+    db: &DatabaseConnection,
+    user_id: u32,
+    name: String,
+    ast_json: String,
+    now: chrono::NaiveDateTime,
+) -> Result<saved_searches::Model, DbErr> {
+    saved_searches::ActiveModel {
+        user_id: Set(user_id),
+        name: Set(name),
+        ast_json: Set(ast_json),
+        created_at: Set(now),
+        updated_at: Set(now),
+        ..Default::default()
+    }
+    .insert(db)
+    .await
+}
+
+/// Apply a partial update to an existing row; `updated_at` always advances.
+pub async fn update(
+    db: &DatabaseConnection,
+    existing: saved_searches::Model,
+    name: Option<String>,
+    ast_json: Option<String>,
+    now: chrono::NaiveDateTime,
+) -> Result<saved_searches::Model, DbErr> {
+    let mut active: saved_searches::ActiveModel = existing.into();
+    if let Some(name) = name {
+        active.name = Set(name);
+    }
+    if let Some(ast_json) = ast_json {
+        active.ast_json = Set(ast_json);
+    }
+    active.updated_at = Set(now);
+    active.update(db).await
+}
+
+/// Delete a saved search by id. Returns whether a row was removed.
+pub async fn delete_by_id(db: &DatabaseConnection, id: u64) -> Result<bool, DbErr> {
+    let res = SavedSearches::delete_by_id(id).exec(db).await?;
+    Ok(res.rows_affected > 0)
+}
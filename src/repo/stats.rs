@@ -33,6 +33,17 @@ pub async fn find_paginated(
     Ok((items, total))
 }
 
+/// Every zone's stats for one event, in frame order.
+pub async fn find_by_event(db: &DatabaseConnection, event_id: u64) -> AppResult<Vec<StatModel>> {
+    use crate::entity::stats::Column;
+    Ok(Stats::find()
+        .filter(Column::EventId.eq(event_id))
+        .order_by_asc(Column::FrameId)
+        .order_by_asc(Column::ZoneId)
+        .all(db)
+        .await?)
+}
+
 pub async fn find_by_id(db: &DatabaseConnection, id: u32) -> AppResult<Option<StatModel>> {
     Ok(Stats::find_by_id(id).one(db).await?)
 }
@@ -69,10 +69,24 @@ pub async fn delete_by_id(db: &DatabaseConnection, id: u64) -> AppResult<bool> {
     Ok(res.rows_affected > 0)
 }
 
+/// `SELECT Id FROM Events WHERE deleted_at IS NULL`, for filtering tag
+/// associations down to live events.
+fn live_event_ids() -> sea_orm::sea_query::SelectStatement {
+    sea_orm::sea_query::Query::select()
+        .column(events::Column::Id)
+        .from(events::Entity)
+        .and_where(events::Column::DeletedAt.is_null())
+        .to_owned()
+}
+
 /// Get event counts for all tags
 pub async fn get_event_counts(db: &DatabaseConnection) -> AppResult<HashMap<u64, u64>> {
-    // Fetch all event-tag associations and count by tag_id
-    let associations = events_tags::Entity::find().all(db).await?;
+    // Fetch all event-tag associations and count by tag_id, leaving out
+    // tombstoned (soft-deleted) events.
+    let associations = events_tags::Entity::find()
+        .filter(events_tags::Column::EventId.in_subquery(live_event_ids()))
+        .all(db)
+        .await?;
 
     let mut counts: HashMap<u64, u64> = HashMap::new();
     for assoc in associations {
@@ -96,18 +110,20 @@ pub async fn find_events_for_tag(
         .await?;
 
     let event_ids: Vec<u64> = associations.iter().map(|a| a.event_id).collect();
-    let total = event_ids.len() as u64;
 
     if event_ids.is_empty() {
         return Ok((vec![], 0));
     }
 
-    // Paginate and fetch events
+    // Paginate and fetch events; tombstoned (soft-deleted) ones are hidden, so
+    // the total comes from the filtered query rather than the associations.
     let paginator = events::Entity::find()
         .filter(events::Column::Id.is_in(event_ids))
+        .filter(events::Column::DeletedAt.is_null())
         .order_by_desc(events::Column::StartDateTime)
         .paginate(db, page_size);
 
+    let total = paginator.num_items().await?;
     let events_list = paginator.fetch_page(page).await?;
 
     Ok((events_list, total))
@@ -23,6 +23,17 @@ pub async fn find_by_id(db: &DatabaseConnection, id: u32) -> AppResult<Option<Us
     Ok(Users::find_by_id(id).one(db).await?)
 }
 
+/// Find a user by username, whatever the state of their account.
+pub async fn find_by_username(
+    db: &DatabaseConnection,
+    username: &str,
+) -> AppResult<Option<UserModel>> {
+    Ok(Users::find()
+        .filter(crate::entity::users::Column::Username.eq(username))
+        .one(db)
+        .await?)
+}
+
 // Find a user by username, filtered to those whose accounts are enabled AND
 // whose API access is enabled. ZoneMinder maintains a separate `APIEnabled`
 // column intended to let an admin disable the API for a user without
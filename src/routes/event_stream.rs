@@ -0,0 +1,26 @@
+//! Event firehose routes
+//!
+//! Cross-monitor event activity as SSE or WebSocket. Both accept the JWT via
+//! `?token=` (`media_auth_middleware`), since neither `EventSource` nor the
+//! browser WebSocket API can set an `Authorization` header.
+
+use axum::{middleware, routing::get, Router};
+
+use crate::handlers::event_stream;
+use crate::server::state::AppState;
+use crate::util::middleware::media_auth_middleware;
+
+/// Add event firehose routes to the router
+pub fn add_event_stream_routes(router: Router<AppState>) -> Router<AppState> {
+    router
+        .route(
+            "/api/v3/events/stream",
+            get(event_stream::stream_events)
+                .route_layer(middleware::from_fn(media_auth_middleware)),
+        )
+        .route(
+            "/api/v3/events/stream/ws",
+            get(event_stream::stream_events_websocket)
+                .route_layer(middleware::from_fn(media_auth_middleware)),
+        )
+}
@@ -1,13 +1,42 @@
-use axum::{middleware, routing::get, Router};
+use axum::{
+    middleware,
+    routing::{get, post},
+    Router,
+};
 
 use crate::util::middleware::auth_middleware;
 use crate::{handlers, server::state::AppState};
 
 /// Create events router using JWT middleware
 pub fn add_event_routes(router: Router<AppState>) -> Router<AppState> {
-    router.nest(
-        "/api/v3/events",
-        routes().layer(middleware::from_fn(auth_middleware)),
+    // `events:bulk` is a custom method on the collection, not a child path, so
+    // it sits beside the nest rather than inside it.
+    let bulk = Router::new()
+        .route("/api/v3/events:bulk", post(handlers::events::bulk_events))
+        .layer(middleware::from_fn(auth_middleware));
+    router
+        .nest(
+            "/api/v3/events",
+            routes().layer(middleware::from_fn(auth_middleware)),
+        )
+        .merge(bulk)
+}
+
+/// Event export bundles. Kept out of [`add_event_routes`] so they can be
+/// served without response compression: archives of mp4s and JPEGs gain
+/// nothing from it, and a compressed body would lose its `Content-Length`.
+pub fn add_event_export_routes(router: Router<AppState>) -> Router<AppState> {
+    router.merge(
+        Router::new()
+            .route(
+                "/api/v3/events/export",
+                post(handlers::events::export_events),
+            )
+            .route(
+                "/api/v3/events/export/{job_id}",
+                get(handlers::events::download_event_export),
+            )
+            .layer(middleware::from_fn(auth_middleware)),
     )
 }
 
@@ -20,11 +49,16 @@ pub fn routes() -> Router<AppState> {
         )
         .route(
             "/{id}",
-            // Use fully qualified handler paths
+            // Use fully qualified handler paths. POST is only the
+            // `{id}:restore` custom method; the router cannot match a literal
+            // suffix after a parameter, so the handler splits it off.
             get(handlers::events::get_event)
                 .patch(handlers::events::update_event)
-                .delete(handlers::events::delete_event),
+                .delete(handlers::events::delete_event)
+                .post(handlers::events::restore_event),
         )
+        // Per-event media jobs (transcode, export, move, copy)
+        .route("/{id}/jobs", post(handlers::events::create_event_job))
         // Event counts grouped by hour
         .route("/counts/{hours}", get(handlers::events::get_event_counts))
         // Event counts grouped by monitor (for console view)
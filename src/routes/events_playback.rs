@@ -8,7 +8,7 @@
 
 use axum::{routing::get, Router};
 
-use crate::handlers::{events_playback, synopsis};
+use crate::handlers::{events_playback, previews, synopsis};
 use crate::server::state::AppState;
 use crate::util::middleware::media_auth_middleware;
 
@@ -44,18 +44,46 @@ pub fn add_events_playback_routes(router: Router<AppState>) -> Router<AppState>
             get(events_playback::get_event_segment)
                 .route_layer(axum::middleware::from_fn(media_auth_middleware)),
         )
+        // Continuous playback across a monitor's events in a time range
+        .route(
+            "/api/v3/monitors/{monitor_id}/playback.m3u8",
+            get(events_playback::get_monitor_playback_playlist)
+                .route_layer(axum::middleware::from_fn(media_auth_middleware)),
+        )
         // Direct video access
         .route(
             "/api/v3/events/{id}/video",
             get(events_playback::get_event_video)
                 .route_layer(axum::middleware::from_fn(media_auth_middleware)),
         )
+        // Sub-range clip, remuxed
+        .route(
+            "/api/v3/events/{id}/clip.mp4",
+            get(events_playback::get_event_clip)
+                .route_layer(axum::middleware::from_fn(media_auth_middleware)),
+        )
         // Thumbnail
         .route(
             "/api/v3/events/{id}/thumbnail",
             get(events_playback::get_event_thumbnail)
                 .route_layer(axum::middleware::from_fn(media_auth_middleware)),
         )
+        // Animated previews and contact sheet, rendered and cached on demand
+        .route(
+            "/api/v3/events/{id}/preview.webp",
+            get(previews::get_event_preview_webp)
+                .route_layer(axum::middleware::from_fn(media_auth_middleware)),
+        )
+        .route(
+            "/api/v3/events/{id}/preview.gif",
+            get(previews::get_event_preview_gif)
+                .route_layer(axum::middleware::from_fn(media_auth_middleware)),
+        )
+        .route(
+            "/api/v3/events/{id}/contact-sheet.jpg",
+            get(previews::get_event_contact_sheet)
+                .route_layer(axum::middleware::from_fn(media_auth_middleware)),
+        )
         // Codec / dimensions / duration metadata
         .route(
             "/api/v3/events/{id}/info",
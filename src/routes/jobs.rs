@@ -0,0 +1,11 @@
+use crate::handlers::jobs;
+use crate::server::state::AppState;
+use crate::util::middleware::auth_middleware;
+use axum::{middleware, routing::get, Router};
+
+pub fn add_job_routes(router: Router<AppState>) -> Router<AppState> {
+    let protected = Router::new()
+        .route("/api/v3/jobs/{id}", get(jobs::get_job))
+        .layer(middleware::from_fn(auth_middleware));
+    router.merge(protected)
+}
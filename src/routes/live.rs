@@ -1,9 +1,9 @@
 //! Live streaming routes
 //!
-//! Provides unified routes for live streaming via HLS and WebRTC.
+//! Provides unified routes for live streaming via HLS, WebRTC and WHEP.
 
 use axum::{
-    routing::{delete, get, post},
+    routing::{delete, get, patch, post},
     Router,
 };
 
@@ -23,8 +23,31 @@ pub fn routes() -> Router<AppState> {
         .route("/hls/live.m3u8", get(live::get_live_media_playlist))
         .route("/hls/init.mp4", get(live::get_live_init_segment))
         .route("/hls/{segment}", get(live::get_live_segment))
+        .route(
+            "/hls/{rendition}/live.m3u8",
+            get(live::get_live_rendition_playlist),
+        )
+        .route(
+            "/hls/{rendition}/init.mp4",
+            get(live::get_live_rendition_init_segment),
+        )
+        .route(
+            "/hls/{rendition}/{segment}",
+            get(live::get_live_rendition_segment),
+        )
+        // MJPEG for legacy clients (token query param for <img>)
+        .route(
+            "/mjpeg",
+            get(live::get_live_mjpeg).route_layer(axum::middleware::from_fn(media_auth_middleware)),
+        )
         // WebRTC endpoints (WebSocket signaling)
         .route("/webrtc/ws", get(live::webrtc_websocket_handler))
+        // WHEP: offer/answer over plain HTTP for standard WebRTC players
+        .route("/whep", post(live::whep_offer))
+        .route(
+            "/whep/{session_id}",
+            patch(live::whep_trickle_ice).delete(live::whep_delete),
+        )
 }
 
 /// Add live streaming routes to the router
@@ -47,4 +70,10 @@ pub fn add_live_routes(router: Router<AppState>) -> Router<AppState> {
             get(live::get_monitor_snapshot)
                 .route_layer(axum::middleware::from_fn(media_auth_middleware)),
         )
+        // Monitor lifecycle events as SSE (token query param for EventSource)
+        .route(
+            "/api/v3/monitors/{monitor_id}/events",
+            get(live::stream_monitor_events)
+                .route_layer(axum::middleware::from_fn(media_auth_middleware)),
+        )
 }
@@ -28,6 +28,7 @@ pub mod devices; // Devices
 #[cfg(feature = "onvif-discovery")]
 pub mod discovery; // ONVIF camera discovery
 pub mod event_data; // Event Data
+pub mod event_stream; // Event firehose (SSE / WebSocket)
 pub mod event_summaries; // Event Summaries (pre-calculated counts)
 pub mod events; // Add events module
 pub mod events_playback; // Event playback (video streaming)
@@ -37,6 +38,7 @@ pub mod frames; // Frames
 pub mod groups; // Groups
 pub mod groups_monitors; // Groups Monitors
 pub mod groups_permissions; // Groups Permissions
+pub mod jobs; // Background jobs
 pub mod live; // Live streaming (unified)
 pub mod logs; // Logs
 pub mod manufacturers; // Manufacturers
@@ -46,9 +48,11 @@ pub mod monitor_status; // Monitor Status
 pub mod monitors;
 pub mod monitors_permissions; // Monitors Permissions
 pub mod montage_layouts; // Montage Layouts
+pub mod notification_rules; // Notification rules
 pub mod object_types; // Object Types
 pub mod ptz; // PTZ control
 pub mod reports; // Reports
+pub mod saved_searches; // Saved event searches
 pub mod search; // Natural-language / semantic event search
 pub mod server;
 pub mod server_stats; // Server Stats
@@ -60,6 +64,7 @@ pub mod states; // States
 pub mod stats; // Stats
 pub mod storage; // Storage
 pub mod tags; // Tags
+pub mod timeline; // Per-monitor event timeline
 pub mod triggers_x10; // X10 Triggers
 pub mod user_preferences; // User Preferences
 pub mod users; // Users
@@ -424,6 +429,19 @@ pub fn create_router_app(state: AppState) -> Router {
     );
     let frame_routes = protect(frames::add_frames_routes(Router::new()), Feature::Events);
     let filter_routes = protect(filters::add_filter_routes(Router::new()), Feature::Events);
+    let saved_search_routes = protect(
+        saved_searches::add_saved_search_routes(Router::new()),
+        Feature::Events,
+    );
+    // Jobs today are all bulk event operations, so they share the Events gate;
+    // the service additionally limits a job to its submitter (or System View).
+    let job_routes = protect(jobs::add_job_routes(Router::new()), Feature::Events);
+    // Rules notify about events; `storage_health` rules additionally need
+    // System View, which the service checks.
+    let notification_rule_routes = protect(
+        notification_rules::add_notification_rule_routes(Router::new()),
+        Feature::Events,
+    );
     let tag_routes = protect(tags::add_tag_routes(Router::new()), Feature::Events);
     let object_type_routes = protect(
         object_types::add_object_type_routes(Router::new()),
@@ -433,10 +451,28 @@ pub fn create_router_app(state: AppState) -> Router {
         events_playback::add_events_playback_routes(Router::new()),
         Feature::Events,
     );
+    // Event export bundles, streamed uncompressed. Row-level ACL is applied
+    // per event inside the service via `MonitorScope`.
+    let event_export_routes = protect(
+        events::add_event_export_routes(Router::new()),
+        Feature::Events,
+    );
+    // Cross-monitor event firehose. Row-level ACL is applied per notification
+    // inside the handlers via `MonitorScope`.
+    let event_stream_routes = protect(
+        event_stream::add_event_stream_routes(Router::new()),
+        Feature::Events,
+    );
     // Natural-language / semantic event search. JSON (compressible), so it lives
     // in the `api` group rather than the streaming group. Row-level ACL is
     // enforced inside the handlers via `MonitorScope`.
     let search_routes = protect(search::add_search_routes(Router::new()), Feature::Events);
+    // Per-monitor event timeline. Covers every monitor the caller may view, so
+    // row-level ACL is applied inside the service via `MonitorScope`.
+    let timeline_routes = protect(
+        timeline::add_timeline_routes(Router::new()),
+        Feature::Events,
+    );
 
     let control_routes = protect(
         controls::add_control_routes(Router::new()),
@@ -490,8 +526,9 @@ pub fn create_router_app(state: AppState) -> Router {
     );
 
     // Live streaming serves a monitor named in the path (`{monitor_id}`);
-    // guard it row-level. `/live/sessions` and `/live/sources` have no path
-    // monitor id, so the guard passes them through.
+    // guard it row-level, at `View` for every method (WHEP offers are POSTs).
+    // `/live/sessions` and `/live/sources` have no path monitor id, so the
+    // guard passes them through.
     //
     // Order matters: `protect` must wrap *outside* the row-level guard so the
     // feature-level RBAC check runs first and the guard's DB query is only
@@ -499,7 +536,7 @@ pub fn create_router_app(state: AppState) -> Router {
     let live_routes = protect(
         live::add_live_routes(Router::new()).route_layer(axum::middleware::from_fn_with_state(
             state.clone(),
-            crate::service::monitor_acl::monitor_path_guard,
+            crate::service::monitor_acl::monitor_stream_guard,
         )),
         Feature::Stream,
     );
@@ -534,12 +571,14 @@ pub fn create_router_app(state: AppState) -> Router {
     );
 
     // Streaming endpoints must bypass response compression: they serve
-    // byte-range video, chunked HLS playlists, JPEG snapshots, SSE and
-    // WebSocket upgrades, all of which `CompressionLayer` would buffer,
-    // invalidate (Range) or corrupt.
+    // byte-range video, chunked HLS playlists, JPEG snapshots, SSE,
+    // WebSocket upgrades and export archives, all of which `CompressionLayer`
+    // would buffer, invalidate (Range) or corrupt.
     let streaming = Router::new()
         .merge(live_routes) // Live streaming (unified)
         .merge(events_playback_routes) // Event playback
+        .merge(event_stream_routes) // Event firehose
+        .merge(event_export_routes) // Event export bundles
         .merge(snapshot_routes)
         .merge(snapshot_event_routes);
 
@@ -561,6 +600,9 @@ pub fn create_router_app(state: AppState) -> Router {
         .merge(config_routes)
         .merge(zone_routes)
         .merge(filter_routes)
+        .merge(saved_search_routes)
+        .merge(job_routes)
+        .merge(notification_rule_routes)
         .merge(user_routes)
         .merge(group_routes)
         .merge(server_info_routes)
@@ -593,6 +635,7 @@ pub fn create_router_app(state: AppState) -> Router {
         .merge(event_summary_routes)
         .merge(event_tag_routes)
         .merge(search_routes)
+        .merge(timeline_routes)
         .merge(daemon_routes) // Daemon control
         .merge(ptz_routes); // PTZ control
 
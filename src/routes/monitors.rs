@@ -1,4 +1,4 @@
-use crate::handlers::{monitor, monitor_pipeline};
+use crate::handlers::{monitor, monitor_pipeline, retention};
 use crate::server::state::AppState;
 use crate::util::middleware::auth_middleware;
 use axum::{
@@ -31,6 +31,12 @@ pub fn add_monitor_routes(router: Router<AppState>) -> Router<AppState> {
                 .put(monitor_pipeline::put_monitor_pipeline)
                 .delete(monitor_pipeline::delete_monitor_pipeline),
         )
+        .route(
+            &format!("{}/monitors/{{id}}/retention", api_prefix),
+            get(retention::get_monitor_retention)
+                .put(retention::put_monitor_retention)
+                .delete(retention::delete_monitor_retention),
+        )
         .route(
             &format!("{}/monitors/{{id}}/zmnext", api_prefix),
             post(monitor_pipeline::enable_monitor_zmnext)
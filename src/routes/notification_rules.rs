@@ -0,0 +1,26 @@
+use crate::handlers::notification_rules;
+use crate::server::state::AppState;
+use crate::util::middleware::auth_middleware;
+use axum::{middleware, routing::get, Router};
+
+pub fn add_notification_rule_routes(router: Router<AppState>) -> Router<AppState> {
+    let api_prefix = "/api/v3";
+    let protected = Router::new()
+        .route(
+            &format!("{}/notification-rules", api_prefix),
+            get(notification_rules::list_notification_rules)
+                .post(notification_rules::create_notification_rule),
+        )
+        .route(
+            &format!("{}/notification-rules/{{id}}", api_prefix),
+            get(notification_rules::get_notification_rule)
+                .put(notification_rules::update_notification_rule)
+                .delete(notification_rules::delete_notification_rule),
+        )
+        .route(
+            &format!("{}/notification-rules/{{id}}/deliveries", api_prefix),
+            get(notification_rules::list_notification_deliveries),
+        )
+        .layer(middleware::from_fn(auth_middleware));
+    router.merge(protected)
+}
@@ -160,6 +160,39 @@ pub fn add_ptz_routes(router: Router<AppState>) -> Router<AppState> {
             &format!("{}/ptz/monitors/{{id}}/relative", api_prefix),
             post(ptz::move_relative),
         )
+        // Aiming from the picture
+        .route(
+            &format!("{}/ptz/monitors/{{id}}/point", api_prefix),
+            post(ptz::point),
+        )
+        .route(
+            &format!("{}/ptz/monitors/{{id}}/area", api_prefix),
+            post(ptz::area),
+        )
+        // Control arbitration
+        .route(
+            &format!("{}/ptz/monitors/{{id}}/control", api_prefix),
+            post(ptz::acquire_control).delete(ptz::release_control),
+        )
+        // Tours
+        .route(
+            &format!("{}/ptz/monitors/{{id}}/tours", api_prefix),
+            get(ptz::list_tours).post(ptz::create_tour),
+        )
+        .route(
+            &format!("{}/ptz/monitors/{{id}}/tours/{{tour_id}}", api_prefix),
+            get(ptz::get_tour)
+                .patch(ptz::update_tour)
+                .delete(ptz::delete_tour),
+        )
+        .route(
+            &format!("{}/ptz/monitors/{{id}}/tours/{{tour_id}}/start", api_prefix),
+            post(ptz::start_tour),
+        )
+        .route(
+            &format!("{}/ptz/monitors/{{id}}/tours/{{tour_id}}/stop", api_prefix),
+            post(ptz::stop_tour),
+        )
         .layer(middleware::from_fn(auth_middleware));
 
     router.merge(protected)
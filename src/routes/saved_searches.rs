@@ -0,0 +1,21 @@
+use crate::handlers::saved_searches;
+use crate::server::state::AppState;
+use crate::util::middleware::auth_middleware;
+use axum::{middleware, routing::get, Router};
+
+pub fn add_saved_search_routes(router: Router<AppState>) -> Router<AppState> {
+    let api_prefix = "/api/v3";
+    let protected = Router::new()
+        .route(
+            &format!("{}/saved-searches", api_prefix),
+            get(saved_searches::list_saved_searches).post(saved_searches::create_saved_search),
+        )
+        .route(
+            &format!("{}/saved-searches/{{id}}", api_prefix),
+            get(saved_searches::get_saved_search)
+                .put(saved_searches::update_saved_search)
+                .delete(saved_searches::delete_saved_search),
+        )
+        .layer(middleware::from_fn(auth_middleware));
+    router.merge(protected)
+}
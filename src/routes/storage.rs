@@ -1,4 +1,4 @@
-use crate::handlers::storage;
+use crate::handlers::{retention, storage};
 use crate::server::state::AppState;
 use crate::util::middleware::auth_middleware;
 use axum::{middleware, routing::get, Router};
@@ -12,9 +12,23 @@ pub fn add_storage_routes(router: Router<AppState>) -> Router<AppState> {
         )
         .route(
             &format!("{}/storage/{{id}}", api_prefix),
+            // POST is only the `{id}:reap` custom method; the router cannot
+            // match a literal suffix after a parameter, so the handler splits
+            // it off.
             get(storage::get_storage)
                 .patch(storage::update_storage)
-                .delete(storage::delete_storage),
+                .delete(storage::delete_storage)
+                .post(storage::reap_storage),
+        )
+        .route(
+            &format!("{}/storage/{{id}}/health", api_prefix),
+            get(storage::get_storage_health),
+        )
+        .route(
+            &format!("{}/storage/{{id}}/retention", api_prefix),
+            get(retention::get_storage_retention)
+                .put(retention::put_storage_retention)
+                .delete(retention::delete_storage_retention),
         )
         .layer(middleware::from_fn(auth_middleware));
     router.merge(protected)
@@ -0,0 +1,15 @@
+use axum::{middleware, routing::get, Router};
+
+use crate::util::middleware::auth_middleware;
+use crate::{handlers, server::state::AppState};
+
+/// Create monitor timeline router
+pub fn add_timeline_routes(router: Router<AppState>) -> Router<AppState> {
+    let protected = Router::new()
+        .route(
+            "/api/v3/monitors/timeline",
+            get(handlers::timeline::get_monitor_timeline),
+        )
+        .layer(middleware::from_fn(auth_middleware));
+    router.merge(protected)
+}
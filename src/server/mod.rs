@@ -106,6 +106,21 @@ impl AppServer {
         #[cfg(feature = "onvif-events")]
         self.state.spawn_onvif_event_listeners().await;
 
+        // Re-stream monitors over RTSP (no-op unless [streaming.rtsp_proxy]
+        // is enabled). A bind failure is logged, not fatal: the API still
+        // serves.
+        if let Err(e) = crate::streaming::rtsp::RtspServer::spawn(&self.state).await {
+            tracing::error!("Failed to start the RTSP server: {e}");
+        }
+
+        // Continue any background job a previous run left queued or running,
+        // from its last committed batch.
+        crate::service::jobs::resume_unfinished(&self.state).await;
+
+        // Name any filter still set to AutoExecute (no longer accepted on
+        // create/update) so operators can move it to a webhook.
+        crate::service::filters::warn_auto_execute(&self.state).await;
+
         // Capture the daemon manager before `self.state` is consumed by the
         // router, so managed daemons can be drained after the server exits.
         let daemon_manager = self.state.daemon_manager.clone();
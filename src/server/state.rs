@@ -12,10 +12,20 @@ use crate::constant::ENV_PREFIX;
 use crate::daemon::DaemonManager;
 use crate::error::AppResult;
 use crate::ptz::PtzManager;
+use crate::service::clips::ClipService;
+use crate::service::event_feed::EventFeed;
+use crate::service::media_jobs::MediaJobPool;
+use crate::service::notifications::Notifier;
+use crate::service::previews::PreviewService;
+use crate::service::ptz_control::ControlWatcher;
+use crate::service::ptz_tours::TourRunner;
 use crate::service::search::SearchService;
 use crate::service::synopsis::SynopsisService;
 use crate::streaming::hls::HlsSessionManager;
+use crate::streaming::live::webrtc::WebRtcLiveConfig;
+use crate::streaming::live::whep::WhepSessions;
 use crate::streaming::live::LiveStreamCoordinator;
+use crate::streaming::mjpeg::MjpegService;
 use crate::streaming::snapshot::SnapshotService;
 use crate::streaming::source::SourceRouter;
 use crate::streaming::webrtc::{session::SessionManager, WebRtcEngine};
@@ -33,16 +43,32 @@ pub struct AppState {
     // Live Streaming Coordinator
     pub source_router: Option<Arc<SourceRouter>>,
     pub live_coordinator: Option<Arc<LiveStreamCoordinator>>,
+    // WHEP sessions (standard WebRTC playback over plain HTTP)
+    pub whep_sessions: Option<Arc<WhepSessions>>,
     // Daemon Controller
     pub daemon_manager: Option<Arc<DaemonManager>>,
     // Snapshot Service
     pub snapshot_service: Option<Arc<SnapshotService>>,
+    // MJPEG live feeds for legacy clients
+    pub mjpeg_service: Option<Arc<MjpegService>>,
     // Motion-synopsis renderer/serving
     pub synopsis_service: Option<Arc<SynopsisService>>,
+    // Animated previews and contact sheets for recorded events
+    pub preview_service: Arc<PreviewService>,
+    // Cut cache for event clip downloads
+    pub clip_service: Arc<ClipService>,
     // Natural-language / semantic event search
     pub search_service: Option<Arc<SearchService>>,
+    // Cross-monitor event firehose (event open/close, alarm score, capture faults)
+    pub event_feed: Arc<EventFeed>,
+    // Notification rules engine (event/score/storage triggers → SMTP, webhook, MQTT)
+    pub notifier: Arc<Notifier>,
+    // Worker slots for per-event media jobs (transcode, export, move, copy)
+    pub media_jobs: Arc<MediaJobPool>,
     // PTZ Manager
     pub ptz_manager: Arc<PtzManager>,
+    // Preset tours driving PTZ cameras (on demand or on a weekly schedule)
+    pub ptz_tours: Arc<TourRunner>,
     // Per-user token-revocation floors (hot-path mirror of Users.TokenMinExpiry)
     pub revocations: Arc<crate::util::revocation::TokenRevocations>,
 }
@@ -72,6 +98,27 @@ impl AppState {
             SearchService::new(http.clone(), db.clone(), config.search.clone()).await,
         ));
 
+        // Event firehose bus. Always present (it costs nothing with no
+        // subscribers); publishers below are wired only where they exist.
+        let event_feed = Arc::new(EventFeed::default());
+
+        // Notification rules engine. Off by default → a notifier that drops
+        // every signal, so the event sources below can call it unconditionally.
+        let notifier = Arc::new(if config.notifications.enabled {
+            tracing::info!(
+                "notifications enabled (storage check every {}s)",
+                config.notifications.storage_check_interval_seconds
+            );
+            Notifier::spawn(db.clone(), config.notifications.clone(), http.clone())
+        } else {
+            tracing::info!("notifications disabled in configuration");
+            Notifier::disabled()
+        });
+
+        // Media jobs queue on this pool; the rest of a job's state is in the
+        // `jobs` table, so the pool itself holds nothing worth persisting.
+        let media_jobs = Arc::new(MediaJobPool::new(config.jobs.media_workers));
+
         // Initialize native WebRTC engine (Phase 2). Pass the configured
         // `[streaming.webrtc]` block, not the defaults: the engine turns
         // `stun_servers`/`turn` into its ICE server list, so defaulting here
@@ -125,12 +172,15 @@ impl AppState {
                     config.zmnext.ingest.clone(),
                     config.synopsis.clone(),
                     search_service.clone(),
-                );
+                )
+                .with_event_feed(Arc::clone(&event_feed))
+                .with_notifier(Arc::clone(&notifier));
                 tokio::spawn(ingestor.run(event_rx));
                 tracing::info!("zm-next event ingest enabled");
             }
 
             let router = Arc::new(router);
+            event_feed.spawn_capture_bridge(router.subscribe_all_events());
 
             // Keep configured monitors' readers hot so the first viewer skips
             // cold spin-up (pre-populated keyframe cache → instant codec + a
@@ -156,11 +206,39 @@ impl AppState {
             (None, None)
         };
 
+        // WHEP sessions share one manager (so `max_connections` bounds them
+        // together) and the engine's DTLS certificate.
+        let whep_sessions = match &source_router {
+            Some(router) if config.streaming.webrtc.enabled => {
+                let mut live = WebRtcLiveConfig::from_webrtc_config(&config.streaming.webrtc);
+                live.certificate = native_webrtc_engine
+                    .as_ref()
+                    .map(|engine| engine.certificate().clone());
+                Some(Arc::new(WhepSessions::new(
+                    Arc::clone(router),
+                    live,
+                    Duration::from_secs(config.streaming.webrtc.connection_timeout_seconds),
+                )))
+            }
+            _ => None,
+        };
+
         // Initialize snapshot service (reuses source router)
         let snapshot_service = source_router
             .as_ref()
             .map(|r| Arc::new(SnapshotService::with_defaults(Arc::clone(r))));
 
+        // MJPEG feeds share the source router and, like RTSP, leave readers
+        // running that a live session or prewarming still needs.
+        let mjpeg_service = match &source_router {
+            Some(router) if config.streaming.mjpeg.enabled => Some(Arc::new(MjpegService::new(
+                Arc::clone(router),
+                live_coordinator.clone(),
+                config.streaming.source.prewarm_monitors.clone(),
+            ))),
+            _ => None,
+        };
+
         // Initialize the motion-synopsis service. Always constructed (it only
         // needs the db + config) so the endpoints can report a clear "disabled"
         // status rather than 404 when `[synopsis].enabled` is false.
@@ -180,6 +258,20 @@ impl AppState {
             }
         }
 
+        // Event previews are rendered on demand; cached renders are swept
+        // hourly once older than `[previews].retention_days`.
+        let preview_service = Arc::new(PreviewService::new(config.previews.clone()));
+        if config.previews.retention_days > 0 {
+            Arc::clone(&preview_service).spawn_retention_task(Duration::from_secs(3600));
+        }
+
+        // Event clips are cut on first request and kept for
+        // `[clips].retention_days`.
+        let clip_service = Arc::new(ClipService::new(config.clips.clone()));
+        if config.clips.retention_days > 0 {
+            Arc::clone(&clip_service).spawn_retention_task(Duration::from_secs(3600));
+        }
+
         // Native replacements for the Perl maintenance daemons. Each is
         // independently switchable and all default off, so an existing install
         // keeps running zmstats/zmaudit/zmtelemetry until the operator moves
@@ -250,6 +342,25 @@ impl AppState {
             tracing::info!("event retention disabled in configuration");
         }
 
+        // Purge soft-deleted events once their restore grace period has
+        // passed. On by default; never stored on AppState (restore and delete
+        // only need the config).
+        if config.trash.enabled {
+            let svc = Arc::new(crate::service::trash::TrashReaper::new(
+                db.clone(),
+                config.trash.clone(),
+                config.streaming.zoneminder.events_dir.clone(),
+            ));
+            Arc::clone(&svc).spawn();
+            tracing::info!(
+                "event trash reaper enabled (every {}s, grace {}h)",
+                config.trash.interval_seconds,
+                config.trash.grace_hours,
+            );
+        } else {
+            tracing::warn!("event trash reaper disabled; deleted events keep their disk space");
+        }
+
         // Initialize daemon manager if enabled
         let daemon_manager = if config.daemon.enabled {
             tracing::info!("Daemon controller enabled, initializing manager");
@@ -281,6 +392,44 @@ impl AppState {
         let ptz_manager = Arc::new(PtzManager::with_defaults());
         tracing::info!("PTZ manager initialized");
 
+        // Preset tours. Always constructed so status and CRUD work; the loop
+        // that drives them only runs when enabled.
+        let ptz_tours = Arc::new(TourRunner::new(
+            db.clone(),
+            Arc::clone(&ptz_manager),
+            config.ptz.tours.clone(),
+        ));
+        if config.ptz.tours.enabled {
+            Arc::clone(&ptz_tours).spawn();
+            tracing::info!(
+                "PTZ tours enabled (resume {}s after manual control)",
+                config.ptz.tours.resume_after_seconds
+            );
+        } else {
+            tracing::info!("PTZ tours disabled in configuration");
+        }
+
+        // Control leases are taken inline by PTZ commands; the watcher lets
+        // lapsed ones go and returns idle cameras to their ReturnLocation.
+        let control = &config.ptz.control;
+        if control.lease().is_some() || control.auto_return {
+            Arc::new(ControlWatcher::new(
+                db.clone(),
+                Arc::clone(&ptz_manager),
+                Arc::clone(&ptz_tours),
+                Arc::clone(&event_feed),
+                control.clone(),
+            ))
+            .spawn();
+            tracing::info!(
+                "PTZ control watcher started (lease {}s, auto-return {})",
+                control.lease_seconds,
+                if control.auto_return { "on" } else { "off" }
+            );
+        } else {
+            tracing::info!("PTZ control leases and auto-return disabled in configuration");
+        }
+
         // Hydrate the in-memory token-revocation floors from
         // Users.TokenMinExpiry so logout/password-change revocations survive
         // restarts. Non-fatal: on failure the floors rebuild as revocations
@@ -306,11 +455,19 @@ impl AppState {
             hls_session_manager,
             source_router,
             live_coordinator,
+            whep_sessions,
             snapshot_service,
+            mjpeg_service,
             synopsis_service,
+            preview_service,
+            clip_service,
             search_service,
             daemon_manager,
+            event_feed,
+            notifier,
+            media_jobs,
             ptz_manager,
+            ptz_tours,
             revocations,
         })
     }
@@ -333,9 +490,17 @@ impl AppState {
             db.clone(),
             config.synopsis.clone(),
         )));
+        let preview_service = std::sync::Arc::new(PreviewService::new(config.previews.clone()));
+        let clip_service = std::sync::Arc::new(ClipService::new(config.clips.clone()));
         let search_service = Some(std::sync::Arc::new(SearchService::disabled(
             config.search.clone(),
         )));
+        let ptz_manager = std::sync::Arc::new(PtzManager::with_defaults());
+        let ptz_tours = std::sync::Arc::new(TourRunner::new(
+            db.clone(),
+            std::sync::Arc::clone(&ptz_manager),
+            config.ptz.tours.clone(),
+        ));
         Self {
             config: std::sync::Arc::new(config),
             db,
@@ -345,11 +510,19 @@ impl AppState {
             hls_session_manager: None,
             source_router: None,
             live_coordinator: None,
+            whep_sessions: None,
             snapshot_service: None,
+            mjpeg_service: None,
             synopsis_service,
+            preview_service,
+            clip_service,
             search_service,
             daemon_manager: None,
-            ptz_manager: std::sync::Arc::new(PtzManager::with_defaults()),
+            event_feed: std::sync::Arc::new(EventFeed::default()),
+            notifier: std::sync::Arc::new(Notifier::disabled()),
+            media_jobs: std::sync::Arc::new(MediaJobPool::new(1)),
+            ptz_manager,
+            ptz_tours,
             revocations: std::sync::Arc::new(crate::util::revocation::TokenRevocations::default()),
         }
     }
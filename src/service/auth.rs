@@ -6,7 +6,7 @@ use crate::error::AppResult;
 use crate::error::ToAppResult;
 use crate::repo::users as user;
 use crate::server::state::AppState;
-use crate::service::token;
+use crate::service::{rtsp_credentials, token};
 use crate::util::authz::UserPermissions;
 use crate::util::claim::UserClaims;
 use crate::util::password;
@@ -27,12 +27,20 @@ pub async fn login(state: &AppState, req: LoginRequest) -> AppResult<TokenRespon
     // wall-clock cost on the dummy hash when the user lookup missed, so the
     // "no such user" path doesn't return faster than "wrong password".
     let user_hash = user_opt.as_ref().map(|u| u.password.clone());
-    let password_ok = password::verify_existing_or_dummy(req.password, user_hash).await;
+    let password_ok = password::verify_existing_or_dummy(req.password.clone(), user_hash).await;
 
     let user = match (user_opt, password_ok) {
         (Some(u), true) => u,
         _ => return Err(invalid_credentials()),
     };
+    rtsp_credentials::on_login(
+        state,
+        user.id,
+        &user.username,
+        &user.password,
+        &req.password,
+    )
+    .await;
 
     let perms = UserPermissions::from(&user);
     let resp = token::generate_tokens(user.username, user.id, perms)?;
@@ -94,8 +102,9 @@ pub async fn change_password(
         ));
     }
 
-    let new_hash = password::hash(new_password).await?;
-    user::set_password(&state.db, uid, new_hash).await?;
+    let new_hash = password::hash(new_password.clone()).await?;
+    user::set_password(&state.db, uid, new_hash.clone()).await?;
+    rtsp_credentials::on_password_set(state, uid, &user.username, &new_hash, &new_password).await;
 
     // A password change invalidates every outstanding session.
     logout(state, uid).await?;
@@ -0,0 +1,386 @@
+//! Bulk archive / unarchive / delete over the events matching a filter AST —
+//! the `POST /api/v3/events:bulk` job runner.
+//!
+//! The filter is compiled to the same parameterised `Condition` as the filter
+//! preview (`service::filter_build`) and walked in keyset batches on `Events.Id`
+//! (`Id > cursor ORDER BY Id LIMIT n`). Keyset rather than offset paging keeps
+//! the walk correct while the job archives or deletes what it has visited, and
+//! gives a resume point: the cursor and counters are committed to the job row
+//! after every batch, with a configurable pause between batches
+//! (`[jobs].bulk_batch_pause_ms`).
+//!
+//! The submitter's monitor scope (`Edit` level) is captured with the job, so a
+//! job resumed after a restart acts on exactly the monitors it was allowed at
+//! submission.
+
+use serde::{Deserialize, Serialize};
+use tracing::{info, warn};
+
+use crate::dto::request::bulk_events::{BulkEventAction, BulkEventsRequest};
+use crate::dto::request::filter_ast::FilterQuery;
+use crate::dto::response::jobs::JobResponse;
+use crate::entity::sea_orm_active_enums::JobKind;
+use crate::entity::{events, jobs};
+use crate::error::{AppError, AppResult};
+use crate::repo;
+use crate::repo::events as events_repo;
+use crate::server::state::AppState;
+use crate::service::jobs::{mark_finished, mark_running};
+use crate::service::monitor_acl::MonitorScope;
+use crate::service::saved_searches;
+use crate::util::authz::Level;
+use crate::util::claim::UserClaims;
+
+/// Event ids kept in a job's result (dry-run sample, failures). The counters
+/// are exact; the lists are only for eyeballing.
+const RESULT_ID_LIMIT: usize = 100;
+
+/// What a bulk job was asked to do, stored as the job's `params_json`.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+struct BulkEventsParams {
+    action: BulkEventAction,
+    dry_run: bool,
+    /// The saved search the filter came from, if any (informational — the
+    /// filter itself is snapshotted so later edits do not change the job).
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    saved_search_id: Option<u64>,
+    filter: FilterQuery,
+    /// Monitors the submitter could edit; `None` is unrestricted.
+    #[serde(default)]
+    monitor_ids: Option<Vec<u32>>,
+}
+
+/// Stored as the job's `result_json`.
+#[derive(Debug, Default, Serialize, Deserialize)]
+struct BulkEventsResult {
+    /// Dry run only: the first events that would be changed.
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    sample_event_ids: Vec<u64>,
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    failed_event_ids: Vec<u64>,
+}
+
+impl BulkEventsResult {
+    fn record(list: &mut Vec<u64>, ids: impl IntoIterator<Item = u64>) {
+        let room = RESULT_ID_LIMIT.saturating_sub(list.len());
+        list.extend(ids.into_iter().take(room));
+    }
+}
+
+/// Whether `action` would change `event`. Delete skips archived events (the
+/// same protection the retention reaper and ZoneMinder's UI give them) and
+/// events still being recorded.
+fn eligible(action: BulkEventAction, event: &events::Model) -> bool {
+    match action {
+        BulkEventAction::Archive => event.archived == 0,
+        BulkEventAction::Unarchive => event.archived != 0,
+        BulkEventAction::Delete => event.archived == 0 && event.end_date_time.is_some(),
+    }
+}
+
+/// Validate a bulk request and queue it as a background job.
+pub async fn submit(
+    state: &AppState,
+    req: BulkEventsRequest,
+    claims: &UserClaims,
+    scope: &MonitorScope,
+) -> AppResult<JobResponse> {
+    let filter = match (req.saved_search_id, req.filter) {
+        (Some(id), None) => {
+            let search = saved_searches::find_readable(state, id, claims).await?;
+            saved_searches::parse_ast(&search)?
+        }
+        (None, Some(filter)) => filter,
+        _ => {
+            return Err(AppError::BadRequestError(
+                "exactly one of saved_search_id and filter is required".into(),
+            ))
+        }
+    };
+    // Compile now so a bad filter is a 400, not a failed job.
+    saved_searches::compile(&filter)?;
+
+    let params = BulkEventsParams {
+        action: req.action,
+        dry_run: req.dry_run,
+        saved_search_id: req.saved_search_id,
+        filter,
+        monitor_ids: scope.visible_ids(Level::Edit),
+    };
+    let params_json = serde_json::to_string(&params)
+        .map_err(|e| AppError::BadRequestError(format!("unserializable job parameters: {e}")))?;
+    let now = chrono::Utc::now().naive_utc();
+    let job = repo::jobs::insert(
+        state.db(),
+        JobKind::BulkEvents,
+        Some(claims.uid),
+        None,
+        params_json,
+        now,
+    )
+    .await?;
+    info!(
+        "bulk events job {} queued by user {}: {:?}{}",
+        job.id,
+        claims.uid,
+        params.action,
+        if params.dry_run { " (dry run)" } else { "" }
+    );
+
+    spawn(state.clone(), job.clone());
+    Ok(job.into())
+}
+
+/// Run (or resume) a bulk job in the background.
+pub(crate) fn spawn(state: AppState, job: jobs::Model) {
+    tokio::spawn(async move {
+        let id = job.id;
+        if let Err(e) = run(&state, job).await {
+            // Only reachable when the job row itself cannot be written; the
+            // job stays `running` and is picked up again on the next start.
+            warn!("bulk events job {id}: could not record progress: {e}");
+        }
+    });
+}
+
+async fn run(state: &AppState, mut job: jobs::Model) -> AppResult<()> {
+    let db = state.db();
+    let prepared = serde_json::from_str::<BulkEventsParams>(&job.params_json)
+        .map_err(|e| format!("unreadable job parameters: {e}"))
+        .and_then(|p| {
+            saved_searches::compile(&p.filter)
+                .map(|c| (p, c))
+                .map_err(|e| format!("filter no longer compiles: {e}"))
+        });
+    let (params, condition) = match prepared {
+        Ok(ok) => ok,
+        Err(reason) => {
+            mark_finished(&mut job, Some(reason));
+            repo::jobs::save(db, &job).await?;
+            return Ok(());
+        }
+    };
+    let monitors = params.monitor_ids.as_deref();
+    let batch_size = state.config.jobs.bulk_batch_size.max(1);
+    let pause = state.config.jobs.bulk_batch_pause();
+    let mut result: BulkEventsResult = job
+        .result_json
+        .as_deref()
+        .and_then(|r| serde_json::from_str(r).ok())
+        .unwrap_or_default();
+
+    mark_running(&mut job);
+    if job.total.is_none() {
+        match events_repo::count_with_condition(db, condition.clone(), monitors).await {
+            Ok(total) => job.total = Some(total),
+            Err(e) => warn!("bulk events job {}: count failed: {e}", job.id),
+        }
+    }
+    job = repo::jobs::save(db, &job).await?;
+
+    loop {
+        let batch = match events_repo::find_batch_after(
+            db,
+            condition.clone(),
+            monitors,
+            job.cursor,
+            batch_size,
+        )
+        .await
+        {
+            Ok(batch) => batch,
+            Err(e) => {
+                mark_finished(&mut job, Some(format!("reading events failed: {e}")));
+                repo::jobs::save(db, &job).await?;
+                return Ok(());
+            }
+        };
+        let Some(last) = batch.last() else { break };
+        job.cursor = Some(last.id);
+
+        let targets: Vec<&events::Model> = batch
+            .iter()
+            .filter(|e| eligible(params.action, e))
+            .collect();
+        if params.dry_run {
+            job.affected += targets.len() as u64;
+            BulkEventsResult::record(&mut result.sample_event_ids, targets.iter().map(|e| e.id));
+        } else {
+            let (affected, failed) = apply(state, params.action, &targets).await;
+            job.affected += affected;
+            job.failed += failed.len() as u64;
+            BulkEventsResult::record(&mut result.failed_event_ids, failed);
+        }
+        job.processed += batch.len() as u64;
+        job.result_json = serde_json::to_string(&result).ok();
+        job.updated_at = chrono::Utc::now().naive_utc();
+        job = repo::jobs::save(db, &job).await?;
+
+        if (batch.len() as u64) < batch_size {
+            break;
+        }
+        if !pause.is_zero() {
+            tokio::time::sleep(pause).await;
+        }
+    }
+
+    mark_finished(&mut job, None);
+    repo::jobs::save(db, &job).await?;
+    info!(
+        "bulk events job {} finished: {} processed, {} affected, {} failed",
+        job.id, job.processed, job.affected, job.failed
+    );
+    Ok(())
+}
+
+/// Apply `action` to one batch of eligible events. Returns how many changed
+/// and the ids that failed; a failure is counted, not fatal to the job.
+async fn apply(
+    state: &AppState,
+    action: BulkEventAction,
+    targets: &[&events::Model],
+) -> (u64, Vec<u64>) {
+    let ids: Vec<u64> = targets.iter().map(|e| e.id).collect();
+    match action {
+        BulkEventAction::Archive | BulkEventAction::Unarchive => {
+            let archived = action == BulkEventAction::Archive;
+            match events_repo::set_archived(state.db(), &ids, archived).await {
+                Ok(changed) => (changed, Vec::new()),
+                Err(e) => {
+                    warn!(
+                        "bulk events: setting archived on {} events failed: {e}",
+                        ids.len()
+                    );
+                    (0, ids)
+                }
+            }
+        }
+        BulkEventAction::Delete => {
+            let mut deleted = 0;
+            let mut failed = Vec::new();
+            for event in targets {
+                // The same soft-delete as `DELETE /events/{id}`: tombstoned and
+                // moved to the trash, restorable until the trash reaper runs.
+                match crate::service::trash::trash_event(state, event).await {
+                    Ok(true) => deleted += 1,
+                    Ok(false) => {}
+                    Err(e) => {
+                        warn!("bulk events: deleting event {} failed: {e}", event.id);
+                        failed.push(event.id);
+                    }
+                }
+            }
+            (deleted, failed)
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::util::authz::UserPermissions;
+    use sea_orm::{DatabaseBackend, MockDatabase};
+    use std::time::Duration;
+
+    fn event(archived: u8, finished: bool) -> events::Model {
+        use crate::entity::sea_orm_active_enums::{Orientation, Scheme};
+        use rust_decimal::Decimal;
+        let start = chrono::DateTime::from_timestamp(1_700_000_000, 0).map(|dt| dt.naive_utc());
+        events::Model {
+            id: 1,
+            monitor_id: 1,
+            storage_id: Some(1),
+            secondary_storage_id: None,
+            name: "ev".into(),
+            cause: None,
+            start_date_time: start,
+            end_date_time: if finished { start } else { None },
+            width: 1920,
+            height: 1080,
+            length: Decimal::new(0, 0),
+            frames: Some(0),
+            alarm_frames: Some(0),
+            default_video: "v.mp4".into(),
+            save_jpe_gs: None,
+            tot_score: 0,
+            avg_score: None,
+            max_score: None,
+            max_score_frame_id: None,
+            archived,
+            videoed: 0,
+            uploaded: 0,
+            emailed: 0,
+            messaged: 0,
+            executed: 0,
+            notes: None,
+            state_id: 1,
+            orientation: Orientation::Rotate0,
+            disk_space: None,
+            scheme: Scheme::Deep,
+            locked: 0,
+            latitude: None,
+            longitude: None,
+            deleted_at: None,
+        }
+    }
+
+    #[test]
+    fn eligibility_follows_the_action() {
+        use BulkEventAction::*;
+        assert!(eligible(Archive, &event(0, true)));
+        assert!(!eligible(Archive, &event(1, true)), "already archived");
+        assert!(eligible(Unarchive, &event(1, true)));
+        assert!(!eligible(Unarchive, &event(0, true)));
+
+        assert!(eligible(Delete, &event(0, true)));
+        assert!(
+            !eligible(Delete, &event(1, true)),
+            "archived events are kept"
+        );
+        assert!(!eligible(Delete, &event(0, false)), "still recording");
+    }
+
+    #[test]
+    fn result_id_lists_are_capped() {
+        let mut result = BulkEventsResult::default();
+        BulkEventsResult::record(&mut result.sample_event_ids, 0..60);
+        BulkEventsResult::record(&mut result.sample_event_ids, 60..200);
+        assert_eq!(result.sample_event_ids.len(), RESULT_ID_LIMIT);
+        assert_eq!(result.sample_event_ids.last(), Some(&99));
+
+        let json = serde_json::to_value(&result).unwrap();
+        assert!(
+            json.get("failed_event_ids").is_none(),
+            "empty lists are omitted"
+        );
+    }
+
+    #[tokio::test]
+    async fn submit_requires_exactly_one_event_selector() {
+        let state =
+            AppState::for_test_with_db(MockDatabase::new(DatabaseBackend::MySql).into_connection());
+        let claims = UserClaims::new(
+            Duration::from_secs(600),
+            "user".into(),
+            2,
+            UserPermissions::default(),
+            crate::util::claim::TokenType::Access,
+        );
+        let filter: FilterQuery = serde_json::from_value(
+            serde_json::json!({"where": {"field": "id", "op": "gt", "value": 0}}),
+        )
+        .unwrap();
+        for (saved_search_id, filter) in [(None, None), (Some(1), Some(filter))] {
+            let req = BulkEventsRequest {
+                saved_search_id,
+                filter,
+                action: BulkEventAction::Archive,
+                dry_run: true,
+            };
+            let err = submit(&state, req, &claims, &MonitorScope::All)
+                .await
+                .expect_err("ambiguous selector");
+            assert!(matches!(err, AppError::BadRequestError(_)));
+        }
+    }
+}
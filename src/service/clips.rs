@@ -0,0 +1,308 @@
+//! Cached sub-range clips of recorded events.
+//!
+//! `GET /api/v3/events/{id}/clip.mp4` is cut once (see
+//! [`crate::streaming::clip`]) and the file kept under
+//! `cache_dir/{event_id}/`, keyed by range and container, so a seeking player
+//! or a repeat download reads it instead of cutting again. The lead-in the
+//! cut reported is kept beside it. Like previews, concurrent cuts are capped
+//! by a semaphore, two requests for the same clip wait on one cut, and the
+//! cache is swept by age.
+
+use std::path::{Path, PathBuf};
+use std::sync::Arc;
+use std::time::{Duration, SystemTime};
+
+use dashmap::DashMap;
+use tokio::fs::File;
+use tokio::sync::{Mutex, Semaphore};
+use tracing::{debug, info, warn};
+
+use crate::configure::clips::ClipsConfig;
+use crate::streaming::clip::{self, ClipFormat, ClipRange};
+use crate::streaming::probe::MediaInfo;
+
+/// Errors surfaced by the clip service.
+#[derive(Debug, thiserror::Error)]
+pub enum ClipError {
+    /// Demuxing or muxing the clip failed.
+    #[error("clip failed: {0}")]
+    CutFailed(String),
+    #[error(transparent)]
+    Io(#[from] std::io::Error),
+}
+
+/// A finished clip, open for reading.
+pub struct CachedClip {
+    pub file: File,
+    pub size: u64,
+    /// How far before the requested start the clip opens, on a keyframe.
+    pub lead_in: Duration,
+}
+
+/// Cuts and caches event clips.
+pub struct ClipService {
+    config: ClipsConfig,
+    /// Caps concurrent cuts (`max_concurrent_cuts`); excess queue.
+    cut_slots: Arc<Semaphore>,
+    /// One lock per clip being cut, so concurrent requests for the same clip
+    /// wait for the first cut instead of repeating it.
+    locks: DashMap<PathBuf, Arc<Mutex<()>>>,
+}
+
+impl ClipService {
+    pub fn new(config: ClipsConfig) -> Self {
+        let permits = config.max_concurrent_cuts.max(1);
+        Self {
+            config,
+            cut_slots: Arc::new(Semaphore::new(permits)),
+            locks: DashMap::new(),
+        }
+    }
+
+    pub fn config(&self) -> &ClipsConfig {
+        &self.config
+    }
+
+    /// Serve the cached clip of `range` of event `event_id` in `format`,
+    /// cutting it from `video` first if there is none.
+    pub async fn get(
+        &self,
+        event_id: u64,
+        video: PathBuf,
+        info: MediaInfo,
+        range: ClipRange,
+        format: ClipFormat,
+    ) -> Result<CachedClip, ClipError> {
+        let path = self
+            .config
+            .cache_dir
+            .join(event_id.to_string())
+            .join(clip_file_name(range, format));
+        if let Some(hit) = open_cached(&path).await {
+            return Ok(hit);
+        }
+
+        let lock = self.locks.entry(path.clone()).or_default().clone();
+        let result = {
+            let _guard = lock.lock().await;
+            // Another request may have cut it while we waited.
+            match open_cached(&path).await {
+                Some(hit) => Ok(hit),
+                None => self.cut_to(&path, video, info, range, format).await,
+            }
+        };
+        drop(lock);
+        self.locks
+            .remove_if(&path, |_, l| Arc::strong_count(l) == 1);
+        result
+    }
+
+    /// Cut under a slot into `path`, recording its lead-in beside it.
+    async fn cut_to(
+        &self,
+        path: &Path,
+        video: PathBuf,
+        info: MediaInfo,
+        range: ClipRange,
+        format: ClipFormat,
+    ) -> Result<CachedClip, ClipError> {
+        let _permit = self
+            .cut_slots
+            .acquire()
+            .await
+            .expect("cut semaphore is never closed");
+        debug!("cutting {path:?}");
+        let lead_in = clip::cut(video, info, range, format, path.to_path_buf())
+            .await
+            .map_err(ClipError::CutFailed)?;
+        let sidecar = lead_in_path(path);
+        let tmp = sidecar.with_extension("tmp");
+        tokio::fs::write(&tmp, lead_in.as_millis().to_string()).await?;
+        tokio::fs::rename(&tmp, &sidecar).await?;
+        let file = File::open(path).await?;
+        let size = file.metadata().await?.len();
+        Ok(CachedClip {
+            file,
+            size,
+            lead_in,
+        })
+    }
+
+    /// Run one retention pass: delete cached clips older than
+    /// `retention_days`, then any event directory left empty. Returns the
+    /// number of files removed.
+    pub async fn run_retention_once(&self) -> Result<usize, ClipError> {
+        let max_age = Duration::from_secs(self.config.retention_days * 86_400);
+        let now = SystemTime::now();
+        let mut removed = 0usize;
+
+        let mut events = match tokio::fs::read_dir(&self.config.cache_dir).await {
+            Ok(rd) => rd,
+            Err(e) if e.kind() == std::io::ErrorKind::NotFound => return Ok(0),
+            Err(e) => return Err(e.into()),
+        };
+        while let Some(event_dir) = events.next_entry().await? {
+            if !event_dir.file_type().await?.is_dir() {
+                continue;
+            }
+            let mut files = tokio::fs::read_dir(event_dir.path()).await?;
+            while let Some(file) = files.next_entry().await? {
+                let modified = file.metadata().await?.modified()?;
+                let age = now.duration_since(modified).unwrap_or_default();
+                if age > max_age && remove_all(&file.path()).await {
+                    removed += 1;
+                }
+            }
+            // Fails harmlessly while the directory still holds clips.
+            let _ = tokio::fs::remove_dir(event_dir.path()).await;
+        }
+        if removed > 0 {
+            info!("clip retention: removed {removed} cached clips");
+        }
+        Ok(removed)
+    }
+
+    /// Spawn the periodic retention loop. A failed pass is logged and retried
+    /// on the next tick.
+    pub fn spawn_retention_task(self: Arc<Self>, interval: Duration) {
+        tokio::spawn(async move {
+            let mut ticker = tokio::time::interval(interval);
+            loop {
+                ticker.tick().await;
+                if let Err(e) = self.run_retention_once().await {
+                    warn!("clip retention pass failed: {e}");
+                }
+            }
+        });
+    }
+}
+
+/// Cache file name within the event's directory: the range to the
+/// millisecond and the container.
+fn clip_file_name(range: ClipRange, format: ClipFormat) -> String {
+    format!(
+        "clip-{}-{}.{}",
+        range.start.as_millis(),
+        range.end.as_millis(),
+        format.extension()
+    )
+}
+
+fn lead_in_path(clip: &Path) -> PathBuf {
+    clip.with_extension(format!(
+        "{}.lead-in",
+        clip.extension()
+            .and_then(|e| e.to_str())
+            .unwrap_or_default()
+    ))
+}
+
+/// The cached clip at `path` and its lead-in, if both are there. The lead-in
+/// is written last, so a clip without one is treated as not yet cached.
+async fn open_cached(path: &Path) -> Option<CachedClip> {
+    let lead_in = tokio::fs::read_to_string(lead_in_path(path)).await.ok()?;
+    let lead_in = Duration::from_millis(lead_in.trim().parse().ok()?);
+    let file = File::open(path).await.ok()?;
+    let size = file.metadata().await.ok()?.len();
+    Some(CachedClip {
+        file,
+        size,
+        lead_in,
+    })
+}
+
+/// Remove a cached file, or the temporary directory of a cut that never
+/// finished.
+async fn remove_all(path: &Path) -> bool {
+    match tokio::fs::metadata(path).await {
+        Ok(m) if m.is_dir() => tokio::fs::remove_dir_all(path).await.is_ok(),
+        Ok(_) => tokio::fs::remove_file(path).await.is_ok(),
+        Err(_) => false,
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn range(start: f64, end: f64) -> ClipRange {
+        ClipRange::new(start, end, 600.0, 0).unwrap()
+    }
+
+    #[test]
+    fn cache_name_covers_range_and_container() {
+        assert_eq!(
+            clip_file_name(range(12.5, 32.0), ClipFormat::Mp4),
+            "clip-12500-32000.mp4"
+        );
+        assert_ne!(
+            clip_file_name(range(12.5, 32.0), ClipFormat::Mp4),
+            clip_file_name(range(12.5, 32.0), ClipFormat::Mkv)
+        );
+        assert_eq!(
+            lead_in_path(Path::new("/c/7/clip-0-1000.mkv")),
+            PathBuf::from("/c/7/clip-0-1000.mkv.lead-in")
+        );
+    }
+
+    #[tokio::test]
+    async fn cached_clip_is_served_without_cutting() {
+        let dir = tempfile::tempdir().unwrap();
+        let service = ClipService::new(ClipsConfig {
+            cache_dir: dir.path().to_path_buf(),
+            ..Default::default()
+        });
+        let r = range(10.0, 20.0);
+        let event_dir = dir.path().join("7");
+        std::fs::create_dir_all(&event_dir).unwrap();
+        let clip = event_dir.join(clip_file_name(r, ClipFormat::Mp4));
+        std::fs::write(&clip, b"fmp4").unwrap();
+        std::fs::write(lead_in_path(&clip), "1250").unwrap();
+
+        let info = MediaInfo {
+            codec: crate::streaming::source::VideoCodec::H264,
+            width: 640,
+            height: 480,
+            duration_seconds: 600.0,
+        };
+        // The source does not exist, so a cut would fail.
+        let hit = service
+            .get(
+                7,
+                PathBuf::from("/nonexistent.mp4"),
+                info,
+                r,
+                ClipFormat::Mp4,
+            )
+            .await
+            .unwrap();
+        assert_eq!(hit.size, 4);
+        assert_eq!(hit.lead_in, Duration::from_millis(1250));
+    }
+
+    #[tokio::test]
+    async fn retention_removes_old_clips_and_empty_dirs() {
+        let dir = tempfile::tempdir().unwrap();
+        let event_dir = dir.path().join("7");
+        std::fs::create_dir_all(&event_dir).unwrap();
+        let two_days_ago = SystemTime::now() - Duration::from_secs(2 * 86_400);
+        for name in ["clip-0-1000.mp4", "clip-0-1000.mp4.lead-in"] {
+            let path = event_dir.join(name);
+            std::fs::write(&path, b"x").unwrap();
+            std::fs::File::options()
+                .write(true)
+                .open(&path)
+                .unwrap()
+                .set_modified(two_days_ago)
+                .unwrap();
+        }
+
+        let service = ClipService::new(ClipsConfig {
+            cache_dir: dir.path().to_path_buf(),
+            retention_days: 1,
+            ..Default::default()
+        });
+        assert_eq!(service.run_retention_once().await.unwrap(), 2);
+        assert!(!event_dir.exists());
+    }
+}
@@ -0,0 +1,342 @@
+//! Streaming zip and tar writers for export bundles.
+//!
+//! Both formats are written strictly front to back — zip entries are stored
+//! with data descriptors, tar entries with their size up front — so a bundle
+//! can go to a client, or be tailed from disk, while it is still being built.
+//! Every entry is hashed with SHA-256 on its way into the archive, for the
+//! manifest.
+
+use std::io;
+use std::pin::Pin;
+use std::task::{Context, Poll};
+
+use async_zip::base::write::ZipFileWriter;
+use async_zip::{Compression, ZipDateTime, ZipEntryBuilder};
+use chrono::{DateTime, Utc};
+use futures::io::AsyncWriteExt as _;
+use sha2::{Digest, Sha256};
+use tokio::io::{AsyncRead, AsyncReadExt, AsyncWrite, ReadBuf};
+use tokio_tar::{EntryType, Header};
+
+use crate::dto::request::event_export::ExportFormat;
+
+/// Read size when copying a file into the archive.
+const COPY_CHUNK: usize = 64 * 1024;
+
+/// Permissions recorded for every entry.
+const ENTRY_MODE: u16 = 0o644;
+
+/// What one entry holds, as recorded in the manifest.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct EntryDigest {
+    pub size: u64,
+    /// Lower-case hex.
+    pub sha256: String,
+}
+
+/// Wraps a reader or writer and hashes every byte passing through.
+pub struct Hashed<T> {
+    inner: T,
+    hasher: Sha256,
+    len: u64,
+}
+
+impl<T> Hashed<T> {
+    pub fn new(inner: T) -> Self {
+        Self {
+            inner,
+            hasher: Sha256::new(),
+            len: 0,
+        }
+    }
+
+    pub fn get_mut(&mut self) -> &mut T {
+        &mut self.inner
+    }
+
+    /// The bytes seen so far and their hash.
+    pub fn finish(self) -> (T, EntryDigest) {
+        let digest = EntryDigest {
+            size: self.len,
+            sha256: hex::encode(self.hasher.finalize()),
+        };
+        (self.inner, digest)
+    }
+}
+
+impl<T: AsyncRead + Unpin> AsyncRead for Hashed<T> {
+    fn poll_read(
+        mut self: Pin<&mut Self>,
+        cx: &mut Context<'_>,
+        buf: &mut ReadBuf<'_>,
+    ) -> Poll<io::Result<()>> {
+        let before = buf.filled().len();
+        let polled = Pin::new(&mut self.inner).poll_read(cx, buf);
+        if let Poll::Ready(Ok(())) = polled {
+            let read = &buf.filled()[before..];
+            self.hasher.update(read);
+            self.len += read.len() as u64;
+        }
+        polled
+    }
+}
+
+impl<T: AsyncWrite + Unpin> AsyncWrite for Hashed<T> {
+    fn poll_write(
+        mut self: Pin<&mut Self>,
+        cx: &mut Context<'_>,
+        buf: &[u8],
+    ) -> Poll<io::Result<usize>> {
+        let polled = Pin::new(&mut self.inner).poll_write(cx, buf);
+        if let Poll::Ready(Ok(written)) = polled {
+            self.hasher.update(&buf[..written]);
+            self.len += written as u64;
+        }
+        polled
+    }
+
+    fn poll_flush(mut self: Pin<&mut Self>, cx: &mut Context<'_>) -> Poll<io::Result<()>> {
+        Pin::new(&mut self.inner).poll_flush(cx)
+    }
+
+    fn poll_shutdown(mut self: Pin<&mut Self>, cx: &mut Context<'_>) -> Poll<io::Result<()>> {
+        Pin::new(&mut self.inner).poll_shutdown(cx)
+    }
+}
+
+enum Inner<W: AsyncWrite + Unpin + Send> {
+    Zip(async_zip::tokio::write::ZipFileWriter<W>),
+    Tar(tokio_tar::Builder<W>),
+}
+
+/// A zip or tar archive being written to `W`.
+pub struct ArchiveWriter<W: AsyncWrite + Unpin + Send> {
+    inner: Inner<W>,
+}
+
+fn zip_error(e: async_zip::error::ZipError) -> io::Error {
+    io::Error::other(e)
+}
+
+fn tar_header(size: u64, mtime: DateTime<Utc>) -> Header {
+    let mut header = Header::new_gnu();
+    header.set_entry_type(EntryType::Regular);
+    header.set_size(size);
+    header.set_mode(u32::from(ENTRY_MODE));
+    header.set_mtime(mtime.timestamp().max(0) as u64);
+    header
+}
+
+fn zip_entry(name: &str, mtime: DateTime<Utc>) -> ZipEntryBuilder {
+    ZipEntryBuilder::new(name.to_string().into(), Compression::Stored)
+        .last_modification_date(ZipDateTime::from_chrono(&mtime))
+        .unix_permissions(ENTRY_MODE)
+}
+
+impl<W: AsyncWrite + Unpin + Send> ArchiveWriter<W> {
+    pub fn new(format: ExportFormat, writer: W) -> Self {
+        let inner = match format {
+            ExportFormat::Zip => Inner::Zip(ZipFileWriter::with_tokio(writer)),
+            // Non-terminated: a bundle that fails half way must not end in
+            // the end-of-archive marker and pass for a complete one.
+            ExportFormat::Tar => Inner::Tar(tokio_tar::Builder::new_non_terminated(writer)),
+        };
+        Self { inner }
+    }
+
+    /// Add an in-memory entry.
+    pub async fn add_bytes(
+        &mut self,
+        name: &str,
+        data: &[u8],
+        mtime: DateTime<Utc>,
+    ) -> io::Result<EntryDigest> {
+        match &mut self.inner {
+            Inner::Zip(zip) => zip
+                .write_entry_whole(zip_entry(name, mtime), data)
+                .await
+                .map_err(zip_error)?,
+            Inner::Tar(tar) => {
+                let mut header = tar_header(data.len() as u64, mtime);
+                tar.append_data(&mut header, name, data).await?;
+            }
+        }
+        Ok(EntryDigest {
+            size: data.len() as u64,
+            sha256: hex::encode(Sha256::digest(data)),
+        })
+    }
+
+    /// Copy a file into the archive. Only the `size` bytes it held when it
+    /// was opened are taken, so a file still being written cannot overrun its
+    /// entry.
+    pub async fn add_file(
+        &mut self,
+        name: &str,
+        file: tokio::fs::File,
+        size: u64,
+        mtime: DateTime<Utc>,
+    ) -> io::Result<EntryDigest> {
+        let mut source = Hashed::new(file.take(size));
+        match &mut self.inner {
+            Inner::Zip(zip) => {
+                let mut entry = zip
+                    .write_entry_stream(zip_entry(name, mtime))
+                    .await
+                    .map_err(zip_error)?;
+                let mut buf = vec![0u8; COPY_CHUNK];
+                loop {
+                    let n = source.read(&mut buf).await?;
+                    if n == 0 {
+                        break;
+                    }
+                    entry.write_all(&buf[..n]).await?;
+                }
+                entry.close().await.map_err(zip_error)?;
+            }
+            Inner::Tar(tar) => {
+                let mut header = tar_header(size, mtime);
+                tar.append_data(&mut header, name, &mut source).await?;
+            }
+        }
+        let (_, digest) = source.finish();
+        if digest.size != size {
+            // The tar header already promised `size` bytes; the archive is no
+            // longer well formed.
+            return Err(io::Error::new(
+                io::ErrorKind::UnexpectedEof,
+                format!("{name} shrank from {size} to {} bytes", digest.size),
+            ));
+        }
+        Ok(digest)
+    }
+
+    /// Write the archive trailer and hand back the writer.
+    pub async fn finish(self) -> io::Result<W> {
+        match self.inner {
+            Inner::Zip(zip) => Ok(zip.close().await.map_err(zip_error)?.into_inner()),
+            Inner::Tar(mut tar) => {
+                tar.finish().await?;
+                tar.into_inner().await
+            }
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use futures::StreamExt;
+    use tokio::io::AsyncWriteExt;
+
+    fn mtime() -> DateTime<Utc> {
+        DateTime::from_timestamp(1_760_000_000, 0).unwrap()
+    }
+
+    async fn temp_file(contents: &[u8]) -> (tempfile::TempDir, tokio::fs::File) {
+        let dir = tempfile::tempdir().unwrap();
+        let path = dir.path().join("clip.mp4");
+        tokio::fs::write(&path, contents).await.unwrap();
+        let file = tokio::fs::File::open(&path).await.unwrap();
+        (dir, file)
+    }
+
+    #[tokio::test]
+    async fn test_tar_round_trip_and_digests() {
+        let (_dir, file) = temp_file(b"not really a video").await;
+        let mut archive = ArchiveWriter::new(ExportFormat::Tar, Vec::new());
+        let json = archive
+            .add_bytes("event-7/event.json", b"{}", mtime())
+            .await
+            .unwrap();
+        let clip = archive
+            .add_file("event-7/clip.mp4", file, 18, mtime())
+            .await
+            .unwrap();
+        let bytes = archive.finish().await.unwrap();
+
+        assert_eq!(json.size, 2);
+        assert_eq!(
+            json.sha256,
+            "44136fa355b3678a1146ad16f7e8649e94fb4fc21fe77e8310c060f61caaff8a"
+        );
+        assert_eq!(clip.size, 18);
+        assert_eq!(
+            clip.sha256,
+            hex::encode(Sha256::digest(b"not really a video"))
+        );
+
+        let mut entries = tokio_tar::Archive::new(bytes.as_slice()).entries().unwrap();
+        let mut names = Vec::new();
+        while let Some(entry) = entries.next().await {
+            let mut entry = entry.unwrap();
+            names.push(entry.path().unwrap().display().to_string());
+            assert_eq!(entry.header().mtime().unwrap(), 1_760_000_000);
+            let mut body = Vec::new();
+            entry.read_to_end(&mut body).await.unwrap();
+            assert_eq!(body.len() as u64, entry.header().size().unwrap());
+        }
+        assert_eq!(names, ["event-7/event.json", "event-7/clip.mp4"]);
+    }
+
+    #[tokio::test]
+    async fn test_zip_is_readable() {
+        let (_dir, file) = temp_file(b"0123456789").await;
+        let mut archive = ArchiveWriter::new(ExportFormat::Zip, Vec::new());
+        archive
+            .add_bytes("manifest.json", b"[]", mtime())
+            .await
+            .unwrap();
+        archive
+            .add_file("event-1/clip.mp4", file, 10, mtime())
+            .await
+            .unwrap();
+        let bytes = archive.finish().await.unwrap();
+
+        let zip = async_zip::base::read::mem::ZipFileReader::new(bytes)
+            .await
+            .unwrap();
+        let names: Vec<String> = zip
+            .file()
+            .entries()
+            .iter()
+            .map(|e| e.filename().as_str().unwrap().to_string())
+            .collect();
+        assert_eq!(names, ["manifest.json", "event-1/clip.mp4"]);
+        assert_eq!(zip.file().entries()[1].uncompressed_size(), 10);
+    }
+
+    #[tokio::test]
+    async fn test_growing_file_is_cut_at_opened_size() {
+        let (_dir, file) = temp_file(b"0123456789").await;
+        let mut archive = ArchiveWriter::new(ExportFormat::Tar, Vec::new());
+        let digest = archive
+            .add_file("clip.mp4", file, 4, mtime())
+            .await
+            .unwrap();
+        assert_eq!(digest.sha256, hex::encode(Sha256::digest(b"0123")));
+    }
+
+    #[tokio::test]
+    async fn test_shrunk_file_fails_the_entry() {
+        let (_dir, file) = temp_file(b"0123").await;
+        let mut archive = ArchiveWriter::new(ExportFormat::Zip, Vec::new());
+        let err = archive
+            .add_file("clip.mp4", file, 10, mtime())
+            .await
+            .unwrap_err();
+        assert_eq!(err.kind(), io::ErrorKind::UnexpectedEof);
+    }
+
+    #[tokio::test]
+    async fn test_hashed_writer_digests_what_was_written() {
+        let mut writer = Hashed::new(Vec::new());
+        writer.write_all(b"abc").await.unwrap();
+        let (inner, digest) = writer.finish();
+        assert_eq!(inner, b"abc");
+        assert_eq!(
+            digest.sha256,
+            "ba7816bf8f01cfea414140de5dae2223b00361a396177a9cb410ff61f20015ad"
+        );
+    }
+}
@@ -0,0 +1,918 @@
+//! Event export bundles — `POST /api/v3/events/export`.
+//!
+//! The evidence hand-over: one zip or tar holding, for every selected event,
+//! its recording (resolved the way playback resolves it), the alarm frame
+//! JPEGs, its `Frames`, `Stats` and tags as JSON and its synopsis mp4 when one
+//! has been rendered, followed by a `manifest.json` that lists every file with
+//! its size and SHA-256 for chain of custody.
+//!
+//! Selections of up to `[jobs].export_inline_max_events` events are built
+//! while they are sent. Larger ones become a job in the shared `jobs` table
+//! that writes `{export_dir}/bundle-{job_id}.{zip|tar}`; downloading it follows
+//! the file as the job appends to it, so the hand-over can begin before the
+//! bundle is complete. Like a media job, an export interrupted by a restart is
+//! rebuilt from the top.
+
+mod archive;
+
+use std::collections::HashSet;
+use std::io;
+use std::path::{Path, PathBuf};
+use std::time::Duration;
+
+use bytes::Bytes;
+use chrono::{DateTime, Utc};
+use futures::stream::{self, BoxStream, Stream, StreamExt};
+use sea_orm::{ColumnTrait, Condition};
+use serde::{Deserialize, Serialize};
+use tokio::io::{AsyncReadExt, AsyncWrite, AsyncWriteExt, BufWriter};
+use tokio_util::io::ReaderStream;
+use tracing::{info, warn};
+
+use crate::dto::request::event_export::{EventExportRequest, ExportFormat};
+use crate::dto::request::filter_ast::FilterQuery;
+use crate::dto::response::events::EventResponse;
+use crate::dto::response::events_tags::TagSummary;
+use crate::dto::response::frames::FrameResponse;
+use crate::dto::response::jobs::JobResponse;
+use crate::dto::response::stats::StatResponse;
+use crate::entity::sea_orm_active_enums::{FrameType, JobKind, JobStatus};
+use crate::entity::{events, jobs, tags};
+use crate::error::{AppError, AppResult, Resource, ResourceType};
+use crate::handlers::events_playback::{event_directory, get_event_media_path};
+use crate::repo;
+use crate::repo::events as events_repo;
+use crate::server::state::AppState;
+use crate::service::filters::{can_view_all, owns};
+use crate::service::jobs::{mark_finished, mark_running};
+use crate::service::monitor_acl::MonitorScope;
+use crate::service::saved_searches;
+use crate::util::authz::Level;
+use crate::util::claim::UserClaims;
+
+use archive::{ArchiveWriter, Hashed};
+
+/// Most events one request may list by id.
+const MAX_EVENT_IDS: usize = 1000;
+
+/// Events read per batch, and per progress commit of a job.
+const BATCH_SIZE: u64 = 50;
+
+/// Event ids kept in a job's result. The manifest lists them all.
+const RESULT_ID_LIMIT: usize = 100;
+
+/// Pipe between an inline bundle's builder and the response body.
+const PIPE_BUFFER: usize = 256 * 1024;
+
+/// How long a download that has caught up with its job waits before looking
+/// for more.
+const FOLLOW_POLL: Duration = Duration::from_millis(500);
+
+/// Read size when sending a bundle from disk.
+const FOLLOW_CHUNK: usize = 64 * 1024;
+
+/// The last entry of every bundle.
+const MANIFEST: &str = "manifest.json";
+
+/// What to export, stored as the job's `params_json`.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+struct ExportParams {
+    format: ExportFormat,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    event_ids: Option<Vec<u64>>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    filter: Option<FilterQuery>,
+    /// Monitors the requester could view; `None` is unrestricted.
+    #[serde(default)]
+    monitor_ids: Option<Vec<u32>>,
+    /// Who asked, for the manifest.
+    requested_by: String,
+}
+
+impl ExportParams {
+    fn condition(&self) -> AppResult<Condition> {
+        match (&self.event_ids, &self.filter) {
+            (Some(ids), None) => {
+                Ok(Condition::all().add(events::Column::Id.is_in(ids.iter().copied())))
+            }
+            (None, Some(filter)) => saved_searches::compile(filter),
+            _ => Err(AppError::BadRequestError(
+                "exactly one of event_ids and filter is required".into(),
+            )),
+        }
+    }
+
+    /// The requester's monitor scope, as captured at submission.
+    fn scope(&self) -> MonitorScope {
+        match &self.monitor_ids {
+            None => MonitorScope::All,
+            Some(ids) => {
+                MonitorScope::Restricted(ids.iter().map(|&id| (id, Level::View)).collect())
+            }
+        }
+    }
+}
+
+/// Stored as the job's `result_json`.
+#[derive(Debug, Default, Serialize, Deserialize)]
+struct ExportResult {
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    path: Option<String>,
+    #[serde(default)]
+    bytes: u64,
+    /// SHA-256 of the whole archive, for the receiving side to check.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    sha256: Option<String>,
+    /// Events whose files are incomplete; the manifest says why.
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    failed_event_ids: Vec<u64>,
+}
+
+/// `manifest.json`.
+#[derive(Debug, Serialize)]
+struct Manifest {
+    generator: String,
+    created_at: String,
+    requested_by: String,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    job_id: Option<u64>,
+    /// The filter the events were selected by, if not by id.
+    #[serde(skip_serializing_if = "Option::is_none")]
+    filter: Option<FilterQuery>,
+    events: Vec<ManifestEvent>,
+    files: Vec<ManifestFile>,
+}
+
+#[derive(Debug, Serialize)]
+struct ManifestEvent {
+    id: u64,
+    monitor_id: u32,
+    /// Why some of the event's files are missing from the bundle.
+    #[serde(skip_serializing_if = "Vec::is_empty")]
+    problems: Vec<String>,
+}
+
+#[derive(Debug, Serialize)]
+struct ManifestFile {
+    path: String,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    event_id: Option<u64>,
+    size: u64,
+    sha256: String,
+    /// Where the file was copied from; absent for generated JSON.
+    #[serde(skip_serializing_if = "Option::is_none")]
+    source: Option<String>,
+}
+
+/// A bundle on its way to the client.
+pub struct BundleStream {
+    pub filename: String,
+    pub content_type: &'static str,
+    /// Known only once the bundle is complete.
+    pub content_length: Option<u64>,
+    /// Ends in an error, rather than early, if the bundle cannot be finished,
+    /// so a client never mistakes a short archive for a whole one.
+    pub body: BoxStream<'static, io::Result<Bytes>>,
+}
+
+/// How an export request is answered.
+pub enum Export {
+    /// Few enough events to build while sending.
+    Inline(BundleStream),
+    /// Built by a background job; download it from
+    /// `GET /api/v3/events/export/{job_id}`.
+    Queued(JobResponse),
+}
+
+fn events_not_found(ids: &[u64]) -> AppError {
+    let ids: Vec<String> = ids.iter().map(u64::to_string).collect();
+    AppError::NotFoundError(Resource {
+        resource_type: ResourceType::Event,
+        details: vec![("event_ids".to_string(), ids.join(","))],
+    })
+}
+
+/// Not-found for missing jobs, other kinds of job and other users' jobs
+/// alike, as in `service::jobs`.
+fn job_not_found(id: u64) -> AppError {
+    AppError::NotFoundError(Resource {
+        resource_type: ResourceType::Message,
+        details: vec![("id".to_string(), id.to_string())],
+    })
+}
+
+fn bundle_path(export_dir: &Path, job_id: u64, format: ExportFormat) -> PathBuf {
+    export_dir.join(format!("bundle-{job_id}.{}", format.extension()))
+}
+
+/// Validate an export request, then either start streaming the bundle or
+/// queue it as a job.
+pub async fn submit(
+    state: &AppState,
+    req: EventExportRequest,
+    claims: &UserClaims,
+    scope: &MonitorScope,
+) -> AppResult<Export> {
+    let event_ids = match req.event_ids {
+        Some(mut ids) => {
+            ids.sort_unstable();
+            ids.dedup();
+            if ids.is_empty() || ids.len() > MAX_EVENT_IDS {
+                return Err(AppError::BadRequestError(format!(
+                    "event_ids must list between 1 and {MAX_EVENT_IDS} events"
+                )));
+            }
+            Some(ids)
+        }
+        None => None,
+    };
+    let params = ExportParams {
+        format: req.format,
+        event_ids,
+        filter: req.filter,
+        monitor_ids: scope.visible_ids(Level::View),
+        requested_by: claims.user.clone(),
+    };
+    // Compile now so a bad filter is a 400, not a failed job.
+    let condition = params.condition()?;
+    let monitors = params.monitor_ids.as_deref();
+
+    let total = match &params.event_ids {
+        Some(ids) => {
+            // Every listed event must exist and be visible; an export quietly
+            // missing one would not be the evidence that was asked for.
+            let found: HashSet<u64> = events_repo::find_batch_after(
+                state.db(),
+                condition,
+                monitors,
+                None,
+                ids.len() as u64,
+            )
+            .await?
+            .into_iter()
+            .map(|e| e.id)
+            .collect();
+            let missing: Vec<u64> = ids
+                .iter()
+                .copied()
+                .filter(|id| !found.contains(id))
+                .collect();
+            if !missing.is_empty() {
+                return Err(events_not_found(&missing));
+            }
+            ids.len() as u64
+        }
+        None => events_repo::count_with_condition(state.db(), condition, monitors).await?,
+    };
+    if total == 0 {
+        return Err(AppError::BadRequestError(
+            "the filter matches no events".into(),
+        ));
+    }
+
+    if total <= state.config.jobs.export_inline_max_events {
+        info!(
+            "event export of {total} event(s) streamed to user {}",
+            claims.uid
+        );
+        return Ok(Export::Inline(stream_inline(state.clone(), params)));
+    }
+
+    let params_json = serde_json::to_string(&params)
+        .map_err(|e| AppError::BadRequestError(format!("unserializable job parameters: {e}")))?;
+    let now = chrono::Utc::now().naive_utc();
+    let mut job = repo::jobs::insert(
+        state.db(),
+        JobKind::EventsExport,
+        Some(claims.uid),
+        None,
+        params_json,
+        now,
+    )
+    .await?;
+    job.total = Some(total);
+    let job = repo::jobs::save(state.db(), &job).await?;
+    info!(
+        "event export job {} queued by user {}: {total} events",
+        job.id, claims.uid
+    );
+
+    spawn(state.clone(), job.clone());
+    Ok(Export::Queued(job.into()))
+}
+
+/// Build a bundle straight into the response body.
+fn stream_inline(state: AppState, params: ExportParams) -> BundleStream {
+    let format = params.format;
+    let (writer, reader) = tokio::io::duplex(PIPE_BUFFER);
+    let builder = tokio::spawn(async move {
+        let (mut writer, _) = build(&state, &params, None, writer).await?;
+        writer
+            .shutdown()
+            .await
+            .map_err(|e| format!("writing the archive failed: {e}"))
+    });
+
+    // The pipe closes when the builder stops, finished or not; only its
+    // outcome tells the two apart.
+    let outcome = stream::once(async move {
+        match builder.await {
+            Ok(Ok(())) => None,
+            Ok(Err(reason)) => {
+                warn!("event export failed: {reason}");
+                Some(Err(io::Error::other(reason)))
+            }
+            Err(e) => Some(Err(io::Error::other(e))),
+        }
+    })
+    .filter_map(futures::future::ready);
+
+    BundleStream {
+        filename: format!(
+            "zm-export-{}.{}",
+            Utc::now().format("%Y%m%dT%H%M%SZ"),
+            format.extension()
+        ),
+        content_type: format.content_type(),
+        content_length: None,
+        body: ReaderStream::new(reader).chain(outcome).boxed(),
+    }
+}
+
+/// Run (or rerun) an export job in the background.
+pub(crate) fn spawn(state: AppState, job: jobs::Model) {
+    tokio::spawn(async move {
+        let id = job.id;
+        if let Err(e) = run(&state, job).await {
+            // Only reachable when the job row itself cannot be written; the
+            // job stays unfinished and is rebuilt on the next start.
+            warn!("event export job {id}: could not record progress: {e}");
+        }
+    });
+}
+
+async fn run(state: &AppState, mut job: jobs::Model) -> AppResult<()> {
+    let db = state.db();
+    let params = match serde_json::from_str::<ExportParams>(&job.params_json) {
+        Ok(params) => params,
+        Err(e) => {
+            mark_finished(&mut job, Some(format!("unreadable job parameters: {e}")));
+            repo::jobs::save(db, &job).await?;
+            return Ok(());
+        }
+    };
+    // A resumed export starts over, so its counters do too.
+    mark_running(&mut job);
+    job.cursor = None;
+    job.processed = 0;
+    job.affected = 0;
+    job.failed = 0;
+    job = repo::jobs::save(db, &job).await?;
+
+    let path = bundle_path(&state.config.jobs.export_dir, job.id, params.format);
+    let error = match write_bundle(state, &params, &mut job, &path).await {
+        Ok(result) => {
+            job.result_json = serde_json::to_string(&result).ok();
+            None
+        }
+        Err(reason) => {
+            // Leave nothing behind that could pass for a complete bundle.
+            if let Err(e) = tokio::fs::remove_file(&path).await {
+                if e.kind() != io::ErrorKind::NotFound {
+                    warn!(
+                        "event export job {}: removing {}: {e}",
+                        job.id,
+                        path.display()
+                    );
+                }
+            }
+            Some(reason)
+        }
+    };
+    mark_finished(&mut job, error);
+    repo::jobs::save(db, &job).await?;
+    info!(
+        "event export job {} finished: {} events, {} incomplete: {}",
+        job.id,
+        job.processed,
+        job.failed,
+        job.error.as_deref().unwrap_or("ok")
+    );
+    Ok(())
+}
+
+async fn write_bundle(
+    state: &AppState,
+    params: &ExportParams,
+    job: &mut jobs::Model,
+    path: &Path,
+) -> Result<ExportResult, String> {
+    if let Some(dir) = path.parent() {
+        tokio::fs::create_dir_all(dir)
+            .await
+            .map_err(|e| format!("creating {} failed: {e}", dir.display()))?;
+    }
+    let file = tokio::fs::File::create(path)
+        .await
+        .map_err(|e| format!("creating {} failed: {e}", path.display()))?;
+
+    let writer = Hashed::new(BufWriter::new(file));
+    let (mut writer, failed) = build(state, params, Some(job), writer).await?;
+    writer
+        .flush()
+        .await
+        .map_err(|e| format!("writing the archive failed: {e}"))?;
+    writer
+        .get_mut()
+        .get_mut()
+        .sync_all()
+        .await
+        .map_err(|e| format!("writing the archive failed: {e}"))?;
+    let (_, digest) = writer.finish();
+
+    Ok(ExportResult {
+        path: Some(path.display().to_string()),
+        bytes: digest.size,
+        sha256: Some(digest.sha256),
+        failed_event_ids: failed.into_iter().take(RESULT_ID_LIMIT).collect(),
+    })
+}
+
+/// Write the bundle for `params` to `writer`, committing progress to `job`
+/// after every batch when there is one. Returns the writer and the events
+/// whose files are incomplete.
+async fn build<W>(
+    state: &AppState,
+    params: &ExportParams,
+    mut job: Option<&mut jobs::Model>,
+    writer: W,
+) -> Result<(W, Vec<u64>), String>
+where
+    W: AsyncWrite + Unpin + Send,
+{
+    let db = state.db();
+    let condition = params
+        .condition()
+        .map_err(|e| format!("filter no longer compiles: {e}"))?;
+    let scope = params.scope();
+    let monitors = params.monitor_ids.as_deref();
+
+    let manifest = Manifest {
+        generator: format!("zm-api {}", crate::constant::API_VERSION),
+        created_at: Utc::now().to_rfc3339(),
+        requested_by: params.requested_by.clone(),
+        job_id: job.as_ref().map(|j| j.id),
+        filter: params.filter.clone(),
+        events: Vec::new(),
+        files: Vec::new(),
+    };
+    let mut bundle = Bundle {
+        archive: ArchiveWriter::new(params.format, writer),
+        manifest,
+    };
+
+    let mut cursor = None;
+    loop {
+        let batch =
+            events_repo::find_batch_after(db, condition.clone(), monitors, cursor, BATCH_SIZE)
+                .await
+                .map_err(|e| format!("reading events failed: {e}"))?;
+        let Some(last) = batch.last() else { break };
+        cursor = Some(last.id);
+
+        let ids: Vec<u64> = batch.iter().map(|e| e.id).collect();
+        let mut tags = events_repo::find_tags_for_events(state, &ids)
+            .await
+            .map_err(|e| format!("reading tags failed: {e}"))?;
+        for event in &batch {
+            let tags = tags.remove(&event.id).unwrap_or_default();
+            bundle
+                .add_event(state, &scope, event, &tags)
+                .await
+                .map_err(|e| format!("writing the archive failed: {e}"))?;
+        }
+
+        if let Some(job) = job.as_deref_mut() {
+            job.cursor = cursor;
+            job.processed += batch.len() as u64;
+            job.failed = bundle.incomplete().count() as u64;
+            job.affected = job.processed - job.failed;
+            job.updated_at = Utc::now().naive_utc();
+            *job = repo::jobs::save(db, job)
+                .await
+                .map_err(|e| format!("recording progress failed: {e}"))?;
+        }
+        if (batch.len() as u64) < BATCH_SIZE {
+            break;
+        }
+    }
+
+    bundle
+        .finish()
+        .await
+        .map_err(|e| format!("writing the archive failed: {e}"))
+}
+
+/// An archive being filled, and the manifest describing it.
+struct Bundle<W: AsyncWrite + Unpin + Send> {
+    archive: ArchiveWriter<W>,
+    manifest: Manifest,
+}
+
+impl<W: AsyncWrite + Unpin + Send> Bundle<W> {
+    /// Add one event's files. Files that cannot be read are noted in the
+    /// manifest and skipped; only failing to write the archive is an error.
+    async fn add_event(
+        &mut self,
+        state: &AppState,
+        scope: &MonitorScope,
+        event: &events::Model,
+        tags: &[tags::Model],
+    ) -> io::Result<()> {
+        let dir = format!("event-{}", event.id);
+        let mut problems = Vec::new();
+
+        let tags: Vec<TagSummary> = tags.iter().map(TagSummary::from).collect();
+        let details = EventResponse::with_tags(event.clone(), tags);
+        self.add_json(format!("{dir}/event.json"), event.id, &details)
+            .await?;
+
+        let mut alarm_frames = Vec::new();
+        match repo::frames::find_all(state.db(), event.id, None).await {
+            Ok(frames) => {
+                alarm_frames.extend(
+                    frames
+                        .iter()
+                        .filter(|f| f.r#type == FrameType::Alarm)
+                        .map(|f| f.frame_id),
+                );
+                let rows: Vec<FrameResponse> = frames.iter().map(FrameResponse::from).collect();
+                self.add_json(format!("{dir}/frames.json"), event.id, &rows)
+                    .await?;
+            }
+            Err(e) => problems.push(format!("reading frames failed: {e}")),
+        }
+        match repo::stats::find_by_event(state.db(), event.id).await {
+            Ok(stats) => {
+                let rows: Vec<StatResponse> = stats.iter().map(StatResponse::from).collect();
+                self.add_json(format!("{dir}/stats.json"), event.id, &rows)
+                    .await?;
+            }
+            Err(e) => problems.push(format!("reading stats failed: {e}")),
+        }
+
+        match get_event_media_path(state, event.id, scope).await {
+            Ok(path) => {
+                let name = path
+                    .file_name()
+                    .map(|n| n.to_string_lossy().into_owned())
+                    .unwrap_or_else(|| format!("{}-video.mp4", event.id));
+                self.add_file(format!("{dir}/{name}"), event.id, &path, &mut problems)
+                    .await?;
+            }
+            // JPEG-only events have no recording.
+            Err(AppError::NotFoundError(_)) => {}
+            Err(e) => problems.push(format!("locating the recording failed: {e}")),
+        }
+
+        if !alarm_frames.is_empty() {
+            match event_directory(state, event, event.id).await {
+                Ok(event_dir) => {
+                    for frame_id in alarm_frames {
+                        for kind in ["capture", "analyse"] {
+                            let name = format!("{frame_id:05}-{kind}.jpg");
+                            let source = event_dir.join(&name);
+                            self.add_file(
+                                format!("{dir}/alarm/{name}"),
+                                event.id,
+                                &source,
+                                &mut problems,
+                            )
+                            .await?;
+                        }
+                    }
+                }
+                Err(e) => problems.push(format!("locating alarm frames failed: {e}")),
+            }
+        }
+
+        if let Some(synopsis) = &state.synopsis_service {
+            if let Ok(path) = synopsis.mp4_path_for_event(event.id).await {
+                self.add_file(
+                    format!("{dir}/synopsis.mp4"),
+                    event.id,
+                    &path,
+                    &mut problems,
+                )
+                .await?;
+            }
+        }
+
+        self.manifest.events.push(ManifestEvent {
+            id: event.id,
+            monitor_id: event.monitor_id,
+            problems,
+        });
+        Ok(())
+    }
+
+    async fn add_json<T: Serialize>(
+        &mut self,
+        path: String,
+        event_id: u64,
+        value: &T,
+    ) -> io::Result<()> {
+        let data = serde_json::to_vec_pretty(value).map_err(io::Error::other)?;
+        let digest = self.archive.add_bytes(&path, &data, Utc::now()).await?;
+        self.manifest.files.push(ManifestFile {
+            path,
+            event_id: Some(event_id),
+            size: digest.size,
+            sha256: digest.sha256,
+            source: None,
+        });
+        Ok(())
+    }
+
+    /// Copy `source` in as `path`. A file that does not exist is skipped
+    /// silently (not every alarm frame has a JPEG); one that cannot be opened
+    /// is noted in `problems`.
+    async fn add_file(
+        &mut self,
+        path: String,
+        event_id: u64,
+        source: &Path,
+        problems: &mut Vec<String>,
+    ) -> io::Result<()> {
+        let opened = match tokio::fs::File::open(source).await {
+            Ok(file) => file.metadata().await.map(|meta| (file, meta)),
+            Err(e) => Err(e),
+        };
+        let (file, meta) = match opened {
+            Ok(opened) => opened,
+            Err(e) if e.kind() == io::ErrorKind::NotFound => return Ok(()),
+            Err(e) => {
+                problems.push(format!("reading {} failed: {e}", source.display()));
+                return Ok(());
+            }
+        };
+        let mtime = meta
+            .modified()
+            .map(DateTime::<Utc>::from)
+            .unwrap_or_else(|_| Utc::now());
+        let digest = self
+            .archive
+            .add_file(&path, file, meta.len(), mtime)
+            .await?;
+        self.manifest.files.push(ManifestFile {
+            path,
+            event_id: Some(event_id),
+            size: digest.size,
+            sha256: digest.sha256,
+            source: Some(source.display().to_string()),
+        });
+        Ok(())
+    }
+
+    /// Events with problems so far.
+    fn incomplete(&self) -> impl Iterator<Item = u64> + '_ {
+        self.manifest
+            .events
+            .iter()
+            .filter(|e| !e.problems.is_empty())
+            .map(|e| e.id)
+    }
+
+    /// Append the manifest and close the archive.
+    async fn finish(mut self) -> io::Result<(W, Vec<u64>)> {
+        let manifest = serde_json::to_vec_pretty(&self.manifest).map_err(io::Error::other)?;
+        self.archive
+            .add_bytes(MANIFEST, &manifest, Utc::now())
+            .await?;
+        let incomplete = self.incomplete().collect();
+        Ok((self.archive.finish().await?, incomplete))
+    }
+}
+
+/// The bundle an export job built or is building, for its submitter or
+/// anyone with System View. A bundle still being built is sent as the job
+/// writes it.
+pub async fn download(
+    state: &AppState,
+    job_id: u64,
+    claims: &UserClaims,
+) -> AppResult<BundleStream> {
+    let job = repo::jobs::find_by_id(state.db(), job_id)
+        .await?
+        .filter(|job| job.kind == JobKind::EventsExport)
+        .filter(|job| can_view_all(claims) || owns(claims, job.user_id))
+        .ok_or_else(|| job_not_found(job_id))?;
+    let params: ExportParams = serde_json::from_str(&job.params_json).map_err(|e| {
+        AppError::InternalServerError(format!("export job {job_id} is unreadable: {e}"))
+    })?;
+
+    let path = bundle_path(&state.config.jobs.export_dir, job_id, params.format);
+    let content_length = match job.status {
+        JobStatus::Failed => {
+            return Err(AppError::ConflictError(format!(
+                "export job {job_id} failed: {}",
+                job.error.as_deref().unwrap_or("unknown error")
+            )))
+        }
+        JobStatus::Succeeded => Some(
+            tokio::fs::metadata(&path)
+                .await
+                .map_err(|_| job_not_found(job_id))?
+                .len(),
+        ),
+        JobStatus::Queued | JobStatus::Running => None,
+    };
+
+    Ok(BundleStream {
+        filename: format!("zm-export-{job_id}.{}", params.format.extension()),
+        content_type: params.format.content_type(),
+        content_length,
+        body: follow(state.clone(), job_id, path).boxed(),
+    })
+}
+
+/// Read a job's bundle from disk, waiting at its end until the job writes
+/// more or finishes.
+fn follow(
+    state: AppState,
+    job_id: u64,
+    path: PathBuf,
+) -> impl Stream<Item = io::Result<Bytes>> + Send + 'static {
+    let follower = Follower {
+        state,
+        job_id,
+        path,
+        file: None,
+        finishing: false,
+    };
+    stream::unfold(Some(follower), |follower| async move {
+        let mut follower = follower?;
+        match follower.next_chunk().await {
+            Ok(Some(chunk)) => Some((Ok(chunk), Some(follower))),
+            Ok(None) => None,
+            Err(e) => Some((Err(e), None)),
+        }
+    })
+}
+
+struct Follower {
+    state: AppState,
+    job_id: u64,
+    path: PathBuf,
+    file: Option<tokio::fs::File>,
+    /// The job has finished: send what is left, then stop.
+    finishing: bool,
+}
+
+impl Follower {
+    async fn next_chunk(&mut self) -> io::Result<Option<Bytes>> {
+        let mut buf = vec![0u8; FOLLOW_CHUNK];
+        loop {
+            if self.file.is_none() {
+                match tokio::fs::File::open(&self.path).await {
+                    Ok(file) => self.file = Some(file),
+                    // Not created yet by a job that has just started.
+                    Err(e) if e.kind() == io::ErrorKind::NotFound && !self.finishing => {}
+                    Err(e) => return Err(e),
+                }
+            }
+            if let Some(file) = &mut self.file {
+                let n = file.read(&mut buf).await?;
+                if n > 0 {
+                    buf.truncate(n);
+                    return Ok(Some(Bytes::from(buf)));
+                }
+            }
+            if self.finishing {
+                return Ok(None);
+            }
+
+            let job = repo::jobs::find_by_id(self.state.db(), self.job_id)
+                .await
+                .map_err(io::Error::other)?
+                .ok_or_else(|| io::Error::other("export job disappeared"))?;
+            match job.status {
+                JobStatus::Succeeded => self.finishing = true,
+                JobStatus::Failed => {
+                    return Err(io::Error::other(format!(
+                        "export failed: {}",
+                        job.error.as_deref().unwrap_or("unknown error")
+                    )))
+                }
+                JobStatus::Queued | JobStatus::Running => tokio::time::sleep(FOLLOW_POLL).await,
+            }
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::util::authz::UserPermissions;
+    use sea_orm::{DatabaseBackend, MockDatabase};
+
+    fn claims() -> UserClaims {
+        UserClaims::new(
+            Duration::from_secs(600),
+            "investigator".into(),
+            2,
+            UserPermissions::default(),
+            crate::util::claim::TokenType::Access,
+        )
+    }
+
+    fn request(event_ids: Option<Vec<u64>>, filter: Option<FilterQuery>) -> EventExportRequest {
+        EventExportRequest {
+            event_ids,
+            filter,
+            format: ExportFormat::Zip,
+        }
+    }
+
+    #[tokio::test]
+    async fn submit_rejects_bad_selectors() {
+        let state =
+            AppState::for_test_with_db(MockDatabase::new(DatabaseBackend::MySql).into_connection());
+        let filter: FilterQuery = serde_json::from_value(
+            serde_json::json!({"where": {"field": "id", "op": "gt", "value": 0}}),
+        )
+        .unwrap();
+        for req in [
+            request(None, None),
+            request(Some(vec![1]), Some(filter)),
+            request(Some(Vec::new()), None),
+            request(Some((0..=MAX_EVENT_IDS as u64).collect()), None),
+        ] {
+            let err = submit(&state, req, &claims(), &MonitorScope::All)
+                .await
+                .err()
+                .expect("rejected");
+            assert!(matches!(err, AppError::BadRequestError(_)), "{err:?}");
+        }
+    }
+
+    #[test]
+    fn params_scope_keeps_only_captured_monitors() {
+        let params = ExportParams {
+            format: ExportFormat::Tar,
+            event_ids: Some(vec![3]),
+            filter: None,
+            monitor_ids: Some(vec![1, 4]),
+            requested_by: "investigator".into(),
+        };
+        let scope = params.scope();
+        assert!(scope.allows(4, Level::View));
+        assert!(!scope.allows(2, Level::View));
+        let unrestricted = ExportParams {
+            monitor_ids: None,
+            ..params
+        };
+        assert_eq!(unrestricted.scope(), MonitorScope::All);
+    }
+
+    #[test]
+    fn bundle_path_is_per_job() {
+        let dir = Path::new("/var/lib/zm-api/exports");
+        assert_eq!(
+            bundle_path(dir, 42, ExportFormat::Zip),
+            dir.join("bundle-42.zip")
+        );
+        assert_eq!(
+            bundle_path(dir, 42, ExportFormat::Tar),
+            dir.join("bundle-42.tar")
+        );
+    }
+
+    #[test]
+    fn manifest_omits_empty_fields() {
+        let manifest = Manifest {
+            generator: "zm-api test".into(),
+            created_at: "2026-10-18T00:00:00+00:00".into(),
+            requested_by: "investigator".into(),
+            job_id: None,
+            filter: None,
+            events: vec![ManifestEvent {
+                id: 7,
+                monitor_id: 1,
+                problems: Vec::new(),
+            }],
+            files: vec![ManifestFile {
+                path: "event-7/event.json".into(),
+                event_id: Some(7),
+                size: 2,
+                sha256: "44136fa355b3678a1146ad16f7e8649e94fb4fc21fe77e8310c060f61caaff8a".into(),
+                source: None,
+            }],
+        };
+        let json = serde_json::to_value(&manifest).unwrap();
+        assert!(json.get("job_id").is_none() && json.get("filter").is_none());
+        assert!(json["events"][0].get("problems").is_none());
+        assert!(json["files"][0].get("source").is_none());
+        assert_eq!(json["files"][0]["size"], 2);
+    }
+}
@@ -0,0 +1,419 @@
+//! Cross-monitor event firehose.
+//!
+//! [`EventFeed`] is an in-process broadcast bus of typed
+//! [`EventNotification`]s, published by every place zm-api learns about event
+//! activity:
+//!
+//! * the zm-next ingest ([`crate::service::zmnext::EventIngestor`]) — event
+//!   open, alarm score and event close as analysis EVENTs are written to rows;
+//! * the ONVIF PullPoint listener — event open/close on alarm edges;
+//! * the source router — capture faults and their recovery, bridged from the
+//!   per-reader EVENT fan-out by [`EventFeed::spawn_capture_bridge`];
+//! * PTZ arbitration — a camera's control lease changing hands.
+//!
+//! `GET /api/v3/events/stream` (SSE) and `/api/v3/events/stream/ws`
+//! (WebSocket) subscribe to it and filter per caller by `MonitorScope`.
+//!
+//! Like the per-monitor SSE stream the feed is ephemeral: nothing is stored
+//! and there is no replay. Publishing never blocks — with no subscribers a
+//! notification is simply dropped, and a subscriber that falls more than the
+//! channel capacity behind is told how many it missed.
+
+use std::sync::atomic::{AtomicU64, Ordering};
+use std::sync::Arc;
+
+use chrono::{DateTime, NaiveDateTime, SecondsFormat, Utc};
+use serde::Serialize;
+use tokio::sync::broadcast;
+use tracing::{debug, info};
+
+use crate::streaming::source::{protocol, MonitorEvent};
+
+/// Notifications buffered per subscriber before it starts lagging.
+pub const FEED_CAPACITY: usize = 256;
+
+/// What happened. Doubles as the SSE `event:` name.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, Serialize, utoipa::ToSchema)]
+#[serde(rename_all = "snake_case")]
+pub enum EventNotificationKind {
+    /// An `Events` row was opened.
+    EventOpen,
+    /// An open `Events` row was finalized with an end time.
+    EventClose,
+    /// A detection scored a frame of an open event.
+    AlarmScore,
+    /// Capture stopped: the camera connection or decode failed.
+    CaptureFault,
+    /// Capture recovered from an earlier fault.
+    CaptureRestored,
+    /// A PTZ camera's control lease was taken, handed over or let go.
+    PtzControl,
+}
+
+impl EventNotificationKind {
+    pub fn as_str(self) -> &'static str {
+        match self {
+            Self::EventOpen => "event_open",
+            Self::EventClose => "event_close",
+            Self::AlarmScore => "alarm_score",
+            Self::CaptureFault => "capture_fault",
+            Self::CaptureRestored => "capture_restored",
+            Self::PtzControl => "ptz_control",
+        }
+    }
+
+    /// Parse the snake_case wire name (as used in the `kinds` filter).
+    pub fn parse(name: &str) -> Option<Self> {
+        Some(match name {
+            "event_open" => Self::EventOpen,
+            "event_close" => Self::EventClose,
+            "alarm_score" => Self::AlarmScore,
+            "capture_fault" => Self::CaptureFault,
+            "capture_restored" => Self::CaptureRestored,
+            "ptz_control" => Self::PtzControl,
+            _ => return None,
+        })
+    }
+}
+
+/// Which subsystem produced a notification.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, utoipa::ToSchema)]
+#[serde(rename_all = "snake_case")]
+pub enum EventNotificationSource {
+    /// zm-next analysis ingest.
+    Zmnext,
+    /// ONVIF PullPoint alarm listener.
+    Onvif,
+    /// The monitor's capture process, via its stream socket.
+    Capture,
+    /// PTZ control arbitration.
+    Ptz,
+}
+
+/// One firehose notification.
+#[derive(Debug, Clone, Serialize, utoipa::ToSchema)]
+pub struct EventNotification {
+    /// Feed-wide sequence number, assigned on publish. Used as the SSE `id:`;
+    /// a filtered stream naturally skips numbers, so gaps do not imply loss.
+    pub seq: u64,
+    pub kind: EventNotificationKind,
+    pub source: EventNotificationSource,
+    pub monitor_id: u32,
+    /// `Events.Id` for event_open / event_close / alarm_score.
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub event_id: Option<u64>,
+    /// Frame score for alarm_score.
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub score: Option<u16>,
+    /// Event cause for event_open (e.g. "Motion", "ONVIF Alarm").
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub cause: Option<String>,
+    /// Capture EVENT name for capture_fault / capture_restored
+    /// (`connection_failed`, `capture_resumed`, ...); for ptz_control, how
+    /// control changed (`acquired`, `taken_over`, `released`, `expired`).
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub code: Option<String>,
+    /// For ptz_control, the user now holding the camera; absent once it is
+    /// free.
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub user: Option<String>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub message: Option<String>,
+    /// When it happened (RFC 3339, UTC).
+    pub time: String,
+}
+
+impl EventNotification {
+    fn new(
+        kind: EventNotificationKind,
+        source: EventNotificationSource,
+        monitor_id: u32,
+        time: DateTime<Utc>,
+    ) -> Self {
+        Self {
+            seq: 0,
+            kind,
+            source,
+            monitor_id,
+            event_id: None,
+            score: None,
+            cause: None,
+            code: None,
+            user: None,
+            message: None,
+            time: time.to_rfc3339_opts(SecondsFormat::Millis, true),
+        }
+    }
+
+    pub fn event_open(
+        source: EventNotificationSource,
+        monitor_id: u32,
+        event_id: u64,
+        cause: Option<String>,
+        start: NaiveDateTime,
+    ) -> Self {
+        Self {
+            event_id: Some(event_id),
+            cause,
+            ..Self::new(
+                EventNotificationKind::EventOpen,
+                source,
+                monitor_id,
+                start.and_utc(),
+            )
+        }
+    }
+
+    pub fn event_close(
+        source: EventNotificationSource,
+        monitor_id: u32,
+        event_id: u64,
+        end: NaiveDateTime,
+    ) -> Self {
+        Self {
+            event_id: Some(event_id),
+            ..Self::new(
+                EventNotificationKind::EventClose,
+                source,
+                monitor_id,
+                end.and_utc(),
+            )
+        }
+    }
+
+    pub fn alarm_score(monitor_id: u32, event_id: u64, score: u16, when: NaiveDateTime) -> Self {
+        Self {
+            event_id: Some(event_id),
+            score: Some(score),
+            ..Self::new(
+                EventNotificationKind::AlarmScore,
+                EventNotificationSource::Zmnext,
+                monitor_id,
+                when.and_utc(),
+            )
+        }
+    }
+
+    /// A PTZ camera's control changed hands. `user` is the new holder, or
+    /// `None` when nobody holds it any more.
+    pub fn ptz_control(
+        monitor_id: u32,
+        change: &str,
+        user: Option<String>,
+        message: String,
+    ) -> Self {
+        Self {
+            code: Some(change.into()),
+            user,
+            message: Some(message),
+            ..Self::new(
+                EventNotificationKind::PtzControl,
+                EventNotificationSource::Ptz,
+                monitor_id,
+                Utc::now(),
+            )
+        }
+    }
+
+    /// Map a capture health EVENT off a stream socket to a notification.
+    /// `None` for everything else — snapshots, state changes and zm-next
+    /// analysis codes are not capture faults (the latter reach the feed via
+    /// ingest, already correlated to an event row).
+    pub fn from_capture_event(monitor_id: u32, ev: &MonitorEvent) -> Option<Self> {
+        let kind = match ev.code {
+            protocol::EVENT_CONNECTION_FAILED
+            | protocol::EVENT_PRIME_CAPTURE_FAILED
+            | protocol::EVENT_CAPTURE_FAILED => EventNotificationKind::CaptureFault,
+            protocol::EVENT_CONNECTION_RESTORED
+            | protocol::EVENT_PRIME_CAPTURE_RESTORED
+            | protocol::EVENT_CAPTURE_RESUMED => EventNotificationKind::CaptureRestored,
+            _ => return None,
+        };
+        let time = ev
+            .wall_clock_us
+            .and_then(|us| DateTime::from_timestamp_micros(us as i64))
+            .unwrap_or_else(Utc::now);
+        Some(Self {
+            code: Some(ev.name().into_owned()),
+            message: ev.message.clone(),
+            ..Self::new(kind, EventNotificationSource::Capture, monitor_id, time)
+        })
+    }
+}
+
+/// Broadcast bus for [`EventNotification`]s. Cheap to share via `Arc`.
+pub struct EventFeed {
+    tx: broadcast::Sender<EventNotification>,
+    next_seq: AtomicU64,
+}
+
+impl Default for EventFeed {
+    fn default() -> Self {
+        Self::new(FEED_CAPACITY)
+    }
+}
+
+impl EventFeed {
+    pub fn new(capacity: usize) -> Self {
+        let (tx, _) = broadcast::channel(capacity.max(1));
+        Self {
+            tx,
+            next_seq: AtomicU64::new(1),
+        }
+    }
+
+    /// Stamp `notification` with the next sequence number and fan it out.
+    /// Never blocks; dropped silently when nobody is subscribed.
+    pub fn publish(&self, mut notification: EventNotification) {
+        notification.seq = self.next_seq.fetch_add(1, Ordering::Relaxed);
+        let _ = self.tx.send(notification);
+    }
+
+    pub fn subscribe(&self) -> broadcast::Receiver<EventNotification> {
+        self.tx.subscribe()
+    }
+
+    pub fn subscriber_count(&self) -> usize {
+        self.tx.receiver_count()
+    }
+
+    /// Forward capture faults from the source router's cross-monitor EVENT
+    /// fan-out (`SourceRouter::subscribe_all_events`) onto the feed. Faults
+    /// are only observable for monitors whose stream-socket reader is running
+    /// (someone is viewing, it is prewarmed, or it has an events subscriber).
+    pub fn spawn_capture_bridge(
+        self: &Arc<Self>,
+        mut rx: broadcast::Receiver<(u32, MonitorEvent)>,
+    ) -> tokio::task::JoinHandle<()> {
+        let feed = Arc::clone(self);
+        tokio::spawn(async move {
+            info!("event feed capture bridge started");
+            loop {
+                match rx.recv().await {
+                    Ok((monitor_id, ev)) => {
+                        if let Some(n) = EventNotification::from_capture_event(monitor_id, &ev) {
+                            feed.publish(n);
+                        }
+                    }
+                    Err(broadcast::error::RecvError::Lagged(n)) => {
+                        debug!("event feed capture bridge lagged, skipped {n} EVENTs");
+                    }
+                    Err(broadcast::error::RecvError::Closed) => break,
+                }
+            }
+            info!("event feed capture bridge stopped (router dropped)");
+        })
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn capture_events_map_to_fault_and_restored() {
+        let failed = MonitorEvent {
+            code: protocol::EVENT_CONNECTION_FAILED,
+            message: Some("rtsp timeout".into()),
+            wall_clock_us: Some(1_781_428_303_501_000),
+            ..Default::default()
+        };
+        let n = EventNotification::from_capture_event(7, &failed).expect("fault");
+        assert_eq!(n.kind, EventNotificationKind::CaptureFault);
+        assert_eq!(n.source, EventNotificationSource::Capture);
+        assert_eq!(n.monitor_id, 7);
+        assert_eq!(n.code.as_deref(), Some("connection_failed"));
+        assert_eq!(n.message.as_deref(), Some("rtsp timeout"));
+        assert_eq!(n.time, "2026-06-14T09:11:43.501Z");
+
+        let resumed = MonitorEvent {
+            code: protocol::EVENT_CAPTURE_RESUMED,
+            ..Default::default()
+        };
+        let n = EventNotification::from_capture_event(7, &resumed).expect("restored");
+        assert_eq!(n.kind, EventNotificationKind::CaptureRestored);
+
+        for code in [
+            protocol::EVENT_SNAPSHOT,
+            protocol::EVENT_STATE_CHANGED,
+            protocol::EVENT_DETECTION,
+        ] {
+            let ev = MonitorEvent {
+                code,
+                ..Default::default()
+            };
+            assert!(EventNotification::from_capture_event(7, &ev).is_none());
+        }
+    }
+
+    #[test]
+    fn kind_names_round_trip() {
+        for kind in [
+            EventNotificationKind::EventOpen,
+            EventNotificationKind::EventClose,
+            EventNotificationKind::AlarmScore,
+            EventNotificationKind::CaptureFault,
+            EventNotificationKind::CaptureRestored,
+            EventNotificationKind::PtzControl,
+        ] {
+            assert_eq!(EventNotificationKind::parse(kind.as_str()), Some(kind));
+            let json = serde_json::to_value(kind).unwrap();
+            assert_eq!(json, kind.as_str());
+        }
+        assert_eq!(EventNotificationKind::parse("lagged"), None);
+    }
+
+    #[tokio::test]
+    async fn publish_stamps_increasing_sequence() {
+        let feed = EventFeed::new(8);
+        let mut rx = feed.subscribe();
+        let start = chrono::NaiveDate::from_ymd_opt(2026, 6, 14)
+            .unwrap()
+            .and_hms_opt(9, 0, 0)
+            .unwrap();
+        feed.publish(EventNotification::event_open(
+            EventNotificationSource::Onvif,
+            3,
+            41,
+            Some("ONVIF Alarm".into()),
+            start,
+        ));
+        feed.publish(EventNotification::event_close(
+            EventNotificationSource::Onvif,
+            3,
+            41,
+            start,
+        ));
+
+        let a = rx.recv().await.unwrap();
+        let b = rx.recv().await.unwrap();
+        assert_eq!((a.seq, a.kind), (1, EventNotificationKind::EventOpen));
+        assert_eq!((b.seq, b.kind), (2, EventNotificationKind::EventClose));
+        assert_eq!(a.time, "2026-06-14T09:00:00.000Z");
+
+        let json = serde_json::to_value(&a).unwrap();
+        assert_eq!(json["cause"], "ONVIF Alarm");
+        assert!(json.get("score").is_none());
+    }
+
+    #[test]
+    fn ptz_control_names_the_new_holder() {
+        let n = EventNotification::ptz_control(
+            5,
+            "taken_over",
+            Some("admin".into()),
+            "admin took control from alice".into(),
+        );
+        let json = serde_json::to_value(&n).unwrap();
+        assert_eq!(json["kind"], "ptz_control");
+        assert_eq!(json["source"], "ptz");
+        assert_eq!(json["code"], "taken_over");
+        assert_eq!(json["user"], "admin");
+
+        let released = EventNotification::ptz_control(5, "released", None, "free".into());
+        assert!(serde_json::to_value(&released)
+            .unwrap()
+            .get("user")
+            .is_none());
+    }
+}
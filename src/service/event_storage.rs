@@ -9,7 +9,7 @@
 //! deleting the wrong directory.
 
 use std::io::ErrorKind;
-use std::path::PathBuf;
+use std::path::{Path, PathBuf};
 
 use chrono::{Datelike, Timelike};
 use tracing::{debug, info, warn};
@@ -133,24 +133,12 @@ pub(crate) fn build_event_directory_path(
 }
 
 /// Remove an event's on-disk media (its leaf directory, plus the `.{id}`
-/// lookup symlink that the Deep scheme leaves beside the monitor directory).
-///
-/// Best-effort: a filesystem failure is logged but does not fail the caller —
-/// the database row is the authoritative record of an event's existence, so a
-/// rare orphaned directory is preferable to a delete that reports failure
-/// after the row is already gone. An already-absent directory is not an error.
-pub(crate) async fn delete_event_media(state: &AppState, event: &EventModel) -> AppResult<()> {
-    let storage_path = resolve_event_storage_path(state, event).await?;
-    remove_event_dir(&storage_path, event).await;
-    Ok(())
-}
-
-/// Remove an event's on-disk media given an already-resolved `storage_path`
-/// (its `Storages.Path` root). The retention reaper calls this directly with
-/// the storage row it already loaded, avoiding a redundant lookup; the
-/// interactive delete path reaches it via [`delete_event_media`]. Sharing this
-/// one function is what keeps the reaper and the endpoint from ever computing
-/// the event directory two different ways.
+/// lookup symlink that the Deep scheme leaves beside the monitor directory),
+/// given an already-resolved `storage_path` (its `Storages.Path` root). The
+/// retention reaper calls this with the storage row it already loaded, and the
+/// trash reaper reaches it via [`purge_event_media`]. Sharing this one function
+/// is what keeps the reapers from ever computing the event directory two
+/// different ways.
 ///
 /// Best-effort: filesystem errors are logged, not returned. An already-absent
 /// directory is not an error.
@@ -202,6 +190,236 @@ pub(crate) async fn remove_event_dir(storage_path: &str, event: &EventModel) {
     }
 }
 
+/// Recreate the `{storage}/{monitor}/.{id}` lookup symlink ZoneMinder keeps
+/// beside Deep-scheme event directories, after an event's media has been
+/// written under `storage_path` by something other than ZoneMinder (a storage
+/// move or copy). The link is relative, as ZoneMinder writes it. No-op for the
+/// other schemes; best-effort like [`remove_event_dir`].
+pub(crate) async fn link_event_dir(storage_path: &str, event: &EventModel) {
+    if !matches!(event.scheme, Scheme::Deep) {
+        return;
+    }
+    let dir = build_event_directory_path(
+        storage_path,
+        event.monitor_id,
+        event.id,
+        event.start_date_time,
+        &event.scheme,
+    );
+    let monitor_root = PathBuf::from(storage_path).join(event.monitor_id.to_string());
+    let Ok(rel) = dir.strip_prefix(&monitor_root) else {
+        return;
+    };
+    let link = monitor_root.join(format!(".{}", event.id));
+    #[cfg(unix)]
+    match tokio::fs::symlink(rel, &link).await {
+        Ok(()) => debug!("Linked {:?} -> {:?}", link, rel),
+        Err(e) if e.kind() == ErrorKind::AlreadyExists => {}
+        Err(e) => warn!("Could not create Deep-scheme symlink {:?}: {e}", link),
+    }
+    #[cfg(not(unix))]
+    let _ = (rel, link);
+}
+
+/// Where a soft-deleted event's media waits for the trash reaper:
+/// `{storage}/.trash/{EventId}`. The trash lives on the event's own storage so
+/// moving media into it is a single same-filesystem `rename`, never a copy.
+pub(crate) fn trash_dir(storage_path: &str, event_id: u64) -> PathBuf {
+    trash_root(storage_path).join(event_id.to_string())
+}
+
+/// `{storage}/.trash`, the parent of every [`trash_dir`].
+pub(crate) fn trash_root(storage_path: &str) -> PathBuf {
+    PathBuf::from(storage_path).join(".trash")
+}
+
+/// `{storage}/.quarantine`, where the audit moves directories it cannot match
+/// to any event. Like the trash it lives on the storage itself, so the move is
+/// a `rename`.
+pub(crate) fn quarantine_root(storage_path: &str) -> PathBuf {
+    PathBuf::from(storage_path).join(".quarantine")
+}
+
+/// Move `dir`, somewhere under `storage_path`, into the storage's quarantine at
+/// the same relative path, so an operator can inspect it and move it back with
+/// one `mv`. Returns the new location. Refuses to replace anything already
+/// there, and to touch a `dir` outside the storage.
+pub(crate) async fn quarantine(storage_path: &str, dir: &Path) -> std::io::Result<PathBuf> {
+    let relative = dir.strip_prefix(storage_path).map_err(|_| {
+        std::io::Error::new(
+            ErrorKind::InvalidInput,
+            format!("{} is not under {storage_path}", dir.display()),
+        )
+    })?;
+    if relative.as_os_str().is_empty() {
+        return Err(std::io::Error::new(
+            ErrorKind::InvalidInput,
+            "refusing to quarantine the storage root",
+        ));
+    }
+    let target = quarantine_root(storage_path).join(relative);
+    if tokio::fs::try_exists(&target).await? {
+        return Err(std::io::Error::new(
+            ErrorKind::AlreadyExists,
+            format!("{} already exists", target.display()),
+        ));
+    }
+    if let Some(parent) = target.parent() {
+        tokio::fs::create_dir_all(parent).await?;
+    }
+    tokio::fs::rename(dir, &target).await?;
+    info!("Quarantined {:?} to {:?}", dir, target);
+    Ok(target)
+}
+
+/// Move an event's media directory into its storage's trash, dropping the
+/// Deep-scheme `.{id}` symlink that would otherwise dangle. Returns `false` when
+/// the event has no media directory to move.
+pub(crate) async fn move_to_trash(storage_path: &str, event: &EventModel) -> std::io::Result<bool> {
+    let dir = build_event_directory_path(
+        storage_path,
+        event.monitor_id,
+        event.id,
+        event.start_date_time,
+        &event.scheme,
+    );
+    if !tokio::fs::try_exists(&dir).await? {
+        return Ok(false);
+    }
+    let target = trash_dir(storage_path, event.id);
+    if let Some(parent) = target.parent() {
+        tokio::fs::create_dir_all(parent).await?;
+    }
+    // Event ids are never reused, so anything already at the target is debris
+    // from an earlier interrupted attempt; `rename` will not replace a
+    // non-empty directory.
+    match tokio::fs::remove_dir_all(&target).await {
+        Ok(()) => debug!("Cleared stale trash directory {:?}", target),
+        Err(e) if e.kind() == ErrorKind::NotFound => {}
+        Err(e) => return Err(e),
+    }
+    tokio::fs::rename(&dir, &target).await?;
+    info!("Moved event {} media {:?} to {:?}", event.id, dir, target);
+
+    if matches!(event.scheme, Scheme::Deep) {
+        let link = PathBuf::from(storage_path)
+            .join(event.monitor_id.to_string())
+            .join(format!(".{}", event.id));
+        if let Err(e) = tokio::fs::remove_file(&link).await {
+            if e.kind() != ErrorKind::NotFound {
+                debug!("Could not remove Deep-scheme symlink {:?}: {e}", link);
+            }
+        }
+    }
+    Ok(true)
+}
+
+/// Move a restored event's media back from the trash to its scheme directory
+/// (recreating the Deep-scheme symlink). Returns `false` when there is nothing
+/// in the trash for it — its media never left the live tree. Refuses to
+/// overwrite a directory that is already back in place.
+pub(crate) async fn restore_from_trash(
+    storage_path: &str,
+    event: &EventModel,
+) -> std::io::Result<bool> {
+    let source = trash_dir(storage_path, event.id);
+    if !tokio::fs::try_exists(&source).await? {
+        return Ok(false);
+    }
+    let dir = build_event_directory_path(
+        storage_path,
+        event.monitor_id,
+        event.id,
+        event.start_date_time,
+        &event.scheme,
+    );
+    if tokio::fs::try_exists(&dir).await? {
+        return Err(std::io::Error::new(
+            ErrorKind::AlreadyExists,
+            format!("{} already exists", dir.display()),
+        ));
+    }
+    if let Some(parent) = dir.parent() {
+        tokio::fs::create_dir_all(parent).await?;
+    }
+    tokio::fs::rename(&source, &dir).await?;
+    info!("Restored event {} media {:?} from trash", event.id, dir);
+    link_event_dir(storage_path, event).await;
+    Ok(true)
+}
+
+/// Remove a purged event's media: its trash directory, and its live directory
+/// too in case moving it into the trash had failed. Best-effort like
+/// [`remove_event_dir`].
+pub(crate) async fn purge_event_media(storage_path: &str, event: &EventModel) {
+    let trashed = trash_dir(storage_path, event.id);
+    match tokio::fs::remove_dir_all(&trashed).await {
+        Ok(()) => info!("Purged event {} trash {:?}", event.id, trashed),
+        Err(e) if e.kind() == ErrorKind::NotFound => {}
+        Err(e) => warn!(
+            "Failed to purge event {} trash {:?}: {e}",
+            event.id, trashed
+        ),
+    }
+    remove_event_dir(storage_path, event).await;
+}
+
+/// [`copy_tree`] on the blocking pool, with errors flattened to a message.
+pub(crate) async fn copy_tree_async(src: PathBuf, dst: PathBuf) -> Result<(u64, u64), String> {
+    tokio::task::spawn_blocking(move || copy_tree(&src, &dst))
+        .await
+        .map_err(|e| format!("copy task failed: {e}"))?
+        .map_err(|e| format!("copying media failed: {e}"))
+}
+
+/// Copy every regular file under `src` into `dst` (created as needed),
+/// overwriting what is there — so a move or copy resumed halfway just copies
+/// again.
+/// Returns `(files, bytes)`. Symlinks and special files are skipped.
+pub(crate) fn copy_tree(src: &Path, dst: &Path) -> std::io::Result<(u64, u64)> {
+    std::fs::create_dir_all(dst)?;
+    let (mut files, mut bytes) = (0, 0);
+    for entry in std::fs::read_dir(src)? {
+        let entry = entry?;
+        let file_type = entry.file_type()?;
+        let to = dst.join(entry.file_name());
+        if file_type.is_dir() {
+            let (f, b) = copy_tree(&entry.path(), &to)?;
+            files += f;
+            bytes += b;
+        } else if file_type.is_file() {
+            let expected = entry.metadata()?.len();
+            let copied = std::fs::copy(entry.path(), &to)?;
+            if copied != expected {
+                return Err(std::io::Error::other(format!(
+                    "short copy of {}: {copied} of {expected} bytes",
+                    entry.path().display()
+                )));
+            }
+            files += 1;
+            bytes += copied;
+        }
+    }
+    Ok((files, bytes))
+}
+
+/// Total size of the regular files under `dir` — what ZoneMinder records as
+/// `Events.DiskSpace`. Symlinks and special files are skipped, as in
+/// [`copy_tree`].
+pub(crate) fn tree_bytes(dir: &Path) -> std::io::Result<u64> {
+    let mut bytes = 0;
+    for entry in std::fs::read_dir(dir)? {
+        let entry = entry?;
+        let file_type = entry.file_type()?;
+        if file_type.is_dir() {
+            bytes += tree_bytes(&entry.path())?;
+        } else if file_type.is_file() {
+            bytes += entry.metadata()?.len();
+        }
+    }
+    Ok(bytes)
+}
+
 #[cfg(test)]
 mod tests {
     use super::*;
@@ -299,31 +517,14 @@ mod tests {
             locked: 0,
             latitude: None,
             longitude: None,
+            deleted_at: None,
         }
     }
 
-    fn mk_storage(id: u16, path: String) -> crate::entity::storage::Model {
-        use crate::entity::sea_orm_active_enums::StorageType;
-        crate::entity::storage::Model {
-            id,
-            path,
-            name: "store".into(),
-            r#type: StorageType::Local,
-            url: None,
-            disk_space: None,
-            scheme: Scheme::Shallow,
-            server_id: None,
-            do_delete: 1,
-            enabled: 1,
-        }
-    }
-
-    /// Deleting an event removes exactly its own leaf directory and leaves a
+    /// Removing an event's media removes exactly its own leaf directory and leaves a
     /// sibling event's directory (under the same monitor) untouched.
     #[tokio::test]
-    async fn delete_event_media_removes_only_the_event_leaf() {
-        use sea_orm::{DatabaseBackend, MockDatabase};
-
+    async fn remove_event_dir_removes_only_the_event_leaf() {
         let tmp = tempfile::tempdir().unwrap();
         let monitor_id = 3u32;
         let event_id = 42u64;
@@ -335,16 +536,8 @@ mod tests {
         std::fs::write(event_dir.join("42-video.mp4"), b"video").unwrap();
         std::fs::write(sibling_dir.join("99-video.mp4"), b"other").unwrap();
 
-        let db = MockDatabase::new(DatabaseBackend::MySql)
-            .append_query_results::<crate::entity::storage::Model, _, _>(vec![vec![mk_storage(
-                1,
-                tmp.path().to_string_lossy().into_owned(),
-            )]])
-            .into_connection();
-        let state = AppState::for_test_with_db(db);
-
         let event = mk_event(event_id, monitor_id, Some(1), Scheme::Shallow);
-        delete_event_media(&state, &event).await.unwrap();
+        remove_event_dir(&tmp.path().to_string_lossy(), &event).await;
 
         assert!(
             !event_dir.exists(),
@@ -358,22 +551,142 @@ mod tests {
 
     /// An already-absent media directory is not an error (idempotent cleanup).
     #[tokio::test]
-    async fn delete_event_media_tolerates_missing_directory() {
-        use sea_orm::{DatabaseBackend, MockDatabase};
+    async fn remove_event_dir_tolerates_missing_directory() {
+        let tmp = tempfile::tempdir().unwrap();
+        // Nothing created on disk; the resolved directory does not exist, and
+        // the call returns without panicking.
+        let event = mk_event(7, 1, Some(1), Scheme::Shallow);
+        remove_event_dir(&tmp.path().to_string_lossy(), &event).await;
+        assert!(!tmp.path().join("1").exists());
+    }
 
+    /// A Deep-scheme event goes into `.trash/{id}` with its lookup symlink
+    /// dropped, and comes back to the same timestamped directory with the
+    /// symlink recreated.
+    #[cfg(unix)]
+    #[tokio::test]
+    async fn trash_round_trip_restores_the_deep_layout() {
         let tmp = tempfile::tempdir().unwrap();
-        let db = MockDatabase::new(DatabaseBackend::MySql)
-            .append_query_results::<crate::entity::storage::Model, _, _>(vec![vec![mk_storage(
-                1,
-                tmp.path().to_string_lossy().into_owned(),
-            )]])
-            .into_connection();
-        let state = AppState::for_test_with_db(db);
-
-        // Nothing created on disk; the resolved directory does not exist.
+        let root = tmp.path().to_string_lossy().into_owned();
+        let mut event = mk_event(42, 3, Some(1), Scheme::Deep);
+        event.start_date_time = Some(sample_dt());
+        let dir = build_event_directory_path(&root, 3, 42, event.start_date_time, &Scheme::Deep);
+        std::fs::create_dir_all(&dir).unwrap();
+        std::fs::write(dir.join("42-video.mp4"), b"video").unwrap();
+        link_event_dir(&root, &event).await;
+        let link = tmp.path().join("3").join(".42");
+        assert!(link.symlink_metadata().is_ok());
+
+        assert!(move_to_trash(&root, &event).await.unwrap());
+        assert!(!dir.exists());
+        assert!(link.symlink_metadata().is_err(), "symlink dropped");
+        let trashed = trash_dir(&root, 42);
+        assert_eq!(
+            std::fs::read(trashed.join("42-video.mp4")).unwrap(),
+            b"video"
+        );
+
+        assert!(restore_from_trash(&root, &event).await.unwrap());
+        assert!(!trashed.exists());
+        assert_eq!(std::fs::read(dir.join("42-video.mp4")).unwrap(), b"video");
+        assert_eq!(
+            std::fs::read(link.join("42-video.mp4")).unwrap(),
+            b"video",
+            "symlink recreated"
+        );
+    }
+
+    /// Nothing to move is not an error in either direction, and restoring
+    /// never overwrites a directory that is already back in place.
+    #[tokio::test]
+    async fn trash_moves_tolerate_missing_media_and_refuse_to_clobber() {
+        let tmp = tempfile::tempdir().unwrap();
+        let root = tmp.path().to_string_lossy().into_owned();
+        let event = mk_event(7, 1, Some(1), Scheme::Shallow);
+        assert!(!move_to_trash(&root, &event).await.unwrap());
+        assert!(!restore_from_trash(&root, &event).await.unwrap());
+
+        std::fs::create_dir_all(trash_dir(&root, 7)).unwrap();
+        std::fs::create_dir_all(tmp.path().join("1").join("7")).unwrap();
+        let err = restore_from_trash(&root, &event).await.unwrap_err();
+        assert_eq!(err.kind(), ErrorKind::AlreadyExists);
+    }
+
+    /// Purging removes the trashed media and, when the move into the trash
+    /// had failed, the live directory too.
+    #[tokio::test]
+    async fn purge_removes_trash_and_any_leftover_live_directory() {
+        let tmp = tempfile::tempdir().unwrap();
+        let root = tmp.path().to_string_lossy().into_owned();
         let event = mk_event(7, 1, Some(1), Scheme::Shallow);
-        delete_event_media(&state, &event)
-            .await
-            .expect("absent media directory must not be an error");
+        let trashed = trash_dir(&root, 7);
+        let live = tmp.path().join("1").join("7");
+        std::fs::create_dir_all(&trashed).unwrap();
+        std::fs::create_dir_all(&live).unwrap();
+
+        purge_event_media(&root, &event).await;
+        assert!(!trashed.exists());
+        assert!(!live.exists());
+        assert!(tmp.path().join(".trash").exists(), "trash root kept");
+    }
+
+    /// Quarantine keeps the path relative to the storage, and never replaces
+    /// or reaches outside anything.
+    #[tokio::test]
+    async fn quarantine_keeps_the_relative_path_and_refuses_to_clobber() {
+        let tmp = tempfile::tempdir().unwrap();
+        let root = tmp.path().to_string_lossy().into_owned();
+        let orphan = tmp.path().join("3").join("2024-03-09").join("41");
+        std::fs::create_dir_all(&orphan).unwrap();
+        std::fs::write(orphan.join("00001-capture.jpg"), b"jpg").unwrap();
+
+        let moved = quarantine(&root, &orphan).await.unwrap();
+        assert_eq!(
+            moved,
+            quarantine_root(&root)
+                .join("3")
+                .join("2024-03-09")
+                .join("41")
+        );
+        assert!(moved.join("00001-capture.jpg").exists());
+        assert!(!orphan.exists());
+
+        std::fs::create_dir_all(&orphan).unwrap();
+        let err = quarantine(&root, &orphan).await.unwrap_err();
+        assert_eq!(err.kind(), ErrorKind::AlreadyExists);
+        assert!(orphan.exists(), "left in place");
+
+        let err = quarantine(&root, tmp.path()).await.unwrap_err();
+        assert_eq!(err.kind(), ErrorKind::InvalidInput);
+        let elsewhere = tempfile::tempdir().unwrap();
+        let err = quarantine(&root, elsewhere.path()).await.unwrap_err();
+        assert_eq!(err.kind(), ErrorKind::InvalidInput);
+    }
+
+    #[test]
+    fn tree_bytes_sums_nested_files() {
+        let dir = tempfile::tempdir().unwrap();
+        std::fs::write(dir.path().join("9-video.mp4"), b"video").unwrap();
+        std::fs::create_dir(dir.path().join("sub")).unwrap();
+        std::fs::write(dir.path().join("sub/00001-capture.jpg"), b"jpg").unwrap();
+        assert_eq!(tree_bytes(dir.path()).unwrap(), 8);
+    }
+
+    #[test]
+    fn copy_tree_copies_nested_files_and_is_repeatable() {
+        let src = tempfile::tempdir().unwrap();
+        let dst = tempfile::tempdir().unwrap();
+        std::fs::write(src.path().join("9-video.mp4"), b"video").unwrap();
+        std::fs::create_dir(src.path().join("sub")).unwrap();
+        std::fs::write(src.path().join("sub/00001-capture.jpg"), b"jpg").unwrap();
+
+        let target = dst.path().join("1/9");
+        assert_eq!(copy_tree(src.path(), &target).unwrap(), (2, 8));
+        // A resumed job copies over its own partial output.
+        assert_eq!(copy_tree(src.path(), &target).unwrap(), (2, 8));
+        assert_eq!(
+            std::fs::read(target.join("sub/00001-capture.jpg")).unwrap(),
+            b"jpg"
+        );
     }
 }
@@ -1,10 +1,14 @@
+use std::collections::HashMap;
+
 use tracing::instrument;
 
 use crate::{
     dto::response::event_summaries::EventSummaryResponse,
     dto::{PaginatedResponse, PaginationParams},
+    entity::event_summaries,
     error::{AppError, AppResult, Resource, ResourceType},
     repo::event_summaries as event_summaries_repo,
+    repo::events::{self as events_repo, EventTally, TrashedTally},
     server::state::AppState,
     service::monitor_acl::MonitorScope,
     util::authz::Level,
@@ -25,11 +29,7 @@ pub async fn list_all(
 ) -> AppResult<Vec<EventSummaryResponse>> {
     let filter = scope.visible_ids(Level::View);
     let summaries = event_summaries_repo::find_all(state, filter.as_deref()).await?;
-
-    Ok(summaries
-        .into_iter()
-        .map(EventSummaryResponse::from)
-        .collect())
+    without_trashed(state, summaries).await
 }
 
 /// List event summaries with pagination
@@ -42,8 +42,7 @@ pub async fn list_paginated(
     let filter = scope.visible_ids(Level::View);
     let (items, total) =
         event_summaries_repo::find_paginated(state, params, filter.as_deref()).await?;
-    let responses: Vec<EventSummaryResponse> =
-        items.into_iter().map(EventSummaryResponse::from).collect();
+    let responses = without_trashed(state, items).await?;
     Ok(PaginatedResponse::from_params(responses, total, params))
 }
 
@@ -60,6 +59,164 @@ pub async fn get_by_monitor_id(
     let summary = event_summaries_repo::find_by_monitor_id(state, monitor_id)
         .await?
         .ok_or_else(|| summary_not_found(monitor_id))?;
+    let mut responses = without_trashed(state, vec![summary]).await?;
+    Ok(responses.remove(0))
+}
+
+/// Summaries as the API reports them: over live events only. ZoneMinder's
+/// triggers keep counting a trashed event until the reaper deletes its row,
+/// so the trash is tallied and taken back out here.
+async fn without_trashed(
+    state: &AppState,
+    summaries: Vec<event_summaries::Model>,
+) -> AppResult<Vec<EventSummaryResponse>> {
+    let ids: Vec<u32> = summaries.iter().map(|s| s.monitor_id).collect();
+    let trashed: HashMap<u32, TrashedTally> = if ids.is_empty() {
+        HashMap::new()
+    } else {
+        events_repo::trashed_tally_by_monitor(state.db(), Some(&ids))
+            .await?
+            .into_iter()
+            .map(|t| (t.monitor_id, t))
+            .collect()
+    };
+    Ok(summaries
+        .into_iter()
+        .map(|summary| {
+            let mut response = EventSummaryResponse::from(summary);
+            if let Some(tally) = trashed.get(&response.monitor_id) {
+                take_out(&mut response, tally);
+            }
+            response
+        })
+        .collect())
+}
+
+/// Subtract a monitor's trashed events from its summary, never below zero.
+fn take_out(summary: &mut EventSummaryResponse, trashed: &TrashedTally) {
+    fn sub(events: &mut i32, bytes: &mut i64, tally: &EventTally) {
+        *events = (i64::from(*events) - tally.events as i64).max(0) as i32;
+        *bytes = bytes.saturating_sub(tally.bytes as i64).max(0);
+    }
+    let [hour, day, week, month] = &trashed.windows;
+    sub(
+        &mut summary.total_events,
+        &mut summary.total_event_disk_space,
+        &trashed.total,
+    );
+    sub(
+        &mut summary.hour_events,
+        &mut summary.hour_event_disk_space,
+        hour,
+    );
+    sub(
+        &mut summary.day_events,
+        &mut summary.day_event_disk_space,
+        day,
+    );
+    sub(
+        &mut summary.week_events,
+        &mut summary.week_event_disk_space,
+        week,
+    );
+    sub(
+        &mut summary.month_events,
+        &mut summary.month_event_disk_space,
+        month,
+    );
+    sub(
+        &mut summary.archived_events,
+        &mut summary.archived_event_disk_space,
+        &trashed.archived,
+    );
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
 
-    Ok(EventSummaryResponse::from(summary))
+    fn summary() -> EventSummaryResponse {
+        EventSummaryResponse::from(event_summaries::Model {
+            monitor_id: 1,
+            total_events: Some(10),
+            total_event_disk_space: Some(1000),
+            hour_events: Some(1),
+            hour_event_disk_space: Some(100),
+            day_events: Some(4),
+            day_event_disk_space: Some(400),
+            week_events: None,
+            week_event_disk_space: None,
+            month_events: Some(10),
+            month_event_disk_space: Some(1000),
+            archived_events: Some(2),
+            archived_event_disk_space: Some(200),
+        })
+    }
+
+    #[test]
+    fn trashed_events_are_taken_out_of_every_counter() {
+        let tally = |events, bytes| EventTally { events, bytes };
+        let mut response = summary();
+        take_out(
+            &mut response,
+            &TrashedTally {
+                monitor_id: 1,
+                total: tally(3, 300),
+                windows: [tally(1, 100), tally(3, 300), tally(0, 0), tally(3, 300)],
+                archived: tally(1, 100),
+            },
+        );
+        assert_eq!(
+            (response.total_events, response.total_event_disk_space),
+            (7, 700)
+        );
+        assert_eq!(
+            (response.hour_events, response.hour_event_disk_space),
+            (0, 0)
+        );
+        assert_eq!(
+            (response.day_events, response.day_event_disk_space),
+            (1, 100)
+        );
+        assert_eq!(
+            (response.month_events, response.month_event_disk_space),
+            (7, 700)
+        );
+        assert_eq!(
+            (response.archived_events, response.archived_event_disk_space),
+            (1, 100)
+        );
+    }
+
+    #[test]
+    fn a_drifted_summary_does_not_go_negative() {
+        let mut response = summary();
+        take_out(
+            &mut response,
+            &TrashedTally {
+                monitor_id: 1,
+                total: EventTally {
+                    events: 50,
+                    bytes: 5000,
+                },
+                windows: [EventTally {
+                    events: 5,
+                    bytes: 500,
+                }; 4],
+                ..Default::default()
+            },
+        );
+        assert_eq!(
+            (response.total_events, response.total_event_disk_space),
+            (0, 0)
+        );
+        assert_eq!(
+            (response.week_events, response.week_event_disk_space),
+            (0, 0)
+        );
+        assert_eq!(
+            (response.archived_events, response.archived_event_disk_space),
+            (2, 200)
+        );
+    }
 }
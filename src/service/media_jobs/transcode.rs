@@ -0,0 +1,304 @@
+//! Event transcode: re-encode an event's recording to an H.264 mp4 in-process.
+//!
+//! Decoding follows the thumbnail path in `streaming::snapshot` (libavformat
+//! demux → libavcodec decode → libswscale); encoding and muxing go through the
+//! synopsis renderer's [`H264Writer`]. zm-api never shells out to the `ffmpeg`
+//! binary. A JPEG-only event (no recorded video) is encoded from its
+//! `NNNNN-capture.jpg` frames instead.
+
+use std::path::{Path, PathBuf};
+
+use ffmpeg::format::Pixel;
+use ffmpeg::{codec, format, frame, media, software, Rational};
+use ffmpeg_next as ffmpeg;
+
+use crate::service::synopsis::compositor;
+use crate::service::synopsis::render::{canvas_to_frame, H264Writer};
+
+/// What to transcode.
+#[derive(Debug)]
+pub(super) enum Source {
+    /// A recorded video file, in any container/codec the linked ffmpeg reads.
+    Video(PathBuf),
+    /// Still frames in capture order, played back at `fps`.
+    Jpegs { frames: Vec<PathBuf>, fps: u32 },
+}
+
+/// Shape of the written file.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub(super) struct Transcoded {
+    pub frames: u64,
+    pub width: u32,
+    pub height: u32,
+}
+
+/// Output size for a `w`×`h` source: scaled down (aspect kept) to at most
+/// `max_width`, never up, and rounded to even dimensions as YUV420P needs.
+pub(super) fn output_size(w: u32, h: u32, max_width: Option<u32>) -> (u32, u32) {
+    match max_width {
+        Some(max) if w > max => {
+            let scaled_h = ((h as u64) * (max as u64) / (w as u64)) as u32;
+            ((max & !1).max(2), scaled_h.max(2) & !1)
+        }
+        _ => ((w & !1).max(2), (h & !1).max(2)),
+    }
+}
+
+/// Encode `source` to an mp4 at `out`. Blocking — run on a blocking thread.
+pub(super) fn transcode_blocking(
+    source: &Source,
+    out: &Path,
+    max_width: Option<u32>,
+) -> Result<Transcoded, String> {
+    let _ = ffmpeg::init();
+    match source {
+        Source::Video(path) => from_video(path, out, max_width),
+        Source::Jpegs { frames, fps } => from_jpegs(frames, *fps, out, max_width),
+    }
+}
+
+/// Scaler + writer, opened on the first decoded frame (whose size and pixel
+/// format are only known then).
+struct Encode {
+    scaler: software::scaling::Context,
+    writer: H264Writer,
+    src_w: u32,
+    src_h: u32,
+    out_w: u32,
+    out_h: u32,
+    next_pts: i64,
+    frames: u64,
+}
+
+impl Encode {
+    fn start(
+        first: &frame::Video,
+        out: &Path,
+        max_width: Option<u32>,
+        time_base: Rational,
+        frame_rate: Option<Rational>,
+    ) -> Result<Self, String> {
+        let (src_w, src_h) = (first.width(), first.height());
+        if src_w == 0 || src_h == 0 {
+            return Err("decoded frame has zero dimensions".into());
+        }
+        let (out_w, out_h) = output_size(src_w, src_h, max_width);
+        let scaler = software::scaling::Context::get(
+            first.format(),
+            src_w,
+            src_h,
+            Pixel::YUV420P,
+            out_w,
+            out_h,
+            software::scaling::Flags::BILINEAR,
+        )
+        .map_err(|e| format!("scaler init failed: {e}"))?;
+        let writer = H264Writer::open(out, out_w, out_h, time_base, frame_rate)
+            .map_err(|e| e.to_string())?;
+        Ok(Self {
+            scaler,
+            writer,
+            src_w,
+            src_h,
+            out_w,
+            out_h,
+            next_pts: 0,
+            frames: 0,
+        })
+    }
+
+    fn push(&mut self, src: &frame::Video, pts: Option<i64>) -> Result<(), String> {
+        let mut yuv = frame::Video::empty();
+        self.scaler
+            .run(src, &mut yuv)
+            .map_err(|e| format!("scale frame failed: {e}"))?;
+        // Keep pts strictly increasing: the muxer rejects repeats, and some
+        // recordings carry none at all.
+        let pts = pts.filter(|&p| p >= self.next_pts).unwrap_or(self.next_pts);
+        yuv.set_pts(Some(pts));
+        self.next_pts = pts + 1;
+        self.writer.write(&yuv).map_err(|e| e.to_string())?;
+        self.frames += 1;
+        Ok(())
+    }
+
+    fn finish(self) -> Result<Transcoded, String> {
+        let done = Transcoded {
+            frames: self.frames,
+            width: self.out_w,
+            height: self.out_h,
+        };
+        self.writer.finish().map_err(|e| e.to_string())?;
+        Ok(done)
+    }
+}
+
+fn from_video(path: &Path, out: &Path, max_width: Option<u32>) -> Result<Transcoded, String> {
+    let mut ictx =
+        format::input(&path).map_err(|e| format!("failed to open {}: {e}", path.display()))?;
+    let stream = ictx
+        .streams()
+        .best(media::Type::Video)
+        .ok_or_else(|| format!("no video stream in {}", path.display()))?;
+    let stream_index = stream.index();
+    // Decoded pts stay in the input stream's time base; encode in the same one
+    // so variable frame timing survives the transcode.
+    let time_base = match stream.time_base() {
+        tb if tb.numerator() > 0 && tb.denominator() > 0 => tb,
+        _ => Rational(1, 90_000),
+    };
+    let rate = stream.avg_frame_rate();
+    let frame_rate = (rate.numerator() > 0 && rate.denominator() > 0).then_some(rate);
+    let mut decoder = codec::context::Context::from_parameters(stream.parameters())
+        .and_then(|ctx| ctx.decoder().video())
+        .map_err(|e| format!("failed to init video decoder: {e}"))?;
+
+    let mut encode: Option<Encode> = None;
+    let mut decoded = frame::Video::empty();
+    let mut receive =
+        |decoder: &mut ffmpeg::decoder::Video, encode: &mut Option<Encode>| -> Result<(), String> {
+            while decoder.receive_frame(&mut decoded).is_ok() {
+                if encode.is_none() {
+                    *encode = Some(Encode::start(
+                        &decoded, out, max_width, time_base, frame_rate,
+                    )?);
+                }
+                let pts = decoded.timestamp();
+                encode
+                    .as_mut()
+                    .expect("started above")
+                    .push(&decoded, pts)?;
+            }
+            Ok(())
+        };
+
+    for (pkt_stream, packet) in ictx.packets() {
+        if pkt_stream.index() != stream_index {
+            continue;
+        }
+        // A corrupt packet mid-recording is skipped, not fatal: ZoneMinder
+        // files cut at a crash often end in one.
+        if decoder.send_packet(&packet).is_err() {
+            continue;
+        }
+        receive(&mut decoder, &mut encode)?;
+    }
+    decoder
+        .send_eof()
+        .map_err(|e| format!("decoder flush failed: {e}"))?;
+    receive(&mut decoder, &mut encode)?;
+
+    encode
+        .ok_or_else(|| format!("no decodable frames in {}", path.display()))?
+        .finish()
+}
+
+fn from_jpegs(
+    frames: &[PathBuf],
+    fps: u32,
+    out: &Path,
+    max_width: Option<u32>,
+) -> Result<Transcoded, String> {
+    let fps = fps.max(1) as i32;
+    let mut encode: Option<Encode> = None;
+    for (i, path) in frames.iter().enumerate() {
+        let Some(img) = compositor::load_rgb(path) else {
+            continue;
+        };
+        let (w, h) = img.dimensions();
+        if let Some(enc) = &encode {
+            if (w, h) != (enc.src_w, enc.src_h) {
+                tracing::debug!("transcode: skipping {:?}, size changed to {w}x{h}", path);
+                continue;
+            }
+        }
+        let mut rgb = frame::Video::new(Pixel::RGB24, w, h);
+        canvas_to_frame(&img, &mut rgb);
+        if encode.is_none() {
+            encode = Some(Encode::start(
+                &rgb,
+                out,
+                max_width,
+                Rational(1, fps),
+                Some(Rational(fps, 1)),
+            )?);
+        }
+        encode
+            .as_mut()
+            .expect("started above")
+            .push(&rgb, Some(i as i64))?;
+    }
+    encode
+        .ok_or_else(|| "no readable capture frames".to_string())?
+        .finish()
+}
+
+/// The event's `NNNNN-capture.jpg` frames, in capture order.
+pub(super) fn capture_frames(dir: &Path) -> std::io::Result<Vec<PathBuf>> {
+    let mut frames: Vec<PathBuf> = std::fs::read_dir(dir)?
+        .filter_map(|entry| entry.ok().map(|e| e.path()))
+        .filter(|p| {
+            p.file_name()
+                .and_then(|n| n.to_str())
+                .is_some_and(|n| n.ends_with("-capture.jpg"))
+        })
+        .collect();
+    // Zero-padded frame numbers sort correctly as strings.
+    frames.sort();
+    Ok(frames)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn output_size_scales_down_only_and_stays_even() {
+        assert_eq!(output_size(1920, 1080, None), (1920, 1080));
+        assert_eq!(output_size(1920, 1080, Some(1280)), (1280, 720));
+        assert_eq!(
+            output_size(640, 480, Some(1280)),
+            (640, 480),
+            "never upscaled"
+        );
+        assert_eq!(output_size(641, 481, None), (640, 480), "odd sizes rounded");
+        assert_eq!(output_size(1000, 563, Some(501)), (500, 282));
+    }
+
+    #[test]
+    fn capture_frames_are_listed_in_order() {
+        let dir = tempfile::tempdir().unwrap();
+        for name in [
+            "00010-capture.jpg",
+            "00002-capture.jpg",
+            "00002-analyse.jpg",
+            "snapshot.jpg",
+        ] {
+            std::fs::write(dir.path().join(name), b"x").unwrap();
+        }
+        let names: Vec<String> = capture_frames(dir.path())
+            .unwrap()
+            .iter()
+            .map(|p| p.file_name().unwrap().to_string_lossy().into_owned())
+            .collect();
+        assert_eq!(names, ["00002-capture.jpg", "00010-capture.jpg"]);
+    }
+
+    #[test]
+    fn unreadable_input_is_an_error_not_a_panic() {
+        let dir = tempfile::tempdir().unwrap();
+        let bogus = dir.path().join("not-a-video.mp4");
+        std::fs::write(&bogus, b"definitely not an mp4").unwrap();
+        let out = dir.path().join("out.mp4");
+        assert!(transcode_blocking(&Source::Video(bogus), &out, None).is_err());
+        assert!(transcode_blocking(
+            &Source::Jpegs {
+                frames: vec![],
+                fps: 5
+            },
+            &out,
+            None
+        )
+        .is_err());
+    }
+}
@@ -1,5 +1,7 @@
 pub mod ai;
 pub mod auth;
+pub mod bulk_events;
+pub mod clips;
 pub mod config;
 pub mod control_presets;
 pub mod controls;
@@ -8,6 +10,8 @@ pub mod devices;
 #[cfg(feature = "onvif-discovery")]
 pub mod discovery;
 pub mod event_data;
+pub mod event_export;
+pub mod event_feed;
 pub mod event_storage;
 pub mod event_summaries;
 pub mod events;
@@ -23,9 +27,11 @@ pub mod groups;
 pub mod groups_monitors;
 pub mod groups_permissions;
 pub mod image_orientation;
+pub mod jobs;
 pub mod logs;
 pub mod maintenance;
 pub mod manufacturers;
+pub mod media_jobs;
 pub mod models;
 pub mod monitor;
 pub mod monitor_acl;
@@ -34,10 +40,17 @@ pub mod monitor_presets;
 pub mod monitor_status;
 pub mod monitors_permissions;
 pub mod montage_layouts;
+pub mod notification_rules;
+pub mod notifications;
 pub mod object_types;
+pub mod previews;
 pub mod ptz;
+pub mod ptz_control;
+pub mod ptz_tours;
 pub mod reports;
 pub mod retention;
+pub mod rtsp_credentials;
+pub mod saved_searches;
 pub mod search;
 pub mod server;
 pub mod server_stats;
@@ -50,7 +63,9 @@ pub mod stats;
 pub mod storage;
 pub mod synopsis;
 pub mod tags;
+pub mod timeline;
 pub mod token;
+pub mod trash;
 pub mod triggers_x10;
 pub mod user_preferences;
 pub mod users;
@@ -142,7 +142,7 @@ pub async fn resolve(db: &DatabaseConnection, user_id: u32) -> AppResult<Monitor
 
 /// Extract a bearer token — `Authorization: Bearer <jwt>` header, or the
 /// `?token=` query parameter used by HTML media elements.
-fn extract_token(headers: &HeaderMap, uri: &Uri) -> Option<String> {
+pub(crate) fn extract_token(headers: &HeaderMap, uri: &Uri) -> Option<String> {
     let from_header = headers
         .get(AUTHORIZATION)
         .and_then(|value| value.to_str().ok())
@@ -200,6 +200,32 @@ pub async fn monitor_path_guard(
     params: RawPathParams,
     request: Request,
     next: Next,
+) -> Result<Response, AppError> {
+    let required = match *request.method() {
+        Method::GET | Method::HEAD | Method::OPTIONS => Level::View,
+        _ => Level::Edit,
+    };
+    guard_path_monitor(&state, &params, required, request, next).await
+}
+
+/// [`monitor_path_guard`] for the live streaming routes: watching needs only
+/// `View` on the monitor whatever the method (a WHEP offer is a POST), just as
+/// `Stream` itself has no `Edit` level.
+pub async fn monitor_stream_guard(
+    State(state): State<AppState>,
+    params: RawPathParams,
+    request: Request,
+    next: Next,
+) -> Result<Response, AppError> {
+    guard_path_monitor(&state, &params, Level::View, request, next).await
+}
+
+async fn guard_path_monitor(
+    state: &AppState,
+    params: &RawPathParams,
+    required: Level,
+    request: Request,
+    next: Next,
 ) -> Result<Response, AppError> {
     let monitor_id = params
         .iter()
@@ -207,11 +233,7 @@ pub async fn monitor_path_guard(
         .and_then(|(_, value)| value.parse::<u32>().ok());
 
     if let Some(mid) = monitor_id {
-        let scope = resolve_from_request(&state, request.headers(), request.uri()).await?;
-        let required = match *request.method() {
-            Method::GET | Method::HEAD | Method::OPTIONS => Level::View,
-            _ => Level::Edit,
-        };
+        let scope = resolve_from_request(state, request.headers(), request.uri()).await?;
         if !scope.allows(mid, required) {
             return Err(AppError::NotFoundError(Resource {
                 details: vec![("monitor_id".to_string(), mid.to_string())],
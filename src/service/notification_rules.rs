@@ -0,0 +1,486 @@
+//! Notification rules: per-user "tell me when ..." definitions.
+//!
+//! CRUD and validation only; matching and delivery live in
+//! [`crate::service::notifications`], which is told to reload its rule cache
+//! after every write here.
+
+use sea_orm::{ActiveValue::Unchanged, Set};
+
+use crate::dto::request::filter_ast::FilterQuery;
+use crate::dto::request::notification_rules::{
+    CreateNotificationRuleRequest, NotificationTemplate, UpdateNotificationRuleRequest,
+};
+use crate::dto::response::notification_rules::{
+    NotificationDeliveryResponse, NotificationRuleResponse,
+};
+use crate::dto::{PaginatedResponse, PaginationParams};
+use crate::entity::notification_rules;
+use crate::entity::sea_orm_active_enums::{NotificationChannel, NotificationTrigger};
+use crate::error::{AppError, AppResult};
+use crate::repo;
+use crate::server::state::AppState;
+use crate::service::filters::{can_manage_all, can_view_all, owns};
+use crate::service::notifications::{self, channels, DEFAULT_STORAGE_THRESHOLD_PCT};
+use crate::util::authz::Level;
+use crate::util::claim::UserClaims;
+
+/// Not-found for both missing rules and other users' rules, so ownership is
+/// not leaked via 404-vs-403 (same rule as filters).
+fn not_found(id: u64) -> AppError {
+    AppError::NotFoundError(crate::error::Resource {
+        details: vec![("id".into(), id.to_string())],
+        resource_type: crate::error::ResourceType::Message,
+    })
+}
+
+/// A newly issued webhook signing key: `whsec_` and 32 random alphanumerics.
+fn generate_secret() -> String {
+    format!("whsec_{}", crate::util::random::generate_random_string(32))
+}
+
+/// The signing key a rule is stored with, and whether it was issued now (and
+/// so must be shown to the caller, once). Webhook rules always have one;
+/// other channels never do.
+fn resolve_secret(
+    channel: NotificationChannel,
+    given: Option<String>,
+    rotate: bool,
+    existing: Option<String>,
+) -> AppResult<(Option<String>, bool)> {
+    if given.is_some() && rotate {
+        return Err(AppError::BadRequestError(
+            "give either secret or rotate_secret, not both".into(),
+        ));
+    }
+    if channel != NotificationChannel::Webhook {
+        if given.is_some() || rotate {
+            return Err(AppError::BadRequestError(
+                "secret only applies to webhook rules".into(),
+            ));
+        }
+        return Ok((None, false));
+    }
+    Ok(match (given, existing) {
+        (Some(secret), _) => (Some(secret), false),
+        (None, Some(secret)) if !rotate => (Some(secret), false),
+        (None, _) => (Some(generate_secret()), true),
+    })
+}
+
+/// The response for a stored rule, carrying the secret only when it was just
+/// issued.
+fn respond(model: notification_rules::Model, issued: bool) -> NotificationRuleResponse {
+    let secret = if issued { model.secret.clone() } else { None };
+    NotificationRuleResponse {
+        secret,
+        ..model.into()
+    }
+}
+
+/// The rule as it will be stored, checked as a whole: create and update both
+/// end here, so a rule can never be saved in a shape the dispatcher would
+/// ignore.
+struct RuleSpec {
+    trigger: NotificationTrigger,
+    predicate: Option<FilterQuery>,
+    threshold: Option<u32>,
+    channel: NotificationChannel,
+    target: String,
+    template: Option<NotificationTemplate>,
+}
+
+impl RuleSpec {
+    /// Validate, filling the `storage_health` default threshold. Returns the
+    /// `(predicate_json, template_json)` to store.
+    fn check(&mut self, claims: &UserClaims) -> AppResult<(Option<String>, Option<String>)> {
+        match self.trigger {
+            NotificationTrigger::ScoreThreshold => match self.threshold {
+                Some(t) if (1..=u32::from(u16::MAX)).contains(&t) => {}
+                Some(t) => {
+                    return Err(AppError::BadRequestError(format!(
+                        "score_threshold needs a threshold between 1 and {}, got {t}",
+                        u16::MAX
+                    )))
+                }
+                None => {
+                    return Err(AppError::BadRequestError(
+                        "score_threshold needs a threshold".into(),
+                    ))
+                }
+            },
+            NotificationTrigger::StorageHealth => {
+                if claims.perms.system < Level::View {
+                    return Err(AppError::PermissionDeniedError(
+                        "storage_health rules require System View".into(),
+                    ));
+                }
+                if self.predicate.is_some() {
+                    return Err(AppError::BadRequestError(
+                        "storage_health rules do not take a predicate".into(),
+                    ));
+                }
+                let t = *self.threshold.get_or_insert(DEFAULT_STORAGE_THRESHOLD_PCT);
+                if !(1..=99).contains(&t) {
+                    return Err(AppError::BadRequestError(format!(
+                        "storage_health threshold is a free-space percentage (1-99), got {t}"
+                    )));
+                }
+            }
+            NotificationTrigger::EventStart | NotificationTrigger::EventEnd => {
+                if self.threshold.is_some() {
+                    return Err(AppError::BadRequestError(
+                        "threshold only applies to score_threshold and storage_health".into(),
+                    ));
+                }
+            }
+        }
+        channels::validate_target(self.channel, &self.target).map_err(AppError::BadRequestError)?;
+
+        let predicate_json = self
+            .predicate
+            .as_ref()
+            .map(|q| {
+                crate::service::saved_searches::compile(q)?;
+                serde_json::to_string(q)
+                    .map_err(|e| AppError::BadRequestError(format!("unserializable filter: {e}")))
+            })
+            .transpose()?;
+        let template_json = self
+            .template
+            .as_ref()
+            .filter(|t| **t != NotificationTemplate::default())
+            .map(|t| {
+                serde_json::to_string(t)
+                    .map_err(|e| AppError::BadRequestError(format!("unserializable template: {e}")))
+            })
+            .transpose()?;
+        Ok((predicate_json, template_json))
+    }
+}
+
+/// Fetch a rule the caller may read: their own, or any with System View.
+async fn find_readable(
+    state: &AppState,
+    id: u64,
+    claims: &UserClaims,
+) -> AppResult<notification_rules::Model> {
+    let item = repo::notification_rules::find_by_id(state.db(), id)
+        .await?
+        .ok_or_else(|| not_found(id))?;
+    if !can_view_all(claims) && !owns(claims, Some(item.user_id)) {
+        return Err(not_found(id));
+    }
+    Ok(item)
+}
+
+/// Fetch a rule the caller may change: their own, or any with System Edit.
+async fn find_manageable(
+    state: &AppState,
+    id: u64,
+    claims: &UserClaims,
+) -> AppResult<notification_rules::Model> {
+    let item = repo::notification_rules::find_by_id(state.db(), id)
+        .await?
+        .ok_or_else(|| not_found(id))?;
+    if !can_manage_all(claims) && !owns(claims, Some(item.user_id)) {
+        return Err(not_found(id));
+    }
+    Ok(item)
+}
+
+pub async fn list_paginated(
+    state: &AppState,
+    params: &PaginationParams,
+    claims: &UserClaims,
+) -> AppResult<PaginatedResponse<NotificationRuleResponse>> {
+    let owner = (!can_view_all(claims)).then_some(claims.uid);
+    let (items, total) =
+        repo::notification_rules::find_paginated(state.db(), params, owner).await?;
+    let responses = items
+        .into_iter()
+        .map(NotificationRuleResponse::from)
+        .collect();
+    Ok(PaginatedResponse::from_params(responses, total, params))
+}
+
+pub async fn get_by_id(
+    state: &AppState,
+    id: u64,
+    claims: &UserClaims,
+) -> AppResult<NotificationRuleResponse> {
+    Ok(find_readable(state, id, claims).await?.into())
+}
+
+pub async fn create(
+    state: &AppState,
+    req: CreateNotificationRuleRequest,
+    claims: &UserClaims,
+) -> AppResult<NotificationRuleResponse> {
+    let mut spec = RuleSpec {
+        trigger: req.trigger,
+        predicate: req.predicate,
+        threshold: req.threshold,
+        channel: req.channel,
+        target: req.target,
+        template: req.template,
+    };
+    let (predicate_json, template_json) = spec.check(claims)?;
+    let (secret, issued) = resolve_secret(spec.channel, req.secret, false, None)?;
+    let now = chrono::Utc::now().naive_utc();
+    let model = repo::notification_rules::insert(
+        state.db(),
+        notification_rules::ActiveModel {
+            user_id: Set(claims.uid),
+            name: Set(req.name),
+            enabled: Set(req.enabled),
+            trigger: Set(spec.trigger),
+            predicate_json: Set(predicate_json),
+            threshold: Set(spec.threshold),
+            channel: Set(spec.channel),
+            target: Set(spec.target),
+            secret: Set(secret),
+            template_json: Set(template_json),
+            dedupe_window_seconds: Set(req.dedupe_window_seconds),
+            created_at: Set(now),
+            updated_at: Set(now),
+            ..Default::default()
+        },
+    )
+    .await?;
+    state.notifier.rules_changed();
+    Ok(respond(model, issued))
+}
+
+pub async fn update(
+    state: &AppState,
+    id: u64,
+    req: UpdateNotificationRuleRequest,
+    claims: &UserClaims,
+) -> AppResult<NotificationRuleResponse> {
+    let existing = find_manageable(state, id, claims).await?;
+    let trigger = req.trigger.unwrap_or(existing.trigger);
+    // Changing the trigger drops a threshold that only meant something to the
+    // old one, unless the body sets a new one.
+    let threshold = match req.threshold {
+        Some(t) => t,
+        None if trigger == existing.trigger => existing.threshold,
+        None => None,
+    };
+    let mut spec = RuleSpec {
+        trigger,
+        predicate: match req.predicate {
+            Some(p) => p,
+            None => notifications::parse_predicate(&existing)?,
+        },
+        threshold,
+        channel: req.channel.unwrap_or(existing.channel),
+        target: req.target.unwrap_or_else(|| existing.target.clone()),
+        template: match req.template {
+            Some(t) => t,
+            None => Some(notifications::parse_template(&existing)),
+        },
+    };
+    let (predicate_json, template_json) = spec.check(claims)?;
+    let (secret, issued) = resolve_secret(
+        spec.channel,
+        req.secret,
+        req.rotate_secret,
+        existing.secret.clone(),
+    )?;
+
+    let mut active = notification_rules::ActiveModel {
+        id: Unchanged(existing.id),
+        trigger: Set(spec.trigger),
+        predicate_json: Set(predicate_json),
+        threshold: Set(spec.threshold),
+        channel: Set(spec.channel),
+        target: Set(spec.target),
+        secret: Set(secret),
+        template_json: Set(template_json),
+        updated_at: Set(chrono::Utc::now().naive_utc()),
+        ..Default::default()
+    };
+    if let Some(name) = req.name {
+        active.name = Set(name);
+    }
+    if let Some(enabled) = req.enabled {
+        active.enabled = Set(enabled);
+    }
+    if let Some(window) = req.dedupe_window_seconds {
+        active.dedupe_window_seconds = Set(window);
+    }
+    let model = repo::notification_rules::update(state.db(), active).await?;
+    state.notifier.rules_changed();
+    Ok(respond(model, issued))
+}
+
+pub async fn delete(state: &AppState, id: u64, claims: &UserClaims) -> AppResult<()> {
+    find_manageable(state, id, claims).await?;
+    if !repo::notification_rules::delete_by_id(state.db(), id).await? {
+        return Err(not_found(id));
+    }
+    state.notifier.rules_changed();
+    Ok(())
+}
+
+/// Delivery history of a rule the caller may read, newest first.
+pub async fn list_deliveries(
+    state: &AppState,
+    id: u64,
+    params: &PaginationParams,
+    claims: &UserClaims,
+) -> AppResult<PaginatedResponse<NotificationDeliveryResponse>> {
+    find_readable(state, id, claims).await?;
+    let (items, total) =
+        repo::notification_deliveries::find_by_rule_paginated(state.db(), id, params).await?;
+    let responses = items
+        .into_iter()
+        .map(NotificationDeliveryResponse::from)
+        .collect();
+    Ok(PaginatedResponse::from_params(responses, total, params))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::util::authz::UserPermissions;
+    use std::time::Duration;
+
+    fn claims(perms: UserPermissions) -> UserClaims {
+        UserClaims::new(
+            Duration::from_secs(600),
+            "user".into(),
+            5,
+            perms,
+            crate::util::claim::TokenType::Access,
+        )
+    }
+
+    fn spec(trigger: NotificationTrigger, threshold: Option<u32>) -> RuleSpec {
+        RuleSpec {
+            trigger,
+            predicate: None,
+            threshold,
+            channel: NotificationChannel::Webhook,
+            target: "https://hooks.example.com/zm".into(),
+            template: None,
+        }
+    }
+
+    #[test]
+    fn thresholds_are_checked_per_trigger() {
+        let user = claims(UserPermissions::default());
+        assert!(spec(NotificationTrigger::ScoreThreshold, None)
+            .check(&user)
+            .is_err());
+        assert!(spec(NotificationTrigger::ScoreThreshold, Some(70_000))
+            .check(&user)
+            .is_err());
+        assert!(spec(NotificationTrigger::ScoreThreshold, Some(60))
+            .check(&user)
+            .is_ok());
+        assert!(spec(NotificationTrigger::EventStart, Some(60))
+            .check(&user)
+            .is_err());
+    }
+
+    #[test]
+    fn storage_health_needs_system_view_and_defaults_its_floor() {
+        let err = spec(NotificationTrigger::StorageHealth, None)
+            .check(&claims(UserPermissions::default()))
+            .expect_err("no System View");
+        assert!(matches!(err, AppError::PermissionDeniedError(_)));
+
+        let admin = claims(UserPermissions::superuser());
+        let mut s = spec(NotificationTrigger::StorageHealth, None);
+        s.check(&admin).unwrap();
+        assert_eq!(s.threshold, Some(DEFAULT_STORAGE_THRESHOLD_PCT));
+        assert!(spec(NotificationTrigger::StorageHealth, Some(100))
+            .check(&admin)
+            .is_err());
+    }
+
+    #[test]
+    fn targets_and_predicates_are_validated() {
+        let user = claims(UserPermissions::default());
+        let mut s = spec(NotificationTrigger::EventStart, None);
+        s.target = "ftp://nope".into();
+        assert!(s.check(&user).is_err());
+
+        let mut s = spec(NotificationTrigger::EventEnd, None);
+        s.predicate = Some(
+            serde_json::from_value(serde_json::json!({
+                "where": {"field": "cause", "op": "regexp", "value": "^Motion"}
+            }))
+            .unwrap(),
+        );
+        assert!(s.check(&user).is_err(), "uncompilable predicate");
+    }
+
+    #[test]
+    fn webhook_rules_always_get_a_secret() {
+        let (secret, issued) =
+            resolve_secret(NotificationChannel::Webhook, None, false, None).unwrap();
+        assert!(issued && secret.unwrap().starts_with("whsec_"));
+
+        // Kept across updates, replaced on rotation.
+        let kept = resolve_secret(
+            NotificationChannel::Webhook,
+            None,
+            false,
+            Some("whsec_old".into()),
+        )
+        .unwrap();
+        assert_eq!(kept, (Some("whsec_old".into()), false));
+        let (rotated, issued) = resolve_secret(
+            NotificationChannel::Webhook,
+            None,
+            true,
+            Some("whsec_old".into()),
+        )
+        .unwrap();
+        assert!(issued && rotated.as_deref() != Some("whsec_old"));
+
+        // Leaving the webhook channel drops it; other channels refuse one.
+        assert_eq!(
+            resolve_secret(NotificationChannel::Smtp, None, false, Some("x".into())).unwrap(),
+            (None, false)
+        );
+        assert!(
+            resolve_secret(NotificationChannel::Mqtt, Some("k".repeat(16)), false, None).is_err()
+        );
+    }
+
+    #[test]
+    fn issued_secrets_are_shown_once() {
+        let now = chrono::Utc::now().naive_utc();
+        let model = notification_rules::Model {
+            id: 1,
+            user_id: 5,
+            name: "hook".into(),
+            enabled: true,
+            trigger: NotificationTrigger::EventEnd,
+            predicate_json: None,
+            threshold: None,
+            channel: NotificationChannel::Webhook,
+            target: "https://hooks.example.com/zm".into(),
+            secret: Some("whsec_abc".into()),
+            template_json: None,
+            dedupe_window_seconds: 0,
+            created_at: now,
+            updated_at: now,
+        };
+        let shown = respond(model.clone(), true);
+        assert_eq!(shown.secret.as_deref(), Some("whsec_abc"));
+        let hidden = respond(model, false);
+        assert!(hidden.secret.is_none() && hidden.has_secret);
+    }
+
+    #[test]
+    fn empty_templates_are_not_stored() {
+        let user = claims(UserPermissions::default());
+        let mut s = spec(NotificationTrigger::EventStart, None);
+        s.template = Some(NotificationTemplate::default());
+        assert_eq!(s.check(&user).unwrap(), (None, None));
+    }
+}
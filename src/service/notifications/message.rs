@@ -0,0 +1,182 @@
+//! What a notification says: the payload every channel carries, and the
+//! `{{placeholder}}` templating used for email subjects and bodies.
+
+use chrono::{SecondsFormat, Utc};
+use serde::Serialize;
+
+use crate::dto::request::notification_rules::NotificationTemplate;
+use crate::entity::notification_rules;
+use crate::entity::sea_orm_active_enums::NotificationTrigger;
+
+/// The notification as delivered: the JSON body of a webhook and an MQTT
+/// message, and the source of the template placeholders.
+#[derive(Debug, Clone, Serialize)]
+pub struct NotificationMessage {
+    pub rule_id: u64,
+    /// The rule's name.
+    pub rule: String,
+    pub trigger: NotificationTrigger,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub monitor_id: Option<u32>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub event_id: Option<u64>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub cause: Option<String>,
+    /// The frame score that crossed a `score_threshold`, or the event's
+    /// maximum score for the other event triggers.
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub score: Option<u16>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub storage_id: Option<u16>,
+    /// Free space, in percent, for `storage_health`.
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub free_pct: Option<f64>,
+    /// When the notification was raised (RFC 3339, UTC).
+    pub time: String,
+    /// The rendered template body, when the rule has one.
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub message: Option<String>,
+}
+
+impl NotificationMessage {
+    pub fn new(rule: &notification_rules::Model) -> Self {
+        Self {
+            rule_id: rule.id,
+            rule: rule.name.clone(),
+            trigger: rule.trigger,
+            monitor_id: None,
+            event_id: None,
+            cause: None,
+            score: None,
+            storage_id: None,
+            free_pct: None,
+            time: Utc::now().to_rfc3339_opts(SecondsFormat::Secs, true),
+            message: None,
+        }
+    }
+
+    fn placeholder(&self, name: &str) -> Option<String> {
+        Some(match name {
+            "rule" => self.rule.clone(),
+            "trigger" => trigger_name(self.trigger).to_string(),
+            "monitor_id" => self.monitor_id?.to_string(),
+            "event_id" => self.event_id?.to_string(),
+            "cause" => self.cause.clone()?,
+            "score" => self.score?.to_string(),
+            "storage_id" => self.storage_id?.to_string(),
+            "free_pct" => format!("{:.1}", self.free_pct?),
+            "time" => self.time.clone(),
+            _ => return None,
+        })
+    }
+
+    /// Fill `{{name}}` placeholders. Unknown names and values this
+    /// notification does not have render as empty text.
+    pub fn render(&self, template: &str) -> String {
+        let mut out = String::with_capacity(template.len());
+        let mut rest = template;
+        while let Some(start) = rest.find("{{") {
+            out.push_str(&rest[..start]);
+            let after = &rest[start + 2..];
+            let Some(end) = after.find("}}") else {
+                // Unterminated: keep the text as written.
+                out.push_str(&rest[start..]);
+                return out;
+            };
+            if let Some(value) = self.placeholder(after[..end].trim()) {
+                out.push_str(&value);
+            }
+            rest = &after[end + 2..];
+        }
+        out.push_str(rest);
+        out
+    }
+
+    /// Email subject: the template's, or a one-line default.
+    pub fn subject(&self, template: &NotificationTemplate) -> String {
+        match &template.subject {
+            Some(t) => self.render(t),
+            None => match (self.trigger, self.monitor_id, self.storage_id) {
+                (NotificationTrigger::StorageHealth, _, Some(storage)) => format!(
+                    "[zm] {}: storage {storage} at {:.1}% free",
+                    self.rule,
+                    self.free_pct.unwrap_or_default()
+                ),
+                (trigger, Some(monitor), _) => format!(
+                    "[zm] {}: {} on monitor {monitor}",
+                    self.rule,
+                    trigger_name(trigger)
+                ),
+                (trigger, None, _) => format!("[zm] {}: {}", self.rule, trigger_name(trigger)),
+            },
+        }
+    }
+
+    /// Email body: the template's, or the notification as pretty JSON.
+    pub fn body(&self, template: &NotificationTemplate) -> String {
+        match &template.body {
+            Some(t) => self.render(t),
+            None => serde_json::to_string_pretty(self).unwrap_or_default(),
+        }
+    }
+}
+
+/// The wire name of a trigger (`event_start`, ...).
+pub fn trigger_name(trigger: NotificationTrigger) -> &'static str {
+    match trigger {
+        NotificationTrigger::EventStart => "event_start",
+        NotificationTrigger::EventEnd => "event_end",
+        NotificationTrigger::ScoreThreshold => "score_threshold",
+        NotificationTrigger::StorageHealth => "storage_health",
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn message() -> NotificationMessage {
+        NotificationMessage {
+            rule_id: 4,
+            rule: "Driveway".into(),
+            trigger: NotificationTrigger::ScoreThreshold,
+            monitor_id: Some(3),
+            event_id: Some(1200),
+            cause: Some("person".into()),
+            score: Some(87),
+            storage_id: None,
+            free_pct: None,
+            time: "2026-10-18T10:00:00Z".into(),
+            message: None,
+        }
+    }
+
+    #[test]
+    fn render_fills_known_placeholders() {
+        let m = message();
+        assert_eq!(
+            m.render("{{cause}} on {{ monitor_id }} (event {{event_id}}, score {{score}})"),
+            "person on 3 (event 1200, score 87)"
+        );
+        // Absent values and unknown names render empty; stray braces survive.
+        assert_eq!(m.render("[{{storage_id}}][{{nope}}]"), "[][]");
+        assert_eq!(m.render("a {{cause"), "a {{cause");
+    }
+
+    #[test]
+    fn default_subject_names_the_monitor_or_storage() {
+        let m = message();
+        let none = NotificationTemplate::default();
+        assert_eq!(
+            m.subject(&none),
+            "[zm] Driveway: score_threshold on monitor 3"
+        );
+
+        let mut s = message();
+        s.trigger = NotificationTrigger::StorageHealth;
+        s.monitor_id = None;
+        s.storage_id = Some(1);
+        s.free_pct = Some(4.31);
+        assert_eq!(s.subject(&none), "[zm] Driveway: storage 1 at 4.3% free");
+    }
+}
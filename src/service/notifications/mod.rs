@@ -0,0 +1,637 @@
+//! Notification rules engine.
+//!
+//! Rules (`notification_rules`) are per user: a trigger, an optional filter-AST
+//! predicate over the event, a channel and a dedupe window. Event sources call
+//! the [`Notifier`] as they open, score and close `Events` rows — the zm-next
+//! ingest ([`crate::service::zmnext::EventIngestor`]) and the ONVIF PullPoint
+//! listener — and a storage check raises `storage_health` on a timer, so alerts
+//! no longer depend on `zmfilter.pl` polling the table.
+//!
+//! Raising a signal never blocks the caller: signals go over a bounded queue to
+//! one dispatcher task, which matches them against the cached enabled rules,
+//! applies the owner's permissions, the predicate and the dedupe window, and
+//! hands each delivery to its own task. Every delivery is recorded in
+//! `notification_deliveries`, which replaces ZoneMinder's per-event
+//! `Emailed`/`Messaged` flags.
+//!
+//! A rule only ever sees what its owner could: event triggers need `Events`
+//! View on the event's monitor, `storage_health` needs `System` View, and a
+//! disabled user's rules are skipped.
+
+pub mod channels;
+pub mod message;
+
+use std::collections::{HashMap, HashSet};
+use std::sync::atomic::{AtomicBool, Ordering};
+use std::sync::Arc;
+use std::time::Instant;
+
+use sea_orm::{
+    ColumnTrait, Condition, DatabaseConnection, EntityTrait, QueryFilter, QueryOrder, Set,
+};
+use tokio::sync::mpsc;
+use tracing::{debug, info, warn};
+
+use crate::client::http::HttpClient;
+use crate::configure::notifications::NotificationsConfig;
+use crate::dto::request::filter_ast::FilterQuery;
+use crate::dto::request::notification_rules::NotificationTemplate;
+use crate::entity::sea_orm_active_enums::{DeliveryStatus, NotificationTrigger};
+use crate::entity::{events, notification_deliveries, notification_rules, storage};
+use crate::error::{AppError, AppResult};
+use crate::repo;
+use crate::util::authz::{Feature, Level, UserPermissions};
+
+use self::message::NotificationMessage;
+
+/// Something a rule may fire on.
+#[derive(Debug, Clone, PartialEq)]
+pub enum Signal {
+    EventStart {
+        monitor_id: u32,
+        event_id: u64,
+    },
+    EventEnd {
+        monitor_id: u32,
+        event_id: u64,
+    },
+    /// A frame of an open event was scored.
+    Score {
+        monitor_id: u32,
+        event_id: u64,
+        score: u16,
+    },
+    /// A periodic free-space reading for one Storage.
+    StorageLevel {
+        storage_id: u16,
+        free_pct: f64,
+    },
+}
+
+impl Signal {
+    fn trigger(&self) -> NotificationTrigger {
+        match self {
+            Signal::EventStart { .. } => NotificationTrigger::EventStart,
+            Signal::EventEnd { .. } => NotificationTrigger::EventEnd,
+            Signal::Score { .. } => NotificationTrigger::ScoreThreshold,
+            Signal::StorageLevel { .. } => NotificationTrigger::StorageHealth,
+        }
+    }
+
+    /// What the dedupe window groups repeats by.
+    fn dedupe_key(&self) -> String {
+        match self {
+            Signal::EventStart { monitor_id, .. }
+            | Signal::EventEnd { monitor_id, .. }
+            | Signal::Score { monitor_id, .. } => format!("monitor:{monitor_id}"),
+            Signal::StorageLevel { storage_id, .. } => format!("storage:{storage_id}"),
+        }
+    }
+
+    fn event(&self) -> Option<(u32, u64)> {
+        match *self {
+            Signal::EventStart {
+                monitor_id,
+                event_id,
+            }
+            | Signal::EventEnd {
+                monitor_id,
+                event_id,
+            }
+            | Signal::Score {
+                monitor_id,
+                event_id,
+                ..
+            } => Some((monitor_id, event_id)),
+            Signal::StorageLevel { .. } => None,
+        }
+    }
+}
+
+/// Whether `rule` fires on `signal`, before permissions, predicate and dedupe.
+fn matches_trigger(rule: &notification_rules::Model, signal: &Signal) -> bool {
+    if rule.trigger != signal.trigger() {
+        return false;
+    }
+    match *signal {
+        Signal::Score { score, .. } => rule.threshold.is_some_and(|t| u32::from(score) >= t),
+        Signal::StorageLevel { free_pct, .. } => {
+            free_pct < f64::from(rule.threshold.unwrap_or(DEFAULT_STORAGE_THRESHOLD_PCT))
+        }
+        _ => true,
+    }
+}
+
+/// Free-space floor for a `storage_health` rule that does not set one.
+pub const DEFAULT_STORAGE_THRESHOLD_PCT: u32 = 10;
+
+/// Handle for raising notification signals. Cheap to share; a disabled
+/// notifier (the default, and in tests) drops every signal.
+#[derive(Default)]
+pub struct Notifier {
+    tx: Option<mpsc::Sender<Signal>>,
+    /// Set when rules change, so the dispatcher reloads its cache.
+    rules_changed: Arc<AtomicBool>,
+}
+
+impl Notifier {
+    /// A notifier that drops every signal.
+    pub fn disabled() -> Self {
+        Self::default()
+    }
+
+    /// Start the dispatcher and the storage check, returning the handle that
+    /// feeds them.
+    pub fn spawn(
+        db: Arc<DatabaseConnection>,
+        config: NotificationsConfig,
+        http: HttpClient,
+    ) -> Self {
+        let (tx, rx) = mpsc::channel(config.queue_capacity.max(1));
+        let rules_changed = Arc::new(AtomicBool::new(true));
+        let config = Arc::new(config);
+
+        spawn_storage_check(db.clone(), Arc::clone(&config), tx.clone());
+        let dispatcher = Dispatcher {
+            db,
+            config,
+            http,
+            rules_changed: Arc::clone(&rules_changed),
+            rules: Vec::new(),
+            last_sent: HashMap::new(),
+            scored: HashSet::new(),
+        };
+        tokio::spawn(dispatcher.run(rx));
+        Self {
+            tx: Some(tx),
+            rules_changed,
+        }
+    }
+
+    pub fn is_enabled(&self) -> bool {
+        self.tx.is_some()
+    }
+
+    pub fn event_start(&self, monitor_id: u32, event_id: u64) {
+        self.raise(Signal::EventStart {
+            monitor_id,
+            event_id,
+        });
+    }
+
+    pub fn event_end(&self, monitor_id: u32, event_id: u64) {
+        self.raise(Signal::EventEnd {
+            monitor_id,
+            event_id,
+        });
+    }
+
+    pub fn score(&self, monitor_id: u32, event_id: u64, score: u16) {
+        self.raise(Signal::Score {
+            monitor_id,
+            event_id,
+            score,
+        });
+    }
+
+    /// Queue a signal without waiting. A full queue drops it with a warning:
+    /// event ingest must never stall behind a slow mail relay.
+    pub fn raise(&self, signal: Signal) {
+        let Some(tx) = &self.tx else { return };
+        if let Err(e) = tx.try_send(signal) {
+            warn!("notifications: queue full or closed, dropping signal: {e}");
+        }
+    }
+
+    /// Tell the dispatcher to reload rules (after a create/update/delete).
+    pub fn rules_changed(&self) {
+        self.rules_changed.store(true, Ordering::Release);
+    }
+}
+
+/// Every `interval`, read each Storage's free space and raise a
+/// [`Signal::StorageLevel`] for it.
+fn spawn_storage_check(
+    db: Arc<DatabaseConnection>,
+    config: Arc<NotificationsConfig>,
+    tx: mpsc::Sender<Signal>,
+) {
+    tokio::spawn(async move {
+        let mut ticker = tokio::time::interval(config.storage_check_interval());
+        ticker.set_missed_tick_behavior(tokio::time::MissedTickBehavior::Delay);
+        loop {
+            ticker.tick().await;
+            let storages = match storage::Entity::find()
+                .order_by_asc(storage::Column::Id)
+                .all(db.as_ref())
+                .await
+            {
+                Ok(s) => s,
+                Err(e) => {
+                    warn!("notifications: storage check could not list storage: {e}");
+                    continue;
+                }
+            };
+            for st in storages {
+                let Some((total, avail)) = crate::service::retention::fs_total_avail(&st.path)
+                else {
+                    continue;
+                };
+                if total == 0 {
+                    continue;
+                }
+                let free_pct = avail as f64 / total as f64 * 100.0;
+                if tx
+                    .send(Signal::StorageLevel {
+                        storage_id: st.id,
+                        free_pct,
+                    })
+                    .await
+                    .is_err()
+                {
+                    return;
+                }
+            }
+        }
+    });
+}
+
+struct Dispatcher {
+    db: Arc<DatabaseConnection>,
+    config: Arc<NotificationsConfig>,
+    http: HttpClient,
+    rules_changed: Arc<AtomicBool>,
+    /// Enabled rules, reloaded when `rules_changed` is set.
+    rules: Vec<notification_rules::Model>,
+    /// When each `(rule, dedupe key)` last dispatched. Covers the window for
+    /// deliveries still in flight; the `sent` rows cover it across restarts.
+    last_sent: HashMap<(u64, String), Instant>,
+    /// `(rule, event)` pairs a `score_threshold` rule has already fired for,
+    /// so further detections of the same event skip the DB check. Cleared
+    /// when the event ends.
+    scored: HashSet<(u64, u64)>,
+}
+
+impl Dispatcher {
+    async fn run(mut self, mut rx: mpsc::Receiver<Signal>) {
+        info!("notification dispatcher started");
+        while let Some(signal) = rx.recv().await {
+            if let Err(e) = self.handle(&signal).await {
+                warn!("notifications: {signal:?} failed: {e}");
+            }
+        }
+    }
+
+    async fn reload_rules(&mut self) -> AppResult<()> {
+        if self.rules_changed.swap(false, Ordering::AcqRel) {
+            self.rules = repo::notification_rules::find_enabled(&self.db).await?;
+            debug!("notifications: {} enabled rule(s) loaded", self.rules.len());
+        }
+        Ok(())
+    }
+
+    async fn handle(&mut self, signal: &Signal) -> AppResult<()> {
+        if let Err(e) = self.reload_rules().await {
+            // Try again on the next signal.
+            self.rules_changed.store(true, Ordering::Release);
+            return Err(e);
+        }
+        let candidates: Vec<notification_rules::Model> = self
+            .rules
+            .iter()
+            .filter(|r| matches_trigger(r, signal))
+            .cloned()
+            .collect();
+
+        // The event, loaded once for all candidate rules.
+        let mut event: Option<events::Model> = None;
+        if let Some((_, event_id)) = signal.event() {
+            if !candidates.is_empty() {
+                event = events::Entity::find_by_id(event_id)
+                    .filter(events::Column::DeletedAt.is_null())
+                    .one(self.db.as_ref())
+                    .await?;
+                if event.is_none() {
+                    debug!("notifications: event {event_id} is gone; nothing to notify");
+                }
+            }
+        }
+
+        for rule in candidates {
+            if let Err(e) = self.consider(&rule, signal, event.as_ref()).await {
+                warn!("notifications: rule {} on {signal:?}: {e}", rule.id);
+            }
+        }
+
+        if let Signal::EventEnd { event_id, .. } = *signal {
+            self.scored.retain(|&(_, e)| e != event_id);
+        }
+        Ok(())
+    }
+
+    /// Decide whether one candidate rule fires, and if so dispatch it.
+    async fn consider(
+        &mut self,
+        rule: &notification_rules::Model,
+        signal: &Signal,
+        event: Option<&events::Model>,
+    ) -> AppResult<()> {
+        let db = self.db.as_ref();
+        if let Some((_, event_id)) = signal.event() {
+            let Some(event) = event else { return Ok(()) };
+            if self.scored.contains(&(rule.id, event_id)) {
+                return Ok(());
+            }
+            // Once per event per rule, across duplicate signals and restarts.
+            if repo::notification_deliveries::exists_for_event(db, rule.id, event_id).await? {
+                return Ok(());
+            }
+            if !owner_sees_monitor(db, rule.user_id, event.monitor_id).await? {
+                return Ok(());
+            }
+            if !predicate_matches(db, rule, event_id).await? {
+                return Ok(());
+            }
+            if rule.trigger == NotificationTrigger::ScoreThreshold {
+                self.scored.insert((rule.id, event_id));
+            }
+        } else if !owner_has_system_view(db, rule.user_id).await? {
+            return Ok(());
+        }
+
+        let key = signal.dedupe_key();
+        if rule.dedupe_window_seconds > 0 {
+            let window = std::time::Duration::from_secs(rule.dedupe_window_seconds.into());
+            let recent_here = self
+                .last_sent
+                .get(&(rule.id, key.clone()))
+                .is_some_and(|t| t.elapsed() < window);
+            let since = chrono::Utc::now().naive_utc()
+                - chrono::Duration::seconds(rule.dedupe_window_seconds.into());
+            if recent_here
+                || repo::notification_deliveries::sent_since(db, rule.id, &key, since).await?
+            {
+                debug!(
+                    "notifications: rule {} suppressed for {key} (dedupe)",
+                    rule.id
+                );
+                return Ok(());
+            }
+            self.last_sent
+                .insert((rule.id, key.clone()), Instant::now());
+        }
+
+        let message = build_message(rule, signal, event);
+        let delivery = notification_deliveries::ActiveModel {
+            rule_id: Set(rule.id),
+            trigger: Set(rule.trigger),
+            channel: Set(rule.channel),
+            event_id: Set(signal.event().map(|(_, e)| e)),
+            monitor_id: Set(signal.event().map(|(m, _)| m)),
+            storage_id: Set(match *signal {
+                Signal::StorageLevel { storage_id, .. } => Some(storage_id),
+                _ => None,
+            }),
+            dedupe_key: Set(key),
+            ..Default::default()
+        };
+        let (db, config, http, rule) = (
+            Arc::clone(&self.db),
+            Arc::clone(&self.config),
+            self.http.clone(),
+            rule.clone(),
+        );
+        tokio::spawn(async move {
+            send_and_record(&db, &config, &http, &rule, message, delivery).await;
+        });
+        Ok(())
+    }
+}
+
+fn build_message(
+    rule: &notification_rules::Model,
+    signal: &Signal,
+    event: Option<&events::Model>,
+) -> NotificationMessage {
+    let mut message = NotificationMessage::new(rule);
+    if let Some((monitor_id, event_id)) = signal.event() {
+        message.monitor_id = Some(monitor_id);
+        message.event_id = Some(event_id);
+        message.cause = event.and_then(|e| e.cause.clone());
+        message.score = match *signal {
+            Signal::Score { score, .. } => Some(score),
+            _ => event.and_then(|e| e.max_score),
+        };
+    }
+    if let Signal::StorageLevel {
+        storage_id,
+        free_pct,
+    } = *signal
+    {
+        message.storage_id = Some(storage_id);
+        message.free_pct = Some(free_pct);
+    }
+    message
+}
+
+/// Deliver and record the outcome, with every call it took. Webhooks retry
+/// transient failures inside [`channels::deliver`]; nothing is re-queued.
+async fn send_and_record(
+    db: &DatabaseConnection,
+    config: &NotificationsConfig,
+    http: &HttpClient,
+    rule: &notification_rules::Model,
+    mut message: NotificationMessage,
+    mut delivery: notification_deliveries::ActiveModel,
+) {
+    let template = parse_template(rule);
+    message.message = template.body.as_deref().map(|t| message.render(t));
+    let report = channels::deliver(config, http, rule, &template, &message).await;
+
+    delivery.attempts = Set(report.attempts.len() as u32);
+    delivery.response_status = Set(report.response_status);
+    delivery.attempt_log_json = Set(serde_json::to_string(&report.attempts).ok());
+    delivery.created_at = Set(chrono::Utc::now().naive_utc());
+    match report.result {
+        Ok(()) => {
+            delivery.status = Set(DeliveryStatus::Sent);
+            debug!("notifications: rule {} delivered", rule.id);
+        }
+        Err(reason) => {
+            warn!(
+                "notifications: rule {} delivery failed after {} call(s): {reason}",
+                rule.id,
+                report.attempts.len()
+            );
+            delivery.status = Set(DeliveryStatus::Failed);
+            delivery.error = Set(Some(reason));
+        }
+    }
+    if let Err(e) = repo::notification_deliveries::insert(db, delivery).await {
+        warn!(
+            "notifications: could not record delivery for rule {}: {e}",
+            rule.id
+        );
+    }
+}
+
+/// The rule's template, or an empty one (default subject and body).
+pub(crate) fn parse_template(rule: &notification_rules::Model) -> NotificationTemplate {
+    rule.template_json
+        .as_deref()
+        .and_then(|t| serde_json::from_str(t).ok())
+        .unwrap_or_default()
+}
+
+/// Parse a stored predicate back into its AST.
+pub(crate) fn parse_predicate(rule: &notification_rules::Model) -> AppResult<Option<FilterQuery>> {
+    rule.predicate_json
+        .as_deref()
+        .map(|p| {
+            serde_json::from_str(p).map_err(|e| {
+                AppError::BadRequestError(format!(
+                    "notification rule {} holds an unreadable predicate: {e}",
+                    rule.id
+                ))
+            })
+        })
+        .transpose()
+}
+
+/// Whether the event satisfies the rule's predicate (always, without one).
+async fn predicate_matches(
+    db: &DatabaseConnection,
+    rule: &notification_rules::Model,
+    event_id: u64,
+) -> AppResult<bool> {
+    let Some(query) = parse_predicate(rule)? else {
+        return Ok(true);
+    };
+    let condition = Condition::all()
+        .add(crate::service::saved_searches::compile(&query)?)
+        .add(events::Column::Id.eq(event_id));
+    Ok(repo::events::count_with_condition(db, condition, None).await? > 0)
+}
+
+async fn owner_permissions(
+    db: &DatabaseConnection,
+    user_id: u32,
+) -> AppResult<Option<UserPermissions>> {
+    Ok(repo::users::find_by_id(db, user_id)
+        .await?
+        .filter(|u| u.enabled != 0)
+        .map(|u| UserPermissions::from(&u)))
+}
+
+async fn owner_sees_monitor(
+    db: &DatabaseConnection,
+    user_id: u32,
+    monitor_id: u32,
+) -> AppResult<bool> {
+    let Some(perms) = owner_permissions(db, user_id).await? else {
+        return Ok(false);
+    };
+    if perms.level(Feature::Events) < Level::View {
+        return Ok(false);
+    }
+    let scope = crate::service::monitor_acl::resolve(db, user_id).await?;
+    Ok(scope.allows(monitor_id, Level::View))
+}
+
+async fn owner_has_system_view(db: &DatabaseConnection, user_id: u32) -> AppResult<bool> {
+    Ok(owner_permissions(db, user_id)
+        .await?
+        .is_some_and(|p| p.level(Feature::System) >= Level::View))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::entity::sea_orm_active_enums::NotificationChannel;
+
+    fn rule(trigger: NotificationTrigger, threshold: Option<u32>) -> notification_rules::Model {
+        let t = chrono::NaiveDate::from_ymd_opt(2026, 1, 1)
+            .unwrap()
+            .and_hms_opt(0, 0, 0)
+            .unwrap();
+        notification_rules::Model {
+            id: 1,
+            user_id: 2,
+            name: "r".into(),
+            enabled: true,
+            trigger,
+            predicate_json: None,
+            threshold,
+            channel: NotificationChannel::Webhook,
+            target: "http://localhost/hook".into(),
+            secret: None,
+            template_json: None,
+            dedupe_window_seconds: 0,
+            created_at: t,
+            updated_at: t,
+        }
+    }
+
+    #[test]
+    fn triggers_and_thresholds_select_rules() {
+        let start = Signal::EventStart {
+            monitor_id: 1,
+            event_id: 9,
+        };
+        assert!(matches_trigger(
+            &rule(NotificationTrigger::EventStart, None),
+            &start
+        ));
+        assert!(!matches_trigger(
+            &rule(NotificationTrigger::EventEnd, None),
+            &start
+        ));
+
+        let score = |s| Signal::Score {
+            monitor_id: 1,
+            event_id: 9,
+            score: s,
+        };
+        let r = rule(NotificationTrigger::ScoreThreshold, Some(50));
+        assert!(matches_trigger(&r, &score(50)));
+        assert!(!matches_trigger(&r, &score(49)));
+        // A score rule without a threshold never fires.
+        assert!(!matches_trigger(
+            &rule(NotificationTrigger::ScoreThreshold, None),
+            &score(255)
+        ));
+
+        let level = |free_pct| Signal::StorageLevel {
+            storage_id: 1,
+            free_pct,
+        };
+        let r = rule(NotificationTrigger::StorageHealth, None);
+        assert!(matches_trigger(&r, &level(9.9)), "default 10% floor");
+        assert!(!matches_trigger(&r, &level(10.0)));
+    }
+
+    #[test]
+    fn dedupe_groups_by_monitor_or_storage() {
+        let a = Signal::EventStart {
+            monitor_id: 3,
+            event_id: 1,
+        };
+        let b = Signal::EventEnd {
+            monitor_id: 3,
+            event_id: 2,
+        };
+        assert_eq!(a.dedupe_key(), b.dedupe_key());
+        let s = Signal::StorageLevel {
+            storage_id: 3,
+            free_pct: 1.0,
+        };
+        assert_eq!(s.dedupe_key(), "storage:3");
+    }
+
+    #[test]
+    fn disabled_notifier_drops_signals() {
+        let n = Notifier::disabled();
+        assert!(!n.is_enabled());
+        n.event_start(1, 1); // must not panic or block
+    }
+}
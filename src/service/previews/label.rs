@@ -0,0 +1,111 @@
+//! A tiny 5×7 bitmap font for the times burned into contact-sheet tiles.
+//!
+//! Labels are only ever times — `HH:MM:SS` or `+MM:SS` — so digits and a few
+//! separators are all it carries; anything else draws as a space. Keeps the
+//! renderer free of a font file and a text-layout dependency.
+
+use image::{Rgb, RgbImage};
+
+const GLYPH_W: u32 = 5;
+const GLYPH_H: u32 = 7;
+/// Blank columns between glyphs, and the padding of the backing box.
+const SPACING: u32 = 1;
+
+/// Rows of a glyph, top to bottom; bit 4 is the leftmost pixel.
+fn glyph(c: char) -> [u8; 7] {
+    match c {
+        '0' => [0x0E, 0x11, 0x13, 0x15, 0x19, 0x11, 0x0E],
+        '1' => [0x04, 0x0C, 0x04, 0x04, 0x04, 0x04, 0x0E],
+        '2' => [0x0E, 0x11, 0x01, 0x02, 0x04, 0x08, 0x1F],
+        '3' => [0x1F, 0x02, 0x04, 0x02, 0x01, 0x11, 0x0E],
+        '4' => [0x02, 0x06, 0x0A, 0x12, 0x1F, 0x02, 0x02],
+        '5' => [0x1F, 0x10, 0x1E, 0x01, 0x01, 0x11, 0x0E],
+        '6' => [0x06, 0x08, 0x10, 0x1E, 0x11, 0x11, 0x0E],
+        '7' => [0x1F, 0x01, 0x02, 0x04, 0x08, 0x08, 0x08],
+        '8' => [0x0E, 0x11, 0x11, 0x0E, 0x11, 0x11, 0x0E],
+        '9' => [0x0E, 0x11, 0x11, 0x0F, 0x01, 0x02, 0x0C],
+        ':' => [0x00, 0x0C, 0x0C, 0x00, 0x0C, 0x0C, 0x00],
+        '+' => [0x00, 0x04, 0x04, 0x1F, 0x04, 0x04, 0x00],
+        '-' => [0x00, 0x00, 0x00, 0x1F, 0x00, 0x00, 0x00],
+        '.' => [0x00, 0x00, 0x00, 0x00, 0x00, 0x0C, 0x0C],
+        _ => [0; 7],
+    }
+}
+
+/// Size in pixels of `text` drawn at `scale`, including its backing box.
+pub fn size(text: &str, scale: u32) -> (u32, u32) {
+    let n = text.chars().count() as u32;
+    let text_w = n * GLYPH_W + n.saturating_sub(1) * SPACING;
+    (
+        (text_w + 2 * SPACING) * scale,
+        (GLYPH_H + 2 * SPACING) * scale,
+    )
+}
+
+/// Draw `text` in white on a black box whose top-left corner is `(x, y)`.
+/// Pixels falling outside `img` are clipped.
+pub fn draw(img: &mut RgbImage, x: u32, y: u32, text: &str, scale: u32) {
+    let scale = scale.max(1);
+    let (box_w, box_h) = size(text, scale);
+    fill(img, x, y, box_w, box_h, Rgb([0, 0, 0]));
+
+    let mut pen_x = x + SPACING * scale;
+    let pen_y = y + SPACING * scale;
+    for c in text.chars() {
+        for (row, bits) in glyph(c).iter().enumerate() {
+            for col in 0..GLYPH_W {
+                if bits & (0x10 >> col) != 0 {
+                    fill(
+                        img,
+                        pen_x + col * scale,
+                        pen_y + row as u32 * scale,
+                        scale,
+                        scale,
+                        Rgb([255, 255, 255]),
+                    );
+                }
+            }
+        }
+        pen_x += (GLYPH_W + SPACING) * scale;
+    }
+}
+
+fn fill(img: &mut RgbImage, x: u32, y: u32, w: u32, h: u32, colour: Rgb<u8>) {
+    let x_end = (x + w).min(img.width());
+    let y_end = (y + h).min(img.height());
+    for py in y.min(y_end)..y_end {
+        for px in x.min(x_end)..x_end {
+            img.put_pixel(px, py, colour);
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn size_counts_glyphs_gaps_and_padding() {
+        // 2 glyphs (10) + 1 gap + 2 padding = 13 wide; 7 + 2 = 9 tall.
+        assert_eq!(size("12", 1), (13, 9));
+        assert_eq!(size("12", 2), (26, 18));
+    }
+
+    #[test]
+    fn draws_white_on_a_black_box() {
+        let mut img = RgbImage::from_pixel(20, 12, Rgb([9, 9, 9]));
+        draw(&mut img, 0, 0, "-", 1);
+        // Padding is black, the dash's middle row is white, outside untouched.
+        assert_eq!(img.get_pixel(0, 0), &Rgb([0, 0, 0]));
+        assert_eq!(img.get_pixel(3, 4), &Rgb([255, 255, 255]));
+        assert_eq!(img.get_pixel(3, 2), &Rgb([0, 0, 0]));
+        assert_eq!(img.get_pixel(10, 10), &Rgb([9, 9, 9]));
+    }
+
+    #[test]
+    fn clips_at_the_image_edge() {
+        let mut img = RgbImage::new(4, 4);
+        draw(&mut img, 2, 2, "00:00:00", 3);
+        assert_eq!(img.dimensions(), (4, 4));
+    }
+}
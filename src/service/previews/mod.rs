@@ -0,0 +1,414 @@
+//! Animated previews and contact sheets for recorded events.
+//!
+//! `preview.webp` / `preview.gif` loop a few seconds of the recording around
+//! the event's highest-scoring frame; `contact-sheet.jpg` tiles evenly spaced
+//! frames with their wall-clock time burned in. Both are decoded straight from
+//! the event's video with ffmpeg-next (see [`render`]).
+//!
+//! Renders are cached on disk under `cache_dir/{event_id}/`, keyed by kind,
+//! size and the monitor's orientation, and swept by age like the synopsis
+//! cache. Concurrent renders are capped by a semaphore, and two requests for
+//! the same file wait on one render rather than starting two.
+
+pub mod label;
+pub mod render;
+
+use std::path::{Path, PathBuf};
+use std::sync::Arc;
+use std::time::{Duration, SystemTime};
+
+use chrono::NaiveDateTime;
+use dashmap::DashMap;
+use tokio::sync::{Mutex, Semaphore};
+use tracing::{debug, info, warn};
+
+use crate::configure::previews::PreviewsConfig;
+use crate::entity::sea_orm_active_enums::Orientation;
+use crate::streaming::probe::MediaInfo;
+
+/// Errors surfaced by the preview service. The HTTP layer maps these onto
+/// `AppError` (see `handlers::previews`).
+#[derive(Debug, thiserror::Error)]
+pub enum PreviewError {
+    /// The linked ffmpeg lacks the encoder the format needs (`libwebp_anim`).
+    #[error("encoder unavailable: {0}")]
+    EncoderUnavailable(String),
+    /// Nothing could be decoded at the requested offsets.
+    #[error("no frames could be decoded")]
+    NoFrames,
+    /// Decoding or encoding failed.
+    #[error("render failed: {0}")]
+    RenderFailed(String),
+    /// The render did not finish within `render_timeout_seconds`.
+    #[error("render exceeded {0}s timeout")]
+    Timeout(u64),
+    #[error(transparent)]
+    Io(#[from] std::io::Error),
+    #[error(transparent)]
+    Join(#[from] tokio::task::JoinError),
+}
+
+/// What to render.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum PreviewKind {
+    /// Looping animated WebP.
+    Webp,
+    /// Looping animated GIF.
+    Gif,
+    /// `frames` evenly spaced tiles, `columns` to a row.
+    ContactSheet { frames: u32, columns: u32 },
+}
+
+impl PreviewKind {
+    pub fn content_type(self) -> &'static str {
+        match self {
+            PreviewKind::Webp => "image/webp",
+            PreviewKind::Gif => "image/gif",
+            PreviewKind::ContactSheet { .. } => "image/jpeg",
+        }
+    }
+
+    /// Cache file name within the event's directory. Everything that changes
+    /// the pixels is in the name, so a different request never hits a stale
+    /// render.
+    fn file_name(self, width: u32, orientation: &Orientation) -> String {
+        let o = orientation_slug(orientation);
+        match self {
+            PreviewKind::Webp => format!("preview-{width}-{o}.webp"),
+            PreviewKind::Gif => format!("preview-{width}-{o}.gif"),
+            PreviewKind::ContactSheet { frames, columns } => {
+                format!("contact-sheet-{frames}x{columns}-{width}-{o}.jpg")
+            }
+        }
+    }
+}
+
+/// The recording a preview is drawn from.
+#[derive(Debug, Clone)]
+pub struct PreviewSource {
+    pub event_id: u64,
+    /// The event's finished video file.
+    pub video: PathBuf,
+    pub info: MediaInfo,
+    /// Seconds into the event of its highest-scoring frame, if known.
+    pub focus: Option<f64>,
+    /// Event start, for the times burned into contact-sheet tiles.
+    pub started: Option<NaiveDateTime>,
+    pub orientation: Orientation,
+}
+
+/// Renders and caches event previews.
+pub struct PreviewService {
+    config: PreviewsConfig,
+    /// Caps concurrent renders (`max_concurrent_renders`); excess queue.
+    render_slots: Arc<Semaphore>,
+    /// One lock per cache file being rendered, so concurrent requests for the
+    /// same preview wait for the first render instead of repeating it.
+    locks: DashMap<PathBuf, Arc<Mutex<()>>>,
+}
+
+impl PreviewService {
+    pub fn new(config: PreviewsConfig) -> Self {
+        let permits = config.max_concurrent_renders.max(1);
+        Self {
+            config,
+            render_slots: Arc::new(Semaphore::new(permits)),
+            locks: DashMap::new(),
+        }
+    }
+
+    pub fn config(&self) -> &PreviewsConfig {
+        &self.config
+    }
+
+    /// Serve the cached render of `kind` for `source`, rendering it first if
+    /// there is none.
+    pub async fn get(
+        &self,
+        source: PreviewSource,
+        kind: PreviewKind,
+    ) -> Result<Vec<u8>, PreviewError> {
+        let path = self
+            .config
+            .cache_dir
+            .join(source.event_id.to_string())
+            .join(kind.file_name(self.config.width, &source.orientation));
+        if let Ok(bytes) = tokio::fs::read(&path).await {
+            return Ok(bytes);
+        }
+
+        let lock = self.locks.entry(path.clone()).or_default().clone();
+        let result = {
+            let _guard = lock.lock().await;
+            // Another request may have rendered it while we waited.
+            match tokio::fs::read(&path).await {
+                Ok(bytes) => Ok(bytes),
+                Err(_) => self.render_to(&path, source, kind).await,
+            }
+        };
+        drop(lock);
+        self.locks
+            .remove_if(&path, |_, l| Arc::strong_count(l) == 1);
+        result
+    }
+
+    /// Render under a slot and write the result to `path` (via a temporary
+    /// name, so a reader never sees half a file).
+    async fn render_to(
+        &self,
+        path: &Path,
+        source: PreviewSource,
+        kind: PreviewKind,
+    ) -> Result<Vec<u8>, PreviewError> {
+        // The permit moves into the blocking task, so a render that outlives
+        // its timeout still holds its slot until it actually stops.
+        let permit = self
+            .render_slots
+            .clone()
+            .acquire_owned()
+            .await
+            .expect("render semaphore is never closed");
+        debug!("rendering {path:?} for event {}", source.event_id);
+
+        let config = self.config.clone();
+        let render = tokio::task::spawn_blocking(move || {
+            let _permit = permit;
+            render_blocking(&config, &source, kind)
+        });
+        let timeout = self.config.render_timeout_seconds.max(1);
+        let bytes = match tokio::time::timeout(Duration::from_secs(timeout), render).await {
+            Ok(joined) => joined??,
+            Err(_) => return Err(PreviewError::Timeout(timeout)),
+        };
+
+        if let Some(dir) = path.parent() {
+            tokio::fs::create_dir_all(dir).await?;
+        }
+        let tmp = path.with_extension("tmp");
+        tokio::fs::write(&tmp, &bytes).await?;
+        tokio::fs::rename(&tmp, path).await?;
+        Ok(bytes)
+    }
+
+    /// Run one retention pass: delete cached renders older than
+    /// `retention_days`, then any event directory left empty. Returns the
+    /// number of files removed.
+    pub async fn run_retention_once(&self) -> Result<usize, PreviewError> {
+        let max_age = Duration::from_secs(self.config.retention_days * 86_400);
+        let now = SystemTime::now();
+        let mut removed = 0usize;
+
+        let mut events = match tokio::fs::read_dir(&self.config.cache_dir).await {
+            Ok(rd) => rd,
+            Err(e) if e.kind() == std::io::ErrorKind::NotFound => return Ok(0),
+            Err(e) => return Err(e.into()),
+        };
+        while let Some(event_dir) = events.next_entry().await? {
+            if !event_dir.file_type().await?.is_dir() {
+                continue;
+            }
+            let mut files = tokio::fs::read_dir(event_dir.path()).await?;
+            while let Some(file) = files.next_entry().await? {
+                let modified = file.metadata().await?.modified()?;
+                let age = now.duration_since(modified).unwrap_or_default();
+                if age > max_age && tokio::fs::remove_file(file.path()).await.is_ok() {
+                    removed += 1;
+                }
+            }
+            // Fails harmlessly while the directory still holds renders.
+            let _ = tokio::fs::remove_dir(event_dir.path()).await;
+        }
+        if removed > 0 {
+            info!("preview retention: removed {removed} cached previews");
+        }
+        Ok(removed)
+    }
+
+    /// Spawn the periodic retention loop. A failed pass is logged and retried
+    /// on the next tick.
+    pub fn spawn_retention_task(self: Arc<Self>, interval: Duration) {
+        tokio::spawn(async move {
+            let mut ticker = tokio::time::interval(interval);
+            loop {
+                ticker.tick().await;
+                if let Err(e) = self.run_retention_once().await {
+                    warn!("preview retention pass failed: {e}");
+                }
+            }
+        });
+    }
+}
+
+fn render_blocking(
+    config: &PreviewsConfig,
+    source: &PreviewSource,
+    kind: PreviewKind,
+) -> Result<Vec<u8>, PreviewError> {
+    let duration = source.info.duration_seconds;
+    match kind {
+        PreviewKind::Webp | PreviewKind::Gif => {
+            let fps = config.animation_fps.max(1);
+            let offsets = animation_offsets(duration, source.focus, config.animation_seconds, fps);
+            let frames =
+                render::sample_frames(&source.video, &offsets, config.width, &source.orientation)?;
+            if kind == PreviewKind::Webp {
+                render::encode_webp(&frames, fps)
+            } else {
+                render::encode_gif(&frames, fps)
+            }
+        }
+        PreviewKind::ContactSheet { frames, columns } => {
+            let offsets = sheet_offsets(duration, frames);
+            let tiles =
+                render::sample_frames(&source.video, &offsets, config.width, &source.orientation)?;
+            let labels: Vec<String> = offsets
+                .iter()
+                .map(|&t| tile_label(source.started, t))
+                .collect();
+            render::contact_sheet(&tiles, &labels, columns)
+        }
+    }
+}
+
+/// Offsets (seconds) of the animation's frames: `seconds` long at `fps`,
+/// centred on `focus` — or the middle of the recording without one — and
+/// shifted to stay inside it. A recording shorter than `seconds` is used
+/// whole.
+pub fn animation_offsets(duration: f64, focus: Option<f64>, seconds: f64, fps: u32) -> Vec<f64> {
+    let duration = duration.max(0.0);
+    let span = seconds.max(0.0).min(duration);
+    let centre = focus.unwrap_or(duration / 2.0);
+    let start = (centre - span / 2.0).clamp(0.0, duration - span);
+    let count = ((span * f64::from(fps)).round() as usize).max(1);
+    (0..count)
+        .map(|i| start + i as f64 / f64::from(fps))
+        .collect()
+}
+
+/// Offsets (seconds) of `n` contact-sheet tiles, each in the middle of an
+/// equal slice of the recording, so neither the first nor the last frame —
+/// often black or mid-transition — is used.
+pub fn sheet_offsets(duration: f64, n: u32) -> Vec<f64> {
+    let duration = duration.max(0.0);
+    (0..n)
+        .map(|i| (f64::from(i) + 0.5) * duration / f64::from(n))
+        .collect()
+}
+
+/// Text burned into a tile: the wall-clock time when the event's start is
+/// known, else the offset into the recording.
+pub fn tile_label(started: Option<NaiveDateTime>, offset: f64) -> String {
+    match started {
+        Some(start) => {
+            let at = start + chrono::Duration::milliseconds((offset * 1000.0) as i64);
+            at.format("%H:%M:%S").to_string()
+        }
+        None => {
+            let secs = offset.max(0.0) as u64;
+            format!("+{:02}:{:02}", secs / 60, secs % 60)
+        }
+    }
+}
+
+fn orientation_slug(orientation: &Orientation) -> &'static str {
+    match orientation {
+        Orientation::Rotate0 => "r0",
+        Orientation::Rotate90 => "r90",
+        Orientation::Rotate180 => "r180",
+        Orientation::Rotate270 => "r270",
+        Orientation::FlipHori => "fh",
+        Orientation::FlipVert => "fv",
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn close(a: &[f64], b: &[f64]) -> bool {
+        a.len() == b.len() && a.iter().zip(b).all(|(x, y)| (x - y).abs() < 1e-9)
+    }
+
+    #[test]
+    fn animation_is_centred_on_the_focus() {
+        let offsets = animation_offsets(60.0, Some(30.0), 2.0, 2);
+        assert!(close(&offsets, &[29.0, 29.5, 30.0, 30.5]));
+    }
+
+    #[test]
+    fn animation_stays_inside_the_recording() {
+        let early = animation_offsets(60.0, Some(0.5), 2.0, 1);
+        assert!(close(&early, &[0.0, 1.0]));
+        let late = animation_offsets(60.0, Some(59.9), 2.0, 1);
+        assert!(close(&late, &[58.0, 59.0]));
+    }
+
+    #[test]
+    fn animation_without_focus_uses_the_middle() {
+        let offsets = animation_offsets(10.0, None, 2.0, 1);
+        assert!(close(&offsets, &[4.0, 5.0]));
+    }
+
+    #[test]
+    fn short_recording_is_used_whole() {
+        let offsets = animation_offsets(1.0, Some(0.8), 4.0, 2);
+        assert!(close(&offsets, &[0.0, 0.5]));
+        assert_eq!(animation_offsets(0.0, None, 4.0, 5), vec![0.0]);
+    }
+
+    #[test]
+    fn sheet_tiles_sit_mid_slice() {
+        assert!(close(&sheet_offsets(40.0, 4), &[5.0, 15.0, 25.0, 35.0]));
+        assert!(sheet_offsets(40.0, 0).is_empty());
+    }
+
+    #[test]
+    fn tile_label_prefers_wall_clock() {
+        let start = chrono::NaiveDate::from_ymd_opt(2026, 10, 18)
+            .unwrap()
+            .and_hms_opt(23, 59, 50)
+            .unwrap();
+        assert_eq!(tile_label(Some(start), 15.4), "00:00:05");
+        assert_eq!(tile_label(None, 75.9), "+01:15");
+    }
+
+    #[test]
+    fn cache_name_covers_everything_that_changes_pixels() {
+        let sheet = PreviewKind::ContactSheet {
+            frames: 12,
+            columns: 4,
+        };
+        assert_eq!(
+            sheet.file_name(320, &Orientation::Rotate90),
+            "contact-sheet-12x4-320-r90.jpg"
+        );
+        assert_ne!(
+            PreviewKind::Gif.file_name(320, &Orientation::Rotate0),
+            PreviewKind::Gif.file_name(480, &Orientation::Rotate0)
+        );
+    }
+
+    #[tokio::test]
+    async fn retention_removes_old_renders_and_empty_dirs() {
+        let dir = tempfile::tempdir().unwrap();
+        let event_dir = dir.path().join("7");
+        std::fs::create_dir_all(&event_dir).unwrap();
+        let old = event_dir.join("preview-320-r0.gif");
+        std::fs::write(&old, b"gif").unwrap();
+        let two_days_ago = SystemTime::now() - Duration::from_secs(2 * 86_400);
+        std::fs::File::options()
+            .write(true)
+            .open(&old)
+            .unwrap()
+            .set_modified(two_days_ago)
+            .unwrap();
+
+        let service = PreviewService::new(PreviewsConfig {
+            cache_dir: dir.path().to_path_buf(),
+            retention_days: 1,
+            ..Default::default()
+        });
+        assert_eq!(service.run_retention_once().await.unwrap(), 1);
+        assert!(!event_dir.exists());
+    }
+}
@@ -0,0 +1,449 @@
+//! Frame sampling and encoding for event previews.
+//!
+//! Frames are decoded from the event's recording with ffmpeg-next, scaled
+//! down and rotated to the monitor's orientation, then encoded: animated WebP
+//! through libavcodec's `libwebp_anim`, GIF and the contact-sheet JPEG through
+//! `image`. zm-api never shells out to the `ffmpeg` binary.
+
+use std::path::Path;
+
+use ffmpeg::format::Pixel;
+use ffmpeg::{codec, decoder, encoder, format, frame, software, Dictionary, Packet, Rational};
+use ffmpeg_next as ffmpeg;
+use image::codecs::gif::{GifEncoder, Repeat};
+use image::codecs::jpeg::JpegEncoder;
+use image::{Delay, DynamicImage, Frame, Rgb, RgbImage};
+
+use super::{label, PreviewError};
+use crate::entity::sea_orm_active_enums::Orientation;
+use crate::service::image_orientation;
+
+/// Seek instead of decoding forward when the next offset is further ahead
+/// than this — roughly a keyframe interval on a ZoneMinder recording.
+const SEEK_THRESHOLD_SECONDS: f64 = 2.0;
+
+/// Gap between contact-sheet tiles, and around the sheet's edge.
+const SHEET_GAP: u32 = 4;
+const SHEET_BACKGROUND: Rgb<u8> = Rgb([24, 24, 24]);
+const SHEET_JPEG_QUALITY: u8 = 85;
+
+fn enc_err(stage: &str) -> impl Fn(ffmpeg::Error) -> PreviewError + '_ {
+    move |e| PreviewError::RenderFailed(format!("{stage}: {e}"))
+}
+
+/// Decodes a recording front to back, seeking over long gaps.
+struct Sampler {
+    ictx: format::context::Input,
+    stream_index: usize,
+    /// Seconds per stream time-base tick.
+    tick: f64,
+    /// Stream start time in seconds; offsets are measured from it.
+    origin: f64,
+    decoder: decoder::Video,
+    eof: bool,
+}
+
+impl Sampler {
+    fn open(path: &Path) -> Result<Self, PreviewError> {
+        let ictx = format::input(&path).map_err(enc_err("open recording"))?;
+        let stream = ictx
+            .streams()
+            .best(ffmpeg::media::Type::Video)
+            .ok_or_else(|| PreviewError::RenderFailed("no video stream".into()))?;
+        let stream_index = stream.index();
+        let tb = stream.time_base();
+        let tick = f64::from(tb.numerator()) / f64::from(tb.denominator().max(1));
+        let origin = match stream.start_time() {
+            ffmpeg::ffi::AV_NOPTS_VALUE => 0.0,
+            ts => ts as f64 * tick,
+        };
+        let decoder = codec::context::Context::from_parameters(stream.parameters())
+            .map_err(enc_err("decoder context"))?
+            .decoder()
+            .video()
+            .map_err(enc_err("open decoder"))?;
+        Ok(Self {
+            ictx,
+            stream_index,
+            tick,
+            origin,
+            decoder,
+            eof: false,
+        })
+    }
+
+    /// Jump to the keyframe at or before `offset`.
+    fn seek(&mut self, offset: f64) {
+        let ts = ((self.origin + offset) * f64::from(ffmpeg::ffi::AV_TIME_BASE)) as i64;
+        // Best effort: on failure decoding simply carries on forward.
+        if self.ictx.seek(ts, ..ts).is_ok() {
+            self.decoder.flush();
+            self.eof = false;
+        }
+    }
+
+    /// Decode the next frame into `out`, returning its offset, or `None` at
+    /// the end of the recording.
+    fn next(&mut self, out: &mut frame::Video) -> Result<Option<f64>, PreviewError> {
+        loop {
+            if self.decoder.receive_frame(out).is_ok() {
+                let ts = out.timestamp().or_else(|| out.pts()).unwrap_or(0);
+                return Ok(Some(ts as f64 * self.tick - self.origin));
+            }
+            if self.eof {
+                return Ok(None);
+            }
+            let mut packet = Packet::empty();
+            match packet.read(&mut self.ictx) {
+                Ok(()) if packet.stream() == self.stream_index => {
+                    // A corrupt packet costs one frame, not the preview.
+                    let _ = self.decoder.send_packet(&packet);
+                }
+                Ok(()) => {}
+                Err(ffmpeg::Error::Eof) => {
+                    self.eof = true;
+                    let _ = self.decoder.send_eof();
+                }
+                Err(e) => return Err(enc_err("read packet")(e)),
+            }
+        }
+    }
+}
+
+/// Converts decoded frames to RGB images at most `width` wide.
+struct Scaler {
+    width: u32,
+    ctx: Option<(software::scaling::Context, (Pixel, u32, u32))>,
+}
+
+impl Scaler {
+    fn new(width: u32) -> Self {
+        Self { width, ctx: None }
+    }
+
+    fn to_rgb(&mut self, src: &frame::Video) -> Result<RgbImage, PreviewError> {
+        let key = (src.format(), src.width(), src.height());
+        if self.ctx.as_ref().map(|(_, k)| *k) != Some(key) {
+            let (w, h) = fit(src.width(), src.height(), self.width);
+            let ctx = software::scaling::Context::get(
+                src.format(),
+                src.width(),
+                src.height(),
+                Pixel::RGB24,
+                w,
+                h,
+                software::scaling::Flags::BILINEAR,
+            )
+            .map_err(enc_err("scaler init"))?;
+            self.ctx = Some((ctx, key));
+        }
+        let (ctx, _) = self.ctx.as_mut().expect("scaler built above");
+        let mut rgb = frame::Video::empty();
+        ctx.run(src, &mut rgb).map_err(enc_err("scale frame"))?;
+        Ok(frame_to_image(&rgb))
+    }
+}
+
+/// Output size for a `w`×`h` frame capped at `max_width`, aspect kept and
+/// both sides even (libwebp's YUV420 needs it).
+fn fit(w: u32, h: u32, max_width: u32) -> (u32, u32) {
+    if w <= max_width {
+        return ((w & !1).max(2), (h & !1).max(2));
+    }
+    let scaled_h = (u64::from(h) * u64::from(max_width) / u64::from(w)) as u32;
+    ((max_width & !1).max(2), (scaled_h & !1).max(2))
+}
+
+/// Copy an `RGB24` AVFrame into a tightly-packed image, honouring its line
+/// stride (which may be wider than `w*3`).
+fn frame_to_image(src: &frame::Video) -> RgbImage {
+    let (w, h) = (src.width(), src.height());
+    let row_bytes = w as usize * 3;
+    let stride = src.stride(0);
+    let data = src.data(0);
+    let mut raw = Vec::with_capacity(row_bytes * h as usize);
+    for y in 0..h as usize {
+        raw.extend_from_slice(&data[y * stride..y * stride + row_bytes]);
+    }
+    RgbImage::from_raw(w, h, raw).expect("buffer sized from frame dimensions")
+}
+
+/// Decode the first frame at or after each of `offsets` (seconds into the
+/// recording, ascending), scaled to at most `width` wide and rotated to
+/// `orientation`. Offsets past the last frame get the last frame; nothing
+/// decodable at all is [`PreviewError::NoFrames`].
+pub fn sample_frames(
+    path: &Path,
+    offsets: &[f64],
+    width: u32,
+    orientation: &Orientation,
+) -> Result<Vec<RgbImage>, PreviewError> {
+    let _ = ffmpeg::init();
+
+    let mut sampler = Sampler::open(path)?;
+    let mut scaler = Scaler::new(width);
+    let mut decoded = frame::Video::empty();
+    // The frame decoded before `decoded`, kept for offsets past the end.
+    let mut skipped = frame::Video::empty();
+    let mut have_skipped = false;
+    // The last frame sampled and its offset.
+    let mut current: Option<(f64, RgbImage)> = None;
+    let mut images = Vec::with_capacity(offsets.len());
+
+    for &target in offsets {
+        let position = match &current {
+            Some((t, img)) if *t >= target => {
+                images.push(img.clone());
+                continue;
+            }
+            Some((t, _)) => *t,
+            None => 0.0,
+        };
+        if target - position > SEEK_THRESHOLD_SECONDS {
+            sampler.seek(target);
+            have_skipped = false;
+        }
+        loop {
+            match sampler.next(&mut decoded)? {
+                Some(t) if t >= target => {
+                    let img = scaler.to_rgb(&decoded)?;
+                    images.push(img.clone());
+                    current = Some((t, img));
+                    break;
+                }
+                Some(_) => {
+                    std::mem::swap(&mut decoded, &mut skipped);
+                    have_skipped = true;
+                }
+                None => {
+                    if have_skipped {
+                        let img = scaler.to_rgb(&skipped)?;
+                        images.push(img.clone());
+                        current = Some((f64::INFINITY, img));
+                    } else if let Some((_, img)) = &current {
+                        images.push(img.clone());
+                    }
+                    break;
+                }
+            }
+        }
+    }
+
+    if images.is_empty() {
+        return Err(PreviewError::NoFrames);
+    }
+    Ok(images
+        .into_iter()
+        .map(|img| image_orientation::apply(DynamicImage::ImageRgb8(img), orientation).to_rgb8())
+        .collect())
+}
+
+/// Encode `frames` as an endlessly looping GIF at `fps`.
+pub fn encode_gif(frames: &[RgbImage], fps: u32) -> Result<Vec<u8>, PreviewError> {
+    let mut out = Vec::new();
+    {
+        // Speed 10 of 1–30: quantisation is most of the cost, and previews
+        // are small enough that the palette loss does not show.
+        let mut gif = GifEncoder::new_with_speed(&mut out, 10);
+        gif.set_repeat(Repeat::Infinite)
+            .map_err(|e| PreviewError::RenderFailed(format!("gif: {e}")))?;
+        let delay = Delay::from_numer_denom_ms(1000, fps.max(1));
+        gif.encode_frames(frames.iter().map(|img| {
+            let rgba = DynamicImage::ImageRgb8(img.clone()).to_rgba8();
+            Frame::from_parts(rgba, 0, 0, delay)
+        }))
+        .map_err(|e| PreviewError::RenderFailed(format!("gif: {e}")))?;
+    }
+    Ok(out)
+}
+
+/// Encode `frames` as an endlessly looping animated WebP at `fps`. Fails
+/// with [`PreviewError::EncoderUnavailable`] when the linked ffmpeg was
+/// built without libwebp.
+pub fn encode_webp(frames: &[RgbImage], fps: u32) -> Result<Vec<u8>, PreviewError> {
+    let _ = ffmpeg::init();
+
+    let codec = encoder::find_by_name("libwebp_anim").ok_or_else(|| {
+        PreviewError::EncoderUnavailable("no libwebp_anim encoder in linked ffmpeg".into())
+    })?;
+    let first = frames.first().ok_or(PreviewError::NoFrames)?;
+    let (w, h) = first.dimensions();
+    let fps = fps.max(1) as i32;
+
+    // libavformat muxes to a path, so go through a temporary file.
+    let tmp = tempfile::NamedTempFile::new()?;
+    let mut octx = format::output_as(&tmp.path(), "webp").map_err(enc_err("open output"))?;
+    let mut ost = octx.add_stream(codec).map_err(enc_err("add stream"))?;
+    let ost_index = ost.index();
+
+    let mut enc = codec::context::Context::new_with_codec(codec)
+        .encoder()
+        .video()
+        .map_err(enc_err("encoder ctx"))?;
+    enc.set_width(w);
+    enc.set_height(h);
+    enc.set_format(Pixel::YUV420P);
+    enc.set_time_base(Rational(1, fps));
+    enc.set_frame_rate(Some(Rational(fps, 1)));
+    let mut opts = Dictionary::new();
+    opts.set("quality", "70");
+    let mut enc = enc.open_with(opts).map_err(enc_err("open encoder"))?;
+    ost.set_parameters(&enc);
+    let enc_tb = enc.time_base();
+
+    let mut mux_opts = Dictionary::new();
+    mux_opts.set("loop", "0");
+    octx.write_header_with(mux_opts)
+        .map_err(enc_err("write header"))?;
+    let ost_tb = octx
+        .stream(ost_index)
+        .expect("output stream exists")
+        .time_base();
+
+    let mut scaler = software::scaling::Context::get(
+        Pixel::RGB24,
+        w,
+        h,
+        Pixel::YUV420P,
+        w,
+        h,
+        software::scaling::Flags::BILINEAR,
+    )
+    .map_err(enc_err("scaler init"))?;
+
+    let drain = |enc: &mut encoder::video::Encoder,
+                 octx: &mut format::context::Output|
+     -> Result<(), PreviewError> {
+        let mut packet = Packet::empty();
+        while enc.receive_packet(&mut packet).is_ok() {
+            packet.set_stream(ost_index);
+            packet.rescale_ts(enc_tb, ost_tb);
+            packet
+                .write_interleaved(octx)
+                .map_err(enc_err("mux write"))?;
+        }
+        Ok(())
+    };
+
+    for (i, img) in frames.iter().enumerate() {
+        let mut rgb = frame::Video::new(Pixel::RGB24, w, h);
+        image_to_frame(img, &mut rgb);
+        let mut yuv = frame::Video::empty();
+        scaler.run(&rgb, &mut yuv).map_err(enc_err("scale frame"))?;
+        yuv.set_pts(Some(i as i64));
+        enc.send_frame(&yuv).map_err(enc_err("send frame"))?;
+        drain(&mut enc, &mut octx)?;
+    }
+    enc.send_eof().map_err(enc_err("send eof"))?;
+    drain(&mut enc, &mut octx)?;
+    octx.write_trailer().map_err(enc_err("write trailer"))?;
+    drop(octx);
+
+    Ok(std::fs::read(tmp.path())?)
+}
+
+/// Copy a tightly-packed RGB image into an `RGB24` AVFrame, honouring its
+/// line stride.
+fn image_to_frame(img: &RgbImage, dst: &mut frame::Video) {
+    let row_bytes = img.width() as usize * 3;
+    let stride = dst.stride(0);
+    let data = dst.data_mut(0);
+    for (y, row) in img.as_raw().chunks_exact(row_bytes).enumerate() {
+        data[y * stride..y * stride + row_bytes].copy_from_slice(row);
+    }
+}
+
+/// Tile `tiles` into a grid `columns` wide with `labels` burned into each
+/// tile's bottom-left corner, and encode it as JPEG. Tiles share one size —
+/// they are frames of one recording.
+pub fn contact_sheet(
+    tiles: &[RgbImage],
+    labels: &[String],
+    columns: u32,
+) -> Result<Vec<u8>, PreviewError> {
+    let first = tiles.first().ok_or(PreviewError::NoFrames)?;
+    let (tile_w, tile_h) = first.dimensions();
+    let (sheet_w, sheet_h) = sheet_size(tile_w, tile_h, tiles.len() as u32, columns);
+    let columns = columns.clamp(1, tiles.len() as u32);
+    let mut sheet = RgbImage::from_pixel(sheet_w, sheet_h, SHEET_BACKGROUND);
+
+    // Labels scale with the tile so they stay legible on large frames.
+    let scale = (tile_w / 160).max(1);
+    for (i, tile) in tiles.iter().enumerate() {
+        let (col, row) = (i as u32 % columns, i as u32 / columns);
+        let x = SHEET_GAP + col * (tile_w + SHEET_GAP);
+        let y = SHEET_GAP + row * (tile_h + SHEET_GAP);
+        image::imageops::replace(&mut sheet, tile, i64::from(x), i64::from(y));
+        if let Some(text) = labels.get(i) {
+            let (_, label_h) = label::size(text, scale);
+            let label_y = (y + tile_h).saturating_sub(label_h);
+            label::draw(&mut sheet, x, label_y, text, scale);
+        }
+    }
+
+    let mut out = Vec::new();
+    JpegEncoder::new_with_quality(&mut out, SHEET_JPEG_QUALITY)
+        .encode_image(&sheet)
+        .map_err(|e| PreviewError::RenderFailed(format!("jpeg: {e}")))?;
+    Ok(out)
+}
+
+/// Pixel size of a sheet of `n` `tile_w`×`tile_h` tiles, `columns` wide.
+fn sheet_size(tile_w: u32, tile_h: u32, n: u32, columns: u32) -> (u32, u32) {
+    let columns = columns.clamp(1, n.max(1));
+    let rows = n.div_ceil(columns).max(1);
+    (
+        SHEET_GAP + columns * (tile_w + SHEET_GAP),
+        SHEET_GAP + rows * (tile_h + SHEET_GAP),
+    )
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn fit_caps_width_and_keeps_sides_even() {
+        assert_eq!(fit(1920, 1080, 320), (320, 180));
+        assert_eq!(fit(1280, 721, 321), (320, 180));
+        assert_eq!(fit(200, 151, 320), (200, 150));
+    }
+
+    #[test]
+    fn sheet_size_rounds_rows_up_and_narrows_to_the_tile_count() {
+        assert_eq!(sheet_size(10, 10, 5, 4), (4 + 4 * 14, 4 + 2 * 14));
+        assert_eq!(sheet_size(10, 10, 2, 4), (4 + 2 * 14, 4 + 14));
+    }
+
+    #[test]
+    fn contact_sheet_is_a_jpeg_of_the_grid() {
+        let tiles = vec![RgbImage::from_pixel(32, 24, Rgb([200, 0, 0])); 3];
+        let labels = vec!["00:00:01".to_string(); 3];
+        let bytes = contact_sheet(&tiles, &labels, 2).unwrap();
+        let img = image::load_from_memory(&bytes).unwrap();
+        assert_eq!((img.width(), img.height()), (4 + 2 * 36, 4 + 2 * 28));
+    }
+
+    #[test]
+    fn gif_carries_every_frame() {
+        let frames = vec![RgbImage::from_pixel(8, 8, Rgb([0, 0, 255])); 3];
+        let bytes = encode_gif(&frames, 5).unwrap();
+        assert_eq!(&bytes[..6], b"GIF89a");
+        let decoder = image::codecs::gif::GifDecoder::new(std::io::Cursor::new(bytes)).unwrap();
+        use image::AnimationDecoder;
+        assert_eq!(decoder.into_frames().count(), 3);
+    }
+
+    #[test]
+    fn webp_is_encoded_or_reported_unavailable() {
+        let frames = vec![RgbImage::from_pixel(16, 16, Rgb([0, 128, 0])); 2];
+        match encode_webp(&frames, 5) {
+            Ok(bytes) => {
+                assert_eq!(&bytes[..4], b"RIFF");
+                assert_eq!(&bytes[8..12], b"WEBP");
+            }
+            Err(PreviewError::EncoderUnavailable(_)) => { /* ffmpeg without libwebp */ }
+            Err(e) => panic!("unexpected encode error: {e}"),
+        }
+    }
+}
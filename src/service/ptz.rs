@@ -3,20 +3,28 @@
 use tracing::{info, instrument, warn};
 
 use crate::dto::request::ptz::{
-    PtzAbsoluteRequest, PtzFocusRequest, PtzMoveRequest, PtzPresetRequest, PtzRelativeRequest,
-    PtzZoomRequest,
+    PtzAbsoluteRequest, PtzAreaRequest, PtzFocusRequest, PtzMoveRequest, PtzPointRequest,
+    PtzPresetRequest, PtzRelativeRequest, PtzZoomRequest,
 };
 use crate::dto::response::ptz::{
-    PtzCapabilitiesResponse, PtzCommandResponse, PtzProtocolInfo, PtzProtocolListResponse,
-    PtzStatusResponse,
+    PtzCapabilitiesResponse, PtzCommandResponse, PtzControlLeaseResponse, PtzPositionResponse,
+    PtzProtocolInfo, PtzProtocolListResponse, PtzStatusResponse,
 };
+use crate::entity::controls::Model as ControlModel;
+use crate::entity::monitors::Model as MonitorModel;
 use crate::error::{AppError, AppResult, Resource, ResourceType};
+use crate::ptz::aim::{self, AimTarget, Lens};
 use crate::ptz::capabilities::PtzCapabilities;
 use crate::ptz::error::PtzError;
 use crate::ptz::traits::{AbsolutePosition, MoveParams, PtzCommand, RelativePosition, ZoomParams};
 use crate::ptz::PtzManager;
 use crate::repo;
 use crate::server::state::AppState;
+use crate::service::ptz_control;
+use crate::util::claim::UserClaims;
+
+/// How long a status request waits for the camera to report its position.
+const POSITION_TIMEOUT: std::time::Duration = std::time::Duration::from_secs(3);
 
 fn ptz_to_app_error(e: PtzError) -> AppError {
     match e {
@@ -82,21 +90,43 @@ pub async fn get_status(
             is_native: false,
             capabilities: PtzCapabilities::default(),
             position: None,
+            tour: None,
+            control: None,
         });
     };
 
-    let capabilities = PtzCapabilities::from(&control);
+    let mut capabilities = PtzCapabilities::from(&control);
     let protocol = control.protocol.clone();
     let is_native = protocol
         .as_ref()
         .map(|p| ptz_manager.is_native_protocol(p))
         .unwrap_or(false);
 
-    if let Err(e) = ptz_manager
+    let mut position = None;
+    match ptz_manager
         .create_and_cache_for_models(&monitor, &control)
         .await
     {
-        warn!(monitor_id, error = %e, "Failed to initialize PTZ control");
+        Ok(()) => {
+            // A native driver reports what its protocol can do on top of the
+            // Controls row, and may read back where the camera points.
+            if let Ok(driver) = ptz_manager.get_capabilities(monitor_id).await {
+                capabilities = driver;
+            }
+            match tokio::time::timeout(POSITION_TIMEOUT, ptz_manager.get_position(monitor_id)).await
+            {
+                Ok(Ok(current)) => {
+                    position = current.map(|p| PtzPositionResponse {
+                        pan: p.pan,
+                        tilt: p.tilt,
+                        zoom: p.zoom,
+                    })
+                }
+                Ok(Err(e)) => warn!(monitor_id, error = %e, "Failed to read PTZ position"),
+                Err(_) => warn!(monitor_id, "Timed out reading PTZ position"),
+            }
+        }
+        Err(e) => warn!(monitor_id, error = %e, "Failed to initialize PTZ control"),
     }
 
     Ok(PtzStatusResponse {
@@ -105,7 +135,12 @@ pub async fn get_status(
         protocol,
         is_native,
         capabilities,
-        position: None,
+        position,
+        tour: state.ptz_tours.status(monitor_id).await,
+        control: ptz_manager
+            .control_lease(monitor_id)
+            .as_ref()
+            .map(PtzControlLeaseResponse::from),
     })
 }
 
@@ -155,10 +190,11 @@ pub fn list_protocols(ptz_manager: &PtzManager) -> PtzProtocolListResponse {
     }
 }
 
-#[instrument(skip(state, ptz_manager))]
+#[instrument(skip(state, ptz_manager, claims))]
 pub async fn move_direction(
     state: &AppState,
     ptz_manager: &PtzManager,
+    claims: &UserClaims,
     monitor_id: u32,
     direction: &str,
     request: PtzMoveRequest,
@@ -181,23 +217,33 @@ pub async fn move_direction(
             )))
         }
     };
-    execute_command(ptz_manager, &monitor, &control, command).await
+    execute_command(state, ptz_manager, claims, &monitor, &control, command).await
 }
 
-#[instrument(skip(state, ptz_manager))]
+#[instrument(skip(state, ptz_manager, claims))]
 pub async fn move_stop(
     state: &AppState,
     ptz_manager: &PtzManager,
+    claims: &UserClaims,
     monitor_id: u32,
 ) -> AppResult<PtzCommandResponse> {
     let (monitor, control) = get_monitor_and_control(state, monitor_id).await?;
-    execute_command(ptz_manager, &monitor, &control, PtzCommand::MoveStop).await
+    execute_command(
+        state,
+        ptz_manager,
+        claims,
+        &monitor,
+        &control,
+        PtzCommand::MoveStop,
+    )
+    .await
 }
 
-#[instrument(skip(state, ptz_manager))]
+#[instrument(skip(state, ptz_manager, claims))]
 pub async fn zoom(
     state: &AppState,
     ptz_manager: &PtzManager,
+    claims: &UserClaims,
     monitor_id: u32,
     direction: &str,
     request: PtzZoomRequest,
@@ -214,23 +260,33 @@ pub async fn zoom(
             )))
         }
     };
-    execute_command(ptz_manager, &monitor, &control, command).await
+    execute_command(state, ptz_manager, claims, &monitor, &control, command).await
 }
 
-#[instrument(skip(state, ptz_manager))]
+#[instrument(skip(state, ptz_manager, claims))]
 pub async fn zoom_stop(
     state: &AppState,
     ptz_manager: &PtzManager,
+    claims: &UserClaims,
     monitor_id: u32,
 ) -> AppResult<PtzCommandResponse> {
     let (monitor, control) = get_monitor_and_control(state, monitor_id).await?;
-    execute_command(ptz_manager, &monitor, &control, PtzCommand::ZoomStop).await
+    execute_command(
+        state,
+        ptz_manager,
+        claims,
+        &monitor,
+        &control,
+        PtzCommand::ZoomStop,
+    )
+    .await
 }
 
-#[instrument(skip(state, ptz_manager))]
+#[instrument(skip(state, ptz_manager, claims))]
 pub async fn focus(
     state: &AppState,
     ptz_manager: &PtzManager,
+    claims: &UserClaims,
     monitor_id: u32,
     direction: &str,
     request: PtzFocusRequest,
@@ -247,17 +303,26 @@ pub async fn focus(
             )))
         }
     };
-    execute_command(ptz_manager, &monitor, &control, command).await
+    execute_command(state, ptz_manager, claims, &monitor, &control, command).await
 }
 
-#[instrument(skip(state, ptz_manager))]
+#[instrument(skip(state, ptz_manager, claims))]
 pub async fn focus_stop(
     state: &AppState,
     ptz_manager: &PtzManager,
+    claims: &UserClaims,
     monitor_id: u32,
 ) -> AppResult<PtzCommandResponse> {
     let (monitor, control) = get_monitor_and_control(state, monitor_id).await?;
-    execute_command(ptz_manager, &monitor, &control, PtzCommand::FocusStop).await
+    execute_command(
+        state,
+        ptz_manager,
+        claims,
+        &monitor,
+        &control,
+        PtzCommand::FocusStop,
+    )
+    .await
 }
 
 /// Iris control: `open`, `close`, `auto`, or `stop`.
@@ -265,10 +330,11 @@ pub async fn focus_stop(
 /// The command layer and zmcontrol mappings already existed; these were simply
 /// unreachable because no route invoked them, even though
 /// `capabilities.iris` advertises them to clients (GH #37).
-#[instrument(skip(state, ptz_manager))]
+#[instrument(skip(state, ptz_manager, claims))]
 pub async fn iris(
     state: &AppState,
     ptz_manager: &PtzManager,
+    claims: &UserClaims,
     monitor_id: u32,
     action: &str,
 ) -> AppResult<PtzCommandResponse> {
@@ -284,7 +350,7 @@ pub async fn iris(
             )))
         }
     };
-    execute_command(ptz_manager, &monitor, &control, command).await
+    execute_command(state, ptz_manager, claims, &monitor, &control, command).await
 }
 
 /// Camera power control: `wake`, `sleep`, `reset`, or `reboot`.
@@ -292,10 +358,11 @@ pub async fn iris(
 /// As with iris, the `PtzCommand` variants and zmcontrol names already existed
 /// and `capabilities.can_wake/can_sleep/can_reset/can_reboot` advertise them;
 /// only the routes were missing (GH #37).
-#[instrument(skip(state, ptz_manager))]
+#[instrument(skip(state, ptz_manager, claims))]
 pub async fn power(
     state: &AppState,
     ptz_manager: &PtzManager,
+    claims: &UserClaims,
     monitor_id: u32,
     action: &str,
 ) -> AppResult<PtzCommandResponse> {
@@ -311,30 +378,37 @@ pub async fn power(
             )))
         }
     };
-    execute_command(ptz_manager, &monitor, &control, command).await
+    execute_command(state, ptz_manager, claims, &monitor, &control, command).await
 }
 
-#[instrument(skip(state, ptz_manager))]
+#[instrument(skip(state, ptz_manager, claims))]
 pub async fn goto_preset(
     state: &AppState,
     ptz_manager: &PtzManager,
+    claims: &UserClaims,
     monitor_id: u32,
     preset_id: u32,
 ) -> AppResult<PtzCommandResponse> {
     let (monitor, control) = get_monitor_and_control(state, monitor_id).await?;
     execute_command(
+        state,
         ptz_manager,
+        claims,
         &monitor,
         &control,
-        PtzCommand::GotoPreset { preset_id },
+        PtzCommand::GotoPreset {
+            preset_id,
+            speed: None,
+        },
     )
     .await
 }
 
-#[instrument(skip(state, ptz_manager))]
+#[instrument(skip(state, ptz_manager, claims))]
 pub async fn set_preset(
     state: &AppState,
     ptz_manager: &PtzManager,
+    claims: &UserClaims,
     monitor_id: u32,
     preset_id: u32,
     request: PtzPresetRequest,
@@ -344,19 +418,22 @@ pub async fn set_preset(
         preset_id,
         name: request.name,
     };
-    execute_command(ptz_manager, &monitor, &control, command).await
+    execute_command(state, ptz_manager, claims, &monitor, &control, command).await
 }
 
-#[instrument(skip(state, ptz_manager))]
+#[instrument(skip(state, ptz_manager, claims))]
 pub async fn clear_preset(
     state: &AppState,
     ptz_manager: &PtzManager,
+    claims: &UserClaims,
     monitor_id: u32,
     preset_id: u32,
 ) -> AppResult<PtzCommandResponse> {
     let (monitor, control) = get_monitor_and_control(state, monitor_id).await?;
     execute_command(
+        state,
         ptz_manager,
+        claims,
         &monitor,
         &control,
         PtzCommand::ClearPreset { preset_id },
@@ -364,20 +441,30 @@ pub async fn clear_preset(
     .await
 }
 
-#[instrument(skip(state, ptz_manager))]
+#[instrument(skip(state, ptz_manager, claims))]
 pub async fn goto_home(
     state: &AppState,
     ptz_manager: &PtzManager,
+    claims: &UserClaims,
     monitor_id: u32,
 ) -> AppResult<PtzCommandResponse> {
     let (monitor, control) = get_monitor_and_control(state, monitor_id).await?;
-    execute_command(ptz_manager, &monitor, &control, PtzCommand::GotoHome).await
+    execute_command(
+        state,
+        ptz_manager,
+        claims,
+        &monitor,
+        &control,
+        PtzCommand::GotoHome,
+    )
+    .await
 }
 
-#[instrument(skip(state, ptz_manager))]
+#[instrument(skip(state, ptz_manager, claims))]
 pub async fn move_absolute(
     state: &AppState,
     ptz_manager: &PtzManager,
+    claims: &UserClaims,
     monitor_id: u32,
     request: PtzAbsoluteRequest,
 ) -> AppResult<PtzCommandResponse> {
@@ -388,7 +475,9 @@ pub async fn move_absolute(
         zoom: request.zoom,
     };
     execute_command(
+        state,
         ptz_manager,
+        claims,
         &monitor,
         &control,
         PtzCommand::MoveAbsolute(pos),
@@ -396,10 +485,11 @@ pub async fn move_absolute(
     .await
 }
 
-#[instrument(skip(state, ptz_manager))]
+#[instrument(skip(state, ptz_manager, claims))]
 pub async fn move_relative(
     state: &AppState,
     ptz_manager: &PtzManager,
+    claims: &UserClaims,
     monitor_id: u32,
     request: PtzRelativeRequest,
 ) -> AppResult<PtzCommandResponse> {
@@ -410,7 +500,9 @@ pub async fn move_relative(
         zoom_delta: request.zoom_delta,
     };
     execute_command(
+        state,
         ptz_manager,
+        claims,
         &monitor,
         &control,
         PtzCommand::MoveRelative(pos),
@@ -418,13 +510,88 @@ pub async fn move_relative(
     .await
 }
 
+/// Center the camera on a point of its picture.
+#[instrument(skip(state, ptz_manager, claims))]
+pub async fn point(
+    state: &AppState,
+    ptz_manager: &PtzManager,
+    claims: &UserClaims,
+    monitor_id: u32,
+    request: PtzPointRequest,
+) -> AppResult<PtzCommandResponse> {
+    aim_at(
+        state,
+        ptz_manager,
+        claims,
+        monitor_id,
+        AimTarget::Point(request.into()),
+    )
+    .await
+}
+
+/// Center the camera on an area of its picture and zoom in to fill the frame.
+#[instrument(skip(state, ptz_manager, claims))]
+pub async fn area(
+    state: &AppState,
+    ptz_manager: &PtzManager,
+    claims: &UserClaims,
+    monitor_id: u32,
+    request: PtzAreaRequest,
+) -> AppResult<PtzCommandResponse> {
+    aim_at(
+        state,
+        ptz_manager,
+        claims,
+        monitor_id,
+        AimTarget::Area(request.into()),
+    )
+    .await
+}
+
+/// Take or renew control of a monitor's camera without moving it.
+#[instrument(skip(state, ptz_manager, claims))]
+pub async fn acquire_control(
+    state: &AppState,
+    ptz_manager: &PtzManager,
+    claims: &UserClaims,
+    monitor_id: u32,
+) -> AppResult<PtzControlLeaseResponse> {
+    if state.config.ptz.control.lease().is_none() {
+        return Err(AppError::ServiceUnavailableError(
+            "PTZ control leases are disabled in configuration".into(),
+        ));
+    }
+    get_monitor_and_control(state, monitor_id).await?;
+    ptz_control::take(state, monitor_id, claims)?;
+    ptz_manager
+        .control_lease(monitor_id)
+        .as_ref()
+        .map(PtzControlLeaseResponse::from)
+        .ok_or_else(|| {
+            AppError::InternalServerError(format!("Lost control of monitor {monitor_id}"))
+        })
+}
+
+/// Let go of a monitor's camera. Clearing another user's lease needs a
+/// higher priority than theirs.
+#[instrument(skip(state, claims))]
+pub async fn release_control(
+    state: &AppState,
+    claims: &UserClaims,
+    monitor_id: u32,
+) -> AppResult<PtzCommandResponse> {
+    get_monitor_and_control(state, monitor_id).await?;
+    let message = match ptz_control::release(state, monitor_id, claims)? {
+        Some(previous) => format!("Released control held by {}", previous.username),
+        None => format!("Nobody controls monitor {monitor_id}"),
+    };
+    Ok(PtzCommandResponse::success(message))
+}
+
 async fn get_monitor_and_control(
     state: &AppState,
     monitor_id: u32,
-) -> AppResult<(
-    crate::entity::monitors::Model,
-    crate::entity::controls::Model,
-)> {
+) -> AppResult<(MonitorModel, ControlModel)> {
     let result = repo::ptz::get_monitor_with_control(state.db(), monitor_id).await?;
     let (monitor, control) = result.ok_or_else(|| {
         AppError::NotFoundError(Resource {
@@ -443,12 +610,72 @@ async fn get_monitor_and_control(
     Ok((monitor, control))
 }
 
+/// The lens of the monitor's camera model, else of its control, else a
+/// typical one.
+async fn monitor_lens(
+    state: &AppState,
+    monitor: &MonitorModel,
+    control: &ControlModel,
+) -> AppResult<Lens> {
+    let model = match monitor.model_id {
+        Some(id) => repo::models::find_by_id(state.db(), id).await?,
+        None => None,
+    };
+    Ok(model
+        .and_then(|m| aim::lens_for_model(&m.name))
+        .or_else(|| aim::lens_for_model(&control.name))
+        .unwrap_or(aim::DEFAULT_LENS))
+}
+
+/// Claim the camera for the caller before a command of theirs.
+fn begin_manual_command(
+    state: &AppState,
+    ptz_manager: &PtzManager,
+    claims: &UserClaims,
+    monitor_id: u32,
+) -> AppResult<()> {
+    ptz_control::take(state, monitor_id, claims)?;
+    // Every command here comes from a user; a running tour pauses for it.
+    ptz_manager.note_manual_control(monitor_id);
+    Ok(())
+}
+
+async fn aim_at(
+    state: &AppState,
+    ptz_manager: &PtzManager,
+    claims: &UserClaims,
+    monitor_id: u32,
+    target: AimTarget,
+) -> AppResult<PtzCommandResponse> {
+    let (monitor, control) = get_monitor_and_control(state, monitor_id).await?;
+    let lens = monitor_lens(state, &monitor, &control).await?;
+    begin_manual_command(state, ptz_manager, claims, monitor.id)?;
+    let result = ptz_manager
+        .aim_with_models(&monitor, &control, target, &lens)
+        .await
+        .map_err(ptz_to_app_error)?;
+
+    info!(
+        monitor_id = monitor.id,
+        success = result.success,
+        "PTZ aim executed"
+    );
+
+    Ok(PtzCommandResponse {
+        success: result.success,
+        message: result.message,
+    })
+}
+
 async fn execute_command(
+    state: &AppState,
     ptz_manager: &PtzManager,
-    monitor: &crate::entity::monitors::Model,
-    control: &crate::entity::controls::Model,
+    claims: &UserClaims,
+    monitor: &MonitorModel,
+    control: &ControlModel,
     command: PtzCommand,
 ) -> AppResult<PtzCommandResponse> {
+    begin_manual_command(state, ptz_manager, claims, monitor.id)?;
     let result = ptz_manager
         .execute_with_models(monitor, control, command)
         .await
@@ -469,9 +696,20 @@ async fn execute_command(
 #[cfg(test)]
 mod tests {
     use super::*;
-    use crate::entity::monitors::Model as MonitorModel;
+    use crate::util::authz::UserPermissions;
+    use crate::util::claim::TokenType;
     use sea_orm::{DatabaseBackend, MockDatabase};
 
+    fn claims() -> UserClaims {
+        UserClaims::new(
+            std::time::Duration::from_secs(600),
+            "operator".into(),
+            2,
+            UserPermissions::superuser(),
+            TokenType::Access,
+        )
+    }
+
     /// An unknown iris action is rejected as a bad request. The monitor lookup
     /// runs first, so the mock supplies an empty result and we assert only that
     /// the failure is *not* a silent success.
@@ -483,7 +721,7 @@ mod tests {
             .into_connection();
         let state = AppState::for_test_with_db(db);
         let mgr = state.ptz_manager();
-        let err = iris(&state, mgr, 1, "sideways")
+        let err = iris(&state, mgr, &claims(), 1, "sideways")
             .await
             .expect_err("unknown iris action must fail");
         // Either the monitor lookup or the action match rejects it; both are
@@ -505,7 +743,7 @@ mod tests {
             .into_connection();
         let state = AppState::for_test_with_db(db);
         let mgr = state.ptz_manager();
-        let err = power(&state, mgr, 1, "explode")
+        let err = power(&state, mgr, &claims(), 1, "explode")
             .await
             .expect_err("unknown power action must fail");
         assert!(
@@ -0,0 +1,387 @@
+//! PTZ control arbitration and auto-return.
+//!
+//! Users drive a camera under a lease kept by [`PtzManager`]: the first to
+//! move it holds it for `[ptz.control].lease_seconds`, renewed by each of
+//! their commands, and anyone else gets 409 unless they have `System: Edit`
+//! and the holder does not. Every change of hands goes out on the
+//! [`EventFeed`] as `ptz_control`.
+//!
+//! [`ControlWatcher`] runs once a second: it lets lapsed leases go and, once
+//! a camera's controls have been idle for its monitor's `ReturnDelay`, sends
+//! it back to `ReturnLocation` (home, or a preset). A camera with a tour
+//! running is left to the tour, which resumes on its own.
+
+use std::collections::HashMap;
+use std::sync::Arc;
+use std::time::{Duration, Instant};
+
+use sea_orm::DatabaseConnection;
+use tokio::time::MissedTickBehavior;
+use tracing::{debug, info, warn};
+
+use crate::configure::ptz::PtzControlConfig;
+use crate::entity::monitors::Model as MonitorModel;
+use crate::error::{AppError, AppResult};
+use crate::ptz::{ControlHolder, LeaseConflict, LeaseGrant, PtzCommand, PtzManager};
+use crate::repo;
+use crate::server::state::AppState;
+use crate::service::event_feed::{EventFeed, EventNotification};
+use crate::service::ptz_tours::TourRunner;
+use crate::util::authz::Level;
+use crate::util::claim::UserClaims;
+
+/// How often leases and idle cameras are checked.
+const TICK: Duration = Duration::from_secs(1);
+
+/// How long the return move may take before it is given up on.
+const RETURN_TIMEOUT: Duration = Duration::from_secs(10);
+
+/// Pause before the loop is restarted after it panicked.
+const RESTART_DELAY: Duration = Duration::from_secs(5);
+
+/// The lease holder a request acts as. Administrators (`System: Edit`)
+/// outrank everyone else.
+pub fn holder(claims: &UserClaims) -> ControlHolder {
+    ControlHolder {
+        user_id: claims.uid,
+        username: claims.user.clone(),
+        priority: u8::from(claims.perms.system == Level::Edit),
+    }
+}
+
+fn conflict(monitor_id: u32, c: LeaseConflict) -> AppError {
+    AppError::ConflictError(format!(
+        "Monitor {monitor_id} is being controlled by {} for another {}s",
+        c.holder.username,
+        c.remaining.as_secs().max(1)
+    ))
+}
+
+/// Take or renew the caller's lease on a monitor's camera; 409 if another
+/// user holds it. A no-op when arbitration is off.
+pub fn take(state: &AppState, monitor_id: u32, claims: &UserClaims) -> AppResult<()> {
+    let Some(lease) = state.config.ptz.control.lease() else {
+        return Ok(());
+    };
+    let requester = holder(claims);
+    let username = requester.username.clone();
+    let grant = state
+        .ptz_manager
+        .acquire_control(monitor_id, requester, lease)
+        .map_err(|c| conflict(monitor_id, c))?;
+    let (change, message) = match grant {
+        LeaseGrant::Renewed => return Ok(()),
+        LeaseGrant::Acquired => ("acquired", format!("{username} took control")),
+        LeaseGrant::TakenOver(previous) => (
+            "taken_over",
+            format!("{username} took control from {}", previous.username),
+        ),
+    };
+    info!(monitor_id, "{message}");
+    state.event_feed.publish(EventNotification::ptz_control(
+        monitor_id,
+        change,
+        Some(username),
+        message,
+    ));
+    Ok(())
+}
+
+/// Let go of a monitor's camera on the caller's behalf, returning who held
+/// it. Someone else's lease can only be cleared by a user who outranks them.
+pub fn release(
+    state: &AppState,
+    monitor_id: u32,
+    claims: &UserClaims,
+) -> AppResult<Option<ControlHolder>> {
+    let released = state
+        .ptz_manager
+        .release_control(monitor_id, &holder(claims))
+        .map_err(|c| conflict(monitor_id, c))?;
+    if let Some(previous) = &released {
+        let message = if previous.user_id == claims.uid {
+            format!("{} released control", previous.username)
+        } else {
+            format!(
+                "{} released control from {}",
+                claims.user, previous.username
+            )
+        };
+        info!(monitor_id, "{message}");
+        state.event_feed.publish(EventNotification::ptz_control(
+            monitor_id, "released", None, message,
+        ));
+    }
+    Ok(released)
+}
+
+/// Where `ReturnLocation` sends an idle camera: -1 nowhere, 0 home, and a
+/// preset number otherwise.
+fn return_command(return_location: i8) -> Option<PtzCommand> {
+    match return_location {
+        ..=-1 => None,
+        0 => Some(PtzCommand::GotoHome),
+        preset => Some(PtzCommand::GotoPreset {
+            preset_id: preset as u32,
+            speed: None,
+        }),
+    }
+}
+
+/// How long a monitor's controls must sit idle before the camera returns;
+/// `None` when it never does.
+fn return_delay(monitor: &MonitorModel) -> Option<Duration> {
+    idle_delay(monitor.return_location, monitor.return_delay)
+}
+
+fn idle_delay(return_location: i8, return_delay: Option<u16>) -> Option<Duration> {
+    return_command(return_location)?;
+    return_delay
+        .filter(|&secs| secs > 0)
+        .map(|secs| Duration::from_secs(secs.into()))
+}
+
+/// A camera waiting to return, keyed by the user command it waits out.
+struct PendingReturn {
+    last_manual: Instant,
+    /// `None` once returned, or when the monitor has no return configured.
+    due: Option<Instant>,
+}
+
+/// Expires control leases and returns idle cameras. Only spawned; nothing
+/// queries it.
+pub struct ControlWatcher {
+    db: Arc<DatabaseConnection>,
+    ptz: Arc<PtzManager>,
+    tours: Arc<TourRunner>,
+    feed: Arc<EventFeed>,
+    config: PtzControlConfig,
+}
+
+impl ControlWatcher {
+    pub fn new(
+        db: Arc<DatabaseConnection>,
+        ptz: Arc<PtzManager>,
+        tours: Arc<TourRunner>,
+        feed: Arc<EventFeed>,
+        config: PtzControlConfig,
+    ) -> Self {
+        Self {
+            db,
+            ptz,
+            tours,
+            feed,
+            config,
+        }
+    }
+
+    /// Spawn the watch loop under a supervisor that restarts it if it
+    /// panics. Returns immediately.
+    pub fn spawn(self: Arc<Self>) {
+        tokio::spawn(async move {
+            loop {
+                let watcher = Arc::clone(&self);
+                if let Err(e) = tokio::spawn(async move { watcher.run().await }).await {
+                    warn!(
+                        "PTZ control watcher stopped ({e}); restarting in {}s",
+                        RESTART_DELAY.as_secs()
+                    );
+                }
+                tokio::time::sleep(RESTART_DELAY).await;
+            }
+        });
+    }
+
+    async fn run(&self) {
+        let mut ticker = tokio::time::interval(TICK);
+        ticker.set_missed_tick_behavior(MissedTickBehavior::Delay);
+        let mut returns: HashMap<u32, PendingReturn> = HashMap::new();
+        loop {
+            ticker.tick().await;
+            for (monitor_id, holder) in self.ptz.expire_control_leases() {
+                let message = format!("{}'s control lapsed", holder.username);
+                debug!(monitor_id, "{message}");
+                self.feed.publish(EventNotification::ptz_control(
+                    monitor_id, "expired", None, message,
+                ));
+            }
+            if self.config.auto_return {
+                self.return_idle(&mut returns).await;
+            }
+        }
+    }
+
+    /// Send every camera whose controls have sat idle past its monitor's
+    /// `ReturnDelay` back to its `ReturnLocation`, once per idle spell.
+    async fn return_idle(&self, returns: &mut HashMap<u32, PendingReturn>) {
+        let now = Instant::now();
+        for (monitor_id, last_manual) in self.ptz.manual_controls() {
+            let known = returns
+                .get(&monitor_id)
+                .filter(|pending| pending.last_manual == last_manual);
+            let due = match known {
+                Some(pending) => pending.due,
+                // A new command: read the monitor's return settings afresh.
+                None => {
+                    let due = match repo::ptz::get_monitor_with_control(&self.db, monitor_id).await
+                    {
+                        Ok(found) => found
+                            .and_then(|(monitor, _)| return_delay(&monitor))
+                            .map(|delay| last_manual + delay),
+                        Err(e) => {
+                            warn!(monitor_id, "PTZ return check failed: {e}");
+                            continue;
+                        }
+                    };
+                    returns.insert(monitor_id, PendingReturn { last_manual, due });
+                    due
+                }
+            };
+            if due.is_none_or(|at| now < at) {
+                continue;
+            }
+            if let Some(pending) = returns.get_mut(&monitor_id) {
+                pending.due = None;
+            }
+            if self.tours.status(monitor_id).await.is_some() {
+                continue;
+            }
+            self.return_camera(monitor_id).await;
+        }
+    }
+
+    async fn return_camera(&self, monitor_id: u32) {
+        let (monitor, control) =
+            match repo::ptz::get_monitor_with_control(&self.db, monitor_id).await {
+                Ok(Some((monitor, Some(control)))) => (monitor, control),
+                Ok(_) => return,
+                Err(e) => {
+                    warn!(monitor_id, "PTZ return skipped: {e}");
+                    return;
+                }
+            };
+        let Some(command) = return_command(monitor.return_location) else {
+            return;
+        };
+        match tokio::time::timeout(
+            RETURN_TIMEOUT,
+            self.ptz.execute_with_models(&monitor, &control, command),
+        )
+        .await
+        {
+            Ok(Ok(result)) if result.success => info!(
+                monitor_id,
+                return_location = monitor.return_location,
+                "PTZ camera returned after idle"
+            ),
+            Ok(Ok(result)) => warn!(monitor_id, "PTZ return failed: {}", result.message),
+            Ok(Err(e)) => warn!(monitor_id, "PTZ return failed: {e}"),
+            Err(_) => warn!(monitor_id, "PTZ return timed out"),
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::util::authz::UserPermissions;
+    use crate::util::claim::TokenType;
+    use axum::http::StatusCode;
+    use sea_orm::{DatabaseBackend, MockDatabase};
+
+    fn claims(uid: u32, perms: UserPermissions) -> UserClaims {
+        UserClaims::new(
+            Duration::from_secs(600),
+            format!("user{uid}"),
+            uid,
+            perms,
+            TokenType::Access,
+        )
+    }
+
+    #[test]
+    fn administrators_outrank_other_users() {
+        let admin = holder(&claims(1, UserPermissions::superuser()));
+        assert_eq!((admin.user_id, admin.priority), (1, 1));
+        assert_eq!(admin.username, "user1");
+
+        let operator = UserPermissions {
+            control: Level::Edit,
+            ..UserPermissions::default()
+        };
+        assert_eq!(holder(&claims(2, operator)).priority, 0);
+    }
+
+    fn operator() -> UserPermissions {
+        UserPermissions {
+            control: Level::Edit,
+            ..UserPermissions::default()
+        }
+    }
+
+    fn state() -> AppState {
+        AppState::for_test_with_db(MockDatabase::new(DatabaseBackend::MySql).into_connection())
+    }
+
+    #[tokio::test]
+    async fn another_users_camera_is_refused_with_409() {
+        let state = state();
+        take(&state, 7, &claims(1, operator())).unwrap();
+        // The holder's own commands renew the lease.
+        take(&state, 7, &claims(1, operator())).unwrap();
+
+        let err = take(&state, 7, &claims(2, operator())).unwrap_err();
+        assert!(
+            matches!(&err, AppError::ConflictError(m) if m.contains("user1")),
+            "got {err:?}"
+        );
+        assert_eq!(err.response().0, StatusCode::CONFLICT);
+        let lease = state.ptz_manager.control_lease(7).unwrap();
+        assert_eq!(lease.holder.user_id, 1);
+    }
+
+    #[tokio::test]
+    async fn administrators_take_over_and_others_cannot_release() {
+        let state = state();
+        take(&state, 7, &claims(1, operator())).unwrap();
+
+        let err = release(&state, 7, &claims(2, operator())).unwrap_err();
+        assert!(matches!(err, AppError::ConflictError(_)), "got {err:?}");
+        assert_eq!(
+            state.ptz_manager.control_lease(7).unwrap().holder.user_id,
+            1
+        );
+
+        take(&state, 7, &claims(3, UserPermissions::superuser())).unwrap();
+        assert_eq!(
+            state.ptz_manager.control_lease(7).unwrap().holder.user_id,
+            3
+        );
+        assert!(take(&state, 7, &claims(1, operator())).is_err());
+
+        let released = release(&state, 7, &claims(3, UserPermissions::superuser())).unwrap();
+        assert_eq!(released.map(|h| h.user_id), Some(3));
+        assert!(state.ptz_manager.control_lease(7).is_none());
+    }
+
+    #[test]
+    fn return_location_picks_home_or_a_preset() {
+        assert!(return_command(-1).is_none());
+        assert!(matches!(return_command(0), Some(PtzCommand::GotoHome)));
+        assert!(matches!(
+            return_command(3),
+            Some(PtzCommand::GotoPreset {
+                preset_id: 3,
+                speed: None
+            })
+        ));
+    }
+
+    #[test]
+    fn no_return_without_a_location_and_a_delay() {
+        assert_eq!(idle_delay(0, Some(20)), Some(Duration::from_secs(20)));
+        assert_eq!(idle_delay(-1, Some(20)), None);
+        assert_eq!(idle_delay(2, None), None);
+        assert_eq!(idle_delay(2, Some(0)), None);
+    }
+}
@@ -0,0 +1,265 @@
+//! PTZ preset tours: named, ordered preset sequences per monitor.
+//!
+//! CRUD, validation and start/stop only; [`runner::TourRunner`] drives the
+//! running tours and is told about every write here. Weekly windows are
+//! evaluated by [`schedule`].
+
+pub mod runner;
+pub mod schedule;
+
+pub use runner::TourRunner;
+
+use sea_orm::{ActiveValue::Unchanged, Set};
+use tracing::instrument;
+
+use crate::dto::request::ptz::{
+    CreatePtzTourRequest, PtzTourStep, PtzTourWindow, UpdatePtzTourRequest, MAX_TOUR_WINDOWS,
+};
+use crate::dto::response::ptz::{PtzCommandResponse, PtzTourResponse, PtzTourStatusResponse};
+use crate::entity::ptz_tours;
+use crate::error::{AppError, AppResult, Resource, ResourceType};
+use crate::repo;
+use crate::server::state::AppState;
+
+/// Not-found for missing tours and for tours of another monitor alike.
+fn not_found(tour_id: u64) -> AppError {
+    AppError::NotFoundError(Resource {
+        details: vec![("tour_id".into(), tour_id.to_string())],
+        resource_type: ResourceType::Message,
+    })
+}
+
+/// The stored steps of a tour.
+pub(crate) fn parse_steps(tour: &ptz_tours::Model) -> serde_json::Result<Vec<PtzTourStep>> {
+    serde_json::from_str(&tour.steps_json)
+}
+
+/// The `schedule_json` to store. An empty list is no schedule.
+fn schedule_json(schedule: Option<Vec<PtzTourWindow>>) -> AppResult<Option<String>> {
+    let Some(windows) = schedule.filter(|w| !w.is_empty()) else {
+        return Ok(None);
+    };
+    if windows.len() > MAX_TOUR_WINDOWS {
+        return Err(AppError::BadRequestError(format!(
+            "a schedule has at most {MAX_TOUR_WINDOWS} windows, got {}",
+            windows.len()
+        )));
+    }
+    if let Some(w) = windows.iter().find(|w| {
+        schedule::parse_time(&w.start).is_none() || schedule::parse_time(&w.end).is_none()
+    }) {
+        return Err(AppError::BadRequestError(format!(
+            "schedule times are HH:MM, got {}-{}",
+            w.start, w.end
+        )));
+    }
+    Ok(Some(serde_json::to_string(&windows)?))
+}
+
+/// 404 unless the monitor exists.
+async fn require_monitor(state: &AppState, monitor_id: u32) -> AppResult<()> {
+    repo::ptz::get_monitor_with_control(state.db(), monitor_id)
+        .await?
+        .map(|_| ())
+        .ok_or_else(|| {
+            AppError::NotFoundError(Resource {
+                details: vec![("id".into(), monitor_id.to_string())],
+                resource_type: ResourceType::Monitor,
+            })
+        })
+}
+
+/// A tour of `monitor_id`.
+async fn find_tour(state: &AppState, monitor_id: u32, tour_id: u64) -> AppResult<ptz_tours::Model> {
+    repo::ptz_tours::find_by_id(state.db(), tour_id)
+        .await?
+        .filter(|tour| tour.monitor_id == monitor_id)
+        .ok_or_else(|| not_found(tour_id))
+}
+
+/// 409 if another of the monitor's tours already has `name`.
+async fn check_name_free(
+    state: &AppState,
+    monitor_id: u32,
+    name: &str,
+    except: Option<u64>,
+) -> AppResult<()> {
+    match repo::ptz_tours::find_by_monitor_and_name(state.db(), monitor_id, name).await? {
+        Some(other) if Some(other.id) != except => Err(AppError::ConflictError(format!(
+            "monitor {monitor_id} already has a tour named {name:?}"
+        ))),
+        _ => Ok(()),
+    }
+}
+
+#[instrument(skip(state))]
+pub async fn list(state: &AppState, monitor_id: u32) -> AppResult<Vec<PtzTourResponse>> {
+    require_monitor(state, monitor_id).await?;
+    let tours = repo::ptz_tours::find_by_monitor(state.db(), monitor_id).await?;
+    Ok(tours.into_iter().map(PtzTourResponse::from).collect())
+}
+
+#[instrument(skip(state))]
+pub async fn get(state: &AppState, monitor_id: u32, tour_id: u64) -> AppResult<PtzTourResponse> {
+    Ok(find_tour(state, monitor_id, tour_id).await?.into())
+}
+
+#[instrument(skip(state, req))]
+pub async fn create(
+    state: &AppState,
+    monitor_id: u32,
+    req: CreatePtzTourRequest,
+) -> AppResult<PtzTourResponse> {
+    require_monitor(state, monitor_id).await?;
+    check_name_free(state, monitor_id, &req.name, None).await?;
+    let schedule_json = schedule_json(req.schedule)?;
+    let now = chrono::Utc::now().naive_utc();
+    let model = repo::ptz_tours::insert(
+        state.db(),
+        ptz_tours::ActiveModel {
+            monitor_id: Set(monitor_id),
+            name: Set(req.name),
+            enabled: Set(req.enabled),
+            steps_json: Set(serde_json::to_string(&req.steps)?),
+            schedule_json: Set(schedule_json),
+            created_at: Set(now),
+            updated_at: Set(now),
+            ..Default::default()
+        },
+    )
+    .await?;
+    state.ptz_tours.tour_changed(&model).await;
+    Ok(model.into())
+}
+
+#[instrument(skip(state, req))]
+pub async fn update(
+    state: &AppState,
+    monitor_id: u32,
+    tour_id: u64,
+    req: UpdatePtzTourRequest,
+) -> AppResult<PtzTourResponse> {
+    let existing = find_tour(state, monitor_id, tour_id).await?;
+    let mut active = ptz_tours::ActiveModel {
+        id: Unchanged(existing.id),
+        updated_at: Set(chrono::Utc::now().naive_utc()),
+        ..Default::default()
+    };
+    if let Some(name) = req.name {
+        check_name_free(state, monitor_id, &name, Some(existing.id)).await?;
+        active.name = Set(name);
+    }
+    if let Some(enabled) = req.enabled {
+        active.enabled = Set(enabled);
+    }
+    if let Some(steps) = req.steps {
+        active.steps_json = Set(serde_json::to_string(&steps)?);
+    }
+    if let Some(schedule) = req.schedule {
+        active.schedule_json = Set(schedule_json(schedule)?);
+    }
+    let model = repo::ptz_tours::update(state.db(), active).await?;
+    state.ptz_tours.tour_changed(&model).await;
+    Ok(model.into())
+}
+
+#[instrument(skip(state))]
+pub async fn delete(state: &AppState, monitor_id: u32, tour_id: u64) -> AppResult<()> {
+    find_tour(state, monitor_id, tour_id).await?;
+    if !repo::ptz_tours::delete_by_id(state.db(), tour_id).await? {
+        return Err(not_found(tour_id));
+    }
+    state.ptz_tours.forget(tour_id).await;
+    Ok(())
+}
+
+/// Start a tour now, replacing any tour running on the monitor. It runs
+/// until stopped, whatever its schedule says.
+#[instrument(skip(state))]
+pub async fn start(
+    state: &AppState,
+    monitor_id: u32,
+    tour_id: u64,
+) -> AppResult<PtzTourStatusResponse> {
+    if !state.config.ptz.tours.enabled {
+        return Err(AppError::ServiceUnavailableError(
+            "PTZ tours are disabled in configuration".into(),
+        ));
+    }
+    let tour = find_tour(state, monitor_id, tour_id).await?;
+    if !tour.enabled {
+        return Err(AppError::BadRequestError(format!(
+            "Tour {tour_id} is disabled"
+        )));
+    }
+    if let Some((_, None)) = repo::ptz::get_monitor_with_control(state.db(), monitor_id).await? {
+        return Err(AppError::BadRequestError(format!(
+            "Monitor {monitor_id} has no PTZ control configured"
+        )));
+    }
+    let steps = parse_steps(&tour)
+        .ok()
+        .filter(|steps| !steps.is_empty())
+        .ok_or_else(|| {
+            AppError::InternalServerError(format!("Tour {tour_id} has no readable steps"))
+        })?;
+    Ok(state.ptz_tours.start(&tour, steps).await)
+}
+
+/// Stop a running tour. A scheduled tour stopped inside its window stays
+/// stopped until the window closes.
+#[instrument(skip(state))]
+pub async fn stop(
+    state: &AppState,
+    monitor_id: u32,
+    tour_id: u64,
+) -> AppResult<PtzCommandResponse> {
+    let tour = find_tour(state, monitor_id, tour_id).await?;
+    if !state.ptz_tours.stop(monitor_id, tour_id).await {
+        return Err(AppError::ConflictError(format!(
+            "Tour {tour_id} is not running on monitor {monitor_id}"
+        )));
+    }
+    Ok(PtzCommandResponse::success(format!(
+        "Tour {} stopped",
+        tour.name
+    )))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::dto::request::ptz::PtzTourDay;
+
+    fn window(start: &str, end: &str) -> PtzTourWindow {
+        PtzTourWindow {
+            days: vec![PtzTourDay::Sat],
+            start: start.into(),
+            end: end.into(),
+        }
+    }
+
+    #[test]
+    fn empty_schedule_is_stored_as_none() {
+        assert_eq!(schedule_json(None).unwrap(), None);
+        assert_eq!(schedule_json(Some(vec![])).unwrap(), None);
+        let stored = schedule_json(Some(vec![window("08:00", "18:00")]))
+            .unwrap()
+            .unwrap();
+        let parsed: Vec<PtzTourWindow> = serde_json::from_str(&stored).unwrap();
+        assert_eq!(parsed, [window("08:00", "18:00")]);
+    }
+
+    #[test]
+    fn schedule_rejects_bad_times_and_too_many_windows() {
+        assert!(matches!(
+            schedule_json(Some(vec![window("08:00", "25:00")])),
+            Err(AppError::BadRequestError(_))
+        ));
+        let many = vec![window("08:00", "18:00"); MAX_TOUR_WINDOWS + 1];
+        assert!(matches!(
+            schedule_json(Some(many)),
+            Err(AppError::BadRequestError(_))
+        ));
+    }
+}
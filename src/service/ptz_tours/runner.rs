@@ -0,0 +1,571 @@
+//! The supervised loop that drives running tours.
+//!
+//! Once a second every running tour whose dwell has elapsed is sent to its
+//! next preset through [`PtzManager::execute_with_models`]. A tour yields to
+//! users: any command a user sends the camera after the tour started pauses
+//! it, and once the controls have been idle for `resume_after_seconds` it
+//! goes back to the preset it was holding and carries on from there.
+//!
+//! Schedules are re-read every `schedule_check_seconds` (and straight after
+//! an edit): a tour whose window opens starts unless its monitor is already
+//! touring, and a tour started by its schedule stops when the window closes.
+//! Stopping a scheduled tour by hand keeps it stopped until its window
+//! closes. Running tours live in memory only, so after a restart scheduled
+//! tours pick up again and tours started by hand do not.
+
+use std::collections::{HashMap, HashSet};
+use std::sync::atomic::{AtomicBool, Ordering};
+use std::sync::Arc;
+use std::time::{Duration, Instant};
+
+use sea_orm::{DatabaseConnection, DbErr};
+use tokio::sync::Mutex;
+use tokio::time::MissedTickBehavior;
+use tracing::{debug, info, warn};
+
+use super::schedule;
+use crate::configure::ptz::PtzToursConfig;
+use crate::dto::request::ptz::{PtzTourStep, PtzTourWindow};
+use crate::dto::response::ptz::{PtzTourStatusResponse, PtzTourTrigger};
+use crate::entity::ptz_tours;
+use crate::ptz::traits::PtzCommand;
+use crate::ptz::PtzManager;
+use crate::repo;
+
+/// How often running tours are checked for a step that is due.
+const TICK: Duration = Duration::from_secs(1);
+
+/// How long one preset recall may take before the step is given up on.
+const GOTO_TIMEOUT: Duration = Duration::from_secs(10);
+
+/// Pause before the loop is restarted after it panicked.
+const RESTART_DELAY: Duration = Duration::from_secs(5);
+
+/// A tour in progress on one monitor.
+struct ActiveTour {
+    tour_id: u64,
+    name: String,
+    steps: Vec<PtzTourStep>,
+    trigger: PtzTourTrigger,
+    started_at: Instant,
+    /// Index of the step the camera is at, or heading to.
+    step: usize,
+    /// When to move on; `None` until the step's preset has been sent.
+    advance_at: Option<Instant>,
+    paused: bool,
+}
+
+impl ActiveTour {
+    fn new(tour: &ptz_tours::Model, steps: Vec<PtzTourStep>, trigger: PtzTourTrigger) -> Self {
+        Self {
+            tour_id: tour.id,
+            name: tour.name.clone(),
+            steps,
+            trigger,
+            started_at: Instant::now(),
+            step: 0,
+            advance_at: None,
+            paused: false,
+        }
+    }
+
+    fn status(&self) -> PtzTourStatusResponse {
+        PtzTourStatusResponse {
+            tour_id: self.tour_id,
+            name: self.name.clone(),
+            started_by: self.trigger,
+            step: self.step,
+            step_count: self.steps.len(),
+            preset_id: self.steps[self.step].preset_id,
+            paused: self.paused,
+            next_step_in_seconds: self
+                .advance_at
+                .filter(|_| !self.paused)
+                .map(|at| at.saturating_duration_since(Instant::now()).as_secs()),
+        }
+    }
+}
+
+#[derive(Default)]
+struct Tours {
+    /// By monitor: one tour drives a camera at a time.
+    active: HashMap<u32, ActiveTour>,
+    /// Scheduled tours stopped by hand while their window is open.
+    suppressed: HashSet<u64>,
+}
+
+/// A preset a tour is due to send.
+struct DueStep {
+    monitor_id: u32,
+    tour_id: u64,
+    step: PtzTourStep,
+}
+
+/// The command sending a camera to a step's preset, at the step's speed.
+fn preset_command(step: &PtzTourStep) -> PtzCommand {
+    PtzCommand::GotoPreset {
+        preset_id: step.preset_id,
+        speed: step.speed,
+    }
+}
+
+/// Pause, resume and advance every running tour as of `now`, returning the
+/// presets to send. `last_manual` is when a user last drove a monitor.
+fn due_steps(
+    tours: &mut Tours,
+    now: Instant,
+    resume_after: Duration,
+    last_manual: impl Fn(u32) -> Option<Instant>,
+) -> Vec<DueStep> {
+    let mut due = Vec::new();
+    for (&monitor_id, tour) in tours.active.iter_mut() {
+        // Only commands sent since the tour started count: starting a tour
+        // is itself taking the camera back.
+        let held = last_manual(monitor_id)
+            .is_some_and(|at| at > tour.started_at && now.duration_since(at) < resume_after);
+        if held {
+            if !tour.paused {
+                info!(
+                    monitor_id,
+                    tour_id = tour.tour_id,
+                    "PTZ tour paused for manual control"
+                );
+                tour.paused = true;
+            }
+            continue;
+        }
+        if tour.paused {
+            info!(monitor_id, tour_id = tour.tour_id, "PTZ tour resumed");
+            tour.paused = false;
+            // The user moved the camera off the preset; go back to it.
+            tour.advance_at = None;
+        }
+        match tour.advance_at {
+            Some(at) if now < at => continue,
+            Some(_) => tour.step = (tour.step + 1) % tour.steps.len(),
+            None => {}
+        }
+        let step = tour.steps[tour.step].clone();
+        tour.advance_at = Some(now + Duration::from_secs(step.dwell_seconds.into()));
+        due.push(DueStep {
+            monitor_id,
+            tour_id: tour.tour_id,
+            step,
+        });
+    }
+    due
+}
+
+/// Drives running PTZ tours. Always constructed; only spawned when
+/// `[ptz.tours].enabled`.
+pub struct TourRunner {
+    db: Arc<DatabaseConnection>,
+    ptz: Arc<PtzManager>,
+    config: PtzToursConfig,
+    /// A tokio mutex because it cannot be poisoned: the loop restarted after
+    /// a panic finds the tours as they were.
+    tours: Mutex<Tours>,
+    /// Set when a tour is edited, so the next tick re-reads schedules.
+    recheck: AtomicBool,
+}
+
+impl TourRunner {
+    pub fn new(db: Arc<DatabaseConnection>, ptz: Arc<PtzManager>, config: PtzToursConfig) -> Self {
+        Self {
+            db,
+            ptz,
+            config,
+            tours: Mutex::new(Tours::default()),
+            recheck: AtomicBool::new(false),
+        }
+    }
+
+    /// Spawn the tour loop under a supervisor that restarts it if it panics.
+    /// Returns immediately.
+    pub fn spawn(self: Arc<Self>) {
+        tokio::spawn(async move {
+            loop {
+                let runner = Arc::clone(&self);
+                if let Err(e) = tokio::spawn(async move { runner.run().await }).await {
+                    warn!(
+                        "PTZ tour runner stopped ({e}); restarting in {}s",
+                        RESTART_DELAY.as_secs()
+                    );
+                }
+                tokio::time::sleep(RESTART_DELAY).await;
+            }
+        });
+    }
+
+    async fn run(&self) {
+        let mut ticker = tokio::time::interval(TICK);
+        ticker.set_missed_tick_behavior(MissedTickBehavior::Delay);
+        let mut checked: Option<Instant> = None;
+        loop {
+            ticker.tick().await;
+            let stale =
+                checked.is_none_or(|at| at.elapsed() >= self.config.schedule_check_interval());
+            if self.recheck.swap(false, Ordering::Relaxed) || stale {
+                if let Err(e) = self.apply_schedules().await {
+                    warn!("PTZ tour schedule check failed: {e}");
+                }
+                checked = Some(Instant::now());
+            }
+            self.advance().await;
+        }
+    }
+
+    /// Start tours whose window is open and stop scheduled runs whose window
+    /// has closed.
+    async fn apply_schedules(&self) -> Result<(), DbErr> {
+        let now = chrono::Local::now().naive_local();
+        let open: Vec<_> = repo::ptz_tours::find_scheduled(&self.db)
+            .await?
+            .into_iter()
+            .filter_map(|tour| {
+                let windows: Vec<PtzTourWindow> =
+                    serde_json::from_str(tour.schedule_json.as_deref()?).ok()?;
+                if !schedule::is_open(&windows, now) {
+                    return None;
+                }
+                let steps = super::parse_steps(&tour).ok().filter(|s| !s.is_empty())?;
+                Some((tour, steps))
+            })
+            .collect();
+        let open_ids: HashSet<u64> = open.iter().map(|(tour, _)| tour.id).collect();
+
+        let mut tours = self.tours.lock().await;
+        tours.suppressed.retain(|id| open_ids.contains(id));
+        tours.active.retain(|&monitor_id, active| {
+            let keep =
+                active.trigger != PtzTourTrigger::Schedule || open_ids.contains(&active.tour_id);
+            if !keep {
+                info!(
+                    monitor_id,
+                    tour_id = active.tour_id,
+                    "PTZ tour window closed"
+                );
+            }
+            keep
+        });
+        for (tour, steps) in open {
+            if tours.suppressed.contains(&tour.id) || tours.active.contains_key(&tour.monitor_id) {
+                continue;
+            }
+            info!(
+                monitor_id = tour.monitor_id,
+                tour_id = tour.id,
+                "PTZ tour window opened"
+            );
+            tours.active.insert(
+                tour.monitor_id,
+                ActiveTour::new(&tour, steps, PtzTourTrigger::Schedule),
+            );
+        }
+        Ok(())
+    }
+
+    /// Send every tour that is due to its next preset.
+    async fn advance(&self) {
+        let due = {
+            let mut tours = self.tours.lock().await;
+            due_steps(
+                &mut tours,
+                Instant::now(),
+                self.config.resume_after(),
+                |monitor_id| self.ptz.last_manual_control(monitor_id),
+            )
+        };
+        futures::future::join_all(due.into_iter().map(|step| self.goto(step))).await;
+    }
+
+    async fn goto(&self, due: DueStep) {
+        let DueStep {
+            monitor_id,
+            tour_id,
+            step,
+        } = due;
+        let (monitor, control) =
+            match repo::ptz::get_monitor_with_control(&self.db, monitor_id).await {
+                Ok(Some((monitor, Some(control)))) => (monitor, control),
+                Ok(_) => {
+                    warn!(
+                        monitor_id,
+                        tour_id, "PTZ tour stopped: no PTZ control configured"
+                    );
+                    self.abandon(monitor_id, tour_id).await;
+                    return;
+                }
+                Err(e) => {
+                    warn!(monitor_id, tour_id, "PTZ tour step skipped: {e}");
+                    return;
+                }
+            };
+        let command = preset_command(&step);
+        let preset_id = step.preset_id;
+        match tokio::time::timeout(
+            GOTO_TIMEOUT,
+            self.ptz.execute_with_models(&monitor, &control, command),
+        )
+        .await
+        {
+            Ok(Ok(result)) if result.success => {
+                debug!(monitor_id, tour_id, preset_id, "PTZ tour step")
+            }
+            Ok(Ok(result)) => warn!(
+                monitor_id,
+                tour_id, preset_id, "PTZ tour step failed: {}", result.message
+            ),
+            Ok(Err(e)) => warn!(monitor_id, tour_id, preset_id, "PTZ tour step failed: {e}"),
+            Err(_) => warn!(monitor_id, tour_id, preset_id, "PTZ tour step timed out"),
+        }
+    }
+
+    /// Stop a tour that cannot run, keeping its schedule from restarting it
+    /// until the window closes.
+    async fn abandon(&self, monitor_id: u32, tour_id: u64) {
+        let mut tours = self.tours.lock().await;
+        if tours
+            .active
+            .get(&monitor_id)
+            .is_some_and(|active| active.tour_id == tour_id)
+        {
+            tours.active.remove(&monitor_id);
+            tours.suppressed.insert(tour_id);
+        }
+    }
+
+    /// Start `tour` on its monitor, replacing whatever tour was running
+    /// there. `steps` must not be empty.
+    pub async fn start(
+        &self,
+        tour: &ptz_tours::Model,
+        steps: Vec<PtzTourStep>,
+    ) -> PtzTourStatusResponse {
+        let active = ActiveTour::new(tour, steps, PtzTourTrigger::Manual);
+        let status = active.status();
+        let mut tours = self.tours.lock().await;
+        tours.suppressed.remove(&tour.id);
+        if let Some(previous) = tours.active.insert(tour.monitor_id, active) {
+            if previous.tour_id != tour.id {
+                info!(
+                    monitor_id = tour.monitor_id,
+                    tour_id = previous.tour_id,
+                    "PTZ tour replaced"
+                );
+            }
+        }
+        info!(
+            monitor_id = tour.monitor_id,
+            tour_id = tour.id,
+            "PTZ tour started"
+        );
+        status
+    }
+
+    /// Stop `tour_id` if it is what drives `monitor_id`. Returns whether it
+    /// was running. The camera stays where it is.
+    pub async fn stop(&self, monitor_id: u32, tour_id: u64) -> bool {
+        let mut tours = self.tours.lock().await;
+        if !tours
+            .active
+            .get(&monitor_id)
+            .is_some_and(|active| active.tour_id == tour_id)
+        {
+            return false;
+        }
+        tours.active.remove(&monitor_id);
+        // Inside an open window, this keeps the schedule from starting it
+        // straight back up.
+        tours.suppressed.insert(tour_id);
+        info!(monitor_id, tour_id, "PTZ tour stopped");
+        true
+    }
+
+    /// Pick up an edited tour: a running one takes its new name and steps
+    /// (starting over if the steps changed) or stops if it was disabled, and
+    /// schedules are re-read on the next tick.
+    pub async fn tour_changed(&self, tour: &ptz_tours::Model) {
+        let steps = super::parse_steps(tour).unwrap_or_default();
+        let mut tours = self.tours.lock().await;
+        if let Some(active) = tours
+            .active
+            .get_mut(&tour.monitor_id)
+            .filter(|active| active.tour_id == tour.id)
+        {
+            if !tour.enabled || steps.is_empty() {
+                tours.active.remove(&tour.monitor_id);
+                info!(
+                    monitor_id = tour.monitor_id,
+                    tour_id = tour.id,
+                    "PTZ tour stopped"
+                );
+            } else {
+                active.name = tour.name.clone();
+                if active.steps != steps {
+                    active.steps = steps;
+                    active.step = 0;
+                    active.advance_at = None;
+                }
+            }
+        }
+        self.recheck.store(true, Ordering::Relaxed);
+    }
+
+    /// Stop a deleted tour wherever it runs.
+    pub async fn forget(&self, tour_id: u64) {
+        let mut tours = self.tours.lock().await;
+        tours.active.retain(|_, active| active.tour_id != tour_id);
+        tours.suppressed.remove(&tour_id);
+    }
+
+    /// The tour driving `monitor_id`, if any.
+    pub async fn status(&self, monitor_id: u32) -> Option<PtzTourStatusResponse> {
+        let tours = self.tours.lock().await;
+        tours.active.get(&monitor_id).map(ActiveTour::status)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use sea_orm::{DatabaseBackend, MockDatabase};
+
+    const RESUME_AFTER: Duration = Duration::from_secs(30);
+
+    fn tour(id: u64, monitor_id: u32) -> ptz_tours::Model {
+        let now = chrono::Utc::now().naive_utc();
+        ptz_tours::Model {
+            id,
+            monitor_id,
+            name: format!("Tour {id}"),
+            enabled: true,
+            steps_json: "[]".into(),
+            schedule_json: None,
+            created_at: now,
+            updated_at: now,
+        }
+    }
+
+    fn steps(presets: &[(u32, u32)]) -> Vec<PtzTourStep> {
+        presets
+            .iter()
+            .map(|&(preset_id, dwell_seconds)| PtzTourStep {
+                preset_id,
+                dwell_seconds,
+                speed: None,
+            })
+            .collect()
+    }
+
+    fn running(steps: Vec<PtzTourStep>) -> (Tours, Instant) {
+        let mut tours = Tours::default();
+        let active = ActiveTour::new(&tour(7, 1), steps, PtzTourTrigger::Manual);
+        let started = active.started_at;
+        tours.active.insert(1, active);
+        (tours, started)
+    }
+
+    fn presets(due: &[DueStep]) -> Vec<u32> {
+        due.iter().map(|d| d.step.preset_id).collect()
+    }
+
+    fn runner() -> TourRunner {
+        let db = MockDatabase::new(DatabaseBackend::MySql).into_connection();
+        TourRunner::new(
+            Arc::new(db),
+            Arc::new(PtzManager::with_defaults()),
+            PtzToursConfig::default(),
+        )
+    }
+
+    #[test]
+    fn steps_follow_their_dwell_and_loop() {
+        let (mut tours, t0) = running(steps(&[(1, 10), (2, 5)]));
+        let idle = |_| None;
+
+        assert_eq!(presets(&due_steps(&mut tours, t0, RESUME_AFTER, idle)), [1]);
+        let t = t0 + Duration::from_secs(9);
+        assert!(due_steps(&mut tours, t, RESUME_AFTER, idle).is_empty());
+        let t = t0 + Duration::from_secs(10);
+        assert_eq!(presets(&due_steps(&mut tours, t, RESUME_AFTER, idle)), [2]);
+        let t = t0 + Duration::from_secs(15);
+        assert_eq!(presets(&due_steps(&mut tours, t, RESUME_AFTER, idle)), [1]);
+    }
+
+    #[test]
+    fn a_step_is_sent_at_its_speed() {
+        let step = PtzTourStep {
+            preset_id: 4,
+            dwell_seconds: 10,
+            speed: Some(30),
+        };
+        assert!(matches!(
+            preset_command(&step),
+            PtzCommand::GotoPreset {
+                preset_id: 4,
+                speed: Some(30)
+            }
+        ));
+    }
+
+    #[test]
+    fn manual_control_pauses_then_returns_to_the_preset() {
+        let (mut tours, t0) = running(steps(&[(1, 10), (2, 10)]));
+        due_steps(&mut tours, t0, RESUME_AFTER, |_| None);
+
+        let touched = t0 + Duration::from_secs(3);
+        let manual = |_| Some(touched);
+        let t = t0 + Duration::from_secs(20);
+        assert!(due_steps(&mut tours, t, RESUME_AFTER, manual).is_empty());
+        assert!(tours.active[&1].paused);
+
+        // Idle long enough: back to the preset it was holding, not the next.
+        let t = touched + RESUME_AFTER;
+        assert_eq!(
+            presets(&due_steps(&mut tours, t, RESUME_AFTER, manual)),
+            [1]
+        );
+        assert!(!tours.active[&1].paused);
+    }
+
+    #[test]
+    fn commands_before_the_start_do_not_pause() {
+        let (mut tours, t0) = running(steps(&[(3, 10)]));
+        let before = t0 - Duration::from_secs(1);
+        let due = due_steps(&mut tours, t0, RESUME_AFTER, |_| Some(before));
+        assert_eq!(presets(&due), [3]);
+        assert!(!tours.active[&1].paused);
+    }
+
+    #[tokio::test]
+    async fn start_reports_status_and_stop_suppresses_the_schedule() {
+        let runner = runner();
+        let status = runner.start(&tour(7, 1), steps(&[(4, 10)])).await;
+        assert_eq!(status.tour_id, 7);
+        assert_eq!(status.preset_id, 4);
+        assert_eq!(status.started_by, PtzTourTrigger::Manual);
+        assert!(runner.status(1).await.is_some());
+
+        assert!(!runner.stop(1, 8).await, "another tour is not running");
+        assert!(runner.stop(1, 7).await);
+        assert!(runner.status(1).await.is_none());
+        assert!(runner.tours.lock().await.suppressed.contains(&7));
+
+        runner.start(&tour(7, 1), steps(&[(4, 10)])).await;
+        assert!(!runner.tours.lock().await.suppressed.contains(&7));
+    }
+
+    #[tokio::test]
+    async fn disabling_a_running_tour_stops_it() {
+        let runner = runner();
+        runner.start(&tour(7, 1), steps(&[(4, 10)])).await;
+        let disabled = ptz_tours::Model {
+            enabled: false,
+            steps_json: r#"[{"preset_id": 4, "dwell_seconds": 10}]"#.into(),
+            ..tour(7, 1)
+        };
+        runner.tour_changed(&disabled).await;
+        assert!(runner.status(1).await.is_none());
+    }
+}
@@ -0,0 +1,114 @@
+//! Weekly tour windows against the local clock.
+
+use chrono::{Datelike, NaiveDateTime, NaiveTime, Weekday};
+
+use crate::dto::request::ptz::{PtzTourDay, PtzTourWindow};
+
+/// `HH:MM`, as the request DTO's pattern admits it.
+pub fn parse_time(value: &str) -> Option<NaiveTime> {
+    NaiveTime::parse_from_str(value, "%H:%M").ok()
+}
+
+fn day(weekday: Weekday) -> PtzTourDay {
+    match weekday {
+        Weekday::Mon => PtzTourDay::Mon,
+        Weekday::Tue => PtzTourDay::Tue,
+        Weekday::Wed => PtzTourDay::Wed,
+        Weekday::Thu => PtzTourDay::Thu,
+        Weekday::Fri => PtzTourDay::Fri,
+        Weekday::Sat => PtzTourDay::Sat,
+        Weekday::Sun => PtzTourDay::Sun,
+    }
+}
+
+/// Whether `window` is open at `now`. A window that closes before it opens
+/// runs past midnight and belongs to the day it opened on, so `fri
+/// 22:00-06:00` is open early on Saturday but not early on Friday.
+pub fn window_is_open(window: &PtzTourWindow, now: NaiveDateTime) -> bool {
+    let (Some(start), Some(end)) = (parse_time(&window.start), parse_time(&window.end)) else {
+        return false;
+    };
+    let today = window.days.contains(&day(now.weekday()));
+    let yesterday = window.days.contains(&day(now.weekday().pred()));
+    let time = now.time();
+    if start < end {
+        today && start <= time && time < end
+    } else if start > end {
+        (today && time >= start) || (yesterday && time < end)
+    } else {
+        today
+    }
+}
+
+/// Whether any of `windows` is open at `now`.
+pub fn is_open(windows: &[PtzTourWindow], now: NaiveDateTime) -> bool {
+    windows.iter().any(|w| window_is_open(w, now))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use chrono::NaiveDate;
+
+    /// 2026-10-16 is a Friday.
+    fn at(day: u32, hour: u32, minute: u32) -> NaiveDateTime {
+        NaiveDate::from_ymd_opt(2026, 10, day)
+            .unwrap()
+            .and_hms_opt(hour, minute, 0)
+            .unwrap()
+    }
+
+    fn window(days: &[PtzTourDay], start: &str, end: &str) -> PtzTourWindow {
+        PtzTourWindow {
+            days: days.to_vec(),
+            start: start.into(),
+            end: end.into(),
+        }
+    }
+
+    #[test]
+    fn daytime_window_is_half_open() {
+        let w = window(&[PtzTourDay::Fri], "08:00", "18:00");
+        assert!(!window_is_open(&w, at(16, 7, 59)));
+        assert!(window_is_open(&w, at(16, 8, 0)));
+        assert!(window_is_open(&w, at(16, 17, 59)));
+        assert!(!window_is_open(&w, at(16, 18, 0)));
+        // Same hours, wrong day.
+        assert!(!window_is_open(&w, at(17, 12, 0)));
+    }
+
+    #[test]
+    fn overnight_window_belongs_to_the_day_it_opens() {
+        let w = window(&[PtzTourDay::Fri], "22:00", "06:00");
+        assert!(window_is_open(&w, at(16, 23, 0)));
+        assert!(window_is_open(&w, at(17, 5, 59)));
+        assert!(!window_is_open(&w, at(17, 6, 0)));
+        assert!(
+            !window_is_open(&w, at(16, 5, 0)),
+            "Thursday night is not in it"
+        );
+        assert!(
+            !window_is_open(&w, at(17, 23, 0)),
+            "Saturday night is not in it"
+        );
+    }
+
+    #[test]
+    fn equal_times_run_all_day() {
+        let w = window(&[PtzTourDay::Sat, PtzTourDay::Sun], "00:00", "00:00");
+        assert!(window_is_open(&w, at(17, 0, 0)));
+        assert!(window_is_open(&w, at(18, 23, 59)));
+        assert!(!window_is_open(&w, at(19, 0, 0)));
+    }
+
+    #[test]
+    fn any_open_window_opens_the_schedule() {
+        let windows = [
+            window(&[PtzTourDay::Mon], "08:00", "09:00"),
+            window(&[PtzTourDay::Fri], "12:00", "13:00"),
+        ];
+        assert!(is_open(&windows, at(16, 12, 30)));
+        assert!(!is_open(&windows, at(16, 8, 30)));
+        assert!(!is_open(&[], at(16, 8, 30)));
+    }
+}
@@ -4,39 +4,77 @@
 //! byte quota by deleting whole events oldest-first — media files **and** the
 //! DB rows that reference them, so the UI never shows 404 playback. This is the
 //! piece ZoneMinder implements with purge filters; here it's a few numeric
-//! knobs (see [`crate::configure::retention::RetentionConfig`]).
+//! knobs (see [`crate::configure::retention::RetentionConfig`]), overridable
+//! per storage and per monitor from the database ([`policy`]).
+//!
+//! A storage whose policy names a *tier* moves its expiring events there
+//! instead: media copied, `Events.StorageId` switched with a compare-and-set,
+//! then the source removed — the same order as a `move` media job. The tier's
+//! own policy decides when they finally go. When a move can't happen (tier
+//! missing, copy failed) the event stays put, unless the free-space floor is
+//! breached: that is the disk-full safety net and deletes as usual.
 //!
 //! Safety rules (never violated):
-//! * `Archived` events are never deleted.
+//! * `Archived` events are never deleted or moved, unless a storage or
+//!   monitor policy sets `protect_archived = false`.
 //! * In-progress events (`EndDateTime IS NULL`) are never deleted.
 //! * The newest event per monitor is always kept, even if a limit is still
 //!   breached — a single huge open event shouldn't be force-killed.
+//! * Tombstoned (soft-deleted) events are left to the trash reaper
+//!   ([`crate::service::trash`]), which owns their purge.
+//! * Events with a media job in flight are never moved.
 //!
 //! DB-before-disk ordering: a crash mid-delete leaves an orphan *file* (cheap
 //! to reclaim) rather than an orphan *row* (shows as broken playback).
 
+pub mod policy;
+
 use std::collections::{HashMap, HashSet};
 use std::path::Path;
 use std::sync::Arc;
 
-use sea_orm::{
-    ColumnTrait, Condition, DatabaseConnection, DbErr, EntityTrait, QueryFilter, QueryOrder,
-};
+use sea_orm::{ColumnTrait, DatabaseConnection, DbErr, EntityTrait, QueryFilter, QueryOrder};
 use tracing::{info, warn};
 
 use crate::configure::retention::RetentionConfig;
 use crate::entity::{events, storage};
+use crate::repo;
+use crate::service::event_storage::{
+    build_event_directory_path, copy_tree_async, link_event_dir, remove_event_dir,
+};
+use policy::StoragePolicy;
 
 pub struct RetentionService {
     db: Arc<DatabaseConnection>,
     config: RetentionConfig,
 }
 
-/// Outcome of reaping a single storage, for logging.
-#[derive(Default)]
-struct ReapStats {
-    deleted: usize,
-    reclaimed: u64,
+/// Held for the length of a pass, so the periodic reaper and an on-demand
+/// `POST /storage/{id}:reap` never work on the same events at once.
+static PASS: tokio::sync::Mutex<()> = tokio::sync::Mutex::const_new(());
+
+/// Outcome of reaping a single storage. Under `dry_run` it is the plan.
+#[derive(Debug, Default)]
+pub struct ReapReport {
+    pub deleted: usize,
+    pub moved: usize,
+    /// Bytes freed on the storage (deleted or moved away).
+    pub reclaimed: u64,
+    pub events: Vec<ReapedEvent>,
+}
+
+/// One event the reaper deleted or moved, and why.
+#[derive(Debug, Clone)]
+pub struct ReapedEvent {
+    pub event_id: u64,
+    pub monitor_id: u32,
+    pub bytes: u64,
+    pub start: Option<chrono::NaiveDateTime>,
+    /// `None` for a delete; the tier storage for a move.
+    pub moved_to: Option<u16>,
+    /// The limits it breached: `min_free_pct`, `max_storage_bytes`,
+    /// `monitor_max_storage_bytes`, `max_age_days`.
+    pub reasons: Vec<&'static str>,
 }
 
 impl RetentionService {
@@ -69,11 +107,12 @@ impl RetentionService {
         // identical to `DaemonManager::zmnext_events_root`'s fallback.
         let default_id = storages.first().map(|s| s.id);
 
+        let _pass = PASS.lock().await;
         for st in &storages {
             let is_default = Some(st.id) == default_id;
             match self.reap_storage(st, is_default).await {
-                Ok(stats) if stats.deleted > 0 => info!(
-                    "retention: storage {} ({}) {} {} events / {:.2} GiB",
+                Ok(report) if report.deleted + report.moved > 0 => info!(
+                    "retention: storage {} ({}) {} {} and {} {} events / {:.2} GiB",
                     st.id,
                     st.path,
                     if self.config.dry_run {
@@ -81,8 +120,14 @@ impl RetentionService {
                     } else {
                         "deleted"
                     },
-                    stats.deleted,
-                    stats.reclaimed as f64 / GIB,
+                    report.deleted,
+                    if self.config.dry_run {
+                        "would move"
+                    } else {
+                        "moved"
+                    },
+                    report.moved,
+                    report.reclaimed as f64 / GIB,
                 ),
                 Ok(_) => {}
                 Err(e) => warn!("retention: storage {} ({}) failed: {e}", st.id, st.path),
@@ -92,7 +137,8 @@ impl RetentionService {
     }
 
     /// Reap a single storage in isolation and report how many events were
-    /// deleted (or would be, under `dry_run`). Exposed for integration tests
+    /// deleted (or would be, under `dry_run`); events moved to a tier are not
+    /// counted. Exposed for integration tests
     /// and targeted manual runs so a caller can exercise one storage without
     /// touching every other storage the way [`reap_once`](Self::reap_once)
     /// does. `is_default` mirrors the default-storage sentinel matching in
@@ -103,37 +149,75 @@ impl RetentionService {
         st: &storage::Model,
         is_default: bool,
     ) -> Result<usize, DbErr> {
-        Ok(self.reap_storage(st, is_default).await?.deleted)
+        Ok(self.reap_storage_report(st, is_default).await?.deleted)
+    }
+
+    /// As [`reap_storage_once`](Self::reap_storage_once), returning the full
+    /// report. Waits for a pass already running to finish first.
+    pub async fn reap_storage_report(
+        &self,
+        st: &storage::Model,
+        is_default: bool,
+    ) -> Result<ReapReport, DbErr> {
+        let _pass = PASS.lock().await;
+        let report = self.reap_storage(st, is_default).await?;
+        info!(
+            "retention: on-demand pass on storage {} ({}){}: {} deleted, {} moved, {:.2} GiB",
+            st.id,
+            st.path,
+            if self.config.dry_run {
+                " [dry-run]"
+            } else {
+                ""
+            },
+            report.deleted,
+            report.moved,
+            report.reclaimed as f64 / GIB,
+        );
+        Ok(report)
     }
 
     async fn reap_storage(
         &self,
         st: &storage::Model,
         is_default: bool,
-    ) -> Result<ReapStats, DbErr> {
+    ) -> Result<ReapReport, DbErr> {
         let cfg = &self.config;
+        let db = self.db.as_ref();
+        let storage_row = repo::retention::find_by_storage(db, st.id).await?;
+        let policy = StoragePolicy::resolve(cfg, storage_row.as_ref());
+        let monitor_rows: HashMap<u32, _> = repo::retention::find_all_monitor(db)
+            .await?
+            .into_iter()
+            .map(|r| (r.monitor_id, r))
+            .collect();
+        let monitor_policy = |monitor_id: u32| policy.for_monitor(monitor_rows.get(&monitor_id));
 
         // Candidate set: events on this storage that are safe to delete.
-        let mut storage_match = Condition::any().add(events::Column::StorageId.eq(st.id));
-        if is_default {
-            storage_match = storage_match
-                .add(events::Column::StorageId.is_null())
-                .add(events::Column::StorageId.eq(0));
-        }
+        // Archived events are fetched too and dropped below unless their
+        // monitor's policy leaves them unprotected.
         let all: Vec<events::Model> = events::Entity::find()
-            .filter(storage_match)
-            .filter(events::Column::Archived.eq(0))
+            .filter(repo::events::on_storage(st.id, is_default))
             .filter(events::Column::EndDateTime.is_not_null())
+            .filter(events::Column::DeletedAt.is_null())
             .order_by_asc(events::Column::StartDateTime)
-            .all(self.db.as_ref())
-            .await?;
+            .all(db)
+            .await?
+            .into_iter()
+            .filter(|e| e.archived == 0 || !monitor_policy(e.monitor_id).protect_archived)
+            .collect();
 
         if all.is_empty() {
-            return Ok(ReapStats::default());
+            return Ok(ReapReport::default());
         }
 
-        // Total bytes held by this storage (incl. protected events) for the quota.
+        // Total bytes held by this storage (incl. protected events) for the
+        // quota, and per monitor for the monitor quotas.
         let mut used: u64 = all.iter().filter_map(|e| e.disk_space).sum();
+        let mut used_by_monitor: HashMap<u32, u64> = HashMap::new();
+        for e in &all {
+            *used_by_monitor.entry(e.monitor_id).or_default() += e.disk_space.unwrap_or(0);
+        }
 
         // Protect the newest event per monitor: since `all` is oldest-first, the
         // last id seen per monitor is its newest.
@@ -148,51 +232,103 @@ impl RetentionService {
         // report a realistic plan.
         let (total, mut avail) = fs_total_avail(&st.path).unwrap_or((0, 0));
 
-        let age_cutoff = (cfg.max_age_days > 0).then(|| {
-            chrono::Utc::now().naive_utc() - chrono::Duration::days(cfg.max_age_days as i64)
-        });
+        // Resolve the tier once per pass; a broken tier only stops moves.
+        let tier = match policy.tier_storage_id {
+            Some(id) => self.load_tier(st, id).await?,
+            None => None,
+        };
 
-        let mut stats = ReapStats::default();
+        let now = chrono::Utc::now().naive_utc();
+        let mut report = ReapReport::default();
         for ev in all {
             if protected.contains(&ev.id) {
                 continue;
             }
+            let mp = monitor_policy(ev.monitor_id);
+            let monitor_used = used_by_monitor.get(&ev.monitor_id).copied().unwrap_or(0);
             let free_pct = if total > 0 {
                 avail as f64 / total as f64 * 100.0
             } else {
                 100.0
             };
             let over_free = cfg.min_free_pct > 0.0 && free_pct < cfg.min_free_pct;
-            let over_bytes = cfg.max_bytes > 0 && used > cfg.max_bytes;
-            let too_old = age_cutoff
+            let over_bytes = policy.max_bytes > 0 && used > policy.max_bytes;
+            let over_monitor = mp.max_bytes > 0 && monitor_used > mp.max_bytes;
+            let too_old = mp
+                .age_cutoff(now)
                 .zip(ev.start_date_time)
                 .is_some_and(|(cut, start)| start < cut);
 
-            if !(over_free || over_bytes || too_old) {
-                // Oldest-first: nothing further can be over-age, and space/quota
-                // are satisfied, so we're done with this storage.
-                break;
+            let reasons: Vec<&'static str> = [
+                (over_free, "min_free_pct"),
+                (over_bytes, "max_storage_bytes"),
+                (over_monitor, "monitor_max_storage_bytes"),
+                (too_old, "max_age_days"),
+            ]
+            .into_iter()
+            .filter_map(|(hit, name)| hit.then_some(name))
+            .collect();
+            if reasons.is_empty() {
+                // Limits differ per monitor, so a later event may still be over
+                // its own; keep looking rather than stopping here.
+                continue;
             }
 
             let bytes = ev.disk_space.unwrap_or(0);
-            if cfg.dry_run {
-                info!(
-                    "retention[dry-run]: would delete event {} (monitor {}, {:.1} MiB, start {:?})",
-                    ev.id,
-                    ev.monitor_id,
-                    bytes as f64 / MIB,
-                    ev.start_date_time
-                );
+            let moved = match &tier {
+                Some(target) if cfg.dry_run => {
+                    info!(
+                        "retention[dry-run]: would move event {} (monitor {}, {:.1} MiB, start {:?}) to storage {}",
+                        ev.id,
+                        ev.monitor_id,
+                        bytes as f64 / MIB,
+                        ev.start_date_time,
+                        target.id
+                    );
+                    true
+                }
+                Some(target) => self.move_event(&ev, st, target).await?,
+                None => false,
+            };
+            let moved_to = moved.then(|| tier.as_ref().map(|t| t.id)).flatten();
+            if moved {
+                report.moved += 1;
             } else {
-                self.delete_event(&ev, st).await?;
+                // A tiered storage deletes only as the disk-full safety net,
+                // when the move could not happen.
+                if policy.tier_storage_id.is_some() && !over_free {
+                    continue;
+                }
+                if cfg.dry_run {
+                    info!(
+                        "retention[dry-run]: would delete event {} (monitor {}, {:.1} MiB, start {:?})",
+                        ev.id,
+                        ev.monitor_id,
+                        bytes as f64 / MIB,
+                        ev.start_date_time
+                    );
+                } else {
+                    self.delete_event(&ev, st).await?;
+                }
+                report.deleted += 1;
             }
             avail = avail.saturating_add(bytes);
             used = used.saturating_sub(bytes);
-            stats.deleted += 1;
-            stats.reclaimed = stats.reclaimed.saturating_add(bytes);
+            if let Some(m) = used_by_monitor.get_mut(&ev.monitor_id) {
+                *m = m.saturating_sub(bytes);
+            }
+            report.reclaimed = report.reclaimed.saturating_add(bytes);
+            report.events.push(ReapedEvent {
+                event_id: ev.id,
+                monitor_id: ev.monitor_id,
+                bytes,
+                start: ev.start_date_time,
+                moved_to,
+                reasons,
+            });
         }
 
-        Ok(stats)
+        Ok(report)
     }
 
     /// Delete one event: DB rows in a transaction, then its on-disk directory.
@@ -205,13 +341,98 @@ impl RetentionService {
         crate::service::event_storage::remove_event_dir(&st.path, ev).await;
         Ok(())
     }
+
+    /// The tier storage row, or `None` (logged) when it can't take events: gone,
+    /// disabled, or with a `..` in its path.
+    async fn load_tier(
+        &self,
+        st: &storage::Model,
+        tier_id: u16,
+    ) -> Result<Option<storage::Model>, DbErr> {
+        let tier = storage::Entity::find_by_id(tier_id)
+            .one(self.db.as_ref())
+            .await?;
+        let problem = match &tier {
+            None => Some("does not exist"),
+            Some(t) if t.enabled == 0 => Some("is disabled"),
+            Some(t) if crate::util::path::contains_traversal(&t.path) => {
+                Some("has a '..' in its path")
+            }
+            Some(_) => None,
+        };
+        if let Some(problem) = problem {
+            warn!(
+                "retention: storage {} tiers to storage {tier_id}, which {problem}; expiring events stay put",
+                st.id
+            );
+            return Ok(None);
+        }
+        Ok(tier)
+    }
+
+    /// Move one event's media to the tier storage and repoint its row there.
+    /// `Ok(false)` when the move didn't happen (a media job owns the event,
+    /// the media is missing, the copy failed, or the row changed meanwhile);
+    /// whatever was copied is removed again.
+    async fn move_event(
+        &self,
+        ev: &events::Model,
+        st: &storage::Model,
+        target: &storage::Model,
+    ) -> Result<bool, DbErr> {
+        let db = self.db.as_ref();
+        if repo::jobs::find_active_for_event(db, ev.id)
+            .await?
+            .is_some()
+        {
+            return Ok(false);
+        }
+        let dir = |root: &str| {
+            build_event_directory_path(root, ev.monitor_id, ev.id, ev.start_date_time, &ev.scheme)
+        };
+        let (src_dir, dst_dir) = (dir(&st.path), dir(&target.path));
+        if src_dir == dst_dir || !tokio::fs::try_exists(&src_dir).await.unwrap_or(false) {
+            warn!(
+                "retention: event {} media {} can't be moved to storage {}",
+                ev.id,
+                src_dir.display(),
+                target.id
+            );
+            return Ok(false);
+        }
+        if let Err(e) = copy_tree_async(src_dir, dst_dir).await {
+            warn!(
+                "retention: moving event {} to storage {}: {e}",
+                ev.id, target.id
+            );
+            remove_event_dir(&target.path, ev).await;
+            return Ok(false);
+        }
+        link_event_dir(&target.path, ev).await;
+
+        // The same compare-and-set a `move` job uses, so the two can't both
+        // win; if the row changed, our copy is an orphan unless it now points
+        // at it.
+        if !repo::events::set_storage(db, ev.id, ev.storage_id, target.id).await? {
+            let now_on = events::Entity::find_by_id(ev.id)
+                .one(db)
+                .await?
+                .and_then(|e| e.storage_id);
+            if now_on != Some(target.id) {
+                remove_event_dir(&target.path, ev).await;
+            }
+            return Ok(false);
+        }
+        remove_event_dir(&st.path, ev).await;
+        Ok(true)
+    }
 }
 
 const GIB: f64 = 1024.0 * 1024.0 * 1024.0;
 const MIB: f64 = 1024.0 * 1024.0;
 
 /// `(total_bytes, available_bytes)` for the filesystem holding `path`.
-fn fs_total_avail(path: &str) -> Option<(u64, u64)> {
+pub(crate) fn fs_total_avail(path: &str) -> Option<(u64, u64)> {
     let s = nix::sys::statvfs::statvfs(Path::new(path)).ok()?;
     let frag = s.fragment_size() as u64;
     Some((s.blocks() as u64 * frag, s.blocks_available() as u64 * frag))
@@ -0,0 +1,348 @@
+//! Per-storage and per-monitor retention overrides.
+//!
+//! The global `[retention]` knobs are the baseline. A `storage_retention` row
+//! overrides them for one storage and may name a colder *tier* storage that
+//! expiring events are moved to instead of deleted; a `monitor_retention` row
+//! overrides the storage's policy for one monitor's events, wherever they are.
+//! A NULL column inherits; `0` for a limit switches it off at that level.
+//!
+//! Resolution is pure ([`StoragePolicy::resolve`], [`StoragePolicy::for_monitor`])
+//! so the reaper and the tests agree on it without a database. The CRUD half
+//! below backs `/api/v3/storage/{id}/retention` and
+//! `/api/v3/monitors/{id}/retention`.
+
+use std::collections::HashMap;
+
+use crate::configure::retention::RetentionConfig;
+use crate::dto::request::retention::{MonitorRetentionRequest, StorageRetentionRequest};
+use crate::dto::response::retention::{MonitorRetentionResponse, StorageRetentionResponse};
+use crate::entity::{monitor_retention, storage_retention};
+use crate::error::{AppError, AppResult, Resource, ResourceType};
+use crate::repo;
+use crate::repo::retention::Overrides;
+use crate::server::state::AppState;
+use crate::service::monitor_acl::MonitorScope;
+
+/// What the reaper enforces on one storage.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct StoragePolicy {
+    /// `0` disables the age limit.
+    pub max_age_days: u64,
+    /// Cap on the storage's total bytes; `0` disables the quota.
+    pub max_bytes: u64,
+    pub protect_archived: bool,
+    /// Move expiring events here instead of deleting them.
+    pub tier_storage_id: Option<u16>,
+}
+
+/// What the reaper enforces on one monitor's events on a given storage.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct MonitorPolicy {
+    /// `0` disables the age limit.
+    pub max_age_days: u64,
+    /// Cap on this monitor's bytes on the storage; `0` means no cap of its own
+    /// (the storage-wide quota still applies).
+    pub max_bytes: u64,
+    pub protect_archived: bool,
+}
+
+impl StoragePolicy {
+    /// The global knobs with a storage's overrides, if it has any, on top.
+    pub fn resolve(cfg: &RetentionConfig, row: Option<&storage_retention::Model>) -> Self {
+        Self {
+            max_age_days: row
+                .and_then(|r| r.max_age_days)
+                .map_or(cfg.max_age_days, u64::from),
+            max_bytes: row
+                .and_then(|r| r.max_storage_bytes)
+                .unwrap_or(cfg.max_bytes),
+            protect_archived: row.and_then(|r| r.protect_archived).unwrap_or(true),
+            tier_storage_id: row.and_then(|r| r.tier_storage_id),
+        }
+    }
+
+    /// This storage's policy with a monitor's overrides, if it has any, on top.
+    /// The byte quota is not inherited: the storage's is a total across every
+    /// monitor, so a monitor only has a cap of its own when it sets one.
+    pub fn for_monitor(&self, row: Option<&monitor_retention::Model>) -> MonitorPolicy {
+        MonitorPolicy {
+            max_age_days: row
+                .and_then(|r| r.max_age_days)
+                .map_or(self.max_age_days, u64::from),
+            max_bytes: row.and_then(|r| r.max_storage_bytes).unwrap_or(0),
+            protect_archived: row
+                .and_then(|r| r.protect_archived)
+                .unwrap_or(self.protect_archived),
+        }
+    }
+}
+
+impl MonitorPolicy {
+    /// Events that started before this have expired.
+    pub fn age_cutoff(&self, now: chrono::NaiveDateTime) -> Option<chrono::NaiveDateTime> {
+        (self.max_age_days > 0).then(|| now - chrono::Duration::days(self.max_age_days as i64))
+    }
+}
+
+/// Whether tiering `storage_id` to `from` would make a loop: `from`'s own tier
+/// chain reaches `storage_id`, or never ends. `tiers` maps each storage to its
+/// configured tier.
+fn tier_loops(storage_id: u16, from: u16, tiers: &HashMap<u16, u16>) -> bool {
+    let mut at = from;
+    // A chain can't be longer than the number of storages with a tier; the
+    // bound only guards against a loop already in the table.
+    for _ in 0..=tiers.len() {
+        if at == storage_id {
+            return true;
+        }
+        match tiers.get(&at) {
+            Some(&next) => at = next,
+            None => return false,
+        }
+    }
+    true
+}
+
+fn no_storage_policy(storage_id: u16) -> AppError {
+    AppError::NotFoundError(Resource {
+        details: vec![("storage_id".into(), storage_id.to_string())],
+        resource_type: ResourceType::Message,
+    })
+}
+
+fn no_monitor_policy(monitor_id: u32) -> AppError {
+    AppError::NotFoundError(Resource {
+        details: vec![("monitor_id".into(), monitor_id.to_string())],
+        resource_type: ResourceType::Monitor,
+    })
+}
+
+/// A storage's stored overrides. 404 if the storage is unknown or has none.
+pub async fn get_storage(state: &AppState, storage_id: u16) -> AppResult<StorageRetentionResponse> {
+    crate::service::storage::get_by_id(state, storage_id).await?;
+    repo::retention::find_by_storage(state.db(), storage_id)
+        .await?
+        .map(StorageRetentionResponse::from)
+        .ok_or_else(|| no_storage_policy(storage_id))
+}
+
+/// Replace a storage's overrides. A tier target must be another existing
+/// storage whose own tier chain does not lead back here.
+pub async fn replace_storage(
+    state: &AppState,
+    storage_id: u16,
+    req: StorageRetentionRequest,
+) -> AppResult<StorageRetentionResponse> {
+    crate::service::storage::get_by_id(state, storage_id).await?;
+    if let Some(tier) = req.tier_storage_id {
+        if tier == storage_id {
+            return Err(AppError::BadRequestError(
+                "a storage cannot be its own retention tier".to_string(),
+            ));
+        }
+        let target = repo::storage::find_by_id(state.db(), tier)
+            .await?
+            .ok_or_else(|| AppError::BadRequestError(format!("storage {tier} does not exist")))?;
+        if crate::util::path::contains_traversal(&target.path) {
+            return Err(AppError::BadRequestError(format!(
+                "storage {tier} path contains '..' traversal"
+            )));
+        }
+        let tiers: HashMap<u16, u16> = repo::retention::find_all_storage(state.db())
+            .await?
+            .into_iter()
+            .filter_map(|r| r.tier_storage_id.map(|t| (r.storage_id, t)))
+            .collect();
+        if tier_loops(storage_id, tier, &tiers) {
+            return Err(AppError::BadRequestError(format!(
+                "tiering storage {storage_id} to {tier} would move events in a loop"
+            )));
+        }
+    }
+    let values = Overrides {
+        max_age_days: req.max_age_days,
+        max_storage_bytes: req.max_storage_bytes,
+        protect_archived: req.protect_archived,
+    };
+    let now = chrono::Utc::now().naive_utc();
+    let row =
+        repo::retention::upsert_storage(state.db(), storage_id, values, req.tier_storage_id, now)
+            .await?;
+    Ok(StorageRetentionResponse::from(row))
+}
+
+/// Remove a storage's overrides so it follows `[retention]` again.
+pub async fn delete_storage(state: &AppState, storage_id: u16) -> AppResult<()> {
+    crate::service::storage::get_by_id(state, storage_id).await?;
+    if repo::retention::delete_by_storage(state.db(), storage_id).await? {
+        Ok(())
+    } else {
+        Err(no_storage_policy(storage_id))
+    }
+}
+
+/// A monitor's stored overrides, after verifying the caller can access the
+/// monitor. 404 if the monitor is unknown/forbidden or has none.
+pub async fn get_monitor(
+    state: &AppState,
+    monitor_id: u32,
+    scope: &MonitorScope,
+) -> AppResult<MonitorRetentionResponse> {
+    crate::service::monitor::get_by_id(state, monitor_id, scope).await?;
+    repo::retention::find_by_monitor(state.db(), monitor_id)
+        .await?
+        .map(MonitorRetentionResponse::from)
+        .ok_or_else(|| no_monitor_policy(monitor_id))
+}
+
+/// Replace a monitor's overrides. Verifies the monitor exists/accessible (404)
+/// before writing, so we never persist an orphan row.
+pub async fn replace_monitor(
+    state: &AppState,
+    monitor_id: u32,
+    req: MonitorRetentionRequest,
+    scope: &MonitorScope,
+) -> AppResult<MonitorRetentionResponse> {
+    crate::service::monitor::get_by_id(state, monitor_id, scope).await?;
+    let values = Overrides {
+        max_age_days: req.max_age_days,
+        max_storage_bytes: req.max_storage_bytes,
+        protect_archived: req.protect_archived,
+    };
+    let now = chrono::Utc::now().naive_utc();
+    let row = repo::retention::upsert_monitor(state.db(), monitor_id, values, now).await?;
+    Ok(MonitorRetentionResponse::from(row))
+}
+
+/// Remove a monitor's overrides so its events follow their storage's policy.
+pub async fn delete_monitor(
+    state: &AppState,
+    monitor_id: u32,
+    scope: &MonitorScope,
+) -> AppResult<()> {
+    crate::service::monitor::get_by_id(state, monitor_id, scope).await?;
+    if repo::retention::delete_by_monitor(state.db(), monitor_id).await? {
+        Ok(())
+    } else {
+        Err(no_monitor_policy(monitor_id))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn at() -> chrono::NaiveDateTime {
+        chrono::NaiveDate::from_ymd_opt(2026, 10, 18)
+            .unwrap()
+            .and_hms_opt(0, 0, 0)
+            .unwrap()
+    }
+
+    fn global() -> RetentionConfig {
+        RetentionConfig {
+            max_age_days: 30,
+            max_bytes: 1000,
+            ..RetentionConfig::default()
+        }
+    }
+
+    fn storage_row(
+        max_age_days: Option<u32>,
+        max_storage_bytes: Option<u64>,
+        protect_archived: Option<bool>,
+        tier_storage_id: Option<u16>,
+    ) -> storage_retention::Model {
+        storage_retention::Model {
+            storage_id: 1,
+            max_age_days,
+            max_storage_bytes,
+            protect_archived,
+            tier_storage_id,
+            created_at: at(),
+            updated_at: at(),
+        }
+    }
+
+    fn monitor_row(
+        max_age_days: Option<u32>,
+        max_storage_bytes: Option<u64>,
+        protect_archived: Option<bool>,
+    ) -> monitor_retention::Model {
+        monitor_retention::Model {
+            monitor_id: 7,
+            max_age_days,
+            max_storage_bytes,
+            protect_archived,
+            created_at: at(),
+            updated_at: at(),
+        }
+    }
+
+    #[test]
+    fn storage_without_overrides_follows_the_global_knobs() {
+        let p = StoragePolicy::resolve(&global(), None);
+        assert_eq!(
+            p,
+            StoragePolicy {
+                max_age_days: 30,
+                max_bytes: 1000,
+                protect_archived: true,
+                tier_storage_id: None,
+            }
+        );
+        // NULL columns inherit exactly like a missing row.
+        let row = storage_row(None, None, None, None);
+        assert_eq!(StoragePolicy::resolve(&global(), Some(&row)), p);
+    }
+
+    #[test]
+    fn storage_overrides_replace_and_zero_disables() {
+        let row = storage_row(Some(0), Some(500), Some(false), Some(2));
+        let p = StoragePolicy::resolve(&global(), Some(&row));
+        assert_eq!(p.max_age_days, 0, "0 switches the global age limit off");
+        assert_eq!(p.max_bytes, 500);
+        assert!(!p.protect_archived);
+        assert_eq!(p.tier_storage_id, Some(2));
+    }
+
+    #[test]
+    fn monitor_inherits_from_its_storage_but_not_the_quota() {
+        let storage = StoragePolicy::resolve(
+            &global(),
+            Some(&storage_row(Some(14), None, Some(false), None)),
+        );
+        let p = storage.for_monitor(None);
+        assert_eq!(p.max_age_days, 14);
+        assert_eq!(p.max_bytes, 0, "the storage total is not a per-monitor cap");
+        assert!(!p.protect_archived);
+
+        let p = storage.for_monitor(Some(&monitor_row(Some(3), Some(200), Some(true))));
+        assert_eq!(
+            p,
+            MonitorPolicy {
+                max_age_days: 3,
+                max_bytes: 200,
+                protect_archived: true,
+            }
+        );
+        assert_eq!(p.age_cutoff(at()), Some(at() - chrono::Duration::days(3)));
+        assert_eq!(
+            storage
+                .for_monitor(Some(&monitor_row(Some(0), None, None)))
+                .age_cutoff(at()),
+            None
+        );
+    }
+
+    #[test]
+    fn tier_chains_that_lead_back_are_loops() {
+        let tiers = HashMap::from([(2, 3), (3, 4)]);
+        assert!(!tier_loops(1, 2, &tiers), "1 -> 2 -> 3 -> 4 ends");
+        assert!(tier_loops(4, 2, &tiers), "4 -> 2 -> 3 -> 4");
+        assert!(tier_loops(3, 3, &tiers));
+        // A loop already in the table does not hang the check.
+        let broken = HashMap::from([(2, 3), (3, 2)]);
+        assert!(tier_loops(1, 2, &broken), "2 -> 3 -> 2 never ends");
+    }
+}
@@ -0,0 +1,226 @@
+//! Capture of the Digest HA1 the RTSP server authenticates against.
+//!
+//! `Users.Password` is bcrypt, which HTTP Digest cannot be checked against, so
+//! zm-api records `MD5(username:realm:password)` in `rtsp_credentials` at the
+//! moments it holds the plaintext: a successful login, and any password being
+//! set (self-service change, admin create/update). A user who has done neither
+//! since `[streaming.rtsp_proxy]` was enabled cannot use RTSP until they sign
+//! in once.
+//!
+//! A password can also change where zm-api never sees it (ZoneMinder's own UI,
+//! or `Users.Password` edited directly), so each digest carries a
+//! [`password_fingerprint`] of the `Users.Password` hash it was captured
+//! against. The RTSP server refuses, and deletes, a digest whose fingerprint
+//! no longer matches, or whose user has been disabled. Deleting a user through
+//! zm-api drops their digest ([`forget`]); digests of users deleted elsewhere
+//! are swept when the RTSP server starts and hourly after that
+//! ([`spawn_orphan_sweep`]), never on an authentication attempt.
+//!
+//! Every function here is best-effort: a failure is logged and never fails the
+//! login or user edit that triggered it.
+
+use std::time::Duration;
+
+use chrono::Utc;
+use sha2::{Digest, Sha256};
+use tracing::{info, warn};
+
+use crate::entity::{rtsp_credentials, users};
+use crate::error::AppResult;
+use crate::repo;
+use crate::server::state::AppState;
+use crate::util::digest::ha1;
+
+/// Identifies the `Users.Password` hash a digest was captured against,
+/// without storing the hash itself: hex SHA-256.
+pub fn password_fingerprint(password_hash: &str) -> String {
+    hex::encode(Sha256::digest(password_hash.as_bytes()))
+}
+
+/// Whether a stored digest may still be used for `user` in `realm`: the
+/// account is enabled with API access, the realm is current and the digest
+/// was captured against the password `Users.Password` holds now.
+pub fn is_current(cred: &rtsp_credentials::Model, user: &users::Model, realm: &str) -> bool {
+    user.enabled == 1
+        && user.api_enabled == 1
+        && cred.realm == realm
+        && cred.password_fingerprint.as_deref() == Some(&password_fingerprint(&user.password))
+}
+
+/// The user named `username` and the digest to verify their RTSP response
+/// against, if both are usable.
+///
+/// A digest gone stale (see [`is_current`]) is deleted rather than merely
+/// skipped, so a later password change back or re-enabled account still needs
+/// a fresh login. An unknown name touches nothing: it is only a lookup, so
+/// unauthenticated clients cannot drive writes.
+pub async fn digest_for(
+    db: &sea_orm::DatabaseConnection,
+    username: &str,
+    realm: &str,
+) -> AppResult<Option<(users::Model, String)>> {
+    let Some(user) = repo::users::find_by_username(db, username).await? else {
+        return Ok(None);
+    };
+    let Some(cred) = repo::rtsp_credentials::find_by_user(db, user.id).await? else {
+        return Ok(None);
+    };
+    if !is_current(&cred, &user, realm) {
+        info!(
+            "dropping stale RTSP digest for user {} ({}); a fresh login is needed",
+            user.username, user.id
+        );
+        repo::rtsp_credentials::delete_by_user(db, user.id).await?;
+        return Ok(None);
+    }
+    Ok(Some((user, cred.ha1)))
+}
+
+/// Record the digest after a successful login. Only while the RTSP server is
+/// enabled — the password has not changed, so a disabled server has nothing
+/// stale to clean up. `password_hash` is the user's `Users.Password`.
+pub async fn on_login(
+    state: &AppState,
+    user_id: u32,
+    username: &str,
+    password_hash: &str,
+    password: &str,
+) {
+    if state.config.streaming.rtsp_proxy.enabled {
+        store(state, user_id, username, password_hash, password).await;
+    }
+}
+
+/// Record the digest for a newly set password, or drop the old one when the
+/// RTSP server is disabled so re-enabling it cannot accept the old password.
+/// `password_hash` is the new `Users.Password`.
+pub async fn on_password_set(
+    state: &AppState,
+    user_id: u32,
+    username: &str,
+    password_hash: &str,
+    password: &str,
+) {
+    if state.config.streaming.rtsp_proxy.enabled {
+        store(state, user_id, username, password_hash, password).await;
+    } else {
+        forget(state, user_id).await;
+    }
+}
+
+/// Drop a user's digest (the user was deleted).
+pub async fn forget(state: &AppState, user_id: u32) {
+    if let Err(e) = repo::rtsp_credentials::delete_by_user(state.db(), user_id).await {
+        warn!("failed to drop the RTSP digest for user {user_id}: {e}");
+    }
+}
+
+/// How often digests of users deleted outside zm-api are swept.
+const ORPHAN_SWEEP_INTERVAL: Duration = Duration::from_secs(3600);
+
+/// Sweep digests of users deleted outside zm-api (ZoneMinder's UI, or the
+/// `Users` table edited directly): once now, then every
+/// [`ORPHAN_SWEEP_INTERVAL`]. Started alongside the RTSP server.
+pub fn spawn_orphan_sweep(state: &AppState) {
+    let state = state.clone();
+    tokio::spawn(async move {
+        let mut ticker = tokio::time::interval(ORPHAN_SWEEP_INTERVAL);
+        ticker.set_missed_tick_behavior(tokio::time::MissedTickBehavior::Delay);
+        loop {
+            ticker.tick().await;
+            match repo::rtsp_credentials::delete_orphans(state.db()).await {
+                Ok(0) => {}
+                Ok(swept) => info!("dropped {swept} RTSP digest(s) of deleted users"),
+                Err(e) => warn!("RTSP digest sweep failed: {e}"),
+            }
+        }
+    });
+}
+
+async fn store(
+    state: &AppState,
+    user_id: u32,
+    username: &str,
+    password_hash: &str,
+    password: &str,
+) {
+    let realm = &state.config.streaming.rtsp_proxy.realm;
+    let digest = ha1(username, realm, password);
+    let fingerprint = password_fingerprint(password_hash);
+    let now = Utc::now().naive_utc();
+    if let Err(e) =
+        repo::rtsp_credentials::upsert(state.db(), user_id, realm, &digest, &fingerprint, now).await
+    {
+        warn!("failed to record the RTSP digest for user {user_id}: {e}");
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn fingerprint_follows_the_stored_hash() {
+        let old = password_fingerprint("$2y$10$abcdefghijklmnopqrstuv");
+        assert_eq!(old.len(), 64);
+        assert_eq!(old, password_fingerprint("$2y$10$abcdefghijklmnopqrstuv"));
+        assert_ne!(old, password_fingerprint("$2y$10$abcdefghijklmnopqrstuw"));
+    }
+
+    fn user(password: &str) -> users::Model {
+        use crate::entity::sea_orm_active_enums as E;
+        users::Model {
+            id: 7,
+            username: "alice".into(),
+            password: password.into(),
+            name: "Alice".into(),
+            email: "".into(),
+            phone: "".into(),
+            language: None,
+            enabled: 1,
+            stream: E::Stream::View,
+            events: E::Events::View,
+            control: E::Control::View,
+            monitors: E::Monitors::View,
+            groups: E::Groups::View,
+            devices: E::Devices::View,
+            snapshots: E::Snapshots::View,
+            system: E::System::View,
+            max_bandwidth: None,
+            token_min_expiry: 0,
+            api_enabled: 1,
+            home_view: "".into(),
+        }
+    }
+
+    fn cred(password_hash: Option<&str>) -> rtsp_credentials::Model {
+        rtsp_credentials::Model {
+            user_id: 7,
+            realm: "zm-api".into(),
+            ha1: ha1("alice", "zm-api", "secret"),
+            password_fingerprint: password_hash.map(password_fingerprint),
+            updated_at: chrono::NaiveDateTime::default(),
+        }
+    }
+
+    #[test]
+    fn a_changed_password_hash_retires_the_digest() {
+        let cred = cred(Some("$2y$10$old"));
+        assert!(is_current(&cred, &user("$2y$10$old"), "zm-api"));
+        // Changed in ZoneMinder's UI or straight in the table.
+        assert!(!is_current(&cred, &user("$2y$10$new"), "zm-api"));
+        assert!(!is_current(&cred, &user("$2y$10$old"), "other"));
+    }
+
+    #[test]
+    fn disabled_accounts_and_unfingerprinted_digests_are_refused() {
+        let cred_ok = cred(Some("$2y$10$old"));
+        let mut disabled = user("$2y$10$old");
+        disabled.enabled = 0;
+        assert!(!is_current(&cred_ok, &disabled, "zm-api"));
+        let mut no_api = user("$2y$10$old");
+        no_api.api_enabled = 0;
+        assert!(!is_current(&cred_ok, &no_api, "zm-api"));
+        assert!(!is_current(&cred(None), &user("$2y$10$old"), "zm-api"));
+    }
+}
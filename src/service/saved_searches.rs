@@ -0,0 +1,205 @@
+//! Saved event searches: named filter ASTs owned by a user.
+//!
+//! The replacement for the search half of a ZoneMinder `Filters` row. A search
+//! is only ever *run* on demand — previewed, or handed to a bulk job — so it
+//! carries none of the `Auto*` action or scheduling columns.
+
+use sea_orm::Condition;
+
+use crate::dto::request::filter_ast::FilterQuery;
+use crate::dto::request::saved_searches::{CreateSavedSearchRequest, UpdateSavedSearchRequest};
+use crate::dto::response::saved_searches::SavedSearchResponse;
+use crate::dto::{PaginatedResponse, PaginationParams};
+use crate::entity::saved_searches;
+use crate::error::{AppError, AppResult};
+use crate::repo;
+use crate::server::state::AppState;
+use crate::service::filters::{can_manage_all, can_view_all, owns};
+use crate::util::claim::UserClaims;
+
+/// Not-found for both missing searches and other users' searches, so ownership
+/// is not leaked via 404-vs-403 (same rule as filters).
+fn not_found(id: u64) -> AppError {
+    AppError::NotFoundError(crate::error::Resource {
+        details: vec![("id".into(), id.to_string())],
+        resource_type: crate::error::ResourceType::Message,
+    })
+}
+
+/// Validate a filter AST and compile it to the parameterised `Condition` it
+/// runs as. Saving compiles too, so a stored search can never fail to run.
+pub(crate) fn compile(query: &FilterQuery) -> AppResult<Condition> {
+    crate::service::filter_translate::validate(query)?;
+    crate::service::filter_build::build_condition(&query.predicate)
+}
+
+fn to_ast_json(query: &FilterQuery) -> AppResult<String> {
+    compile(query)?;
+    serde_json::to_string(query)
+        .map_err(|e| AppError::BadRequestError(format!("unserializable filter: {e}")))
+}
+
+/// Parse a stored search back into its AST.
+pub(crate) fn parse_ast(model: &saved_searches::Model) -> AppResult<FilterQuery> {
+    serde_json::from_str(&model.ast_json).map_err(|e| {
+        AppError::BadRequestError(format!(
+            "saved search {} holds an unreadable filter: {e}",
+            model.id
+        ))
+    })
+}
+
+/// Fetch a search the caller may read: their own, or any with System View.
+pub(crate) async fn find_readable(
+    state: &AppState,
+    id: u64,
+    claims: &UserClaims,
+) -> AppResult<saved_searches::Model> {
+    let item = repo::saved_searches::find_by_id(state.db(), id)
+        .await?
+        .ok_or_else(|| not_found(id))?;
+    if !can_view_all(claims) && !owns(claims, Some(item.user_id)) {
+        return Err(not_found(id));
+    }
+    Ok(item)
+}
+
+/// Fetch a search the caller may change: their own, or any with System Edit.
+async fn find_manageable(
+    state: &AppState,
+    id: u64,
+    claims: &UserClaims,
+) -> AppResult<saved_searches::Model> {
+    let item = repo::saved_searches::find_by_id(state.db(), id)
+        .await?
+        .ok_or_else(|| not_found(id))?;
+    if !can_manage_all(claims) && !owns(claims, Some(item.user_id)) {
+        return Err(not_found(id));
+    }
+    Ok(item)
+}
+
+pub async fn list_paginated(
+    state: &AppState,
+    params: &PaginationParams,
+    claims: &UserClaims,
+) -> AppResult<PaginatedResponse<SavedSearchResponse>> {
+    let owner = (!can_view_all(claims)).then_some(claims.uid);
+    let (items, total) = repo::saved_searches::find_paginated(state.db(), params, owner).await?;
+    let responses = items.into_iter().map(SavedSearchResponse::from).collect();
+    Ok(PaginatedResponse::from_params(responses, total, params))
+}
+
+pub async fn get_by_id(
+    state: &AppState,
+    id: u64,
+    claims: &UserClaims,
+) -> AppResult<SavedSearchResponse> {
+    Ok(find_readable(state, id, claims).await?.into())
+}
+
+pub async fn create(
+    state: &AppState,
+    req: CreateSavedSearchRequest,
+    claims: &UserClaims,
+) -> AppResult<SavedSearchResponse> {
+    let ast_json = to_ast_json(&req.filter)?;
+    let now = chrono::Utc::now().naive_utc();
+    let model =
+        repo::saved_searches::insert(state.db(), claims.uid, req.name, ast_json, now).await?;
+    Ok(model.into())
+}
+
+pub async fn update(
+    state: &AppState,
+    id: u64,
+    req: UpdateSavedSearchRequest,
+    claims: &UserClaims,
+) -> AppResult<SavedSearchResponse> {
+    let existing = find_manageable(state, id, claims).await?;
+    let ast_json = req.filter.as_ref().map(to_ast_json).transpose()?;
+    let now = chrono::Utc::now().naive_utc();
+    let model = repo::saved_searches::update(state.db(), existing, req.name, ast_json, now).await?;
+    Ok(model.into())
+}
+
+pub async fn delete(state: &AppState, id: u64, claims: &UserClaims) -> AppResult<()> {
+    find_manageable(state, id, claims).await?;
+    if repo::saved_searches::delete_by_id(state.db(), id).await? {
+        Ok(())
+    } else {
+        Err(not_found(id))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::util::authz::UserPermissions;
+    use sea_orm::{DatabaseBackend, MockDatabase};
+    use std::time::Duration;
+
+    fn user_claims(uid: u32) -> UserClaims {
+        UserClaims::new(
+            Duration::from_secs(600),
+            "user".into(),
+            uid,
+            UserPermissions::default(),
+            crate::util::claim::TokenType::Access,
+        )
+    }
+
+    fn query(json: serde_json::Value) -> FilterQuery {
+        serde_json::from_value(json).expect("valid FilterQuery json")
+    }
+
+    fn mk_search(id: u64, owner: u32) -> saved_searches::Model {
+        let now = chrono::Utc::now().naive_utc();
+        saved_searches::Model {
+            id,
+            user_id: owner,
+            name: "s".into(),
+            ast_json: r#"{"where":{"field":"archived","op":"eq","value":0}}"#.into(),
+            created_at: now,
+            updated_at: now,
+        }
+    }
+
+    #[test]
+    fn saving_rejects_filters_that_cannot_run() {
+        // Valid AST shape, but `regexp` is not compilable to a portable query.
+        let q = query(serde_json::json!({
+            "where": {"field": "cause", "op": "regexp", "value": "^Motion"}
+        }));
+        assert!(to_ast_json(&q).is_err());
+
+        let q = query(serde_json::json!({
+            "where": {"match": "all", "rules": [
+                {"field": "monitor_id", "op": "in", "value": [1, 2]},
+                {"field": "archived", "op": "eq", "value": 0}
+            ]}
+        }));
+        let json = to_ast_json(&q).expect("compilable filter saves");
+        let round: FilterQuery = serde_json::from_str(&json).unwrap();
+        assert!(compile(&round).is_ok(), "stored AST compiles again");
+    }
+
+    #[tokio::test]
+    async fn other_users_searches_are_not_found() {
+        let db = MockDatabase::new(DatabaseBackend::MySql)
+            .append_query_results::<saved_searches::Model, _, _>(vec![
+                vec![mk_search(7, 2)],
+                vec![mk_search(7, 3)],
+            ])
+            .into_connection();
+        let state = AppState::for_test_with_db(db);
+        let err = get_by_id(&state, 7, &user_claims(3))
+            .await
+            .expect_err("user 3 must not read user 2's search");
+        assert!(matches!(err, AppError::NotFoundError(_)));
+        let ok = get_by_id(&state, 7, &user_claims(3))
+            .await
+            .expect("owner reads own search");
+        assert_eq!(ok.user_id, 3);
+    }
+}
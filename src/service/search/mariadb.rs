@@ -59,7 +59,12 @@ impl MariaDbVectorStore {
                 format!("{alias}.{c}")
             }
         };
-        let mut clause = String::new();
+        // Tombstoned (soft-deleted) events stay indexed until the trash reaper
+        // purges them, but are never returned.
+        let mut clause = format!(
+            " AND NOT EXISTS (SELECT 1 FROM Events e WHERE e.Id = {} AND e.deleted_at IS NOT NULL)",
+            col("event_id")
+        );
         let mut params: Vec<Value> = Vec::new();
 
         if !f.monitor_ids.is_empty() {
@@ -333,9 +338,12 @@ mod tests {
     }
 
     #[test]
-    fn empty_filter_is_empty_clause() {
+    fn empty_filter_only_hides_trashed_events() {
         let (clause, params) = MariaDbVectorStore::filter_clause("t", &Filter::default());
-        assert!(clause.is_empty());
+        assert_eq!(
+            clause,
+            " AND NOT EXISTS (SELECT 1 FROM Events e WHERE e.Id = t.event_id AND e.deleted_at IS NOT NULL)"
+        );
         assert!(params.is_empty());
     }
 
@@ -1,8 +1,15 @@
+use crate::dto::response::storage::{
+    StorageHealthResponse, StorageMonitorUsage, StorageReapResponse,
+};
 use crate::dto::response::StorageResponse;
+use crate::dto::wrappers::DateTimeWrapper;
 use crate::dto::{PaginatedResponse, PaginationParams};
 use crate::error::{AppError, AppResult, Resource, ResourceType};
 use crate::repo;
 use crate::server::state::AppState;
+use crate::service::retention::policy::StoragePolicy;
+use crate::service::retention::{fs_total_avail, RetentionService};
+use crate::util::naive_local_to_utc;
 
 pub async fn list_all(state: &AppState) -> AppResult<Vec<StorageResponse>> {
     let items = repo::storage::find_all(state.db()).await?;
@@ -84,6 +91,97 @@ pub async fn delete(state: &AppState, id: u16) -> AppResult<()> {
     }
 }
 
+/// The storage row, and whether it is the default (lowest-id) storage that
+/// also owns `StorageId` 0 / NULL events. 404 if it doesn't exist.
+async fn find_with_default(
+    state: &AppState,
+    id: u16,
+) -> AppResult<(crate::entity::storage::Model, bool)> {
+    let item = repo::storage::find_by_id(state.db(), id)
+        .await?
+        .ok_or_else(|| {
+            AppError::NotFoundError(Resource {
+                details: vec![("id".into(), id.to_string())],
+                resource_type: ResourceType::Message,
+            })
+        })?;
+    let is_default = repo::storage::find_default(state.db())
+        .await?
+        .is_some_and(|d| d.id == id);
+    Ok((item, is_default))
+}
+
+/// Whether a storage is past a retention limit: below the free-space floor
+/// (when the filesystem could be read) or over its byte quota.
+fn is_full(free_pct: Option<f64>, min_free_pct: f64, used: u64, quota: Option<u64>) -> bool {
+    let below_floor = min_free_pct > 0.0 && free_pct.is_some_and(|pct| pct < min_free_pct);
+    let over_quota = quota.is_some_and(|q| used > q);
+    below_floor || over_quota
+}
+
+/// Usage, limits and a per-monitor breakdown for one storage. Bytes are the
+/// `Events.DiskSpace` ZoneMinder records, over events not in the trash;
+/// capacity and free space come from the filesystem.
+pub async fn health(state: &AppState, id: u16) -> AppResult<StorageHealthResponse> {
+    let (item, is_default) = find_with_default(state, id).await?;
+    let usage = repo::events::usage_by_monitor(state.db(), id, is_default).await?;
+    let overrides = repo::retention::find_by_storage(state.db(), id).await?;
+    let policy = StoragePolicy::resolve(&state.config.retention, overrides.as_ref());
+
+    let fs = fs_total_avail(&item.path).filter(|&(total, _)| total > 0);
+    let free_pct = fs.map(|(total, avail)| avail as f64 / total as f64 * 100.0);
+    let used_bytes = usage.iter().map(|u| u.bytes).sum();
+    let quota_bytes = (policy.max_bytes > 0).then_some(policy.max_bytes);
+    let min_free_pct = state.config.retention.min_free_pct;
+    let to_ts = |ndt| DateTimeWrapper(naive_local_to_utc(ndt));
+
+    Ok(StorageHealthResponse {
+        storage_id: id,
+        used_bytes,
+        archived_bytes: usage.iter().map(|u| u.archived_bytes).sum(),
+        events: usage.iter().map(|u| u.events).sum(),
+        capacity_bytes: fs.map(|(total, _)| total),
+        free_bytes: fs.map(|(_, avail)| avail),
+        free_pct,
+        min_free_pct,
+        quota_bytes,
+        oldest_event_ts: usage.iter().filter_map(|u| u.oldest_start).min().map(to_ts),
+        full: is_full(free_pct, min_free_pct, used_bytes, quota_bytes),
+        monitors: usage
+            .into_iter()
+            .map(|u| StorageMonitorUsage {
+                monitor_id: u.monitor_id,
+                events: u.events,
+                bytes: u.bytes,
+                archived_bytes: u.archived_bytes,
+                oldest_event_ts: u.oldest_start.map(to_ts),
+            })
+            .collect(),
+    })
+}
+
+/// Run the retention reaper on one storage now, with the configured limits
+/// and this storage's overrides, whether or not `[retention]` is enabled.
+/// `dry_run` only reports.
+pub async fn reap(state: &AppState, id: u16, dry_run: bool) -> AppResult<StorageReapResponse> {
+    let (item, is_default) = find_with_default(state, id).await?;
+    let config = crate::configure::retention::RetentionConfig {
+        dry_run,
+        ..state.config.retention.clone()
+    };
+    let report = RetentionService::new(state.db.clone(), config)
+        .reap_storage_report(&item, is_default)
+        .await?;
+    Ok(StorageReapResponse {
+        storage_id: id,
+        dry_run,
+        deleted: report.deleted,
+        moved: report.moved,
+        reclaimed_bytes: report.reclaimed,
+        events: report.events.into_iter().map(Into::into).collect(),
+    })
+}
+
 #[cfg(test)]
 mod tests {
     use super::*;
@@ -244,4 +342,14 @@ mod tests {
         let state = AppState::for_test_with_db(db);
         assert!(delete(&state, 1).await.is_ok());
     }
+
+    #[test]
+    fn full_means_below_the_floor_or_over_the_quota() {
+        assert!(!is_full(Some(50.0), 10.0, 100, None));
+        assert!(is_full(Some(5.0), 10.0, 100, None));
+        assert!(!is_full(Some(5.0), 0.0, 100, None), "floor disabled");
+        assert!(!is_full(None, 10.0, 100, None), "unreadable filesystem");
+        assert!(is_full(None, 10.0, 101, Some(100)));
+        assert!(!is_full(Some(50.0), 10.0, 100, Some(100)), "at the quota");
+    }
 }
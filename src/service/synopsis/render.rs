@@ -106,7 +106,7 @@ fn render_frame(
 
 /// Copy a tightly-packed RGB canvas into an `RGB24` AVFrame, honouring its line
 /// stride (which may be wider than `w*3`).
-fn canvas_to_frame(canvas: &Canvas, dst: &mut frame::Video) {
+pub(crate) fn canvas_to_frame(canvas: &Canvas, dst: &mut frame::Video) {
     let w = canvas.width() as usize;
     let row_bytes = w * 3;
     let stride = dst.stride(0);
@@ -142,6 +142,108 @@ fn enc_err(stage: &str) -> impl Fn(ffmpeg::Error) -> SynopsisError + '_ {
     move |e| SynopsisError::RenderFailed(format!("{stage}: {e}"))
 }
 
+/// An open H.264 encoder feeding an mp4 muxer: YUV420P frames in, a finished
+/// container out. Shared by the synopsis renderer and the event transcode job
+/// (`service::media_jobs`), so there is one place that knows how to set up
+/// libx264 + the mp4 muxer.
+pub(crate) struct H264Writer {
+    octx: format::context::Output,
+    enc: encoder::video::Encoder,
+    ost_index: usize,
+    enc_tb: Rational,
+    ost_tb: Rational,
+}
+
+impl H264Writer {
+    /// Open `out_path` as an mp4 — regardless of its extension, so callers can
+    /// write to a temporary name and rename — for `w`×`h` YUV420P frames whose
+    /// pts count in `time_base`. The parent directory must exist. Fails with
+    /// [`SynopsisError::EncoderUnavailable`] when no H.264 encoder is built into
+    /// the linked ffmpeg.
+    pub(crate) fn open(
+        out_path: &Path,
+        w: u32,
+        h: u32,
+        time_base: Rational,
+        frame_rate: Option<Rational>,
+    ) -> Result<Self, SynopsisError> {
+        let codec = encoder::find(codec::Id::H264).ok_or_else(|| {
+            SynopsisError::EncoderUnavailable("no H.264 encoder in linked ffmpeg".into())
+        })?;
+
+        let mut octx = format::output_as(&out_path, "mp4").map_err(enc_err("open output"))?;
+        let global_header = octx.format().flags().contains(format::Flags::GLOBAL_HEADER);
+
+        let mut ost = octx.add_stream(codec).map_err(enc_err("add stream"))?;
+        let ost_index = ost.index();
+
+        let mut enc = codec::context::Context::new_with_codec(codec)
+            .encoder()
+            .video()
+            .map_err(enc_err("encoder ctx"))?;
+        enc.set_width(w);
+        enc.set_height(h);
+        enc.set_format(Pixel::YUV420P);
+        enc.set_time_base(time_base);
+        enc.set_frame_rate(frame_rate);
+        if global_header {
+            enc.set_flags(codec::Flags::GLOBAL_HEADER);
+        }
+
+        let mut opts = Dictionary::new();
+        opts.set("preset", "veryfast");
+        opts.set("crf", "23");
+        let enc = enc.open_with(opts).map_err(enc_err("open encoder"))?;
+        ost.set_parameters(&enc);
+        // The encoder's authoritative time_base after open (used for packet pts/dts).
+        let enc_tb = enc.time_base();
+
+        octx.write_header().map_err(enc_err("write header"))?;
+        // The muxer finalises the stream time_base during write_header — read it now,
+        // after the header, so packet timestamps rescale to the right base.
+        let ost_tb = octx
+            .stream(ost_index)
+            .expect("output stream exists")
+            .time_base();
+
+        Ok(Self {
+            octx,
+            enc,
+            ost_index,
+            enc_tb,
+            ost_tb,
+        })
+    }
+
+    /// Encode one frame (pts already set) and mux whatever packets it yields.
+    pub(crate) fn write(&mut self, frame: &frame::Video) -> Result<(), SynopsisError> {
+        self.enc.send_frame(frame).map_err(enc_err("send frame"))?;
+        drain(
+            &mut self.enc,
+            &mut self.octx,
+            self.ost_index,
+            self.enc_tb,
+            self.ost_tb,
+        )
+    }
+
+    /// Flush the encoder and finalise the container.
+    pub(crate) fn finish(mut self) -> Result<(), SynopsisError> {
+        self.enc.send_eof().map_err(enc_err("send eof"))?;
+        drain(
+            &mut self.enc,
+            &mut self.octx,
+            self.ost_index,
+            self.enc_tb,
+            self.ost_tb,
+        )?;
+        self.octx
+            .write_trailer()
+            .map_err(enc_err("write trailer"))?;
+        Ok(())
+    }
+}
+
 /// Render the synopsis to an H.264 mp4 at `out_path`. The parent directory must
 /// exist. Fails with [`SynopsisError::EncoderUnavailable`] when no H.264 encoder
 /// is built into the linked ffmpeg, and [`SynopsisError::InvalidManifest`] when
@@ -173,44 +275,13 @@ pub fn render_mp4(
         .collect();
 
     // --- muxer + encoder setup ---------------------------------------------
-    let codec = encoder::find(codec::Id::H264).ok_or_else(|| {
-        SynopsisError::EncoderUnavailable("no H.264 encoder in linked ffmpeg".into())
-    })?;
-
-    let mut octx = format::output(&out_path).map_err(enc_err("open output"))?;
-    let global_header = octx.format().flags().contains(format::Flags::GLOBAL_HEADER);
-
-    let mut ost = octx.add_stream(codec).map_err(enc_err("add stream"))?;
-    let ost_index = ost.index();
-
-    let mut enc = codec::context::Context::new_with_codec(codec)
-        .encoder()
-        .video()
-        .map_err(enc_err("encoder ctx"))?;
-    enc.set_width(w);
-    enc.set_height(h);
-    enc.set_format(Pixel::YUV420P);
-    enc.set_time_base(Rational(1, fps as i32));
-    enc.set_frame_rate(Some(Rational(fps as i32, 1)));
-    if global_header {
-        enc.set_flags(codec::Flags::GLOBAL_HEADER);
-    }
-
-    let mut opts = Dictionary::new();
-    opts.set("preset", "veryfast");
-    opts.set("crf", "23");
-    let mut enc = enc.open_with(opts).map_err(enc_err("open encoder"))?;
-    ost.set_parameters(&enc);
-    // The encoder's authoritative time_base after open (used for packet pts/dts).
-    let enc_tb = enc.time_base();
-
-    octx.write_header().map_err(enc_err("write header"))?;
-    // The muxer finalises the stream time_base during write_header — read it now,
-    // after the header, so packet timestamps rescale to the right base.
-    let ost_tb = octx
-        .stream(ost_index)
-        .expect("output stream exists")
-        .time_base();
+    let mut writer = H264Writer::open(
+        out_path,
+        w,
+        h,
+        Rational(1, fps as i32),
+        Some(Rational(fps as i32, 1)),
+    )?;
 
     // RGB24 → YUV420P scaler reused for every frame.
     let mut scaler = software::scaling::Context::get(
@@ -246,15 +317,10 @@ pub fn render_mp4(
         scaler.run(&rgb, &mut yuv).map_err(enc_err("scale frame"))?;
         yuv.set_pts(Some(f));
 
-        enc.send_frame(&yuv).map_err(enc_err("send frame"))?;
-        drain(&mut enc, &mut octx, ost_index, enc_tb, ost_tb)?;
+        writer.write(&yuv)?;
     }
 
-    // Flush the encoder and finalise the container.
-    enc.send_eof().map_err(enc_err("send eof"))?;
-    drain(&mut enc, &mut octx, ost_index, enc_tb, ost_tb)?;
-    octx.write_trailer().map_err(enc_err("write trailer"))?;
-    Ok(())
+    writer.finish()
 }
 
 /// Convenience: the cache path for an event's rendered synopsis mp4.
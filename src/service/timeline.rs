@@ -0,0 +1,452 @@
+//! Event density per monitor — `GET /api/v3/monitors/timeline`.
+//!
+//! Cuts `[from, to)` into fixed-width buckets and reports, per visible monitor
+//! and bucket, how many events overlap it, their highest score, the seconds of
+//! alarm and of recording in it, and the event ids, for coverage bars and
+//! activity heatmaps.
+//!
+//! A range that lies inside one of ZoneMinder's rolling `Events_Hour/Day/Week/
+//! Month` windows (see [`maintenance::stats`](crate::service::maintenance::stats))
+//! is read from that window, which is far smaller than `Events`; any other
+//! range is read from `Events` by start time. Either way one more grouped
+//! query picks up, per monitor, the event that was already running at `from`.
+
+use std::collections::BTreeMap;
+
+use chrono::{DateTime, Utc};
+use rust_decimal::prelude::ToPrimitive;
+
+use crate::dto::request::timeline::TimelineQuery;
+use crate::dto::response::timeline::{MonitorTimeline, TimelineBucket, TimelineResponse};
+use crate::dto::wrappers::DateTimeWrapper;
+use crate::error::{AppError, AppResult};
+use crate::repo;
+use crate::repo::events::{EventSpan, EventWindow};
+use crate::server::state::AppState;
+use crate::service::monitor_acl::MonitorScope;
+use crate::util::authz::Level;
+use crate::util::{naive_local_to_utc, utc_to_naive_local};
+
+/// Most buckets one request may ask for.
+const MAX_BUCKETS: u64 = 2000;
+
+/// Most events one request may cover.
+const MAX_EVENTS: u64 = 20_000;
+
+/// Event ids listed per bucket.
+const BUCKET_EVENT_IDS: usize = 100;
+
+/// A window is only trusted for ranges starting this far inside it, so rows
+/// the maintenance pass is about to age out are not relied on.
+const ROLLUP_MARGIN: chrono::Duration = chrono::Duration::minutes(5);
+
+/// Parse a `bucket` parameter into its width.
+fn parse_bucket(raw: Option<&str>) -> AppResult<chrono::Duration> {
+    let raw = raw.map(str::trim).unwrap_or("hour");
+    let invalid = || {
+        AppError::BadRequestError(format!(
+            "invalid bucket `{raw}`: use hour, day, week, a number with s, m, h, d or w, \
+             or seconds"
+        ))
+    };
+    let width = match raw {
+        "hour" => chrono::Duration::hours(1),
+        "day" => chrono::Duration::days(1),
+        "week" => chrono::Duration::weeks(1),
+        _ => {
+            let split = raw.find(|c: char| !c.is_ascii_digit()).unwrap_or(raw.len());
+            let (amount, unit) = raw.split_at(split);
+            let amount: i64 = amount.parse().map_err(|_| invalid())?;
+            let unit_seconds = match unit {
+                "" | "s" => 1,
+                "m" => 60,
+                "h" => 3600,
+                "d" => 86_400,
+                "w" => 604_800,
+                _ => return Err(invalid()),
+            };
+            chrono::Duration::seconds(amount.checked_mul(unit_seconds).ok_or_else(invalid)?)
+        }
+    };
+    if width <= chrono::Duration::zero() {
+        return Err(invalid());
+    }
+    Ok(width)
+}
+
+/// The start of the `width`-wide bucket, counted from the Unix epoch, that
+/// holds `at`.
+fn align_down(at: DateTime<Utc>, width: chrono::Duration) -> DateTime<Utc> {
+    let width = width.num_milliseconds().max(1);
+    let ms = at.timestamp_millis();
+    DateTime::from_timestamp_millis(ms - ms.rem_euclid(width)).unwrap_or(at)
+}
+
+/// The smallest rolling window that holds every event of the first bucket.
+///
+/// Timeline clients page by whole buckets, so the window is chosen for the
+/// epoch-aligned bucket `from` falls in, not for `from` alone: a bucket that
+/// straddles a window's edge is read from `Events`, and consecutive pages of
+/// the same grid come from the same source.
+fn rollup_for(
+    from: DateTime<Utc>,
+    width: chrono::Duration,
+    now: DateTime<Utc>,
+) -> Option<EventWindow> {
+    let first_bucket = align_down(from, width);
+    EventWindow::ALL
+        .into_iter()
+        .find(|window| first_bucket >= now - window.span() + ROLLUP_MARGIN)
+}
+
+/// An event placed on the timeline.
+#[derive(Debug, Clone)]
+struct Span {
+    id: u64,
+    monitor_id: u32,
+    start: DateTime<Utc>,
+    /// Now, for an event still recording.
+    end: DateTime<Utc>,
+    alarm_seconds: f64,
+    max_score: u16,
+}
+
+impl Span {
+    fn from_row(row: &EventSpan, now: DateTime<Utc>) -> Option<Span> {
+        let start = naive_local_to_utc(row.start_date_time?);
+        let end = row
+            .end_date_time
+            .map(naive_local_to_utc)
+            .unwrap_or(now)
+            .max(start);
+        let length = row.length.to_f64().unwrap_or(0.0).max(0.0);
+        let alarm_seconds = match (row.frames, row.alarm_frames) {
+            (Some(frames), Some(alarm)) if frames > 0 => {
+                length * f64::from(alarm.min(frames)) / f64::from(frames)
+            }
+            _ => 0.0,
+        };
+        Some(Span {
+            id: row.id,
+            monitor_id: row.monitor_id,
+            start,
+            end,
+            alarm_seconds,
+            max_score: row.max_score.unwrap_or(0),
+        })
+    }
+}
+
+/// The bucket grid over a range.
+struct Grid {
+    from: DateTime<Utc>,
+    to: DateTime<Utc>,
+    width: chrono::Duration,
+}
+
+impl Grid {
+    fn bucket_count(&self) -> u64 {
+        let range = (self.to - self.from).num_milliseconds();
+        let width = self.width.num_milliseconds();
+        ((range + width - 1) / width) as u64
+    }
+
+    /// Spread `spans` (of one monitor, oldest first) over the grid, keeping
+    /// only buckets they touch.
+    fn fill(&self, spans: &[Span]) -> Vec<TimelineBucket> {
+        let width = self.width.num_milliseconds();
+        let range = (self.to - self.from).num_milliseconds();
+        let mut buckets: BTreeMap<i64, TimelineBucket> = BTreeMap::new();
+
+        for span in spans {
+            let start = (span.start - self.from).num_milliseconds().max(0);
+            let end = (span.end - self.from).num_milliseconds().min(range);
+            let duration = (span.end - span.start).num_milliseconds();
+            // A zero-length event still marks the bucket it happened in.
+            let (first, last) = if duration == 0 && start == end && start < range {
+                (start / width, start / width)
+            } else if start < end {
+                (start / width, (end - 1) / width)
+            } else {
+                continue;
+            };
+
+            for index in first..=last {
+                let overlap = end.min((index + 1) * width) - start.max(index * width);
+                let bucket = buckets.entry(index).or_insert_with(|| TimelineBucket {
+                    index: index as u64,
+                    start: DateTimeWrapper(self.from + self.width * index as i32),
+                    event_count: 0,
+                    max_score: 0,
+                    alarm_seconds: 0.0,
+                    recorded_seconds: 0.0,
+                    event_ids: Vec::new(),
+                });
+                bucket.event_count += 1;
+                bucket.max_score = bucket.max_score.max(span.max_score);
+                bucket.recorded_seconds += overlap.max(0) as f64 / 1000.0;
+                bucket.alarm_seconds += if duration > 0 {
+                    span.alarm_seconds * overlap.max(0) as f64 / duration as f64
+                } else {
+                    span.alarm_seconds
+                };
+                if bucket.event_ids.len() < BUCKET_EVENT_IDS {
+                    bucket.event_ids.push(span.id);
+                }
+            }
+        }
+        buckets.into_values().collect()
+    }
+}
+
+/// The timeline of every monitor the caller may view.
+pub async fn get(
+    state: &AppState,
+    query: TimelineQuery,
+    scope: &MonitorScope,
+) -> AppResult<TimelineResponse> {
+    let (from, to) = (query.from.0, query.to.0);
+    if to <= from {
+        return Err(AppError::BadRequestError(
+            "`to` must be after `from`".to_string(),
+        ));
+    }
+    let grid = Grid {
+        from,
+        to,
+        width: parse_bucket(query.bucket.as_deref())?,
+    };
+    let bucket_count = grid.bucket_count();
+    if bucket_count > MAX_BUCKETS {
+        return Err(AppError::BadRequestError(format!(
+            "{bucket_count} buckets requested; at most {MAX_BUCKETS} fit in one timeline, \
+             so use a wider bucket or a shorter range"
+        )));
+    }
+
+    let visible = scope.visible_ids(Level::View);
+    let monitor_ids: Vec<u32> = repo::monitors::find_all(state.db(), visible.as_deref())
+        .await?
+        .into_iter()
+        .map(|m| m.id)
+        .collect();
+
+    let now = Utc::now();
+    let window = rollup_for(from, grid.width, now);
+    let mut rows = Vec::new();
+    if !monitor_ids.is_empty() {
+        let (from_local, to_local) = (utc_to_naive_local(from), utc_to_naive_local(to));
+        // Already running at `from`: the last event of each monitor to start
+        // before it.
+        rows.extend(
+            repo::events::find_last_spans_before(state.db(), &monitor_ids, from_local).await?,
+        );
+        let starting = repo::events::find_spans_starting_in(
+            state.db(),
+            &monitor_ids,
+            from_local,
+            to_local,
+            window,
+            MAX_EVENTS + 1,
+        )
+        .await?;
+        if starting.len() as u64 > MAX_EVENTS {
+            return Err(AppError::BadRequestError(format!(
+                "more than {MAX_EVENTS} events in range; ask for a shorter one"
+            )));
+        }
+        rows.extend(starting);
+    }
+
+    let mut spans: BTreeMap<u32, Vec<Span>> =
+        monitor_ids.iter().map(|&id| (id, Vec::new())).collect();
+    for span in rows.iter().filter_map(|row| Span::from_row(row, now)) {
+        spans.entry(span.monitor_id).or_default().push(span);
+    }
+    let monitors = spans
+        .into_iter()
+        .map(|(monitor_id, mut spans)| {
+            spans.sort_by_key(|s| (s.start, s.id));
+            MonitorTimeline {
+                monitor_id,
+                buckets: grid.fill(&spans),
+            }
+        })
+        .collect();
+
+    Ok(TimelineResponse {
+        from: DateTimeWrapper(from),
+        to: DateTimeWrapper(to),
+        bucket_seconds: grid.width.num_seconds() as u64,
+        bucket_count,
+        source: window.map_or("Events", EventWindow::table).to_string(),
+        monitors,
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn at(secs: i64) -> DateTime<Utc> {
+        DateTime::from_timestamp(1_760_000_000 + secs, 0).unwrap()
+    }
+
+    fn span(id: u64, start: i64, end: i64, alarm_seconds: f64, max_score: u16) -> Span {
+        Span {
+            id,
+            monitor_id: 1,
+            start: at(start),
+            end: at(end),
+            alarm_seconds,
+            max_score,
+        }
+    }
+
+    fn grid(from: i64, to: i64, width: i64) -> Grid {
+        Grid {
+            from: at(from),
+            to: at(to),
+            width: chrono::Duration::seconds(width),
+        }
+    }
+
+    #[test]
+    fn bucket_widths_parse() {
+        let secs = |raw| parse_bucket(raw).map(|d| d.num_seconds());
+        assert_eq!(secs(None).unwrap(), 3600);
+        assert_eq!(secs(Some("day")).unwrap(), 86_400);
+        assert_eq!(secs(Some("15m")).unwrap(), 900);
+        assert_eq!(secs(Some("6h")).unwrap(), 21_600);
+        assert_eq!(secs(Some("2w")).unwrap(), 1_209_600);
+        assert_eq!(secs(Some("300")).unwrap(), 300);
+        for bad in ["", "0", "0m", "m", "5y", "-5m", "month"] {
+            assert!(parse_bucket(Some(bad)).is_err(), "{bad}");
+        }
+    }
+
+    #[test]
+    fn smallest_window_reaching_the_first_bucket_is_used() {
+        let minute = chrono::Duration::minutes(1);
+        let hour = chrono::Duration::hours(1);
+        // Midnight, day 100.
+        let now = DateTime::from_timestamp(100 * 86_400, 0).unwrap();
+        assert_eq!(
+            rollup_for(now - chrono::Duration::minutes(30), minute, now),
+            Some(EventWindow::Hour)
+        );
+        assert_eq!(
+            rollup_for(now - chrono::Duration::hours(23), hour, now),
+            Some(EventWindow::Day)
+        );
+        // Too close to the edge of the day window.
+        assert_eq!(
+            rollup_for(now - chrono::Duration::hours(24), hour, now),
+            Some(EventWindow::Week)
+        );
+        assert_eq!(
+            rollup_for(now - chrono::Duration::days(20), hour, now),
+            Some(EventWindow::Month)
+        );
+        assert_eq!(
+            rollup_for(now - chrono::Duration::days(40), hour, now),
+            None
+        );
+    }
+
+    #[test]
+    fn window_is_chosen_for_the_aligned_bucket() {
+        let now = DateTime::from_timestamp(100 * 86_400, 0).unwrap();
+        // 50 minutes ago is inside the hour window, but its 15-minute bucket
+        // started an hour ago, at the window's edge.
+        let from = now - chrono::Duration::minutes(50);
+        assert_eq!(
+            rollup_for(from, chrono::Duration::minutes(5), now),
+            Some(EventWindow::Hour)
+        );
+        assert_eq!(
+            rollup_for(from, chrono::Duration::minutes(15), now),
+            Some(EventWindow::Day)
+        );
+        assert_eq!(
+            align_down(from, chrono::Duration::minutes(15)),
+            now - chrono::Duration::minutes(60)
+        );
+    }
+
+    #[test]
+    fn events_spanning_buckets_count_in_each() {
+        let g = grid(0, 3600, 900);
+        assert_eq!(g.bucket_count(), 4);
+        // 600s..1500s: 300s in bucket 0, 600s in bucket 1; 90s of alarm.
+        let buckets = g.fill(&[span(7, 600, 1500, 90.0, 40), span(8, 1000, 1100, 0.0, 90)]);
+
+        assert_eq!(buckets.len(), 2);
+        assert_eq!((buckets[0].index, buckets[0].event_count), (0, 1));
+        assert_eq!(buckets[0].start.0, at(0));
+        assert!((buckets[0].recorded_seconds - 300.0).abs() < 1e-9);
+        assert!((buckets[0].alarm_seconds - 30.0).abs() < 1e-9);
+
+        assert_eq!((buckets[1].index, buckets[1].event_count), (1, 2));
+        assert_eq!(buckets[1].start.0, at(900));
+        assert_eq!(buckets[1].max_score, 90);
+        assert_eq!(buckets[1].event_ids, [7, 8]);
+        assert!((buckets[1].recorded_seconds - 700.0).abs() < 1e-9);
+        assert!((buckets[1].alarm_seconds - 60.0).abs() < 1e-9);
+    }
+
+    #[test]
+    fn events_are_clipped_to_the_range() {
+        let g = grid(0, 1000, 600);
+        assert_eq!(g.bucket_count(), 2);
+        let buckets = g.fill(&[
+            // Ran into the range from before it.
+            span(1, -300, 300, 0.0, 10),
+            // Ended exactly at `from`: not in range.
+            span(2, -100, 0, 0.0, 99),
+            // Runs past `to`.
+            span(3, 900, 2000, 0.0, 5),
+            // Zero-length, inside.
+            span(4, 700, 700, 2.0, 1),
+        ]);
+
+        assert_eq!(buckets.len(), 2);
+        assert_eq!(buckets[0].event_ids, [1]);
+        assert!((buckets[0].recorded_seconds - 300.0).abs() < 1e-9);
+        assert_eq!(buckets[1].event_ids, [3, 4]);
+        assert!((buckets[1].recorded_seconds - 100.0).abs() < 1e-9);
+        assert!((buckets[1].alarm_seconds - 2.0).abs() < 1e-9);
+    }
+
+    #[test]
+    fn alarm_seconds_follow_alarm_frame_share() {
+        let row = EventSpan {
+            id: 1,
+            monitor_id: 1,
+            start_date_time: Some(utc_to_naive_local(at(0))),
+            end_date_time: None,
+            length: rust_decimal::Decimal::new(6000, 2),
+            frames: Some(600),
+            alarm_frames: Some(150),
+            max_score: None,
+        };
+        let span = Span::from_row(&row, at(60)).unwrap();
+        assert!((span.alarm_seconds - 15.0).abs() < 1e-9);
+        assert_eq!(span.end, at(60), "a running event ends now");
+        assert_eq!(span.max_score, 0);
+
+        let no_frames = EventSpan {
+            frames: Some(0),
+            ..row.clone()
+        };
+        assert_eq!(
+            Span::from_row(&no_frames, at(60)).unwrap().alarm_seconds,
+            0.0
+        );
+        let no_start = EventSpan {
+            start_date_time: None,
+            ..row
+        };
+        assert!(Span::from_row(&no_start, at(60)).is_none());
+    }
+}
@@ -0,0 +1,245 @@
+//! Event trash: soft-delete, restore, and the reaper that empties it.
+//!
+//! Deleting an event is two-stage (see `docs/STORAGE_PLAN.md`).
+//! [`trash_event`] stamps `Events.deleted_at` — from then on every list, count
+//! and search query skips the row — and renames the media into
+//! `{storage}/.trash/{EventId}`, one atomic syscall on the same filesystem.
+//! Until the grace period (`[trash].grace_hours`) has passed,
+//! [`restore_event`] puts both back. After it, [`TrashReaper`] purges the event
+//! for good: DB rows first, then the trashed directory.
+//!
+//! The tombstone is the authoritative record. A rename that fails leaves the
+//! media where it was, and the reaper removes it from there as well.
+
+use std::collections::HashMap;
+use std::sync::Arc;
+
+use futures::stream::{self, StreamExt};
+use sea_orm::{DatabaseConnection, DbErr, EntityTrait, QueryOrder};
+use tracing::{info, warn};
+
+use crate::configure::trash::TrashConfig;
+use crate::entity::{events, storage};
+use crate::error::{AppError, AppResult};
+use crate::repo;
+use crate::server::state::AppState;
+use crate::service::event_storage;
+
+/// Tombstones the reaper loads per query.
+const BATCH: u64 = 200;
+
+/// Soft-delete `event`: tombstone it, then move its media into the trash.
+/// Returns `false` when it was already tombstoned, so a repeated delete is a
+/// no-op. An event with a media job queued or running is refused; the job
+/// would be working on files about to move.
+pub(crate) async fn trash_event(state: &AppState, event: &events::Model) -> AppResult<bool> {
+    if let Some(job) = repo::jobs::find_active_for_event(state.db(), event.id).await? {
+        return Err(AppError::ConflictError(format!(
+            "event {} has media job {} in progress",
+            event.id, job.id
+        )));
+    }
+    let now = chrono::Utc::now().naive_utc();
+    if !repo::events::soft_delete(state.db(), event.id, now).await? {
+        return Ok(false);
+    }
+    // The event is already hidden; from here on a failure only means its media
+    // is purged from the live tree instead of the trash.
+    match event_storage::resolve_event_storage_path(state, event).await {
+        Ok(root) => {
+            if let Err(e) = event_storage::move_to_trash(&root, event).await {
+                warn!(
+                    "Event {} deleted, but its media could not be moved to the trash: {e}",
+                    event.id
+                );
+            }
+        }
+        Err(e) => warn!(
+            "Event {} deleted, but its storage could not be resolved: {e}",
+            event.id
+        ),
+    }
+    Ok(true)
+}
+
+/// Undo [`trash_event`] for a tombstoned `event`: move its media back, then
+/// clear the tombstone. Refused once the grace period has passed, since the
+/// reaper may already be purging it.
+pub(crate) async fn restore_event(state: &AppState, event: &events::Model) -> AppResult<()> {
+    let config = &state.config.trash;
+    let not_before = grace_cutoff(config, chrono::Utc::now().naive_utc());
+    let expired = || {
+        AppError::ConflictError(format!(
+            "event {} was deleted more than {} hours ago and can no longer be restored",
+            event.id, config.grace_hours
+        ))
+    };
+    if event.deleted_at.is_some_and(|at| at < not_before) {
+        return Err(expired());
+    }
+    let root = event_storage::resolve_event_storage_path(state, event).await?;
+    event_storage::restore_from_trash(&root, event)
+        .await
+        .map_err(|e| {
+            AppError::InternalServerError(format!("restoring event {} media failed: {e}", event.id))
+        })?;
+    if !repo::events::restore(state.db(), event.id, not_before).await? {
+        return Err(expired());
+    }
+    Ok(())
+}
+
+/// Tombstones stamped before this instant are past the grace period.
+fn grace_cutoff(config: &TrashConfig, now: chrono::NaiveDateTime) -> chrono::NaiveDateTime {
+    now.checked_sub_signed(config.grace())
+        .unwrap_or(chrono::NaiveDateTime::MIN)
+}
+
+/// The `Storages.Path` an event lives under: its own storage's, or the default
+/// one for the `StorageId` 0 / NULL sentinel and for a storage row that no
+/// longer exists.
+fn storage_root<'a>(
+    roots: &'a HashMap<u16, String>,
+    default_root: &'a str,
+    storage_id: Option<u16>,
+) -> &'a str {
+    match storage_id {
+        Some(sid) if !event_storage::is_default_storage(storage_id) => {
+            roots.get(&sid).map_or(default_root, String::as_str)
+        }
+        _ => default_root,
+    }
+}
+
+/// Periodically purges events whose tombstone is older than the grace period.
+pub struct TrashReaper {
+    db: Arc<DatabaseConnection>,
+    config: TrashConfig,
+    /// Root for default-storage events when the `Storage` table is empty,
+    /// as in [`event_storage::default_storage_path`].
+    events_dir: String,
+}
+
+impl TrashReaper {
+    pub fn new(db: Arc<DatabaseConnection>, config: TrashConfig, events_dir: String) -> Self {
+        Self {
+            db,
+            config,
+            events_dir,
+        }
+    }
+
+    /// Spawn the periodic reaper loop. Returns immediately.
+    pub fn spawn(self: Arc<Self>) {
+        let interval = self.config.interval();
+        tokio::spawn(async move {
+            let mut ticker = tokio::time::interval(interval);
+            ticker.set_missed_tick_behavior(tokio::time::MissedTickBehavior::Delay);
+            loop {
+                ticker.tick().await;
+                if let Err(e) = self.reap_once().await {
+                    warn!("trash pass failed: {e}");
+                }
+            }
+        });
+    }
+
+    /// Purge every event tombstoned before the grace period and report how
+    /// many went. Public for tests / manual runs.
+    pub async fn reap_once(&self) -> Result<usize, DbErr> {
+        let cutoff = grace_cutoff(&self.config, chrono::Utc::now().naive_utc());
+        let storages = storage::Entity::find()
+            .order_by_asc(storage::Column::Id)
+            .all(self.db.as_ref())
+            .await?;
+        // Events with StorageId 0 / NULL live on the lowest-id storage.
+        let default_root = storages
+            .first()
+            .map(|s| s.path.clone())
+            .unwrap_or_else(|| self.events_dir.clone());
+        let roots: HashMap<u16, String> = storages.into_iter().map(|s| (s.id, s.path)).collect();
+
+        let mut purged = 0;
+        let mut after = None;
+        loop {
+            let batch =
+                repo::events::find_trashed_before(self.db.as_ref(), cutoff, after, BATCH).await?;
+            let Some(last) = batch.last() else { break };
+            after = Some(last.id);
+            // Bounded concurrency: each purge is a directory walk on the same
+            // disk ZoneMinder is recording to.
+            purged += stream::iter(&batch)
+                .map(|ev| self.purge(ev, storage_root(&roots, &default_root, ev.storage_id)))
+                .buffer_unordered(self.config.max_concurrent.max(1))
+                .filter(|done| std::future::ready(*done))
+                .count()
+                .await;
+            if (batch.len() as u64) < BATCH {
+                break;
+            }
+        }
+        if purged > 0 {
+            info!("trash: purged {purged} events deleted before {cutoff}");
+        }
+        Ok(purged)
+    }
+
+    /// Purge one event: DB rows in a transaction, then its media. Returns
+    /// whether the rows went; a failure is logged and retried next pass.
+    async fn purge(&self, ev: &events::Model, root: &str) -> bool {
+        // The same delete the retention reaper uses, so the two can never
+        // leave different debris behind.
+        if let Err(e) = repo::events::delete_with_children(self.db.as_ref(), ev.id).await {
+            warn!("trash: purging event {} failed: {e}", ev.id);
+            return false;
+        }
+        if crate::util::path::contains_traversal(root) {
+            warn!(
+                "trash: refusing storage path with '..' traversal {:?}; event {} media left on disk",
+                root, ev.id
+            );
+        } else {
+            event_storage::purge_event_media(root, ev).await;
+        }
+        true
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn grace_cutoff_counts_back_from_now_and_saturates() {
+        let now = chrono::NaiveDate::from_ymd_opt(2026, 10, 18)
+            .unwrap()
+            .and_hms_opt(12, 0, 0)
+            .unwrap();
+        let config = TrashConfig {
+            grace_hours: 36,
+            ..TrashConfig::default()
+        };
+        assert_eq!(
+            grace_cutoff(&config, now),
+            now - chrono::Duration::hours(36)
+        );
+        let forever = TrashConfig {
+            grace_hours: u64::MAX,
+            ..TrashConfig::default()
+        };
+        assert_eq!(grace_cutoff(&forever, now), chrono::NaiveDateTime::MIN);
+    }
+
+    #[test]
+    fn storage_root_falls_back_to_the_default_storage() {
+        let roots = HashMap::from([(1, "/srv/a".to_string()), (2, "/srv/b".to_string())]);
+        assert_eq!(storage_root(&roots, "/srv/a", Some(2)), "/srv/b");
+        assert_eq!(storage_root(&roots, "/srv/a", Some(0)), "/srv/a");
+        assert_eq!(storage_root(&roots, "/srv/a", None), "/srv/a");
+        assert_eq!(
+            storage_root(&roots, "/srv/a", Some(9)),
+            "/srv/a",
+            "row gone"
+        );
+    }
+}
@@ -3,6 +3,7 @@ use crate::dto::{PaginatedResponse, PaginationParams};
 use crate::error::{AppError, AppResult, Resource, ResourceType};
 use crate::repo;
 use crate::server::state::AppState;
+use crate::service::rtsp_credentials;
 
 pub async fn list_all(state: &AppState) -> AppResult<Vec<UserResponse>> {
     let items = repo::users::find_all(state.db()).await?;
@@ -51,6 +52,9 @@ pub async fn update(
             resource_type: ResourceType::User,
         })
     })?;
+    if let Some(pw) = req.password.as_deref().filter(|pw| !pw.is_empty()) {
+        rtsp_credentials::on_password_set(state, id, &item.username, &item.password, pw).await;
+    }
     Ok(UserResponse::from(&item))
 }
 
@@ -62,14 +66,18 @@ pub async fn create(
     // plaintext is stored verbatim, which both leaks credentials and makes the
     // account unusable (the bcrypt login verifier can never match a plaintext
     // value). See docs/REVIEW_FIXES_PLAN.md §1.1.
-    req.password = crate::util::password::hash(req.password).await?;
+    let password = std::mem::take(&mut req.password);
+    req.password = crate::util::password::hash(password.clone()).await?;
     let model = repo::users::create(state.db(), &req).await?;
+    rtsp_credentials::on_password_set(state, model.id, &model.username, &model.password, &password)
+        .await;
     Ok(UserResponse::from(&model))
 }
 
 pub async fn delete(state: &AppState, id: u32) -> AppResult<()> {
     let ok = repo::users::delete_by_id(state.db(), id).await?;
     if ok {
+        rtsp_credentials::forget(state, id).await;
         Ok(())
     } else {
         Err(crate::error::AppError::NotFoundError(
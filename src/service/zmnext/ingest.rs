@@ -46,6 +46,8 @@ use crate::entity::sea_orm_active_enums::{FrameType, Scheme, SynopsisStatus};
 use crate::entity::{event_synopsis, events, frames, monitors, states, storage};
 use crate::error::AppResult;
 use crate::repo;
+use crate::service::event_feed::{EventFeed, EventNotification, EventNotificationSource};
+use crate::service::notifications::Notifier;
 use crate::service::search::SearchService;
 use crate::streaming::source::{protocol, MonitorEvent, MonitorEventEnvelope};
 
@@ -118,6 +120,12 @@ pub struct EventIngestor {
     /// Optional NL/semantic search service for embed-at-ingest. `None` (or a
     /// disabled service) makes indexing a no-op.
     search: Option<Arc<SearchService>>,
+    /// Event firehose to announce opened/scored/closed events on. `None`
+    /// (tests) publishes nothing.
+    feed: Option<Arc<EventFeed>>,
+    /// Notification rules engine, told about the same transitions. `None`
+    /// (tests) notifies nothing.
+    notifier: Option<Arc<Notifier>>,
     open: HashMap<u32, OpenEvent>,
     dims: HashMap<u32, MonitorDims>,
     /// Cached active monitoring-state id. `Events.StateId` is NOT NULL with no
@@ -137,12 +145,32 @@ impl EventIngestor {
             config,
             synopsis,
             search,
+            feed: None,
+            notifier: None,
             open: HashMap::new(),
             dims: HashMap::new(),
             active_state_id: None,
         }
     }
 
+    /// Publish opened/scored/closed events on the firehose.
+    pub fn with_event_feed(mut self, feed: Arc<EventFeed>) -> Self {
+        self.feed = Some(feed);
+        self
+    }
+
+    /// Raise notification signals for opened/scored/closed events.
+    pub fn with_notifier(mut self, notifier: Arc<Notifier>) -> Self {
+        self.notifier = Some(notifier);
+        self
+    }
+
+    fn publish(&self, notification: EventNotification) {
+        if let Some(feed) = &self.feed {
+            feed.publish(notification);
+        }
+    }
+
     /// Resolve and cache the active monitoring-state id used for `Events.StateId`
     /// (NOT NULL, no DB default). Prefers the `States` row flagged active, else
     /// the lowest-id state, else `1` (ZoneMinder's implicit default state).
@@ -272,6 +300,12 @@ impl EventIngestor {
         .update(&*self.db)
         .await?;
 
+        self.publish(EventNotification::alarm_score(
+            monitor_id, event_id, score, when,
+        ));
+        if let Some(n) = &self.notifier {
+            n.score(monitor_id, event_id, score);
+        }
         Ok(())
     }
 
@@ -445,6 +479,15 @@ impl EventIngestor {
             detail.duration.unwrap_or(0.0)
         );
         self.open.remove(&monitor_id);
+        self.publish(EventNotification::event_close(
+            EventNotificationSource::Zmnext,
+            monitor_id,
+            event_id,
+            end,
+        ));
+        if let Some(n) = &self.notifier {
+            n.event_end(monitor_id, event_id);
+        }
         Ok(())
     }
 
@@ -583,7 +626,7 @@ impl EventIngestor {
         let model = events::ActiveModel {
             monitor_id: Set(monitor_id),
             name: Set(self.config.event_name.clone()),
-            cause: Set(cause),
+            cause: Set(cause.clone()),
             start_date_time: Set(Some(start)),
             state_id: Set(state_id),
             width: Set(dims.width),
@@ -603,6 +646,16 @@ impl EventIngestor {
         );
         self.open
             .insert(monitor_id, OpenEvent::new(model.id, monitor_id, start));
+        self.publish(EventNotification::event_open(
+            EventNotificationSource::Zmnext,
+            monitor_id,
+            model.id,
+            cause,
+            start,
+        ));
+        if let Some(n) = &self.notifier {
+            n.event_start(monitor_id, model.id);
+        }
         Ok(model.id)
     }
 
@@ -0,0 +1,612 @@
+//! Sub-range clips of recorded events, remuxed without transcoding.
+//!
+//! The event is demuxed with the ffmpeg libraries (never the `ffmpeg` binary).
+//! Video packets from the last keyframe at or before the clip start up to the
+//! clip end are fed, with timestamps rebased onto that keyframe, through the
+//! same in-Rust [`HlsSegmenter`] HLS-VOD packaging uses; its init segment
+//! followed by its media segments is a playable fragmented MP4. An AAC audio
+//! track comes along, rebased onto the same keyframe and trimmed to the same
+//! span; other audio codecs are left out. A Matroska clip is that file
+//! stream-copied once more by libavformat.
+//!
+//! A clip is cut in a temporary directory beside its destination and renamed
+//! into place, so a reader never sees half a file. Caching finished clips is
+//! up to the caller (see `service::clips`).
+
+use std::fs::File;
+use std::io::{BufWriter, Write};
+use std::path::{Path, PathBuf};
+use std::time::Duration;
+
+use serde::Deserialize;
+
+use crate::streaming::hls::segmenter::HlsSegmenter;
+use crate::streaming::hls::vod::read_extradata;
+use crate::streaming::probe::MediaInfo;
+use crate::streaming::source::media::{avcc_to_annexb, parse_extradata, AdtsWrapper};
+
+/// Target duration of each fragment in a clip.
+const FRAGMENT_SECONDS: u64 = 2;
+
+/// Container a clip is written in.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Deserialize)]
+#[serde(rename_all = "lowercase")]
+pub enum ClipFormat {
+    #[default]
+    Mp4,
+    Mkv,
+}
+
+impl ClipFormat {
+    pub fn extension(self) -> &'static str {
+        match self {
+            ClipFormat::Mp4 => "mp4",
+            ClipFormat::Mkv => "mkv",
+        }
+    }
+
+    pub fn content_type(self) -> &'static str {
+        match self {
+            ClipFormat::Mp4 => "video/mp4",
+            ClipFormat::Mkv => "video/x-matroska",
+        }
+    }
+}
+
+/// The part of an event a clip covers, as offsets from the event's start.
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct ClipRange {
+    pub start: Duration,
+    pub end: Duration,
+}
+
+impl ClipRange {
+    /// Validate `start`/`end` seconds against a recording `duration` seconds
+    /// long (`0` when unknown), clamping `end` to the recording, and refuse a
+    /// clip longer than `max_seconds` (`0` for no limit).
+    pub fn new(start: f64, end: f64, duration: f64, max_seconds: u64) -> Result<Self, String> {
+        if !start.is_finite() || !end.is_finite() || start < 0.0 {
+            return Err("`start` and `end` must be non-negative seconds".to_string());
+        }
+        if end <= start {
+            return Err("`end` must be after `start`".to_string());
+        }
+        let end = if duration > 0.0 {
+            if start >= duration {
+                return Err(format!(
+                    "`start` is past the end of the recording ({duration:.3}s)"
+                ));
+            }
+            end.min(duration)
+        } else {
+            end
+        };
+        if max_seconds > 0 && end - start > max_seconds as f64 {
+            return Err(format!(
+                "clips are limited to {max_seconds}s; ask for a shorter range"
+            ));
+        }
+        Ok(Self {
+            start: Duration::from_secs_f64(start),
+            end: Duration::from_secs_f64(end),
+        })
+    }
+}
+
+/// Cut `range` out of the recording at `source` in `format`, writing the clip
+/// to `dest`. Returns how far before the requested start the clip opens, on a
+/// keyframe.
+pub async fn cut(
+    source: PathBuf,
+    info: MediaInfo,
+    range: ClipRange,
+    format: ClipFormat,
+    dest: PathBuf,
+) -> Result<Duration, String> {
+    tokio::task::spawn_blocking(move || cut_blocking(&source, info, range, format, &dest))
+        .await
+        .map_err(|e| format!("clip task failed: {e}"))?
+}
+
+fn cut_blocking(
+    source: &Path,
+    info: MediaInfo,
+    range: ClipRange,
+    format: ClipFormat,
+    dest: &Path,
+) -> Result<Duration, String> {
+    let parent = dest.parent().unwrap_or(Path::new("."));
+    std::fs::create_dir_all(parent).map_err(|e| format!("create {parent:?}: {e}"))?;
+    let dir = tempfile::tempdir_in(parent).map_err(|e| format!("clip directory: {e}"))?;
+    let mp4 = dir.path().join("clip.mp4");
+    let opened = {
+        let file = File::create(&mp4).map_err(|e| format!("create {mp4:?}: {e}"))?;
+        let mut out = BufWriter::new(file);
+        let opened = write_fmp4(source, info, range, &mut out)?;
+        out.flush().map_err(|e| format!("write {mp4:?}: {e}"))?;
+        opened
+    };
+    let path = match format {
+        ClipFormat::Mp4 => mp4,
+        ClipFormat::Mkv => {
+            let mkv = dir.path().join("clip.mkv");
+            remux(&mp4, &mkv, "matroska")?;
+            mkv
+        }
+    };
+    std::fs::rename(&path, dest).map_err(|e| format!("rename {path:?}: {e}"))?;
+    Ok(range.start.saturating_sub(opened))
+}
+
+/// Write `range` of `source` to `out` as fragmented MP4. Returns the offset
+/// of the keyframe the clip opens on.
+fn write_fmp4(
+    source: &Path,
+    info: MediaInfo,
+    range: ClipRange,
+    out: &mut impl Write,
+) -> Result<Duration, String> {
+    use ffmpeg_next as ffmpeg;
+
+    let mut ictx = ffmpeg::format::input(&source).map_err(|e| format!("open {source:?}: {e}"))?;
+
+    let (stream_index, time_base, extradata, start_time) = {
+        let stream = ictx
+            .streams()
+            .best(ffmpeg::media::Type::Video)
+            .ok_or("no video stream")?;
+        (
+            stream.index(),
+            stream.time_base(),
+            read_extradata(&stream.parameters()),
+            stream.start_time(),
+        )
+    };
+    let num = i128::from(time_base.numerator());
+    let den = i128::from(time_base.denominator()).max(1);
+    let to_us = |ts: i64| (i128::from(ts) * num * 1_000_000 / den).max(0) as u64;
+    // Offsets are from the first frame, which need not be at pts 0.
+    let origin_us = if start_time == ffmpeg::ffi::AV_NOPTS_VALUE {
+        0
+    } else {
+        to_us(start_time)
+    };
+    let start_us = origin_us + range.start.as_micros() as u64;
+    let end_us = origin_us + range.end.as_micros() as u64;
+
+    // AAC audio is framed as ADTS, which is what the segmenter takes.
+    let audio = ictx
+        .streams()
+        .best(ffmpeg::media::Type::Audio)
+        .filter(|stream| stream.parameters().id() == ffmpeg::codec::Id::AAC)
+        .and_then(|stream| {
+            let adts = AdtsWrapper::from_asc(&read_extradata(&stream.parameters()))?;
+            Some((stream.index(), stream.time_base(), adts))
+        });
+
+    // Best effort: land on the keyframe before `start` instead of demuxing
+    // from the top. The gate below does not rely on it.
+    let _ = ictx.seek(start_us as i64, ..start_us as i64);
+
+    let mut seg = HlsSegmenter::new(0, Duration::from_secs(FRAGMENT_SECONDS));
+    seg.set_codec(info.codec);
+    seg.set_dimensions(info.width, info.height);
+    let (param_nals, length_size) = parse_extradata(&extradata, info.codec)?;
+    if param_nals.is_empty() {
+        return Err("no parameter sets in extradata".to_string());
+    }
+    for nal in &param_nals {
+        seg.process_nal(nal, 0, false);
+    }
+    if let Some(frame) = audio.as_ref().and_then(|(_, _, adts)| adts.wrap(&[])) {
+        // Describes the audio track for the init segment; the frame itself is
+        // dropped, as it comes before any keyframe.
+        seg.process_audio(&frame, 0);
+    }
+    let init = seg
+        .generate_init_segment()
+        .ok_or("failed to build init segment")?;
+    out.write_all(&init.data)
+        .map_err(|e| format!("write clip: {e}"))?;
+
+    let mut gate = KeyframeGate::new(start_us, end_us);
+    let mut held_audio = HeldAudio::default();
+    for (stream, packet) in ictx.packets() {
+        let Some(data) = packet.data() else { continue };
+        let pts = packet.pts().or_else(|| packet.dts()).unwrap_or(0);
+        if let Some((index, audio_tb, adts)) = &audio {
+            if stream.index() == *index {
+                let num = i128::from(audio_tb.numerator());
+                let den = i128::from(audio_tb.denominator()).max(1);
+                let ts_us = (i128::from(pts) * num * 1_000_000 / den).max(0) as u64;
+                if let Some(frame) = adts.wrap(data) {
+                    held_audio.push(ts_us, frame);
+                }
+                continue;
+            }
+        }
+        if stream.index() != stream_index {
+            continue;
+        }
+        let samples = gate.push(to_us(pts), packet.is_key(), data.to_vec());
+        write_samples(&mut seg, out, samples, length_size, &gate, &mut held_audio)?;
+        if gate.opened_at().is_none() {
+            if let Some(from) = gate.held_from() {
+                held_audio.discard_before(from);
+            }
+        }
+        if gate.is_done() {
+            break;
+        }
+    }
+    let last = gate.finish();
+    write_samples(&mut seg, out, last, length_size, &gate, &mut held_audio)?;
+    let opened = gate
+        .opened_at()
+        .ok_or("no keyframe in the requested range")?;
+    for (ts_us, frame) in held_audio.release(opened, end_us) {
+        seg.process_audio(&frame, ts_us);
+    }
+    if let Some(s) = seg.flush() {
+        out.write_all(&s.data)
+            .map_err(|e| format!("write clip: {e}"))?;
+    }
+    Ok(Duration::from_micros(opened.saturating_sub(origin_us)))
+}
+
+/// Feed AVCC samples through the segmenter, writing out finished fragments.
+/// The audio that plays before each sample goes in first, so it lands in the
+/// fragment it belongs to.
+fn write_samples(
+    seg: &mut HlsSegmenter,
+    out: &mut impl Write,
+    samples: Vec<(u64, bool, Vec<u8>)>,
+    length_size: usize,
+    gate: &KeyframeGate<Vec<u8>>,
+    audio: &mut HeldAudio<Vec<u8>>,
+) -> Result<(), String> {
+    for (ts_us, is_key, data) in samples {
+        if let Some(opened) = gate.opened_at() {
+            for (audio_us, frame) in audio.release(opened, opened + ts_us) {
+                seg.process_audio(&frame, audio_us);
+            }
+        }
+        for nal in avcc_to_annexb(&data, length_size) {
+            if let Some(s) = seg.process_nal(&nal, ts_us, is_key) {
+                out.write_all(&s.data)
+                    .map_err(|e| format!("write clip: {e}"))?;
+            }
+        }
+    }
+    Ok(())
+}
+
+/// Stream-copy the video and audio of `input` into a new `format` container
+/// at `output`.
+fn remux(input: &Path, output: &Path, format: &str) -> Result<(), String> {
+    use ffmpeg_next as ffmpeg;
+
+    let mut ictx = ffmpeg::format::input(&input).map_err(|e| format!("open {input:?}: {e}"))?;
+    let mut octx = ffmpeg::format::output_as(&output, format)
+        .map_err(|e| format!("create {output:?}: {e}"))?;
+
+    // Input stream index → (output stream index, input time base).
+    let mut mapping = Vec::new();
+    for ist in ictx.streams() {
+        let kind = ist.parameters().medium();
+        if kind != ffmpeg::media::Type::Video && kind != ffmpeg::media::Type::Audio {
+            continue;
+        }
+        let mut ost = octx
+            .add_stream(ffmpeg::encoder::find(ffmpeg::codec::Id::None))
+            .map_err(|e| format!("add stream: {e}"))?;
+        ost.set_parameters(ist.parameters());
+        // SAFETY: `ost` owns valid codec parameters; the MP4 sample entry tag
+        // means nothing to other containers, so clear it for the muxer to
+        // choose.
+        unsafe {
+            (*ost.parameters().as_mut_ptr()).codec_tag = 0;
+        }
+        mapping.push((ist.index(), ost.index(), ist.time_base()));
+    }
+    if mapping.is_empty() {
+        return Err("no video stream".to_string());
+    }
+    octx.write_header()
+        .map_err(|e| format!("write header: {e}"))?;
+
+    for (stream, mut packet) in ictx.packets() {
+        let Some(&(_, out_index, ist_tb)) = mapping.iter().find(|m| m.0 == stream.index()) else {
+            continue;
+        };
+        // The muxer settles each stream's time base in `write_header`.
+        let ost_tb = octx
+            .stream(out_index)
+            .ok_or("no output stream")?
+            .time_base();
+        packet.rescale_ts(ist_tb, ost_tb);
+        packet.set_position(-1);
+        packet.set_stream(out_index);
+        packet
+            .write_interleaved(&mut octx)
+            .map_err(|e| format!("write packet: {e}"))?;
+    }
+    octx.write_trailer()
+        .map_err(|e| format!("write trailer: {e}"))?;
+    Ok(())
+}
+
+/// Picks the samples of a clip out of a demuxed stream, in decode order.
+///
+/// Samples at or before `start` are held back a group of pictures at a time,
+/// so the clip can open on the last keyframe before `start`; if there is
+/// none, it opens on the first keyframe after. Everything from the opening
+/// keyframe up to `end` is let through with its timestamp rebased onto that
+/// keyframe.
+struct KeyframeGate<T> {
+    start_us: u64,
+    end_us: u64,
+    /// The group of pictures running at `start`, from its keyframe.
+    gop: Vec<(u64, bool, T)>,
+    /// Timestamp of the keyframe the clip opens on.
+    opened: Option<u64>,
+    done: bool,
+}
+
+impl<T> KeyframeGate<T> {
+    fn new(start_us: u64, end_us: u64) -> Self {
+        Self {
+            start_us,
+            end_us,
+            gop: Vec::new(),
+            opened: None,
+            done: false,
+        }
+    }
+
+    /// Offer one sample; returns those now known to be in the clip.
+    fn push(&mut self, ts_us: u64, is_key: bool, sample: T) -> Vec<(u64, bool, T)> {
+        let mut out = Vec::new();
+        if self.done {
+            return out;
+        }
+        if self.opened.is_none() {
+            if ts_us <= self.start_us {
+                if is_key {
+                    self.gop.clear();
+                }
+                if is_key || !self.gop.is_empty() {
+                    self.gop.push((ts_us, is_key, sample));
+                }
+                return out;
+            }
+            match self.gop.first() {
+                Some(&(first, ..)) => {
+                    self.opened = Some(first);
+                    out.append(&mut self.gop);
+                }
+                None if is_key => self.opened = Some(ts_us),
+                None => return out,
+            }
+        }
+        if ts_us >= self.end_us {
+            self.done = true;
+        } else {
+            out.push((ts_us, is_key, sample));
+        }
+        self.rebase(out)
+    }
+
+    /// The stream ended: release a group of pictures still held back.
+    fn finish(&mut self) -> Vec<(u64, bool, T)> {
+        if self.opened.is_some() || self.gop.is_empty() {
+            return Vec::new();
+        }
+        self.opened = Some(self.gop[0].0);
+        self.done = true;
+        let held = std::mem::take(&mut self.gop);
+        self.rebase(held)
+    }
+
+    fn rebase(&self, samples: Vec<(u64, bool, T)>) -> Vec<(u64, bool, T)> {
+        let origin = self.opened.unwrap_or(0);
+        samples
+            .into_iter()
+            .map(|(ts, key, sample)| (ts.saturating_sub(origin), key, sample))
+            .collect()
+    }
+
+    fn is_done(&self) -> bool {
+        self.done
+    }
+
+    /// Timestamp of the keyframe heading the group held back, if any.
+    fn held_from(&self) -> Option<u64> {
+        self.gop.first().map(|&(ts, ..)| ts)
+    }
+
+    fn opened_at(&self) -> Option<u64> {
+        self.opened
+    }
+}
+
+/// Audio frames demuxed ahead of the video they play under, in timestamp
+/// order.
+///
+/// Until the clip's opening keyframe is known, frames are kept from the
+/// keyframe heading the group [`KeyframeGate`] holds back. Once it is, they
+/// are let through with the same rebasing as the video, and only between the
+/// opening keyframe and the clip end.
+struct HeldAudio<T> {
+    frames: Vec<(u64, T)>,
+}
+
+impl<T> Default for HeldAudio<T> {
+    fn default() -> Self {
+        Self { frames: Vec::new() }
+    }
+}
+
+impl<T> HeldAudio<T> {
+    fn push(&mut self, ts_us: u64, frame: T) {
+        self.frames.push((ts_us, frame));
+    }
+
+    /// Drop frames before `ts_us`, which no clip opening there can use.
+    fn discard_before(&mut self, ts_us: u64) {
+        self.frames.retain(|&(ts, _)| ts >= ts_us);
+    }
+
+    /// Take the frames before `until_us` (absolute), keeping those at or after
+    /// the opening keyframe `opened_us`, rebased onto it.
+    fn release(&mut self, opened_us: u64, until_us: u64) -> Vec<(u64, T)> {
+        let n = self.frames.partition_point(|&(ts, _)| ts < until_us);
+        self.frames
+            .drain(..n)
+            .filter(|&(ts, _)| ts >= opened_us)
+            .map(|(ts, frame)| (ts - opened_us, frame))
+            .collect()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    /// Frames every 100ms, a keyframe every `gop` frames; the sample is the
+    /// frame number.
+    fn run(
+        start_ms: u64,
+        end_ms: u64,
+        gop: u64,
+        frames: u64,
+    ) -> (Vec<(u64, bool, u64)>, Option<u64>) {
+        let mut gate = KeyframeGate::new(start_ms * 1000, end_ms * 1000);
+        let mut out = Vec::new();
+        for n in 0..frames {
+            out.extend(gate.push(n * 100_000, n % gop == 0, n));
+            if gate.is_done() {
+                break;
+            }
+        }
+        out.extend(gate.finish());
+        (out, gate.opened_at())
+    }
+
+    #[test]
+    fn clip_opens_on_preceding_keyframe() {
+        // Keyframes at 0, 1.0s, 2.0s…; asking for 1.45s..2.25s.
+        let (out, opened) = run(1450, 2250, 10, 50);
+        assert_eq!(opened, Some(1_000_000));
+        let frames: Vec<u64> = out.iter().map(|s| s.2).collect();
+        assert_eq!(frames, (10..23).collect::<Vec<_>>());
+        assert_eq!(out[0], (0, true, 10), "rebased onto the keyframe");
+        assert_eq!(out[5].0, 500_000);
+        assert!(out[10].1, "the 2.0s keyframe is kept");
+    }
+
+    #[test]
+    fn clip_starting_on_a_keyframe_has_no_lead_in() {
+        let (out, opened) = run(2000, 2300, 10, 50);
+        assert_eq!(opened, Some(2_000_000));
+        assert_eq!(out.iter().map(|s| s.2).collect::<Vec<_>>(), [20, 21, 22]);
+    }
+
+    #[test]
+    fn clip_without_earlier_keyframe_waits_for_the_next() {
+        // The stream starts mid-GOP: no keyframe before 0.35s.
+        let mut gate = KeyframeGate::new(350_000, 900_000);
+        let mut out = Vec::new();
+        for n in 1..20u64 {
+            out.extend(gate.push(n * 100_000, n == 5, n));
+        }
+        assert_eq!(gate.opened_at(), Some(500_000));
+        assert_eq!(out.iter().map(|s| s.2).collect::<Vec<_>>(), [5, 6, 7, 8]);
+    }
+
+    #[test]
+    fn clip_past_the_last_sample_keeps_the_final_group() {
+        let (out, opened) = run(4500, 9000, 10, 43);
+        assert_eq!(opened, Some(4_000_000));
+        assert_eq!(out.iter().map(|s| s.2).collect::<Vec<_>>(), [40, 41, 42]);
+    }
+
+    #[test]
+    fn clip_with_no_keyframe_in_reach_is_empty() {
+        let mut gate = KeyframeGate::new(0, 500_000);
+        for n in 1..10u64 {
+            assert!(gate.push(n * 100_000, false, n).is_empty());
+        }
+        assert!(gate.finish().is_empty());
+        assert_eq!(gate.opened_at(), None);
+    }
+
+    #[test]
+    fn clip_range_is_validated_and_clamped() {
+        let r = ClipRange::new(5.0, 25.0, 600.0, 0).unwrap();
+        assert_eq!(r.start, Duration::from_secs(5));
+        assert_eq!(r.end, Duration::from_secs(25));
+        assert_eq!(
+            ClipRange::new(590.0, 700.0, 600.0, 0).unwrap().end,
+            Duration::from_secs(600)
+        );
+        assert_eq!(
+            ClipRange::new(0.0, 30.0, 0.0, 0).unwrap().end,
+            Duration::from_secs(30),
+            "unknown duration: end is kept"
+        );
+        assert!(ClipRange::new(10.0, 10.0, 600.0, 0).is_err());
+        assert!(ClipRange::new(-1.0, 10.0, 600.0, 0).is_err());
+        assert!(ClipRange::new(f64::NAN, 10.0, 600.0, 0).is_err());
+        assert!(ClipRange::new(600.0, 610.0, 600.0, 0).is_err());
+    }
+
+    #[test]
+    fn clip_length_is_capped_after_clamping() {
+        assert!(ClipRange::new(0.0, 120.0, 600.0, 60).is_err());
+        assert!(ClipRange::new(0.0, 60.0, 600.0, 60).is_ok());
+        // Asking past the end is fine when what remains fits.
+        assert!(ClipRange::new(570.0, 9000.0, 600.0, 60).is_ok());
+    }
+
+    #[test]
+    fn audio_is_rebased_onto_the_opening_keyframe() {
+        // AAC frames every ~21ms (1024 samples at 48 kHz), as microseconds.
+        let mut audio = HeldAudio::default();
+        for n in 0..100u64 {
+            audio.push(n * 21_333, n);
+        }
+        // A new group of pictures starts at 0.8s: nothing before it is needed.
+        audio.discard_before(800_000);
+        // The clip opens on that keyframe; release up to the video at 0.9s.
+        let first = audio.release(800_000, 900_000);
+        let frames: Vec<u64> = first.iter().map(|f| f.1).collect();
+        assert_eq!(frames, (38..43).collect::<Vec<_>>());
+        assert_eq!(first[0].0, 38 * 21_333 - 800_000);
+        // The rest of the clip, up to its end at 1.5s; later audio stays put.
+        let rest = audio.release(800_000, 1_500_000);
+        assert_eq!(rest.first().map(|f| f.1), Some(43));
+        assert_eq!(rest.last().map(|f| f.1), Some(70));
+        assert_eq!(audio.frames.first().map(|f| f.1), Some(71));
+    }
+
+    #[test]
+    fn audio_before_the_opening_keyframe_is_dropped() {
+        let mut audio = HeldAudio::default();
+        for n in 0..10u64 {
+            audio.push(n * 100_000, n);
+        }
+        // Opening on 0.5s: earlier frames are released and discarded.
+        let out = audio.release(500_000, 800_000);
+        assert_eq!(out, vec![(0, 5), (100_000, 6), (200_000, 7)]);
+    }
+
+    #[test]
+    fn formats_map_to_extension_and_type() {
+        assert_eq!(ClipFormat::default(), ClipFormat::Mp4);
+        assert_eq!(ClipFormat::Mkv.extension(), "mkv");
+        assert_eq!(ClipFormat::Mkv.content_type(), "video/x-matroska");
+        let f: ClipFormat = serde_json::from_str("\"mkv\"").unwrap();
+        assert_eq!(f, ClipFormat::Mkv);
+    }
+}
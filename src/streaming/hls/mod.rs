@@ -21,7 +21,7 @@ pub mod session;
 pub mod storage;
 pub mod vod;
 
-pub use playlist::{MasterPlaylist, MediaPlaylist, PlaylistGenerator};
+pub use playlist::{MasterPlaylist, MediaPlaylist, PlaylistGenerator, Rendition};
 pub use segmenter::{FMP4Segment, HlsSegmenter, InitSegment};
 pub use session::{HlsError, HlsSession, HlsSessionManager, HlsSessionStats};
-pub use storage::{HlsStorage, SegmentInfo};
+pub use storage::{HlsStorage, SegmentInfo, StreamKey};
@@ -4,9 +4,60 @@
 
 use std::fmt::Write;
 
+/// One of a monitor's streams, published as its own HLS rendition.
+///
+/// The main stream keeps the top of the monitor's `/hls/` path (and its
+/// storage directory), so single-rendition players are unaffected; every
+/// other rendition lives in a subdirectory named by [`Rendition::dir`].
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, PartialOrd, Ord)]
+pub enum Rendition {
+    /// The stream zmc captures (`Path`).
+    Main,
+    /// The camera's secondary stream (`SecondPath`).
+    Sub,
+}
+
+impl Rendition {
+    /// Subdirectory of the monitor's HLS path and storage; `None` for the
+    /// main stream.
+    pub fn dir(self) -> Option<&'static str> {
+        match self {
+            Rendition::Main => None,
+            Rendition::Sub => Some("sub"),
+        }
+    }
+
+    /// Parse a [`Rendition::dir`] back from a URL path segment.
+    pub fn from_dir(dir: &str) -> Option<Self> {
+        match dir {
+            "sub" => Some(Rendition::Sub),
+            _ => None,
+        }
+    }
+
+    /// The media playlist's path relative to the monitor's `/hls/`, without
+    /// the `.m3u8` extension (the [`QualityVariant::name`]).
+    pub fn playlist_name(self) -> String {
+        match self.dir() {
+            Some(dir) => format!("{dir}/live"),
+            None => "live".to_string(),
+        }
+    }
+
+    /// `BANDWIDTH` to advertise before any segment has been measured.
+    pub fn default_bandwidth(self) -> u64 {
+        match self {
+            Rendition::Main => 5_000_000,
+            Rendition::Sub => 1_000_000,
+        }
+    }
+}
+
 /// Quality variant for ABR streaming
 #[derive(Debug, Clone)]
 pub struct QualityVariant {
+    /// Media playlist path relative to the master's base URL, without the
+    /// `.m3u8` extension.
     pub name: String,
     pub bandwidth: u64,
     pub resolution: Option<(u32, u32)>,
@@ -311,21 +362,35 @@ impl PlaylistGenerator {
     pub fn generate_master_playlist(&self) -> MasterPlaylist {
         let mut master = MasterPlaylist::new(&self.base_url);
 
+        // Add default passthrough variant
+        master.add_variant(self.variant(
+            &Rendition::Main.playlist_name(),
+            Rendition::Main.default_bandwidth(),
+            None, // Will be detected from stream
+        ));
+
+        master
+    }
+
+    /// The master-playlist entry for this generator's stream.
+    pub fn variant(
+        &self,
+        name: &str,
+        bandwidth: u64,
+        resolution: Option<(u32, u32)>,
+    ) -> QualityVariant {
         let codecs = self
             .codec
             .clone()
             .unwrap_or_else(|| "avc1.42e01e".to_string());
 
-        // Add default passthrough variant
-        master.add_variant(QualityVariant {
-            name: "live".to_string(),
-            bandwidth: 5_000_000,
-            resolution: None, // Will be detected from stream
+        QualityVariant {
+            name: name.to_string(),
+            bandwidth,
+            resolution,
             codecs: Some(codecs),
             frame_rate: None,
-        });
-
-        master
+        }
     }
 
     /// Generate a media playlist
@@ -536,6 +601,25 @@ mod tests {
         );
     }
 
+    #[test]
+    fn test_rendition_paths() {
+        assert_eq!(Rendition::Main.playlist_name(), "live");
+        assert_eq!(Rendition::Sub.playlist_name(), "sub/live");
+        assert_eq!(Rendition::from_dir("sub"), Some(Rendition::Sub));
+        assert_eq!(Rendition::from_dir("live"), None);
+
+        let mut master = MasterPlaylist::new("/api/v3/live/1/hls");
+        let generator = PlaylistGenerator::new(1, "/api/v3/live/1/hls/sub", 4, 6);
+        master.add_variant(generator.variant(
+            &Rendition::Sub.playlist_name(),
+            800_000,
+            Some((640, 360)),
+        ));
+        let content = master.generate();
+        assert!(content.contains("BANDWIDTH=800000,RESOLUTION=640x360"));
+        assert!(content.contains("/api/v3/live/1/hls/sub/live.m3u8"));
+    }
+
     #[test]
     fn test_generator_dynamic_codec() {
         let mut generator = PlaylistGenerator::new(1, "/api/v3/live/1/hls", 4, 6);
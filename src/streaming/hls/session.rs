@@ -1,7 +1,9 @@
 //! HLS session management
 //!
 //! Manages active HLS streaming sessions, coordinating segmentation,
-//! storage, and playlist generation for each monitor.
+//! storage, and playlist generation for each monitor. A monitor with a
+//! substream has one session per [`Rendition`], each with its own segmenter
+//! and storage directory, published together in the master playlist.
 
 use std::collections::HashMap;
 use std::sync::{Arc, Mutex};
@@ -10,16 +12,19 @@ use tokio::sync::{broadcast, RwLock};
 use tokio::task::JoinHandle;
 use tracing::{info, warn};
 
-use super::playlist::{MediaPlaylist, PlaylistGenerator, SegmentRef};
+use super::playlist::{
+    MasterPlaylist, MediaPlaylist, PlaylistGenerator, QualityVariant, Rendition, SegmentRef,
+};
 use super::segmenter::{FMP4Segment, HlsSegmenter, InitSegment};
-use super::storage::{HlsStorage, StorageConfig, StorageError};
+use super::storage::{HlsStorage, StorageConfig, StorageError, StreamKey};
 use crate::configure::streaming::HlsConfig;
 use crate::streaming::source::VideoPacket;
 use crate::streaming::source::{AudioCodec, AudioPacket};
 
-/// HLS session for a single monitor
+/// HLS session for a single monitor rendition
 pub struct HlsSession {
     monitor_id: u32,
+    rendition: Rendition,
     segmenter: HlsSegmenter,
     playlist: MediaPlaylist,
     playlist_generator: PlaylistGenerator,
@@ -27,6 +32,11 @@ pub struct HlsSession {
     segment_count: u64,
     bytes_written: u64,
     viewer_count: usize,
+    /// Picture size from the current init segment.
+    resolution: Option<(u32, u32)>,
+    /// Highest bitrate of any segment so far (bits/s): the master playlist's
+    /// `BANDWIDTH`, which HLS defines as the peak segment bitrate.
+    peak_bandwidth: Option<u64>,
     /// Channel for notifying about new segments
     segment_tx: broadcast::Sender<u64>,
     /// Last time a client requested this session's playlist or media. Updated
@@ -64,6 +74,7 @@ impl HlsSession {
 
         Self {
             monitor_id,
+            rendition: Rendition::Main,
             segmenter,
             playlist,
             playlist_generator,
@@ -71,11 +82,19 @@ impl HlsSession {
             segment_count: 0,
             bytes_written: 0,
             viewer_count: 0,
+            resolution: None,
+            peak_bandwidth: None,
             segment_tx,
             last_access: Mutex::new(Instant::now()),
         }
     }
 
+    /// Make this session a rendition other than the main stream.
+    pub fn with_rendition(mut self, rendition: Rendition) -> Self {
+        self.rendition = rendition;
+        self
+    }
+
     /// Mark the session as freshly accessed (a client fetched its playlist or
     /// media). Resets the idle clock.
     fn touch(&self) {
@@ -130,6 +149,10 @@ impl HlsSession {
 
         // Update target duration before adding segment (HLS spec compliance)
         let duration_secs = segment.duration.as_secs_f64();
+        if duration_secs > 0.0 {
+            let bandwidth = (segment.data.len() as f64 * 8.0 / duration_secs).ceil() as u64;
+            self.peak_bandwidth = Some(self.peak_bandwidth.map_or(bandwidth, |p| p.max(bandwidth)));
+        }
         self.playlist.update_target_duration(duration_secs);
 
         // Add to playlist
@@ -153,6 +176,7 @@ impl HlsSession {
     /// Also extracts the codec profile-level-id from SPS for the master playlist.
     pub fn generate_init_segment(&mut self) -> Option<InitSegment> {
         let init = self.segmenter.generate_init_segment()?;
+        self.resolution = Some((init.width, init.height));
 
         // Extract profile-level-id from SPS and update master playlist codec
         if let Some(sps) = self.segmenter.sps() {
@@ -180,6 +204,16 @@ impl HlsSession {
         self.playlist.generate()
     }
 
+    /// This rendition's entry in the master playlist.
+    pub fn variant(&self) -> QualityVariant {
+        self.playlist_generator.variant(
+            &self.rendition.playlist_name(),
+            self.peak_bandwidth
+                .unwrap_or_else(|| self.rendition.default_bandwidth()),
+            self.resolution,
+        )
+    }
+
     /// Subscribe to segment notifications
     pub fn subscribe(&self) -> broadcast::Receiver<u64> {
         self.segment_tx.subscribe()
@@ -212,6 +246,11 @@ impl HlsSession {
     pub fn monitor_id(&self) -> u32 {
         self.monitor_id
     }
+
+    /// Which of the monitor's streams this session carries
+    pub fn rendition(&self) -> Rendition {
+        self.rendition
+    }
 }
 
 /// Session statistics
@@ -241,7 +280,7 @@ mod duration_serde {
 
 /// Manages all HLS sessions
 pub struct HlsSessionManager {
-    sessions: Arc<RwLock<HashMap<u32, HlsSession>>>,
+    sessions: Arc<RwLock<HashMap<StreamKey, HlsSession>>>,
     storage: Arc<HlsStorage>,
     config: HlsConfig,
     base_url: String,
@@ -273,30 +312,49 @@ impl HlsSessionManager {
 
     /// Start an HLS session for a monitor
     pub async fn start_session(&self, monitor_id: u32, has_audio: bool) -> Result<(), HlsError> {
+        self.start_stream(StreamKey::from(monitor_id), has_audio)
+            .await
+    }
+
+    /// Start the substream rendition of a monitor whose main session is
+    /// running. The substream carries video only.
+    pub async fn start_substream(&self, monitor_id: u32) -> Result<(), HlsError> {
+        self.start_stream(StreamKey::new(monitor_id, Rendition::Sub), false)
+            .await
+    }
+
+    async fn start_stream(&self, stream: StreamKey, has_audio: bool) -> Result<(), HlsError> {
+        let monitor_id = stream.monitor_id;
         let mut sessions = self.sessions.write().await;
 
-        if sessions.contains_key(&monitor_id) {
+        if sessions.contains_key(&stream) {
             return Err(HlsError::SessionExists(monitor_id));
         }
 
         // Initialize storage and clean any stale files from a previous session
-        self.storage.init_monitor(monitor_id).await?;
-        self.storage.clean_monitor(monitor_id).await?;
+        self.storage.init_monitor(stream).await?;
+        self.storage.clean_monitor(stream).await?;
 
         // Create session with per-monitor base URL including /hls/ prefix
-        let session_base_url = format!("{}/{}/hls", self.base_url, monitor_id);
-        let session = HlsSession::new(monitor_id, &self.config, &session_base_url, has_audio);
-        sessions.insert(monitor_id, session);
+        let mut session_base_url = format!("{}/{}/hls", self.base_url, monitor_id);
+        if let Some(dir) = stream.rendition.dir() {
+            session_base_url = format!("{session_base_url}/{dir}");
+        }
+        let session = HlsSession::new(monitor_id, &self.config, &session_base_url, has_audio)
+            .with_rendition(stream.rendition);
+        sessions.insert(stream, session);
 
-        info!("Started HLS session for monitor {}", monitor_id);
+        info!("Started HLS session for monitor {}", stream);
         Ok(())
     }
 
-    /// Stop an HLS session
+    /// Stop a monitor's HLS sessions, every rendition included
     pub async fn stop_session(&self, monitor_id: u32) -> Result<(), HlsError> {
         let mut sessions = self.sessions.write().await;
 
-        if sessions.remove(&monitor_id).is_none() {
+        let before = sessions.len();
+        sessions.retain(|stream, _| stream.monitor_id != monitor_id);
+        if sessions.len() == before {
             return Err(HlsError::SessionNotFound(monitor_id));
         }
 
@@ -310,17 +368,27 @@ impl HlsSessionManager {
     /// Check if a session exists
     pub async fn has_session(&self, monitor_id: u32) -> bool {
         let sessions = self.sessions.read().await;
-        sessions.contains_key(&monitor_id)
+        sessions.contains_key(&StreamKey::from(monitor_id))
     }
 
     /// Process a packet for a session
     pub async fn process_packet(&self, packet: &VideoPacket) -> Result<(), HlsError> {
+        self.process_rendition_packet(Rendition::Main, packet).await
+    }
+
+    /// Process a packet of one of the monitor's renditions
+    pub async fn process_rendition_packet(
+        &self,
+        rendition: Rendition,
+        packet: &VideoPacket,
+    ) -> Result<(), HlsError> {
         let monitor_id = packet.monitor_id;
+        let stream = StreamKey::new(monitor_id, rendition);
 
         let segment = {
             let mut sessions = self.sessions.write().await;
             let session = sessions
-                .get_mut(&monitor_id)
+                .get_mut(&stream)
                 .ok_or(HlsError::SessionNotFound(monitor_id))?;
 
             // Process the packet first — this extracts SPS/PPS from NAL units
@@ -329,9 +397,7 @@ impl HlsSessionManager {
             // Now try to generate init segment (requires SPS+PPS from above)
             if session.get_init_segment().is_none() {
                 if let Some(init) = session.generate_init_segment() {
-                    self.storage
-                        .store_init_segment(monitor_id, &init.data)
-                        .await?;
+                    self.storage.store_init_segment(stream, &init.data).await?;
                 }
             }
 
@@ -341,16 +407,11 @@ impl HlsSessionManager {
         // If a segment was produced, store it
         if let Some(segment) = segment {
             self.storage
-                .store_segment(
-                    monitor_id,
-                    segment.sequence,
-                    &segment.data,
-                    segment.duration,
-                )
+                .store_segment(stream, segment.sequence, &segment.data, segment.duration)
                 .await?;
 
             let mut sessions = self.sessions.write().await;
-            if let Some(session) = sessions.get_mut(&monitor_id) {
+            if let Some(session) = sessions.get_mut(&stream) {
                 session.on_segment_complete(&segment);
             }
         }
@@ -364,80 +425,117 @@ impl HlsSessionManager {
     pub async fn process_audio_packet(&self, packet: &AudioPacket) -> Result<(), HlsError> {
         let mut sessions = self.sessions.write().await;
         let session = sessions
-            .get_mut(&packet.monitor_id)
+            .get_mut(&StreamKey::from(packet.monitor_id))
             .ok_or(HlsError::SessionNotFound(packet.monitor_id))?;
         session.process_audio(packet);
         Ok(())
     }
 
-    /// Get the current playlist for a monitor
-    pub async fn get_playlist(&self, monitor_id: u32) -> Result<String, HlsError> {
+    /// Get the current playlist for a monitor's stream
+    pub async fn get_playlist(&self, stream: impl Into<StreamKey>) -> Result<String, HlsError> {
+        let stream = stream.into();
         let sessions = self.sessions.read().await;
         let session = sessions
-            .get(&monitor_id)
-            .ok_or(HlsError::SessionNotFound(monitor_id))?;
+            .get(&stream)
+            .ok_or(HlsError::SessionNotFound(stream.monitor_id))?;
 
         session.touch();
         Ok(session.generate_playlist())
     }
 
-    /// Get the master playlist for a monitor
+    /// Get the master playlist for a monitor: the main stream, then each
+    /// other rendition once its init segment (and so its resolution and
+    /// codec) is known — a substream the camera never delivers is not
+    /// offered to players.
     pub async fn get_master_playlist(&self, monitor_id: u32) -> Result<String, HlsError> {
         let sessions = self.sessions.read().await;
-        let session = sessions
-            .get(&monitor_id)
+        let main = sessions
+            .get(&StreamKey::from(monitor_id))
             .ok_or(HlsError::SessionNotFound(monitor_id))?;
 
-        session.touch();
-        let master = session.playlist_generator.generate_master_playlist();
+        main.touch();
+        let mut master = MasterPlaylist::new(&main.playlist_generator.base_url);
+        master.add_variant(main.variant());
+
+        let mut others: Vec<&HlsSession> = sessions
+            .iter()
+            .filter(|(stream, session)| {
+                stream.monitor_id == monitor_id
+                    && stream.rendition != Rendition::Main
+                    && session.get_init_segment().is_some()
+            })
+            .map(|(_, session)| session)
+            .collect();
+        others.sort_by_key(|session| session.rendition());
+        for session in others {
+            master.add_variant(session.variant());
+        }
         Ok(master.generate())
     }
 
-    /// Record that a client accessed this monitor's session (keeps it alive).
+    /// Record that a client accessed this stream's session (keeps it alive).
     /// A no-op if the session doesn't exist.
-    async fn note_access(&self, monitor_id: u32) {
+    async fn note_access(&self, stream: StreamKey) {
         let sessions = self.sessions.read().await;
-        if let Some(session) = sessions.get(&monitor_id) {
+        if let Some(session) = sessions.get(&stream) {
             session.touch();
         }
     }
 
     /// Get init segment data
-    pub async fn get_init_segment(&self, monitor_id: u32) -> Result<Vec<u8>, HlsError> {
-        self.note_access(monitor_id).await;
+    pub async fn get_init_segment(
+        &self,
+        stream: impl Into<StreamKey>,
+    ) -> Result<Vec<u8>, HlsError> {
+        let stream = stream.into();
+        self.note_access(stream).await;
         self.storage
-            .read_init_segment(monitor_id)
+            .read_init_segment(stream)
             .await
             .map_err(HlsError::from)
     }
 
     /// Get segment data
-    pub async fn get_segment(&self, monitor_id: u32, sequence: u64) -> Result<Vec<u8>, HlsError> {
-        self.note_access(monitor_id).await;
+    pub async fn get_segment(
+        &self,
+        stream: impl Into<StreamKey>,
+        sequence: u64,
+    ) -> Result<Vec<u8>, HlsError> {
+        let stream = stream.into();
+        self.note_access(stream).await;
         self.storage
-            .read_segment(monitor_id, sequence)
+            .read_segment(stream, sequence)
             .await
             .map_err(HlsError::from)
     }
 
     /// Monitors whose sessions have not been accessed for at least `idle_for`.
     /// Used by the coordinator's watchdog to reap sessions left behind by
-    /// viewers who navigated away. See REVIEW_FIXES_PLAN §3.2.
+    /// viewers who navigated away. See REVIEW_FIXES_PLAN §3.2. A monitor is
+    /// idle only when every one of its renditions is: a phone watching the
+    /// substream keeps the main stream's session alive too.
     pub async fn idle_sessions(&self, idle_for: Duration) -> Vec<u32> {
         let sessions = self.sessions.read().await;
-        sessions
-            .iter()
-            .filter(|(_, session)| session.idle_for() >= idle_for)
-            .map(|(id, _)| *id)
+        let mut idle: HashMap<u32, bool> = HashMap::new();
+        for (stream, session) in sessions.iter() {
+            *idle.entry(stream.monitor_id).or_insert(true) &= session.idle_for() >= idle_for;
+        }
+        idle.into_iter()
+            .filter(|(_, idle)| *idle)
+            .map(|(id, _)| id)
             .collect()
     }
 
     /// Subscribe to new segments for LL-HLS blocking reload
-    pub async fn subscribe(&self, monitor_id: u32) -> Result<broadcast::Receiver<u64>, HlsError> {
+    pub async fn subscribe(
+        &self,
+        stream: impl Into<StreamKey>,
+    ) -> Result<broadcast::Receiver<u64>, HlsError> {
+        let stream = stream.into();
         let sessions = self.sessions.read().await;
         let session = sessions
-            .get(&monitor_id)
-            .ok_or(HlsError::SessionNotFound(monitor_id))?;
+            .get(&stream)
+            .ok_or(HlsError::SessionNotFound(stream.monitor_id))?;
 
         Ok(session.subscribe())
     }
@@ -445,16 +543,18 @@ impl HlsSessionManager {
     /// Wait for a specific segment (for LL-HLS blocking reload)
     pub async fn wait_for_segment(
         &self,
-        monitor_id: u32,
+        stream: impl Into<StreamKey>,
         target_sequence: u64,
         timeout: Duration,
     ) -> Result<(), HlsError> {
-        let mut receiver = self.subscribe(monitor_id).await?;
+        let stream = stream.into();
+        let monitor_id = stream.monitor_id;
+        let mut receiver = self.subscribe(stream).await?;
 
         // Check if already available
         {
             let sessions = self.sessions.read().await;
-            if let Some(session) = sessions.get(&monitor_id) {
+            if let Some(session) = sessions.get(&stream) {
                 // A blocking LL-HLS poll is the clearest sign of an active
                 // viewer — keep the session off the idle-reap list.
                 session.touch();
@@ -486,7 +586,7 @@ impl HlsSessionManager {
     pub async fn get_stats(&self, monitor_id: u32) -> Result<HlsSessionStats, HlsError> {
         let sessions = self.sessions.read().await;
         let session = sessions
-            .get(&monitor_id)
+            .get(&StreamKey::from(monitor_id))
             .ok_or(HlsError::SessionNotFound(monitor_id))?;
 
         Ok(session.stats())
@@ -495,14 +595,18 @@ impl HlsSessionManager {
     /// Get all active sessions
     pub async fn list_sessions(&self) -> Vec<u32> {
         let sessions = self.sessions.read().await;
-        sessions.keys().copied().collect()
+        sessions
+            .keys()
+            .filter(|stream| stream.rendition == Rendition::Main)
+            .map(|stream| stream.monitor_id)
+            .collect()
     }
 
     /// Add viewer to session
     pub async fn add_viewer(&self, monitor_id: u32) -> Result<(), HlsError> {
         let mut sessions = self.sessions.write().await;
         let session = sessions
-            .get_mut(&monitor_id)
+            .get_mut(&StreamKey::from(monitor_id))
             .ok_or(HlsError::SessionNotFound(monitor_id))?;
         session.add_viewer();
         Ok(())
@@ -512,7 +616,7 @@ impl HlsSessionManager {
     pub async fn remove_viewer(&self, monitor_id: u32) -> Result<(), HlsError> {
         let mut sessions = self.sessions.write().await;
         let session = sessions
-            .get_mut(&monitor_id)
+            .get_mut(&StreamKey::from(monitor_id))
             .ok_or(HlsError::SessionNotFound(monitor_id))?;
         session.remove_viewer();
         Ok(())
@@ -580,6 +684,7 @@ mod tests {
             ll_hls_enabled: false,
             partial_segment_ms: 200,
             idle_timeout_seconds: 90,
+            substream_enabled: true,
             storage: crate::configure::streaming::HlsStorageConfig {
                 path: "/tmp/hls-test".to_string(),
                 retention_minutes: 5,
@@ -650,6 +755,66 @@ mod tests {
         assert_eq!(all, vec![1, 2]);
     }
 
+    #[tokio::test]
+    async fn test_substream_joins_master_once_initialised() {
+        let tmp = tempfile::TempDir::new().unwrap();
+        let mut config = test_config();
+        config.storage.path = tmp.path().to_string_lossy().into_owned();
+        let manager = HlsSessionManager::new(config, "/api/v3/live");
+
+        manager.start_session(3, false).await.unwrap();
+        manager.start_substream(3).await.unwrap();
+        assert!(matches!(
+            manager.start_substream(3).await,
+            Err(HlsError::SessionExists(3))
+        ));
+        assert_eq!(manager.list_sessions().await, vec![3]);
+
+        // No init segment yet: only the main stream is offered.
+        let master = manager.get_master_playlist(3).await.unwrap();
+        assert_eq!(master.matches("#EXT-X-STREAM-INF").count(), 1);
+        assert!(master.contains("/api/v3/live/3/hls/live.m3u8"));
+
+        // A 1280x720 High-profile SPS (from ffmpeg), a PPS and an IDR make
+        // the substream's init segment.
+        let sps = [
+            0, 0, 0, 1, 0x67, 0x64, 0x00, 0x1F, 0xAC, 0xD9, 0x40, 0x50, 0x05, 0xBB, 0x01, 0x10,
+            0x00, 0x00, 0x03, 0x00, 0x10, 0x00, 0x00, 0x03, 0x03, 0x20, 0xF1, 0x83, 0x19, 0x60,
+        ];
+        let pps = [0, 0, 0, 1, 0x68, 0xcb, 0x83, 0xcb, 0x20];
+        let idr = [0, 0, 0, 1, 0x65, 0x88, 0x84, 0x00, 0x33];
+        for (nal, keyframe) in [(&sps[..], false), (&pps[..], false), (&idr[..], true)] {
+            let packet = VideoPacket {
+                monitor_id: 3,
+                timestamp_us: 0,
+                data: nal.to_vec(),
+                is_keyframe: keyframe,
+                codec: crate::streaming::source::VideoCodec::H264,
+            };
+            manager
+                .process_rendition_packet(Rendition::Sub, &packet)
+                .await
+                .unwrap();
+        }
+
+        let master = manager.get_master_playlist(3).await.unwrap();
+        assert_eq!(master.matches("#EXT-X-STREAM-INF").count(), 2);
+        assert!(master.contains("BANDWIDTH=1000000,RESOLUTION=1280x720,CODECS=\"avc1.64001f\""));
+        assert!(master.contains("/api/v3/live/3/hls/sub/live.m3u8"));
+        assert!(manager
+            .get_init_segment(StreamKey::new(3, Rendition::Sub))
+            .await
+            .is_ok());
+        assert!(manager.get_init_segment(3).await.is_err());
+
+        // Stopping the monitor stops both renditions.
+        manager.stop_session(3).await.unwrap();
+        assert!(manager
+            .get_playlist(StreamKey::new(3, Rendition::Sub))
+            .await
+            .is_err());
+    }
+
     #[tokio::test]
     async fn test_get_playlist_refreshes_access() {
         // Fetching the playlist must reset the idle clock (a polling player
@@ -1,6 +1,8 @@
 //! HLS segment storage and lifecycle management
 //!
 //! Manages segment files on disk with automatic cleanup of old segments.
+//! Each monitor has a directory under the base path; renditions other than
+//! the main stream are stored in a subdirectory of it (see [`StreamKey`]).
 
 use std::collections::HashMap;
 use std::path::{Path, PathBuf};
@@ -10,6 +12,8 @@ use tokio::fs;
 use tokio::sync::RwLock;
 use tracing::{debug, info, warn};
 
+use super::playlist::Rendition;
+
 /// Information about a stored segment
 #[derive(Debug, Clone)]
 pub struct SegmentInfo {
@@ -41,7 +45,39 @@ impl Default for StorageConfig {
     }
 }
 
-/// Per-monitor storage state
+/// One stored stream: a monitor's rendition. A bare monitor id converts to
+/// its main rendition.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub struct StreamKey {
+    pub monitor_id: u32,
+    pub rendition: Rendition,
+}
+
+impl StreamKey {
+    pub fn new(monitor_id: u32, rendition: Rendition) -> Self {
+        Self {
+            monitor_id,
+            rendition,
+        }
+    }
+}
+
+impl From<u32> for StreamKey {
+    fn from(monitor_id: u32) -> Self {
+        Self::new(monitor_id, Rendition::Main)
+    }
+}
+
+impl std::fmt::Display for StreamKey {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self.rendition.dir() {
+            Some(dir) => write!(f, "{}/{dir}", self.monitor_id),
+            None => write!(f, "{}", self.monitor_id),
+        }
+    }
+}
+
+/// Per-stream storage state
 struct MonitorStorage {
     init_segment: Option<Vec<u8>>,
     segments: HashMap<u64, SegmentInfo>,
@@ -63,7 +99,7 @@ impl MonitorStorage {
 /// HLS segment storage manager
 pub struct HlsStorage {
     config: StorageConfig,
-    monitors: Arc<RwLock<HashMap<u32, MonitorStorage>>>,
+    monitors: Arc<RwLock<HashMap<StreamKey, MonitorStorage>>>,
 }
 
 impl HlsStorage {
@@ -80,9 +116,22 @@ impl HlsStorage {
         Self::new(StorageConfig::default())
     }
 
-    /// Initialize storage directory for a monitor
-    pub async fn init_monitor(&self, monitor_id: u32) -> Result<PathBuf, StorageError> {
-        let monitor_path = self.config.base_path.join(monitor_id.to_string());
+    /// Directory holding a stream's files.
+    fn stream_path(&self, stream: StreamKey) -> PathBuf {
+        let monitor_path = self.config.base_path.join(stream.monitor_id.to_string());
+        match stream.rendition.dir() {
+            Some(dir) => monitor_path.join(dir),
+            None => monitor_path,
+        }
+    }
+
+    /// Initialize storage directory for a monitor's stream
+    pub async fn init_monitor(
+        &self,
+        stream: impl Into<StreamKey>,
+    ) -> Result<PathBuf, StorageError> {
+        let stream = stream.into();
+        let monitor_path = self.stream_path(stream);
 
         // Create directory if it doesn't exist
         if !monitor_path.exists() {
@@ -94,15 +143,13 @@ impl HlsStorage {
                 })?;
             info!(
                 "Created HLS directory for monitor {}: {:?}",
-                monitor_id, monitor_path
+                stream, monitor_path
             );
         }
 
         // Initialize monitor storage state
         let mut monitors = self.monitors.write().await;
-        monitors
-            .entry(monitor_id)
-            .or_insert_with(MonitorStorage::new);
+        monitors.entry(stream).or_insert_with(MonitorStorage::new);
 
         Ok(monitor_path)
     }
@@ -110,10 +157,11 @@ impl HlsStorage {
     /// Store initialization segment
     pub async fn store_init_segment(
         &self,
-        monitor_id: u32,
+        stream: impl Into<StreamKey>,
         data: &[u8],
     ) -> Result<PathBuf, StorageError> {
-        let monitor_path = self.init_monitor(monitor_id).await?;
+        let stream = stream.into();
+        let monitor_path = self.init_monitor(stream).await?;
         let init_path = monitor_path.join("init.mp4");
 
         fs::write(&init_path, data)
@@ -125,35 +173,36 @@ impl HlsStorage {
 
         // Cache in memory
         let mut monitors = self.monitors.write().await;
-        if let Some(storage) = monitors.get_mut(&monitor_id) {
+        if let Some(storage) = monitors.get_mut(&stream) {
             storage.init_segment = Some(data.to_vec());
         }
 
         debug!(
             "Stored init segment for monitor {}: {} bytes",
-            monitor_id,
+            stream,
             data.len()
         );
         Ok(init_path)
     }
 
     /// Get cached init segment
-    pub async fn get_init_segment(&self, monitor_id: u32) -> Option<Vec<u8>> {
+    pub async fn get_init_segment(&self, stream: impl Into<StreamKey>) -> Option<Vec<u8>> {
         let monitors = self.monitors.read().await;
         monitors
-            .get(&monitor_id)
+            .get(&stream.into())
             .and_then(|s| s.init_segment.clone())
     }
 
     /// Store a media segment
     pub async fn store_segment(
         &self,
-        monitor_id: u32,
+        stream: impl Into<StreamKey>,
         sequence: u64,
         data: &[u8],
         duration: Duration,
     ) -> Result<SegmentInfo, StorageError> {
-        let monitor_path = self.init_monitor(monitor_id).await?;
+        let stream = stream.into();
+        let monitor_path = self.init_monitor(stream).await?;
         let segment_name = format!("segment_{:05}.m4s", sequence);
         let segment_path = monitor_path.join(&segment_name);
 
@@ -183,7 +232,7 @@ impl HlsStorage {
         let mut due_for_interval_cleanup = false;
         let evicted_paths: Vec<PathBuf> = {
             let mut monitors = self.monitors.write().await;
-            match monitors.get_mut(&monitor_id) {
+            match monitors.get_mut(&stream) {
                 Some(storage) => {
                     storage.segments.insert(sequence, info.clone());
 
@@ -216,13 +265,13 @@ impl HlsStorage {
         }
 
         if due_for_interval_cleanup {
-            self.cleanup_monitor(monitor_id).await;
+            self.cleanup_monitor(stream).await;
         }
 
         debug!(
             "Stored segment {} for monitor {}: {} bytes, {:.2}s",
             sequence,
-            monitor_id,
+            stream,
             data.len(),
             duration.as_secs_f64()
         );
@@ -233,13 +282,14 @@ impl HlsStorage {
     /// Store a partial segment (for LL-HLS)
     pub async fn store_partial_segment(
         &self,
-        monitor_id: u32,
+        stream: impl Into<StreamKey>,
         sequence: u64,
         part_index: u32,
         data: &[u8],
         duration: Duration,
     ) -> Result<SegmentInfo, StorageError> {
-        let monitor_path = self.init_monitor(monitor_id).await?;
+        let stream = stream.into();
+        let monitor_path = self.init_monitor(stream).await?;
         let segment_name = format!("segment_{:05}.{}.m4s", sequence, part_index);
         let segment_path = monitor_path.join(&segment_name);
 
@@ -261,7 +311,7 @@ impl HlsStorage {
 
         // Track partial segment
         let mut monitors = self.monitors.write().await;
-        if let Some(storage) = monitors.get_mut(&monitor_id) {
+        if let Some(storage) = monitors.get_mut(&stream) {
             storage
                 .partial_segments
                 .insert((sequence, part_index), info.clone());
@@ -271,7 +321,7 @@ impl HlsStorage {
             "Stored partial segment {}.{} for monitor {}: {} bytes",
             sequence,
             part_index,
-            monitor_id,
+            stream,
             data.len()
         );
 
@@ -279,26 +329,31 @@ impl HlsStorage {
     }
 
     /// Get segment info
-    pub async fn get_segment_info(&self, monitor_id: u32, sequence: u64) -> Option<SegmentInfo> {
+    pub async fn get_segment_info(
+        &self,
+        stream: impl Into<StreamKey>,
+        sequence: u64,
+    ) -> Option<SegmentInfo> {
         let monitors = self.monitors.read().await;
         monitors
-            .get(&monitor_id)
+            .get(&stream.into())
             .and_then(|s| s.segments.get(&sequence).cloned())
     }
 
     /// Read segment data from disk
     pub async fn read_segment(
         &self,
-        monitor_id: u32,
+        stream: impl Into<StreamKey>,
         sequence: u64,
     ) -> Result<Vec<u8>, StorageError> {
-        let info =
-            self.get_segment_info(monitor_id, sequence)
-                .await
-                .ok_or(StorageError::NotFound {
-                    monitor_id,
-                    sequence,
-                })?;
+        let stream = stream.into();
+        let info = self
+            .get_segment_info(stream, sequence)
+            .await
+            .ok_or(StorageError::NotFound {
+                monitor_id: stream.monitor_id,
+                sequence,
+            })?;
 
         fs::read(&info.path)
             .await
@@ -309,18 +364,18 @@ impl HlsStorage {
     }
 
     /// Read init segment from disk
-    pub async fn read_init_segment(&self, monitor_id: u32) -> Result<Vec<u8>, StorageError> {
+    pub async fn read_init_segment(
+        &self,
+        stream: impl Into<StreamKey>,
+    ) -> Result<Vec<u8>, StorageError> {
+        let stream = stream.into();
         // Try cache first
-        if let Some(data) = self.get_init_segment(monitor_id).await {
+        if let Some(data) = self.get_init_segment(stream).await {
             return Ok(data);
         }
 
         // Read from disk
-        let path = self
-            .config
-            .base_path
-            .join(monitor_id.to_string())
-            .join("init.mp4");
+        let path = self.stream_path(stream).join("init.mp4");
 
         fs::read(&path)
             .await
@@ -328,10 +383,10 @@ impl HlsStorage {
     }
 
     /// Get list of available segments for a monitor
-    pub async fn list_segments(&self, monitor_id: u32) -> Vec<SegmentInfo> {
+    pub async fn list_segments(&self, stream: impl Into<StreamKey>) -> Vec<SegmentInfo> {
         let monitors = self.monitors.read().await;
         monitors
-            .get(&monitor_id)
+            .get(&stream.into())
             .map(|s| {
                 let mut segments: Vec<_> = s.segments.values().cloned().collect();
                 segments.sort_by_key(|s| s.sequence);
@@ -341,23 +396,28 @@ impl HlsStorage {
     }
 
     /// Get the latest N segments
-    pub async fn get_latest_segments(&self, monitor_id: u32, count: usize) -> Vec<SegmentInfo> {
-        let mut segments = self.list_segments(monitor_id).await;
+    pub async fn get_latest_segments(
+        &self,
+        stream: impl Into<StreamKey>,
+        count: usize,
+    ) -> Vec<SegmentInfo> {
+        let mut segments = self.list_segments(stream).await;
         if segments.len() > count {
             segments.drain(0..segments.len() - count);
         }
         segments
     }
 
-    /// Cleanup old segments for a monitor
-    pub async fn cleanup_monitor(&self, monitor_id: u32) {
+    /// Cleanup old segments for a monitor's stream
+    pub async fn cleanup_monitor(&self, stream: impl Into<StreamKey>) {
+        let stream = stream.into();
         let now = Instant::now();
         let mut to_remove = Vec::new();
         let mut partial_to_remove = Vec::new();
 
         {
             let monitors = self.monitors.read().await;
-            if let Some(storage) = monitors.get(&monitor_id) {
+            if let Some(storage) = monitors.get(&stream) {
                 // Find expired segments
                 for (seq, info) in &storage.segments {
                     if now.duration_since(info.created_at) > self.config.retention {
@@ -391,7 +451,7 @@ impl HlsStorage {
         // Delete files and update state
         if !to_remove.is_empty() || !partial_to_remove.is_empty() {
             let mut monitors = self.monitors.write().await;
-            if let Some(storage) = monitors.get_mut(&monitor_id) {
+            if let Some(storage) = monitors.get_mut(&stream) {
                 for (seq, path) in &to_remove {
                     if let Err(e) = fs::remove_file(path).await {
                         warn!("Failed to remove segment file {:?}: {}", path, e);
@@ -414,29 +474,31 @@ impl HlsStorage {
             debug!(
                 "Cleaned up {} segments for monitor {}",
                 to_remove.len(),
-                monitor_id
+                stream
             );
         }
     }
 
     /// Cleanup all monitors
     pub async fn cleanup_all(&self) {
-        let monitor_ids: Vec<u32> = {
+        let streams: Vec<StreamKey> = {
             let monitors = self.monitors.read().await;
             monitors.keys().copied().collect()
         };
 
-        for monitor_id in monitor_ids {
-            self.cleanup_monitor(monitor_id).await;
+        for stream in streams {
+            self.cleanup_monitor(stream).await;
         }
     }
 
-    /// Clean stale segment files and init.mp4 from a monitor directory without removing the directory.
+    /// Clean stale segment files and init.mp4 from a stream's directory without removing the directory.
     ///
     /// Called at session start to prevent leftover files from a previous session
     /// from misleading clients (e.g., a stale `init.mp4` with 0 packets processed).
-    pub async fn clean_monitor(&self, monitor_id: u32) -> Result<(), StorageError> {
-        let monitor_path = self.config.base_path.join(monitor_id.to_string());
+    /// Other renditions' subdirectories are left alone.
+    pub async fn clean_monitor(&self, stream: impl Into<StreamKey>) -> Result<(), StorageError> {
+        let stream = stream.into();
+        let monitor_path = self.stream_path(stream);
 
         if !monitor_path.exists() {
             return Ok(());
@@ -462,19 +524,19 @@ impl HlsStorage {
             }
         }
 
-        // Clear in-memory state for this monitor
+        // Clear in-memory state for this stream
         let mut monitors = self.monitors.write().await;
-        if let Some(storage) = monitors.get_mut(&monitor_id) {
+        if let Some(storage) = monitors.get_mut(&stream) {
             storage.init_segment = None;
             storage.segments.clear();
             storage.partial_segments.clear();
         }
 
-        debug!("Cleaned stale HLS files for monitor {}", monitor_id);
+        debug!("Cleaned stale HLS files for monitor {}", stream);
         Ok(())
     }
 
-    /// Remove all data for a monitor
+    /// Remove all data for a monitor, every rendition included
     pub async fn remove_monitor(&self, monitor_id: u32) -> Result<(), StorageError> {
         let monitor_path = self.config.base_path.join(monitor_id.to_string());
 
@@ -488,16 +550,16 @@ impl HlsStorage {
         }
 
         let mut monitors = self.monitors.write().await;
-        monitors.remove(&monitor_id);
+        monitors.retain(|stream, _| stream.monitor_id != monitor_id);
 
         info!("Removed HLS storage for monitor {}", monitor_id);
         Ok(())
     }
 
     /// Get storage statistics
-    pub async fn get_stats(&self, monitor_id: u32) -> Option<StorageStats> {
+    pub async fn get_stats(&self, stream: impl Into<StreamKey>) -> Option<StorageStats> {
         let monitors = self.monitors.read().await;
-        monitors.get(&monitor_id).map(|storage| {
+        monitors.get(&stream.into()).map(|storage| {
             let total_size: u64 = storage.segments.values().map(|s| s.size).sum();
             let total_duration: Duration = storage.segments.values().map(|s| s.duration).sum();
 
@@ -746,6 +808,34 @@ mod tests {
         storage.clean_monitor(999).await.unwrap();
     }
 
+    #[tokio::test]
+    async fn test_renditions_are_stored_apart() {
+        let (storage, _temp) = create_test_storage().await;
+        let sub = StreamKey::new(1, Rendition::Sub);
+
+        storage.store_init_segment(1, b"main init").await.unwrap();
+        let sub_init = storage.store_init_segment(sub, b"sub init").await.unwrap();
+        assert!(sub_init.ends_with("1/sub/init.mp4"));
+        storage
+            .store_segment(sub, 0, b"sub0", Duration::from_secs(4))
+            .await
+            .unwrap();
+
+        assert_eq!(storage.read_init_segment(1).await.unwrap(), b"main init");
+        assert_eq!(storage.read_init_segment(sub).await.unwrap(), b"sub init");
+        assert!(storage.read_segment(1, 0).await.is_err());
+        assert_eq!(storage.read_segment(sub, 0).await.unwrap(), b"sub0");
+
+        // Cleaning the main stream leaves the substream alone...
+        storage.clean_monitor(1).await.unwrap();
+        assert_eq!(storage.read_segment(sub, 0).await.unwrap(), b"sub0");
+
+        // ...while removing the monitor takes both.
+        storage.remove_monitor(1).await.unwrap();
+        assert!(storage.get_init_segment(sub).await.is_none());
+        assert!(!sub_init.exists());
+    }
+
     #[tokio::test]
     async fn test_remove_monitor() {
         let (storage, _temp) = create_test_storage().await;
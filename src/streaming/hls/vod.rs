@@ -7,11 +7,17 @@
 //!
 //! Results are cached per event id (a recorded file is immutable). Generation
 //! is single-flighted so concurrent requests don't re-segment the same event.
+//!
+//! [`build_range_playlist`] stitches several events into one time-range
+//! playlist for a monitor. It only needs each event's segment durations, which
+//! [`get_or_index`] reads from packet timestamps without packaging anything;
+//! the segments themselves are still fetched from each event's own routes.
 
 use std::path::{Path, PathBuf};
 use std::sync::{Arc, OnceLock};
 use std::time::Duration;
 
+use chrono::{DateTime, SecondsFormat, Utc};
 use dashmap::DashMap;
 use tokio::sync::Mutex;
 
@@ -28,14 +34,30 @@ const VOD_SEGMENT_SECONDS: u64 = 4;
 pub struct VodAssets {
     pub init: Vec<u8>,
     pub segments: Vec<Vec<u8>>,
+    /// Duration of each of `segments`, in order.
+    pub durations: Vec<Duration>,
     pub playlist: String,
 }
 
+/// One event's part of a time-range playlist.
+pub struct RangeEntry {
+    pub event_id: u64,
+    /// When the event's first segment starts.
+    pub start: DateTime<Utc>,
+    /// Duration of each of the event's segments, as [`get_or_index`] gives.
+    pub durations: Arc<Vec<Duration>>,
+}
+
 fn cache() -> &'static DashMap<u64, Arc<VodAssets>> {
     static CACHE: OnceLock<DashMap<u64, Arc<VodAssets>>> = OnceLock::new();
     CACHE.get_or_init(DashMap::new)
 }
 
+fn index_cache() -> &'static DashMap<u64, Arc<Vec<Duration>>> {
+    static CACHE: OnceLock<DashMap<u64, Arc<Vec<Duration>>>> = OnceLock::new();
+    CACHE.get_or_init(DashMap::new)
+}
+
 fn locks() -> &'static DashMap<u64, Arc<Mutex<()>>> {
     static LOCKS: OnceLock<DashMap<u64, Arc<Mutex<()>>>> = OnceLock::new();
     LOCKS.get_or_init(DashMap::new)
@@ -70,6 +92,104 @@ pub async fn get_or_build(
     Ok(assets)
 }
 
+/// Return the durations of an event's VOD segments: those of its packaged
+/// assets when cached, otherwise read from the file's packet timestamps and
+/// keyframe flags — no payload is read or converted — and cached in turn.
+/// They match what [`get_or_build`] produces, so segment indices line up.
+pub async fn get_or_index(
+    event_id: u64,
+    video_path: PathBuf,
+) -> Result<Arc<Vec<Duration>>, String> {
+    if let Some(a) = cache().get(&event_id) {
+        return Ok(Arc::new(a.durations.clone()));
+    }
+    if let Some(d) = index_cache().get(&event_id) {
+        return Ok(d.clone());
+    }
+
+    let target = Duration::from_secs(VOD_SEGMENT_SECONDS);
+    let durations = Arc::new(
+        tokio::task::spawn_blocking(move || index_blocking(&video_path, target))
+            .await
+            .map_err(|e| format!("vod index task failed: {e}"))??,
+    );
+    index_cache().insert(event_id, durations.clone());
+    Ok(durations)
+}
+
+fn index_blocking(path: &Path, target: Duration) -> Result<Vec<Duration>, String> {
+    use ffmpeg_next as ffmpeg;
+
+    let mut ictx = ffmpeg::format::input(&path).map_err(|e| format!("open {path:?}: {e}"))?;
+    let (stream_index, time_base) = {
+        let stream = ictx
+            .streams()
+            .best(ffmpeg::media::Type::Video)
+            .ok_or("no video stream")?;
+        (stream.index(), stream.time_base())
+    };
+    let num = i128::from(time_base.numerator());
+    let den = i128::from(time_base.denominator()).max(1);
+
+    let frames = ictx.packets().filter_map(|(stream, packet)| {
+        if stream.index() != stream_index || packet.size() == 0 {
+            return None;
+        }
+        let pts = packet.pts().or_else(|| packet.dts()).unwrap_or(0).max(0);
+        Some((
+            (i128::from(pts) * num * 1_000_000 / den) as u64,
+            packet.is_key(),
+        ))
+    });
+    let durations = segment_durations(frames, target);
+    if durations.is_empty() {
+        return Err("no segments produced".to_string());
+    }
+    Ok(durations)
+}
+
+/// The segment durations [`HlsSegmenter`] produces for frames with these
+/// timestamps (µs) and keyframe flags: frames before the first keyframe are
+/// dropped, a segment closes on a keyframe once it holds at least two frames
+/// and `target`, and the last one ends a frame interval after its last frame.
+fn segment_durations(
+    frames: impl IntoIterator<Item = (u64, bool)>,
+    target: Duration,
+) -> Vec<Duration> {
+    let mut durations = Vec::new();
+    let mut base = None;
+    let mut segment_start = 0u64;
+    // Frames of the open segment before the current one, and the timestamps
+    // of its last two.
+    let mut count = 0usize;
+    let mut last = [0u64; 2];
+    for (ts, is_key) in frames {
+        let base = match base {
+            Some(b) => b,
+            None if is_key => *base.insert(ts),
+            None => continue,
+        };
+        let ts = ts.saturating_sub(base);
+        if is_key && count >= 2 && Duration::from_micros(ts.saturating_sub(segment_start)) >= target
+        {
+            durations.push(Duration::from_micros(ts.saturating_sub(segment_start)));
+            segment_start = ts;
+            count = 0;
+        }
+        last = [last[1], ts];
+        count += 1;
+    }
+    if count > 0 {
+        let frame = if count >= 2 {
+            last[1].saturating_sub(last[0]).max(1)
+        } else {
+            1_000_000 / 30
+        };
+        durations.push(Duration::from_micros(last[1] + frame - segment_start));
+    }
+    durations
+}
+
 fn package_blocking(path: &Path, info: MediaInfo, target: Duration) -> Result<VodAssets, String> {
     use ffmpeg_next as ffmpeg;
 
@@ -134,13 +254,14 @@ fn package_blocking(path: &Path, info: MediaInfo, target: Duration) -> Result<Vo
     let playlist = build_playlist(&segments, target);
     Ok(VodAssets {
         init: init.data,
+        durations: segments.iter().map(|s| s.duration).collect(),
         segments: segments.into_iter().map(|s| s.data).collect(),
         playlist,
     })
 }
 
 /// Read a codec's extradata (the avcC/hvcC box) from ffmpeg parameters.
-fn read_extradata(params: &ffmpeg_next::codec::Parameters) -> Vec<u8> {
+pub(crate) fn read_extradata(params: &ffmpeg_next::codec::Parameters) -> Vec<u8> {
     // SAFETY: `as_ptr()` yields a valid AVCodecParameters for the lifetime of
     // `params`; we only read the extradata slice it points at.
     unsafe {
@@ -180,6 +301,70 @@ fn build_playlist(segments: &[FMP4Segment], target: Duration) -> String {
     p
 }
 
+/// Build one VOD playlist over consecutive events, keeping only the segments
+/// that overlap `[from, to)`.
+///
+/// Each event opens with `#EXT-X-DISCONTINUITY` (after the first), its own
+/// `#EXT-X-MAP` and an `#EXT-X-PROGRAM-DATE-TIME` for its first kept segment,
+/// so a player can place gaps between recordings on a wall-clock timeline.
+/// URIs are relative to `/api/v3/monitors/{id}/playback.m3u8` and point at
+/// each event's own init and segment routes. `None` when no segment falls in
+/// the range.
+pub fn build_range_playlist(
+    entries: &[RangeEntry],
+    from: DateTime<Utc>,
+    to: DateTime<Utc>,
+) -> Option<String> {
+    let mut body = String::new();
+    let mut longest = Duration::ZERO;
+    let mut first = true;
+    for entry in entries {
+        let mut at = entry.start;
+        let mut opened = false;
+        for (i, duration) in entry.durations.iter().enumerate() {
+            let start = at;
+            at += chrono::Duration::from_std(*duration).unwrap_or_default();
+            if at <= from || start >= to {
+                continue;
+            }
+            if !opened {
+                if !first {
+                    body.push_str("#EXT-X-DISCONTINUITY\n");
+                }
+                body.push_str(&format!(
+                    "#EXT-X-MAP:URI=\"../../events/{}/stream/init.mp4\"\n",
+                    entry.event_id
+                ));
+                body.push_str(&format!(
+                    "#EXT-X-PROGRAM-DATE-TIME:{}\n",
+                    start.to_rfc3339_opts(SecondsFormat::Millis, true)
+                ));
+                opened = true;
+                first = false;
+            }
+            longest = longest.max(*duration);
+            body.push_str(&format!(
+                "#EXTINF:{:.3},\n../../events/{}/stream/segment/{i}\n",
+                duration.as_secs_f64(),
+                entry.event_id
+            ));
+        }
+    }
+    if first {
+        return None;
+    }
+
+    let target_secs = longest.as_secs_f64().ceil().max(1.0) as u64;
+    let mut p = String::with_capacity(128 + body.len());
+    p.push_str("#EXTM3U\n#EXT-X-VERSION:7\n");
+    p.push_str(&format!("#EXT-X-TARGETDURATION:{target_secs}\n"));
+    p.push_str("#EXT-X-PLAYLIST-TYPE:VOD\n");
+    p.push_str("#EXT-X-INDEPENDENT-SEGMENTS\n");
+    p.push_str(&body);
+    p.push_str("#EXT-X-ENDLIST\n");
+    Some(p)
+}
+
 #[cfg(test)]
 mod tests {
     use super::*;
@@ -322,6 +507,16 @@ mod tests {
             "VOD playlist must terminate with ENDLIST"
         );
 
+        // The index a range playlist uses matches the packaged segments.
+        let index = index_blocking(&src, Duration::from_millis(500)).expect("index");
+        assert_eq!(index.len(), assets.durations.len(), "segment count");
+        for (i, (a, b)) in index.iter().zip(&assets.durations).enumerate() {
+            assert!(
+                a.abs_diff(*b) < Duration::from_millis(1),
+                "segment {i}: indexed {a:?}, packaged {b:?}"
+            );
+        }
+
         // Source packet count (frames) for parity.
         let (src_count, src_id) = count_video_packets(&src).expect("demux source");
         assert!(src_count > 0, "source must contain packets");
@@ -388,4 +583,72 @@ mod tests {
         // Exactly one URI per segment.
         assert_eq!(p.matches("\nsegment/").count(), 3);
     }
+
+    #[test]
+    fn segment_durations_follow_the_segmenter() {
+        let ms = |n: u64| n * 1_000;
+        // 10 fps, a keyframe every 2 s, leading non-keyframes dropped.
+        let frames = (0..55u64).map(|i| (ms(1_000 + i * 100), i >= 5 && (i - 5) % 20 == 0));
+        let d = segment_durations(frames, Duration::from_secs(4));
+        // Keyframes at 0 s, 2 s and 4 s: the first cut is at 4 s, then the
+        // last 0.9 s plus one frame interval.
+        assert_eq!(d, [Duration::from_secs(4), Duration::from_secs(1)]);
+
+        // A single frame still makes a segment, a frame (1/30 s) long.
+        let d = segment_durations([(ms(500), true)], Duration::from_secs(4));
+        assert_eq!(d, [Duration::from_micros(33_333)]);
+        assert!(segment_durations([(0, false)], Duration::from_secs(4)).is_empty());
+    }
+
+    fn entry(event_id: u64, start: DateTime<Utc>, secs: &[u64]) -> RangeEntry {
+        RangeEntry {
+            event_id,
+            start,
+            durations: Arc::new(secs.iter().map(|s| Duration::from_secs(*s)).collect()),
+        }
+    }
+
+    #[test]
+    fn range_playlist_stitches_events_with_discontinuities() {
+        let t0 = DateTime::from_timestamp(1_760_000_000, 0).unwrap();
+        let entries = [
+            entry(7, t0, &[4, 4, 2]),
+            entry(9, t0 + chrono::Duration::seconds(60), &[4, 3]),
+        ];
+        let p = build_range_playlist(&entries, t0, t0 + chrono::Duration::hours(1))
+            .expect("segments in range");
+
+        assert!(p.starts_with("#EXTM3U"));
+        assert!(p.contains("#EXT-X-PLAYLIST-TYPE:VOD"));
+        assert!(p.contains("#EXT-X-TARGETDURATION:4"));
+        assert_eq!(p.matches("#EXT-X-DISCONTINUITY\n").count(), 1);
+        assert_eq!(p.matches("#EXT-X-MAP:").count(), 2);
+        assert!(p.contains("#EXT-X-MAP:URI=\"../../events/9/stream/init.mp4\""));
+        assert!(p.contains("#EXT-X-PROGRAM-DATE-TIME:2025-10-09T08:53:20.000Z"));
+        assert!(p.contains("#EXT-X-PROGRAM-DATE-TIME:2025-10-09T08:54:20.000Z"));
+        assert!(p.contains("#EXTINF:2.000,\n../../events/7/stream/segment/2\n"));
+        assert!(p.trim_end().ends_with("#EXT-X-ENDLIST"));
+        // The discontinuity sits between the two events, not before the first.
+        let cut = p.find("#EXT-X-DISCONTINUITY").unwrap();
+        assert!(p[..cut].contains("events/7/") && !p[..cut].contains("events/9/"));
+    }
+
+    #[test]
+    fn range_playlist_keeps_only_overlapping_segments() {
+        let t0 = DateTime::from_timestamp(1_760_000_000, 0).unwrap();
+        let entries = [entry(7, t0, &[4, 4, 4, 4])];
+        let from = t0 + chrono::Duration::seconds(5);
+        let to = t0 + chrono::Duration::seconds(9);
+        let p = build_range_playlist(&entries, from, to).unwrap();
+
+        assert_eq!(p.matches("#EXTINF").count(), 2);
+        assert!(p.contains("stream/segment/1\n") && p.contains("stream/segment/2\n"));
+        // The date is that of the first kept segment, not of the event.
+        assert!(p.contains("#EXT-X-PROGRAM-DATE-TIME:2025-10-09T08:53:24.000Z"));
+
+        let later = t0 + chrono::Duration::hours(1);
+        assert!(
+            build_range_playlist(&entries, later, later + chrono::Duration::hours(1)).is_none()
+        );
+    }
 }
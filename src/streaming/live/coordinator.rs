@@ -7,13 +7,13 @@ use std::collections::HashMap;
 use std::sync::Arc;
 use std::time::Duration;
 
-use tokio::sync::RwLock;
+use tokio::sync::{mpsc, RwLock};
 use tokio::task::JoinHandle;
 use tracing::{debug, info, warn};
 
-use crate::streaming::hls::HlsSessionManager;
+use crate::streaming::hls::{HlsSessionManager, Rendition};
 use crate::streaming::source::router::ReaderHealth;
-use crate::streaming::source::SourceRouter;
+use crate::streaming::source::{rtsp_pull, SourceRouter, SubstreamSource};
 
 /// Status of a live streaming session
 #[derive(Debug, Clone, Copy, PartialEq, Eq, serde::Serialize)]
@@ -43,6 +43,8 @@ pub struct LiveSessionStats {
 pub struct LiveStreamConfig {
     pub enable_hls: bool,
     pub enable_webrtc: bool,
+    /// Camera substream to publish as a second HLS rendition
+    pub substream: Option<SubstreamSource>,
 }
 
 impl Default for LiveStreamConfig {
@@ -50,6 +52,7 @@ impl Default for LiveStreamConfig {
         Self {
             enable_hls: true,
             enable_webrtc: false, // Not implemented yet
+            substream: None,
         }
     }
 }
@@ -60,6 +63,8 @@ struct LiveSession {
     status: SessionStatus,
     config: LiveStreamConfig,
     task_handle: Option<JoinHandle<()>>,
+    /// Pulls the substream into its HLS rendition
+    substream_handle: Option<JoinHandle<()>>,
     started_at: std::time::Instant,
     packets_processed: u64,
     errors: u64,
@@ -72,6 +77,7 @@ impl LiveSession {
             status: SessionStatus::Starting,
             config,
             task_handle: None,
+            substream_handle: None,
             started_at: std::time::Instant::now(),
             packets_processed: 0,
             errors: 0,
@@ -118,6 +124,9 @@ pub enum CoordinatorError {
 /// session. The HELLOs arrive immediately after connect when zmc is up.
 const STREAM_INFO_TIMEOUT: Duration = Duration::from_secs(5);
 
+/// Substream packets buffered between the RTSP pull and the HLS segmenter.
+const SUBSTREAM_CHANNEL_CAPACITY: usize = 256;
+
 /// Coordinates live streaming from per-monitor stream sockets to output
 /// protocols
 pub struct LiveStreamCoordinator {
@@ -267,15 +276,36 @@ impl LiveStreamCoordinator {
             sessions.insert(monitor_id, session);
         }
 
+        // The substream is best-effort: the main rendition plays without it.
+        let mut substream_handle = None;
+        if let (true, Some(substream), Some(hls_manager)) =
+            (config.enable_hls, &config.substream, &self.hls_manager)
+        {
+            match hls_manager.start_substream(monitor_id).await {
+                Ok(()) => {
+                    substream_handle = Some(Self::start_substream_task(
+                        monitor_id,
+                        substream.clone(),
+                        Arc::clone(hls_manager),
+                    ))
+                }
+                Err(e) => warn!(
+                    "Monitor {}: not publishing the substream: {}",
+                    monitor_id, e
+                ),
+            }
+        }
+
         // Start processing task with pre-subscribed receivers
         let task_handle =
             self.start_processing_task(monitor_id, config, video_rx, audio_rx, reader_health_rx);
 
-        // Store the task handle
+        // Store the task handles
         {
             let mut sessions = self.sessions.write().await;
             if let Some(session) = sessions.get_mut(&monitor_id) {
                 session.task_handle = Some(task_handle);
+                session.substream_handle = substream_handle;
             }
         }
 
@@ -283,6 +313,29 @@ impl LiveStreamCoordinator {
         Ok(())
     }
 
+    /// Pull the monitor's substream from the camera into its HLS rendition.
+    /// Runs until aborted; the pull reconnects on its own.
+    fn start_substream_task(
+        monitor_id: u32,
+        source: SubstreamSource,
+        hls_manager: Arc<HlsSessionManager>,
+    ) -> JoinHandle<()> {
+        tokio::spawn(async move {
+            let (tx, mut rx) = mpsc::channel(SUBSTREAM_CHANNEL_CAPACITY);
+            let forward = async {
+                while let Some(packet) = rx.recv().await {
+                    if let Err(e) = hls_manager
+                        .process_rendition_packet(Rendition::Sub, &packet)
+                        .await
+                    {
+                        debug!("Monitor {}: substream packet dropped: {}", monitor_id, e);
+                    }
+                }
+            };
+            tokio::join!(rtsp_pull::run(monitor_id, source, tx), forward);
+        })
+    }
+
     /// Start the packet processing task with a pre-subscribed receiver.
     ///
     /// The receiver must be created BEFORE the stream-socket reader starts to guarantee
@@ -509,10 +562,13 @@ impl LiveStreamCoordinator {
 
         session.status = SessionStatus::Stopping;
 
-        // Abort the processing task
+        // Abort the processing tasks
         if let Some(handle) = session.task_handle.take() {
             handle.abort();
         }
+        if let Some(handle) = session.substream_handle.take() {
+            handle.abort();
+        }
 
         // Stop HLS session if enabled
         if session.config.enable_hls {
@@ -521,8 +577,17 @@ impl LiveStreamCoordinator {
             }
         }
 
-        // Stop the source reader
-        let _ = self.source_router.stop_reader(monitor_id).await;
+        // Stop the source reader — unless someone is subscribed to the
+        // monitor's EVENTs (`GET /monitors/{id}/events`). Faults must stay
+        // observable while nobody is watching video, so an event subscriber
+        // keeps the reader connected after the media session goes.
+        let has_event_subscribers = self
+            .source_router
+            .get_existing_source(monitor_id)
+            .is_some_and(|source| source.event_subscriber_count() > 0);
+        if !has_event_subscribers {
+            let _ = self.source_router.stop_reader(monitor_id).await;
+        }
 
         sessions.remove(&monitor_id);
 
@@ -619,6 +684,7 @@ mod tests {
         let config = LiveStreamConfig {
             enable_hls: true,
             enable_webrtc: false,
+            substream: None,
         };
 
         let result = coordinator.start_session(1, config).await;
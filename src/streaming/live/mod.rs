@@ -1,7 +1,7 @@
 //! Live streaming coordination module
 //!
 //! Coordinates the flow of video data from per-monitor stream sockets to
-//! various output protocols (HLS, WebRTC). This module bridges the
+//! various output protocols (HLS, WebRTC, WHEP). This module bridges the
 //! SourceRouter (which reads zmc's stream sockets) to the protocol-specific
 //! output handlers.
 
@@ -9,5 +9,6 @@ pub mod audio;
 pub mod coordinator;
 pub mod hls;
 pub mod webrtc;
+pub mod whep;
 
 pub use coordinator::*;
@@ -12,7 +12,7 @@ use std::time::Duration;
 use dashmap::DashMap;
 use interceptor::registry::Registry;
 use tokio::sync::RwLock;
-use tracing::{debug, info};
+use tracing::{debug, info, warn};
 use uuid::Uuid;
 use webrtc::api::interceptor_registry::register_default_interceptors;
 use webrtc::api::media_engine::MediaEngine;
@@ -22,6 +22,7 @@ use webrtc::dtls_transport::dtls_transport_state::RTCDtlsTransportState;
 use webrtc::ice_transport::ice_candidate::RTCIceCandidateInit;
 use webrtc::ice_transport::ice_connection_state::RTCIceConnectionState;
 use webrtc::ice_transport::ice_server::RTCIceServer;
+use webrtc::peer_connection::certificate::RTCCertificate;
 use webrtc::peer_connection::configuration::RTCConfiguration;
 use webrtc::peer_connection::peer_connection_state::RTCPeerConnectionState;
 use webrtc::peer_connection::sdp::session_description::RTCSessionDescription;
@@ -33,8 +34,10 @@ use webrtc::track::track_local::track_local_static_sample::TrackLocalStaticSampl
 use webrtc::track::track_local::TrackLocal;
 use webrtc_media::Sample;
 
+use crate::streaming::live::audio::AacToOpusTranscoder;
 use crate::streaming::source::{
-    h264_nal_type, h265_nal_type, slice_starts_picture, VideoCodec, VideoPacket,
+    h264_nal_type, h265_nal_type, slice_starts_picture, AudioCodec, AudioPacket, CachedKeyframe,
+    MonitorSource, VideoCodec, VideoPacket,
 };
 
 // Re-export for consumers that imported from here
@@ -444,12 +447,18 @@ pub enum WebRtcLiveError {
     #[error("Session already exists for monitor {0}")]
     SessionExists(u32),
 
+    #[error("Maximum sessions reached ({0})")]
+    SessionLimit(usize),
+
     #[error("WebRTC error: {0}")]
     WebRtcError(String),
 
     #[error("Codec not supported: {0}")]
     UnsupportedCodec(String),
 
+    #[error("Invalid SDP: {0}")]
+    InvalidSdp(String),
+
     #[error("No video track available")]
     NoVideoTrack,
 }
@@ -462,6 +471,9 @@ pub struct WebRtcLiveConfig {
     pub turn_username: Option<String>,
     pub turn_password: Option<String>,
     pub max_sessions: usize,
+    /// DTLS certificate for every peer connection; `None` generates one per
+    /// session (webrtc-rs' default). WHEP sessions reuse `WebRtcEngine`'s.
+    pub certificate: Option<RTCCertificate>,
 }
 
 impl Default for WebRtcLiveConfig {
@@ -475,6 +487,7 @@ impl Default for WebRtcLiveConfig {
             turn_username: None,
             turn_password: None,
             max_sessions: 100,
+            certificate: None,
         }
     }
 }
@@ -503,6 +516,7 @@ impl WebRtcLiveConfig {
             turn_username,
             turn_password,
             max_sessions: cfg.max_connections as usize,
+            certificate: None,
         }
     }
 }
@@ -530,8 +544,33 @@ pub struct SessionHandshake {
     pub pc_state_rx: tokio::sync::watch::Receiver<RTCPeerConnectionState>,
     pub ice_connected_rx: tokio::sync::watch::Receiver<bool>,
     pub candidate_rx: tokio::sync::mpsc::UnboundedReceiver<RTCIceCandidateInit>,
+    /// The viewer's microphone, as Opus payloads, when talkback was asked for.
+    pub talkback_rx: Option<tokio::sync::mpsc::Receiver<bytes::Bytes>>,
+}
+
+/// What the WHEP handler needs after a session has answered the client's
+/// offer. The answer already carries the gathered candidates.
+pub struct AnswerHandshake {
+    pub session_id: String,
+    pub answer: RTCSessionDescription,
+    pub pc_state_rx: tokio::sync::watch::Receiver<RTCPeerConnectionState>,
+}
+
+/// A peer connection with its tracks added, not yet offered or answered.
+struct NewPeer {
+    peer_connection: RTCPeerConnection,
+    video_track: Arc<TrackLocalStaticSample>,
+    audio_track: Option<Arc<TrackLocalStaticSample>>,
+    pc_state_rx: tokio::sync::watch::Receiver<RTCPeerConnectionState>,
+    ice_connected_rx: tokio::sync::watch::Receiver<bool>,
+    candidate_rx: tokio::sync::mpsc::UnboundedReceiver<RTCIceCandidateInit>,
+    talkback_rx: Option<tokio::sync::mpsc::Receiver<bytes::Bytes>>,
 }
 
+/// Opus payloads buffered from a viewer's microphone: one second of 20 ms
+/// packets. A stalled camera drops speech rather than delaying it.
+const TALKBACK_QUEUE_PACKETS: usize = 50;
+
 /// The H.264 `profile-level-id` values to advertise in the SDP offer.
 ///
 /// These are **not** the camera's native profile. The server is a pure
@@ -593,28 +632,178 @@ impl WebRtcLiveManager {
         }
     }
 
-    /// Create a new WebRTC session for a monitor
-    ///
-    /// Builds the webrtc-rs API with interceptor registry and setting engine,
-    /// waits for ICE gathering to complete, and returns the full SDP offer
-    /// with embedded ICE candidates.
-    /// Create a new WebRTC session for a monitor.
+    /// Create a new WebRTC session for a monitor, the server offering.
     ///
     /// `profile_level_id` is the 6-hex-char value extracted from the H.264
-    /// SPS NAL (e.g. `"4d0033"` for Main Profile Level 5.1).  When `None`,
-    /// defaults to `"4d0033"` which covers most IP cameras.
+    /// SPS NAL (e.g. `"4d0033"` for Main Profile Level 5.1); it is only
+    /// logged — see `h264_offer_profile_level_ids`.
+    ///
+    /// With `talkback` the offer also carries a receive-only Opus m-line for
+    /// the viewer's microphone, delivered on `SessionHandshake::talkback_rx`.
     pub async fn create_session(
         &self,
         monitor_id: u32,
         codec: VideoCodec,
         profile_level_id: Option<&str>,
         audio: Option<AudioTrackKind>,
+        talkback: bool,
     ) -> Result<SessionHandshake, WebRtcLiveError> {
+        let peer = self
+            .new_peer(monitor_id, codec, profile_level_id, audio, talkback)
+            .await?;
+
+        // Create offer
+        let offer = peer
+            .peer_connection
+            .create_offer(None)
+            .await
+            .map_err(|e| WebRtcLiveError::WebRtcError(e.to_string()))?;
+
+        // Set local description (starts ICE gathering).
+        peer.peer_connection
+            .set_local_description(offer)
+            .await
+            .map_err(|e| WebRtcLiveError::WebRtcError(e.to_string()))?;
+
+        // Trickle ICE: return the offer NOW — with ICE ufrag/pwd, the DTLS
+        // fingerprint and the m-lines, but possibly no candidates yet — instead
+        // of blocking on `gathering_complete_promise()`. That gather wait was the
+        // dominant `offer_ms` cost: it stalled on STUN round-trips (and up to the
+        // ~5s STUN timeout if the servers were unreachable). Candidates now stream
+        // to the browser via `on_ice_candidate` as they are discovered, so the
+        // offer is sent in tens of ms regardless of STUN reachability, and DTLS
+        // can begin the moment the first candidate pair connects.
+        let complete_offer = peer
+            .peer_connection
+            .local_description()
+            .await
+            .ok_or_else(|| {
+                WebRtcLiveError::WebRtcError("No local description available after set".to_string())
+            })?;
+
+        // Advertise trickle support (RFC 8838 §10). webrtc-rs does not emit
+        // `a=ice-options:trickle`; without it a strict answerer could wait for
+        // `a=end-of-candidates` instead of accepting our streamed candidates.
+        // Browsers accept trickle regardless, but signalling it is correct and
+        // helps non-browser peers. Insert once at session level (before the
+        // first media section); the PC's own local description is unchanged, so
+        // ICE ufrag/pwd/fingerprint still match.
+        let complete_offer = ensure_trickle_ice_option(complete_offer)?;
+
+        debug!(
+            "Trickle offer ready for monitor {} (candidates stream via signaling)",
+            monitor_id
+        );
+
+        let session_id = self.register(
+            monitor_id,
+            peer.peer_connection,
+            peer.video_track,
+            peer.audio_track,
+        );
+
+        Ok(SessionHandshake {
+            session_id,
+            offer: complete_offer,
+            pc_state_rx: peer.pc_state_rx,
+            ice_connected_rx: peer.ice_connected_rx,
+            candidate_rx: peer.candidate_rx,
+            talkback_rx: peer.talkback_rx,
+        })
+    }
+
+    /// Create a new WebRTC session for a monitor, answering the client's
+    /// offer (WHEP).
+    ///
+    /// WHEP has no channel for the server's trickled candidates, so the
+    /// answer waits for ICE gathering — bounded by `gather_timeout`, after
+    /// which it carries whatever has been gathered (host candidates come
+    /// first, within milliseconds).
+    pub async fn answer_session(
+        &self,
+        monitor_id: u32,
+        codec: VideoCodec,
+        audio: Option<AudioTrackKind>,
+        offer_sdp: String,
+        gather_timeout: Duration,
+    ) -> Result<AnswerHandshake, WebRtcLiveError> {
+        let offer = RTCSessionDescription::offer(offer_sdp)
+            .map_err(|e| WebRtcLiveError::InvalidSdp(e.to_string()))?;
+        let peer = self.new_peer(monitor_id, codec, None, audio, false).await?;
+        let pc = &peer.peer_connection;
+
+        // The sendonly transceivers added by `new_peer` bind to the offer's
+        // recvonly m-lines of the same kind.
+        if let Err(e) = pc.set_remote_description(offer).await {
+            let _ = pc.close().await;
+            return Err(WebRtcLiveError::InvalidSdp(e.to_string()));
+        }
+        let mut gathered = pc.gathering_complete_promise().await;
+        let answered = match pc.create_answer(None).await {
+            Ok(answer) => pc.set_local_description(answer).await,
+            Err(e) => Err(e),
+        };
+        if let Err(e) = answered {
+            let _ = pc.close().await;
+            return Err(WebRtcLiveError::WebRtcError(e.to_string()));
+        }
+        if tokio::time::timeout(gather_timeout, gathered.recv())
+            .await
+            .is_err()
+        {
+            debug!(
+                "Monitor {monitor_id}: ICE gathering incomplete after {gather_timeout:?}; \
+                 answering with the candidates gathered so far"
+            );
+        }
+        let answer = pc.local_description().await.ok_or_else(|| {
+            WebRtcLiveError::WebRtcError("No local description available after set".to_string())
+        })?;
+
+        // A client that cannot receive the camera's codec gets the video
+        // m-line rejected (port 0); nothing could ever play.
+        if answer.sdp.lines().any(|l| l.starts_with("m=video 0 ")) {
+            let _ = pc.close().await;
+            return Err(WebRtcLiveError::UnsupportedCodec(format!(
+                "{} is not in the client's offer",
+                codec.as_str()
+            )));
+        }
+
+        let session_id = self.register(
+            monitor_id,
+            peer.peer_connection,
+            peer.video_track,
+            peer.audio_track,
+        );
+        if let Some(session) = self.get_session(&session_id) {
+            session
+                .read()
+                .await
+                .set_state(WebRtcSessionState::Connecting)
+                .await;
+        }
+
+        Ok(AnswerHandshake {
+            session_id,
+            answer,
+            pc_state_rx: peer.pc_state_rx,
+        })
+    }
+
+    /// Build a peer connection carrying the monitor's tracks, with the state
+    /// watches both signalling styles need.
+    async fn new_peer(
+        &self,
+        monitor_id: u32,
+        codec: VideoCodec,
+        profile_level_id: Option<&str>,
+        audio: Option<AudioTrackKind>,
+        talkback: bool,
+    ) -> Result<NewPeer, WebRtcLiveError> {
         // Check session limit
         if self.sessions.len() >= self.config.max_sessions {
-            return Err(WebRtcLiveError::WebRtcError(
-                "Maximum sessions reached".to_string(),
-            ));
+            return Err(WebRtcLiveError::SessionLimit(self.config.max_sessions));
         }
 
         // Create media engine with codec support
@@ -697,6 +886,21 @@ impl WebRtcLiveManager {
                 .map_err(|e| WebRtcLiveError::WebRtcError(e.to_string()))?;
         }
 
+        // Talkback receives Opus whatever the camera sends.
+        let talkback_codec = webrtc::rtp_transceiver::rtp_codec::RTCRtpCodecParameters {
+            capability: AudioTrackKind::Opus.capability(),
+            payload_type: AudioTrackKind::Opus.payload_type(),
+            ..Default::default()
+        };
+        if talkback && audio != Some(AudioTrackKind::Opus) {
+            media_engine
+                .register_codec(
+                    talkback_codec.clone(),
+                    webrtc::rtp_transceiver::rtp_codec::RTPCodecType::Audio,
+                )
+                .map_err(|e| WebRtcLiveError::WebRtcError(e.to_string()))?;
+        }
+
         // Register default interceptors (RTCP feedback, NACK, etc.)
         let mut registry = Registry::new();
         registry = register_default_interceptors(registry, &mut media_engine)
@@ -738,6 +942,7 @@ impl WebRtcLiveManager {
 
         let rtc_config = RTCConfiguration {
             ice_servers,
+            certificates: self.config.certificate.iter().cloned().collect(),
             ..Default::default()
         };
 
@@ -873,7 +1078,7 @@ impl WebRtcLiveManager {
                 "audio".to_string(),
                 format!("zm-live-{}", monitor_id),
             ));
-            peer_connection
+            let transceiver = peer_connection
                 .add_transceiver_from_track(
                     Arc::clone(&track) as Arc<dyn TrackLocal + Send + Sync>,
                     Some(RTCRtpTransceiverInit {
@@ -883,50 +1088,94 @@ impl WebRtcLiveManager {
                 )
                 .await
                 .map_err(|e| WebRtcLiveError::WebRtcError(e.to_string()))?;
+            // With talkback's Opus registered too, keep this m-line to the
+            // track's own codec.
+            if talkback {
+                transceiver
+                    .set_codec_preferences(vec![
+                        webrtc::rtp_transceiver::rtp_codec::RTCRtpCodecParameters {
+                            capability: kind.capability(),
+                            payload_type: kind.payload_type(),
+                            ..Default::default()
+                        },
+                    ])
+                    .await
+                    .map_err(|e| WebRtcLiveError::WebRtcError(e.to_string()))?;
+            }
             Some(track)
         } else {
             None
         };
 
-        // Create offer
-        let offer = peer_connection
-            .create_offer(None)
-            .await
-            .map_err(|e| WebRtcLiveError::WebRtcError(e.to_string()))?;
-
-        // Set local description (starts ICE gathering).
-        peer_connection
-            .set_local_description(offer)
-            .await
-            .map_err(|e| WebRtcLiveError::WebRtcError(e.to_string()))?;
-
-        // Trickle ICE: return the offer NOW — with ICE ufrag/pwd, the DTLS
-        // fingerprint and the m-lines, but possibly no candidates yet — instead
-        // of blocking on `gathering_complete_promise()`. That gather wait was the
-        // dominant `offer_ms` cost: it stalled on STUN round-trips (and up to the
-        // ~5s STUN timeout if the servers were unreachable). Candidates now stream
-        // to the browser via `on_ice_candidate` as they are discovered, so the
-        // offer is sent in tens of ms regardless of STUN reachability, and DTLS
-        // can begin the moment the first candidate pair connects.
-        let complete_offer = peer_connection.local_description().await.ok_or_else(|| {
-            WebRtcLiveError::WebRtcError("No local description available after set".to_string())
-        })?;
+        // Talkback: a recvonly Opus m-line the browser attaches its
+        // microphone to. Payloads go to the handler undecoded.
+        let talkback_rx = if talkback {
+            let transceiver = peer_connection
+                .add_transceiver_from_kind(
+                    webrtc::rtp_transceiver::rtp_codec::RTPCodecType::Audio,
+                    Some(RTCRtpTransceiverInit {
+                        direction: RTCRtpTransceiverDirection::Recvonly,
+                        send_encodings: vec![],
+                    }),
+                )
+                .await
+                .map_err(|e| WebRtcLiveError::WebRtcError(e.to_string()))?;
+            transceiver
+                .set_codec_preferences(vec![talkback_codec])
+                .await
+                .map_err(|e| WebRtcLiveError::WebRtcError(e.to_string()))?;
 
-        // Advertise trickle support (RFC 8838 §10). webrtc-rs does not emit
-        // `a=ice-options:trickle`; without it a strict answerer could wait for
-        // `a=end-of-candidates` instead of accepting our streamed candidates.
-        // Browsers accept trickle regardless, but signalling it is correct and
-        // helps non-browser peers. Insert once at session level (before the
-        // first media section); the PC's own local description is unchanged, so
-        // ICE ufrag/pwd/fingerprint still match.
-        let complete_offer = ensure_trickle_ice_option(complete_offer)?;
+            let (mic_tx, mic_rx) = tokio::sync::mpsc::channel(TALKBACK_QUEUE_PACKETS);
+            peer_connection.on_track(Box::new(move |track, _receiver, _transceiver| {
+                let mic_tx = mic_tx.clone();
+                Box::pin(async move {
+                    if !track
+                        .codec()
+                        .capability
+                        .mime_type
+                        .eq_ignore_ascii_case("audio/opus")
+                    {
+                        return;
+                    }
+                    debug!("monitor {monitor_id}: talkback track started");
+                    tokio::spawn(async move {
+                        while let Ok((packet, _)) = track.read_rtp().await {
+                            if packet.payload.is_empty() {
+                                continue;
+                            }
+                            if let Err(tokio::sync::mpsc::error::TrySendError::Closed(_)) =
+                                mic_tx.try_send(packet.payload)
+                            {
+                                break;
+                            }
+                        }
+                    });
+                })
+            }));
+            Some(mic_rx)
+        } else {
+            None
+        };
 
-        debug!(
-            "Trickle offer ready for monitor {} (candidates stream via signaling)",
-            monitor_id
-        );
+        Ok(NewPeer {
+            peer_connection,
+            video_track,
+            audio_track,
+            pc_state_rx,
+            ice_connected_rx,
+            candidate_rx,
+            talkback_rx,
+        })
+    }
 
-        // Create session
+    /// Track a negotiated peer connection as a session; returns its id.
+    fn register(
+        &self,
+        monitor_id: u32,
+        peer_connection: RTCPeerConnection,
+        video_track: Arc<TrackLocalStaticSample>,
+        audio_track: Option<Arc<TrackLocalStaticSample>>,
+    ) -> String {
         let session_id = Uuid::new_v4();
         let session = WebRtcLiveSession {
             id: session_id,
@@ -954,14 +1203,7 @@ impl WebRtcLiveManager {
             "Created WebRTC session {} for monitor {}",
             session_id_str, monitor_id
         );
-
-        Ok(SessionHandshake {
-            session_id: session_id_str,
-            offer: complete_offer,
-            pc_state_rx,
-            ice_connected_rx,
-            candidate_rx,
-        })
+        session_id_str
     }
 
     /// Handle answer from client
@@ -1112,6 +1354,160 @@ impl WebRtcLiveManager {
     }
 }
 
+// ============================================================================
+// Session startup and media forwarding (WebSocket signaling and WHEP)
+// ============================================================================
+
+/// The video codec to negotiate, and the keyframe cache snapshot taken before
+/// signaling (the warm-start fallback for [`inject_startup_keyframe`]).
+pub async fn startup_codec(source: &MonitorSource) -> (VideoCodec, Option<CachedKeyframe>) {
+    // Try the keyframe cache first (warm path: reader already active).
+    // If populated, we skip the cold-path codec-detection grace period entirely.
+    if let Some(ck) = source.cached_keyframe() {
+        info!(
+            "Using cached keyframe for monitor {} (profile-level-id={})",
+            source.monitor_id(),
+            ck.profile_level_id
+        );
+        return (ck.codec, Some(ck));
+    }
+
+    // Cold path: no cached keyframe yet (first viewer since the reader
+    // started). Determine the codec from the source — the socket HELLO sets
+    // it as soon as it parses a packet; give it a brief grace period, then
+    // default to H.264.
+    //
+    // We deliberately do NOT wait for an SPS NAL here. The SDP offer
+    // advertises fixed, browser-universal H.264 profiles regardless of the
+    // camera's real profile-level-id (see `h264_offer_profile_level_ids`),
+    // so the detected value only ever reached a debug log — the previous
+    // up-to-5s SPS scan added that much startup latency for nothing.
+    // See REVIEW_FIXES_PLAN §2.3.
+    debug!(
+        "No keyframe cache for monitor {}, detecting codec from source",
+        source.monitor_id()
+    );
+    tokio::time::sleep(Duration::from_millis(100)).await;
+    let codec = match source.codec().await {
+        VideoCodec::Unknown => VideoCodec::H264,
+        known => known,
+    };
+    (codec, None)
+}
+
+/// Decide a session's audio track. G.711 passes through unchanged (a
+/// mandatory WebRTC codec); AAC is transcoded to Opus — browsers do not
+/// implement AAC over RTP. The transcoder is created up front so a missing
+/// libopus downgrades the session to video-only instead of negotiating an
+/// audio m-line that could never carry media.
+pub fn select_audio_track(
+    monitor_id: u32,
+    source: &MonitorSource,
+) -> (Option<AudioTrackKind>, Option<AacToOpusTranscoder>) {
+    match source.audio_codec() {
+        Some(AudioCodec::G711Ulaw) => (Some(AudioTrackKind::Pcmu), None),
+        Some(AudioCodec::G711Alaw) => (Some(AudioTrackKind::Pcma), None),
+        Some(AudioCodec::Aac) => match AacToOpusTranscoder::new() {
+            Ok(t) => (Some(AudioTrackKind::Opus), Some(t)),
+            Err(e) => {
+                warn!(
+                    "Monitor {} has AAC audio but transcoding is unavailable ({}); \
+                     offering video-only",
+                    monitor_id, e
+                );
+                (None, None)
+            }
+        },
+        Some(other) => {
+            debug!(
+                "Monitor {} audio codec {:?} not supported over WebRTC; video-only",
+                monitor_id, other
+            );
+            (None, None)
+        }
+        None => (None, None),
+    }
+}
+
+/// Inject a keyframe at DTLS-connected time so the viewer sees a picture
+/// immediately instead of waiting a full GOP for the next natural IDR.
+///
+/// Crucially this re-reads the source's keyframe cache **live**. On a cold
+/// start the cache is empty when signaling begins, but the socket reader almost
+/// always populates it during the seconds of ICE/DTLS negotiation. The old code
+/// injected the snapshot captured *before* signaling (`pre_signaling`), which on
+/// a cold start was `None` — throwing away the freshly-cached keyframe and
+/// forcing a GOP-length stall (1–8s depending on the camera). We prefer the live
+/// read, fall back to the pre-signaling snapshot, then to nothing (the normal
+/// access-unit path then waits for the next keyframe). See REVIEW_FIXES_PLAN §2.1.
+pub async fn inject_startup_keyframe(
+    webrtc_manager: &WebRtcLiveManager,
+    session_id: &str,
+    source: &MonitorSource,
+    pre_signaling: &Option<CachedKeyframe>,
+    au_assembler: &mut AccessUnitAssembler,
+) -> bool {
+    let Some(ck) = source.cached_keyframe().or_else(|| pre_signaling.clone()) else {
+        return false;
+    };
+    let au = AssembledAccessUnit {
+        data: ck.keyframe_au.clone(),
+        timestamp_us: ck.timestamp_us,
+        is_keyframe: true,
+    };
+    if let Err(e) = webrtc_manager.write_access_unit(session_id, &au).await {
+        warn!("Failed to inject startup keyframe: {}", e);
+        return false;
+    }
+    au_assembler.clear_needs_keyframe();
+    true
+}
+
+/// Forward one source audio packet to a session's audio track: G.711 as-is,
+/// AAC through the session's Opus transcoder.
+pub async fn write_audio_packet(
+    manager: &WebRtcLiveManager,
+    session_id: &str,
+    packet: &AudioPacket,
+    transcoder: &mut Option<AacToOpusTranscoder>,
+) {
+    match packet.codec {
+        AudioCodec::G711Alaw | AudioCodec::G711Ulaw => {
+            // G.711: 8 kHz, one byte per sample — pass through.
+            let duration = Duration::from_micros(packet.data.len() as u64 * 1_000_000 / 8_000);
+            if let Err(e) = manager
+                .write_audio_sample(session_id, &packet.data, duration)
+                .await
+            {
+                debug!("Failed to write audio sample: {}", e);
+            }
+        }
+        AudioCodec::Aac => {
+            // ADTS AAC → Opus. Sub-millisecond per frame, so inline in the
+            // async loop is fine.
+            let Some(transcoder) = transcoder.as_mut() else {
+                return;
+            };
+            match transcoder.transcode(&packet.data) {
+                Ok(frames) => {
+                    for frame in frames {
+                        if let Err(e) = manager
+                            .write_audio_sample(session_id, &frame.data, frame.duration)
+                            .await
+                        {
+                            debug!("Failed to write Opus frame: {}", e);
+                        }
+                    }
+                }
+                Err(e) => {
+                    debug!("AAC→Opus transcode error for session {}: {}", session_id, e);
+                }
+            }
+        }
+        _ => {}
+    }
+}
+
 #[cfg(test)]
 mod tests {
     use super::*;
@@ -1204,7 +1600,7 @@ mod tests {
     async fn test_session_with_audio_offers_audio_m_line() {
         let manager = lan_only_manager();
         let h = manager
-            .create_session(1, VideoCodec::H264, None, Some(AudioTrackKind::Opus))
+            .create_session(1, VideoCodec::H264, None, Some(AudioTrackKind::Opus), false)
             .await
             .expect("session with audio");
         assert!(h.offer.sdp.contains("m=video"), "video m-line expected");
@@ -1216,7 +1612,7 @@ mod tests {
     async fn test_session_with_g711_offers_pcma() {
         let manager = lan_only_manager();
         let h = manager
-            .create_session(1, VideoCodec::H264, None, Some(AudioTrackKind::Pcma))
+            .create_session(1, VideoCodec::H264, None, Some(AudioTrackKind::Pcma), false)
             .await
             .expect("session with G.711 audio");
         assert!(h.offer.sdp.contains("m=audio"));
@@ -1227,7 +1623,7 @@ mod tests {
     async fn test_video_only_session_has_no_audio_m_line() {
         let manager = lan_only_manager();
         let h = manager
-            .create_session(1, VideoCodec::H264, None, None)
+            .create_session(1, VideoCodec::H264, None, None, false)
             .await
             .expect("video-only session");
         assert!(h.offer.sdp.contains("m=video"));
@@ -1241,7 +1637,7 @@ mod tests {
     async fn test_h265_session_offers_h265_with_fmtp() {
         let manager = lan_only_manager();
         let h = manager
-            .create_session(1, VideoCodec::H265, None, None)
+            .create_session(1, VideoCodec::H265, None, None, false)
             .await
             .expect("H.265 session");
         assert!(h.offer.sdp.contains("H265"), "H265 codec expected in SDP");
@@ -1251,6 +1647,35 @@ mod tests {
         );
     }
 
+    #[tokio::test]
+    async fn test_talkback_session_offers_a_recvonly_opus_m_line() {
+        let manager = lan_only_manager();
+        let h = manager
+            .create_session(1, VideoCodec::H264, None, Some(AudioTrackKind::Pcma), true)
+            .await
+            .expect("session with talkback");
+        let audio: Vec<&str> = h
+            .offer
+            .sdp
+            .split("\nm=")
+            .filter(|section| section.starts_with("audio"))
+            .collect();
+        assert_eq!(audio.len(), 2, "camera audio plus talkback");
+        assert!(audio[0].contains("a=sendonly") && audio[0].contains("PCMA"));
+        assert!(
+            !audio[0].contains("opus"),
+            "camera audio keeps its own codec"
+        );
+        assert!(audio[1].contains("a=recvonly") && audio[1].contains("opus/48000"));
+        assert!(h.talkback_rx.is_some());
+
+        let h = manager
+            .create_session(1, VideoCodec::H264, None, None, false)
+            .await
+            .expect("session without talkback");
+        assert!(h.talkback_rx.is_none());
+    }
+
     /// Trickle ICE: the offer is returned immediately with ICE ufrag + DTLS
     /// fingerprint (so the browser can answer at once) and host candidates are
     /// streamed on the candidate channel rather than embedded after a gather
@@ -1259,7 +1684,7 @@ mod tests {
     async fn test_trickle_offer_is_immediate_and_streams_candidates() {
         let manager = lan_only_manager();
         let mut h = manager
-            .create_session(1, VideoCodec::H264, None, None)
+            .create_session(1, VideoCodec::H264, None, None, false)
             .await
             .expect("session");
         // The offer carries everything the browser needs to answer without
@@ -1287,6 +1712,76 @@ mod tests {
         );
     }
 
+    /// A receive-only offer, as a WHEP player sends.
+    async fn player_offer() -> String {
+        let mut media_engine = MediaEngine::default();
+        media_engine.register_default_codecs().unwrap();
+        let api = APIBuilder::new().with_media_engine(media_engine).build();
+        let player = api
+            .new_peer_connection(RTCConfiguration::default())
+            .await
+            .unwrap();
+        player
+            .add_transceiver_from_kind(
+                webrtc::rtp_transceiver::rtp_codec::RTPCodecType::Video,
+                Some(RTCRtpTransceiverInit {
+                    direction: RTCRtpTransceiverDirection::Recvonly,
+                    send_encodings: vec![],
+                }),
+            )
+            .await
+            .unwrap();
+        let offer = player.create_offer(None).await.unwrap();
+        let _ = player.close().await;
+        offer.sdp
+    }
+
+    #[tokio::test]
+    async fn test_answer_session_answers_a_recvonly_offer_with_candidates() {
+        let manager = lan_only_manager();
+        let h = manager
+            .answer_session(
+                1,
+                VideoCodec::H264,
+                None,
+                player_offer().await,
+                Duration::from_secs(3),
+            )
+            .await
+            .expect("answer");
+        assert!(h.answer.sdp.contains("H264/90000"), "answer sends H.264");
+        assert!(h.answer.sdp.contains("a=sendonly"), "answer is send-only");
+        assert!(
+            h.answer.sdp.contains("a=candidate:"),
+            "answer carries the gathered candidates"
+        );
+        assert_eq!(manager.get_monitor_sessions(1), vec![h.session_id.clone()]);
+        let state = manager.get_session(&h.session_id).unwrap();
+        assert_eq!(
+            state.read().await.get_state().await,
+            WebRtcSessionState::Connecting
+        );
+        manager.remove_session(&h.session_id).await.unwrap();
+    }
+
+    #[tokio::test]
+    async fn test_answer_session_rejects_garbage_sdp() {
+        let manager = lan_only_manager();
+        let err = manager
+            .answer_session(
+                1,
+                VideoCodec::H264,
+                None,
+                "not sdp".to_string(),
+                Duration::from_secs(1),
+            )
+            .await
+            .err()
+            .expect("garbage offer is refused");
+        assert!(matches!(err, WebRtcLiveError::InvalidSdp(_)), "{err}");
+        assert_eq!(manager.session_count(), 0);
+    }
+
     // --- AccessUnitAssembler tests ---
 
     /// Build a single-NAL `VideoPacket`. For VCL slice NAL types a slice-header
@@ -0,0 +1,445 @@
+//! WHEP (WebRTC-HTTP Egress Protocol, RFC 9725) sessions.
+//!
+//! A WHEP player POSTs an SDP offer and gets the answer back; the session
+//! then lives at a resource URL the player PATCHes with trickled ICE
+//! candidates and DELETEs to hang up. The peer is built exactly as for the
+//! WebSocket path — [`WebRtcLiveManager`] tracks fed from the
+//! [`SourceRouter`] broadcast, with the keyframe warm start and the same
+//! audio track selection — but with no socket to hold it open, each session
+//! runs as a task that ends when the peer connection fails or closes, the
+//! reader dies, or the resource is deleted.
+
+use std::sync::Arc;
+use std::time::Duration;
+
+use dashmap::DashMap;
+use tokio::sync::{broadcast, oneshot, watch};
+use tracing::{debug, info, warn};
+use webrtc::peer_connection::peer_connection_state::RTCPeerConnectionState;
+
+use super::audio::AacToOpusTranscoder;
+use super::webrtc::{
+    inject_startup_keyframe, select_audio_track, startup_codec, write_audio_packet,
+    AccessUnitAssembler, AnswerHandshake, AudioTrackKind, WebRtcLiveConfig, WebRtcLiveError,
+    WebRtcLiveManager,
+};
+use crate::streaming::source::{CachedKeyframe, MonitorSource, RouterError, SourceRouter};
+
+/// How long the answer waits for ICE gathering. Host candidates are ready
+/// in milliseconds; this only bounds the wait on slow STUN servers.
+const ANSWER_GATHER_TIMEOUT: Duration = Duration::from_secs(2);
+
+#[derive(Debug, thiserror::Error)]
+pub enum WhepError {
+    #[error(transparent)]
+    Source(#[from] RouterError),
+
+    #[error(transparent)]
+    WebRtc(#[from] WebRtcLiveError),
+
+    #[error("WHEP session {0} not found")]
+    SessionNotFound(String),
+
+    #[error("ICE restart is not supported; start a new session")]
+    IceRestart,
+}
+
+/// The answer to a WHEP offer.
+#[derive(Debug)]
+pub struct WhepAnswer {
+    pub session_id: String,
+    pub sdp: String,
+}
+
+/// A running session, as the HTTP resource sees it.
+struct WhepSession {
+    monitor_id: u32,
+    /// The player's ICE username fragment from its offer; a PATCH naming
+    /// another one is an ICE restart.
+    ice_ufrag: Option<String>,
+    /// Dropped to stop the session's task.
+    _stop: oneshot::Sender<()>,
+}
+
+/// The WHEP sessions of this process.
+pub struct WhepSessions {
+    router: Arc<SourceRouter>,
+    webrtc: WebRtcLiveManager,
+    sessions: DashMap<String, WhepSession>,
+    /// How long a session may take to connect after its answer.
+    connect_timeout: Duration,
+}
+
+impl WhepSessions {
+    pub fn new(
+        router: Arc<SourceRouter>,
+        config: WebRtcLiveConfig,
+        connect_timeout: Duration,
+    ) -> Self {
+        Self {
+            router,
+            webrtc: WebRtcLiveManager::new(config),
+            sessions: DashMap::new(),
+            connect_timeout,
+        }
+    }
+
+    /// Answer a player's offer for `monitor_id` and start streaming to it.
+    pub async fn create(
+        self: &Arc<Self>,
+        monitor_id: u32,
+        offer_sdp: String,
+    ) -> Result<WhepAnswer, WhepError> {
+        let source = self.router.get_source(monitor_id).await?;
+        let (codec, cached) = startup_codec(&source).await;
+        let (audio_kind, audio_transcoder) = select_audio_track(monitor_id, &source);
+        let ice_ufrag = ice_ufrag(&offer_sdp);
+
+        let AnswerHandshake {
+            session_id,
+            answer,
+            pc_state_rx,
+        } = self
+            .webrtc
+            .answer_session(
+                monitor_id,
+                codec,
+                audio_kind,
+                offer_sdp,
+                ANSWER_GATHER_TIMEOUT,
+            )
+            .await?;
+
+        let (stop_tx, stop_rx) = oneshot::channel();
+        self.sessions.insert(
+            session_id.clone(),
+            WhepSession {
+                monitor_id,
+                ice_ufrag,
+                _stop: stop_tx,
+            },
+        );
+        tokio::spawn(Arc::clone(self).run(Stream {
+            session_id: session_id.clone(),
+            source,
+            cached,
+            audio_kind,
+            audio_transcoder,
+            pc_state_rx,
+            stop_rx,
+        }));
+
+        info!("WHEP session {session_id} created for monitor {monitor_id}");
+        Ok(WhepAnswer {
+            session_id,
+            sdp: answer.sdp,
+        })
+    }
+
+    /// Add the player's trickled candidates (an `application/trickle-ice-sdpfrag`
+    /// body) to a session.
+    pub async fn add_candidates(
+        &self,
+        monitor_id: u32,
+        session_id: &str,
+        fragment: &str,
+    ) -> Result<(), WhepError> {
+        let ice_ufrag = self
+            .sessions
+            .get(session_id)
+            .filter(|s| s.monitor_id == monitor_id)
+            .map(|s| s.ice_ufrag.clone())
+            .ok_or_else(|| WhepError::SessionNotFound(session_id.to_string()))?;
+
+        for candidate in parse_sdpfrag(fragment) {
+            if candidate.ufrag.is_some() && ice_ufrag.is_some() && candidate.ufrag != ice_ufrag {
+                return Err(WhepError::IceRestart);
+            }
+            self.webrtc
+                .add_ice_candidate(
+                    session_id,
+                    &candidate.candidate,
+                    candidate.sdp_mid,
+                    candidate.sdp_mline_index,
+                )
+                .await?;
+        }
+        Ok(())
+    }
+
+    /// End a session. Its task tears down the peer connection.
+    pub fn remove(&self, monitor_id: u32, session_id: &str) -> Result<(), WhepError> {
+        match self
+            .sessions
+            .remove_if(session_id, |_, s| s.monitor_id == monitor_id)
+        {
+            Some(_) => {
+                info!("WHEP session {session_id} deleted");
+                Ok(())
+            }
+            None => Err(WhepError::SessionNotFound(session_id.to_string())),
+        }
+    }
+
+    /// Number of live WHEP sessions.
+    pub fn session_count(&self) -> usize {
+        self.sessions.len()
+    }
+
+    /// Forward the monitor's media until the session ends.
+    async fn run(self: Arc<Self>, stream: Stream) {
+        let Stream {
+            session_id,
+            source,
+            cached,
+            audio_kind,
+            mut audio_transcoder,
+            mut pc_state_rx,
+            mut stop_rx,
+        } = stream;
+        let monitor_id = source.monitor_id();
+        let mut video_rx = source.subscribe_video();
+        let mut audio_rx = audio_kind.is_some().then(|| source.subscribe_audio());
+        let mut reader_health_rx = source.subscribe_reader_health();
+        let mut au_assembler = AccessUnitAssembler::new();
+        let mut streaming = false;
+        let connect_timeout = tokio::time::sleep(self.connect_timeout);
+        tokio::pin!(connect_timeout);
+
+        // The peer may have connected while the answer was on its way.
+        if *pc_state_rx.borrow_and_update() == RTCPeerConnectionState::Connected {
+            streaming = true;
+            self.start(&session_id, &source, &cached, &mut au_assembler)
+                .await;
+        }
+
+        loop {
+            tokio::select! {
+                _ = &mut stop_rx => break,
+
+                result = pc_state_rx.changed() => {
+                    if result.is_err() {
+                        break;
+                    }
+                    let state = *pc_state_rx.borrow_and_update();
+                    match state {
+                        RTCPeerConnectionState::Connected if !streaming => {
+                            streaming = true;
+                            self.start(&session_id, &source, &cached, &mut au_assembler).await;
+                        }
+                        RTCPeerConnectionState::Failed | RTCPeerConnectionState::Closed => {
+                            info!("WHEP session {session_id} peer connection {state:?}");
+                            break;
+                        }
+                        // Disconnected may recover; ICE declares Failed if not.
+                        _ => debug!("WHEP session {session_id} state: {state:?}"),
+                    }
+                }
+
+                () = &mut connect_timeout, if !streaming => {
+                    warn!(
+                        "WHEP session {session_id} did not connect within {:?}",
+                        self.connect_timeout
+                    );
+                    break;
+                }
+
+                result = video_rx.recv(), if streaming => match result {
+                    Ok(packet) => {
+                        if let Some(au) = au_assembler.push(&packet) {
+                            if let Err(e) = self.webrtc.write_access_unit(&session_id, &au).await {
+                                debug!("Failed to write access unit: {}", e);
+                            }
+                        }
+                    }
+                    Err(broadcast::error::RecvError::Lagged(n)) => {
+                        warn!("WHEP session {session_id} lagged {n} packets");
+                    }
+                    Err(broadcast::error::RecvError::Closed) => break,
+                },
+
+                // Pends forever for video-only sessions; audio before the
+                // peer connects is drained and dropped.
+                result = async {
+                    match audio_rx.as_mut() {
+                        Some(rx) => rx.recv().await,
+                        None => std::future::pending().await,
+                    }
+                } => match result {
+                    Ok(packet) if streaming => {
+                        write_audio_packet(&self.webrtc, &session_id, &packet, &mut audio_transcoder)
+                            .await;
+                    }
+                    Ok(_) | Err(broadcast::error::RecvError::Lagged(_)) => {}
+                    Err(broadcast::error::RecvError::Closed) => audio_rx = None,
+                },
+
+                // A dead reader never closes the broadcast channel; end the
+                // session rather than hang.
+                result = reader_health_rx.changed() => {
+                    if result.is_err() {
+                        warn!("Reader for monitor {monitor_id} exited, ending WHEP session {session_id}");
+                        break;
+                    }
+                }
+            }
+        }
+
+        self.sessions.remove(&session_id);
+        let _ = self.webrtc.remove_session(&session_id).await;
+        info!("WHEP session {session_id} for monitor {monitor_id} ended");
+    }
+
+    /// The peer connected: warm-start it with the freshest keyframe.
+    async fn start(
+        &self,
+        session_id: &str,
+        source: &MonitorSource,
+        cached: &Option<CachedKeyframe>,
+        au_assembler: &mut AccessUnitAssembler,
+    ) {
+        if inject_startup_keyframe(&self.webrtc, session_id, source, cached, au_assembler).await {
+            info!("Injected startup keyframe for WHEP session {session_id} (fast start)");
+        }
+        info!("WHEP session {session_id} connected, streaming started");
+    }
+}
+
+/// What a session's task streams, and the signals it watches.
+struct Stream {
+    session_id: String,
+    source: Arc<MonitorSource>,
+    cached: Option<CachedKeyframe>,
+    audio_kind: Option<AudioTrackKind>,
+    audio_transcoder: Option<AacToOpusTranscoder>,
+    pc_state_rx: watch::Receiver<RTCPeerConnectionState>,
+    stop_rx: oneshot::Receiver<()>,
+}
+
+/// The first `a=ice-ufrag` of an SDP.
+fn ice_ufrag(sdp: &str) -> Option<String> {
+    sdp.lines()
+        .find_map(|l| l.trim_end().strip_prefix("a=ice-ufrag:"))
+        .map(str::to_string)
+}
+
+/// One candidate from a trickle-ice-sdpfrag body.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct FragmentCandidate {
+    /// The attribute value, starting `candidate:`.
+    pub candidate: String,
+    pub sdp_mid: Option<String>,
+    pub sdp_mline_index: Option<u16>,
+    /// The `a=ice-ufrag` in effect for the candidate's section.
+    pub ufrag: Option<String>,
+}
+
+/// The candidates of an `application/trickle-ice-sdpfrag` body (RFC 8840):
+/// each `m=` line opens a media section whose `a=mid` and `a=ice-ufrag`
+/// apply to its candidates wherever they appear in it; session-level
+/// attributes apply to every section. `a=end-of-candidates` is ignored.
+pub fn parse_sdpfrag(body: &str) -> Vec<FragmentCandidate> {
+    #[derive(Default)]
+    struct Section {
+        mid: Option<String>,
+        ufrag: Option<String>,
+        candidates: Vec<String>,
+    }
+
+    let mut session = Section::default();
+    let mut media: Vec<Section> = Vec::new();
+    for line in body.lines().map(str::trim_end) {
+        if line.starts_with("m=") {
+            media.push(Section::default());
+            continue;
+        }
+        let section = media.last_mut().unwrap_or(&mut session);
+        if let Some(mid) = line.strip_prefix("a=mid:") {
+            section.mid = Some(mid.to_string());
+        } else if let Some(ufrag) = line.strip_prefix("a=ice-ufrag:") {
+            section.ufrag = Some(ufrag.to_string());
+        } else if let Some(candidate) = line.strip_prefix("a=") {
+            if candidate.starts_with("candidate:") {
+                section.candidates.push(candidate.to_string());
+            }
+        }
+    }
+
+    let mut out: Vec<FragmentCandidate> = session
+        .candidates
+        .iter()
+        .map(|c| FragmentCandidate {
+            candidate: c.clone(),
+            sdp_mid: None,
+            sdp_mline_index: None,
+            ufrag: session.ufrag.clone(),
+        })
+        .collect();
+    for (index, section) in media.into_iter().enumerate() {
+        let ufrag = section.ufrag.or_else(|| session.ufrag.clone());
+        out.extend(section.candidates.into_iter().map(|c| FragmentCandidate {
+            candidate: c,
+            sdp_mid: section.mid.clone(),
+            sdp_mline_index: Some(index as u16),
+            ufrag: ufrag.clone(),
+        }));
+    }
+    out
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn sdpfrag_candidates_take_their_section_mid_and_index() {
+        let body = "a=ice-ufrag:EsAw\r\n\
+                    a=ice-pwd:P2uYro0UCOQ4zxjKXaWCBui1\r\n\
+                    m=audio 9 RTP/AVP 0\r\n\
+                    a=candidate:1387637174 1 udp 2122260223 192.0.2.1 61764 typ host\r\n\
+                    a=mid:0\r\n\
+                    m=video 9 RTP/AVP 96\r\n\
+                    a=mid:1\r\n\
+                    a=candidate:3471623853 1 udp 2122194687 198.51.100.2 61765 typ host\r\n\
+                    a=end-of-candidates\r\n";
+        let candidates = parse_sdpfrag(body);
+        assert_eq!(candidates.len(), 2);
+        assert_eq!(
+            candidates[0],
+            FragmentCandidate {
+                candidate: "candidate:1387637174 1 udp 2122260223 192.0.2.1 61764 typ host"
+                    .to_string(),
+                sdp_mid: Some("0".to_string()),
+                sdp_mline_index: Some(0),
+                ufrag: Some("EsAw".to_string()),
+            }
+        );
+        assert_eq!(candidates[1].sdp_mid.as_deref(), Some("1"));
+        assert_eq!(candidates[1].sdp_mline_index, Some(1));
+    }
+
+    #[test]
+    fn sdpfrag_without_media_lines_keeps_session_candidates() {
+        let body = "a=candidate:1 1 udp 1 10.0.0.1 5000 typ host\n";
+        let candidates = parse_sdpfrag(body);
+        assert_eq!(candidates.len(), 1);
+        assert_eq!(candidates[0].sdp_mid, None);
+        assert_eq!(candidates[0].sdp_mline_index, None);
+        assert!(parse_sdpfrag("a=end-of-candidates\r\n").is_empty());
+    }
+
+    #[test]
+    fn media_level_ufrag_overrides_session_level() {
+        let body = "a=ice-ufrag:old\r\nm=video 9 RTP/AVP 96\r\na=ice-ufrag:new\r\n\
+                    a=candidate:1 1 udp 1 10.0.0.1 5000 typ host\r\n";
+        assert_eq!(parse_sdpfrag(body)[0].ufrag.as_deref(), Some("new"));
+    }
+
+    #[test]
+    fn offer_ufrag_is_the_first_one() {
+        let sdp = "v=0\r\na=group:BUNDLE 0\r\nm=video 9 UDP/TLS/RTP/SAVPF 96\r\n\
+                   a=ice-ufrag:abcd\r\na=ice-ufrag:efgh\r\n";
+        assert_eq!(ice_ufrag(sdp).as_deref(), Some("abcd"));
+        assert_eq!(ice_ufrag("v=0\r\n"), None);
+    }
+}
@@ -0,0 +1,452 @@
+//! MJPEG live streams for legacy clients
+//!
+//! Serves a monitor as `multipart/x-mixed-replace` JPEG parts — what
+//! ZoneMinder's `nph-zms` served, and all that many kiosks, old tablets and
+//! home-automation integrations understand. Frames come from the live
+//! broadcast, decoded and encoded through the same libav path as snapshots.
+//!
+//! One feed (a decoder plus encoder) runs per monitor, frame rate and scale,
+//! however many viewers watch it; each viewer is handed the newest JPEG, so a
+//! slow viewer skips frames instead of holding the others back. When the
+//! camera's keyframes come at least as often as the requested rate only
+//! keyframes are decoded; otherwise every frame is decoded and every Nth
+//! encoded. A feed stops once its last viewer has gone.
+
+use std::sync::Arc;
+use std::time::Duration;
+
+use bytes::Bytes;
+use dashmap::mapref::entry::Entry;
+use dashmap::DashMap;
+use tokio::sync::{broadcast, mpsc, watch};
+use tracing::{debug, info, warn};
+
+use crate::streaming::live::LiveStreamCoordinator;
+use crate::streaming::snapshot::{open_decoder, JpegEncoder, SnapshotError};
+use crate::streaming::source::router::RouterError;
+use crate::streaming::source::{MonitorSource, SourceRouter, VideoCodec, VideoPacket};
+
+/// Multipart boundary. ZoneMinder's own, so clients that hard-code it work.
+pub const BOUNDARY: &str = "ZoneMinderFrame";
+
+/// How often a feed checks for viewers and keeps its reader alive.
+const TICK: Duration = Duration::from_secs(1);
+
+/// Access units buffered for the decoder. Past this the feed drops frames
+/// and resumes at the next keyframe.
+const DECODE_QUEUE: usize = 8;
+
+/// What a viewer asked for; viewers asking for the same thing share a feed.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub struct MjpegKey {
+    pub monitor_id: u32,
+    /// Frames per second, at least 1.
+    pub fps: u32,
+    /// Output size as a percentage of the camera's, 1–100.
+    pub scale: u32,
+}
+
+/// One shared decoder, fanned out to its viewers.
+struct Feed {
+    frames: watch::Sender<Option<Bytes>>,
+}
+
+/// Runs the MJPEG feeds.
+pub struct MjpegService {
+    source_router: Arc<SourceRouter>,
+    live_coordinator: Option<Arc<LiveStreamCoordinator>>,
+    /// Monitors whose readers are kept hot regardless of viewers.
+    prewarm_monitors: Vec<u32>,
+    feeds: Arc<DashMap<MjpegKey, Arc<Feed>>>,
+}
+
+impl MjpegService {
+    pub fn new(
+        source_router: Arc<SourceRouter>,
+        live_coordinator: Option<Arc<LiveStreamCoordinator>>,
+        prewarm_monitors: Vec<u32>,
+    ) -> Self {
+        Self {
+            source_router,
+            live_coordinator,
+            prewarm_monitors,
+            feeds: Arc::new(DashMap::new()),
+        }
+    }
+
+    /// Join the feed for `key`, starting it if nobody is watching yet. The
+    /// receiver sees each new JPEG; it closes when the feed's source goes.
+    pub async fn subscribe(
+        &self,
+        key: MjpegKey,
+    ) -> Result<watch::Receiver<Option<Bytes>>, SnapshotError> {
+        if let Some(feed) = self.feeds.get(&key) {
+            return Ok(feed.frames.subscribe());
+        }
+
+        let source = self
+            .source_router
+            .get_source(key.monitor_id)
+            .await
+            .map_err(|e| match e {
+                RouterError::SocketNotFound(_) => SnapshotError::SourceNotAvailable(key.monitor_id),
+                other => SnapshotError::RouterError(other),
+            })?;
+
+        // Subscribing under the entry lock pairs with the feed's own
+        // `remove_if`, so a viewer never joins a feed that is shutting down.
+        let frames = match self.feeds.entry(key) {
+            Entry::Occupied(entry) => entry.get().frames.subscribe(),
+            Entry::Vacant(entry) => {
+                let (tx, rx) = watch::channel(None);
+                let feed = Arc::new(Feed { frames: tx });
+                entry.insert(Arc::clone(&feed));
+                info!(
+                    "Starting MJPEG feed for monitor {} ({} fps, {}%)",
+                    key.monitor_id, key.fps, key.scale
+                );
+                tokio::spawn(run_feed(
+                    key,
+                    source,
+                    feed,
+                    Arc::clone(&self.feeds),
+                    Arc::clone(&self.source_router),
+                    self.live_coordinator.clone(),
+                    self.prewarm_monitors.contains(&key.monitor_id),
+                ));
+                rx
+            }
+        };
+        Ok(frames)
+    }
+
+    /// Number of running feeds
+    pub fn feed_count(&self) -> usize {
+        self.feeds.len()
+    }
+}
+
+/// Frame one JPEG as a multipart part.
+pub fn multipart_part(jpeg: &[u8]) -> Bytes {
+    let header = format!(
+        "--{BOUNDARY}\r\nContent-Type: image/jpeg\r\nContent-Length: {}\r\n\r\n",
+        jpeg.len()
+    );
+    let mut part = Vec::with_capacity(header.len() + jpeg.len() + 2);
+    part.extend_from_slice(header.as_bytes());
+    part.extend_from_slice(jpeg);
+    part.extend_from_slice(b"\r\n");
+    Bytes::from(part)
+}
+
+/// Output size for `scale` percent of `width`x`height`. libswscale needs even
+/// dimensions for the YUV420 JPEG target.
+fn scaled_size(width: u32, height: u32, scale: u32) -> (u32, u32) {
+    let scale = |v: u32| ((u64::from(v) * u64::from(scale) / 100) as u32).max(2) & !1;
+    (scale(width), scale(height))
+}
+
+/// Pump the monitor's broadcast into the feed's decoder until the last
+/// viewer leaves or the source goes away.
+async fn run_feed(
+    key: MjpegKey,
+    mut source: Arc<MonitorSource>,
+    feed: Arc<Feed>,
+    feeds: Arc<DashMap<MjpegKey, Arc<Feed>>>,
+    router: Arc<SourceRouter>,
+    live_coordinator: Option<Arc<LiveStreamCoordinator>>,
+    prewarmed: bool,
+) {
+    let monitor_id = key.monitor_id;
+    let (au_tx, au_rx) = mpsc::channel(DECODE_QUEUE);
+    let decoder = {
+        let feed = Arc::clone(&feed);
+        tokio::task::spawn_blocking(move || decode_feed(key, au_rx, feed))
+    };
+
+    let mut video_rx = source.subscribe_video();
+    let mut assembler = AccessUnitAssembler::default();
+    let mut selector = FrameSelector::new(key.fps);
+    let mut tick = tokio::time::interval(TICK);
+
+    loop {
+        tokio::select! {
+            packet = video_rx.recv() => match packet {
+                Ok(packet) => {
+                    let Some(au) = assembler.push(&packet) else { continue };
+                    if !selector.accept(&au) {
+                        continue;
+                    }
+                    match au_tx.try_send(au) {
+                        Ok(()) => {}
+                        Err(mpsc::error::TrySendError::Full(_)) => selector.resync(),
+                        Err(mpsc::error::TrySendError::Closed(_)) => break,
+                    }
+                }
+                Err(broadcast::error::RecvError::Lagged(n)) => {
+                    debug!("MJPEG feed of monitor {monitor_id} lagged {n} packets");
+                    selector.resync();
+                }
+                Err(broadcast::error::RecvError::Closed) => break,
+            },
+            _ = tick.tick() => {
+                if feeds
+                    .remove_if(&key, |_, f| {
+                        Arc::ptr_eq(f, &feed) && f.frames.receiver_count() == 0
+                    })
+                    .is_some()
+                {
+                    break;
+                }
+                // The HLS idle reaper stops readers it started, and a source
+                // can be replaced outright; keep ours alive, as RTSP does.
+                match router.get_existing_source(monitor_id) {
+                    Some(current) if Arc::ptr_eq(&current, &source) => {
+                        let _ = router.start_reader(monitor_id).await;
+                    }
+                    _ => match router.get_source(monitor_id).await {
+                        Ok(current) => {
+                            source = current;
+                            video_rx = source.subscribe_video();
+                            selector.resync();
+                        }
+                        Err(e) => debug!("MJPEG source for monitor {monitor_id} unavailable: {e}"),
+                    },
+                }
+            }
+        }
+    }
+
+    feeds.remove_if(&key, |_, f| Arc::ptr_eq(f, &feed));
+    drop(au_tx);
+    let _ = decoder.await;
+    info!("Stopped MJPEG feed for monitor {monitor_id}");
+
+    // Stop the reader when nothing else is using it, as RTSP teardown does.
+    drop(video_rx);
+    let in_use = source.video_subscriber_count() > 0
+        || source.audio_subscriber_count() > 0
+        || source.event_subscriber_count() > 0
+        || prewarmed;
+    drop(source);
+    let live = match &live_coordinator {
+        Some(coordinator) => coordinator.has_session(monitor_id).await,
+        None => false,
+    };
+    if !in_use && !live {
+        let _ = router.stop_reader(monitor_id).await;
+    }
+}
+
+/// Decode access units and publish a JPEG at most every `1/fps` seconds.
+/// Runs on a blocking thread: libav is synchronous.
+fn decode_feed(key: MjpegKey, mut aus: mpsc::Receiver<AccessUnit>, feed: Arc<Feed>) {
+    let min_gap_us = frame_interval_us(key.fps) * 9 / 10;
+    let mut current: Option<(VideoCodec, ffmpeg_next::codec::decoder::Video)> = None;
+    let mut encoder = JpegEncoder::new();
+    let mut frame = ffmpeg_next::frame::Video::empty();
+    let mut last_emit_us: Option<i64> = None;
+
+    while let Some(au) = aus.blocking_recv() {
+        let decoder = match &mut current {
+            Some((codec, decoder)) if *codec == au.codec => decoder,
+            slot => match open_decoder(au.codec) {
+                Ok(opened) => &mut slot.insert((au.codec, opened)).1,
+                Err(e) => {
+                    warn!("MJPEG feed of monitor {}: {}", key.monitor_id, e);
+                    return;
+                }
+            },
+        };
+
+        let mut packet = ffmpeg_next::Packet::copy(&au.data);
+        packet.set_pts(Some(au.timestamp_us));
+        packet.set_dts(Some(au.timestamp_us));
+        if let Err(e) = decoder.send_packet(&packet) {
+            debug!("MJPEG feed of monitor {}: {}", key.monitor_id, e);
+            continue;
+        }
+
+        while decoder.receive_frame(&mut frame).is_ok() {
+            let ts = frame.pts().unwrap_or(au.timestamp_us);
+            // A timestamp going backwards is a source restart, not a frame
+            // to skip.
+            if last_emit_us.is_some_and(|last| ts >= last && ts - last < min_gap_us) {
+                continue;
+            }
+            let (width, height) = scaled_size(frame.width(), frame.height(), key.scale);
+            match encoder.encode(&frame, width, height) {
+                Ok(jpeg) => {
+                    last_emit_us = Some(ts);
+                    feed.frames.send_replace(Some(Bytes::from(jpeg)));
+                }
+                Err(e) => debug!("MJPEG feed of monitor {}: {}", key.monitor_id, e),
+            }
+        }
+    }
+}
+
+fn frame_interval_us(fps: u32) -> i64 {
+    1_000_000 / i64::from(fps.max(1))
+}
+
+/// One coded picture: every NAL of an access unit, parameter sets included.
+#[derive(Debug)]
+struct AccessUnit {
+    data: Vec<u8>,
+    timestamp_us: i64,
+    is_keyframe: bool,
+    codec: VideoCodec,
+}
+
+/// Groups the broadcast's per-NAL packets into access units. All NALs of an
+/// access unit share its timestamp, so one is complete when the next begins.
+#[derive(Default)]
+struct AccessUnitAssembler {
+    current: Option<AccessUnit>,
+}
+
+impl AccessUnitAssembler {
+    fn push(&mut self, packet: &VideoPacket) -> Option<AccessUnit> {
+        if let Some(au) = &mut self.current {
+            if au.timestamp_us == packet.timestamp_us {
+                au.data.extend_from_slice(&packet.data);
+                au.is_keyframe |= packet.is_keyframe;
+                return None;
+            }
+        }
+        self.current.replace(AccessUnit {
+            data: packet.data.clone(),
+            timestamp_us: packet.timestamp_us,
+            is_keyframe: packet.is_keyframe,
+            codec: packet.codec,
+        })
+    }
+}
+
+/// Decides which access units the decoder needs.
+struct FrameSelector {
+    interval_us: i64,
+    last_keyframe_us: Option<i64>,
+    /// Keyframes alone meet the frame rate: skip decoding the rest.
+    keyframes_only: bool,
+    /// Decoding must (re)start at a keyframe.
+    need_keyframe: bool,
+}
+
+impl FrameSelector {
+    fn new(fps: u32) -> Self {
+        Self {
+            interval_us: frame_interval_us(fps),
+            last_keyframe_us: None,
+            keyframes_only: false,
+            need_keyframe: true,
+        }
+    }
+
+    fn accept(&mut self, au: &AccessUnit) -> bool {
+        if au.is_keyframe {
+            if let Some(last) = self.last_keyframe_us {
+                let gop = au.timestamp_us - last;
+                self.keyframes_only = gop > 0 && gop <= self.interval_us;
+            }
+            self.last_keyframe_us = Some(au.timestamp_us);
+            self.need_keyframe = false;
+            return true;
+        }
+        !self.need_keyframe && !self.keyframes_only
+    }
+
+    /// Frames were lost; wait for the next keyframe.
+    fn resync(&mut self) {
+        self.need_keyframe = true;
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn packet(nal_type: u8, ts: i64, is_keyframe: bool) -> VideoPacket {
+        VideoPacket {
+            monitor_id: 1,
+            timestamp_us: ts,
+            data: vec![0x00, 0x00, 0x00, 0x01, nal_type, 0xAA],
+            is_keyframe,
+            codec: VideoCodec::H264,
+        }
+    }
+
+    fn au(ts: i64, is_keyframe: bool) -> AccessUnit {
+        AccessUnit {
+            data: Vec::new(),
+            timestamp_us: ts,
+            is_keyframe,
+            codec: VideoCodec::H264,
+        }
+    }
+
+    #[test]
+    fn test_assembler_groups_nals_by_timestamp() {
+        let mut assembler = AccessUnitAssembler::default();
+        assert!(assembler.push(&packet(0x67, 0, false)).is_none());
+        assert!(assembler.push(&packet(0x68, 0, false)).is_none());
+        assert!(assembler.push(&packet(0x65, 0, true)).is_none());
+
+        let idr = assembler.push(&packet(0x41, 40_000, false)).unwrap();
+        assert_eq!(idr.timestamp_us, 0);
+        assert!(idr.is_keyframe);
+        assert_eq!(idr.data.len(), 18);
+
+        let p = assembler.push(&packet(0x41, 80_000, false)).unwrap();
+        assert_eq!(p.timestamp_us, 40_000);
+        assert!(!p.is_keyframe);
+    }
+
+    #[test]
+    fn test_selector_decodes_everything_when_keyframes_are_sparse() {
+        // 5 fps wanted, keyframes every 2s: every frame must be decoded.
+        let mut selector = FrameSelector::new(5);
+        assert!(!selector.accept(&au(0, false)), "must start at a keyframe");
+        assert!(selector.accept(&au(40_000, true)));
+        assert!(selector.accept(&au(80_000, false)));
+        assert!(selector.accept(&au(2_040_000, true)));
+        assert!(selector.accept(&au(2_080_000, false)));
+    }
+
+    #[test]
+    fn test_selector_decodes_keyframes_only_when_they_suffice() {
+        // 1 fps wanted, keyframes every second: delta frames are skipped.
+        let mut selector = FrameSelector::new(1);
+        assert!(selector.accept(&au(0, true)));
+        assert!(selector.accept(&au(40_000, false)), "GOP not measured yet");
+        assert!(selector.accept(&au(1_000_000, true)));
+        assert!(!selector.accept(&au(1_040_000, false)));
+        assert!(selector.accept(&au(2_000_000, true)));
+    }
+
+    #[test]
+    fn test_selector_resync_waits_for_keyframe() {
+        let mut selector = FrameSelector::new(10);
+        assert!(selector.accept(&au(0, true)));
+        selector.resync();
+        assert!(!selector.accept(&au(40_000, false)));
+        assert!(selector.accept(&au(2_000_000, true)));
+        assert!(selector.accept(&au(2_040_000, false)));
+    }
+
+    #[test]
+    fn test_scaled_size_is_even() {
+        assert_eq!(scaled_size(1920, 1080, 100), (1920, 1080));
+        assert_eq!(scaled_size(1920, 1080, 50), (960, 540));
+        assert_eq!(scaled_size(1280, 720, 33), (422, 236));
+        assert_eq!(scaled_size(640, 360, 1), (6, 2));
+    }
+
+    #[test]
+    fn test_multipart_part_framing() {
+        let part = multipart_part(&[0xFF, 0xD8, 0xFF, 0xD9]);
+        let expected = b"--ZoneMinderFrame\r\nContent-Type: image/jpeg\r\nContent-Length: 4\r\n\r\n\xFF\xD8\xFF\xD9\r\n";
+        assert_eq!(&part[..], &expected[..]);
+    }
+}
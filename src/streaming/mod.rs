@@ -1,6 +1,10 @@
+pub mod clip;
 pub mod hls;
 pub mod live;
+pub mod mjpeg;
 pub mod probe;
+pub mod rtsp;
 pub mod snapshot;
 pub mod source;
+pub mod talkback;
 pub mod webrtc;
@@ -0,0 +1,128 @@
+//! Digest nonces for the RTSP server (RFC 2617, as RFC 2326 §D.2 reuses it).
+//!
+//! The header parsing and response check live in [`crate::util::digest`]; the
+//! server side needs `HA1 = MD5(username:realm:password)`, which zm-api
+//! captures into `rtsp_credentials` whenever it sees a plaintext password (see
+//! [`crate::service::rtsp_credentials`]). Both the RFC 2069 form (no `qop`, as
+//! live555-based clients send) and `qop=auth` are accepted.
+//!
+//! Nonces are stateless: the issue time plus an HMAC of it under a per-process
+//! secret, so no table of outstanding nonces is kept and a restart simply makes
+//! clients re-challenge.
+
+use hmac::{Hmac, Mac};
+use rand::RngCore as _;
+use sha2::Sha256;
+
+/// How long a nonce stays valid. A client past it is re-challenged with
+/// `stale=true` and retries without prompting for credentials.
+const NONCE_TTL_SECS: i64 = 300;
+
+/// Outcome of checking a nonce the client echoed back.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum NonceCheck {
+    Valid,
+    /// Ours, but expired: re-challenge with `stale=true`.
+    Stale,
+    /// Not issued by this process.
+    Invalid,
+}
+
+/// Issues and checks nonces for one realm.
+pub struct DigestAuth {
+    realm: String,
+    secret: [u8; 32],
+}
+
+impl DigestAuth {
+    pub fn new(realm: impl Into<String>) -> Self {
+        let mut secret = [0u8; 32];
+        rand::rng().fill_bytes(&mut secret);
+        Self {
+            realm: realm.into(),
+            secret,
+        }
+    }
+
+    pub fn realm(&self) -> &str {
+        &self.realm
+    }
+
+    /// The `WWW-Authenticate` value for a 401 issued at `now` (unix seconds).
+    pub fn challenge(&self, now: i64, stale: bool) -> String {
+        let mut value = format!(
+            "Digest realm=\"{}\", nonce=\"{}\", algorithm=MD5",
+            self.realm,
+            self.nonce_at(now)
+        );
+        if stale {
+            value.push_str(", stale=true");
+        }
+        value
+    }
+
+    fn nonce_at(&self, issued: i64) -> String {
+        let stamp = format!("{issued:x}");
+        format!("{stamp}{}", self.tag(&stamp))
+    }
+
+    fn tag(&self, stamp: &str) -> String {
+        let mut mac =
+            Hmac::<Sha256>::new_from_slice(&self.secret).expect("HMAC accepts any key length");
+        mac.update(stamp.as_bytes());
+        hex::encode(&mac.finalize().into_bytes()[..16])
+    }
+
+    pub fn check_nonce(&self, nonce: &str, now: i64) -> NonceCheck {
+        // 32 hex chars of tag after a variable-length hex timestamp.
+        let Some(split) = nonce.len().checked_sub(32).filter(|n| *n > 0) else {
+            return NonceCheck::Invalid;
+        };
+        let (stamp, tag) = nonce.split_at(split);
+        let expected = self.tag(stamp);
+        if !openssl::memcmp::eq(expected.as_bytes(), tag.as_bytes()) {
+            return NonceCheck::Invalid;
+        }
+        match i64::from_str_radix(stamp, 16) {
+            Ok(issued) if now - issued <= NONCE_TTL_SECS && issued <= now => NonceCheck::Valid,
+            Ok(_) => NonceCheck::Stale,
+            Err(_) => NonceCheck::Invalid,
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn nonces_expire_and_cannot_be_forged() {
+        let auth = DigestAuth::new("zm-api");
+        let nonce = auth.nonce_at(1_000);
+        assert_eq!(auth.check_nonce(&nonce, 1_000), NonceCheck::Valid);
+        assert_eq!(
+            auth.check_nonce(&nonce, 1_000 + NONCE_TTL_SECS),
+            NonceCheck::Valid
+        );
+        assert_eq!(
+            auth.check_nonce(&nonce, 1_001 + NONCE_TTL_SECS),
+            NonceCheck::Stale
+        );
+        // Another process's secret does not validate.
+        let other = DigestAuth::new("zm-api");
+        assert_eq!(other.check_nonce(&nonce, 1_000), NonceCheck::Invalid);
+        // Nor does a rewound timestamp under the original tag.
+        let forged = format!("{:x}{}", 2_000, &nonce[nonce.len() - 32..]);
+        assert_eq!(auth.check_nonce(&forged, 2_000), NonceCheck::Invalid);
+        assert_eq!(auth.check_nonce("short", 1_000), NonceCheck::Invalid);
+    }
+
+    #[test]
+    fn challenge_names_realm_and_staleness() {
+        let auth = DigestAuth::new("zm-api");
+        let fresh = auth.challenge(1_000, false);
+        assert!(fresh.starts_with("Digest realm=\"zm-api\", nonce=\""));
+        assert!(!fresh.contains("stale"));
+        assert!(auth.challenge(1_000, true).ends_with(", stale=true"));
+    }
+}
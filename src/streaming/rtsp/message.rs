@@ -0,0 +1,513 @@
+//! RTSP/1.0 message framing (RFC 2326 §4–§12).
+//!
+//! The server reads from one TCP stream that carries both text requests and,
+//! once a client plays over TCP, `$`-prefixed interleaved binary frames (RTCP
+//! receiver reports, in practice). [`parse`] takes whichever comes next off a
+//! buffer and reports how many bytes it used, so the caller can keep reading
+//! until a whole message has arrived.
+
+use std::fmt::Write as _;
+
+/// Upper bound on a request's start line plus headers.
+const MAX_HEADER_BYTES: usize = 8 * 1024;
+/// Upper bound on a request body (clients only send them for SET_PARAMETER).
+const MAX_BODY_BYTES: usize = 64 * 1024;
+
+#[derive(Debug, thiserror::Error, PartialEq, Eq)]
+pub enum MessageError {
+    #[error("malformed RTSP request")]
+    Malformed,
+    #[error("RTSP request too large")]
+    TooLarge,
+}
+
+/// One request from the client.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Request {
+    pub method: String,
+    pub uri: String,
+    headers: Vec<(String, String)>,
+    pub body: Vec<u8>,
+}
+
+impl Request {
+    /// A header's value; names are case-insensitive.
+    pub fn header(&self, name: &str) -> Option<&str> {
+        self.headers
+            .iter()
+            .find(|(k, _)| k.eq_ignore_ascii_case(name))
+            .map(|(_, v)| v.as_str())
+    }
+
+    pub fn cseq(&self) -> Option<&str> {
+        self.header("CSeq")
+    }
+}
+
+/// What came off the wire next.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum Frame {
+    Request(Request),
+    /// An interleaved binary frame (RFC 2326 §10.12).
+    Interleaved {
+        channel: u8,
+        payload: Vec<u8>,
+    },
+}
+
+/// Parse the next frame at the start of `buf`. `Ok(None)` means more bytes are
+/// needed; on success the second value is how many bytes were consumed.
+pub fn parse(buf: &[u8]) -> Result<Option<(Frame, usize)>, MessageError> {
+    if buf.first() == Some(&b'$') {
+        if buf.len() < 4 {
+            return Ok(None);
+        }
+        let len = u16::from_be_bytes([buf[2], buf[3]]) as usize;
+        if buf.len() < 4 + len {
+            return Ok(None);
+        }
+        let frame = Frame::Interleaved {
+            channel: buf[1],
+            payload: buf[4..4 + len].to_vec(),
+        };
+        return Ok(Some((frame, 4 + len)));
+    }
+
+    let Some(head_len) = find_header_end(buf) else {
+        return if buf.len() > MAX_HEADER_BYTES {
+            Err(MessageError::TooLarge)
+        } else {
+            Ok(None)
+        };
+    };
+    if head_len > MAX_HEADER_BYTES {
+        return Err(MessageError::TooLarge);
+    }
+    let head = std::str::from_utf8(&buf[..head_len]).map_err(|_| MessageError::Malformed)?;
+    let mut lines = head.split("\r\n").filter(|l| !l.is_empty());
+
+    let start = lines.next().ok_or(MessageError::Malformed)?;
+    let mut parts = start.split(' ');
+    let (Some(method), Some(uri), Some(version), None) =
+        (parts.next(), parts.next(), parts.next(), parts.next())
+    else {
+        return Err(MessageError::Malformed);
+    };
+    if !version.starts_with("RTSP/") || method.is_empty() || uri.is_empty() {
+        return Err(MessageError::Malformed);
+    }
+
+    let mut headers = Vec::new();
+    for line in lines {
+        let (name, value) = line.split_once(':').ok_or(MessageError::Malformed)?;
+        headers.push((name.trim().to_string(), value.trim().to_string()));
+    }
+    let mut request = Request {
+        method: method.to_string(),
+        uri: uri.to_string(),
+        headers,
+        body: Vec::new(),
+    };
+
+    let body_len = match request.header("Content-Length") {
+        Some(v) => v.parse::<usize>().map_err(|_| MessageError::Malformed)?,
+        None => 0,
+    };
+    if body_len > MAX_BODY_BYTES {
+        return Err(MessageError::TooLarge);
+    }
+    if buf.len() < head_len + body_len {
+        return Ok(None);
+    }
+    request.body = buf[head_len..head_len + body_len].to_vec();
+    Ok(Some((Frame::Request(request), head_len + body_len)))
+}
+
+/// A response from an RTSP server, for the few places zm-api is the client
+/// (the ONVIF talkback backchannel).
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Reply {
+    pub status: u16,
+    headers: Vec<(String, String)>,
+    pub body: Vec<u8>,
+}
+
+impl Reply {
+    /// A header's value; names are case-insensitive.
+    pub fn header(&self, name: &str) -> Option<&str> {
+        self.headers
+            .iter()
+            .find(|(k, _)| k.eq_ignore_ascii_case(name))
+            .map(|(_, v)| v.as_str())
+    }
+
+    /// Every value of a header that may repeat (`WWW-Authenticate`).
+    pub fn headers<'a>(&'a self, name: &'a str) -> impl Iterator<Item = &'a str> + 'a {
+        self.headers
+            .iter()
+            .filter(move |(k, _)| k.eq_ignore_ascii_case(name))
+            .map(|(_, v)| v.as_str())
+    }
+}
+
+/// Parse the response at the start of `buf`, as [`parse`] does requests.
+/// Interleaved frames are not responses; the caller skips them with [`parse`].
+pub fn parse_reply(buf: &[u8]) -> Result<Option<(Reply, usize)>, MessageError> {
+    let Some(head_len) = find_header_end(buf) else {
+        return if buf.len() > MAX_HEADER_BYTES {
+            Err(MessageError::TooLarge)
+        } else {
+            Ok(None)
+        };
+    };
+    if head_len > MAX_HEADER_BYTES {
+        return Err(MessageError::TooLarge);
+    }
+    let head = std::str::from_utf8(&buf[..head_len]).map_err(|_| MessageError::Malformed)?;
+    let mut lines = head.split("\r\n").filter(|l| !l.is_empty());
+
+    // `RTSP/1.0 200 OK` — the reason phrase may contain spaces.
+    let start = lines.next().ok_or(MessageError::Malformed)?;
+    let mut parts = start.splitn(3, ' ');
+    let (Some(version), Some(status)) = (parts.next(), parts.next()) else {
+        return Err(MessageError::Malformed);
+    };
+    if !version.starts_with("RTSP/") {
+        return Err(MessageError::Malformed);
+    }
+    let status = status.parse::<u16>().map_err(|_| MessageError::Malformed)?;
+
+    let mut headers = Vec::new();
+    for line in lines {
+        let (name, value) = line.split_once(':').ok_or(MessageError::Malformed)?;
+        headers.push((name.trim().to_string(), value.trim().to_string()));
+    }
+    let mut reply = Reply {
+        status,
+        headers,
+        body: Vec::new(),
+    };
+
+    let body_len = match reply.header("Content-Length") {
+        Some(v) => v.parse::<usize>().map_err(|_| MessageError::Malformed)?,
+        None => 0,
+    };
+    if body_len > MAX_BODY_BYTES {
+        return Err(MessageError::TooLarge);
+    }
+    if buf.len() < head_len + body_len {
+        return Ok(None);
+    }
+    reply.body = buf[head_len..head_len + body_len].to_vec();
+    Ok(Some((reply, head_len + body_len)))
+}
+
+/// Offset just past the blank line ending the headers.
+fn find_header_end(buf: &[u8]) -> Option<usize> {
+    buf.windows(4).position(|w| w == b"\r\n\r\n").map(|i| i + 4)
+}
+
+/// A response to send back.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Response {
+    pub status: u16,
+    headers: Vec<(String, String)>,
+    body: Vec<u8>,
+}
+
+impl Response {
+    pub fn new(status: u16) -> Self {
+        Self {
+            status,
+            headers: Vec::new(),
+            body: Vec::new(),
+        }
+    }
+
+    pub fn header(mut self, name: &str, value: impl Into<String>) -> Self {
+        self.headers.push((name.to_string(), value.into()));
+        self
+    }
+
+    pub fn body(mut self, content_type: &str, body: impl Into<Vec<u8>>) -> Self {
+        self.body = body.into();
+        self.header("Content-Type", content_type)
+    }
+
+    /// Serialise, echoing the request's `CSeq` as every response must.
+    pub fn to_bytes(&self, cseq: Option<&str>) -> Vec<u8> {
+        let mut head = format!("RTSP/1.0 {} {}\r\n", self.status, reason(self.status));
+        if let Some(cseq) = cseq {
+            let _ = write!(head, "CSeq: {cseq}\r\n");
+        }
+        head.push_str("Server: zm-api\r\n");
+        for (name, value) in &self.headers {
+            let _ = write!(head, "{name}: {value}\r\n");
+        }
+        if !self.body.is_empty() {
+            let _ = write!(head, "Content-Length: {}\r\n", self.body.len());
+        }
+        head.push_str("\r\n");
+        let mut out = head.into_bytes();
+        out.extend_from_slice(&self.body);
+        out
+    }
+}
+
+fn reason(status: u16) -> &'static str {
+    match status {
+        200 => "OK",
+        400 => "Bad Request",
+        401 => "Unauthorized",
+        403 => "Forbidden",
+        404 => "Not Found",
+        405 => "Method Not Allowed",
+        453 => "Not Enough Bandwidth",
+        454 => "Session Not Found",
+        455 => "Method Not Valid in This State",
+        459 => "Aggregate Operation Not Allowed",
+        461 => "Unsupported Transport",
+        500 => "Internal Server Error",
+        501 => "Not Implemented",
+        503 => "Service Unavailable",
+        505 => "RTSP Version Not Supported",
+        _ => "Unknown",
+    }
+}
+
+/// Frame `payload` for sending interleaved on `channel`.
+pub fn interleaved(channel: u8, payload: &[u8]) -> Vec<u8> {
+    let mut out = Vec::with_capacity(4 + payload.len());
+    out.push(b'$');
+    out.push(channel);
+    out.extend_from_slice(&(payload.len() as u16).to_be_bytes());
+    out.extend_from_slice(payload);
+    out
+}
+
+/// What a request URI addresses: a monitor, optionally one of its tracks.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct Target {
+    pub monitor_id: u32,
+    pub track: Option<usize>,
+}
+
+/// Resolve `rtsp://host[:port]/monitor/{id}[/trackID={n}]`. The scheme and
+/// authority are optional, since some clients send an absolute path; a
+/// trailing slash and query string are ignored.
+pub fn parse_target(uri: &str) -> Option<Target> {
+    let path = match uri.split_once("://") {
+        Some((_, rest)) => rest.find('/').map_or("", |i| &rest[i..]),
+        None => uri,
+    };
+    let path = path.split('?').next().unwrap_or_default();
+    let mut segments = path.split('/').filter(|s| !s.is_empty());
+    if segments.next() != Some("monitor") {
+        return None;
+    }
+    let monitor_id = segments.next()?.parse().ok()?;
+    let track = match segments.next() {
+        None => None,
+        Some(seg) => Some(seg.strip_prefix("trackID=")?.parse().ok()?),
+    };
+    if segments.next().is_some() {
+        return None;
+    }
+    Some(Target { monitor_id, track })
+}
+
+/// How the client asked to receive one track.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum TransportSpec {
+    /// RTP and RTCP to these client ports over UDP.
+    Udp { client_rtp: u16, client_rtcp: u16 },
+    /// Interleaved on the RTSP connection; the client may leave the channel
+    /// choice to us.
+    Tcp { channel: Option<u8> },
+}
+
+/// Choose the first of the client's comma-separated transport alternatives
+/// the server allows. Only unicast RTP/AVP is offered.
+pub fn parse_transport(header: &str, allow_udp: bool, allow_tcp: bool) -> Option<TransportSpec> {
+    header.split(',').find_map(|spec| {
+        let mut params = spec.split(';').map(str::trim);
+        let profile = params.next()?.to_ascii_uppercase();
+        let params: Vec<&str> = params.collect();
+        if params.iter().any(|p| p.eq_ignore_ascii_case("multicast")) {
+            return None;
+        }
+        let value = |key: &str| {
+            params.iter().find_map(|p| {
+                let (k, v) = p.split_once('=')?;
+                k.trim().eq_ignore_ascii_case(key).then(|| v.trim())
+            })
+        };
+        match profile.as_str() {
+            "RTP/AVP" | "RTP/AVP/UDP" if allow_udp => {
+                let (rtp, rtcp) = port_pair(value("client_port")?)?;
+                Some(TransportSpec::Udp {
+                    client_rtp: rtp,
+                    client_rtcp: rtcp,
+                })
+            }
+            "RTP/AVP/TCP" if allow_tcp => {
+                let channel = match value("interleaved") {
+                    Some(v) => Some(v.split('-').next()?.parse().ok()?),
+                    None => None,
+                };
+                Some(TransportSpec::Tcp { channel })
+            }
+            _ => None,
+        }
+    })
+}
+
+/// `5000-5001` or a lone `5000` (RTCP then defaults to the next port).
+fn port_pair(v: &str) -> Option<(u16, u16)> {
+    match v.split_once('-') {
+        Some((a, b)) => Some((a.parse().ok()?, b.parse().ok()?)),
+        None => {
+            let a: u16 = v.parse().ok()?;
+            Some((a, a.checked_add(1)?))
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn parses_reply_with_spaced_reason_and_body() {
+        let raw = b"RTSP/1.0 401 Unauthorized Access\r\nCSeq: 2\r\nWWW-Authenticate: Basic realm=\"c\"\r\nWWW-Authenticate: Digest realm=\"c\", nonce=\"n\"\r\nContent-Length: 3\r\n\r\nab";
+        assert_eq!(parse_reply(raw), Ok(None));
+        let mut full = raw.to_vec();
+        full.extend_from_slice(b"c$\x00");
+        let (reply, used) = parse_reply(&full).unwrap().unwrap();
+        assert_eq!(used, full.len() - 2);
+        assert_eq!(reply.status, 401);
+        assert_eq!(reply.header("cseq"), Some("2"));
+        assert_eq!(reply.headers("WWW-Authenticate").count(), 2);
+        assert_eq!(reply.body, b"abc");
+        assert_eq!(
+            parse_reply(b"OPTIONS rtsp://h RTSP/1.0\r\n\r\n"),
+            Err(MessageError::Malformed)
+        );
+    }
+
+    #[test]
+    fn parses_request_and_waits_for_body() {
+        let raw =
+            b"SET_PARAMETER rtsp://h/monitor/1 RTSP/1.0\r\nCSeq: 4\r\ncontent-length: 5\r\n\r\nab";
+        assert_eq!(parse(raw), Ok(None));
+        let mut full = raw.to_vec();
+        full.extend_from_slice(b"cde");
+        full.extend_from_slice(b"OPTIONS"); // start of the next request
+        let (frame, used) = parse(&full).unwrap().unwrap();
+        assert_eq!(used, full.len() - "OPTIONS".len());
+        let Frame::Request(req) = frame else {
+            panic!("expected a request")
+        };
+        assert_eq!(req.method, "SET_PARAMETER");
+        assert_eq!(req.cseq(), Some("4"));
+        assert_eq!(req.header("Content-Length"), Some("5"));
+        assert_eq!(req.body, b"abcde");
+    }
+
+    #[test]
+    fn parses_interleaved_frames() {
+        assert_eq!(parse(b"$\x01\x00\x03ab"), Ok(None));
+        let (frame, used) = parse(b"$\x01\x00\x03abcXYZ").unwrap().unwrap();
+        assert_eq!(used, 7);
+        assert_eq!(
+            frame,
+            Frame::Interleaved {
+                channel: 1,
+                payload: b"abc".to_vec()
+            }
+        );
+    }
+
+    #[test]
+    fn rejects_garbage_and_oversized_headers() {
+        assert_eq!(parse(b"hello\r\n\r\n"), Err(MessageError::Malformed));
+        assert_eq!(
+            parse(b"OPTIONS * HTTP/1.1\r\n\r\n"),
+            Err(MessageError::Malformed)
+        );
+        let huge = vec![b'a'; MAX_HEADER_BYTES + 1];
+        assert_eq!(parse(&huge), Err(MessageError::TooLarge));
+    }
+
+    #[test]
+    fn response_echoes_cseq_and_sizes_body() {
+        let bytes = Response::new(200)
+            .header("Public", "OPTIONS")
+            .body("application/sdp", "v=0\r\n")
+            .to_bytes(Some("7"));
+        let text = String::from_utf8(bytes).unwrap();
+        assert!(text.starts_with("RTSP/1.0 200 OK\r\nCSeq: 7\r\n"));
+        assert!(text.contains("Content-Type: application/sdp\r\n"));
+        assert!(text.ends_with("Content-Length: 5\r\n\r\nv=0\r\n"));
+    }
+
+    #[test]
+    fn targets_resolve_monitor_and_track() {
+        assert_eq!(
+            parse_target("rtsp://nvr:8554/monitor/7"),
+            Some(Target {
+                monitor_id: 7,
+                track: None
+            })
+        );
+        assert_eq!(
+            parse_target("rtsp://nvr/monitor/7/trackID=1?x=y"),
+            Some(Target {
+                monitor_id: 7,
+                track: Some(1)
+            })
+        );
+        assert_eq!(parse_target("/monitor/7/").map(|t| t.monitor_id), Some(7));
+        assert_eq!(parse_target("rtsp://nvr/monitor/x"), None);
+        assert_eq!(parse_target("rtsp://nvr/monitor/7/audio"), None);
+        assert_eq!(parse_target("rtsp://nvr/other/7"), None);
+        assert_eq!(parse_target("*"), None);
+    }
+
+    #[test]
+    fn transport_picks_first_allowed_alternative() {
+        let both = "RTP/AVP/TCP;unicast;interleaved=2-3, RTP/AVP;unicast;client_port=5000-5001";
+        assert_eq!(
+            parse_transport(both, true, true),
+            Some(TransportSpec::Tcp { channel: Some(2) })
+        );
+        assert_eq!(
+            parse_transport(both, true, false),
+            Some(TransportSpec::Udp {
+                client_rtp: 5000,
+                client_rtcp: 5001
+            })
+        );
+        assert_eq!(
+            parse_transport("RTP/AVP;unicast;client_port=6000", true, true),
+            Some(TransportSpec::Udp {
+                client_rtp: 6000,
+                client_rtcp: 6001
+            })
+        );
+        assert_eq!(
+            parse_transport("RTP/AVP/TCP;unicast", true, true),
+            Some(TransportSpec::Tcp { channel: None })
+        );
+        assert_eq!(
+            parse_transport("RTP/AVP;unicast;client_port=6000", false, true),
+            None
+        );
+        assert_eq!(parse_transport("RTP/AVP;multicast", true, true), None);
+    }
+
+    #[test]
+    fn interleaved_prefixes_channel_and_length() {
+        assert_eq!(interleaved(3, b"ab"), b"$\x03\x00\x02ab");
+    }
+}
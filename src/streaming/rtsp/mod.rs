@@ -0,0 +1,725 @@
+//! Native RTSP re-streaming server.
+//!
+//! Serves every monitor at `rtsp://host:port/monitor/{id}` from the same
+//! [`SourceRouter`] broadcast that feeds WebRTC and HLS, so NVRs, VLC and
+//! home-automation systems share one camera connection instead of each opening
+//! their own. Driven by `[streaming.rtsp_proxy]`.
+//!
+//! * **Transport** — RTP over UDP with server ports from the configured range,
+//!   or interleaved on the RTSP connection (`RTP/AVP/TCP`); `transport`
+//!   restricts which SETUP may negotiate.
+//! * **Auth** — HTTP Digest against `Users` (see [`auth`]). The user needs
+//!   `Stream: View`, and the monitor must pass the row-level monitor ACL;
+//!   monitors outside it answer 404, as the HTTP API does.
+//! * **Sessions** — one per connection, ending when the connection closes or
+//!   on TEARDOWN. `max_sessions` bounds concurrent connections.
+//!
+//! Methods: OPTIONS, DESCRIBE, SETUP, PLAY, TEARDOWN, GET_PARAMETER and
+//! SET_PARAMETER (both accepted as keep-alives). There is no PAUSE — it is a
+//! live stream.
+
+pub mod auth;
+pub mod message;
+pub mod ports;
+pub mod rtp;
+pub mod sdp;
+mod session;
+
+use std::net::SocketAddr;
+use std::sync::Arc;
+use std::time::Duration;
+
+use rand::Rng as _;
+use tokio::io::{AsyncReadExt, AsyncWriteExt};
+use tokio::net::{TcpListener, TcpStream};
+use tokio::sync::{mpsc, OwnedSemaphorePermit, Semaphore};
+use tokio::task::JoinHandle;
+use tracing::{debug, info, warn};
+
+use self::auth::{DigestAuth, NonceCheck};
+use self::message::{Frame, Request, Response, Target, TransportSpec};
+use self::ports::PortAllocator;
+use self::rtp::{AudioPacketizer, VideoPacketizer};
+use self::sdp::{AudioTrack, MediaDescription, VideoTrack};
+use self::session::{Output, Sink};
+use crate::configure::streaming::RtspProxyConfig;
+use crate::server::state::AppState;
+use crate::service::monitor_acl::{self, MonitorScope};
+use crate::streaming::source::{AudioCodec, MonitorSource, RouterError, SourceRouter, VideoCodec};
+use crate::util::authz::{Level, UserPermissions};
+use crate::util::digest::DigestCredentials;
+
+/// Advertised in `Session:`; a UDP client silent on the RTSP connection for
+/// twice this long is dropped.
+const SESSION_TIMEOUT_SECS: u64 = 60;
+/// How long DESCRIBE waits for a cold reader's handshake and first keyframe.
+const DESCRIBE_WAIT: Duration = Duration::from_secs(5);
+/// How long DESCRIBE waits for an AAC frame to read the stream's config from.
+const AAC_CONFIG_WAIT: Duration = Duration::from_secs(2);
+/// Outgoing messages (responses and interleaved RTP) queued per connection.
+const OUTBOUND_QUEUE: usize = 1024;
+
+const PUBLIC_METHODS: &str =
+    "OPTIONS, DESCRIBE, SETUP, PLAY, TEARDOWN, GET_PARAMETER, SET_PARAMETER";
+
+/// The RTSP listener and what every connection shares.
+pub struct RtspServer {
+    state: AppState,
+    router: Arc<SourceRouter>,
+    config: RtspProxyConfig,
+    digest: DigestAuth,
+    ports: Arc<PortAllocator>,
+    sessions: Arc<Semaphore>,
+}
+
+impl RtspServer {
+    /// Bind `[streaming.rtsp_proxy].port` on the API's address and serve in
+    /// the background. `Ok(false)` when the server is disabled or live
+    /// streaming (which it sources from) is off.
+    pub async fn spawn(state: &AppState) -> std::io::Result<bool> {
+        let config = state.config.streaming.rtsp_proxy.clone();
+        if !config.enabled {
+            return Ok(false);
+        }
+        let Some(router) = state.source_router.clone() else {
+            warn!("RTSP server enabled but live streaming is disabled; not starting it");
+            return Ok(false);
+        };
+        let ip = state
+            .config
+            .server
+            .get_socket_addr()
+            .map_err(|e| std::io::Error::new(std::io::ErrorKind::InvalidInput, e))?
+            .ip();
+        let listener = TcpListener::bind(SocketAddr::new(ip, config.port)).await?;
+        info!(
+            "RTSP server listening on rtsp://{}/monitor/{{id}} (transport {}, RTP ports {}-{}, max {} sessions)",
+            listener.local_addr()?,
+            config.transport,
+            config.rtp_port_range_start,
+            config.rtp_port_range_end,
+            config.max_sessions,
+        );
+        let server = Arc::new(Self {
+            state: state.clone(),
+            router,
+            digest: DigestAuth::new(config.realm.clone()),
+            ports: Arc::new(PortAllocator::new(
+                ip,
+                config.rtp_port_range_start,
+                config.rtp_port_range_end,
+            )),
+            sessions: Arc::new(Semaphore::new(config.max_sessions as usize)),
+            config,
+        });
+        tokio::spawn(server.run(listener));
+        crate::service::rtsp_credentials::spawn_orphan_sweep(state);
+        Ok(true)
+    }
+
+    async fn run(self: Arc<Self>, listener: TcpListener) {
+        loop {
+            let (stream, peer) = match listener.accept().await {
+                Ok(accepted) => accepted,
+                Err(e) => {
+                    warn!("RTSP accept failed: {e}");
+                    tokio::time::sleep(Duration::from_millis(100)).await;
+                    continue;
+                }
+            };
+            let _ = stream.set_nodelay(true);
+            let server = Arc::clone(&self);
+            match Arc::clone(&self.sessions).try_acquire_owned() {
+                Ok(permit) => {
+                    tokio::spawn(async move { server.serve(stream, peer, permit).await });
+                }
+                Err(_) => {
+                    debug!("RTSP connection from {peer} refused: max_sessions reached");
+                    tokio::spawn(refuse(stream));
+                }
+            }
+        }
+    }
+
+    async fn serve(
+        self: Arc<Self>,
+        stream: TcpStream,
+        peer: SocketAddr,
+        _permit: OwnedSemaphorePermit,
+    ) {
+        let Ok(local) = stream.local_addr() else {
+            return;
+        };
+        let (mut reader, mut writer) = stream.into_split();
+        let (out, mut outbound) = mpsc::channel::<Vec<u8>>(OUTBOUND_QUEUE);
+        let write_task = tokio::spawn(async move {
+            while let Some(bytes) = outbound.recv().await {
+                if writer.write_all(&bytes).await.is_err() {
+                    break;
+                }
+            }
+        });
+
+        let mut conn = Connection {
+            server: Arc::clone(&self),
+            peer,
+            local,
+            out,
+            user: None,
+            described: None,
+            session: None,
+        };
+        let mut buf = Vec::with_capacity(4096);
+        let mut chunk = [0u8; 4096];
+        'read: loop {
+            let read = if conn.session.as_ref().is_some_and(Session::uses_udp) {
+                let idle = Duration::from_secs(2 * SESSION_TIMEOUT_SECS);
+                match tokio::time::timeout(idle, reader.read(&mut chunk)).await {
+                    Ok(read) => read,
+                    Err(_) => {
+                        debug!("RTSP client {peer} timed out");
+                        break;
+                    }
+                }
+            } else {
+                reader.read(&mut chunk).await
+            };
+            match read {
+                Ok(0) | Err(_) => break,
+                Ok(n) => buf.extend_from_slice(&chunk[..n]),
+            }
+            loop {
+                match message::parse(&buf) {
+                    Ok(Some((frame, used))) => {
+                        buf.drain(..used);
+                        // Interleaved frames from the client are RTCP
+                        // receiver reports; nothing here needs them.
+                        if let Frame::Request(req) = frame {
+                            let resp = conn.handle(&req).await;
+                            if conn.out.send(resp.to_bytes(req.cseq())).await.is_err() {
+                                break 'read;
+                            }
+                        }
+                    }
+                    Ok(None) => break,
+                    Err(e) => {
+                        debug!("RTSP client {peer}: {e}");
+                        let _ = conn.out.send(Response::new(400).to_bytes(None)).await;
+                        break 'read;
+                    }
+                }
+            }
+        }
+
+        conn.teardown().await;
+        drop(conn);
+        let _ = write_task.await;
+    }
+}
+
+/// Answer a connection over `max_sessions` with 503 (echoing its first
+/// request's CSeq) and close it.
+async fn refuse(mut stream: TcpStream) {
+    let mut buf = [0u8; 4096];
+    let read = tokio::time::timeout(Duration::from_secs(5), stream.read(&mut buf)).await;
+    let cseq = match read {
+        Ok(Ok(n)) => match message::parse(&buf[..n]) {
+            Ok(Some((Frame::Request(req), _))) => req.cseq().map(str::to_string),
+            _ => None,
+        },
+        _ => None,
+    };
+    let _ = stream
+        .write_all(&Response::new(503).to_bytes(cseq.as_deref()))
+        .await;
+}
+
+/// An authenticated user, cached for the rest of the connection.
+struct User {
+    id: u32,
+    username: String,
+    ha1: String,
+    scope: MonitorScope,
+}
+
+/// The connection's RTSP session.
+struct Session {
+    id: String,
+    monitor_id: u32,
+    source: Arc<MonitorSource>,
+    media: MediaDescription,
+    /// Per track, its sink once SETUP; taken by PLAY.
+    tracks: Vec<Option<Sink>>,
+    playing: Option<JoinHandle<()>>,
+    udp: bool,
+}
+
+impl Session {
+    fn uses_udp(&self) -> bool {
+        self.udp
+    }
+
+    fn header(&self) -> String {
+        format!("{};timeout={SESSION_TIMEOUT_SECS}", self.id)
+    }
+}
+
+struct Connection {
+    server: Arc<RtspServer>,
+    peer: SocketAddr,
+    local: SocketAddr,
+    out: mpsc::Sender<Vec<u8>>,
+    user: Option<User>,
+    /// The last DESCRIBE's result, reused by the SETUPs that follow it.
+    described: Option<(u32, Arc<MonitorSource>, MediaDescription)>,
+    session: Option<Session>,
+}
+
+impl Connection {
+    async fn handle(&mut self, req: &Request) -> Response {
+        if req.method == "OPTIONS" {
+            return Response::new(200).header("Public", PUBLIC_METHODS);
+        }
+        if !matches!(
+            req.method.as_str(),
+            "DESCRIBE" | "SETUP" | "PLAY" | "TEARDOWN" | "GET_PARAMETER" | "SET_PARAMETER"
+        ) {
+            return Response::new(501).header("Public", PUBLIC_METHODS);
+        }
+        if let Err(resp) = self.authenticate(req).await {
+            return resp;
+        }
+        match req.method.as_str() {
+            "DESCRIBE" => self.describe(req).await,
+            "SETUP" => self.setup(req).await,
+            "PLAY" => self.play(req),
+            "TEARDOWN" => self.teardown_request(req).await,
+            _ => match self.check_session(req) {
+                Ok(()) => self.with_session(Response::new(200)),
+                Err(resp) => resp,
+            },
+        }
+    }
+
+    /// Verify the request's Digest credentials, loading and caching the user
+    /// on first success.
+    async fn authenticate(&mut self, req: &Request) -> Result<(), Response> {
+        let now = chrono::Utc::now().timestamp();
+        let challenge = |stale| {
+            Response::new(401).header("WWW-Authenticate", self.server.digest.challenge(now, stale))
+        };
+        let Some(creds) = req
+            .header("Authorization")
+            .and_then(DigestCredentials::parse)
+        else {
+            return Err(challenge(false));
+        };
+        if creds.realm != self.server.digest.realm() {
+            return Err(challenge(false));
+        }
+        match self.server.digest.check_nonce(&creds.nonce, now) {
+            NonceCheck::Valid => {}
+            NonceCheck::Stale => return Err(challenge(true)),
+            NonceCheck::Invalid => return Err(challenge(false)),
+        }
+
+        if let Some(user) = &self.user {
+            if user.username == creds.username && creds.verify(&user.ha1, &req.method) {
+                return Ok(());
+            }
+            return Err(challenge(false));
+        }
+
+        let user = match self.load_user(&creds.username).await {
+            Ok(Some((user, ha1))) if creds.verify(&ha1, &req.method) => (user, ha1),
+            Ok(_) => {
+                info!(
+                    "RTSP authentication failed for user {} from {}",
+                    creds.username, self.peer
+                );
+                return Err(challenge(false));
+            }
+            Err(e) => {
+                warn!("RTSP user lookup failed: {e}");
+                return Err(Response::new(500));
+            }
+        };
+        let (user, ha1) = user;
+        if UserPermissions::from(&user).stream < Level::View {
+            return Err(Response::new(403));
+        }
+        let scope = monitor_acl::resolve(self.server.state.db(), user.id)
+            .await
+            .map_err(|e| {
+                warn!("RTSP monitor ACL lookup failed: {e}");
+                Response::new(500)
+            })?;
+        debug!(
+            "RTSP user {} authenticated from {}",
+            user.username, self.peer
+        );
+        self.user = Some(User {
+            id: user.id,
+            username: user.username,
+            ha1,
+            scope,
+        });
+        Ok(())
+    }
+
+    /// The enabled user and their cached digest for this realm, if both exist
+    /// and the digest still matches the user's current password.
+    async fn load_user(
+        &self,
+        username: &str,
+    ) -> crate::error::AppResult<Option<(crate::entity::users::Model, String)>> {
+        crate::service::rtsp_credentials::digest_for(
+            self.server.state.db(),
+            username,
+            &self.server.config.realm,
+        )
+        .await
+    }
+
+    /// Resolve the request URI to a monitor the user may view.
+    async fn target(&self, req: &Request) -> Result<Target, Response> {
+        let target = message::parse_target(&req.uri).ok_or_else(|| Response::new(404))?;
+        let Some(user) = &self.user else {
+            return Err(Response::new(401));
+        };
+        crate::service::monitor::get_by_id(&self.server.state, target.monitor_id, &user.scope)
+            .await
+            .map_err(|_| Response::new(404))?;
+        Ok(target)
+    }
+
+    async fn describe(&mut self, req: &Request) -> Response {
+        let target = match self.target(req).await {
+            Ok(t) if t.track.is_none() => t,
+            Ok(_) => return Response::new(404),
+            Err(resp) => return resp,
+        };
+        let (source, media) = match self.media_for(target.monitor_id).await {
+            Ok(found) => found,
+            Err(resp) => return resp,
+        };
+        let session_id: u64 = rand::rng().random();
+        let sdp = media.sdp(session_id, &self.local.ip().to_string(), target.monitor_id);
+        self.described = Some((target.monitor_id, source, media));
+        if let Some(user) = &self.user {
+            info!(
+                "RTSP DESCRIBE monitor {} by user {} ({}) from {}",
+                target.monitor_id, user.username, user.id, self.peer
+            );
+        }
+        Response::new(200)
+            .header("Content-Base", format!("{}/", self.absolute_uri(&req.uri)))
+            .body("application/sdp", sdp)
+    }
+
+    fn absolute_uri(&self, uri: &str) -> String {
+        let uri = uri.trim_end_matches('/');
+        if uri.contains("://") {
+            uri.to_string()
+        } else {
+            format!("rtsp://{}{uri}", self.local)
+        }
+    }
+
+    /// The monitor's source and what it carries, waiting briefly on a cold
+    /// reader for its handshake and first keyframe.
+    async fn media_for(
+        &self,
+        monitor_id: u32,
+    ) -> Result<(Arc<MonitorSource>, MediaDescription), Response> {
+        let source = self
+            .server
+            .router
+            .get_source(monitor_id)
+            .await
+            .map_err(|e| {
+                debug!("RTSP source for monitor {monitor_id} unavailable: {e}");
+                match e {
+                    RouterError::SocketNotFound(_) | RouterError::SourceNotAvailable(_) => {
+                        Response::new(503)
+                    }
+                    _ => Response::new(500),
+                }
+            })?;
+        let info = source
+            .wait_for_stream_info(DESCRIBE_WAIT)
+            .await
+            .ok_or_else(|| Response::new(503))?;
+        if info.video_codec == VideoCodec::Unknown {
+            return Err(Response::new(503));
+        }
+
+        let keyframe = match source.cached_keyframe() {
+            Some(k) => Some(k),
+            None => {
+                let mut rx = source.subscribe_keyframe_cache();
+                tokio::time::timeout(DESCRIBE_WAIT, rx.wait_for(Option::is_some))
+                    .await
+                    .ok()
+                    .and_then(|r| r.ok().and_then(|k| k.clone()))
+            }
+        };
+        let video = match keyframe {
+            Some(k) => VideoTrack::from_keyframe(info.video_codec, &k.keyframe_au),
+            None => VideoTrack {
+                codec: info.video_codec,
+                parameter_sets: Vec::new(),
+            },
+        };
+
+        let audio = match info.audio_codec {
+            Some(AudioCodec::G711Ulaw) => Some(AudioTrack::Pcmu),
+            Some(AudioCodec::G711Alaw) => Some(AudioTrack::Pcma),
+            Some(AudioCodec::Opus) => Some(AudioTrack::Opus),
+            Some(AudioCodec::Aac) => {
+                let mut rx = source.subscribe_audio();
+                let first = tokio::time::timeout(AAC_CONFIG_WAIT, rx.recv()).await;
+                let track = match first {
+                    Ok(Ok(frame)) => AudioTrack::aac_from_adts(&frame.data),
+                    _ => None,
+                };
+                if track.is_none() {
+                    debug!("no AAC config for monitor {monitor_id}; describing video only");
+                }
+                track
+            }
+            Some(AudioCodec::Unknown) | None => None,
+        };
+        Ok((source, MediaDescription { video, audio }))
+    }
+
+    /// Reject a request naming a session other than ours.
+    fn check_session(&self, req: &Request) -> Result<(), Response> {
+        let given = req
+            .header("Session")
+            .map(|s| s.split(';').next().unwrap_or_default().trim());
+        match (given, &self.session) {
+            (None, _) => Ok(()),
+            (Some(id), Some(session)) if id == session.id => Ok(()),
+            _ => Err(Response::new(454)),
+        }
+    }
+
+    fn with_session(&self, resp: Response) -> Response {
+        match &self.session {
+            Some(session) => resp.header("Session", session.header()),
+            None => resp,
+        }
+    }
+
+    async fn setup(&mut self, req: &Request) -> Response {
+        if let Err(resp) = self.check_session(req) {
+            return resp;
+        }
+        let target = match self.target(req).await {
+            Ok(t) => t,
+            Err(resp) => return resp,
+        };
+
+        if let Some(session) = &self.session {
+            if session.monitor_id != target.monitor_id {
+                return Response::new(459);
+            }
+            if session.playing.is_some() {
+                return Response::new(455);
+            }
+        } else {
+            let described = match self.described.take() {
+                Some((id, source, media)) if id == target.monitor_id => (source, media),
+                _ => match self.media_for(target.monitor_id).await {
+                    Ok(found) => found,
+                    Err(resp) => return resp,
+                },
+            };
+            let (source, media) = described;
+            let id: u64 = rand::rng().random();
+            self.session = Some(Session {
+                id: format!("{id:016X}"),
+                monitor_id: target.monitor_id,
+                source,
+                tracks: (0..media.track_count()).map(|_| None).collect(),
+                media,
+                playing: None,
+                udp: false,
+            });
+        }
+        let Some(session) = self.session.as_mut() else {
+            return Response::new(500);
+        };
+
+        // A single-track stream may be set up on the aggregate URI.
+        let track = match target.track {
+            Some(t) => t,
+            None if session.tracks.len() == 1 => 0,
+            None => return Response::new(459),
+        };
+        if track >= session.tracks.len() {
+            return Response::new(404);
+        }
+
+        let config = &self.server.config;
+        let Some(spec) = req
+            .header("Transport")
+            .and_then(|t| message::parse_transport(t, config.allows_udp(), config.allows_tcp()))
+        else {
+            return Response::new(461);
+        };
+        let (sink, transport) = match spec {
+            TransportSpec::Udp {
+                client_rtp,
+                client_rtcp,
+            } => {
+                let Some(ports) = self.server.ports.bind().await else {
+                    warn!("RTSP RTP port range exhausted");
+                    return Response::new(453);
+                };
+                let transport = format!(
+                    "RTP/AVP;unicast;client_port={client_rtp}-{client_rtcp};server_port={}-{}",
+                    ports.rtp_port,
+                    ports.rtp_port + 1
+                );
+                // Media only ever goes to the address the RTSP connection
+                // came from, never a `destination=` the client names.
+                let ip = self.peer.ip();
+                let sink = Sink::Udp {
+                    ports,
+                    client_rtp: SocketAddr::new(ip, client_rtp),
+                    client_rtcp: SocketAddr::new(ip, client_rtcp),
+                };
+                (sink, transport)
+            }
+            TransportSpec::Tcp { channel } => {
+                let channel = channel.unwrap_or(2 * track as u8);
+                let transport = format!(
+                    "RTP/AVP/TCP;unicast;interleaved={channel}-{}",
+                    channel.wrapping_add(1)
+                );
+                let sink = Sink::Tcp {
+                    channel,
+                    out: self.out.clone(),
+                };
+                (sink, transport)
+            }
+        };
+        session.udp |= sink.is_udp();
+        session.tracks[track] = Some(sink);
+        let session_header = session.header();
+        Response::new(200)
+            .header("Transport", transport)
+            .header("Session", session_header)
+    }
+
+    fn play(&mut self, req: &Request) -> Response {
+        if let Err(resp) = self.check_session(req) {
+            return resp;
+        }
+        let base = self.absolute_uri(&req.uri);
+        let Some(session) = self.session.as_mut() else {
+            return Response::new(455);
+        };
+        if session.playing.is_some() {
+            return self.with_session(Response::new(200).header("Range", "npt=0.000-"));
+        }
+        let Some(video_sink) = session.tracks.first_mut().and_then(Option::take) else {
+            // Audio-only playback is not offered.
+            return Response::new(455);
+        };
+
+        let video = Output {
+            packetizer: VideoPacketizer::new(session.media.video.codec),
+            sink: video_sink,
+        };
+        let mut rtp_info = vec![format!(
+            "url={base}/trackID=0;seq={};rtptime={}",
+            video.packetizer.rtp.next_seq(),
+            video.packetizer.rtp.origin_ts()
+        )];
+        let audio = match (
+            session.media.audio,
+            session.tracks.get_mut(1).and_then(Option::take),
+        ) {
+            (Some(track), Some(sink)) => {
+                let packetizer = AudioPacketizer::new(
+                    track.payload_type(),
+                    track.clock_rate(),
+                    matches!(track, AudioTrack::Aac { .. }),
+                );
+                rtp_info.push(format!(
+                    "url={base}/trackID=1;seq={};rtptime={}",
+                    packetizer.rtp.next_seq(),
+                    packetizer.rtp.origin_ts()
+                ));
+                Some(Output { packetizer, sink })
+            }
+            _ => None,
+        };
+
+        session.playing = Some(tokio::spawn(session::play(
+            Arc::clone(&self.server.router),
+            Arc::clone(&session.source),
+            video,
+            audio,
+        )));
+        if let Some(user) = &self.user {
+            info!(
+                "RTSP PLAY monitor {} by user {} from {}",
+                session.monitor_id, user.username, self.peer
+            );
+        }
+        self.with_session(
+            Response::new(200)
+                .header("Range", "npt=0.000-")
+                .header("RTP-Info", rtp_info.join(",")),
+        )
+    }
+
+    async fn teardown_request(&mut self, req: &Request) -> Response {
+        if let Err(resp) = self.check_session(req) {
+            return resp;
+        }
+        if self.session.is_none() {
+            return Response::new(454);
+        }
+        self.teardown().await;
+        Response::new(200)
+    }
+
+    /// Stop playback and release the session's ports. The monitor's reader is
+    /// stopped too when nothing else is using it — no other subscriber, no
+    /// HLS session, not prewarmed — as the coordinator does for HLS.
+    async fn teardown(&mut self) {
+        let Some(session) = self.session.take() else {
+            return;
+        };
+        if let Some(handle) = session.playing {
+            handle.abort();
+            let _ = handle.await;
+        }
+        let Session {
+            monitor_id, source, ..
+        } = session;
+        let state = &self.server.state;
+        let in_use = source.video_subscriber_count() > 0
+            || source.audio_subscriber_count() > 0
+            || source.event_subscriber_count() > 0
+            || state
+                .config
+                .streaming
+                .source
+                .prewarm_monitors
+                .contains(&monitor_id);
+        drop(source);
+        let hls = match &state.live_coordinator {
+            Some(coordinator) => coordinator.has_session(monitor_id).await,
+            None => false,
+        };
+        if !in_use && !hls {
+            let _ = self.server.router.stop_reader(monitor_id).await;
+        }
+    }
+}
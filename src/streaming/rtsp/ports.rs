@@ -0,0 +1,124 @@
+//! Server-side UDP port pairs for RTP over UDP.
+//!
+//! Each UDP track binds an even RTP port and the odd RTCP port above it, both
+//! drawn from `[rtp_port_range_start, rtp_port_range_end]` so a firewall can
+//! open one known range. A pair stays reserved until its [`PortPair`] drops.
+
+use std::collections::BTreeSet;
+use std::net::{IpAddr, SocketAddr};
+use std::sync::{Arc, Mutex};
+
+use tokio::net::UdpSocket;
+
+/// Hands out port pairs from the configured range.
+#[derive(Debug)]
+pub struct PortAllocator {
+    bind_ip: IpAddr,
+    start: u16,
+    end: u16,
+    in_use: Mutex<BTreeSet<u16>>,
+}
+
+/// A bound RTP/RTCP socket pair; returns its ports to the allocator on drop.
+#[derive(Debug)]
+pub struct PortPair {
+    pub rtp: UdpSocket,
+    pub rtcp: UdpSocket,
+    pub rtp_port: u16,
+    allocator: Arc<PortAllocator>,
+}
+
+impl Drop for PortPair {
+    fn drop(&mut self) {
+        self.allocator.release(self.rtp_port);
+    }
+}
+
+impl PortAllocator {
+    pub fn new(bind_ip: IpAddr, start: u16, end: u16) -> Self {
+        Self {
+            bind_ip,
+            start,
+            end,
+            in_use: Mutex::new(BTreeSet::new()),
+        }
+    }
+
+    /// The even RTP ports in range, lowest first.
+    fn candidates(&self) -> impl Iterator<Item = u16> {
+        let first = self.start.saturating_add(self.start % 2);
+        let end = self.end;
+        (first..end).step_by(2)
+    }
+
+    /// Reserve the lowest free even port whose pair fits in range.
+    fn reserve(&self, skip: &BTreeSet<u16>) -> Option<u16> {
+        let mut in_use = self.in_use.lock().unwrap_or_else(|e| e.into_inner());
+        let port = self
+            .candidates()
+            .find(|p| !in_use.contains(p) && !skip.contains(p))?;
+        in_use.insert(port);
+        Some(port)
+    }
+
+    fn release(&self, port: u16) {
+        self.in_use
+            .lock()
+            .unwrap_or_else(|e| e.into_inner())
+            .remove(&port);
+    }
+
+    /// Bind the next free pair. Ports another process holds are skipped.
+    /// `None` when the range is exhausted.
+    pub async fn bind(self: &Arc<Self>) -> Option<PortPair> {
+        let mut tried = BTreeSet::new();
+        while let Some(port) = self.reserve(&tried) {
+            let rtp = UdpSocket::bind(SocketAddr::new(self.bind_ip, port)).await;
+            let rtcp = UdpSocket::bind(SocketAddr::new(self.bind_ip, port + 1)).await;
+            if let (Ok(rtp), Ok(rtcp)) = (rtp, rtcp) {
+                return Some(PortPair {
+                    rtp,
+                    rtcp,
+                    rtp_port: port,
+                    allocator: Arc::clone(self),
+                });
+            }
+            self.release(port);
+            tried.insert(port);
+        }
+        None
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::net::Ipv4Addr;
+
+    #[test]
+    fn candidates_are_even_and_leave_room_for_rtcp() {
+        let a = PortAllocator::new(IpAddr::V4(Ipv4Addr::LOCALHOST), 20001, 20006);
+        assert_eq!(a.candidates().collect::<Vec<_>>(), vec![20002, 20004]);
+        let b = PortAllocator::new(IpAddr::V4(Ipv4Addr::LOCALHOST), 20000, 20001);
+        assert_eq!(b.candidates().collect::<Vec<_>>(), vec![20000]);
+        let empty = PortAllocator::new(IpAddr::V4(Ipv4Addr::LOCALHOST), 20000, 20000);
+        assert_eq!(empty.candidates().count(), 0);
+    }
+
+    #[tokio::test]
+    async fn pairs_are_released_on_drop() {
+        let a = Arc::new(PortAllocator::new(
+            IpAddr::V4(Ipv4Addr::LOCALHOST),
+            41000,
+            41003,
+        ));
+        let first = a.bind().await.expect("first pair");
+        let second = a.bind().await.expect("second pair");
+        assert_ne!(first.rtp_port, second.rtp_port);
+        assert_eq!(first.rtcp.local_addr().unwrap().port(), first.rtp_port + 1);
+        assert!(a.bind().await.is_none(), "range exhausted");
+        let freed = first.rtp_port;
+        drop(first);
+        assert_eq!(a.bind().await.map(|p| p.rtp_port), Some(freed));
+    }
+}
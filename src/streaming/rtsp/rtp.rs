@@ -0,0 +1,374 @@
+//! RTP packetisation of the source's media for the RTSP server.
+//!
+//! * H.264 — RFC 6184, packetization-mode 1: single NAL unit packets, and FU-A
+//!   fragments for NALs over the payload budget.
+//! * H.265 — RFC 7798: single NAL unit packets and FU fragments.
+//! * AAC — RFC 3640 `mpeg4-generic` in `AAC-hbr` mode, one frame per packet
+//!   with its ADTS header stripped.
+//! * G.711 / Opus — the frame as the payload (RFC 3551 / RFC 7587).
+//!
+//! The source delivers one NAL per [`VideoPacket`], every NAL of an access
+//! unit sharing its timestamp, so [`VideoPacketizer`] holds an access unit
+//! until the next one starts and then sets the marker bit on its last packet.
+//!
+//! [`VideoPacket`]: crate::streaming::source::VideoPacket
+
+use rand::Rng as _;
+
+use crate::streaming::source::{AdtsHeader, VideoCodec};
+
+/// Largest RTP payload sent, keeping a packet inside a 1500-byte Ethernet MTU
+/// with IP/UDP headers and the 12-byte RTP header.
+pub const MAX_PAYLOAD: usize = 1400;
+
+/// The RTP header state of one outgoing stream (RFC 3550 §5.1).
+#[derive(Debug)]
+pub struct RtpWriter {
+    payload_type: u8,
+    clock_rate: u32,
+    ssrc: u32,
+    seq: u16,
+    /// RTP timestamp of the session's media origin.
+    ts_offset: u32,
+    packets: u32,
+    octets: u32,
+    last_ts: u32,
+}
+
+impl RtpWriter {
+    /// A stream with random SSRC, initial sequence number and timestamp
+    /// offset, as RFC 3550 asks.
+    pub fn new(payload_type: u8, clock_rate: u32) -> Self {
+        let mut rng = rand::rng();
+        Self {
+            payload_type,
+            clock_rate,
+            ssrc: rng.random(),
+            seq: rng.random(),
+            ts_offset: rng.random(),
+            packets: 0,
+            octets: 0,
+            last_ts: 0,
+        }
+    }
+
+    /// Sequence number the next packet will carry (for `RTP-Info`).
+    pub fn next_seq(&self) -> u16 {
+        self.seq
+    }
+
+    /// RTP timestamp of the media origin (for `RTP-Info`).
+    pub fn origin_ts(&self) -> u32 {
+        self.ts_offset
+    }
+
+    /// RTP timestamp for media `elapsed_us` after the origin.
+    pub fn timestamp(&self, elapsed_us: i64) -> u32 {
+        let ticks = (elapsed_us.max(0) as i128 * self.clock_rate as i128 / 1_000_000) as u32;
+        self.ts_offset.wrapping_add(ticks)
+    }
+
+    /// One RTP packet.
+    pub fn packet(&mut self, marker: bool, timestamp: u32, payload: &[u8]) -> Vec<u8> {
+        let mut out = Vec::with_capacity(12 + payload.len());
+        out.push(0x80); // V=2, no padding, no extension, no CSRC
+        out.push(((marker as u8) << 7) | (self.payload_type & 0x7F));
+        out.extend_from_slice(&self.seq.to_be_bytes());
+        out.extend_from_slice(&timestamp.to_be_bytes());
+        out.extend_from_slice(&self.ssrc.to_be_bytes());
+        out.extend_from_slice(payload);
+        self.seq = self.seq.wrapping_add(1);
+        self.packets = self.packets.wrapping_add(1);
+        self.octets = self.octets.wrapping_add(payload.len() as u32);
+        self.last_ts = timestamp;
+        out
+    }
+
+    /// An RTCP sender report (RFC 3550 §6.4.1) pairing `now` with the last
+    /// packet's RTP timestamp, so clients can line audio up with video.
+    pub fn sender_report(&self, now: std::time::SystemTime) -> Vec<u8> {
+        let since_epoch = now
+            .duration_since(std::time::UNIX_EPOCH)
+            .unwrap_or_default();
+        // NTP counts from 1900.
+        let ntp_secs = since_epoch.as_secs() + 2_208_988_800;
+        let ntp_frac = ((since_epoch.subsec_nanos() as u64) << 32) / 1_000_000_000;
+
+        let mut out = Vec::with_capacity(28);
+        out.push(0x80); // V=2, no report blocks
+        out.push(200); // SR
+        out.extend_from_slice(&6u16.to_be_bytes()); // length in words, minus one
+        out.extend_from_slice(&self.ssrc.to_be_bytes());
+        out.extend_from_slice(&(ntp_secs as u32).to_be_bytes());
+        out.extend_from_slice(&(ntp_frac as u32).to_be_bytes());
+        out.extend_from_slice(&self.last_ts.to_be_bytes());
+        out.extend_from_slice(&self.packets.to_be_bytes());
+        out.extend_from_slice(&self.octets.to_be_bytes());
+        out
+    }
+}
+
+/// Strip an Annex B start code.
+fn nal_body(nal: &[u8]) -> &[u8] {
+    if nal.starts_with(&[0, 0, 0, 1]) {
+        &nal[4..]
+    } else if nal.starts_with(&[0, 0, 1]) {
+        &nal[3..]
+    } else {
+        nal
+    }
+}
+
+/// RTP payloads for one NAL: the NAL itself when it fits, else fragments.
+fn fragment_nal(codec: VideoCodec, nal: &[u8]) -> Vec<Vec<u8>> {
+    if nal.len() <= MAX_PAYLOAD {
+        return vec![nal.to_vec()];
+    }
+    match codec {
+        VideoCodec::H265 => {
+            // 2-byte NAL header; FU payload header type 49, FU header S|E|type.
+            let nal_type = (nal[0] >> 1) & 0x3F;
+            let header = [(nal[0] & 0x81) | (49 << 1), nal[1]];
+            fragments(&nal[2..], MAX_PAYLOAD - 3, |start, end| {
+                vec![
+                    header[0],
+                    header[1],
+                    ((start as u8) << 7) | ((end as u8) << 6) | nal_type,
+                ]
+            })
+        }
+        VideoCodec::H264 | VideoCodec::Unknown => {
+            // FU-A: indicator keeps F/NRI with type 28, header S|E|R|type.
+            let indicator = (nal[0] & 0xE0) | 28;
+            let nal_type = nal[0] & 0x1F;
+            fragments(&nal[1..], MAX_PAYLOAD - 2, |start, end| {
+                vec![
+                    indicator,
+                    ((start as u8) << 7) | ((end as u8) << 6) | nal_type,
+                ]
+            })
+        }
+    }
+}
+
+fn fragments(body: &[u8], chunk: usize, header: impl Fn(bool, bool) -> Vec<u8>) -> Vec<Vec<u8>> {
+    let count = body.len().div_ceil(chunk);
+    body.chunks(chunk)
+        .enumerate()
+        .map(|(i, part)| {
+            let mut payload = header(i == 0, i + 1 == count);
+            payload.extend_from_slice(part);
+            payload
+        })
+        .collect()
+}
+
+/// Packetises a monitor's video for one client.
+#[derive(Debug)]
+pub struct VideoPacketizer {
+    pub rtp: RtpWriter,
+    codec: VideoCodec,
+    /// NAL bodies of the access unit being collected, and its timestamp.
+    pending: Vec<Vec<u8>>,
+    pending_us: i64,
+}
+
+impl VideoPacketizer {
+    pub const PAYLOAD_TYPE: u8 = 96;
+    pub const CLOCK_RATE: u32 = 90_000;
+
+    pub fn new(codec: VideoCodec) -> Self {
+        Self {
+            rtp: RtpWriter::new(Self::PAYLOAD_TYPE, Self::CLOCK_RATE),
+            codec,
+            pending: Vec::new(),
+            pending_us: 0,
+        }
+    }
+
+    /// Add one NAL (Annex B) at `elapsed_us` past the origin. Returns the RTP
+    /// packets of the previous access unit once this NAL starts a new one.
+    pub fn push(&mut self, nal: &[u8], elapsed_us: i64) -> Vec<Vec<u8>> {
+        let flushed = if !self.pending.is_empty() && elapsed_us != self.pending_us {
+            self.flush()
+        } else {
+            Vec::new()
+        };
+        let body = nal_body(nal);
+        if !body.is_empty() {
+            self.pending.push(body.to_vec());
+            self.pending_us = elapsed_us;
+        }
+        flushed
+    }
+
+    /// Packetise the collected access unit, marker on its last packet.
+    pub fn flush(&mut self) -> Vec<Vec<u8>> {
+        let ts = self.rtp.timestamp(self.pending_us);
+        let payloads: Vec<Vec<u8>> = self
+            .pending
+            .drain(..)
+            .flat_map(|nal| fragment_nal(self.codec, &nal))
+            .collect();
+        let last = payloads.len().saturating_sub(1);
+        payloads
+            .iter()
+            .enumerate()
+            .map(|(i, p)| self.rtp.packet(i == last, ts, p))
+            .collect()
+    }
+}
+
+/// Packetises a monitor's audio for one client.
+#[derive(Debug)]
+pub struct AudioPacketizer {
+    pub rtp: RtpWriter,
+    aac: bool,
+}
+
+impl AudioPacketizer {
+    pub fn new(payload_type: u8, clock_rate: u32, aac: bool) -> Self {
+        Self {
+            rtp: RtpWriter::new(payload_type, clock_rate),
+            aac,
+        }
+    }
+
+    /// The RTP packet for one frame at `elapsed_us` past the origin. `None`
+    /// for an AAC frame without a usable ADTS header.
+    pub fn packet(&mut self, frame: &[u8], elapsed_us: i64) -> Option<Vec<u8>> {
+        let ts = self.rtp.timestamp(elapsed_us);
+        if !self.aac {
+            return Some(self.rtp.packet(true, ts, frame));
+        }
+        let adts = AdtsHeader::parse(frame)?;
+        let raw = frame.get(adts.header_len..adts.frame_len.min(frame.len()))?;
+        if raw.len() >= 1 << 13 {
+            return None;
+        }
+        // AU-headers-length (bits), then one AU-header: 13-bit size, 3-bit index.
+        let mut payload = Vec::with_capacity(4 + raw.len());
+        payload.extend_from_slice(&16u16.to_be_bytes());
+        payload.extend_from_slice(&((raw.len() as u16) << 3).to_be_bytes());
+        payload.extend_from_slice(raw);
+        Some(self.rtp.packet(true, ts, &payload))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn header(packet: &[u8]) -> (bool, u8, u16, u32) {
+        (
+            packet[1] & 0x80 != 0,
+            packet[1] & 0x7F,
+            u16::from_be_bytes([packet[2], packet[3]]),
+            u32::from_be_bytes([packet[4], packet[5], packet[6], packet[7]]),
+        )
+    }
+
+    #[test]
+    fn timestamps_scale_from_the_origin_and_wrap() {
+        let mut w = RtpWriter::new(96, 90_000);
+        w.ts_offset = u32::MAX - 10;
+        assert_eq!(w.timestamp(0), u32::MAX - 10);
+        assert_eq!(w.timestamp(1_000_000), 90_000 - 11);
+        assert_eq!(w.timestamp(-5), u32::MAX - 10);
+    }
+
+    #[test]
+    fn access_unit_is_flushed_when_the_next_starts() {
+        let mut p = VideoPacketizer::new(VideoCodec::H264);
+        let seq0 = p.rtp.next_seq();
+        assert!(p.push(&[0, 0, 0, 1, 0x67, 1, 2], 0).is_empty()); // SPS
+        assert!(p.push(&[0, 0, 1, 0x68, 3], 0).is_empty()); // PPS
+        assert!(p.push(&[0, 0, 0, 1, 0x65, 9, 9], 0).is_empty()); // IDR
+        let packets = p.push(&[0, 0, 0, 1, 0x41, 7], 40_000);
+        assert_eq!(packets.len(), 3);
+        let origin = p.rtp.origin_ts();
+        for (i, pkt) in packets.iter().enumerate() {
+            let (marker, pt, seq, ts) = header(pkt);
+            assert_eq!(marker, i == 2, "marker only on the AU's last packet");
+            assert_eq!(pt, 96);
+            assert_eq!(seq, seq0.wrapping_add(i as u16));
+            assert_eq!(ts, origin);
+        }
+        assert_eq!(&packets[0][12..], &[0x67, 1, 2]);
+        assert_eq!(&packets[1][12..], &[0x68, 3]);
+        let tail = p.flush();
+        assert_eq!(tail.len(), 1);
+        assert_eq!(header(&tail[0]).3, origin.wrapping_add(3_600));
+    }
+
+    #[test]
+    fn large_h264_nal_is_split_into_fu_a() {
+        let mut nal = vec![0, 0, 0, 1, 0x65];
+        nal.extend(std::iter::repeat_n(0xAB, 3_000));
+        let mut p = VideoPacketizer::new(VideoCodec::H264);
+        p.push(&nal, 0);
+        let packets = p.flush();
+        assert_eq!(packets.len(), 3);
+        let fu: Vec<&[u8]> = packets.iter().map(|pkt| &pkt[12..]).collect();
+        assert!(fu.iter().all(|f| f[0] == 0x7C)); // NRI 3, type 28
+        assert_eq!(fu[0][1], 0x85); // start, type 5
+        assert_eq!(fu[1][1], 0x05);
+        assert_eq!(fu[2][1], 0x45); // end, type 5
+        assert!(packets.iter().all(|pkt| pkt.len() <= 12 + MAX_PAYLOAD));
+        let body: usize = fu.iter().map(|f| f.len() - 2).sum();
+        assert_eq!(body, 3_000);
+    }
+
+    #[test]
+    fn large_h265_nal_is_split_into_fu() {
+        // IDR_W_RADL (type 19): header 0x26 0x01.
+        let mut nal = vec![0, 0, 0, 1, 0x26, 0x01];
+        nal.extend(std::iter::repeat_n(0xCD, 2_000));
+        let mut p = VideoPacketizer::new(VideoCodec::H265);
+        p.push(&nal, 0);
+        let packets = p.flush();
+        assert_eq!(packets.len(), 2);
+        let first = &packets[0][12..];
+        assert_eq!(&first[..3], &[49 << 1, 0x01, 0x80 | 19]);
+        assert_eq!(packets[1][12 + 2], 0x40 | 19);
+    }
+
+    #[test]
+    fn aac_frame_gets_an_au_header_instead_of_adts() {
+        // 7-byte ADTS header for AAC-LC 48 kHz stereo, 3 payload bytes.
+        let wrapper =
+            crate::streaming::source::media::AdtsWrapper::from_asc(&[0x11, 0x90]).unwrap();
+        let frame = wrapper.wrap(&[1, 2, 3]).unwrap();
+        let mut p = AudioPacketizer::new(97, 48_000, true);
+        let pkt = p.packet(&frame, 0).unwrap();
+        assert_eq!(&pkt[12..], &[0x00, 0x10, 0x00, 0x18, 1, 2, 3]);
+        assert!(p.packet(&[1, 2, 3], 0).is_none());
+    }
+
+    #[test]
+    fn g711_frame_is_sent_as_is() {
+        let mut p = AudioPacketizer::new(0, 8_000, false);
+        let pkt = p.packet(&[0xFF; 160], 20_000).unwrap();
+        let (marker, pt, _, ts) = header(&pkt);
+        assert!(marker);
+        assert_eq!(pt, 0);
+        assert_eq!(ts, p.rtp.origin_ts().wrapping_add(160));
+        assert_eq!(pkt.len(), 12 + 160);
+    }
+
+    #[test]
+    fn sender_report_carries_counts() {
+        let mut w = RtpWriter::new(96, 90_000);
+        w.packet(true, 1234, &[0; 10]);
+        let sr = w.sender_report(std::time::UNIX_EPOCH + std::time::Duration::from_secs(1));
+        assert_eq!(sr.len(), 28);
+        assert_eq!(sr[1], 200);
+        assert_eq!(
+            u32::from_be_bytes([sr[8], sr[9], sr[10], sr[11]]),
+            2_208_988_801
+        );
+        assert_eq!(u32::from_be_bytes([sr[16], sr[17], sr[18], sr[19]]), 1234);
+        assert_eq!(u32::from_be_bytes([sr[20], sr[21], sr[22], sr[23]]), 1);
+        assert_eq!(u32::from_be_bytes([sr[24], sr[25], sr[26], sr[27]]), 10);
+    }
+}
@@ -0,0 +1,297 @@
+//! The SDP a `DESCRIBE` returns (RFC 4566), one media section per track.
+//!
+//! Track 0 is always video; track 1, when present, is audio. Parameter sets
+//! go in `sprop-*` so a client can set up its decoder before the first
+//! keyframe, though keyframes also carry them in-band.
+
+use std::fmt::Write as _;
+
+use base64::Engine as _;
+
+use super::rtp::VideoPacketizer;
+use crate::streaming::source::media::split_annexb_nals;
+use crate::streaming::source::{h264_nal_type, h265_nal_type, AdtsHeader, VideoCodec};
+
+/// Dynamic payload type used for AAC and Opus.
+pub const AUDIO_DYNAMIC_PT: u8 = 97;
+
+/// The video track.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct VideoTrack {
+    pub codec: VideoCodec,
+    /// H.264: SPS, PPS. H.265: VPS, SPS, PPS. Without start codes; empty
+    /// when no keyframe has been seen yet.
+    pub parameter_sets: Vec<Vec<u8>>,
+}
+
+impl VideoTrack {
+    /// Pull the parameter sets out of a cached keyframe access unit.
+    pub fn from_keyframe(codec: VideoCodec, keyframe_au: &[u8]) -> Self {
+        let mut parameter_sets = Vec::new();
+        for nal in split_annexb_nals(keyframe_au.to_vec()) {
+            let is_parameter_set = match codec {
+                VideoCodec::H265 => h265_nal_type(&nal).is_some_and(|t| (32..=34).contains(&t)),
+                VideoCodec::H264 | VideoCodec::Unknown => {
+                    h264_nal_type(&nal).is_some_and(|t| t == 7 || t == 8)
+                }
+            };
+            if is_parameter_set {
+                let start = if nal.starts_with(&[0, 0, 0, 1]) { 4 } else { 3 };
+                parameter_sets.push(nal[start..].to_vec());
+            }
+        }
+        Self {
+            codec,
+            parameter_sets,
+        }
+    }
+
+    fn fmtp(&self) -> Option<String> {
+        let b64 = |nal: &Vec<u8>| base64::engine::general_purpose::STANDARD.encode(nal);
+        match self.codec {
+            VideoCodec::H265 => {
+                let of_type = |t: u8| {
+                    self.parameter_sets
+                        .iter()
+                        .find(|n| n.first().is_some_and(|b| (b >> 1) & 0x3F == t))
+                        .map(b64)
+                };
+                let (vps, sps, pps) = (of_type(32)?, of_type(33)?, of_type(34)?);
+                Some(format!("sprop-vps={vps};sprop-sps={sps};sprop-pps={pps}"))
+            }
+            VideoCodec::H264 | VideoCodec::Unknown => {
+                let mut fmtp = "packetization-mode=1".to_string();
+                if let Some(sps) = self
+                    .parameter_sets
+                    .iter()
+                    .find(|n| n.len() >= 4 && n[0] & 0x1F == 7)
+                {
+                    let _ = write!(fmtp, ";profile-level-id={}", hex::encode(&sps[1..4]));
+                }
+                if !self.parameter_sets.is_empty() {
+                    let sets: Vec<String> = self.parameter_sets.iter().map(b64).collect();
+                    let _ = write!(fmtp, ";sprop-parameter-sets={}", sets.join(","));
+                }
+                Some(fmtp)
+            }
+        }
+    }
+}
+
+/// The audio track, when the monitor has audio RTP can carry.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum AudioTrack {
+    Aac {
+        sample_rate: u32,
+        channels: u8,
+        config: [u8; 2],
+    },
+    Pcmu,
+    Pcma,
+    Opus,
+}
+
+impl AudioTrack {
+    /// Describe AAC from the ADTS header of one of its frames.
+    pub fn aac_from_adts(frame: &[u8]) -> Option<Self> {
+        let adts = AdtsHeader::parse(frame)?;
+        Some(Self::Aac {
+            sample_rate: adts.sample_rate,
+            channels: adts.channel_configuration,
+            config: adts.audio_specific_config(),
+        })
+    }
+
+    pub fn payload_type(&self) -> u8 {
+        match self {
+            AudioTrack::Pcmu => 0,
+            AudioTrack::Pcma => 8,
+            AudioTrack::Aac { .. } | AudioTrack::Opus => AUDIO_DYNAMIC_PT,
+        }
+    }
+
+    pub fn clock_rate(&self) -> u32 {
+        match self {
+            AudioTrack::Aac { sample_rate, .. } => *sample_rate,
+            AudioTrack::Pcmu | AudioTrack::Pcma => 8_000,
+            AudioTrack::Opus => 48_000,
+        }
+    }
+}
+
+/// Everything `DESCRIBE` learned about a monitor's stream.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct MediaDescription {
+    pub video: VideoTrack,
+    pub audio: Option<AudioTrack>,
+}
+
+impl MediaDescription {
+    pub fn track_count(&self) -> usize {
+        1 + self.audio.is_some() as usize
+    }
+
+    /// Render the SDP. `session_id` makes the `o=` line unique per DESCRIBE;
+    /// `server_ip` is the address the client reached us on.
+    pub fn sdp(&self, session_id: u64, server_ip: &str, monitor_id: u32) -> String {
+        let addr_type = if server_ip.contains(':') {
+            "IP6"
+        } else {
+            "IP4"
+        };
+        let mut sdp = String::new();
+        let _ = write!(
+            sdp,
+            "v=0\r\n\
+             o=- {session_id} 1 IN {addr_type} {server_ip}\r\n\
+             s=Monitor {monitor_id}\r\n\
+             c=IN {addr_type} 0.0.0.0\r\n\
+             t=0 0\r\n\
+             a=control:*\r\n\
+             a=range:npt=0-\r\n"
+        );
+
+        let pt = VideoPacketizer::PAYLOAD_TYPE;
+        let encoding = match self.video.codec {
+            VideoCodec::H265 => "H265",
+            VideoCodec::H264 | VideoCodec::Unknown => "H264",
+        };
+        let _ = write!(
+            sdp,
+            "m=video 0 RTP/AVP {pt}\r\na=rtpmap:{pt} {encoding}/{}\r\n",
+            VideoPacketizer::CLOCK_RATE
+        );
+        if let Some(fmtp) = self.video.fmtp() {
+            let _ = write!(sdp, "a=fmtp:{pt} {fmtp}\r\n");
+        }
+        sdp.push_str("a=control:trackID=0\r\n");
+
+        if let Some(audio) = &self.audio {
+            let pt = audio.payload_type();
+            let _ = write!(sdp, "m=audio 0 RTP/AVP {pt}\r\n");
+            match audio {
+                AudioTrack::Aac {
+                    sample_rate,
+                    channels,
+                    config,
+                } => {
+                    let _ = write!(
+                        sdp,
+                        "a=rtpmap:{pt} mpeg4-generic/{sample_rate}/{channels}\r\n\
+                         a=fmtp:{pt} streamtype=5;profile-level-id=1;mode=AAC-hbr;\
+                         sizelength=13;indexlength=3;indexdeltalength=3;config={}\r\n",
+                        hex::encode(config)
+                    );
+                }
+                AudioTrack::Pcmu => {
+                    let _ = write!(sdp, "a=rtpmap:{pt} PCMU/8000\r\n");
+                }
+                AudioTrack::Pcma => {
+                    let _ = write!(sdp, "a=rtpmap:{pt} PCMA/8000\r\n");
+                }
+                AudioTrack::Opus => {
+                    let _ = write!(sdp, "a=rtpmap:{pt} opus/48000/2\r\n");
+                }
+            }
+            sdp.push_str("a=control:trackID=1\r\n");
+        }
+        sdp
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const SPS: &[u8] = &[0x67, 0x4d, 0x00, 0x33, 0xaa];
+    const PPS: &[u8] = &[0x68, 0xee, 0x3c, 0x80];
+
+    fn annexb(nals: &[&[u8]]) -> Vec<u8> {
+        nals.iter()
+            .flat_map(|n| [&[0u8, 0, 0, 1][..], n].concat())
+            .collect()
+    }
+
+    #[test]
+    fn h264_sdp_carries_parameter_sets_and_profile() {
+        let au = annexb(&[SPS, PPS, &[0x65, 0x88, 0x84]]);
+        let video = VideoTrack::from_keyframe(VideoCodec::H264, &au);
+        assert_eq!(video.parameter_sets, vec![SPS.to_vec(), PPS.to_vec()]);
+
+        let sdp = MediaDescription { video, audio: None }.sdp(42, "10.0.0.5", 3);
+        assert!(sdp.contains("o=- 42 1 IN IP4 10.0.0.5\r\n"));
+        assert!(sdp.contains("m=video 0 RTP/AVP 96\r\na=rtpmap:96 H264/90000\r\n"));
+        assert!(sdp.contains(
+            "a=fmtp:96 packetization-mode=1;profile-level-id=4d0033;\
+             sprop-parameter-sets=Z00AM6o=,aO48gA==\r\n"
+        ));
+        assert!(sdp.contains("a=control:trackID=0\r\n"));
+        assert!(!sdp.contains("m=audio"));
+    }
+
+    #[test]
+    fn h264_without_keyframe_still_declares_packetization() {
+        let video = VideoTrack {
+            codec: VideoCodec::H264,
+            parameter_sets: vec![],
+        };
+        let sdp = MediaDescription { video, audio: None }.sdp(1, "::1", 1);
+        assert!(sdp.contains("IN IP6 ::1"));
+        assert!(sdp.contains("a=fmtp:96 packetization-mode=1\r\n"));
+    }
+
+    #[test]
+    fn h265_sdp_needs_all_three_parameter_sets() {
+        let vps: &[u8] = &[0x40, 0x01, 0x0c];
+        let sps: &[u8] = &[0x42, 0x01, 0x01];
+        let pps: &[u8] = &[0x44, 0x01, 0xc1];
+        let au = annexb(&[vps, sps, pps, &[0x26, 0x01, 0xaf]]);
+        let video = VideoTrack::from_keyframe(VideoCodec::H265, &au);
+        assert_eq!(video.parameter_sets.len(), 3);
+        let sdp = MediaDescription {
+            video: video.clone(),
+            audio: None,
+        }
+        .sdp(1, "10.0.0.5", 1);
+        assert!(sdp.contains("a=rtpmap:96 H265/90000\r\n"));
+        assert!(sdp.contains("a=fmtp:96 sprop-vps=QAEM;sprop-sps=QgEB;sprop-pps=RAHB\r\n"));
+
+        let partial = VideoTrack {
+            parameter_sets: video.parameter_sets[1..].to_vec(),
+            ..video
+        };
+        let sdp = MediaDescription {
+            video: partial,
+            audio: None,
+        }
+        .sdp(1, "10.0.0.5", 1);
+        assert!(!sdp.contains("a=fmtp:96"));
+    }
+
+    #[test]
+    fn aac_track_is_described_from_adts() {
+        let wrapper =
+            crate::streaming::source::media::AdtsWrapper::from_asc(&[0x11, 0x90]).unwrap();
+        let audio = AudioTrack::aac_from_adts(&wrapper.wrap(&[0; 4]).unwrap()).unwrap();
+        assert_eq!(audio.clock_rate(), 48_000);
+        let media = MediaDescription {
+            video: VideoTrack {
+                codec: VideoCodec::H264,
+                parameter_sets: vec![],
+            },
+            audio: Some(audio),
+        };
+        assert_eq!(media.track_count(), 2);
+        let sdp = media.sdp(1, "10.0.0.5", 1);
+        assert!(sdp.contains("m=audio 0 RTP/AVP 97\r\na=rtpmap:97 mpeg4-generic/48000/2\r\n"));
+        assert!(sdp.contains(";config=1190\r\na=control:trackID=1\r\n"));
+    }
+
+    #[test]
+    fn g711_uses_static_payload_types() {
+        assert_eq!(AudioTrack::Pcmu.payload_type(), 0);
+        assert_eq!(AudioTrack::Pcma.payload_type(), 8);
+        assert_eq!(AudioTrack::Pcma.clock_rate(), 8_000);
+        assert_eq!(AudioTrack::Opus.clock_rate(), 48_000);
+    }
+}
@@ -0,0 +1,301 @@
+//! The PLAY loop: forwards one monitor's broadcast to one client as RTP.
+//!
+//! Video starts on the next keyframe (with the parameter sets sent just
+//! before it), so a client never decodes P-frames against a picture it does
+//! not have. Audio is held back until video has started so both tracks share
+//! one origin. A lagging client skips ahead to the next keyframe rather than
+//! slowing the source.
+
+use std::net::SocketAddr;
+use std::sync::Arc;
+use std::time::{Duration, Instant, SystemTime};
+
+use tokio::sync::{broadcast, mpsc};
+use tracing::{debug, info};
+
+use super::message::interleaved;
+use super::ports::PortPair;
+use super::rtp::{AudioPacketizer, VideoPacketizer};
+use crate::streaming::source::{AudioPacket, MonitorSource, SourceRouter, VideoPacket};
+
+/// How often the reader is re-ensured and sender reports go out.
+const TICK: Duration = Duration::from_secs(5);
+
+/// Where one track's packets go.
+#[derive(Debug)]
+pub(super) enum Sink {
+    Udp {
+        ports: PortPair,
+        client_rtp: SocketAddr,
+        client_rtcp: SocketAddr,
+    },
+    /// Interleaved on the RTSP connection: RTP on `channel`, RTCP on the next.
+    Tcp {
+        channel: u8,
+        out: mpsc::Sender<Vec<u8>>,
+    },
+}
+
+impl Sink {
+    /// Send RTP; `false` once the client is gone.
+    async fn rtp(&self, packet: Vec<u8>) -> bool {
+        match self {
+            Sink::Udp {
+                ports, client_rtp, ..
+            } => {
+                // A lost datagram (or an ICMP error from a client that
+                // stopped listening) does not end the session; closing the
+                // RTSP connection does.
+                if let Err(e) = ports.rtp.send_to(&packet, client_rtp).await {
+                    debug!("RTP send to {client_rtp} failed: {e}");
+                }
+                true
+            }
+            Sink::Tcp { channel, out } => out.send(interleaved(*channel, &packet)).await.is_ok(),
+        }
+    }
+
+    async fn rtcp(&self, packet: Vec<u8>) -> bool {
+        match self {
+            Sink::Udp {
+                ports, client_rtcp, ..
+            } => {
+                let _ = ports.rtcp.send_to(&packet, client_rtcp).await;
+                true
+            }
+            Sink::Tcp { channel, out } => out
+                .send(interleaved(channel.wrapping_add(1), &packet))
+                .await
+                .is_ok(),
+        }
+    }
+
+    pub(super) fn is_udp(&self) -> bool {
+        matches!(self, Sink::Udp { .. })
+    }
+}
+
+/// A track ready to play.
+pub(super) struct Output<P> {
+    pub packetizer: P,
+    pub sink: Sink,
+}
+
+/// Maps source timestamps onto the session's media timeline.
+///
+/// The first forwarded packet is the origin. The source's clock restarts when
+/// its reader reconnects to zmc, so a jump backwards (or implausibly far
+/// forwards) re-bases the origin to continue one frame after the last
+/// timestamp sent, keeping RTP timestamps monotonic for the client.
+#[derive(Debug, Default)]
+struct MediaClock {
+    origin: Option<i64>,
+    last: i64,
+}
+
+impl MediaClock {
+    const MAX_BACKWARD_US: i64 = 1_000_000;
+    const MAX_FORWARD_US: i64 = 10_000_000;
+    const REBASE_STEP_US: i64 = 40_000;
+
+    fn elapsed(&mut self, timestamp_us: i64) -> i64 {
+        let origin = *self.origin.get_or_insert(timestamp_us);
+        let mut elapsed = timestamp_us - origin;
+        if elapsed < self.last - Self::MAX_BACKWARD_US || elapsed > self.last + Self::MAX_FORWARD_US
+        {
+            elapsed = self.last + Self::REBASE_STEP_US;
+            self.origin = Some(timestamp_us - elapsed);
+        }
+        self.last = self.last.max(elapsed);
+        elapsed
+    }
+
+    fn started(&self) -> bool {
+        self.origin.is_some()
+    }
+}
+
+/// Collects the NALs of the access unit in flight while waiting for a
+/// keyframe, so its parameter sets are not lost when the IDR arrives.
+#[derive(Debug, Default)]
+struct KeyframeGate {
+    open: bool,
+    lead: Vec<VideoPacket>,
+}
+
+impl KeyframeGate {
+    /// The packets to forward now: nothing while closed, the whole keyframe
+    /// access unit when it opens, then every packet.
+    fn admit(&mut self, packet: VideoPacket) -> Vec<VideoPacket> {
+        if self.open {
+            return vec![packet];
+        }
+        if self
+            .lead
+            .first()
+            .is_some_and(|p| p.timestamp_us != packet.timestamp_us)
+        {
+            self.lead.clear();
+        }
+        let keyframe = packet.is_keyframe;
+        self.lead.push(packet);
+        if keyframe {
+            self.open = true;
+            std::mem::take(&mut self.lead)
+        } else {
+            Vec::new()
+        }
+    }
+
+    fn close(&mut self) {
+        self.open = false;
+        self.lead.clear();
+    }
+}
+
+/// Stream `source` to the client until it goes away or the task is aborted
+/// (TEARDOWN, connection closed).
+pub(super) async fn play(
+    router: Arc<SourceRouter>,
+    mut source: Arc<MonitorSource>,
+    mut video: Output<VideoPacketizer>,
+    mut audio: Option<Output<AudioPacketizer>>,
+) {
+    let monitor_id = source.monitor_id();
+    let mut video_rx = source.subscribe_video();
+    let mut audio_rx = audio.as_ref().map(|_| source.subscribe_audio());
+    let mut clock = MediaClock::default();
+    let mut gate = KeyframeGate::default();
+    let mut tick = tokio::time::interval(TICK);
+    let mut last_report = Instant::now();
+    info!("RTSP playback started for monitor {monitor_id}");
+
+    loop {
+        tokio::select! {
+            packet = video_rx.recv() => match packet {
+                Ok(packet) => {
+                    for packet in gate.admit(packet) {
+                        let elapsed = clock.elapsed(packet.timestamp_us);
+                        for rtp in video.packetizer.push(&packet.data, elapsed) {
+                            if !video.sink.rtp(rtp).await {
+                                return;
+                            }
+                        }
+                    }
+                }
+                Err(broadcast::error::RecvError::Lagged(n)) => {
+                    debug!("RTSP client of monitor {monitor_id} lagged {n} video packets");
+                    gate.close();
+                }
+                Err(broadcast::error::RecvError::Closed) => break,
+            },
+            packet = recv_audio(&mut audio_rx) => match packet {
+                Ok(packet) => {
+                    let Some(out) = audio.as_mut() else { continue };
+                    if !clock.started() {
+                        continue;
+                    }
+                    let elapsed = clock.elapsed(packet.timestamp_us);
+                    if let Some(rtp) = out.packetizer.packet(&packet.data, elapsed) {
+                        if !out.sink.rtp(rtp).await {
+                            return;
+                        }
+                    }
+                }
+                Err(broadcast::error::RecvError::Lagged(_)) => {}
+                Err(broadcast::error::RecvError::Closed) => audio_rx = None,
+            },
+            _ = tick.tick() => {
+                // The HLS idle reaper stops readers it started, and a source
+                // can be replaced outright; keep ours alive, as the prewarm
+                // task does.
+                match router.get_existing_source(monitor_id) {
+                    Some(current) if Arc::ptr_eq(&current, &source) => {
+                        let _ = router.start_reader(monitor_id).await;
+                    }
+                    _ => match router.get_source(monitor_id).await {
+                        Ok(current) => {
+                            source = current;
+                            video_rx = source.subscribe_video();
+                            if audio.is_some() {
+                                audio_rx = Some(source.subscribe_audio());
+                            }
+                            gate.close();
+                        }
+                        Err(e) => debug!("RTSP source for monitor {monitor_id} unavailable: {e}"),
+                    },
+                }
+                if last_report.elapsed() >= TICK && clock.started() {
+                    last_report = Instant::now();
+                    let now = SystemTime::now();
+                    if !video.sink.rtcp(video.packetizer.rtp.sender_report(now)).await {
+                        return;
+                    }
+                    if let Some(out) = &audio {
+                        if !out.sink.rtcp(out.packetizer.rtp.sender_report(now)).await {
+                            return;
+                        }
+                    }
+                }
+            }
+        }
+    }
+    info!("RTSP playback ended for monitor {monitor_id}");
+}
+
+/// The next audio packet, or never when the session has no audio track.
+async fn recv_audio(
+    rx: &mut Option<broadcast::Receiver<AudioPacket>>,
+) -> Result<AudioPacket, broadcast::error::RecvError> {
+    match rx {
+        Some(rx) => rx.recv().await,
+        None => std::future::pending().await,
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::streaming::source::VideoCodec;
+
+    fn nal(ts: i64, keyframe: bool) -> VideoPacket {
+        VideoPacket {
+            monitor_id: 1,
+            timestamp_us: ts,
+            data: vec![0, 0, 0, 1, if keyframe { 0x65 } else { 0x67 }],
+            is_keyframe: keyframe,
+            codec: VideoCodec::H264,
+        }
+    }
+
+    #[test]
+    fn gate_releases_the_keyframe_access_unit_with_its_parameter_sets() {
+        let mut gate = KeyframeGate::default();
+        assert!(gate.admit(nal(0, false)).is_empty()); // P-frame, dropped
+        assert!(gate.admit(nal(40, false)).is_empty()); // SPS of the next AU
+        assert!(gate.admit(nal(40, false)).is_empty()); // PPS
+        let released = gate.admit(nal(40, true));
+        assert_eq!(released.len(), 3);
+        assert!(released.iter().all(|p| p.timestamp_us == 40));
+        assert_eq!(gate.admit(nal(80, false)).len(), 1);
+
+        gate.close();
+        assert!(gate.admit(nal(120, false)).is_empty());
+        assert_eq!(gate.admit(nal(160, true)).len(), 1);
+    }
+
+    #[test]
+    fn clock_starts_at_zero_and_rebases_on_source_restart() {
+        let mut clock = MediaClock::default();
+        assert!(!clock.started());
+        assert_eq!(clock.elapsed(5_000_000), 0);
+        assert_eq!(clock.elapsed(5_040_000), 40_000);
+        // Audio a little behind video is fine.
+        assert_eq!(clock.elapsed(5_020_000), 20_000);
+        // The reader reconnected and its clock restarted near zero.
+        assert_eq!(clock.elapsed(10_000), 80_000);
+        assert_eq!(clock.elapsed(50_000), 120_000);
+        // A wild forward jump is also smoothed over.
+        assert_eq!(clock.elapsed(3_600_000_000), 160_000);
+    }
+}
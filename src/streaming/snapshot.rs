@@ -198,19 +198,7 @@ impl SnapshotService {
     ) -> Result<Vec<u8>, SnapshotError> {
         use ffmpeg_next as ffmpeg;
 
-        // Select the decoder matching the captured stream's codec.
-        let codec_id = match codec {
-            VideoCodec::H265 => ffmpeg::codec::Id::HEVC,
-            VideoCodec::H264 | VideoCodec::Unknown => ffmpeg::codec::Id::H264,
-        };
-        let decoder_codec = ffmpeg::codec::decoder::find(codec_id).ok_or_else(|| {
-            SnapshotError::DecodeFailed(format!("{} decoder not found", codec.as_str()))
-        })?;
-
-        let mut decoder_ctx = ffmpeg::codec::Context::new_with_codec(decoder_codec)
-            .decoder()
-            .video()
-            .map_err(|e| SnapshotError::DecodeFailed(format!("Failed to open decoder: {}", e)))?;
+        let mut decoder_ctx = open_decoder(codec)?;
 
         // Feed the raw Annex B data as a single packet
         let mut av_packet = ffmpeg::Packet::copy(video_data);
@@ -236,72 +224,11 @@ impl SnapshotService {
             ));
         }
 
-        // Convert pixel format to YUVJ420P (MJPEG's native format)
-        let target_format = ffmpeg::format::Pixel::YUVJ420P;
-        let mut scaler = ffmpeg::software::scaling::Context::get(
-            decoded_frame.format(),
-            decoded_frame.width(),
-            decoded_frame.height(),
-            target_format,
+        JpegEncoder::new().encode(
+            &decoded_frame,
             decoded_frame.width(),
             decoded_frame.height(),
-            ffmpeg::software::scaling::Flags::BILINEAR,
         )
-        .map_err(|e| SnapshotError::EncodeFailed(format!("Scaler init failed: {}", e)))?;
-
-        let mut yuv_frame = ffmpeg::frame::Video::empty();
-        scaler
-            .run(&decoded_frame, &mut yuv_frame)
-            .map_err(|e| SnapshotError::EncodeFailed(format!("Scaler run failed: {}", e)))?;
-
-        // Encode as MJPEG
-        let encoder_codec = ffmpeg::codec::encoder::find(ffmpeg::codec::Id::MJPEG)
-            .ok_or_else(|| SnapshotError::EncodeFailed("MJPEG encoder not found".into()))?;
-
-        let mut encoder_ctx = ffmpeg::codec::Context::new_with_codec(encoder_codec)
-            .encoder()
-            .video()
-            .map_err(|e| SnapshotError::EncodeFailed(format!("Failed to init encoder: {}", e)))?;
-
-        encoder_ctx.set_width(decoded_frame.width());
-        encoder_ctx.set_height(decoded_frame.height());
-        encoder_ctx.set_format(target_format);
-        encoder_ctx.set_time_base(ffmpeg::Rational(1, 25));
-        encoder_ctx.set_quality(JPEG_QUALITY as usize);
-
-        // Set global quality via priv_data for MJPEG (qscale maps to quality)
-        let encoder_ctx = encoder_ctx.open().map_err(|e| {
-            SnapshotError::EncodeFailed(format!("Failed to open MJPEG encoder: {}", e))
-        })?;
-
-        // Wrap in a new scope to encode
-        let mut encoder = encoder_ctx;
-        yuv_frame.set_pts(Some(0));
-
-        encoder
-            .send_frame(&yuv_frame)
-            .map_err(|e| SnapshotError::EncodeFailed(format!("send_frame failed: {}", e)))?;
-        encoder
-            .send_eof()
-            .map_err(|e| SnapshotError::EncodeFailed(format!("encoder send_eof failed: {}", e)))?;
-
-        let mut encoded_packet = ffmpeg::Packet::empty();
-        encoder
-            .receive_packet(&mut encoded_packet)
-            .map_err(|e| SnapshotError::EncodeFailed(format!("No JPEG packet received: {}", e)))?;
-
-        let jpeg_data = encoded_packet
-            .data()
-            .ok_or_else(|| SnapshotError::EncodeFailed("Encoded packet has no data".into()))?
-            .to_vec();
-
-        if jpeg_data.is_empty() {
-            return Err(SnapshotError::EncodeFailed(
-                "MJPEG encoder produced empty output".into(),
-            ));
-        }
-
-        Ok(jpeg_data)
     }
 }
 
@@ -400,66 +327,136 @@ fn extract_mp4_thumbnail_blocking(path: &Path, max_width: u32) -> Result<Vec<u8>
         (max_width & !1, scaled_h.max(2) & !1)
     };
 
-    let target_format = ffmpeg::format::Pixel::YUVJ420P;
-    let mut scaler = ffmpeg::software::scaling::Context::get(
-        decoded.format(),
-        src_w,
-        src_h,
-        target_format,
-        dst_w,
-        dst_h,
-        ffmpeg::software::scaling::Flags::BILINEAR,
-    )
-    .map_err(|e| SnapshotError::EncodeFailed(format!("Scaler init failed: {}", e)))?;
-
-    let mut yuv_frame = ffmpeg::frame::Video::empty();
-    scaler
-        .run(&decoded, &mut yuv_frame)
-        .map_err(|e| SnapshotError::EncodeFailed(format!("Scaler run failed: {}", e)))?;
-
-    let encoder_codec = ffmpeg::codec::encoder::find(ffmpeg::codec::Id::MJPEG)
-        .ok_or_else(|| SnapshotError::EncodeFailed("MJPEG encoder not found".into()))?;
-
-    let mut encoder_ctx = ffmpeg::codec::Context::new_with_codec(encoder_codec)
-        .encoder()
+    JpegEncoder::new().encode(&decoded, dst_w, dst_h)
+}
+
+/// Open a decoder for a live stream's codec.
+pub(crate) fn open_decoder(
+    codec: VideoCodec,
+) -> Result<ffmpeg_next::codec::decoder::Video, SnapshotError> {
+    use ffmpeg_next as ffmpeg;
+
+    // Select the decoder matching the captured stream's codec.
+    let codec_id = match codec {
+        VideoCodec::H265 => ffmpeg::codec::Id::HEVC,
+        VideoCodec::H264 | VideoCodec::Unknown => ffmpeg::codec::Id::H264,
+    };
+    let decoder_codec = ffmpeg::codec::decoder::find(codec_id).ok_or_else(|| {
+        SnapshotError::DecodeFailed(format!("{} decoder not found", codec.as_str()))
+    })?;
+
+    ffmpeg::codec::Context::new_with_codec(decoder_codec)
+        .decoder()
         .video()
-        .map_err(|e| SnapshotError::EncodeFailed(format!("Failed to init encoder: {}", e)))?;
-
-    encoder_ctx.set_width(dst_w);
-    encoder_ctx.set_height(dst_h);
-    encoder_ctx.set_format(target_format);
-    encoder_ctx.set_time_base(ffmpeg::Rational(1, 25));
-    encoder_ctx.set_quality(JPEG_QUALITY as usize);
-
-    let mut encoder = encoder_ctx
-        .open()
-        .map_err(|e| SnapshotError::EncodeFailed(format!("Failed to open MJPEG encoder: {}", e)))?;
-
-    yuv_frame.set_pts(Some(0));
-    encoder
-        .send_frame(&yuv_frame)
-        .map_err(|e| SnapshotError::EncodeFailed(format!("send_frame failed: {}", e)))?;
-    encoder
-        .send_eof()
-        .map_err(|e| SnapshotError::EncodeFailed(format!("encoder send_eof failed: {}", e)))?;
-
-    let mut encoded_packet = ffmpeg::Packet::empty();
-    encoder
-        .receive_packet(&mut encoded_packet)
-        .map_err(|e| SnapshotError::EncodeFailed(format!("No JPEG packet received: {}", e)))?;
-
-    let jpeg = encoded_packet
-        .data()
-        .ok_or_else(|| SnapshotError::EncodeFailed("Encoded packet has no data".into()))?
-        .to_vec();
-
-    if jpeg.is_empty() {
-        return Err(SnapshotError::EncodeFailed(
-            "MJPEG encoder produced empty output".into(),
-        ));
+        .map_err(|e| SnapshotError::DecodeFailed(format!("Failed to open decoder: {}", e)))
+}
+
+/// Scales decoded frames to YUVJ420P (MJPEG's native format) and encodes
+/// them as JPEG. The scaler and encoder are kept while the source format and
+/// output size stay the same, so a stream of frames pays for setup once.
+pub(crate) struct JpegEncoder {
+    state: Option<EncoderState>,
+    pts: i64,
+}
+
+struct EncoderState {
+    /// Source format, source size and output size the state was built for.
+    geometry: (ffmpeg_next::format::Pixel, u32, u32, u32, u32),
+    scaler: ffmpeg_next::software::scaling::Context,
+    encoder: ffmpeg_next::codec::encoder::video::Encoder,
+}
+
+impl JpegEncoder {
+    pub(crate) fn new() -> Self {
+        Self {
+            state: None,
+            pts: 0,
+        }
     }
 
-    Ok(jpeg)
+    /// Encode `frame` as a `dst_w`x`dst_h` JPEG. MJPEG is intra-only, so each
+    /// frame's packet comes straight back without flushing the encoder.
+    pub(crate) fn encode(
+        &mut self,
+        frame: &ffmpeg_next::frame::Video,
+        dst_w: u32,
+        dst_h: u32,
+    ) -> Result<Vec<u8>, SnapshotError> {
+        use ffmpeg_next as ffmpeg;
+
+        let target_format = ffmpeg::format::Pixel::YUVJ420P;
+        let geometry = (frame.format(), frame.width(), frame.height(), dst_w, dst_h);
+        let state = match &mut self.state {
+            Some(state) if state.geometry == geometry => state,
+            slot => {
+                let scaler = ffmpeg::software::scaling::Context::get(
+                    frame.format(),
+                    frame.width(),
+                    frame.height(),
+                    target_format,
+                    dst_w,
+                    dst_h,
+                    ffmpeg::software::scaling::Flags::BILINEAR,
+                )
+                .map_err(|e| SnapshotError::EncodeFailed(format!("Scaler init failed: {}", e)))?;
+
+                let encoder_codec = ffmpeg::codec::encoder::find(ffmpeg::codec::Id::MJPEG)
+                    .ok_or_else(|| SnapshotError::EncodeFailed("MJPEG encoder not found".into()))?;
+                let mut encoder_ctx = ffmpeg::codec::Context::new_with_codec(encoder_codec)
+                    .encoder()
+                    .video()
+                    .map_err(|e| {
+                        SnapshotError::EncodeFailed(format!("Failed to init encoder: {}", e))
+                    })?;
+                encoder_ctx.set_width(dst_w);
+                encoder_ctx.set_height(dst_h);
+                encoder_ctx.set_format(target_format);
+                encoder_ctx.set_time_base(ffmpeg::Rational(1, 25));
+                encoder_ctx.set_quality(JPEG_QUALITY as usize);
+                let encoder = encoder_ctx.open().map_err(|e| {
+                    SnapshotError::EncodeFailed(format!("Failed to open MJPEG encoder: {}", e))
+                })?;
+
+                slot.insert(EncoderState {
+                    geometry,
+                    scaler,
+                    encoder,
+                })
+            }
+        };
+
+        let mut yuv_frame = ffmpeg::frame::Video::empty();
+        state
+            .scaler
+            .run(frame, &mut yuv_frame)
+            .map_err(|e| SnapshotError::EncodeFailed(format!("Scaler run failed: {}", e)))?;
+
+        yuv_frame.set_pts(Some(self.pts));
+        self.pts += 1;
+        state
+            .encoder
+            .send_frame(&yuv_frame)
+            .map_err(|e| SnapshotError::EncodeFailed(format!("send_frame failed: {}", e)))?;
+
+        let mut encoded_packet = ffmpeg::Packet::empty();
+        state
+            .encoder
+            .receive_packet(&mut encoded_packet)
+            .map_err(|e| SnapshotError::EncodeFailed(format!("No JPEG packet received: {}", e)))?;
+
+        let jpeg = encoded_packet
+            .data()
+            .ok_or_else(|| SnapshotError::EncodeFailed("Encoded packet has no data".into()))?
+            .to_vec();
+
+        if jpeg.is_empty() {
+            return Err(SnapshotError::EncodeFailed(
+                "MJPEG encoder produced empty output".into(),
+            ));
+        }
+
+        Ok(jpeg)
+    }
 }
 
 /// A codec parameter-set NAL unit.
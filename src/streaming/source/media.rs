@@ -396,6 +396,21 @@ impl AdtsWrapper {
         })
     }
 
+    /// AAC-LC at `sample_rate` with `channels` — what an encoder configured
+    /// that way produces. `None` for a rate outside the ADTS table.
+    pub fn aac_lc(sample_rate: u32, channels: u8) -> Option<Self> {
+        let sampling_frequency_index =
+            AAC_SAMPLE_RATES.iter().position(|r| *r == sample_rate)? as u8;
+        if channels == 0 || channels > 7 {
+            return None;
+        }
+        Some(Self {
+            audio_object_type: 2,
+            sampling_frequency_index,
+            channel_configuration: channels,
+        })
+    }
+
     /// Frame `raw` with a 7-byte ADTS header (no CRC). Returns `None` when
     /// the frame exceeds the 13-bit ADTS length field (never for real AAC).
     pub fn wrap(&self, raw: &[u8]) -> Option<Vec<u8>> {
@@ -949,6 +964,16 @@ mod tests {
         assert!(AdtsWrapper::from_asc(&[0x12, 0x00]).is_none());
     }
 
+    #[test]
+    fn test_adts_wrapper_for_aac_lc_matches_its_asc() {
+        assert_eq!(
+            AdtsWrapper::aac_lc(16000, 1),
+            AdtsWrapper::from_asc(&[0x14, 0x08])
+        );
+        assert!(AdtsWrapper::aac_lc(12345, 1).is_none());
+        assert!(AdtsWrapper::aac_lc(8000, 0).is_none());
+    }
+
     #[test]
     fn test_adts_wrapper_rejects_oversized_frame() {
         let wrapper = AdtsWrapper::from_asc(&[0x12, 0x10]).unwrap();
@@ -1,6 +1,7 @@
 pub mod media;
 pub mod protocol;
 pub mod router;
+pub mod rtsp_pull;
 pub mod stream_socket;
 
 // Re-export media types and bitstream helpers
@@ -12,6 +13,9 @@ pub use media::{
 // Re-export stream-socket reader types
 pub use stream_socket::{stream_socket_path, SocketEvent, SourceError, StreamSocketReader};
 
+// Re-export the substream pull source
+pub use rtsp_pull::SubstreamSource;
+
 // Re-export the monitor EVENT type for DB ingest consumers.
 pub use protocol::MonitorEvent;
 
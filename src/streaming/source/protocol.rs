@@ -17,6 +17,9 @@
 //! ```
 //!
 //! Version 1 has no client-to-server messages; zmc ignores inbound bytes.
+//! zm-next's optional control extension adds a few (see [`MSG_TYPE_COMMAND`]
+//! and [`MSG_TYPE_TALKBACK`]); they are only ever written to a worker that
+//! consumes them.
 
 use super::media::{AudioCodec, VideoCodec};
 
@@ -122,6 +125,20 @@ pub const EVENT_REVIEW_ASSETS: u16 = 0x0306; // motion-synopsis ingredients: tub
 /// consumes this to learn the event id + target path for a recording segment.
 pub const MSG_TYPE_COMMAND: u8 = 0x11;
 
+/// Client→server talkback audio (the `0x13 Talkback` of the control
+/// extension): one encoded frame for the camera's backchannel, on the audio
+/// stream with its `pts_us`. The payload is
+///
+/// ```text
+/// u32  codec_id     AVCodecID: PCM_MULAW, PCM_ALAW or AAC
+/// u32  sample_rate  Hz
+/// ...  frame        G.711 bytes, or one ADTS-framed AAC frame
+/// ```
+pub const MSG_TYPE_TALKBACK: u8 = 0x13;
+
+/// Bytes of talkback payload ahead of the frame.
+const TALKBACK_PREFIX_LEN: usize = 8;
+
 // EVENT TLV tags
 const TLV_WALL_CLOCK_US: u8 = 0x01; // u64, unix-epoch microseconds
 const TLV_MESSAGE: u8 = 0x02; // utf8, human-readable detail
@@ -288,6 +305,46 @@ pub struct MonitorEvent {
     /// zm-next extension: structured analysis/AI detail as a UTF-8 JSON
     /// document (detection object list, description text, recording metadata).
     pub json_detail: Option<String>,
+    /// Header `sequence`: the per-monitor event counter, so a gap means events
+    /// were dropped. Filled in by the socket reader — [`parse_event`] only sees
+    /// the payload and leaves it 0.
+    pub sequence: u32,
+    /// Header `generation`: the media stream epoch in effect at emission (0 if
+    /// no media has started). Filled in by the socket reader, like `sequence`.
+    pub generation: u32,
+}
+
+impl MonitorEvent {
+    /// The wire name for this event's code (`"connection_failed"`, ...), as
+    /// used for SSE `event:` fields. Unknown codes render as `0x09ff` so they
+    /// are still surfaced rather than dropped.
+    pub fn name(&self) -> std::borrow::Cow<'static, str> {
+        match event_code_name(self.code) {
+            Some(name) => std::borrow::Cow::Borrowed(name),
+            None => std::borrow::Cow::Owned(format!("{:#06x}", self.code)),
+        }
+    }
+}
+
+/// The canonical name of a known `EVENT_*` code, or `None` for a code this
+/// build does not know.
+pub fn event_code_name(code: u16) -> Option<&'static str> {
+    Some(match code {
+        EVENT_SNAPSHOT => "snapshot",
+        EVENT_CONNECTION_FAILED => "connection_failed",
+        EVENT_CONNECTION_RESTORED => "connection_restored",
+        EVENT_PRIME_CAPTURE_FAILED => "prime_capture_failed",
+        EVENT_PRIME_CAPTURE_RESTORED => "prime_capture_restored",
+        EVENT_CAPTURE_FAILED => "capture_failed",
+        EVENT_CAPTURE_RESUMED => "capture_resumed",
+        EVENT_STATE_CHANGED => "state_changed",
+        EVENT_DETECTION => "detection",
+        EVENT_DESCRIPTION => "description",
+        EVENT_RECORDING_SAVED => "recording_saved",
+        EVENT_RECORDING_OPENING => "recording_opening",
+        EVENT_REVIEW_ASSETS => "review_assets",
+        _ => return None,
+    })
 }
 
 /// Parse an EVENT payload: a u16 `code` followed by a TLV tail of
@@ -359,6 +416,32 @@ pub fn build_control_message(msg_type: u8, sequence: u32, payload: &[u8]) -> Vec
     out
 }
 
+/// Serialize a `0x13 Talkback` message carrying one backchannel frame. Unlike
+/// the other control messages it rides the audio stream and carries a real
+/// `pts_us`, so the worker can pace playback out of the camera's speaker.
+pub fn build_talkback_message(
+    sequence: u32,
+    pts_us: i64,
+    codec_id: u32,
+    sample_rate: u32,
+    frame: &[u8],
+) -> Vec<u8> {
+    let payload_len = TALKBACK_PREFIX_LEN + frame.len();
+    let mut out = Vec::with_capacity(HEADER_SIZE + payload_len);
+    out.extend_from_slice(&(HEADER_LENGTH_BYTES + payload_len as u32).to_le_bytes());
+    out.push(PROTOCOL_VERSION);
+    out.push(MSG_TYPE_TALKBACK);
+    out.push(StreamId::Audio as u8);
+    out.push(0); // flags
+    out.extend_from_slice(&sequence.to_le_bytes());
+    out.extend_from_slice(&0u32.to_le_bytes()); // generation (unused)
+    out.extend_from_slice(&(pts_us.max(0) as u64).to_le_bytes());
+    out.extend_from_slice(&codec_id.to_le_bytes());
+    out.extend_from_slice(&sample_rate.to_le_bytes());
+    out.extend_from_slice(frame);
+    out
+}
+
 /// Parse a STATS payload: u64 messages sent, u64 messages dropped for this
 /// consumer.
 pub fn parse_stats(data: &[u8]) -> Result<(u64, u64), ProtocolError> {
@@ -668,6 +751,27 @@ mod tests {
         assert_eq!(ev.message.as_deref(), Some("hi"));
     }
 
+    #[test]
+    fn event_names_cover_known_and_unknown_codes() {
+        assert_eq!(
+            event_code_name(EVENT_CONNECTION_FAILED),
+            Some("connection_failed")
+        );
+        assert_eq!(event_code_name(EVENT_STATE_CHANGED), Some("state_changed"));
+        assert_eq!(event_code_name(0x09FF), None);
+
+        let known = MonitorEvent {
+            code: EVENT_CAPTURE_RESUMED,
+            ..MonitorEvent::default()
+        };
+        assert_eq!(known.name(), "capture_resumed");
+        let unknown = MonitorEvent {
+            code: 0x09FF,
+            ..MonitorEvent::default()
+        };
+        assert_eq!(unknown.name(), "0x09ff");
+    }
+
     #[test]
     fn event_rejects_short_code_and_truncated_tlv() {
         // Fewer than the 2 fixed code bytes.
@@ -697,6 +801,23 @@ mod tests {
         assert_eq!(MessageType::from_u8(header.msg_type), None);
     }
 
+    #[test]
+    fn talkback_message_carries_codec_rate_and_pts() {
+        let frame = [0xFFu8; 160];
+        let msg = build_talkback_message(9, 40_000, alaw_codec_id(), 8000, &frame);
+        let header = parse_header(msg[..HEADER_SIZE].try_into().unwrap()).unwrap();
+        assert_eq!(header.msg_type, MSG_TYPE_TALKBACK);
+        assert_eq!(header.stream, 1); // Audio
+        assert_eq!(header.sequence, 9);
+        assert_eq!(header.pts_us, 40_000);
+        assert_eq!(header.payload_len, 8 + frame.len());
+        let payload = &msg[HEADER_SIZE..];
+        assert_eq!(read_u32(payload, 0), alaw_codec_id());
+        assert_eq!(read_u32(payload, 4), 8000);
+        assert_eq!(&payload[8..], &frame);
+        assert_eq!(MessageType::from_u8(header.msg_type), None);
+    }
+
     #[test]
     fn stats_parse() {
         let mut payload = 1000u64.to_le_bytes().to_vec();
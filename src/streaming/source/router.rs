@@ -45,6 +45,8 @@ pub struct MonitorEventEnvelope {
 pub struct ControlReply {
     tx: mpsc::Sender<Vec<u8>>,
     seq: Arc<AtomicU32>,
+    /// Talkback rides the audio stream, which has its own sequence.
+    talkback_seq: Arc<AtomicU32>,
 }
 
 impl ControlReply {
@@ -52,6 +54,7 @@ impl ControlReply {
         Self {
             tx,
             seq: Arc::new(AtomicU32::new(0)),
+            talkback_seq: Arc::new(AtomicU32::new(0)),
         }
     }
 
@@ -73,11 +76,45 @@ impl ControlReply {
         let msg = protocol::build_control_message(protocol::MSG_TYPE_COMMAND, seq, json.as_bytes());
         self.tx.try_send(msg).is_ok()
     }
+
+    /// Queue a `0x13 Talkback` frame for the camera's backchannel. Same
+    /// best-effort semantics: a frame that does not fit is dropped, which the
+    /// listener hears as a gap rather than as growing latency.
+    pub fn send_talkback(
+        &self,
+        pts_us: i64,
+        codec_id: u32,
+        sample_rate: u32,
+        frame: &[u8],
+    ) -> bool {
+        let seq = self.talkback_seq.fetch_add(1, Ordering::Relaxed);
+        let msg = protocol::build_talkback_message(seq, pts_us, codec_id, sample_rate, frame);
+        self.tx.try_send(msg).is_ok()
+    }
+
+    /// Whether the connection behind this handle has gone.
+    pub fn is_closed(&self) -> bool {
+        self.tx.is_closed()
+    }
 }
 
 /// Default broadcast channel capacity for source packets
 const DEFAULT_SOURCE_CAPACITY: usize = 100;
 
+/// Control messages queued per connection. Talkback queues a frame every
+/// 20 ms, so this is also how much speech may back up behind a slow write.
+const CONTROL_QUEUE_CAPACITY: usize = 32;
+
+/// Longest a control write may block the reader. zmc never reads inbound
+/// bytes, so once the socket buffer fills a write would stall media for good;
+/// past this the connection is dropped and re-established.
+const CONTROL_WRITE_TIMEOUT: Duration = Duration::from_secs(1);
+
+/// Broadcast capacity for monitor EVENTs. Events are sparse next to media, so
+/// a small buffer absorbs bursts; a subscriber that still falls behind sees
+/// `Lagged` and re-syncs from the status snapshot.
+const EVENT_CHANNEL_CAPACITY: usize = 64;
+
 /// Health state of the stream-socket reader task.
 ///
 /// Subscribers (e.g. the coordinator's processing task) can watch this to
@@ -223,6 +260,61 @@ impl KeyframeCacheBuilder {
     }
 }
 
+/// Fold one EVENT into the cached status snapshot. Returns whether the
+/// snapshot changed.
+///
+/// A `snapshot` from the producer replaces the cache outright. Health and
+/// state transitions update the matching fields, so a subscriber joining later
+/// gets the *current* status rather than whatever held at zmc's last connect.
+/// Analysis events (detection, recordings, ...) describe no status and leave it
+/// alone.
+fn fold_status_snapshot(status: &mut Option<MonitorEvent>, event: &MonitorEvent) -> bool {
+    use protocol::{
+        EVENT_CAPTURE_FAILED, EVENT_CAPTURE_RESUMED, EVENT_CONNECTION_FAILED,
+        EVENT_CONNECTION_RESTORED, EVENT_PRIME_CAPTURE_FAILED, EVENT_PRIME_CAPTURE_RESTORED,
+        EVENT_SNAPSHOT, EVENT_STATE_CHANGED,
+    };
+
+    if event.code == EVENT_SNAPSHOT {
+        *status = Some(event.clone());
+        return true;
+    }
+
+    let is_fault = matches!(
+        event.code,
+        EVENT_CONNECTION_FAILED | EVENT_PRIME_CAPTURE_FAILED | EVENT_CAPTURE_FAILED
+    );
+    let is_recovery = matches!(
+        event.code,
+        EVENT_CONNECTION_RESTORED | EVENT_PRIME_CAPTURE_RESTORED | EVENT_CAPTURE_RESUMED
+    );
+    if !is_fault && !is_recovery && event.code != EVENT_STATE_CHANGED {
+        return false;
+    }
+
+    let snap = status.get_or_insert_with(|| MonitorEvent {
+        code: EVENT_SNAPSHOT,
+        ..MonitorEvent::default()
+    });
+    if is_fault {
+        snap.health_code = Some(event.code);
+        snap.message = event.message.clone();
+        snap.detail = event.detail;
+    } else if is_recovery {
+        snap.health_code = Some(0);
+        snap.message = None;
+        snap.detail = None;
+    } else {
+        snap.state_id = event.state_id.or(snap.state_id);
+        snap.prev_state_id = event.prev_state_id;
+        snap.state_name = event.state_name.clone().or(snap.state_name.take());
+    }
+    snap.wall_clock_us = event.wall_clock_us.or(snap.wall_clock_us);
+    snap.sequence = event.sequence;
+    snap.generation = event.generation;
+    true
+}
+
 /// Represents an active monitor source with video and optional audio streams
 pub struct MonitorSource {
     monitor_id: u32,
@@ -253,6 +345,17 @@ pub struct MonitorSource {
     /// Updated each time an IDR is seen by the reader task.
     keyframe_cache_tx: watch::Sender<Option<CachedKeyframe>>,
     keyframe_cache_rx: watch::Receiver<Option<CachedKeyframe>>,
+    /// Broadcast sender for monitor EVENTs decoded off the socket.
+    event_tx: broadcast::Sender<MonitorEvent>,
+    /// Current health + state as a `snapshot` event: zmc's last snapshot with
+    /// every later health/state transition folded in (see
+    /// [`fold_status_snapshot`]). The events analogue of `keyframe_cache` —
+    /// replayed to each new subscriber and after a lag.
+    status_tx: watch::Sender<Option<MonitorEvent>>,
+    status_rx: watch::Receiver<Option<MonitorEvent>>,
+    /// Control handle of the current connection; `None` while disconnected.
+    control_tx: watch::Sender<Option<ControlReply>>,
+    control_rx: watch::Receiver<Option<ControlReply>>,
 }
 
 impl MonitorSource {
@@ -263,6 +366,9 @@ impl MonitorSource {
         let (reader_health_tx, reader_health_rx) = watch::channel(ReaderHealth::Idle);
         let (stream_info_tx, stream_info_rx) = watch::channel(None);
         let (keyframe_cache_tx, keyframe_cache_rx) = watch::channel(None);
+        let (event_tx, _) = broadcast::channel(EVENT_CHANNEL_CAPACITY);
+        let (status_tx, status_rx) = watch::channel(None);
+        let (control_tx, control_rx) = watch::channel(None);
 
         Self {
             monitor_id,
@@ -278,6 +384,11 @@ impl MonitorSource {
             stream_info_rx,
             keyframe_cache_tx,
             keyframe_cache_rx,
+            event_tx,
+            status_tx,
+            status_rx,
+            control_tx,
+            control_rx,
         }
     }
 
@@ -363,6 +474,34 @@ impl MonitorSource {
         self.keyframe_cache_rx.clone()
     }
 
+    /// Subscribe to monitor EVENTs (lifecycle, health, analysis) as they are
+    /// decoded off the socket. Pair with [`Self::current_status`] for the
+    /// state as of subscription — the broadcast itself has no replay.
+    pub fn subscribe_events(&self) -> broadcast::Receiver<MonitorEvent> {
+        self.event_tx.subscribe()
+    }
+
+    /// Current health + state as a `snapshot` event, or `None` when the
+    /// producer has sent neither a snapshot nor a health/state transition yet.
+    pub fn current_status(&self) -> Option<MonitorEvent> {
+        self.status_rx.borrow().clone()
+    }
+
+    /// Control handle for the socket connection, for client→server messages
+    /// such as talkback. `None` while the reader is not connected.
+    pub fn control(&self) -> Option<ControlReply> {
+        self.control_rx
+            .borrow()
+            .clone()
+            .filter(|reply| !reply.is_closed())
+    }
+
+    /// Get the number of EVENT subscribers. A non-zero count keeps the reader
+    /// connected even with no media session (see the coordinator's reaper).
+    pub fn event_subscriber_count(&self) -> usize {
+        self.event_tx.receiver_count()
+    }
+
     /// Get the number of video subscribers
     pub fn video_subscriber_count(&self) -> usize {
         self.video_tx.receiver_count()
@@ -436,6 +575,10 @@ pub struct SourceRouter {
     /// `try_send`, so a slow/backed-up ingest never stalls the media reader).
     /// `None` means events are simply not ingested (e.g. tests, or DB absent).
     event_sink: Option<mpsc::Sender<MonitorEventEnvelope>>,
+    /// Cross-monitor copy of every decoded EVENT, tagged with its monitor id,
+    /// for consumers that watch all monitors at once (the event firehose's
+    /// capture-fault bridge). Unlike `event_sink` it is lossy and always on.
+    event_fanout: broadcast::Sender<(u32, MonitorEvent)>,
     /// Most-recent WebRTC startup timing per monitor, recorded by the signaling
     /// handler and surfaced on `/live/{id}/stats` to confirm cold-vs-warm.
     webrtc_startup: DashMap<u32, WebRtcStartupTiming>,
@@ -477,6 +620,7 @@ impl SourceRouter {
             active_sources: DashMap::new(),
             config,
             event_sink: None,
+            event_fanout: broadcast::channel(EVENT_CHANNEL_CAPACITY).0,
             webrtc_startup: DashMap::new(),
         }
     }
@@ -490,6 +634,13 @@ impl SourceRouter {
         self.event_sink = Some(sink);
     }
 
+    /// Subscribe to EVENTs from every monitor whose reader is running, as
+    /// `(monitor_id, event)` pairs. Lossy under backpressure, like the
+    /// per-source [`MonitorSource::subscribe_events`].
+    pub fn subscribe_all_events(&self) -> broadcast::Receiver<(u32, MonitorEvent)> {
+        self.event_fanout.subscribe()
+    }
+
     /// Create a source router from ZoneMinder configuration
     pub fn from_zoneminder_config(zm_config: ZoneMinderConfig) -> Self {
         Self::with_config(RouterConfig::from_zoneminder(zm_config))
@@ -593,7 +744,11 @@ impl SourceRouter {
         let health_tx = source.reader_health_tx.clone();
         let stream_info_tx = source.stream_info_tx.clone();
         let keyframe_cache_tx = source.keyframe_cache_tx.clone();
+        let event_tx = source.event_tx.clone();
+        let status_tx = source.status_tx.clone();
+        let control_tx = source.control_tx.clone();
         let event_sink = self.event_sink.clone();
+        let event_fanout = self.event_fanout.clone();
 
         let handle = tokio::spawn(async move {
             info!(
@@ -638,13 +793,15 @@ impl SourceRouter {
                 }
 
                 // Per-connection control channel for the id-assignment
-                // handshake: ingest queues replies here and the reader task
-                // writes them to this connection's write half. Recreated each
-                // reconnect; the old channel + writer drop with the old socket.
+                // handshake and talkback: ingest and talkback sessions queue
+                // messages here and the reader task writes them to this
+                // connection's write half. Recreated each reconnect; the old
+                // channel + writer drop with the old socket.
                 let mut writer = reader.take_writer();
-                let (cmd_tx, mut cmd_rx) = mpsc::channel::<Vec<u8>>(8);
+                let (cmd_tx, mut cmd_rx) = mpsc::channel::<Vec<u8>>(CONTROL_QUEUE_CAPACITY);
                 let control_reply = ControlReply::new(cmd_tx.clone());
                 let _cmd_keepalive = cmd_tx; // hold the channel open for this connection
+                let _ = control_tx.send(Some(control_reply.clone()));
 
                 // Topology of this connection. zmc sends every stream's HELLO
                 // before any media, so the first media event confirms the
@@ -662,10 +819,24 @@ impl SourceRouter {
                         biased;
                         Some(bytes) = cmd_rx.recv() => {
                             if let Some(w) = writer.as_mut() {
-                                if let Err(e) = w.write_all(&bytes).await {
-                                    warn!("Monitor {}: control write failed: {}", monitor_id, e);
-                                } else {
-                                    let _ = w.flush().await;
+                                let write = async {
+                                    w.write_all(&bytes).await?;
+                                    w.flush().await
+                                };
+                                match tokio::time::timeout(CONTROL_WRITE_TIMEOUT, write).await {
+                                    Ok(Ok(())) => {}
+                                    Ok(Err(e)) => {
+                                        warn!("Monitor {}: control write failed: {}", monitor_id, e);
+                                    }
+                                    Err(_) => {
+                                        // A partial write has desynchronised the
+                                        // framing; start over on a fresh connection.
+                                        warn!(
+                                            "Monitor {}: control write stalled, reconnecting",
+                                            monitor_id
+                                        );
+                                        break;
+                                    }
                                 }
                             }
                         }
@@ -725,6 +896,17 @@ impl SourceRouter {
                             let _ = audio_tx.send(packet);
                         }
                         Ok(SocketEvent::MonitorEvent(event)) => {
+                            // Live subscribers (SSE) first: keep the status
+                            // snapshot current, then fan out. No receivers is
+                            // fine — nobody is watching this monitor's events.
+                            status_tx.send_if_modified(|status| {
+                                fold_status_snapshot(status, &event)
+                            });
+                            let _ = event_tx.send(event.clone());
+                            if event_fanout.receiver_count() > 0 {
+                                let _ = event_fanout.send((monitor_id, event.clone()));
+                            }
+
                             // Forward to DB ingest. `try_send` keeps the media
                             // reader non-blocking: if ingest is backed up or
                             // absent we drop the event rather than stall video.
@@ -770,6 +952,7 @@ impl SourceRouter {
                 }
 
                 // Signal reconnecting before the delay
+                let _ = control_tx.send(None);
                 *source_for_task.active.write().await = false;
                 let _ = health_tx.send(ReaderHealth::Reconnecting);
 
@@ -1061,6 +1244,69 @@ mod tests {
         assert_eq!(source.codec().await, VideoCodec::Unknown);
     }
 
+    #[test]
+    fn status_snapshot_folds_health_and_state_transitions() {
+        use super::super::protocol::{
+            EVENT_CAPTURE_FAILED, EVENT_CAPTURE_RESUMED, EVENT_DETECTION, EVENT_SNAPSHOT,
+            EVENT_STATE_CHANGED,
+        };
+
+        let mut status = None;
+        // Analysis events carry no status.
+        let detection = MonitorEvent {
+            code: EVENT_DETECTION,
+            ..MonitorEvent::default()
+        };
+        assert!(!fold_status_snapshot(&mut status, &detection));
+        assert!(status.is_none());
+
+        let snapshot = MonitorEvent {
+            code: EVENT_SNAPSHOT,
+            state_id: Some(0),
+            state_name: Some("Idle".to_string()),
+            health_code: Some(0),
+            sequence: 1,
+            ..MonitorEvent::default()
+        };
+        assert!(fold_status_snapshot(&mut status, &snapshot));
+
+        let failed = MonitorEvent {
+            code: EVENT_CAPTURE_FAILED,
+            message: Some("decoder error".to_string()),
+            detail: Some(5),
+            sequence: 2,
+            ..MonitorEvent::default()
+        };
+        assert!(fold_status_snapshot(&mut status, &failed));
+        let s = status.as_ref().unwrap();
+        assert_eq!(s.code, EVENT_SNAPSHOT);
+        assert_eq!(s.health_code, Some(EVENT_CAPTURE_FAILED));
+        assert_eq!(s.message.as_deref(), Some("decoder error"));
+        assert_eq!(s.state_name.as_deref(), Some("Idle"));
+        assert_eq!(s.sequence, 2);
+
+        let alarm = MonitorEvent {
+            code: EVENT_STATE_CHANGED,
+            state_id: Some(3),
+            prev_state_id: Some(0),
+            state_name: Some("Alarm".to_string()),
+            sequence: 3,
+            ..MonitorEvent::default()
+        };
+        assert!(fold_status_snapshot(&mut status, &alarm));
+        let resumed = MonitorEvent {
+            code: EVENT_CAPTURE_RESUMED,
+            sequence: 4,
+            ..MonitorEvent::default()
+        };
+        assert!(fold_status_snapshot(&mut status, &resumed));
+        let s = status.unwrap();
+        assert_eq!(s.health_code, Some(0));
+        assert_eq!(s.message, None);
+        assert_eq!(s.state_id, Some(3));
+        assert_eq!(s.state_name.as_deref(), Some("Alarm"));
+    }
+
     #[test]
     fn test_is_available_nonexistent() {
         let router = SourceRouter::new();
@@ -1568,6 +1814,67 @@ mod tests {
         let _ = std::fs::remove_dir_all(&dir);
     }
 
+    /// EVENTs reach live subscribers and update the status snapshot even with
+    /// no ingest sink registered.
+    #[tokio::test]
+    async fn test_events_broadcast_to_subscribers() {
+        use super::super::protocol::{EVENT_CONNECTION_FAILED, EVENT_SNAPSHOT};
+
+        let dir = test_sock_dir("router_events");
+        let mut script = encode_message(
+            0x01,
+            0,
+            0,
+            0,
+            0,
+            0,
+            &hello_payload(h264_codec_id(), &h264_extradata()),
+        );
+        let snapshot = event_payload(EVENT_SNAPSHOT, &tlv_u16(0x07, 0));
+        let failed = event_payload(EVENT_CONNECTION_FAILED, &tlv(0x02, b"rtsp timeout"));
+        script.extend_from_slice(&encode_message(0x06, 2, 0, 0, 0, 0, &snapshot));
+        script.extend_from_slice(&encode_message(0x06, 2, 0, 1, 3, 0, &failed));
+        let server = spawn_fake_zmc(dir.join("stream_21.sock"), script, false);
+
+        let router = SourceRouter::from_zoneminder_config(test_zm_config(&dir));
+        let source = router.create_source(21).await.expect("create_source");
+        let mut events = source.subscribe_events();
+        let mut all_events = router.subscribe_all_events();
+        assert_eq!(source.event_subscriber_count(), 1);
+        router.start_reader(21).await.expect("start_reader");
+
+        let first = tokio::time::timeout(Duration::from_secs(5), events.recv())
+            .await
+            .expect("event within 5s")
+            .expect("snapshot");
+        assert_eq!(first.code, EVENT_SNAPSHOT);
+        let second = tokio::time::timeout(Duration::from_secs(5), events.recv())
+            .await
+            .expect("event within 5s")
+            .expect("connection_failed");
+        assert_eq!(second.code, EVENT_CONNECTION_FAILED);
+        assert_eq!(second.sequence, 1);
+        assert_eq!(second.generation, 3);
+
+        // The router-wide fan-out sees the same EVENTs, tagged by monitor.
+        for code in [EVENT_SNAPSHOT, EVENT_CONNECTION_FAILED] {
+            let (monitor_id, ev) = tokio::time::timeout(Duration::from_secs(5), all_events.recv())
+                .await
+                .expect("fan-out event within 5s")
+                .expect("fan-out event");
+            assert_eq!((monitor_id, ev.code), (21, code));
+        }
+
+        let status = source.current_status().expect("status snapshot");
+        assert_eq!(status.code, EVENT_SNAPSHOT);
+        assert_eq!(status.health_code, Some(EVENT_CONNECTION_FAILED));
+        assert_eq!(status.message.as_deref(), Some("rtsp timeout"));
+
+        let _ = router.stop_reader(21).await;
+        server.abort();
+        let _ = std::fs::remove_dir_all(&dir);
+    }
+
     /// A monitor without audio: the handshake completes with no audio codec.
     #[tokio::test]
     async fn test_reader_announces_video_only_topology() {
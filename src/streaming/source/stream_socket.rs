@@ -293,7 +293,11 @@ impl StreamSocketReader {
         // not desync the byte stream, and media must keep flowing.
         if msg_type == MessageType::Event {
             match protocol::parse_event(&payload) {
-                Ok(ev) => self.pending.push_back(SocketEvent::MonitorEvent(ev)),
+                Ok(mut ev) => {
+                    ev.sequence = header.sequence;
+                    ev.generation = header.generation;
+                    self.pending.push_back(SocketEvent::MonitorEvent(ev));
+                }
                 Err(e) => debug!(
                     "Monitor {}: skipping malformed EVENT payload: {e}",
                     self.monitor_id
@@ -523,7 +527,7 @@ impl StreamSocketReader {
 
 /// Whether an AU's NAL list already includes an SPS (the indicator that the
 /// camera repeats parameter sets in-band).
-fn au_has_parameter_sets(nals: &[Vec<u8>], codec: VideoCodec) -> bool {
+pub(super) fn au_has_parameter_sets(nals: &[Vec<u8>], codec: VideoCodec) -> bool {
     nals.iter().any(|nal| match codec {
         VideoCodec::H264 => h264_nal_type(nal) == Some(7),
         VideoCodec::H265 => h265_nal_type(nal) == Some(33),
@@ -1036,6 +1040,9 @@ mod tests {
         };
         assert_eq!(det.code, EVENT_DETECTION);
         assert_eq!(det.json_detail.as_deref(), Some(detection_json));
+        // The header sequence rides along so SSE consumers can spot gaps.
+        assert_eq!(snap.sequence, 0);
+        assert_eq!(det.sequence, 1);
 
         // Media still flows after the events.
         assert!(matches!(events[3], SocketEvent::Video(_)));
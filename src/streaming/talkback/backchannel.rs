@@ -0,0 +1,614 @@
+//! ONVIF audio backchannel client (ONVIF Streaming Specification §5.3).
+//!
+//! A camera with a speaker offers the backchannel on its ordinary RTSP URL to
+//! clients that send `Require: www.onvif.org/ver20/backchannel`: the DESCRIBE
+//! answer then carries an extra audio section marked `a=sendonly`, the
+//! direction the *client* sends in. zm-api SETUPs only that section,
+//! interleaved on the RTSP connection, PLAYs, and writes RTP to it.
+//!
+//! Authentication is Digest (or Basic, for cameras that only offer it) with
+//! the monitor's credentials. The session is kept alive with
+//! `GET_PARAMETER`; anything the camera sends back — replies, RTCP — is read
+//! and discarded so its socket buffer never fills.
+
+use std::time::{Duration, Instant};
+
+use base64::Engine as _;
+use rand::Rng as _;
+use tokio::io::{AsyncReadExt, AsyncWriteExt};
+use tokio::net::tcp::OwnedWriteHalf;
+use tokio::net::TcpStream;
+use tokio::task::JoinHandle;
+use tracing::{debug, info};
+
+use super::transcode::BackchannelFrame;
+use super::{TalkbackCodec, TalkbackError};
+use crate::entity::monitors;
+use crate::service::zmnext::pipeline::split_url_credentials;
+use crate::streaming::rtsp::message::{self, Frame, Reply};
+use crate::streaming::rtsp::rtp::AudioPacketizer;
+use crate::util::digest::DigestChallenge;
+
+/// The ONVIF feature tag that asks the camera for its backchannel.
+const REQUIRE_BACKCHANNEL: &str = "www.onvif.org/ver20/backchannel";
+/// Session timeout assumed when the camera does not state one (RFC 2326 §12.37).
+const DEFAULT_SESSION_TIMEOUT_SECS: u64 = 60;
+const DEFAULT_RTSP_PORT: u16 = 554;
+
+/// The camera to open a backchannel to.
+#[derive(Clone)]
+pub struct BackchannelTarget {
+    /// RTSP URL without userinfo.
+    pub url: String,
+    pub credentials: Option<(String, String)>,
+}
+
+impl std::fmt::Debug for BackchannelTarget {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        f.debug_struct("BackchannelTarget")
+            .field("url", &self.url)
+            .field(
+                "username",
+                &self.credentials.as_ref().map(|(user, _)| user.as_str()),
+            )
+            .finish_non_exhaustive()
+    }
+}
+
+impl BackchannelTarget {
+    /// The monitor's `Path`, if it is an RTSP URL.
+    pub fn from_monitor(monitor: &monitors::Model) -> Option<Self> {
+        Self::from_parts(
+            monitor.path.as_deref()?,
+            monitor.user.as_deref(),
+            monitor.pass.as_deref(),
+        )
+    }
+
+    /// Credentials follow the substream's rule: `User`/`Pass` win over ones
+    /// embedded in the URL.
+    fn from_parts(path: &str, user: Option<&str>, pass: Option<&str>) -> Option<Self> {
+        let url = path.trim();
+        if !url.to_ascii_lowercase().starts_with("rtsp://") {
+            return None;
+        }
+        let (url, embedded) = split_url_credentials(url);
+        let credentials = match user.filter(|u| !u.is_empty()) {
+            Some(user) => Some((user.to_string(), pass.unwrap_or_default().to_string())),
+            None => embedded,
+        };
+        Some(Self { url, credentials })
+    }
+}
+
+/// The backchannel section of a camera's SDP.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct BackchannelMedia {
+    /// URL to SETUP.
+    pub control: String,
+    pub payload_type: u8,
+    pub codec: TalkbackCodec,
+}
+
+/// Find the first `a=sendonly` audio section whose codec zm-api can encode.
+/// `base` resolves relative `a=control` URLs.
+pub fn find_backchannel(sdp: &str, base: &str) -> Option<BackchannelMedia> {
+    let mut sections = sdp.split("\nm=").skip(1);
+    sections.find_map(|section| {
+        let mut lines = section.lines().map(str::trim_end);
+        let media = lines.next()?;
+        let mut fields = media.split_whitespace();
+        if fields.next() != Some("audio") {
+            return None;
+        }
+        let payload_types: Vec<u8> = fields.skip(2).filter_map(|pt| pt.parse().ok()).collect();
+
+        let mut sendonly = false;
+        let mut control = None;
+        let mut rtpmap = Vec::new();
+        for line in lines {
+            if line == "a=sendonly" {
+                sendonly = true;
+            } else if let Some(value) = line.strip_prefix("a=control:") {
+                control = Some(value.trim());
+            } else if let Some(value) = line.strip_prefix("a=rtpmap:") {
+                if let Some((pt, encoding)) = value.split_once(' ') {
+                    if let Ok(pt) = pt.parse::<u8>() {
+                        rtpmap.push((pt, encoding.trim()));
+                    }
+                }
+            }
+        }
+        if !sendonly {
+            return None;
+        }
+
+        let (payload_type, codec) = payload_types.iter().find_map(|&pt| {
+            let encoding = rtpmap.iter().find(|(p, _)| *p == pt).map(|(_, e)| *e);
+            codec_for(pt, encoding).map(|codec| (pt, codec))
+        })?;
+        Some(BackchannelMedia {
+            control: resolve_control(base, control.unwrap_or("*")),
+            payload_type,
+            codec,
+        })
+    })
+}
+
+/// Codec of payload type `pt` given its `rtpmap` encoding, if supported.
+/// Static types 0 and 8 need no `rtpmap`.
+fn codec_for(pt: u8, encoding: Option<&str>) -> Option<TalkbackCodec> {
+    let Some(encoding) = encoding else {
+        return match pt {
+            0 => Some(TalkbackCodec::Pcmu),
+            8 => Some(TalkbackCodec::Pcma),
+            _ => None,
+        };
+    };
+    let mut parts = encoding.split('/');
+    let name = parts.next()?.to_ascii_uppercase();
+    let rate = parts.next().and_then(|r| r.parse::<u32>().ok());
+    let channels = parts
+        .next()
+        .and_then(|c| c.parse::<u32>().ok())
+        .unwrap_or(1);
+    match name.as_str() {
+        "PCMU" if rate == Some(8000) => Some(TalkbackCodec::Pcmu),
+        "PCMA" if rate == Some(8000) => Some(TalkbackCodec::Pcma),
+        "MPEG4-GENERIC" if channels == 1 => Some(TalkbackCodec::Aac { sample_rate: rate? }),
+        _ => None,
+    }
+}
+
+fn resolve_control(base: &str, control: &str) -> String {
+    if control == "*" || control.is_empty() {
+        base.to_string()
+    } else if control.to_ascii_lowercase().starts_with("rtsp://") {
+        control.to_string()
+    } else {
+        format!("{}/{}", base.trim_end_matches('/'), control)
+    }
+}
+
+/// `interleaved=N-M` of a SETUP reply's `Transport`: the RTP channel.
+fn interleaved_channel(transport: &str) -> Option<u8> {
+    transport.split(';').find_map(|param| {
+        let range = param.trim().strip_prefix("interleaved=")?;
+        range.split('-').next()?.parse().ok()
+    })
+}
+
+/// Session id and timeout of a `Session: id;timeout=N` header.
+fn parse_session(value: &str) -> (String, u64) {
+    let mut parts = value.split(';');
+    let id = parts.next().unwrap_or_default().trim().to_string();
+    let timeout = parts
+        .find_map(|p| p.trim().strip_prefix("timeout=")?.parse().ok())
+        .unwrap_or(DEFAULT_SESSION_TIMEOUT_SECS);
+    (id, timeout)
+}
+
+enum Auth {
+    Digest(DigestChallenge),
+    Basic,
+}
+
+/// Builds requests: CSeq, session and the camera's chosen authentication.
+struct Signer {
+    credentials: Option<(String, String)>,
+    auth: Option<Auth>,
+    cseq: u32,
+    nc: u32,
+    cnonce: String,
+    session: Option<String>,
+}
+
+impl Signer {
+    fn new(credentials: Option<(String, String)>) -> Self {
+        Self {
+            credentials,
+            auth: None,
+            cseq: 0,
+            nc: 0,
+            cnonce: format!("{:016x}", rand::rng().random::<u64>()),
+            session: None,
+        }
+    }
+
+    fn request(&mut self, method: &str, uri: &str, extra: &[(&str, &str)]) -> Vec<u8> {
+        self.cseq += 1;
+        let mut out = format!(
+            "{method} {uri} RTSP/1.0\r\nCSeq: {}\r\nUser-Agent: zm-api\r\nRequire: {REQUIRE_BACKCHANNEL}\r\n",
+            self.cseq
+        );
+        if let Some(session) = &self.session {
+            out.push_str(&format!("Session: {session}\r\n"));
+        }
+        if let (Some(auth), Some((user, pass))) = (&self.auth, &self.credentials) {
+            let value = match auth {
+                Auth::Digest(challenge) => {
+                    self.nc += 1;
+                    challenge.authorization(user, pass, method, uri, self.nc, &self.cnonce)
+                }
+                Auth::Basic => format!(
+                    "Basic {}",
+                    base64::engine::general_purpose::STANDARD.encode(format!("{user}:{pass}"))
+                ),
+            };
+            out.push_str(&format!("Authorization: {value}\r\n"));
+        }
+        for (name, value) in extra {
+            out.push_str(&format!("{name}: {value}\r\n"));
+        }
+        out.push_str("\r\n");
+        out.into_bytes()
+    }
+
+    /// Adopt a 401's challenge. `false` when there is nothing new to try.
+    fn challenged(&mut self, reply: &Reply) -> bool {
+        if self.credentials.is_none() {
+            return false;
+        }
+        let digest = reply
+            .headers("WWW-Authenticate")
+            .find_map(DigestChallenge::parse);
+        let next = match digest {
+            Some(challenge) => Auth::Digest(challenge),
+            None if reply
+                .headers("WWW-Authenticate")
+                .any(|h| h.trim().to_ascii_lowercase().starts_with("basic")) =>
+            {
+                Auth::Basic
+            }
+            None => return false,
+        };
+        // Basic that already failed will fail again.
+        if matches!((&self.auth, &next), (Some(Auth::Basic), Auth::Basic)) {
+            return false;
+        }
+        self.nc = 0;
+        self.auth = Some(next);
+        true
+    }
+}
+
+/// The RTSP connection during setup, while replies are still read.
+struct Handshake {
+    stream: TcpStream,
+    buf: Vec<u8>,
+    signer: Signer,
+}
+
+impl Handshake {
+    /// Send a request, answering one authentication challenge.
+    async fn request(
+        &mut self,
+        method: &'static str,
+        uri: &str,
+        extra: &[(&str, &str)],
+    ) -> Result<Reply, TalkbackError> {
+        let mut retried = false;
+        loop {
+            let bytes = self.signer.request(method, uri, extra);
+            self.stream.write_all(&bytes).await?;
+            let reply = self.read_reply().await?;
+            match reply.status {
+                200..=299 => return Ok(reply),
+                401 if !retried && self.signer.challenged(&reply) => retried = true,
+                status => return Err(TalkbackError::Rtsp { method, status }),
+            }
+        }
+    }
+
+    async fn read_reply(&mut self) -> Result<Reply, TalkbackError> {
+        loop {
+            if self.buf.first() == Some(&b'$') {
+                // Interleaved data ahead of the reply; not ours to read.
+                if let Ok(Some((Frame::Interleaved { .. }, used))) = message::parse(&self.buf) {
+                    self.buf.drain(..used);
+                    continue;
+                }
+            } else if let Some((reply, used)) =
+                message::parse_reply(&self.buf).map_err(|_| TalkbackError::Malformed)?
+            {
+                self.buf.drain(..used);
+                return Ok(reply);
+            }
+            if self.stream.read_buf(&mut self.buf).await? == 0 {
+                return Err(TalkbackError::Io(std::io::ErrorKind::UnexpectedEof.into()));
+            }
+        }
+    }
+}
+
+/// A playing backchannel: RTP goes out interleaved on the RTSP connection.
+pub struct OnvifBackchannel {
+    writer: OwnedWriteHalf,
+    signer: Signer,
+    /// Aggregate URL, for keep-alives and TEARDOWN.
+    url: String,
+    channel: u8,
+    codec: TalkbackCodec,
+    packetizer: AudioPacketizer,
+    /// Media time sent so far; the RTP clock.
+    elapsed_us: i64,
+    keepalive_every: Duration,
+    last_keepalive: Instant,
+    drain: JoinHandle<()>,
+}
+
+impl OnvifBackchannel {
+    /// DESCRIBE, SETUP and PLAY the camera's backchannel, within `timeout`.
+    pub async fn connect(
+        target: &BackchannelTarget,
+        timeout: Duration,
+    ) -> Result<Self, TalkbackError> {
+        tokio::time::timeout(timeout, Self::connect_inner(target))
+            .await
+            .map_err(|_| TalkbackError::Timeout)?
+    }
+
+    async fn connect_inner(target: &BackchannelTarget) -> Result<Self, TalkbackError> {
+        let url = url::Url::parse(&target.url).map_err(|_| TalkbackError::NoRtspUrl)?;
+        let host = url.host_str().ok_or(TalkbackError::NoRtspUrl)?;
+        let port = url.port().unwrap_or(DEFAULT_RTSP_PORT);
+        let stream = TcpStream::connect((host, port)).await?;
+        let _ = stream.set_nodelay(true);
+        let mut conn = Handshake {
+            stream,
+            buf: Vec::new(),
+            signer: Signer::new(target.credentials.clone()),
+        };
+
+        let described = conn
+            .request("DESCRIBE", &target.url, &[("Accept", "application/sdp")])
+            .await?;
+        let base = described
+            .header("Content-Base")
+            .or_else(|| described.header("Content-Location"))
+            .unwrap_or(&target.url)
+            .to_string();
+        let sdp = String::from_utf8_lossy(&described.body);
+        let media = find_backchannel(&sdp, &base).ok_or(TalkbackError::NoBackchannel)?;
+        debug!(
+            "Backchannel at {} (PT {}, {})",
+            media.control,
+            media.payload_type,
+            media.codec.as_str()
+        );
+
+        let set_up = conn
+            .request(
+                "SETUP",
+                &media.control,
+                &[("Transport", "RTP/AVP/TCP;unicast;interleaved=0-1")],
+            )
+            .await?;
+        let channel = set_up
+            .header("Transport")
+            .and_then(interleaved_channel)
+            .unwrap_or(0);
+        let (session, timeout_secs) = set_up
+            .header("Session")
+            .map(parse_session)
+            .ok_or(TalkbackError::Malformed)?;
+        conn.signer.session = Some(session);
+
+        conn.request("PLAY", &base, &[("Range", "npt=0.000-")])
+            .await?;
+        info!("ONVIF backchannel playing to {}", target.url);
+
+        let Handshake { stream, signer, .. } = conn;
+        let (mut reader, writer) = stream.into_split();
+        let drain = tokio::spawn(async move {
+            let mut sink = [0u8; 2048];
+            while matches!(reader.read(&mut sink).await, Ok(n) if n > 0) {}
+        });
+
+        Ok(Self {
+            writer,
+            signer,
+            url: base,
+            channel,
+            codec: media.codec,
+            packetizer: AudioPacketizer::new(
+                media.payload_type,
+                media.codec.sample_rate(),
+                matches!(media.codec, TalkbackCodec::Aac { .. }),
+            ),
+            elapsed_us: 0,
+            keepalive_every: Duration::from_secs((timeout_secs / 2).max(5)),
+            last_keepalive: Instant::now(),
+            drain,
+        })
+    }
+
+    /// The codec the camera asked for.
+    pub fn codec(&self) -> TalkbackCodec {
+        self.codec
+    }
+
+    /// Send one frame, and a keep-alive when one is due.
+    pub async fn send(&mut self, frame: &BackchannelFrame) -> Result<(), TalkbackError> {
+        if self.drain.is_finished() {
+            return Err(TalkbackError::Io(
+                std::io::ErrorKind::ConnectionReset.into(),
+            ));
+        }
+        if self.last_keepalive.elapsed() >= self.keepalive_every {
+            let keepalive = self.signer.request("GET_PARAMETER", &self.url, &[]);
+            self.writer.write_all(&keepalive).await?;
+            self.last_keepalive = Instant::now();
+        }
+        if let Some(rtp) = self.packetizer.packet(&frame.data, self.elapsed_us) {
+            self.writer
+                .write_all(&message::interleaved(self.channel, &rtp))
+                .await?;
+        }
+        self.elapsed_us += frame.duration.as_micros() as i64;
+        Ok(())
+    }
+
+    /// TEARDOWN, freeing the camera's backchannel for the next speaker.
+    pub async fn close(mut self) {
+        let teardown = self.signer.request("TEARDOWN", &self.url, &[]);
+        let _ = self.writer.write_all(&teardown).await;
+        let _ = self.writer.shutdown().await;
+        self.drain.abort();
+    }
+}
+
+impl Drop for OnvifBackchannel {
+    fn drop(&mut self) {
+        self.drain.abort();
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use tokio::net::TcpListener;
+
+    const SDP: &str = "v=0\r\no=- 1 1 IN IP4 10.0.0.5\r\ns=Session\r\nt=0 0\r\n\
+        m=video 0 RTP/AVP 96\r\na=rtpmap:96 H264/90000\r\na=control:trackID=1\r\na=recvonly\r\n\
+        m=audio 0 RTP/AVP 0\r\na=control:trackID=2\r\na=recvonly\r\n\
+        m=audio 0 RTP/AVP 97 8\r\na=rtpmap:97 L16/8000\r\na=rtpmap:8 PCMA/8000\r\n\
+        a=control:trackID=3\r\na=sendonly\r\n";
+
+    #[test]
+    fn finds_the_sendonly_audio_section() {
+        let media = find_backchannel(SDP, "rtsp://10.0.0.5/stream1/").unwrap();
+        assert_eq!(media.control, "rtsp://10.0.0.5/stream1/trackID=3");
+        // L16 is skipped; PCMA is the first codec we can encode.
+        assert_eq!(media.payload_type, 8);
+        assert_eq!(media.codec, TalkbackCodec::Pcma);
+    }
+
+    #[test]
+    fn no_backchannel_without_sendonly_audio() {
+        let plain = SDP.replace("a=sendonly", "a=recvonly");
+        assert_eq!(find_backchannel(&plain, "rtsp://cam/"), None);
+    }
+
+    #[test]
+    fn aac_backchannel_takes_its_rate_from_rtpmap() {
+        let sdp = "v=0\r\nm=audio 0 RTP/AVP 96\r\na=rtpmap:96 mpeg4-generic/16000/1\r\n\
+            a=control:rtsp://cam/back\r\na=sendonly\r\n";
+        let media = find_backchannel(sdp, "rtsp://cam/live").unwrap();
+        assert_eq!(media.control, "rtsp://cam/back");
+        assert_eq!(media.codec, TalkbackCodec::Aac { sample_rate: 16000 });
+        // Stereo AAC is not what the encoder produces.
+        let stereo = sdp.replace("16000/1", "16000/2");
+        assert_eq!(find_backchannel(&stereo, "rtsp://cam/"), None);
+    }
+
+    #[test]
+    fn transport_and_session_headers() {
+        assert_eq!(
+            interleaved_channel("RTP/AVP/TCP;unicast;interleaved=4-5"),
+            Some(4)
+        );
+        assert_eq!(interleaved_channel("RTP/AVP;unicast"), None);
+        assert_eq!(parse_session("A1B2;timeout=30"), ("A1B2".to_string(), 30));
+        assert_eq!(parse_session("A1B2"), ("A1B2".to_string(), 60));
+    }
+
+    #[test]
+    fn target_prefers_monitor_credentials_over_the_url() {
+        let target = BackchannelTarget::from_parts("rtsp://u:p@cam:8554/live", None, None).unwrap();
+        assert_eq!(target.url, "rtsp://cam:8554/live");
+        assert_eq!(target.credentials, Some(("u".to_string(), "p".to_string())));
+        let target = BackchannelTarget::from_parts(
+            "rtsp://u:p@cam:8554/live",
+            Some("admin"),
+            Some("secret"),
+        )
+        .unwrap();
+        assert_eq!(
+            target.credentials,
+            Some(("admin".to_string(), "secret".to_string()))
+        );
+        assert!(BackchannelTarget::from_parts("/dev/video0", Some("admin"), None).is_none());
+    }
+
+    /// Read one request off `stream`; returns its text.
+    async fn read_request(stream: &mut TcpStream) -> String {
+        let mut buf = Vec::new();
+        while !buf.ends_with(b"\r\n\r\n") {
+            let mut byte = [0u8; 1];
+            stream.read_exact(&mut byte).await.unwrap();
+            buf.push(byte[0]);
+        }
+        String::from_utf8(buf).unwrap()
+    }
+
+    /// A scripted camera: challenges DESCRIBE once, then walks through the
+    /// backchannel setup and checks the first interleaved RTP packet.
+    #[tokio::test]
+    async fn sets_up_a_digest_protected_backchannel_and_sends_rtp() {
+        let listener = TcpListener::bind("127.0.0.1:0").await.unwrap();
+        let port = listener.local_addr().unwrap().port();
+        let camera = tokio::spawn(async move {
+            let (mut s, _) = listener.accept().await.unwrap();
+            let req = read_request(&mut s).await;
+            assert!(req.starts_with("DESCRIBE "));
+            assert!(req.contains("Require: www.onvif.org/ver20/backchannel"));
+            s.write_all(b"RTSP/1.0 401 Unauthorized\r\nCSeq: 1\r\nWWW-Authenticate: Digest realm=\"cam\", nonce=\"abc\"\r\n\r\n")
+                .await
+                .unwrap();
+
+            let req = read_request(&mut s).await;
+            assert!(req.contains("Authorization: Digest username=\"admin\""));
+            let body = SDP.as_bytes();
+            s.write_all(
+                format!(
+                    "RTSP/1.0 200 OK\r\nCSeq: 2\r\nContent-Base: rtsp://127.0.0.1:{port}/live/\r\nContent-Length: {}\r\n\r\n",
+                    body.len()
+                )
+                .as_bytes(),
+            )
+            .await
+            .unwrap();
+            s.write_all(body).await.unwrap();
+
+            let req = read_request(&mut s).await;
+            assert!(req.starts_with(&format!("SETUP rtsp://127.0.0.1:{port}/live/trackID=3 ")));
+            s.write_all(b"RTSP/1.0 200 OK\r\nCSeq: 3\r\nSession: S1;timeout=20\r\nTransport: RTP/AVP/TCP;unicast;interleaved=2-3\r\n\r\n")
+                .await
+                .unwrap();
+
+            let req = read_request(&mut s).await;
+            assert!(req.starts_with("PLAY "));
+            assert!(req.contains("Session: S1\r\n"));
+            s.write_all(b"RTSP/1.0 200 OK\r\nCSeq: 4\r\n\r\n")
+                .await
+                .unwrap();
+
+            let mut head = [0u8; 4];
+            s.read_exact(&mut head).await.unwrap();
+            assert_eq!(head[0], b'$');
+            assert_eq!(head[1], 2);
+            let mut rtp = vec![0u8; u16::from_be_bytes([head[2], head[3]]) as usize];
+            s.read_exact(&mut rtp).await.unwrap();
+            rtp
+        });
+
+        let target = BackchannelTarget {
+            url: format!("rtsp://127.0.0.1:{port}/live"),
+            credentials: Some(("admin".to_string(), "pw".to_string())),
+        };
+        let mut back = OnvifBackchannel::connect(&target, Duration::from_secs(5))
+            .await
+            .expect("backchannel");
+        assert_eq!(back.codec(), TalkbackCodec::Pcma);
+        let frame = BackchannelFrame {
+            data: vec![0xD5; 160],
+            duration: Duration::from_millis(20),
+        };
+        back.send(&frame).await.unwrap();
+
+        let rtp = camera.await.unwrap();
+        assert_eq!(rtp[1] & 0x7F, 8); // PCMA
+        assert_eq!(&rtp[12..], &frame.data[..]);
+    }
+}
@@ -0,0 +1,377 @@
+//! Two-way audio: the viewer's microphone to the camera's speaker.
+//!
+//! A WebRTC viewer that asks for talkback gets a receive-only audio
+//! transceiver; the Opus it sends arrives here as RTP payloads. [`run`]
+//! transcodes it to what the camera plays and hands it to one of two sinks:
+//!
+//! - **ONVIF backchannel** ([`backchannel`]): an RTSP session to the camera's
+//!   own URL, carrying RTP interleaved. Codec and payload type come from the
+//!   camera's SDP.
+//! - **Stream socket**: `Talkback` messages (`0x13`) up the monitor's
+//!   stream-socket connection, for a worker that owns the camera. The codec
+//!   follows the monitor's audio stream, else `streaming.talkback.socket_codec`.
+//!
+//! The sink opens on the first frame and closes after [`IDLE_TIMEOUT`] of
+//! silence from the browser, so an idle viewer does not hold the camera's
+//! backchannel — many cameras allow only one.
+
+pub mod backchannel;
+pub mod transcode;
+
+use std::sync::Arc;
+use std::time::Duration;
+
+use bytes::Bytes;
+use ffmpeg_next::ffi::AVCodecID;
+use tokio::sync::mpsc;
+use tracing::{debug, info, warn};
+
+use self::backchannel::{BackchannelTarget, OnvifBackchannel};
+use self::transcode::{BackchannelFrame, OpusToBackchannel};
+use crate::configure::streaming::TalkbackConfig;
+use crate::entity::monitors;
+use crate::streaming::source::router::{ControlReply, MonitorSource};
+use crate::streaming::source::AudioCodec;
+
+/// Close the sink after this long without microphone audio.
+pub const IDLE_TIMEOUT: Duration = Duration::from_secs(30);
+/// Wait before retrying a sink that failed to open.
+const RETRY_DELAY: Duration = Duration::from_secs(5);
+
+/// What the camera plays.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum TalkbackCodec {
+    Pcmu,
+    Pcma,
+    Aac { sample_rate: u32 },
+}
+
+impl TalkbackCodec {
+    /// Parse `pcmu` / `pcma` / `aac`, the config spelling.
+    pub fn from_name(name: &str, aac_sample_rate: u32) -> Option<Self> {
+        match name.to_ascii_lowercase().as_str() {
+            "pcmu" => Some(Self::Pcmu),
+            "pcma" => Some(Self::Pcma),
+            "aac" => Some(Self::Aac {
+                sample_rate: aac_sample_rate,
+            }),
+            _ => None,
+        }
+    }
+
+    pub fn sample_rate(&self) -> u32 {
+        match self {
+            Self::Pcmu | Self::Pcma => 8000,
+            Self::Aac { sample_rate } => *sample_rate,
+        }
+    }
+
+    pub fn as_str(&self) -> &'static str {
+        match self {
+            Self::Pcmu => "pcmu",
+            Self::Pcma => "pcma",
+            Self::Aac { .. } => "aac",
+        }
+    }
+
+    /// ffmpeg `AVCodecID`, as the stream-socket protocol identifies codecs.
+    pub fn codec_id(&self) -> u32 {
+        match self {
+            Self::Pcmu => AVCodecID::AV_CODEC_ID_PCM_MULAW as u32,
+            Self::Pcma => AVCodecID::AV_CODEC_ID_PCM_ALAW as u32,
+            Self::Aac { .. } => AVCodecID::AV_CODEC_ID_AAC as u32,
+        }
+    }
+}
+
+#[derive(Debug, thiserror::Error)]
+pub enum TalkbackError {
+    #[error("monitor has no RTSP URL to open a backchannel on")]
+    NoRtspUrl,
+
+    #[error("camera offers no backchannel in a supported codec")]
+    NoBackchannel,
+
+    #[error("stream socket is not connected")]
+    SocketNotConnected,
+
+    #[error("RTSP {method} failed with status {status}")]
+    Rtsp { method: &'static str, status: u16 },
+
+    #[error("malformed RTSP reply")]
+    Malformed,
+
+    #[error("timed out setting up the backchannel")]
+    Timeout,
+
+    #[error("transcoder: {0}")]
+    Transcoder(String),
+
+    #[error(transparent)]
+    Io(#[from] std::io::Error),
+}
+
+/// How talkback reaches the camera.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Transport {
+    Auto,
+    Onvif,
+    Socket,
+}
+
+impl Transport {
+    fn from_config(value: &str) -> Self {
+        match value.to_ascii_lowercase().as_str() {
+            "onvif" => Self::Onvif,
+            "socket" => Self::Socket,
+            _ => Self::Auto,
+        }
+    }
+}
+
+/// Everything [`run`] needs for one viewer's talkback.
+#[derive(Clone)]
+pub struct TalkbackPlan {
+    pub monitor_id: u32,
+    transport: Transport,
+    onvif: Option<BackchannelTarget>,
+    socket_codec: TalkbackCodec,
+    aac_sample_rate: u32,
+    source: Arc<MonitorSource>,
+    connect_timeout: Duration,
+}
+
+impl TalkbackPlan {
+    /// Work out where talkback for `monitor` goes. Fails only when no
+    /// transport can apply — `onvif` configured for a non-RTSP monitor.
+    pub fn resolve(
+        config: &TalkbackConfig,
+        monitor: &monitors::Model,
+        source: Arc<MonitorSource>,
+    ) -> Result<Self, TalkbackError> {
+        let transport = Transport::from_config(&config.transport);
+        let onvif = match transport {
+            Transport::Socket => None,
+            _ => BackchannelTarget::from_monitor(monitor),
+        };
+        if transport == Transport::Onvif && onvif.is_none() {
+            return Err(TalkbackError::NoRtspUrl);
+        }
+        let socket_codec = TalkbackCodec::from_name(&config.socket_codec, config.aac_sample_rate)
+            .unwrap_or(TalkbackCodec::Pcmu);
+        Ok(Self {
+            monitor_id: monitor.id,
+            transport,
+            onvif,
+            socket_codec,
+            aac_sample_rate: config.aac_sample_rate,
+            source,
+            connect_timeout: Duration::from_secs(config.connect_timeout_seconds.max(1)),
+        })
+    }
+
+    /// The stream-socket codec: the monitor's own audio codec when it is one
+    /// the camera evidently speaks, else the configured one.
+    fn socket_codec(&self) -> TalkbackCodec {
+        match self.source.audio_codec() {
+            Some(AudioCodec::G711Ulaw) => TalkbackCodec::Pcmu,
+            Some(AudioCodec::G711Alaw) => TalkbackCodec::Pcma,
+            Some(AudioCodec::Aac) => TalkbackCodec::Aac {
+                sample_rate: self.aac_sample_rate,
+            },
+            _ => self.socket_codec,
+        }
+    }
+
+    async fn open(&self) -> Result<Sink, TalkbackError> {
+        if let Some(target) = &self.onvif {
+            match OnvifBackchannel::connect(target, self.connect_timeout).await {
+                Ok(back) => return Ok(Sink::Onvif(back)),
+                Err(e) if self.transport == Transport::Onvif => return Err(e),
+                Err(e) => debug!(
+                    "Monitor {}: no ONVIF backchannel ({e}), using the stream socket",
+                    self.monitor_id
+                ),
+            }
+        }
+        let reply = self
+            .source
+            .control()
+            .ok_or(TalkbackError::SocketNotConnected)?;
+        Ok(Sink::Socket {
+            reply,
+            codec: self.socket_codec(),
+            elapsed_us: 0,
+        })
+    }
+}
+
+enum Sink {
+    Onvif(OnvifBackchannel),
+    Socket {
+        reply: ControlReply,
+        codec: TalkbackCodec,
+        /// Media time sent so far; the frames' pts.
+        elapsed_us: i64,
+    },
+}
+
+impl Sink {
+    fn codec(&self) -> TalkbackCodec {
+        match self {
+            Sink::Onvif(back) => back.codec(),
+            Sink::Socket { codec, .. } => *codec,
+        }
+    }
+
+    fn name(&self) -> &'static str {
+        match self {
+            Sink::Onvif(_) => "ONVIF backchannel",
+            Sink::Socket { .. } => "stream socket",
+        }
+    }
+
+    async fn send(&mut self, frame: &BackchannelFrame) -> Result<(), TalkbackError> {
+        match self {
+            Sink::Onvif(back) => back.send(frame).await,
+            Sink::Socket {
+                reply,
+                codec,
+                elapsed_us,
+            } => {
+                if reply.is_closed() {
+                    return Err(TalkbackError::SocketNotConnected);
+                }
+                // A full queue drops the frame: a gap, not growing latency.
+                reply.send_talkback(
+                    *elapsed_us,
+                    codec.codec_id(),
+                    codec.sample_rate(),
+                    &frame.data,
+                );
+                *elapsed_us += frame.duration.as_micros() as i64;
+                Ok(())
+            }
+        }
+    }
+
+    async fn close(self) {
+        if let Sink::Onvif(back) = self {
+            back.close().await;
+        }
+    }
+}
+
+/// Play `mic_rx` — Opus payloads from the viewer — out of the camera until the
+/// viewer goes away.
+pub async fn run(plan: TalkbackPlan, mut mic_rx: mpsc::Receiver<Bytes>) {
+    let monitor_id = plan.monitor_id;
+    let mut active: Option<(Sink, OpusToBackchannel)> = None;
+    let mut retry_at: Option<tokio::time::Instant> = None;
+
+    loop {
+        let opus = match tokio::time::timeout(IDLE_TIMEOUT, mic_rx.recv()).await {
+            Ok(Some(opus)) => opus,
+            Ok(None) => break,
+            Err(_) => {
+                if let Some((sink, _)) = active.take() {
+                    debug!(
+                        "Monitor {monitor_id}: talkback idle, closing the {}",
+                        sink.name()
+                    );
+                    sink.close().await;
+                }
+                continue;
+            }
+        };
+
+        if active.is_none() {
+            if retry_at.is_some_and(|at| tokio::time::Instant::now() < at) {
+                continue;
+            }
+            match open_sink(&plan).await {
+                Ok(opened) => {
+                    info!(
+                        "Monitor {monitor_id}: talkback via {} ({})",
+                        opened.0.name(),
+                        opened.0.codec().as_str()
+                    );
+                    retry_at = None;
+                    active = Some(opened);
+                }
+                Err(e) => {
+                    warn!("Monitor {monitor_id}: talkback unavailable: {e}");
+                    retry_at = Some(tokio::time::Instant::now() + RETRY_DELAY);
+                    continue;
+                }
+            }
+        }
+
+        let Some((sink, transcoder)) = active.as_mut() else {
+            continue;
+        };
+        let frames = match transcoder.transcode(&opus) {
+            Ok(frames) => frames,
+            Err(e) => {
+                debug!("Monitor {monitor_id}: talkback transcode: {e}");
+                continue;
+            }
+        };
+        for frame in &frames {
+            if let Err(e) = sink.send(frame).await {
+                warn!("Monitor {monitor_id}: talkback {} failed: {e}", sink.name());
+                if let Some((sink, _)) = active.take() {
+                    sink.close().await;
+                }
+                retry_at = Some(tokio::time::Instant::now() + RETRY_DELAY);
+                break;
+            }
+        }
+    }
+
+    if let Some((sink, _)) = active {
+        sink.close().await;
+    }
+    debug!("Monitor {monitor_id}: talkback ended");
+}
+
+async fn open_sink(plan: &TalkbackPlan) -> Result<(Sink, OpusToBackchannel), TalkbackError> {
+    let sink = plan.open().await?;
+    match OpusToBackchannel::new(sink.codec()) {
+        Ok(transcoder) => Ok((sink, transcoder)),
+        Err(e) => {
+            sink.close().await;
+            Err(TalkbackError::Transcoder(e))
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn codec_names_and_ids() {
+        assert_eq!(
+            TalkbackCodec::from_name("PCMA", 16000),
+            Some(TalkbackCodec::Pcma)
+        );
+        assert_eq!(
+            TalkbackCodec::from_name("aac", 16000),
+            Some(TalkbackCodec::Aac { sample_rate: 16000 })
+        );
+        assert_eq!(TalkbackCodec::from_name("opus", 16000), None);
+        assert_eq!(TalkbackCodec::Pcmu.sample_rate(), 8000);
+        assert_eq!(
+            TalkbackCodec::Pcma.codec_id(),
+            AVCodecID::AV_CODEC_ID_PCM_ALAW as u32
+        );
+    }
+
+    #[test]
+    fn unknown_transport_falls_back_to_auto() {
+        assert_eq!(Transport::from_config("ONVIF"), Transport::Onvif);
+        assert_eq!(Transport::from_config("socket"), Transport::Socket);
+        assert_eq!(Transport::from_config("rtp"), Transport::Auto);
+    }
+}
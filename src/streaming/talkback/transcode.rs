@@ -0,0 +1,321 @@
+//! Opus → backchannel transcoding for talkback.
+//!
+//! The reverse of `streaming::live::audio`: browsers send Opus, cameras play
+//! G.711 or AAC. Built the same way on ffmpeg-next — decode Opus → resample
+//! to the camera's rate, mono → encode `pcm_mulaw` / `pcm_alaw` in 20 ms
+//! frames, or `aac` in its native 1024-sample frames (ADTS-framed, as the rest
+//! of the codebase carries AAC).
+
+use std::time::Duration;
+
+use ffmpeg_next as ffmpeg;
+
+use super::TalkbackCodec;
+use crate::streaming::source::media::AdtsWrapper;
+
+/// G.711 frame length: 20 ms at 8 kHz.
+const G711_FRAME_SAMPLES: usize = 160;
+
+/// One encoded frame for the camera.
+#[derive(Debug, Clone)]
+pub struct BackchannelFrame {
+    pub data: Vec<u8>,
+    pub duration: Duration,
+}
+
+/// Streaming Opus → G.711 / AAC transcoder.
+///
+/// The decoder and encoder are created eagerly, so a build without them fails
+/// when the session opens rather than on the first word; the resampler is
+/// created from the first decoded frame's format.
+pub struct OpusToBackchannel {
+    codec: TalkbackCodec,
+    decoder: ffmpeg::decoder::Audio,
+    resampler: Option<ffmpeg::software::resampling::Context>,
+    encoder: ffmpeg::encoder::Audio,
+    /// Encoder input format: s16 for G.711, planar float for AAC.
+    format: ffmpeg::format::Sample,
+    /// Mono samples at the output rate awaiting a full encoder frame.
+    sample_buf: Vec<u8>,
+    /// Encoder frame size in samples.
+    frame_size: usize,
+    /// Running output pts in samples.
+    next_pts: i64,
+    adts: Option<AdtsWrapper>,
+}
+
+impl OpusToBackchannel {
+    /// Create the transcoder. Fails when this ffmpeg build lacks the Opus
+    /// decoder or the target encoder.
+    pub fn new(codec: TalkbackCodec) -> Result<Self, String> {
+        ffmpeg::init().map_err(|e| format!("ffmpeg init: {e}"))?;
+
+        let opus = ffmpeg::decoder::find(ffmpeg::codec::Id::OPUS)
+            .ok_or_else(|| "Opus decoder not available".to_string())?;
+        let decoder = ffmpeg::codec::context::Context::new_with_codec(opus)
+            .decoder()
+            .audio()
+            .map_err(|e| format!("Opus decoder context: {e}"))?;
+
+        let (encoder_name, format) = match codec {
+            TalkbackCodec::Pcmu => ("pcm_mulaw", sample_s16()),
+            TalkbackCodec::Pcma => ("pcm_alaw", sample_s16()),
+            TalkbackCodec::Aac { .. } => (
+                "aac",
+                ffmpeg::format::Sample::F32(ffmpeg::format::sample::Type::Planar),
+            ),
+        };
+        let found = ffmpeg::encoder::find_by_name(encoder_name)
+            .ok_or_else(|| format!("{encoder_name} encoder not available in this ffmpeg build"))?;
+        let mut enc = ffmpeg::codec::context::Context::new_with_codec(found)
+            .encoder()
+            .audio()
+            .map_err(|e| format!("{encoder_name} encoder context: {e}"))?;
+        let rate = codec.sample_rate();
+        enc.set_rate(rate as i32);
+        enc.set_format(format);
+        enc.set_channel_layout(ffmpeg::channel_layout::ChannelLayout::MONO);
+        enc.set_time_base(ffmpeg::Rational(1, rate as i32));
+        if matches!(codec, TalkbackCodec::Aac { .. }) {
+            enc.set_bit_rate(32_000);
+        }
+        let encoder = enc
+            .open()
+            .map_err(|e| format!("{encoder_name} encoder open: {e}"))?;
+
+        // PCM encoders take any frame length; AAC fixes its own (1024).
+        let frame_size = match encoder.frame_size() as usize {
+            0 => G711_FRAME_SAMPLES,
+            n => n,
+        };
+        let adts = match codec {
+            TalkbackCodec::Aac { sample_rate } => Some(
+                AdtsWrapper::aac_lc(sample_rate, 1)
+                    .ok_or_else(|| format!("{sample_rate} Hz is not an AAC sample rate"))?,
+            ),
+            _ => None,
+        };
+
+        Ok(Self {
+            codec,
+            decoder,
+            resampler: None,
+            encoder,
+            format,
+            sample_buf: Vec::new(),
+            frame_size,
+            next_pts: 0,
+            adts,
+        })
+    }
+
+    pub fn codec(&self) -> TalkbackCodec {
+        self.codec
+    }
+
+    /// Transcode one Opus packet; returns zero or more backchannel frames.
+    pub fn transcode(&mut self, opus: &[u8]) -> Result<Vec<BackchannelFrame>, String> {
+        let packet = ffmpeg::Packet::copy(opus);
+        self.decoder
+            .send_packet(&packet)
+            .map_err(|e| format!("Opus decode: {e}"))?;
+
+        let mut decoded = ffmpeg::frame::Audio::empty();
+        while self.decoder.receive_frame(&mut decoded).is_ok() {
+            self.resample_into_buf(&decoded)?;
+        }
+
+        self.drain_encoder()
+    }
+
+    /// Downmix and resample a decoded frame, appending the samples.
+    fn resample_into_buf(&mut self, decoded: &ffmpeg::frame::Audio) -> Result<(), String> {
+        let rate = self.codec.sample_rate();
+        if self.resampler.is_none() {
+            let resampler = ffmpeg::software::resampler(
+                (decoded.format(), decoded.channel_layout(), decoded.rate()),
+                (
+                    self.format,
+                    ffmpeg::channel_layout::ChannelLayout::MONO,
+                    rate,
+                ),
+            )
+            .map_err(|e| format!("resampler: {e}"))?;
+            self.resampler = Some(resampler);
+        }
+        let resampler = self.resampler.as_mut().expect("resampler initialized");
+
+        // Sized for the converted frame plus whatever the resampler holds —
+        // see `AacToOpusTranscoder::resample_into_buf`.
+        let in_rate = decoded.rate().max(1) as usize;
+        let buffered = resampler
+            .delay()
+            .map(|d| d.output.max(0) as usize)
+            .unwrap_or(0);
+        let est = decoded.samples() * rate as usize / in_rate + buffered + 64;
+        let mut resampled = ffmpeg::frame::Audio::new(
+            self.format,
+            est,
+            ffmpeg::channel_layout::ChannelLayout::MONO,
+        );
+        resampler
+            .run(decoded, &mut resampled)
+            .map_err(|e| format!("resample: {e}"))?;
+
+        // Mono: packed and planar share one layout, all in plane 0.
+        let len = resampled.samples() * self.format.bytes();
+        self.sample_buf.extend_from_slice(&resampled.data(0)[..len]);
+        Ok(())
+    }
+
+    /// Encode every complete frame in the buffer.
+    fn drain_encoder(&mut self) -> Result<Vec<BackchannelFrame>, String> {
+        let rate = self.codec.sample_rate();
+        let chunk_len = self.frame_size * self.format.bytes();
+        let mut out = Vec::new();
+
+        while self.sample_buf.len() >= chunk_len {
+            let mut frame = ffmpeg::frame::Audio::new(
+                self.format,
+                self.frame_size,
+                ffmpeg::channel_layout::ChannelLayout::MONO,
+            );
+            frame.set_rate(rate);
+            frame.set_pts(Some(self.next_pts));
+            self.next_pts += self.frame_size as i64;
+            frame.data_mut(0)[..chunk_len].copy_from_slice(&self.sample_buf[..chunk_len]);
+            self.sample_buf.drain(..chunk_len);
+
+            self.encoder
+                .send_frame(&frame)
+                .map_err(|e| format!("{} encode: {e}", self.codec.as_str()))?;
+
+            let mut packet = ffmpeg::Packet::empty();
+            while self.encoder.receive_packet(&mut packet).is_ok() {
+                let Some(data) = packet.data() else {
+                    continue;
+                };
+                let data = match &self.adts {
+                    Some(adts) => match adts.wrap(data) {
+                        Some(framed) => framed,
+                        None => continue,
+                    },
+                    None => data.to_vec(),
+                };
+                // G.711 is one byte per sample; AAC frames are `frame_size`.
+                let samples = match self.adts {
+                    Some(_) => self.frame_size,
+                    None => data.len(),
+                };
+                out.push(BackchannelFrame {
+                    data,
+                    duration: Duration::from_micros(samples as u64 * 1_000_000 / u64::from(rate)),
+                });
+            }
+        }
+
+        Ok(out)
+    }
+}
+
+fn sample_s16() -> ffmpeg::format::Sample {
+    ffmpeg::format::Sample::I16(ffmpeg::format::sample::Type::Packed)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::streaming::source::AdtsHeader;
+
+    /// Encode `frames` 20 ms Opus packets of a stereo sine wave, as a
+    /// browser would send. Returns None when libopus is missing.
+    fn opus_packets(frames: usize) -> Option<Vec<Vec<u8>>> {
+        ffmpeg::init().ok();
+        let codec = ffmpeg::encoder::find_by_name("libopus")?;
+        let mut enc = ffmpeg::codec::context::Context::new_with_codec(codec)
+            .encoder()
+            .audio()
+            .ok()?;
+        enc.set_rate(48_000);
+        enc.set_format(sample_s16());
+        enc.set_channel_layout(ffmpeg::channel_layout::ChannelLayout::STEREO);
+        enc.set_time_base(ffmpeg::Rational(1, 48_000));
+        let mut enc = enc.open().ok()?;
+
+        let mut out = Vec::new();
+        for n in 0..frames {
+            let mut frame = ffmpeg::frame::Audio::new(
+                sample_s16(),
+                960,
+                ffmpeg::channel_layout::ChannelLayout::STEREO,
+            );
+            frame.set_rate(48_000);
+            frame.set_pts(Some(n as i64 * 960));
+            let data = frame.data_mut(0);
+            for i in 0..960 {
+                let v = ((((n * 960 + i) as f32) * 0.03).sin() * 8000.0) as i16;
+                let b = v.to_le_bytes();
+                data[i * 4..i * 4 + 2].copy_from_slice(&b);
+                data[i * 4 + 2..i * 4 + 4].copy_from_slice(&b);
+            }
+            enc.send_frame(&frame).ok()?;
+            let mut packet = ffmpeg::Packet::empty();
+            while enc.receive_packet(&mut packet).is_ok() {
+                out.push(packet.data()?.to_vec());
+            }
+        }
+        Some(out)
+    }
+
+    #[test]
+    fn transcodes_opus_to_20ms_g711_frames() {
+        let Some(packets) = opus_packets(50) else {
+            eprintln!("libopus not in this ffmpeg build; skipping");
+            return;
+        };
+        let mut t = OpusToBackchannel::new(TalkbackCodec::Pcmu).expect("transcoder");
+        let mut bytes = 0;
+        for p in &packets {
+            for f in t.transcode(p).expect("transcode") {
+                assert_eq!(f.data.len(), G711_FRAME_SAMPLES);
+                assert_eq!(f.duration, Duration::from_millis(20));
+                bytes += f.data.len();
+            }
+        }
+        // 1 s of speech is 8000 G.711 bytes, less the resampler's delay.
+        assert!(bytes >= 7_000, "expected ~8000 bytes of G.711, got {bytes}");
+    }
+
+    #[test]
+    fn transcodes_opus_to_adts_aac() {
+        let Some(packets) = opus_packets(50) else {
+            eprintln!("libopus not in this ffmpeg build; skipping");
+            return;
+        };
+        let mut t = OpusToBackchannel::new(TalkbackCodec::Aac {
+            sample_rate: 16_000,
+        })
+        .expect("transcoder");
+        let mut frames = 0;
+        for p in &packets {
+            for f in t.transcode(p).expect("transcode") {
+                let h = AdtsHeader::parse(&f.data).expect("ADTS-framed");
+                assert_eq!(h.sample_rate, 16_000);
+                assert_eq!(h.channel_configuration, 1);
+                assert_eq!(h.frame_len, f.data.len());
+                assert_eq!(f.duration, Duration::from_millis(64));
+                frames += 1;
+            }
+        }
+        // 1 s at 16 kHz is ~15.6 AAC frames, less the encoder's priming delay.
+        assert!(frames >= 10, "expected ~15 AAC frames, got {frames}");
+    }
+
+    #[test]
+    fn rejects_an_aac_rate_adts_cannot_express() {
+        assert!(OpusToBackchannel::new(TalkbackCodec::Aac {
+            sample_rate: 12_345
+        })
+        .is_err());
+    }
+}
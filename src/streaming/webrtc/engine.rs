@@ -123,6 +123,12 @@ impl WebRtcEngine {
         })
     }
 
+    /// The shared DTLS certificate, for peer connections built elsewhere
+    /// (the WHEP sessions) to skip per-session generation too.
+    pub fn certificate(&self) -> &RTCCertificate {
+        &self.certificate
+    }
+
     /// Parse ICE servers from configuration
     fn parse_ice_servers(config: &WebRtcConfig) -> Result<Vec<RTCIceServer>, EngineError> {
         let mut ice_servers = Vec::new();
@@ -14,7 +14,7 @@
 
 use axum::{
     extract::Request,
-    http::Method,
+    http::{HeaderMap, Method, Uri},
     middleware::{from_fn, Next},
     response::Response,
     Router,
@@ -27,6 +27,7 @@ use crate::entity::sea_orm_active_enums as enums;
 use crate::entity::users::Model as UserModel;
 use crate::error::AppError;
 use crate::server::state::AppState;
+use crate::service::monitor_acl;
 use crate::util::claim::UserClaims;
 use crate::util::middleware::{extract_token_from_header, extract_token_from_query};
 
@@ -198,9 +199,26 @@ async fn enforce(
 ) -> Result<Response, AppError> {
     // Self-contained: decode the bearer token (header or `?token=` for media
     // elements) rather than relying on auth middleware ordering.
-    let token = extract_token_from_header(&request)
-        .or_else(|| extract_token_from_query(&request))
-        .ok_or_else(|| AppError::UnauthorizedError("Authentication required".to_string()))?;
+    let token = extract_token_from_header(&request).or_else(|| extract_token_from_query(&request));
+    let claims = verify(&state, token)?;
+
+    let required = required_level(feature, request.method());
+    let granted = claims.perms.level(feature);
+
+    if granted >= required {
+        Ok(next.run(request).await)
+    } else {
+        Err(AppError::PermissionDeniedError(format!(
+            "{:?} access to {:?} required",
+            required, feature
+        )))
+    }
+}
+
+/// Decode an access token and apply the revocation floor.
+fn verify(state: &AppState, token: Option<String>) -> Result<UserClaims, AppError> {
+    let token =
+        token.ok_or_else(|| AppError::UnauthorizedError("Authentication required".to_string()))?;
 
     let claims = UserClaims::decode_access(&token)
         .map_err(|_| AppError::UnauthorizedError("Invalid token".to_string()))?
@@ -213,12 +231,24 @@ async fn enforce(
             "Token has been revoked".to_string(),
         ));
     }
+    Ok(claims)
+}
 
-    let required = required_level(feature, request.method());
-    let granted = claims.perms.level(feature);
-
-    if granted >= required {
-        Ok(next.run(request).await)
+/// Require `required` access to `feature` on a request the router's
+/// [`protect`] layer has already admitted at a lower level — e.g. a `Stream`
+/// WebSocket that asks for talkback, which needs `Control: Edit`. The token
+/// comes from the header or `?token=`, as for [`protect`].
+pub(crate) fn require(
+    state: &AppState,
+    headers: &HeaderMap,
+    uri: &Uri,
+    feature: Feature,
+    required: Level,
+) -> Result<UserClaims, AppError> {
+    let token = monitor_acl::extract_token(headers, uri);
+    let claims = verify(state, token)?;
+    if claims.perms.level(feature) >= required {
+        Ok(claims)
     } else {
         Err(AppError::PermissionDeniedError(format!(
             "{:?} access to {:?} required",
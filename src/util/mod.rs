@@ -1,6 +1,7 @@
 pub mod assertion;
 pub mod authz;
 pub mod claim;
+pub mod digest;
 pub mod dir;
 pub mod file;
 pub mod hash;
@@ -37,6 +38,12 @@ pub fn naive_local_to_utc(ndt: chrono::NaiveDateTime) -> chrono::DateTime<chrono
     }
 }
 
+/// The inverse of [`naive_local_to_utc`]: a UTC instant as the naive local
+/// wall-clock ZoneMinder stores, for comparing against `DATETIME` columns.
+pub fn utc_to_naive_local(dt: chrono::DateTime<chrono::Utc>) -> chrono::NaiveDateTime {
+    dt.with_timezone(&chrono::Local).naive_local()
+}
+
 #[cfg(test)]
 mod naive_local_to_utc_tests {
     use super::naive_local_to_utc;
@@ -59,6 +66,7 @@ mod naive_local_to_utc_tests {
         if let chrono::LocalResult::Single(expected) = Local.from_local_datetime(&ndt) {
             assert_eq!(utc, expected.with_timezone(&chrono::Utc));
         }
+        assert_eq!(super::utc_to_naive_local(utc), ndt);
     }
 }
 
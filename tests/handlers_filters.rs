@@ -33,3 +33,25 @@ async fn filters_get_not_found_maps_404() {
 
     res.assert_status(StatusCode::NOT_FOUND);
 }
+
+#[tokio::test]
+async fn filters_create_with_auto_execute_maps_400() {
+    // Rejected before any query runs, so the mock needs no results.
+    let db = MockDatabase::new(DatabaseBackend::MySql).into_connection();
+    let state = AppState::for_test_with_db(db);
+    let app = zm_api::routes::filters::add_filter_routes(Router::new()).with_state(state);
+    let server = TestServer::new(app.into_make_service());
+    let res = server
+        .post("/api/v3/filters")
+        .add_header("Authorization", auth_header())
+        .json(&serde_json::json!({
+            "name": "run a command",
+            "query_json": "{}",
+            "auto_execute": 1,
+            "auto_execute_cmd": "/usr/local/bin/notify %EID%"
+        }))
+        .await;
+
+    res.assert_status(StatusCode::BAD_REQUEST);
+    assert!(res.text().contains("webhook"), "{}", res.text());
+}
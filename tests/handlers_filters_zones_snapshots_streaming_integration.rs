@@ -247,7 +247,7 @@ async fn test_api_streaming_routes_require_auth() {
     let app = build_app(db);
 
     // The live-streaming routes that require authentication. The per-monitor
-    // route is guarded by `monitor_path_guard`; the global session/source
+    // route is guarded by `monitor_stream_guard`; the global session/source
     // routes carry `auth_middleware` directly. All must reject a tokenless
     // request with 401 rather than serving or erroring.
     for path in [
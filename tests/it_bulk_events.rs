@@ -0,0 +1,237 @@
+//! Integration tests for saved searches and background bulk event jobs.
+//!
+//! Covers, against the real test database:
+//!   - `/api/v3/saved-searches` CRUD and auth;
+//!   - `POST /api/v3/events:bulk` selector validation;
+//!   - a dry-run bulk archive polled to completion via `GET /api/v3/jobs/{id}`,
+//!     and a real archive that flips the matching events.
+//!
+//! `saved_searches` and `jobs` are zm-api-owned, so each test first applies the
+//! crate migrations (idempotent).
+//!
+//! Requires the test database — run with:
+//!   APP_PROFILE=test-db cargo test --test it_bulk_events -- --include-ignored
+
+mod common;
+
+use axum::http::{Method, StatusCode};
+use sea_orm::{ActiveModelTrait, EntityTrait, Set};
+use serde_json::{json, Value};
+
+use common::fixtures::{insert_monitor, unique_name, RowGuard};
+use common::harness::{superuser_token, TestApp};
+
+use zm_api::client::database::migrate_database;
+
+/// Apply the crate migrations once per test process (the migrator is not safe
+/// to run concurrently against one database).
+async fn ensure_schema(db: &sea_orm::DatabaseConnection) {
+    static SCHEMA: tokio::sync::OnceCell<()> = tokio::sync::OnceCell::const_new();
+    SCHEMA
+        .get_or_init(|| async {
+            migrate_database(db).await.expect("apply zm-api migrations");
+        })
+        .await;
+}
+
+fn guard_event(id: u64) -> RowGuard {
+    RowGuard::new(format!("Events#{id}"), move |db| async move {
+        let _ = zm_api::entity::events::Entity::delete_by_id(id)
+            .exec(&db)
+            .await;
+    })
+}
+
+fn guard_job(id: u64) -> RowGuard {
+    RowGuard::new(format!("jobs#{id}"), move |db| async move {
+        let _ = zm_api::entity::jobs::Entity::delete_by_id(id)
+            .exec(&db)
+            .await;
+    })
+}
+
+async fn insert_event(app: &TestApp, monitor_id: u32) -> u64 {
+    zm_api::entity::events::ActiveModel {
+        monitor_id: Set(monitor_id),
+        state_id: Set(1),
+        name: Set(unique_name("BulkEvt")),
+        archived: Set(0),
+        ..Default::default()
+    }
+    .insert(&app.db)
+    .await
+    .expect("insert event")
+    .id
+}
+
+fn monitor_filter(monitor_id: u32) -> Value {
+    json!({"where": {"field": "monitor_id", "op": "eq", "value": monitor_id}})
+}
+
+/// Poll a job until it leaves `queued`/`running`, or give up after ~10s.
+async fn wait_for_job(app: &TestApp, token: &str, id: u64) -> Value {
+    for _ in 0..100 {
+        let resp = app.get(&format!("/api/v3/jobs/{id}"), token).await;
+        assert_eq!(resp.status(), StatusCode::OK, "job poll: {}", resp.text());
+        let job: Value = resp.json();
+        if job["status"] != "queued" && job["status"] != "running" {
+            return job;
+        }
+        tokio::time::sleep(std::time::Duration::from_millis(100)).await;
+    }
+    panic!("job {id} did not finish in time");
+}
+
+#[tokio::test]
+#[ignore = "requires the test database (APP_PROFILE=test-db)"]
+async fn saved_searches_without_token_are_unauthorized() {
+    let app = TestApp::spawn().await;
+    let resp = app
+        .request(Method::GET, "/api/v3/saved-searches")
+        .send()
+        .await;
+    assert_eq!(resp.status(), StatusCode::UNAUTHORIZED, "{}", resp.text());
+}
+
+#[tokio::test]
+#[ignore = "requires the test database (APP_PROFILE=test-db)"]
+async fn saved_search_create_get_delete() {
+    let app = TestApp::spawn().await;
+    ensure_schema(&app.db).await;
+    let token = superuser_token();
+
+    let resp = app
+        .post_json(
+            "/api/v3/saved-searches",
+            &token,
+            &json!({"name": "Unarchived on 1", "filter": monitor_filter(1)}),
+        )
+        .await;
+    assert_eq!(resp.status(), StatusCode::CREATED, "{}", resp.text());
+    let created: Value = resp.json();
+    let id = created["id"].as_u64().expect("id");
+    assert_eq!(created["filter"]["where"]["field"], "monitor_id");
+
+    let resp = app
+        .get(&format!("/api/v3/saved-searches/{id}"), &token)
+        .await;
+    assert_eq!(resp.status(), StatusCode::OK, "{}", resp.text());
+
+    let resp = app
+        .delete(&format!("/api/v3/saved-searches/{id}"), &token)
+        .await;
+    assert_eq!(resp.status(), StatusCode::NO_CONTENT, "{}", resp.text());
+    let resp = app
+        .get(&format!("/api/v3/saved-searches/{id}"), &token)
+        .await;
+    assert_eq!(resp.status(), StatusCode::NOT_FOUND, "{}", resp.text());
+}
+
+#[tokio::test]
+#[ignore = "requires the test database (APP_PROFILE=test-db)"]
+async fn saved_search_rejects_uncompilable_filter() {
+    let app = TestApp::spawn().await;
+    ensure_schema(&app.db).await;
+    let resp = app
+        .post_json(
+            "/api/v3/saved-searches",
+            &superuser_token(),
+            &json!({
+                "name": "regexp",
+                "filter": {"where": {"field": "cause", "op": "regexp", "value": "^M"}}
+            }),
+        )
+        .await;
+    assert_eq!(resp.status(), StatusCode::BAD_REQUEST, "{}", resp.text());
+}
+
+#[tokio::test]
+#[ignore = "requires the test database (APP_PROFILE=test-db)"]
+async fn bulk_requires_exactly_one_selector() {
+    let app = TestApp::spawn().await;
+    ensure_schema(&app.db).await;
+    let token = superuser_token();
+
+    let resp = app
+        .post_json("/api/v3/events:bulk", &token, &json!({"action": "archive"}))
+        .await;
+    assert_eq!(resp.status(), StatusCode::BAD_REQUEST, "{}", resp.text());
+
+    let resp = app
+        .post_json(
+            "/api/v3/events:bulk",
+            &token,
+            &json!({"action": "archive", "saved_search_id": 1, "filter": monitor_filter(1)}),
+        )
+        .await;
+    assert_eq!(resp.status(), StatusCode::BAD_REQUEST, "{}", resp.text());
+}
+
+#[tokio::test]
+#[ignore = "requires the test database (APP_PROFILE=test-db)"]
+async fn bulk_archive_dry_run_then_apply() {
+    let app = TestApp::spawn().await;
+    ensure_schema(&app.db).await;
+    let token = superuser_token();
+
+    let monitor = insert_monitor(&app.db, "BulkEvents")
+        .await
+        .expect("insert monitor");
+    let _mon = RowGuard::monitor(monitor.id);
+    let mut _events = Vec::new();
+    let mut ids = Vec::new();
+    for _ in 0..3 {
+        let id = insert_event(&app, monitor.id).await;
+        ids.push(id);
+        _events.push(guard_event(id));
+    }
+
+    // Dry run: counts and samples, changes nothing.
+    let resp = app
+        .post_json(
+            "/api/v3/events:bulk",
+            &token,
+            &json!({"action": "archive", "filter": monitor_filter(monitor.id), "dry_run": true}),
+        )
+        .await;
+    assert_eq!(resp.status(), StatusCode::ACCEPTED, "{}", resp.text());
+    let job_id = resp.json::<Value>()["id"].as_u64().expect("job id");
+    let _job = guard_job(job_id);
+    let job = wait_for_job(&app, &token, job_id).await;
+    assert_eq!(job["status"], "succeeded", "{job}");
+    assert_eq!(job["affected"], 3, "{job}");
+    let sample: Vec<u64> =
+        serde_json::from_value(job["result"]["sample_event_ids"].clone()).expect("sample ids");
+    assert_eq!(sample, ids);
+    for id in &ids {
+        let ev = zm_api::entity::events::Entity::find_by_id(*id)
+            .one(&app.db)
+            .await
+            .unwrap()
+            .unwrap();
+        assert_eq!(ev.archived, 0, "dry run must not archive");
+    }
+
+    // Real run archives every match.
+    let resp = app
+        .post_json(
+            "/api/v3/events:bulk",
+            &token,
+            &json!({"action": "archive", "filter": monitor_filter(monitor.id)}),
+        )
+        .await;
+    assert_eq!(resp.status(), StatusCode::ACCEPTED, "{}", resp.text());
+    let job_id = resp.json::<Value>()["id"].as_u64().expect("job id");
+    let _job2 = guard_job(job_id);
+    let job = wait_for_job(&app, &token, job_id).await;
+    assert_eq!(job["status"], "succeeded", "{job}");
+    assert_eq!(job["affected"], 3, "{job}");
+    for id in &ids {
+        let ev = zm_api::entity::events::Entity::find_by_id(*id)
+            .one(&app.db)
+            .await
+            .unwrap()
+            .unwrap();
+        assert_eq!(ev.archived, 1, "event {id} archived");
+    }
+}
@@ -1,22 +1,31 @@
-//! End-to-end coverage for `DELETE /api/v3/events/{id}`.
+//! End-to-end coverage for `DELETE /api/v3/events/{id}` and
+//! `POST /api/v3/events/{id}:restore`.
 //!
-//! The delete must remove the event's on-disk media directory **and** its child
-//! DB rows (frames, per-period stats — which have no FK cascade), not just the
-//! `Events` row. This drives the full HTTP stack against a real event whose
-//! media lives in a tempdir-backed `Storage`.
+//! A delete tombstones the event (`Events.deleted_at`), hides it from reads and
+//! moves its media into the storage's `.trash/`; a restore within the grace
+//! period puts both back. The trash reaper then purges expired tombstones: the
+//! media **and** the child DB rows (frames, per-period stats — which have no FK
+//! cascade), not just the `Events` row. This drives the full HTTP stack against
+//! real events whose media lives in a tempdir-backed `Storage`.
 //!
 //! Requires the test database — run with:
 //!   APP_PROFILE=test-db cargo test --test it_event_delete -- --include-ignored
 
 mod common;
 
+use std::sync::Arc;
+
+use axum::http::{Method, StatusCode};
 use common::fixtures::{insert_monitor, insert_storage, unique_name, RowGuard};
 use common::harness::{superuser_token, TestApp};
+use common::test_db::get_test_db;
 use sea_orm::{
-    ActiveModelTrait, ColumnTrait, DatabaseConnection, EntityTrait, PaginatorTrait, QueryFilter,
-    Set,
+    ActiveModelTrait, ColumnTrait, DatabaseConnection, EntityTrait, IntoActiveModel,
+    PaginatorTrait, QueryFilter, Set,
 };
+use zm_api::configure::trash::TrashConfig;
 use zm_api::entity::sea_orm_active_enums::Scheme;
+use zm_api::service::trash::TrashReaper;
 
 fn guard_event(id: u64) -> RowGuard {
     RowGuard::new(format!("Events#{id}"), move |db| async move {
@@ -70,9 +79,35 @@ async fn frame_count(db: &DatabaseConnection, event_id: u64) -> u64 {
         .expect("count frames")
 }
 
+/// A Shallow-scheme event with two frames and a video file under `root`;
+/// returns its id and media directory.
+async fn event_with_media(
+    db: &DatabaseConnection,
+    monitor_id: u32,
+    storage_id: u16,
+    root: &std::path::Path,
+) -> (u64, std::path::PathBuf) {
+    let event_id =
+        insert_event_on_storage(db, monitor_id, storage_id, Scheme::Shallow, "EvtDel").await;
+    insert_frame(db, event_id, 1).await;
+    insert_frame(db, event_id, 2).await;
+    // Shallow layout on disk: {root}/{monitor}/{event_id}/{event_id}-video.mp4
+    let dir = root.join(monitor_id.to_string()).join(event_id.to_string());
+    std::fs::create_dir_all(&dir).unwrap();
+    std::fs::write(dir.join(format!("{event_id}-video.mp4")), b"video-bytes").unwrap();
+    (event_id, dir)
+}
+
+async fn restore(app: &TestApp, token: &str, event_id: u64) -> common::harness::TestResponse {
+    app.request(Method::POST, &format!("/api/v3/events/{event_id}:restore"))
+        .bearer(token)
+        .send()
+        .await
+}
+
 #[tokio::test]
 #[ignore = "requires the test database (APP_PROFILE=test-db)"]
-async fn delete_event_removes_media_directory_and_child_rows() {
+async fn delete_event_hides_it_and_moves_media_to_trash() {
     let app = TestApp::spawn().await;
     let token = superuser_token();
     let tmp = tempfile::tempdir().expect("tempdir");
@@ -90,45 +125,144 @@ async fn delete_event_removes_media_directory_and_child_rows() {
     .await
     .expect("insert storage");
     let _sg = RowGuard::storage(storage.id);
-
-    let event_id =
-        insert_event_on_storage(&app.db, monitor.id, storage.id, Scheme::Shallow, "EvtDel").await;
+    let (event_id, dir) = event_with_media(&app.db, monitor.id, storage.id, tmp.path()).await;
     let _eg = guard_event(event_id);
-    insert_frame(&app.db, event_id, 1).await;
-    insert_frame(&app.db, event_id, 2).await;
 
-    // Shallow layout on disk: {root}/{monitor}/{event_id}/{event_id}-video.mp4
-    let dir = tmp
-        .path()
-        .join(monitor.id.to_string())
-        .join(event_id.to_string());
-    std::fs::create_dir_all(&dir).unwrap();
-    std::fs::write(dir.join(format!("{event_id}-video.mp4")), b"video-bytes").unwrap();
+    let resp = app
+        .delete(&format!("/api/v3/events/{event_id}"), &token)
+        .await;
+    assert_eq!(resp.status(), StatusCode::NO_CONTENT, "{}", resp.text());
 
-    // Preconditions: media on disk and child rows present.
-    assert!(dir.exists(), "precondition: media directory exists");
+    // Media moved into the storage's trash, not removed.
+    assert!(!dir.exists(), "the live media directory must be gone");
+    let trashed = tmp.path().join(".trash").join(event_id.to_string());
     assert_eq!(
-        frame_count(&app.db, event_id).await,
-        2,
-        "precondition: frames exist"
+        std::fs::read(trashed.join(format!("{event_id}-video.mp4"))).unwrap(),
+        b"video-bytes"
     );
+    // The row and its children stay, tombstoned, until the reaper runs.
+    let row = zm_api::entity::events::Entity::find_by_id(event_id)
+        .one(&app.db)
+        .await
+        .unwrap()
+        .expect("row kept");
+    assert!(row.deleted_at.is_some(), "row tombstoned");
+    assert_eq!(frame_count(&app.db, event_id).await, 2);
+
+    // Hidden from reads.
+    let resp = app.get(&format!("/api/v3/events/{event_id}"), &token).await;
+    assert_eq!(resp.status(), StatusCode::NOT_FOUND);
+    let resp = app
+        .get(&format!("/api/v3/events?monitor_id={}", monitor.id), &token)
+        .await;
+    assert_eq!(resp.status(), StatusCode::OK);
+    let list: serde_json::Value = resp.json();
+    assert_eq!(list["total"], 0, "{list}");
 
+    // Deleting again is a no-op success.
     let resp = app
         .delete(&format!("/api/v3/events/{event_id}"), &token)
         .await;
-    assert!(
-        resp.status().is_success(),
-        "delete should succeed; status {} body {}",
-        resp.status(),
-        resp.text()
-    );
+    assert_eq!(resp.status(), StatusCode::NO_CONTENT, "{}", resp.text());
+}
 
-    // Media directory removed.
-    assert!(
-        !dir.exists(),
-        "the event's media directory must be gone after delete"
+#[tokio::test]
+#[ignore = "requires the test database (APP_PROFILE=test-db)"]
+async fn restore_brings_back_the_event_and_its_media() {
+    let app = TestApp::spawn().await;
+    let token = superuser_token();
+    let tmp = tempfile::tempdir().expect("tempdir");
+
+    let monitor = insert_monitor(&app.db, "EvtDelMon")
+        .await
+        .expect("insert monitor");
+    let _mg = RowGuard::monitor(monitor.id);
+    let storage = insert_storage(
+        &app.db,
+        "EvtDelStore",
+        tmp.path().to_str().unwrap(),
+        Scheme::Shallow,
+    )
+    .await
+    .expect("insert storage");
+    let _sg = RowGuard::storage(storage.id);
+    let (event_id, dir) = event_with_media(&app.db, monitor.id, storage.id, tmp.path()).await;
+    let _eg = guard_event(event_id);
+
+    // Only a deleted event can be restored.
+    let resp = restore(&app, &token, event_id).await;
+    assert_eq!(resp.status(), StatusCode::NOT_FOUND, "{}", resp.text());
+
+    let resp = app
+        .delete(&format!("/api/v3/events/{event_id}"), &token)
+        .await;
+    assert_eq!(resp.status(), StatusCode::NO_CONTENT, "{}", resp.text());
+
+    let resp = restore(&app, &token, event_id).await;
+    assert_eq!(resp.status(), StatusCode::OK, "{}", resp.text());
+    let event: serde_json::Value = resp.json();
+    assert_eq!(event["id"], event_id);
+    assert_eq!(
+        std::fs::read(dir.join(format!("{event_id}-video.mp4"))).unwrap(),
+        b"video-bytes"
     );
-    // Event row removed.
+    assert!(!tmp
+        .path()
+        .join(".trash")
+        .join(event_id.to_string())
+        .exists());
+    let resp = app.get(&format!("/api/v3/events/{event_id}"), &token).await;
+    assert_eq!(resp.status(), StatusCode::OK);
+}
+
+#[tokio::test]
+#[ignore = "requires the test database (APP_PROFILE=test-db)"]
+async fn reaper_purges_rows_and_media_after_the_grace_period() {
+    let app = TestApp::spawn().await;
+    let token = superuser_token();
+    let tmp = tempfile::tempdir().expect("tempdir");
+
+    let monitor = insert_monitor(&app.db, "EvtDelMon")
+        .await
+        .expect("insert monitor");
+    let _mg = RowGuard::monitor(monitor.id);
+    let storage = insert_storage(
+        &app.db,
+        "EvtDelStore",
+        tmp.path().to_str().unwrap(),
+        Scheme::Shallow,
+    )
+    .await
+    .expect("insert storage");
+    let _sg = RowGuard::storage(storage.id);
+    let (event_id, _dir) = event_with_media(&app.db, monitor.id, storage.id, tmp.path()).await;
+    let _eg = guard_event(event_id);
+
+    let resp = app
+        .delete(&format!("/api/v3/events/{event_id}"), &token)
+        .await;
+    assert_eq!(resp.status(), StatusCode::NO_CONTENT, "{}", resp.text());
+
+    // Age the tombstone past the default 72h grace period.
+    let grace = TrashConfig::default().grace();
+    let mut row = zm_api::entity::events::Entity::find_by_id(event_id)
+        .one(&app.db)
+        .await
+        .unwrap()
+        .expect("row kept")
+        .into_active_model();
+    row.deleted_at = Set(Some(
+        chrono::Utc::now().naive_utc() - grace - chrono::Duration::hours(1),
+    ));
+    row.update(&app.db).await.expect("age tombstone");
+
+    let resp = restore(&app, &token, event_id).await;
+    assert_eq!(resp.status(), StatusCode::CONFLICT, "{}", resp.text());
+
+    let db = Arc::new(get_test_db().await.expect("reaper db connection"));
+    let reaper = TrashReaper::new(db, TrashConfig::default(), "/nonexistent".into());
+    assert!(reaper.reap_once().await.expect("reap") >= 1);
+
     assert!(
         zm_api::entity::events::Entity::find_by_id(event_id)
             .one(&app.db)
@@ -143,4 +277,11 @@ async fn delete_event_removes_media_directory_and_child_rows() {
         0,
         "frames must be deleted together with the event"
     );
+    assert!(
+        !tmp.path()
+            .join(".trash")
+            .join(event_id.to_string())
+            .exists(),
+        "trashed media purged"
+    );
 }
@@ -0,0 +1,140 @@
+//! Integration tests for event export bundles.
+//!
+//! Covers, against the real test database:
+//!   - `POST /api/v3/events/export` selector validation and unknown ids;
+//!   - a small export streamed back inline as a tar whose manifest lists the
+//!     event's files with their SHA-256.
+//!
+//! Requires the test database — run with:
+//!   APP_PROFILE=test-db cargo test --test it_event_export -- --include-ignored
+
+mod common;
+
+use axum::http::StatusCode;
+use futures::StreamExt;
+use sea_orm::{ActiveModelTrait, EntityTrait, Set};
+use serde_json::{json, Value};
+use sha2::{Digest, Sha256};
+use tokio::io::AsyncReadExt;
+
+use common::fixtures::{insert_monitor, unique_name, RowGuard};
+use common::harness::{superuser_token, TestApp};
+
+use zm_api::client::database::migrate_database;
+
+/// Apply the crate migrations once per test process (the migrator is not safe
+/// to run concurrently against one database).
+async fn ensure_schema(db: &sea_orm::DatabaseConnection) {
+    static SCHEMA: tokio::sync::OnceCell<()> = tokio::sync::OnceCell::const_new();
+    SCHEMA
+        .get_or_init(|| async {
+            migrate_database(db).await.expect("apply zm-api migrations");
+        })
+        .await;
+}
+
+fn guard_event(id: u64) -> RowGuard {
+    RowGuard::new(format!("Events#{id}"), move |db| async move {
+        let _ = zm_api::entity::events::Entity::delete_by_id(id)
+            .exec(&db)
+            .await;
+    })
+}
+
+#[tokio::test]
+#[ignore = "requires the test database (APP_PROFILE=test-db)"]
+async fn export_requires_exactly_one_selector() {
+    let app = TestApp::spawn().await;
+    ensure_schema(&app.db).await;
+    let token = superuser_token();
+
+    let resp = app
+        .post_json("/api/v3/events/export", &token, &json!({}))
+        .await;
+    assert_eq!(resp.status(), StatusCode::BAD_REQUEST, "{}", resp.text());
+
+    let resp = app
+        .post_json(
+            "/api/v3/events/export",
+            &token,
+            &json!({
+                "event_ids": [1],
+                "filter": {"where": {"field": "monitor_id", "op": "eq", "value": 1}}
+            }),
+        )
+        .await;
+    assert_eq!(resp.status(), StatusCode::BAD_REQUEST, "{}", resp.text());
+}
+
+#[tokio::test]
+#[ignore = "requires the test database (APP_PROFILE=test-db)"]
+async fn export_of_unknown_event_is_not_found() {
+    let app = TestApp::spawn().await;
+    ensure_schema(&app.db).await;
+    let resp = app
+        .post_json(
+            "/api/v3/events/export",
+            &superuser_token(),
+            &json!({"event_ids": [u32::MAX]}),
+        )
+        .await;
+    assert_eq!(resp.status(), StatusCode::NOT_FOUND, "{}", resp.text());
+}
+
+#[tokio::test]
+#[ignore = "requires the test database (APP_PROFILE=test-db)"]
+async fn small_export_streams_a_tar_with_manifest() {
+    let app = TestApp::spawn().await;
+    ensure_schema(&app.db).await;
+
+    let monitor = insert_monitor(&app.db, "Export")
+        .await
+        .expect("insert monitor");
+    let _mon = RowGuard::monitor(monitor.id);
+    let event_id = zm_api::entity::events::ActiveModel {
+        monitor_id: Set(monitor.id),
+        state_id: Set(1),
+        name: Set(unique_name("ExportEvt")),
+        archived: Set(0),
+        ..Default::default()
+    }
+    .insert(&app.db)
+    .await
+    .expect("insert event")
+    .id;
+    let _event = guard_event(event_id);
+
+    let resp = app
+        .post_json(
+            "/api/v3/events/export",
+            &superuser_token(),
+            &json!({"event_ids": [event_id], "format": "tar"}),
+        )
+        .await;
+    assert_eq!(resp.status(), StatusCode::OK, "{}", resp.text());
+
+    let mut entries = tokio_tar::Archive::new(resp.body.as_slice())
+        .entries()
+        .expect("tar entries");
+    let mut files = std::collections::HashMap::new();
+    while let Some(entry) = entries.next().await {
+        let mut entry = entry.expect("tar entry");
+        let path = entry.path().unwrap().display().to_string();
+        let mut body = Vec::new();
+        entry.read_to_end(&mut body).await.unwrap();
+        files.insert(path, body);
+    }
+
+    let manifest: Value =
+        serde_json::from_slice(&files["manifest.json"]).expect("manifest is JSON");
+    assert_eq!(manifest["events"][0]["id"], event_id);
+    let listed = manifest["files"].as_array().expect("files");
+    assert!(!listed.is_empty());
+    for file in listed {
+        let path = file["path"].as_str().unwrap();
+        let body = &files[path];
+        assert_eq!(file["size"], body.len() as u64, "{path}");
+        assert_eq!(file["sha256"], hex::encode(Sha256::digest(body)), "{path}");
+    }
+    assert!(files.contains_key(&format!("event-{event_id}/event.json")));
+}
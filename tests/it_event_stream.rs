@@ -0,0 +1,61 @@
+//! Integration tests for the event firehose (`src/handlers/event_stream.rs`):
+//!   - SSE:       `GET /api/v3/events/stream`
+//!   - WebSocket: `GET /api/v3/events/stream/ws`
+//!
+//! A request that clears auth and validation opens a never-ending stream,
+//! which `TestRequest::send` would wait on forever, so these tests stop at the
+//! edges: auth gating (401), query-token acceptance and query validation (400).
+//! Per-notification ACL filtering is unit-tested in the handler module.
+//!
+//! Requires the test database — run with:
+//!   APP_PROFILE=test-db cargo test --test it_event_stream -- --include-ignored
+
+mod common;
+
+use axum::http::{Method, StatusCode};
+use common::assertions::assert_error;
+use common::harness::{superuser_token, TestApp};
+
+#[tokio::test]
+#[ignore = "requires the test database (APP_PROFILE=test-db)"]
+async fn event_stream_rejects_unauthenticated_access() {
+    let app = TestApp::spawn().await;
+
+    for path in ["/api/v3/events/stream", "/api/v3/events/stream/ws"] {
+        let resp = app.request(Method::GET, path).send().await;
+        assert_error(&resp, StatusCode::UNAUTHORIZED, "UNAUTHORIZED_ERROR");
+    }
+}
+
+#[tokio::test]
+#[ignore = "requires the test database (APP_PROFILE=test-db)"]
+async fn event_stream_accepts_query_token_and_validates_filters() {
+    let app = TestApp::spawn().await;
+    let token = superuser_token();
+
+    // The token rides the query string (EventSource / browser WebSocket). A
+    // malformed filter must then fail validation — proof auth was cleared.
+    for path in [
+        format!("/api/v3/events/stream?token={token}&kinds=event_open,bogus"),
+        format!("/api/v3/events/stream?token={token}&monitors=1,x"),
+    ] {
+        let resp = app.request(Method::GET, &path).send().await;
+        assert_error(&resp, StatusCode::BAD_REQUEST, "BAD_REQUEST_ERROR");
+    }
+
+    // `oneshot` cannot drive a protocol upgrade, so the WebSocket route is
+    // refused by the upgrade extractor — but not by auth.
+    let resp = app
+        .request(
+            Method::GET,
+            &format!("/api/v3/events/stream/ws?token={token}&kinds=event_open"),
+        )
+        .send()
+        .await;
+    assert!(
+        resp.status().is_client_error() && resp.status() != StatusCode::UNAUTHORIZED,
+        "non-upgrade WebSocket request should be a non-auth 4xx, got {}; body: {}",
+        resp.status(),
+        resp.text()
+    );
+}
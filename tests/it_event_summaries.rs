@@ -1,4 +1,5 @@
 //! Integration tests for the Event Summaries API — read-only (list + get).
+//! Trashed events, still counted by the ZoneMinder triggers, are taken out.
 //!
 //! Requires the test database — run with:
 //!   APP_PROFILE=test-db cargo test --test it_event_summaries -- --include-ignored
@@ -7,9 +8,9 @@ mod common;
 
 use axum::http::StatusCode;
 use common::assertions::{assert_error, assert_status};
-use common::fixtures::{insert_monitor, RowGuard};
+use common::fixtures::{insert_monitor, unique_name, RowGuard};
 use common::harness::{superuser_token, TestApp};
-use sea_orm::{ActiveModelTrait, EntityTrait, Set};
+use sea_orm::{ActiveModelTrait, ColumnTrait, EntityTrait, QueryFilter, Set};
 use zm_api::dto::response::{EventSummaryResponse, PaginatedEventSummariesResponse};
 
 /// Guard an `Event_Summaries` row (keyed by monitor id).
@@ -94,3 +95,123 @@ async fn get_missing_event_summary_is_not_found() {
         "EVENT_SUMMARY_NOT_FOUND_ERROR",
     );
 }
+
+/// Insert a finished event of `disk_space` bytes, tombstoned when `trashed`,
+/// and guard it together with its rows in the window tables.
+async fn insert_event(
+    db: &sea_orm::DatabaseConnection,
+    monitor_id: u32,
+    disk_space: u64,
+    trashed: bool,
+) -> (u64, RowGuard) {
+    let now = chrono::Utc::now().naive_utc();
+    let id = zm_api::entity::events::ActiveModel {
+        monitor_id: Set(monitor_id),
+        state_id: Set(1),
+        name: Set(unique_name("SummaryEvent")),
+        start_date_time: Set(Some(now)),
+        end_date_time: Set(Some(now)),
+        disk_space: Set(Some(disk_space)),
+        deleted_at: Set(trashed.then_some(now)),
+        ..Default::default()
+    }
+    .insert(db)
+    .await
+    .expect("insert event")
+    .id;
+    let guard = RowGuard::new(format!("Events#{id}"), move |db| async move {
+        clear_windows(&db, id).await;
+        let _ = zm_api::entity::events::Entity::delete_by_id(id)
+            .exec(&db)
+            .await;
+    });
+    (id, guard)
+}
+
+/// Drop an event from the four window tables, whatever the triggers put there.
+async fn clear_windows(db: &sea_orm::DatabaseConnection, event_id: u64) {
+    use zm_api::entity::{events_day, events_hour, events_month, events_week};
+    let id = event_id as u32;
+    let _ = events_hour::Entity::delete_many()
+        .filter(events_hour::Column::EventId.eq(id))
+        .exec(db)
+        .await;
+    let _ = events_day::Entity::delete_many()
+        .filter(events_day::Column::EventId.eq(id))
+        .exec(db)
+        .await;
+    let _ = events_week::Entity::delete_many()
+        .filter(events_week::Column::EventId.eq(id))
+        .exec(db)
+        .await;
+    let _ = events_month::Entity::delete_many()
+        .filter(events_month::Column::EventId.eq(id))
+        .exec(db)
+        .await;
+}
+
+#[tokio::test]
+#[ignore = "requires the test database (APP_PROFILE=test-db)"]
+async fn trashed_events_are_not_counted() {
+    let app = TestApp::spawn().await;
+    let token = superuser_token();
+    let monitor = insert_monitor(&app.db, "SummaryTrash")
+        .await
+        .expect("insert monitor");
+    let _mon = RowGuard::monitor(monitor.id);
+    let (live, _live) = insert_event(&app.db, monitor.id, 400, false).await;
+    let (trashed, _trashed) = insert_event(&app.db, monitor.id, 100, true).await;
+
+    // Pin the window tables and the summary, whatever the triggers did: the
+    // trashed event is still in the day window, and counted everywhere.
+    clear_windows(&app.db, live).await;
+    clear_windows(&app.db, trashed).await;
+    zm_api::entity::events_day::ActiveModel {
+        event_id: Set(trashed as u32),
+        monitor_id: Set(monitor.id),
+        start_date_time: Set(Some(chrono::Utc::now().naive_utc())),
+        disk_space: Set(Some(100)),
+    }
+    .insert(&app.db)
+    .await
+    .expect("insert day window row");
+    let _ = zm_api::entity::event_summaries::Entity::delete_by_id(monitor.id)
+        .exec(&app.db)
+        .await;
+    zm_api::entity::event_summaries::ActiveModel {
+        monitor_id: Set(monitor.id),
+        total_events: Set(Some(2)),
+        total_event_disk_space: Set(Some(500)),
+        hour_events: Set(Some(1)),
+        hour_event_disk_space: Set(Some(400)),
+        day_events: Set(Some(2)),
+        day_event_disk_space: Set(Some(500)),
+        ..Default::default()
+    }
+    .insert(&app.db)
+    .await
+    .expect("insert event summary fixture");
+    let _summary = guard_summary(monitor.id);
+
+    let resp = app
+        .get(&format!("/api/v3/event-summaries/{}", monitor.id), &token)
+        .await;
+    assert_status(&resp, StatusCode::OK);
+    let body: EventSummaryResponse = resp.json();
+    assert_eq!((body.total_events, body.total_event_disk_space), (1, 400));
+    assert_eq!((body.day_events, body.day_event_disk_space), (1, 400));
+    // Not in the hour window, so nothing to take out there.
+    assert_eq!((body.hour_events, body.hour_event_disk_space), (1, 400));
+
+    let resp = app
+        .get("/api/v3/event-summaries?page=1&page_size=1000", &token)
+        .await;
+    assert_status(&resp, StatusCode::OK);
+    let body: PaginatedEventSummariesResponse = resp.json();
+    let row = body
+        .items
+        .iter()
+        .find(|s| s.monitor_id == monitor.id)
+        .expect("summary listed");
+    assert_eq!(row.total_events, 1);
+}
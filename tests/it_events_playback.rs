@@ -8,6 +8,8 @@
 //!   - `GET /api/v3/events/{id}/video`
 //!   - `GET /api/v3/events/{id}/thumbnail`
 //!   - `GET /api/v3/events/{id}/info`
+//!   - `GET /api/v3/events/{id}/preview.webp` / `preview.gif`
+//!   - `GET /api/v3/events/{id}/contact-sheet.jpg`
 //!
 //! Covers not-found, unauthenticated (header + `?token=` query param), and the
 //! event-exists-but-media-missing path.
@@ -327,3 +329,85 @@ async fn vod_playlist_for_event_with_no_media_file_errors() {
         resp.text()
     );
 }
+
+#[tokio::test]
+#[ignore = "requires the test database (APP_PROFILE=test-db)"]
+async fn previews_for_unknown_event_are_not_found() {
+    let app = TestApp::spawn().await;
+    let token = superuser_token();
+
+    for route in ["preview.webp", "preview.gif", "contact-sheet.jpg"] {
+        let resp = app
+            .get(
+                &format!("/api/v3/events/{}/{route}", MISSING_EVENT_ID),
+                &token,
+            )
+            .await;
+        assert_eq!(
+            resp.status(),
+            StatusCode::NOT_FOUND,
+            "unknown event {route} should be 404; body: {}",
+            resp.text()
+        );
+    }
+}
+
+#[tokio::test]
+#[ignore = "requires the test database (APP_PROFILE=test-db)"]
+async fn contact_sheet_rejects_out_of_range_layouts() {
+    let app = TestApp::spawn().await;
+    let token = superuser_token();
+
+    for query in ["frames=0", "frames=61", "columns=0", "columns=13"] {
+        let resp = app
+            .get(
+                &format!(
+                    "/api/v3/events/{}/contact-sheet.jpg?{query}",
+                    MISSING_EVENT_ID
+                ),
+                &token,
+            )
+            .await;
+        assert_eq!(
+            resp.status(),
+            StatusCode::BAD_REQUEST,
+            "{query} should be 400; body: {}",
+            resp.text()
+        );
+    }
+}
+
+#[tokio::test]
+#[ignore = "requires the test database (APP_PROFILE=test-db)"]
+async fn preview_of_event_still_recording_is_not_found() {
+    let app = TestApp::spawn().await;
+    let token = superuser_token();
+
+    let monitor = insert_monitor(&app.db, "PlaybackPreviewLive")
+        .await
+        .expect("insert monitor fixture");
+    let _mon = RowGuard::monitor(monitor.id);
+
+    // No EndDateTime: ZoneMinder is still recording it.
+    let event = zm_api::entity::events::ActiveModel {
+        monitor_id: Set(monitor.id),
+        state_id: Set(1),
+        name: Set(unique_name("PlaybackPreviewLive")),
+        ..Default::default()
+    }
+    .insert(&app.db)
+    .await
+    .expect("insert event fixture");
+    let _evt = guard_event(event.id);
+
+    let resp = app
+        .get(&format!("/api/v3/events/{}/preview.gif", event.id), &token)
+        .await;
+    assert_eq!(
+        resp.status(),
+        StatusCode::NOT_FOUND,
+        "in-progress event preview should be 404; body: {}",
+        resp.text()
+    );
+    assert!(resp.text().contains("still recording"), "{}", resp.text());
+}
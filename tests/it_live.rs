@@ -5,6 +5,7 @@
 //!   - per-monitor: `/live/{id}/{stats,hls/*}`
 //!   - global:      `/live/sessions`, `/live/sources`
 //!   - snapshot:    `/monitors/{id}/snapshot`
+//!   - events (SSE): `/monitors/{id}/events`
 //!
 //! The test `AppState` is built with no streaming services wired (see
 //! `AppState::for_test_with_db`), so any request that clears authentication
@@ -63,6 +64,7 @@ async fn live_endpoints_reject_unauthenticated_access() {
         "/api/v3/live/sessions",
         "/api/v3/live/sources",
         &format!("/api/v3/monitors/{MISSING_MONITOR_ID}/snapshot"),
+        &format!("/api/v3/monitors/{MISSING_MONITOR_ID}/events"),
     ] {
         let resp = app.request(Method::GET, path).send().await;
         assert_eq!(
@@ -244,3 +246,35 @@ async fn snapshot_without_token_is_unauthorized() {
         resp.text()
     );
 }
+
+// ---------------------------------------------------------------------------
+// monitor events (SSE) endpoint
+// ---------------------------------------------------------------------------
+
+#[tokio::test]
+#[ignore = "requires the test database (APP_PROFILE=test-db)"]
+async fn monitor_events_accepts_query_token_and_reports_service_unavailable() {
+    let app = TestApp::spawn().await;
+    let token = superuser_token();
+    let monitor = insert_monitor(&app.db, "LiveEvents")
+        .await
+        .expect("insert monitor fixture");
+    let _mon = RowGuard::monitor(monitor.id);
+
+    // `EventSource` cannot set headers, so the token rides the query string.
+    // It must clear auth (not 401); with no source router wired the handler
+    // then reports 503 rather than opening a stream.
+    let resp = app
+        .request(
+            Method::GET,
+            &format!("/api/v3/monitors/{}/events?token={token}", monitor.id),
+        )
+        .send()
+        .await;
+    assert_eq!(
+        resp.status(),
+        StatusCode::SERVICE_UNAVAILABLE,
+        "events without a source router should be 503; body: {}",
+        resp.text()
+    );
+}
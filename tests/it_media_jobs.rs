@@ -0,0 +1,411 @@
+//! Integration tests for per-event media jobs (`POST /api/v3/events/{id}/jobs`).
+//!
+//! Covers, against the real test database:
+//!   - option validation (move/copy need a target storage);
+//!   - a `move` between two tempdir-backed `Storage` rows, polled to
+//!     completion via `GET /api/v3/jobs/{id}`: the media lands under the
+//!     target, the source directory is gone and `Events.StorageId` follows;
+//!   - a `copy`, which leaves the source alone and records
+//!     `Events.SecondaryStorageId`;
+//!   - one active job per event: concurrent inserts and concurrent submits
+//!     never leave two jobs queued or running for the same event.
+//!
+//! `jobs` is zm-api-owned, so each test first applies the crate migrations
+//! (idempotent).
+//!
+//! Requires the test database — run with:
+//!   APP_PROFILE=test-db cargo test --test it_media_jobs -- --include-ignored
+
+mod common;
+
+use axum::http::StatusCode;
+use sea_orm::{ActiveModelTrait, DatabaseConnection, EntityTrait, Set};
+use serde_json::{json, Value};
+
+use common::fixtures::{insert_monitor, insert_storage, unique_name, RowGuard};
+use common::harness::{superuser_token, TestApp};
+
+use zm_api::client::database::migrate_database;
+use zm_api::entity::sea_orm_active_enums::{JobKind, Scheme};
+
+/// Apply the crate migrations once per test process (the migrator is not safe
+/// to run concurrently against one database).
+async fn ensure_schema(db: &DatabaseConnection) {
+    static SCHEMA: tokio::sync::OnceCell<()> = tokio::sync::OnceCell::const_new();
+    SCHEMA
+        .get_or_init(|| async {
+            migrate_database(db).await.expect("apply zm-api migrations");
+        })
+        .await;
+}
+
+fn guard_event(id: u64) -> RowGuard {
+    RowGuard::new(format!("Events#{id}"), move |db| async move {
+        let _ = zm_api::entity::events::Entity::delete_by_id(id)
+            .exec(&db)
+            .await;
+    })
+}
+
+fn guard_job(id: u64) -> RowGuard {
+    RowGuard::new(format!("jobs#{id}"), move |db| async move {
+        let _ = zm_api::entity::jobs::Entity::delete_by_id(id)
+            .exec(&db)
+            .await;
+    })
+}
+
+/// Insert a finished Shallow-scheme event on `storage_id` and give it some
+/// media under `storage_root`.
+async fn insert_event_with_media(
+    db: &DatabaseConnection,
+    monitor_id: u32,
+    storage_id: u16,
+    storage_root: &std::path::Path,
+) -> u64 {
+    let now = chrono::Utc::now().naive_utc();
+    let id = zm_api::entity::events::ActiveModel {
+        monitor_id: Set(monitor_id),
+        storage_id: Set(Some(storage_id)),
+        state_id: Set(1),
+        name: Set(unique_name("MediaJobEvt")),
+        scheme: Set(Scheme::Shallow),
+        start_date_time: Set(Some(now)),
+        end_date_time: Set(Some(now)),
+        ..Default::default()
+    }
+    .insert(db)
+    .await
+    .expect("insert event")
+    .id;
+    let dir = storage_root
+        .join(monitor_id.to_string())
+        .join(id.to_string());
+    std::fs::create_dir_all(&dir).unwrap();
+    std::fs::write(dir.join(format!("{id}-video.mp4")), b"video bytes").unwrap();
+    std::fs::write(dir.join("00001-capture.jpg"), b"jpeg").unwrap();
+    id
+}
+
+/// Poll a job until it leaves `queued`/`running`, or give up after ~10s.
+async fn wait_for_job(app: &TestApp, token: &str, id: u64) -> Value {
+    for _ in 0..100 {
+        let resp = app.get(&format!("/api/v3/jobs/{id}"), token).await;
+        assert_eq!(resp.status(), StatusCode::OK, "job poll: {}", resp.text());
+        let job: Value = resp.json();
+        if job["status"] != "queued" && job["status"] != "running" {
+            return job;
+        }
+        tokio::time::sleep(std::time::Duration::from_millis(100)).await;
+    }
+    panic!("job {id} did not finish in time");
+}
+
+#[tokio::test]
+#[ignore = "requires the test database (APP_PROFILE=test-db)"]
+async fn move_and_copy_require_a_target_storage() {
+    let app = TestApp::spawn().await;
+    ensure_schema(&app.db).await;
+    let token = superuser_token();
+    let tmp = tempfile::tempdir().expect("tempdir");
+
+    let monitor = insert_monitor(&app.db, "MediaJobsMon")
+        .await
+        .expect("insert monitor");
+    let _mon = RowGuard::monitor(monitor.id);
+    let storage = insert_storage(
+        &app.db,
+        "MediaJobsSrc",
+        tmp.path().to_str().unwrap(),
+        Scheme::Shallow,
+    )
+    .await
+    .expect("insert storage");
+    let _sto = RowGuard::storage(storage.id);
+    let id = insert_event_with_media(&app.db, monitor.id, storage.id, tmp.path()).await;
+    let _ev = guard_event(id);
+
+    for kind in ["move", "copy"] {
+        let resp = app
+            .post_json(
+                &format!("/api/v3/events/{id}/jobs"),
+                &token,
+                &json!({"kind": kind}),
+            )
+            .await;
+        assert_eq!(resp.status(), StatusCode::BAD_REQUEST, "{}", resp.text());
+    }
+    let resp = app
+        .post_json(
+            &format!("/api/v3/events/{id}/jobs"),
+            &token,
+            &json!({"kind": "move", "target_storage_id": storage.id}),
+        )
+        .await;
+    assert_eq!(
+        resp.status(),
+        StatusCode::BAD_REQUEST,
+        "already on that storage: {}",
+        resp.text()
+    );
+}
+
+#[tokio::test]
+#[ignore = "requires the test database (APP_PROFILE=test-db)"]
+async fn move_relocates_media_and_repoints_storage_id() {
+    let app = TestApp::spawn().await;
+    ensure_schema(&app.db).await;
+    let token = superuser_token();
+    let hot = tempfile::tempdir().expect("tempdir");
+    let cold = tempfile::tempdir().expect("tempdir");
+
+    let monitor = insert_monitor(&app.db, "MediaJobsMon")
+        .await
+        .expect("insert monitor");
+    let _mon = RowGuard::monitor(monitor.id);
+    let src = insert_storage(
+        &app.db,
+        "Hot",
+        hot.path().to_str().unwrap(),
+        Scheme::Shallow,
+    )
+    .await
+    .expect("insert storage");
+    let _src = RowGuard::storage(src.id);
+    let dst = insert_storage(
+        &app.db,
+        "Cold",
+        cold.path().to_str().unwrap(),
+        Scheme::Shallow,
+    )
+    .await
+    .expect("insert storage");
+    let _dst = RowGuard::storage(dst.id);
+    let id = insert_event_with_media(&app.db, monitor.id, src.id, hot.path()).await;
+    let _ev = guard_event(id);
+
+    let resp = app
+        .post_json(
+            &format!("/api/v3/events/{id}/jobs"),
+            &token,
+            &json!({"kind": "move", "target_storage_id": dst.id}),
+        )
+        .await;
+    assert_eq!(resp.status(), StatusCode::ACCEPTED, "{}", resp.text());
+    let job_id = resp.json::<Value>()["id"].as_u64().expect("job id");
+    let _job = guard_job(job_id);
+    let job = wait_for_job(&app, &token, job_id).await;
+    assert_eq!(job["status"], "succeeded", "{job}");
+    assert_eq!(job["kind"], "event_move", "{job}");
+    assert_eq!(job["processed"], 2, "{job}");
+
+    let moved = cold
+        .path()
+        .join(monitor.id.to_string())
+        .join(id.to_string());
+    assert_eq!(
+        std::fs::read(moved.join(format!("{id}-video.mp4"))).unwrap(),
+        b"video bytes"
+    );
+    assert!(
+        !hot.path()
+            .join(monitor.id.to_string())
+            .join(id.to_string())
+            .exists(),
+        "source directory removed after the switch"
+    );
+    let ev = zm_api::entity::events::Entity::find_by_id(id)
+        .one(&app.db)
+        .await
+        .unwrap()
+        .unwrap();
+    assert_eq!(ev.storage_id, Some(dst.id));
+}
+
+#[tokio::test]
+#[ignore = "requires the test database (APP_PROFILE=test-db)"]
+async fn copy_keeps_the_source_and_records_secondary_storage() {
+    let app = TestApp::spawn().await;
+    ensure_schema(&app.db).await;
+    let token = superuser_token();
+    let hot = tempfile::tempdir().expect("tempdir");
+    let cold = tempfile::tempdir().expect("tempdir");
+
+    let monitor = insert_monitor(&app.db, "MediaJobsMon")
+        .await
+        .expect("insert monitor");
+    let _mon = RowGuard::monitor(monitor.id);
+    let src = insert_storage(
+        &app.db,
+        "Hot",
+        hot.path().to_str().unwrap(),
+        Scheme::Shallow,
+    )
+    .await
+    .expect("insert storage");
+    let _src = RowGuard::storage(src.id);
+    let dst = insert_storage(
+        &app.db,
+        "Cold",
+        cold.path().to_str().unwrap(),
+        Scheme::Shallow,
+    )
+    .await
+    .expect("insert storage");
+    let _dst = RowGuard::storage(dst.id);
+    let id = insert_event_with_media(&app.db, monitor.id, src.id, hot.path()).await;
+    let _ev = guard_event(id);
+
+    let resp = app
+        .post_json(
+            &format!("/api/v3/events/{id}/jobs"),
+            &token,
+            &json!({"kind": "copy", "target_storage_id": dst.id}),
+        )
+        .await;
+    assert_eq!(resp.status(), StatusCode::ACCEPTED, "{}", resp.text());
+    let job_id = resp.json::<Value>()["id"].as_u64().expect("job id");
+    let _job = guard_job(job_id);
+
+    let job = wait_for_job(&app, &token, job_id).await;
+    assert_eq!(job["status"], "succeeded", "{job}");
+    for root in [hot.path(), cold.path()] {
+        assert!(root
+            .join(monitor.id.to_string())
+            .join(id.to_string())
+            .join("00001-capture.jpg")
+            .exists());
+    }
+    let ev = zm_api::entity::events::Entity::find_by_id(id)
+        .one(&app.db)
+        .await
+        .unwrap()
+        .unwrap();
+    assert_eq!(ev.storage_id, Some(src.id), "primary storage unchanged");
+    assert_eq!(ev.secondary_storage_id, Some(dst.id));
+}
+
+#[tokio::test]
+#[ignore = "requires the test database (APP_PROFILE=test-db)"]
+async fn concurrent_inserts_leave_one_active_job_per_event() {
+    let app = TestApp::spawn().await;
+    ensure_schema(&app.db).await;
+    let tmp = tempfile::tempdir().expect("tempdir");
+
+    let monitor = insert_monitor(&app.db, "MediaJobsMon")
+        .await
+        .expect("insert monitor");
+    let _mon = RowGuard::monitor(monitor.id);
+    let storage = insert_storage(
+        &app.db,
+        "MediaJobsSrc",
+        tmp.path().to_str().unwrap(),
+        Scheme::Shallow,
+    )
+    .await
+    .expect("insert storage");
+    let _sto = RowGuard::storage(storage.id);
+    let id = insert_event_with_media(&app.db, monitor.id, storage.id, tmp.path()).await;
+    let _ev = guard_event(id);
+
+    // Straight at the table, so nothing but the unique key stands between
+    // the two: no pre-check, no worker finishing the first job early.
+    let now = chrono::Utc::now().naive_utc();
+    let insert = |kind| zm_api::repo::jobs::insert(&app.db, kind, None, Some(id), "{}".into(), now);
+    let (a, b) = tokio::join!(insert(JobKind::EventMove), insert(JobKind::EventTranscode));
+    let _guards: Vec<RowGuard> = [&a, &b]
+        .into_iter()
+        .filter_map(|r| r.as_ref().ok())
+        .map(|job| guard_job(job.id))
+        .collect();
+    let refused: Vec<_> = [a, b].into_iter().filter_map(Result::err).collect();
+    assert_eq!(refused.len(), 1, "exactly one insert must be refused");
+    assert!(
+        matches!(
+            refused[0].sql_err(),
+            Some(sea_orm::SqlErr::UniqueConstraintViolation(_))
+        ),
+        "{:?}",
+        refused[0]
+    );
+}
+
+#[tokio::test]
+#[ignore = "requires the test database (APP_PROFILE=test-db)"]
+async fn concurrent_submits_never_run_two_jobs_on_one_event() {
+    let app = TestApp::spawn().await;
+    ensure_schema(&app.db).await;
+    let token = superuser_token();
+    let hot = tempfile::tempdir().expect("tempdir");
+    let cold = tempfile::tempdir().expect("tempdir");
+
+    let monitor = insert_monitor(&app.db, "MediaJobsMon")
+        .await
+        .expect("insert monitor");
+    let _mon = RowGuard::monitor(monitor.id);
+    let src = insert_storage(
+        &app.db,
+        "Hot",
+        hot.path().to_str().unwrap(),
+        Scheme::Shallow,
+    )
+    .await
+    .expect("insert storage");
+    let _src = RowGuard::storage(src.id);
+    let dst = insert_storage(
+        &app.db,
+        "Cold",
+        cold.path().to_str().unwrap(),
+        Scheme::Shallow,
+    )
+    .await
+    .expect("insert storage");
+    let _dst = RowGuard::storage(dst.id);
+    let id = insert_event_with_media(&app.db, monitor.id, src.id, hot.path()).await;
+    let _ev = guard_event(id);
+
+    let path = format!("/api/v3/events/{id}/jobs");
+    let move_body = json!({"kind": "move", "target_storage_id": dst.id});
+    let transcode_body = json!({"kind": "transcode"});
+    let (first, second) = tokio::join!(
+        app.post_json(&path, &token, &move_body),
+        app.post_json(&path, &token, &transcode_body)
+    );
+
+    let mut accepted = Vec::new();
+    for resp in [first, second] {
+        match resp.status() {
+            StatusCode::ACCEPTED => {
+                accepted.push(resp.json::<Value>()["id"].as_u64().expect("job id"))
+            }
+            StatusCode::CONFLICT => {}
+            other => panic!("unexpected {other}: {}", resp.text()),
+        }
+    }
+    assert!(!accepted.is_empty(), "one of the submits must be queued");
+    let _jobs: Vec<RowGuard> = accepted.iter().map(|&job| guard_job(job)).collect();
+
+    // Both may be accepted only one after the other: the second was created
+    // no earlier than the first finished.
+    let mut jobs = Vec::new();
+    for &job_id in &accepted {
+        wait_for_job(&app, &token, job_id).await;
+        jobs.push(
+            zm_api::entity::jobs::Entity::find_by_id(job_id)
+                .one(&app.db)
+                .await
+                .unwrap()
+                .unwrap(),
+        );
+    }
+    jobs.sort_by_key(|job| job.id);
+    if let [earlier, later] = jobs.as_slice() {
+        let finished = earlier.finished_at.expect("first job finished");
+        assert!(
+            later.created_at >= finished,
+            "job {} was queued while job {} was still active",
+            later.id,
+            earlier.id
+        );
+    }
+}
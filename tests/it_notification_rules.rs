@@ -0,0 +1,203 @@
+//! Integration tests for notification rules (`/api/v3/notification-rules`).
+//!
+//! Covers, against the real test database: auth, CRUD, the create-time
+//! validation (channel target, `score_threshold` threshold), clearing a field
+//! with `null`, the (empty) delivery history of a new rule, and webhook
+//! signing keys being issued once and never shown again.
+//!
+//! `notification_rules` and `notification_deliveries` are zm-api-owned, so
+//! each test first applies the crate migrations (idempotent).
+//!
+//! Requires the test database — run with:
+//!   APP_PROFILE=test-db cargo test --test it_notification_rules -- --include-ignored
+
+mod common;
+
+use axum::http::{Method, StatusCode};
+use serde_json::{json, Value};
+
+use common::harness::{superuser_token, TestApp};
+
+use zm_api::client::database::migrate_database;
+
+/// Apply the crate migrations once per test process (the migrator is not safe
+/// to run concurrently against one database).
+async fn ensure_schema(db: &sea_orm::DatabaseConnection) {
+    static SCHEMA: tokio::sync::OnceCell<()> = tokio::sync::OnceCell::const_new();
+    SCHEMA
+        .get_or_init(|| async {
+            migrate_database(db).await.expect("apply zm-api migrations");
+        })
+        .await;
+}
+
+#[tokio::test]
+#[ignore = "requires the test database (APP_PROFILE=test-db)"]
+async fn notification_rules_without_token_are_unauthorized() {
+    let app = TestApp::spawn().await;
+    let resp = app
+        .request(Method::GET, "/api/v3/notification-rules")
+        .send()
+        .await;
+    assert_eq!(resp.status(), StatusCode::UNAUTHORIZED, "{}", resp.text());
+}
+
+#[tokio::test]
+#[ignore = "requires the test database (APP_PROFILE=test-db)"]
+async fn notification_rule_crud() {
+    let app = TestApp::spawn().await;
+    ensure_schema(&app.db).await;
+    let token = superuser_token();
+
+    let resp = app
+        .post_json(
+            "/api/v3/notification-rules",
+            &token,
+            &json!({
+                "name": "Person on 1",
+                "trigger": "score_threshold",
+                "threshold": 60,
+                "predicate": {"where": {"field": "monitor_id", "op": "eq", "value": 1}},
+                "channel": "webhook",
+                "target": "https://hooks.example.com/zm",
+                "template": {"body": "{{cause}} on {{monitor_id}}"},
+                "dedupe_window_seconds": 300
+            }),
+        )
+        .await;
+    assert_eq!(resp.status(), StatusCode::CREATED, "{}", resp.text());
+    let created: Value = resp.json();
+    let id = created["id"].as_u64().expect("id");
+    assert_eq!(created["enabled"], true);
+    assert_eq!(created["threshold"], 60);
+    assert_eq!(created["predicate"]["where"]["field"], "monitor_id");
+
+    let resp = app
+        .get(&format!("/api/v3/notification-rules/{id}"), &token)
+        .await;
+    assert_eq!(resp.status(), StatusCode::OK, "{}", resp.text());
+
+    // `null` clears the predicate; omitted fields are kept.
+    let resp = app
+        .request(Method::PUT, &format!("/api/v3/notification-rules/{id}"))
+        .bearer(&token)
+        .json(&json!({"predicate": null, "enabled": false}))
+        .send()
+        .await;
+    assert_eq!(resp.status(), StatusCode::OK, "{}", resp.text());
+    let updated: Value = resp.json();
+    assert!(updated["predicate"].is_null());
+    assert_eq!(updated["enabled"], false);
+    assert_eq!(updated["threshold"], 60);
+    assert_eq!(updated["template"]["body"], "{{cause}} on {{monitor_id}}");
+
+    let resp = app
+        .get(
+            &format!("/api/v3/notification-rules/{id}/deliveries"),
+            &token,
+        )
+        .await;
+    assert_eq!(resp.status(), StatusCode::OK, "{}", resp.text());
+    assert_eq!(resp.json::<Value>()["total"], 0);
+
+    let resp = app
+        .delete(&format!("/api/v3/notification-rules/{id}"), &token)
+        .await;
+    assert_eq!(resp.status(), StatusCode::NO_CONTENT, "{}", resp.text());
+    let resp = app
+        .get(&format!("/api/v3/notification-rules/{id}"), &token)
+        .await;
+    assert_eq!(resp.status(), StatusCode::NOT_FOUND, "{}", resp.text());
+}
+
+#[tokio::test]
+#[ignore = "requires the test database (APP_PROFILE=test-db)"]
+async fn notification_rule_create_is_validated() {
+    let app = TestApp::spawn().await;
+    ensure_schema(&app.db).await;
+    let token = superuser_token();
+
+    // A webhook needs an http(s) URL.
+    let resp = app
+        .post_json(
+            "/api/v3/notification-rules",
+            &token,
+            &json!({
+                "name": "bad target",
+                "trigger": "event_start",
+                "channel": "webhook",
+                "target": "not a url"
+            }),
+        )
+        .await;
+    assert_eq!(resp.status(), StatusCode::BAD_REQUEST, "{}", resp.text());
+
+    // score_threshold needs a threshold.
+    let resp = app
+        .post_json(
+            "/api/v3/notification-rules",
+            &token,
+            &json!({
+                "name": "no threshold",
+                "trigger": "score_threshold",
+                "channel": "smtp",
+                "target": "alerts@example.com"
+            }),
+        )
+        .await;
+    assert_eq!(resp.status(), StatusCode::BAD_REQUEST, "{}", resp.text());
+}
+
+#[tokio::test]
+#[ignore = "requires the test database (APP_PROFILE=test-db)"]
+async fn webhook_secret_is_shown_only_when_issued() {
+    let app = TestApp::spawn().await;
+    ensure_schema(&app.db).await;
+    let token = superuser_token();
+
+    let resp = app
+        .post_json(
+            "/api/v3/notification-rules",
+            &token,
+            &json!({
+                "name": "Signed hook",
+                "trigger": "event_end",
+                "channel": "webhook",
+                "target": "https://hooks.example.com/zm"
+            }),
+        )
+        .await;
+    assert_eq!(resp.status(), StatusCode::CREATED, "{}", resp.text());
+    let created: Value = resp.json();
+    let id = created["id"].as_u64().expect("id");
+    let issued = created["secret"]
+        .as_str()
+        .expect("issued secret")
+        .to_string();
+    assert!(issued.starts_with("whsec_"));
+
+    let resp = app
+        .get(&format!("/api/v3/notification-rules/{id}"), &token)
+        .await;
+    let fetched: Value = resp.json();
+    assert_eq!(fetched["has_secret"], true);
+    assert!(fetched.get("secret").is_none(), "secret is not shown again");
+
+    let resp = app
+        .request(Method::PUT, &format!("/api/v3/notification-rules/{id}"))
+        .bearer(&token)
+        .json(&json!({"rotate_secret": true}))
+        .send()
+        .await;
+    assert_eq!(resp.status(), StatusCode::OK, "{}", resp.text());
+    let rotated = resp.json::<Value>()["secret"]
+        .as_str()
+        .expect("rotated secret")
+        .to_string();
+    assert_ne!(rotated, issued);
+
+    let resp = app
+        .delete(&format!("/api/v3/notification-rules/{id}"), &token)
+        .await;
+    assert_eq!(resp.status(), StatusCode::NO_CONTENT, "{}", resp.text());
+}
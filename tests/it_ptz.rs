@@ -1,8 +1,12 @@
 //! Integration tests for the PTZ API.
 //!
 //! PTZ movement endpoints actuate real camera hardware, so this suite covers
-//! only the hardware-independent surface: the static protocol list and the
-//! not-found paths for status/capabilities on a missing monitor.
+//! only the hardware-independent surface: the static protocol list, the
+//! not-found paths for status/capabilities on a missing monitor, validation
+//! and driver support for aiming at the picture, and control arbitration and
+//! preset tours. The controllable fixtures point at a closed
+//! local port, so a command fails at the camera but still claims the lease,
+//! and pauses any tour, before it is sent.
 //!
 //! Requires the test database — run with:
 //!   APP_PROFILE=test-db cargo test --test it_ptz -- --include-ignored
@@ -10,8 +14,93 @@
 mod common;
 
 use axum::http::StatusCode;
-use common::assertions::{assert_error, assert_status};
-use common::harness::{superuser_token, TestApp};
+use common::assertions::{assert_error, assert_ok_json, assert_status};
+use common::fixtures::{
+    cleanup_monitor_permissions, grant_monitor_permission, insert_monitor, insert_user_with_id,
+    unique_name, RowGuard,
+};
+use common::harness::{superuser_token, token_for, TestApp};
+use common::test_db::get_test_db;
+use sea_orm::{ActiveModelTrait, IntoActiveModel, Set};
+use serde_json::json;
+use zm_api::dto::response::ptz::{PtzControlLeaseResponse, PtzStatusResponse, PtzTourResponse};
+use zm_api::entity::sea_orm_active_enums::{MonitorType, Permission};
+use zm_api::server::state::AppState;
+use zm_api::util::authz::{Level, UserPermissions};
+
+/// Ghost user ids for the arbitration tests; none has ACL rows, so each sees
+/// every monitor.
+const OPERATOR_A: u32 = 999_999_101;
+const OPERATOR_B: u32 = 999_999_102;
+const ADMIN: u32 = 999_999_103;
+/// A real user, for the row-level ACL test.
+const TOUR_ACL_UID: u32 = 990_501;
+
+fn operator_token(user_id: u32) -> String {
+    token_for(
+        user_id,
+        UserPermissions {
+            control: Level::Edit,
+            ..UserPermissions::default()
+        },
+    )
+}
+
+fn admin_token() -> String {
+    token_for(ADMIN, UserPermissions::superuser())
+}
+
+/// Insert a controllable monitor whose HikVision control points at a closed
+/// local port. Keep the guards alive for the test.
+async fn insert_ptz_monitor(
+    db: &sea_orm::DatabaseConnection,
+    label: &str,
+) -> (u32, RowGuard, RowGuard) {
+    insert_ptz_monitor_with(db, label, "HikVision").await
+}
+
+/// As [`insert_ptz_monitor`], with the control speaking `protocol`.
+async fn insert_ptz_monitor_with(
+    db: &sea_orm::DatabaseConnection,
+    label: &str,
+    protocol: &str,
+) -> (u32, RowGuard, RowGuard) {
+    let control = zm_api::entity::controls::ActiveModel {
+        name: Set(unique_name(label)),
+        r#type: Set(MonitorType::Ffmpeg),
+        protocol: Set(Some(protocol.to_string())),
+        ..Default::default()
+    }
+    .insert(db)
+    .await
+    .expect("insert control fixture");
+    let control_guard = RowGuard::control(control.id);
+
+    let monitor = insert_monitor(db, label).await.expect("insert monitor");
+    let monitor_guard = RowGuard::monitor(monitor.id);
+    let mut active = monitor.into_active_model();
+    active.controllable = Set(1);
+    active.control_id = Set(Some(control.id));
+    active.control_address = Set(Some("127.0.0.1:1".to_string()));
+    let monitor = active.update(db).await.expect("make monitor controllable");
+    (monitor.id, monitor_guard, control_guard)
+}
+
+fn control_path(monitor_id: u32) -> String {
+    format!("/api/v3/ptz/monitors/{monitor_id}/control")
+}
+
+fn stop_path(monitor_id: u32) -> String {
+    format!("/api/v3/ptz/monitors/{monitor_id}/move/stop")
+}
+
+fn point_path(monitor_id: u32) -> String {
+    format!("/api/v3/ptz/monitors/{monitor_id}/point")
+}
+
+fn area_path(monitor_id: u32) -> String {
+    format!("/api/v3/ptz/monitors/{monitor_id}/area")
+}
 
 #[tokio::test]
 #[ignore = "requires the test database (APP_PROFILE=test-db)"]
@@ -52,3 +141,463 @@ async fn get_capabilities_for_missing_monitor_is_not_found() {
         .await;
     assert_error(&resp, StatusCode::NOT_FOUND, "MONITOR_NOT_FOUND_ERROR");
 }
+
+#[tokio::test]
+#[ignore = "requires the test database (APP_PROFILE=test-db)"]
+async fn a_command_claims_the_camera_and_other_users_get_409() {
+    let app = TestApp::spawn().await;
+    let (monitor_id, _mon, _ctl) = insert_ptz_monitor(&app.db, "PtzLeaseCmd").await;
+    let (a, b) = (operator_token(OPERATOR_A), operator_token(OPERATOR_B));
+
+    // The camera is unreachable, but the lease is taken before the command.
+    let resp = app.post_json(&stop_path(monitor_id), &a, &json!({})).await;
+    assert_ne!(resp.status(), StatusCode::CONFLICT, "{}", resp.text());
+
+    let resp = app.post_json(&stop_path(monitor_id), &b, &json!({})).await;
+    assert_error(&resp, StatusCode::CONFLICT, "CONFLICT_ERROR");
+    let resp = app
+        .post_json(&control_path(monitor_id), &b, &json!({}))
+        .await;
+    assert_error(&resp, StatusCode::CONFLICT, "CONFLICT_ERROR");
+
+    let resp = app.delete(&control_path(monitor_id), &a).await;
+    assert_status(&resp, StatusCode::OK);
+    let resp = app
+        .post_json(&control_path(monitor_id), &b, &json!({}))
+        .await;
+    let lease: PtzControlLeaseResponse = assert_ok_json(&resp);
+    assert_eq!(lease.user_id, OPERATOR_B);
+}
+
+#[tokio::test]
+#[ignore = "requires the test database (APP_PROFILE=test-db)"]
+async fn an_administrator_takes_over_from_an_operator() {
+    let app = TestApp::spawn().await;
+    let (monitor_id, _mon, _ctl) = insert_ptz_monitor(&app.db, "PtzLeaseTakeover").await;
+    let a = operator_token(OPERATOR_A);
+
+    let resp = app
+        .post_json(&control_path(monitor_id), &a, &json!({}))
+        .await;
+    let lease: PtzControlLeaseResponse = assert_ok_json(&resp);
+    assert_eq!((lease.user_id, lease.priority), (OPERATOR_A, 0));
+
+    let resp = app
+        .post_json(&control_path(monitor_id), &admin_token(), &json!({}))
+        .await;
+    let lease: PtzControlLeaseResponse = assert_ok_json(&resp);
+    assert_eq!((lease.user_id, lease.priority), (ADMIN, 1));
+
+    // The operator cannot take it back while the administrator holds it.
+    let resp = app
+        .post_json(&control_path(monitor_id), &a, &json!({}))
+        .await;
+    assert_error(&resp, StatusCode::CONFLICT, "CONFLICT_ERROR");
+}
+
+#[tokio::test]
+#[ignore = "requires the test database (APP_PROFILE=test-db)"]
+async fn only_the_holder_or_a_higher_priority_user_releases() {
+    let app = TestApp::spawn().await;
+    let (monitor_id, _mon, _ctl) = insert_ptz_monitor(&app.db, "PtzLeaseRelease").await;
+    let (a, b) = (operator_token(OPERATOR_A), operator_token(OPERATOR_B));
+
+    let resp = app
+        .post_json(&control_path(monitor_id), &a, &json!({}))
+        .await;
+    assert_status(&resp, StatusCode::OK);
+
+    let resp = app.delete(&control_path(monitor_id), &b).await;
+    assert_error(&resp, StatusCode::CONFLICT, "CONFLICT_ERROR");
+    // Still the operator's.
+    let resp = app
+        .post_json(&control_path(monitor_id), &b, &json!({}))
+        .await;
+    assert_error(&resp, StatusCode::CONFLICT, "CONFLICT_ERROR");
+
+    let resp = app.delete(&control_path(monitor_id), &admin_token()).await;
+    assert_status(&resp, StatusCode::OK);
+    let resp = app
+        .post_json(&control_path(monitor_id), &b, &json!({}))
+        .await;
+    assert_status(&resp, StatusCode::OK);
+}
+
+#[tokio::test]
+#[ignore = "requires the test database (APP_PROFILE=test-db)"]
+async fn commands_renew_the_holders_lease() {
+    let app = TestApp::spawn().await;
+    let (monitor_id, _mon, _ctl) = insert_ptz_monitor(&app.db, "PtzLeaseRenew").await;
+    let a = operator_token(OPERATOR_A);
+
+    let resp = app
+        .post_json(&control_path(monitor_id), &a, &json!({}))
+        .await;
+    let first: PtzControlLeaseResponse = assert_ok_json(&resp);
+
+    tokio::time::sleep(std::time::Duration::from_millis(2_200)).await;
+    let resp = app.post_json(&stop_path(monitor_id), &a, &json!({})).await;
+    assert_ne!(resp.status(), StatusCode::CONFLICT, "{}", resp.text());
+
+    let resp = app
+        .post_json(&control_path(monitor_id), &a, &json!({}))
+        .await;
+    let renewed: PtzControlLeaseResponse = assert_ok_json(&resp);
+    // The same lease, held since the first request, runs for its full term
+    // again from the command.
+    assert!(renewed.held_for_seconds >= 2, "{renewed:?}");
+    assert!(
+        renewed.expires_in_seconds + 1 >= first.expires_in_seconds,
+        "{first:?} then {renewed:?}"
+    );
+}
+
+/// Guard the tours of a monitor; they are keyed by tour id, not monitor.
+#[tokio::test]
+#[ignore = "requires the test database (APP_PROFILE=test-db)"]
+async fn aim_targets_outside_the_frame_are_rejected() {
+    let app = TestApp::spawn().await;
+    let (monitor_id, _mon, _ctl) = insert_ptz_monitor(&app.db, "PtzAimRange").await;
+    let a = operator_token(OPERATOR_A);
+
+    for body in [
+        json!({ "x": 1.5, "y": 0.5 }),
+        json!({ "x": 0.5, "y": -0.1 }),
+    ] {
+        let resp = app.post_json(&point_path(monitor_id), &a, &body).await;
+        assert_error(&resp, StatusCode::BAD_REQUEST, "INVALID_INPUT_ERROR");
+    }
+    for body in [
+        json!({ "x": -0.2, "y": 0.2, "width": 0.5, "height": 0.5 }),
+        json!({ "x": 0.2, "y": 0.2, "width": 0.0, "height": 0.5 }),
+        json!({ "x": 0.2, "y": 0.2, "width": 0.5, "height": 1.5 }),
+    ] {
+        let resp = app.post_json(&area_path(monitor_id), &a, &body).await;
+        assert_error(&resp, StatusCode::BAD_REQUEST, "INVALID_INPUT_ERROR");
+    }
+
+    // A rejected aim never reaches the camera, so it claims nothing.
+    let resp = app
+        .post_json(
+            &control_path(monitor_id),
+            &operator_token(OPERATOR_B),
+            &json!({}),
+        )
+        .await;
+    let lease: PtzControlLeaseResponse = assert_ok_json(&resp);
+    assert_eq!(lease.user_id, OPERATOR_B);
+}
+
+#[tokio::test]
+#[ignore = "requires the test database (APP_PROFILE=test-db)"]
+async fn aiming_needs_a_driver_that_knows_its_units() {
+    let app = TestApp::spawn().await;
+    // No native driver: the Perl bridge can neither aim nor convert angles.
+    let (monitor_id, _mon, _ctl) =
+        insert_ptz_monitor_with(&app.db, "PtzAimUnsupported", "PanasonicIP").await;
+    let token = superuser_token();
+
+    let resp = app
+        .post_json(
+            &point_path(monitor_id),
+            &token,
+            &json!({ "x": 0.25, "y": 0.75 }),
+        )
+        .await;
+    assert_error(&resp, StatusCode::BAD_REQUEST, "BAD_REQUEST_ERROR");
+    assert!(resp.text().contains("cannot aim"), "{}", resp.text());
+
+    let resp = app
+        .post_json(
+            &area_path(monitor_id),
+            &token,
+            &json!({ "x": 0.1, "y": 0.1, "width": 0.3, "height": 0.3 }),
+        )
+        .await;
+    assert_error(&resp, StatusCode::BAD_REQUEST, "BAD_REQUEST_ERROR");
+    assert!(resp.text().contains("cannot aim"), "{}", resp.text());
+}
+
+fn tours_guard(monitor_id: u32) -> RowGuard {
+    RowGuard::new(
+        format!("PtzTours(monitor={monitor_id})"),
+        move |db| async move {
+            use sea_orm::{ColumnTrait, EntityTrait, QueryFilter};
+            let _ = zm_api::entity::ptz_tours::Entity::delete_many()
+                .filter(zm_api::entity::ptz_tours::Column::MonitorId.eq(monitor_id))
+                .exec(&db)
+                .await;
+        },
+    )
+}
+
+fn tours_path(monitor_id: u32) -> String {
+    format!("/api/v3/ptz/monitors/{monitor_id}/tours")
+}
+
+fn tour_body(label: &str) -> serde_json::Value {
+    json!({
+        "name": unique_name(label),
+        "steps": [
+            { "preset_id": 1, "dwell_seconds": 5 },
+            { "preset_id": 2, "dwell_seconds": 5, "speed": 40 }
+        ],
+        "schedule": [{ "days": ["mon", "fri"], "start": "08:00", "end": "18:00" }]
+    })
+}
+
+async fn create_tour(app: &TestApp, monitor_id: u32, label: &str) -> PtzTourResponse {
+    let resp = app
+        .post_json(
+            &tours_path(monitor_id),
+            &superuser_token(),
+            &tour_body(label),
+        )
+        .await;
+    assert_status(&resp, StatusCode::CREATED);
+    resp.json()
+}
+
+/// A [`TestApp`] whose tour runner is spawned, as the server does.
+async fn spawn_with_tour_runner() -> TestApp {
+    let expect_msg = "connect to test database (is it running on :3307?)";
+    let state = AppState::for_test_with_db(get_test_db().await.expect(expect_msg));
+    std::sync::Arc::clone(&state.ptz_tours).spawn();
+    let mut app = TestApp::from_state(state);
+    app.db = get_test_db().await.expect(expect_msg);
+    app
+}
+
+#[tokio::test]
+#[ignore = "requires the test database (APP_PROFILE=test-db)"]
+async fn tour_crud_round_trips() {
+    let app = TestApp::spawn().await;
+    let token = superuser_token();
+    let monitor = insert_monitor(&app.db, "PtzTourCrud")
+        .await
+        .expect("insert monitor");
+    let _mon = RowGuard::monitor(monitor.id);
+    let _tours = tours_guard(monitor.id);
+
+    let tour = create_tour(&app, monitor.id, "Perimeter").await;
+    assert_eq!(tour.monitor_id, monitor.id);
+    assert_eq!(tour.steps.len(), 2);
+    assert_eq!(tour.steps[1].speed, Some(40));
+    assert_eq!(tour.schedule.as_ref().map(Vec::len), Some(1));
+    let path = format!("{}/{}", tours_path(monitor.id), tour.id);
+
+    let resp = app.get(&tours_path(monitor.id), &token).await;
+    let list: Vec<PtzTourResponse> = assert_ok_json(&resp);
+    assert!(list.iter().any(|t| t.id == tour.id));
+
+    // The same name twice on one monitor is refused.
+    let resp = app
+        .post_json(
+            &tours_path(monitor.id),
+            &token,
+            &json!({ "name": tour.name, "steps": [{ "preset_id": 1, "dwell_seconds": 5 }] }),
+        )
+        .await;
+    assert_error(&resp, StatusCode::CONFLICT, "CONFLICT_ERROR");
+
+    let resp = app
+        .patch_json(
+            &path,
+            &token,
+            &json!({ "enabled": false, "schedule": null }),
+        )
+        .await;
+    let updated: PtzTourResponse = assert_ok_json(&resp);
+    assert!(!updated.enabled);
+    assert!(updated.schedule.is_none());
+
+    let resp = app.delete(&path, &token).await;
+    assert_status(&resp, StatusCode::NO_CONTENT);
+    let resp = app.get(&path, &token).await;
+    assert_status(&resp, StatusCode::NOT_FOUND);
+}
+
+#[tokio::test]
+#[ignore = "requires the test database (APP_PROFILE=test-db)"]
+async fn tour_schedules_are_validated() {
+    let app = TestApp::spawn().await;
+    let token = superuser_token();
+    let monitor = insert_monitor(&app.db, "PtzTourSchedule")
+        .await
+        .expect("insert monitor");
+    let _mon = RowGuard::monitor(monitor.id);
+    let _tours = tours_guard(monitor.id);
+
+    for window in [
+        json!({ "days": ["mon"], "start": "25:00", "end": "18:00" }),
+        json!({ "days": ["mon"], "start": "8:00", "end": "18:00" }),
+        json!({ "days": ["mon"], "start": "08:00", "end": "18:60" }),
+        json!({ "days": [], "start": "08:00", "end": "18:00" }),
+    ] {
+        let resp = app
+            .post_json(
+                &tours_path(monitor.id),
+                &token,
+                &json!({
+                    "name": unique_name("BadSchedule"),
+                    "steps": [{ "preset_id": 1, "dwell_seconds": 5 }],
+                    "schedule": [window],
+                }),
+            )
+            .await;
+        assert_status(&resp, StatusCode::BAD_REQUEST);
+    }
+
+    // An unknown day never deserializes.
+    let resp = app
+        .post_json(
+            &tours_path(monitor.id),
+            &token,
+            &json!({
+                "name": unique_name("BadDay"),
+                "steps": [{ "preset_id": 1, "dwell_seconds": 5 }],
+                "schedule": [{ "days": ["someday"], "start": "08:00", "end": "18:00" }],
+            }),
+        )
+        .await;
+    assert!(resp.status().is_client_error(), "{}", resp.text());
+
+    let resp = app.get(&tours_path(monitor.id), &token).await;
+    let list: Vec<PtzTourResponse> = assert_ok_json(&resp);
+    assert!(list.is_empty(), "no invalid tour may be stored");
+}
+
+#[tokio::test]
+#[ignore = "requires the test database (APP_PROFILE=test-db)"]
+async fn unknown_tours_are_not_found() {
+    let app = TestApp::spawn().await;
+    let token = superuser_token();
+    let monitor = insert_monitor(&app.db, "PtzTourMissing")
+        .await
+        .expect("insert monitor");
+    let _mon = RowGuard::monitor(monitor.id);
+    let other = insert_monitor(&app.db, "PtzTourOther")
+        .await
+        .expect("insert monitor");
+    let _other = RowGuard::monitor(other.id);
+    let _tours = tours_guard(other.id);
+
+    let base = tours_path(monitor.id);
+    for resp in [
+        app.get(&format!("{base}/999000111"), &token).await,
+        app.post_json(&format!("{base}/999000111/start"), &token, &json!({}))
+            .await,
+        app.post_json(&format!("{base}/999000111/stop"), &token, &json!({}))
+            .await,
+    ] {
+        assert_status(&resp, StatusCode::NOT_FOUND);
+    }
+
+    // Another monitor's tour is not found through this one.
+    let tour = create_tour(&app, other.id, "Elsewhere").await;
+    let resp = app.get(&format!("{base}/{}", tour.id), &token).await;
+    assert_status(&resp, StatusCode::NOT_FOUND);
+}
+
+#[tokio::test]
+#[ignore = "requires the test database (APP_PROFILE=test-db)"]
+async fn tours_follow_the_monitor_acl() {
+    let app = TestApp::spawn().await;
+    let visible = insert_monitor(&app.db, "PtzTourAclVisible")
+        .await
+        .expect("insert monitor");
+    let _visible = RowGuard::monitor(visible.id);
+    let hidden = insert_monitor(&app.db, "PtzTourAclHidden")
+        .await
+        .expect("insert monitor");
+    let _hidden = RowGuard::monitor(hidden.id);
+    let _visible_tours = tours_guard(visible.id);
+    let _hidden_tours = tours_guard(hidden.id);
+    let tour = create_tour(&app, hidden.id, "Hidden").await;
+
+    insert_user_with_id(&app.db, TOUR_ACL_UID, "PtzTourAclUser")
+        .await
+        .expect("insert acl user");
+    let _user = RowGuard::user(TOUR_ACL_UID);
+    grant_monitor_permission(&app.db, visible.id, TOUR_ACL_UID, Permission::View)
+        .await
+        .expect("grant permission");
+    let _perms = RowGuard::new(
+        format!("Monitors_Permissions(user={TOUR_ACL_UID})"),
+        |db| async move {
+            let _ = cleanup_monitor_permissions(&db, TOUR_ACL_UID).await;
+        },
+    );
+    let token = token_for(TOUR_ACL_UID, UserPermissions::superuser());
+
+    // Someone else's monitor looks missing, whatever the method.
+    let resp = app.get(&tours_path(hidden.id), &token).await;
+    assert_error(&resp, StatusCode::NOT_FOUND, "MONITOR_NOT_FOUND_ERROR");
+    let resp = app
+        .post_json(
+            &format!("{}/{}/start", tours_path(hidden.id), tour.id),
+            &token,
+            &json!({}),
+        )
+        .await;
+    assert_error(&resp, StatusCode::NOT_FOUND, "MONITOR_NOT_FOUND_ERROR");
+
+    // View on a monitor lists its tours but does not allow writing them.
+    let resp = app.get(&tours_path(visible.id), &token).await;
+    assert_status(&resp, StatusCode::OK);
+    let resp = app
+        .post_json(&tours_path(visible.id), &token, &tour_body("ViewOnly"))
+        .await;
+    assert_error(&resp, StatusCode::NOT_FOUND, "MONITOR_NOT_FOUND_ERROR");
+}
+
+#[tokio::test]
+#[ignore = "requires the test database (APP_PROFILE=test-db)"]
+async fn a_running_tour_pauses_for_manual_control() {
+    let app = spawn_with_tour_runner().await;
+    let token = superuser_token();
+    let (monitor_id, _mon, _ctl) = insert_ptz_monitor(&app.db, "PtzTourPause").await;
+    let _tours = tours_guard(monitor_id);
+    let tour = create_tour(&app, monitor_id, "Pausing").await;
+    let tour_path = format!("{}/{}", tours_path(monitor_id), tour.id);
+    let status_path = format!("/api/v3/ptz/monitors/{monitor_id}/status");
+
+    let resp = app
+        .post_json(&format!("{tour_path}/start"), &token, &json!({}))
+        .await;
+    assert_status(&resp, StatusCode::OK);
+    let status: PtzStatusResponse = assert_ok_json(&app.get(&status_path, &token).await);
+    let running = status.tour.expect("tour running");
+    assert_eq!(running.tour_id, tour.id);
+    assert!(!running.paused);
+
+    let resp = app
+        .post_json(
+            &stop_path(monitor_id),
+            &operator_token(OPERATOR_A),
+            &json!({}),
+        )
+        .await;
+    assert_ne!(resp.status(), StatusCode::CONFLICT, "{}", resp.text());
+
+    // The runner notices on its next tick.
+    let mut paused = false;
+    for _ in 0..30 {
+        tokio::time::sleep(std::time::Duration::from_millis(200)).await;
+        let status: PtzStatusResponse = assert_ok_json(&app.get(&status_path, &token).await);
+        if status.tour.is_some_and(|t| t.paused) {
+            paused = true;
+            break;
+        }
+    }
+    assert!(paused, "tour should pause while a user drives the camera");
+
+    let resp = app
+        .post_json(&format!("{tour_path}/stop"), &token, &json!({}))
+        .await;
+    assert_status(&resp, StatusCode::OK);
+    let resp = app
+        .post_json(&format!("{tour_path}/stop"), &token, &json!({}))
+        .await;
+    assert_error(&resp, StatusCode::CONFLICT, "CONFLICT_ERROR");
+}
@@ -3,8 +3,10 @@
 //! Each test builds an isolated `Storage` rooted at a tempdir and reaps only
 //! that storage via `RetentionService::reap_storage_once`, so a shared test
 //! database is safe (the global `reap_once` would touch every storage).
-//! Deletion is driven by the byte quota (`max_bytes`) with the free-space and
-//! age checks disabled, so it is deterministic regardless of the host disk.
+//! Deletion is driven by the byte quota (`max_bytes`) or by day-scale ages,
+//! with the free-space check disabled, so it is deterministic regardless of
+//! the host disk. Per-storage / per-monitor overrides are written through
+//! `repo::retention` and removed by a guard.
 //!
 //! Requires the test database — run with:
 //!   APP_PROFILE=test-db cargo test --test it_retention -- --include-ignored
@@ -19,6 +21,7 @@ use sea_orm::{ActiveModelTrait, DatabaseConnection, EntityTrait, Set};
 use zm_api::configure::retention::RetentionConfig;
 use zm_api::entity::sea_orm_active_enums::Scheme;
 use zm_api::entity::storage::Model as StorageModel;
+use zm_api::repo::retention::Overrides;
 use zm_api::service::retention::RetentionService;
 
 const MIB: u64 = 1024 * 1024;
@@ -248,7 +251,7 @@ async fn reap_never_touches_archived_or_in_progress_events() {
         guard_event(newest),
     );
 
-    // Candidate bytes (archived + in-progress are excluded from the query, so
+    // Candidate bytes (archived + in-progress are not candidates, so
     // used = normal_old + newest = 200 MiB) exceed the 150 MiB quota: normal_old
     // is deleted, newest is protected.
     let deleted = service(quota_config(150 * MIB, false))
@@ -339,3 +342,178 @@ async fn reap_dry_run_reports_but_deletes_nothing() {
     assert!(event_exists(&db, e2).await, "dry-run must not delete e2");
     assert!(event_exists(&db, e3).await, "dry-run must not delete e3");
 }
+
+fn guard_monitor_retention(monitor_id: u32) -> RowGuard {
+    RowGuard::new(
+        format!("monitor_retention#{monitor_id}"),
+        move |db| async move {
+            let _ = zm_api::repo::retention::delete_by_monitor(&db, monitor_id).await;
+        },
+    )
+}
+
+fn guard_storage_retention(storage_id: u16) -> RowGuard {
+    RowGuard::new(
+        format!("storage_retention#{storage_id}"),
+        move |db| async move {
+            let _ = zm_api::repo::retention::delete_by_storage(&db, storage_id).await;
+        },
+    )
+}
+
+#[tokio::test]
+#[ignore = "requires the test database (APP_PROFILE=test-db)"]
+async fn monitor_override_expires_by_age_and_can_unprotect_archived() {
+    let db = get_test_db().await.expect("db");
+    let tmp = tempfile::tempdir().unwrap();
+
+    let monitor = insert_monitor(&db, "ReapPolicyMon").await.expect("monitor");
+    let _mg = RowGuard::monitor(monitor.id);
+    let (storage, _sg) = dedicated_storage(&db, &tmp, "ReapPolicyStore").await;
+
+    let base = chrono::Utc::now().naive_utc();
+    let archived = insert_event(
+        &db,
+        monitor.id,
+        storage.id,
+        base - chrono::Duration::days(3),
+        true,
+        1,
+        MIB,
+        "parch",
+    )
+    .await;
+    let old = insert_event(
+        &db,
+        monitor.id,
+        storage.id,
+        base - chrono::Duration::days(2),
+        true,
+        0,
+        MIB,
+        "pold",
+    )
+    .await;
+    let newest = insert_event(
+        &db,
+        monitor.id,
+        storage.id,
+        base - chrono::Duration::hours(1),
+        true,
+        0,
+        MIB,
+        "pnew",
+    )
+    .await;
+    let (_ga, _go, _gn) = (guard_event(archived), guard_event(old), guard_event(newest));
+
+    // No global limits at all: only the monitor's one-day age limit applies,
+    // and it opts this monitor's archived events out of protection.
+    zm_api::repo::retention::upsert_monitor(
+        &db,
+        monitor.id,
+        Overrides {
+            max_age_days: Some(1),
+            max_storage_bytes: None,
+            protect_archived: Some(false),
+        },
+        base,
+    )
+    .await
+    .expect("monitor override");
+    let _pg = guard_monitor_retention(monitor.id);
+
+    let deleted = service(quota_config(0, false))
+        .await
+        .reap_storage_once(&storage, false)
+        .await
+        .expect("reap");
+    assert_eq!(deleted, 2, "both day-old events expire");
+    assert!(
+        !event_exists(&db, archived).await,
+        "unprotected archived event expired"
+    );
+    assert!(!event_exists(&db, old).await, "old event expired");
+    assert!(event_exists(&db, newest).await, "newest event is protected");
+}
+
+#[tokio::test]
+#[ignore = "requires the test database (APP_PROFILE=test-db)"]
+async fn tiered_storage_moves_expiring_events_instead_of_deleting() {
+    let db = get_test_db().await.expect("db");
+    let hot_dir = tempfile::tempdir().unwrap();
+    let cold_dir = tempfile::tempdir().unwrap();
+
+    let monitor = insert_monitor(&db, "ReapTierMon").await.expect("monitor");
+    let _mg = RowGuard::monitor(monitor.id);
+    let (hot, _hg) = dedicated_storage(&db, &hot_dir, "ReapTierHot").await;
+    let (cold, _cg) = dedicated_storage(&db, &cold_dir, "ReapTierCold").await;
+
+    let base = chrono::Utc::now().naive_utc();
+    let mut ids = Vec::new();
+    for (hours, label) in [(3, "t1"), (2, "t2"), (1, "t3")] {
+        ids.push(
+            insert_event(
+                &db,
+                monitor.id,
+                hot.id,
+                base - chrono::Duration::hours(hours),
+                true,
+                0,
+                100 * MIB,
+                label,
+            )
+            .await,
+        );
+    }
+    let _guards: Vec<_> = ids.iter().map(|&id| guard_event(id)).collect();
+    let dirs: Vec<_> = ids
+        .iter()
+        .map(|&id| make_event_dir(hot_dir.path(), monitor.id, id))
+        .collect();
+
+    // The hot storage's own 150 MiB quota (the global one is off) pushes the
+    // two oldest events down to the cold tier.
+    zm_api::repo::retention::upsert_storage(
+        &db,
+        hot.id,
+        Overrides {
+            max_storage_bytes: Some(150 * MIB),
+            ..Default::default()
+        },
+        Some(cold.id),
+        base,
+    )
+    .await
+    .expect("storage override");
+    let _pg = guard_storage_retention(hot.id);
+
+    let deleted = service(quota_config(0, false))
+        .await
+        .reap_storage_once(&hot, false)
+        .await
+        .expect("reap");
+    assert_eq!(deleted, 0, "a tiered storage moves rather than deletes");
+
+    for (i, (&id, dir)) in ids.iter().zip(&dirs).enumerate() {
+        let row = zm_api::entity::events::Entity::find_by_id(id)
+            .one(&db)
+            .await
+            .unwrap()
+            .expect("row kept");
+        let moved_dir = cold_dir
+            .path()
+            .join(monitor.id.to_string())
+            .join(id.to_string());
+        if i < 2 {
+            assert_eq!(row.storage_id, Some(cold.id), "event {id} repointed");
+            assert!(
+                !dir.exists() && moved_dir.exists(),
+                "event {id} media moved"
+            );
+        } else {
+            assert_eq!(row.storage_id, Some(hot.id), "newest stays on hot");
+            assert!(dir.exists() && !moved_dir.exists());
+        }
+    }
+}
@@ -0,0 +1,158 @@
+//! Integration tests for the RTSP server's cached Digest credentials.
+//!
+//! Covers, against the real test database, the digest lookup the RTSP server
+//! authenticates with (`service::rtsp_credentials::digest_for`):
+//!   - a digest captured against the current `Users.Password` is used;
+//!   - changing `Users.Password` behind zm-api's back (as ZoneMinder's own UI
+//!     does) refuses RTSP auth and deletes the digest;
+//!   - so does disabling the account;
+//!   - a lookup of a user deleted outside zm-api writes nothing; the periodic
+//!     sweep (`repo::rtsp_credentials::delete_orphans`) drops the digest.
+//!
+//! `rtsp_credentials` is zm-api-owned, so each test first applies the crate
+//! migrations (idempotent).
+//!
+//! Requires the test database — run with:
+//!   APP_PROFILE=test-db cargo test --test it_rtsp_credentials -- --include-ignored
+
+mod common;
+
+use sea_orm::{ActiveModelTrait, DatabaseConnection, EntityTrait, Set};
+
+use common::fixtures::{unique_name, RowGuard};
+use common::harness::TestApp;
+
+use zm_api::client::database::migrate_database;
+use zm_api::entity::users;
+use zm_api::repo;
+use zm_api::service::rtsp_credentials::{digest_for, password_fingerprint};
+
+const REALM: &str = "zm-api";
+const HA1: &str = "0123456789abcdef0123456789abcdef";
+const HASH: &str = "$2y$10$aaaaaaaaaaaaaaaaaaaaaOaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaa";
+
+/// Apply the crate migrations once per test process (the migrator is not safe
+/// to run concurrently against one database).
+async fn ensure_schema(db: &DatabaseConnection) {
+    static SCHEMA: tokio::sync::OnceCell<()> = tokio::sync::OnceCell::const_new();
+    SCHEMA
+        .get_or_init(|| async {
+            migrate_database(db).await.expect("apply zm-api migrations");
+        })
+        .await;
+}
+
+fn guard_credential(user_id: u32) -> RowGuard {
+    RowGuard::new(
+        format!("rtsp_credentials#{user_id}"),
+        move |db| async move {
+            let _ = repo::rtsp_credentials::delete_by_user(&db, user_id).await;
+        },
+    )
+}
+
+/// An enabled API user whose digest was captured against [`HASH`].
+async fn user_with_digest(db: &DatabaseConnection, label: &str) -> users::Model {
+    let name = unique_name(label);
+    let user = users::ActiveModel {
+        username: Set(name.clone()),
+        password: Set(HASH.to_string()),
+        name: Set(name),
+        enabled: Set(1),
+        api_enabled: Set(1),
+        ..Default::default()
+    }
+    .insert(db)
+    .await
+    .expect("insert user");
+    let now = chrono::Utc::now().naive_utc();
+    repo::rtsp_credentials::upsert(db, user.id, REALM, HA1, &password_fingerprint(HASH), now)
+        .await
+        .expect("store digest");
+    user
+}
+
+#[tokio::test]
+#[ignore = "requires the test database (APP_PROFILE=test-db)"]
+async fn password_changed_outside_zm_api_refuses_rtsp_auth() {
+    let app = TestApp::spawn().await;
+    ensure_schema(&app.db).await;
+    let user = user_with_digest(&app.db, "rtsp-pw").await;
+    let _user_guard = RowGuard::user(user.id);
+    let _cred_guard = guard_credential(user.id);
+
+    let found = digest_for(&app.db, &user.username, REALM)
+        .await
+        .expect("lookup");
+    assert_eq!(found.map(|(_, ha1)| ha1).as_deref(), Some(HA1));
+
+    // What ZoneMinder's own UI does: write a new hash straight to the table.
+    let mut active: users::ActiveModel = user.clone().into();
+    active.password = Set("$2y$10$bbbbbbbbbbbbbbbbbbbbbObbbbbbbbbbbbbbbbbbbbbbbbbbbbbbb".into());
+    active.update(&app.db).await.expect("change password");
+
+    let found = digest_for(&app.db, &user.username, REALM)
+        .await
+        .expect("lookup");
+    assert!(found.is_none(), "the old password's digest must be refused");
+    let cred = repo::rtsp_credentials::find_by_user(&app.db, user.id)
+        .await
+        .expect("query");
+    assert!(cred.is_none(), "the stale digest must be deleted");
+}
+
+#[tokio::test]
+#[ignore = "requires the test database (APP_PROFILE=test-db)"]
+async fn disabled_user_is_refused_and_digest_dropped() {
+    let app = TestApp::spawn().await;
+    ensure_schema(&app.db).await;
+    let user = user_with_digest(&app.db, "rtsp-off").await;
+    let _user_guard = RowGuard::user(user.id);
+    let _cred_guard = guard_credential(user.id);
+
+    let mut active: users::ActiveModel = user.clone().into();
+    active.enabled = Set(0);
+    active.update(&app.db).await.expect("disable user");
+
+    let found = digest_for(&app.db, &user.username, REALM)
+        .await
+        .expect("lookup");
+    assert!(found.is_none());
+    let cred = repo::rtsp_credentials::find_by_user(&app.db, user.id)
+        .await
+        .expect("query");
+    assert!(cred.is_none());
+}
+
+#[tokio::test]
+#[ignore = "requires the test database (APP_PROFILE=test-db)"]
+async fn digests_of_deleted_users_are_swept() {
+    let app = TestApp::spawn().await;
+    ensure_schema(&app.db).await;
+    let user = user_with_digest(&app.db, "rtsp-gone").await;
+    let _cred_guard = guard_credential(user.id);
+
+    users::Entity::delete_by_id(user.id)
+        .exec(&app.db)
+        .await
+        .expect("delete user");
+
+    let found = digest_for(&app.db, &user.username, REALM)
+        .await
+        .expect("lookup");
+    assert!(found.is_none());
+    // Unknown names are only looked up; the auth path never sweeps.
+    let cred = repo::rtsp_credentials::find_by_user(&app.db, user.id)
+        .await
+        .expect("query");
+    assert!(cred.is_some(), "an auth attempt must not delete anything");
+
+    let swept = repo::rtsp_credentials::delete_orphans(&app.db)
+        .await
+        .expect("sweep");
+    assert!(swept >= 1);
+    let cred = repo::rtsp_credentials::find_by_user(&app.db, user.id)
+        .await
+        .expect("query");
+    assert!(cred.is_none(), "a deleted user's digest must not linger");
+}
@@ -7,10 +7,11 @@ mod common;
 
 use axum::http::StatusCode;
 use common::assertions::{assert_error, assert_status};
-use common::fixtures::{unique_name, RowGuard};
+use common::fixtures::{insert_monitor, unique_name, RowGuard};
 use common::harness::{superuser_token, TestApp};
-use sea_orm::{ActiveModelTrait, Set};
+use sea_orm::{ActiveModelTrait, EntityTrait, Set};
 use serde_json::json;
+use zm_api::dto::response::storage::{StorageHealthResponse, StorageReapResponse};
 use zm_api::dto::response::{PaginatedStorageResponse, StorageResponse};
 use zm_api::entity::sea_orm_active_enums::{Scheme, StorageType};
 
@@ -128,3 +129,137 @@ async fn create_storage_with_invalid_body_is_rejected() {
         resp.text()
     );
 }
+
+/// Insert an ended event of `bytes` on `storage_id`, started `hours_ago`.
+async fn insert_event(
+    db: &sea_orm::DatabaseConnection,
+    monitor_id: u32,
+    storage_id: u16,
+    hours_ago: i64,
+    archived: u8,
+    bytes: u64,
+) -> u64 {
+    let start = chrono::Utc::now().naive_utc() - chrono::Duration::hours(hours_ago);
+    zm_api::entity::events::ActiveModel {
+        monitor_id: Set(monitor_id),
+        storage_id: Set(Some(storage_id)),
+        state_id: Set(1),
+        name: Set(unique_name("StorageHealthEvent")),
+        scheme: Set(Scheme::Shallow),
+        start_date_time: Set(Some(start)),
+        end_date_time: Set(Some(start)),
+        archived: Set(archived),
+        disk_space: Set(Some(bytes)),
+        ..Default::default()
+    }
+    .insert(db)
+    .await
+    .expect("insert event fixture")
+    .id
+}
+
+fn guard_event(id: u64) -> RowGuard {
+    RowGuard::new(format!("Events#{id}"), move |db| async move {
+        let _ = zm_api::entity::events::Entity::delete_by_id(id)
+            .exec(&db)
+            .await;
+    })
+}
+
+#[tokio::test]
+#[ignore = "requires the test database (APP_PROFILE=test-db)"]
+async fn storage_health_breaks_usage_down_by_monitor() {
+    let app = TestApp::spawn().await;
+    let token = superuser_token();
+    let id = insert_storage(&app.db, "StorageHealth").await;
+    let _guard = RowGuard::storage(id);
+    let m1 = insert_monitor(&app.db, "HealthMonA")
+        .await
+        .expect("monitor");
+    let _g1 = RowGuard::monitor(m1.id);
+    let m2 = insert_monitor(&app.db, "HealthMonB")
+        .await
+        .expect("monitor");
+    let _g2 = RowGuard::monitor(m2.id);
+
+    let events = [
+        insert_event(&app.db, m1.id, id, 3, 1, 300).await,
+        insert_event(&app.db, m1.id, id, 2, 0, 200).await,
+        insert_event(&app.db, m2.id, id, 1, 0, 50).await,
+    ];
+    let _events: Vec<_> = events.iter().map(|&e| guard_event(e)).collect();
+
+    let resp = app
+        .get(&format!("/api/v3/storage/{id}/health"), &token)
+        .await;
+    assert_status(&resp, StatusCode::OK);
+    let body: StorageHealthResponse = resp.json();
+    assert_eq!(body.used_bytes, 550);
+    assert_eq!(body.archived_bytes, 300);
+    assert_eq!(body.events, 3);
+    assert!(body.oldest_event_ts.is_some());
+    let by_monitor: Vec<_> = body
+        .monitors
+        .iter()
+        .map(|m| (m.monitor_id, m.events, m.bytes, m.archived_bytes))
+        .collect();
+    let mut expected = vec![(m1.id, 2, 500, 300), (m2.id, 1, 50, 0)];
+    expected.sort();
+    assert_eq!(by_monitor, expected);
+
+    let resp = app.get("/api/v3/storage/65000/health", &token).await;
+    assert_error(&resp, StatusCode::NOT_FOUND, "MESSAGE_NOT_FOUND_ERROR");
+}
+
+#[tokio::test]
+#[ignore = "requires the test database (APP_PROFILE=test-db)"]
+async fn reap_storage_defaults_to_a_dry_run() {
+    let app = TestApp::spawn().await;
+    let token = superuser_token();
+    let id = insert_storage(&app.db, "StorageReap").await;
+    let _guard = RowGuard::storage(id);
+    let monitor = insert_monitor(&app.db, "ReapNowMon")
+        .await
+        .expect("monitor");
+    let _mg = RowGuard::monitor(monitor.id);
+    let old = insert_event(&app.db, monitor.id, id, 3, 0, 100).await;
+    let newest = insert_event(&app.db, monitor.id, id, 1, 0, 100).await;
+    let (_go, _gn) = (guard_event(old), guard_event(newest));
+
+    // A quota below what the storage holds puts the older event over it.
+    zm_api::repo::retention::upsert_storage(
+        &app.db,
+        id,
+        zm_api::repo::retention::Overrides {
+            max_storage_bytes: Some(150),
+            ..Default::default()
+        },
+        None,
+        chrono::Utc::now().naive_utc(),
+    )
+    .await
+    .expect("storage override");
+    let _pg = RowGuard::new(format!("storage_retention#{id}"), move |db| async move {
+        let _ = zm_api::repo::retention::delete_by_storage(&db, id).await;
+    });
+
+    let resp = app
+        .post_json(&format!("/api/v3/storage/{id}:reap"), &token, &json!({}))
+        .await;
+    assert_status(&resp, StatusCode::OK);
+    let body: StorageReapResponse = resp.json();
+    assert!(body.dry_run);
+    assert_eq!(body.deleted, 1);
+    assert_eq!(body.events.len(), 1);
+    assert_eq!(body.events[0].event_id, old);
+    assert_eq!(body.events[0].action, "delete");
+    assert!(body.events[0]
+        .reasons
+        .contains(&"max_storage_bytes".to_string()));
+
+    let still_there = zm_api::entity::events::Entity::find_by_id(old)
+        .one(&app.db)
+        .await
+        .unwrap();
+    assert!(still_there.is_some(), "a dry run deletes nothing");
+}
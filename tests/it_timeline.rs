@@ -0,0 +1,118 @@
+//! Integration tests for the per-monitor event timeline.
+//!
+//! Covers, against the real test database:
+//!   - `GET /api/v3/monitors/timeline` parameter validation;
+//!   - an event spread over the buckets it overlaps, read from `Events` for a
+//!     range older than the rolling windows.
+//!
+//! Requires the test database — run with:
+//!   APP_PROFILE=test-db cargo test --test it_timeline -- --include-ignored
+
+mod common;
+
+use axum::http::StatusCode;
+use chrono::{DateTime, Utc};
+use rust_decimal::Decimal;
+use sea_orm::{ActiveModelTrait, EntityTrait, Set};
+use serde_json::Value;
+
+use common::fixtures::{insert_monitor, unique_name, RowGuard};
+use common::harness::{superuser_token, TestApp};
+
+use zm_api::util::utc_to_naive_local;
+
+fn guard_event(id: u64) -> RowGuard {
+    RowGuard::new(format!("Events#{id}"), move |db| async move {
+        let _ = zm_api::entity::events::Entity::delete_by_id(id)
+            .exec(&db)
+            .await;
+    })
+}
+
+fn utc(rfc3339: &str) -> DateTime<Utc> {
+    DateTime::parse_from_rfc3339(rfc3339).unwrap().into()
+}
+
+#[tokio::test]
+#[ignore = "requires the test database (APP_PROFILE=test-db)"]
+async fn timeline_rejects_bad_ranges_and_buckets() {
+    let app = TestApp::spawn().await;
+    let token = superuser_token();
+    for query in [
+        "from=2020-01-02T00:00:00Z&to=2020-01-01T00:00:00Z",
+        "from=2020-01-01T00:00:00Z&to=2020-01-02T00:00:00Z&bucket=fortnight",
+        "from=2020-01-01T00:00:00Z&to=2020-01-02T00:00:00Z&bucket=30s",
+    ] {
+        let resp = app
+            .get(&format!("/api/v3/monitors/timeline?{query}"), &token)
+            .await;
+        assert_eq!(
+            resp.status(),
+            StatusCode::BAD_REQUEST,
+            "{query}: {}",
+            resp.text()
+        );
+    }
+}
+
+#[tokio::test]
+#[ignore = "requires the test database (APP_PROFILE=test-db)"]
+async fn event_is_spread_over_overlapping_buckets() {
+    let app = TestApp::spawn().await;
+    let monitor = insert_monitor(&app.db, "Timeline")
+        .await
+        .expect("insert monitor");
+    let _mon = RowGuard::monitor(monitor.id);
+    // 10:40 to 11:10: 20 minutes in the 10:00 bucket, 10 in the 11:00 one.
+    let event_id = zm_api::entity::events::ActiveModel {
+        monitor_id: Set(monitor.id),
+        state_id: Set(1),
+        name: Set(unique_name("TimelineEvt")),
+        archived: Set(0),
+        start_date_time: Set(Some(utc_to_naive_local(utc("2020-01-01T10:40:00Z")))),
+        end_date_time: Set(Some(utc_to_naive_local(utc("2020-01-01T11:10:00Z")))),
+        length: Set(Decimal::new(1800, 0)),
+        frames: Set(Some(1800)),
+        alarm_frames: Set(Some(180)),
+        max_score: Set(Some(42)),
+        ..Default::default()
+    }
+    .insert(&app.db)
+    .await
+    .expect("insert event")
+    .id;
+    let _event = guard_event(event_id);
+
+    let resp = app
+        .get(
+            "/api/v3/monitors/timeline?from=2020-01-01T08:00:00Z&to=2020-01-01T14:00:00Z",
+            &superuser_token(),
+        )
+        .await;
+    assert_eq!(resp.status(), StatusCode::OK, "{}", resp.text());
+    let body: Value = resp.json();
+    assert_eq!(body["source"], "Events");
+    assert_eq!(body["bucket_seconds"], 3600);
+    assert_eq!(body["bucket_count"], 6);
+
+    let timeline = body["monitors"]
+        .as_array()
+        .unwrap()
+        .iter()
+        .find(|m| m["monitor_id"] == monitor.id)
+        .expect("monitor listed");
+    let buckets = timeline["buckets"].as_array().unwrap();
+    assert_eq!(buckets.len(), 2);
+    assert_eq!(buckets[0]["index"], 2);
+    assert_eq!(buckets[0]["start"], "2020-01-01T10:00:00Z");
+    assert_eq!(buckets[0]["recorded_seconds"], 1200.0);
+    assert_eq!(buckets[0]["alarm_seconds"], 120.0);
+    assert_eq!(buckets[1]["index"], 3);
+    assert_eq!(buckets[1]["recorded_seconds"], 600.0);
+    assert_eq!(buckets[1]["alarm_seconds"], 60.0);
+    for bucket in buckets {
+        assert_eq!(bucket["event_count"], 1);
+        assert_eq!(bucket["max_score"], 42);
+        assert_eq!(bucket["event_ids"], serde_json::json!([event_id]));
+    }
+}